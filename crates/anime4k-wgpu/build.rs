@@ -5,7 +5,8 @@
 //! that are embedded directly into the compiled binary for maximum performance.
 
 use anime4k_wgpu_build::{
-    cnn_glsl_to_executable_pipeline,
+    cnn::EdgeMode,
+    cnn_glsl_to_executable_pipeline, cnn_glsl_to_executable_pipeline_fused,
     pipelines::ExecutablePipeline,
     predefined::{PREDEFINED_PIPELINES_AUX, PREDEFINED_PIPELINES_CNN},
     wgsl_to_executable_pipeline,
@@ -27,10 +28,15 @@ fn dump_shader_string_literal(shader: &str) -> String {
 fn dump_executable_pipeline(name: &str, pipeline: &ExecutablePipeline) -> String {
     let mut output = String::new();
     output.push_str("ExecutablePipeline {\n");
+    output.push_str(&format!("    id: {},\n", dump_shader_string_literal(&pipeline.id)));
     output.push_str(&format!("    name: \"Anime4K {name}\",\n"));
+    match &pipeline.description {
+        Some(description) => output.push_str(&format!("    description: Some({}),\n", dump_shader_string_literal(description))),
+        None => output.push_str("    description: None,\n"),
+    }
 
     // Generate physical texture definitions
-    output.push_str("    textures: &[\n");
+    output.push_str("    textures: std::borrow::Cow::Borrowed(&[\n");
     for texture in &pipeline.physical_textures {
         output.push_str("        PhysicalTexture {\n");
         output.push_str(&format!("            id: {},\n", texture.id));
@@ -48,7 +54,7 @@ fn dump_executable_pipeline(name: &str, pipeline: &ExecutablePipeline) -> String
         output.push_str(&format!("            is_source: {},\n", texture.is_source));
         output.push_str("        },\n");
     }
-    output.push_str("    ],\n");
+    output.push_str("    ]),\n");
 
     // Generate required sampler definitions
     output.push_str("    samplers: &[\n");
@@ -58,9 +64,10 @@ fn dump_executable_pipeline(name: &str, pipeline: &ExecutablePipeline) -> String
     output.push_str("    ],\n");
 
     // Generate shader pass definitions
-    output.push_str("    passes: &[\n");
+    output.push_str("    passes: std::borrow::Cow::Borrowed(&[\n");
     for pass in &pipeline.passes {
         output.push_str("        ExecutablePass {\n");
+        output.push_str(&format!("            id: {},\n", dump_shader_string_literal(&pass.id)));
         output.push_str(&format!("            name: \"Anime4K {name} {}\",\n", pass.id));
         output.push_str(&format!("            shader: {},\n", dump_shader_string_literal(&pass.shader)));
         output.push_str(&format!(
@@ -99,7 +106,7 @@ fn dump_executable_pipeline(name: &str, pipeline: &ExecutablePipeline) -> String
         output.push_str("            ],\n");
         output.push_str("        },\n");
     }
-    output.push_str("    ],\n");
+    output.push_str("    ]),\n");
     output.push('}');
 
     output
@@ -108,8 +115,20 @@ fn dump_executable_pipeline(name: &str, pipeline: &ExecutablePipeline) -> String
 /// Generates a Rust constant declaration for a CNN shader from GLSL
 ///
 /// Converts a GLSL CNN/GAN shader file to an optimized ExecutablePipeline constant.
+/// With the `fused-cnn` feature, same-resolution CNNs are emitted as a single fused pass.
+/// With the `mirror-edges` feature, convolutions mirror texels at the image edges.
 fn dump_cnn_shader_decl(id: &str, glsl_filepath: &str, helpers_dir: &str, minify: bool) -> String {
-    let pipeline = cnn_glsl_to_executable_pipeline(glsl_filepath, helpers_dir, minify).expect("Failed to convert CNN GLSL to executable pipeline");
+    let convert = if std::env::var_os("CARGO_FEATURE_FUSED_CNN").is_some() {
+        cnn_glsl_to_executable_pipeline_fused
+    } else {
+        cnn_glsl_to_executable_pipeline
+    };
+    let edge_mode = if std::env::var_os("CARGO_FEATURE_MIRROR_EDGES").is_some() {
+        EdgeMode::Mirror
+    } else {
+        EdgeMode::Clamp
+    };
+    let pipeline = convert(glsl_filepath, helpers_dir, minify, false, edge_mode).expect("Failed to convert CNN GLSL to executable pipeline");
     format!("    pub const {id}: ExecutablePipeline = {};\n", dump_executable_pipeline(id, &pipeline))
 }
 
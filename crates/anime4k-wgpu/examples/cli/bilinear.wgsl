@@ -0,0 +1,19 @@
+// Bilinear upscaling compute shader
+//
+// Resamples the input to the output size with a linear sampler, as the plain upscale
+// Anime4K output is compared against.
+
+@group(0) @binding(0) var input_texture: texture_2d<f32>;
+@group(0) @binding(1) var input_sampler: sampler;
+@group(0) @binding(2) var output_texture: texture_storage_2d<rgba32float, write>;
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) global_id: vec3u) {
+    let dims = textureDimensions(output_texture);
+    if global_id.x >= dims.x || global_id.y >= dims.y {
+        return;
+    }
+
+    let uv = (vec2f(global_id.xy) + 0.5) / vec2f(dims);
+    textureStore(output_texture, vec2i(global_id.xy), textureSampleLevel(input_texture, input_sampler, uv, 0.0));
+}
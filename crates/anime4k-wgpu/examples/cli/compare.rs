@@ -0,0 +1,165 @@
+//! Self-contained HTML reports comparing presets on one image
+//!
+//! The report embeds the input and a thumbnail of each preset's output as PNG data
+//! URIs, next to a table of output sizes and, when profiled, GPU times, so it can be
+//! attached to a review or opened from a chat without any accompanying files.
+
+use anime4k_wgpu::presets::{Anime4KPerformancePreset, Anime4KPreset};
+use anime4k_wgpu_verification::thumbnail::downsample_srgb_correct;
+use std::path::Path;
+
+/// Longer side in pixels that thumbnails are reduced to
+pub const THUMBNAIL_SIZE: u32 = 512;
+
+/// Result of running one preset for the report
+pub struct ComparisonEntry {
+    /// Anime4K preset that was run
+    pub preset: Anime4KPreset,
+    /// Performance preset that was run
+    pub performance_preset: Anime4KPerformancePreset,
+    /// Output width and height in pixels
+    pub output_size: (u32, u32),
+    /// Total GPU time of the Anime4K passes in milliseconds, or None if not profiled
+    pub gpu_time_ms: Option<f64>,
+    /// PNG-encoded thumbnail of the output
+    pub thumbnail_png: Vec<u8>,
+}
+
+/// Reduces an image to a thumbnail and encodes it as PNG
+///
+/// The image is box-filtered in linear light by the smallest integer factor that fits
+/// its longer side within `THUMBNAIL_SIZE`, so thin dark line art keeps its weight.
+/// Images already within `THUMBNAIL_SIZE` keep their size. Values are clamped to [0, 1].
+///
+/// # Arguments
+/// * `image` - Image to encode, with sRGB-encoded color components
+///
+/// # Returns
+/// The PNG file contents
+pub fn encode_thumbnail(image: &image::Rgba32FImage) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let (width, height) = image.dimensions();
+    let thumbnail = downsample_srgb_correct(image, width.max(height).div_ceil(THUMBNAIL_SIZE));
+
+    let mut png = Vec::new();
+    image::DynamicImage::ImageRgba32F(thumbnail)
+        .to_rgba8()
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)?;
+    Ok(png)
+}
+
+/// Encodes bytes as standard base64 with padding
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], chunk.get(1).copied().unwrap_or(0), chunk.get(2).copied().unwrap_or(0)];
+        let bits = ((bytes[0] as u32) << 16) | ((bytes[1] as u32) << 8) | bytes[2] as u32;
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[(bits >> (18 - index * 6)) as usize & 0x3f] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Escapes text for use in HTML content and attribute values
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Returns an `img` element showing PNG data
+fn png_image(png: &[u8], alt: &str) -> String {
+    format!("<img src=\"data:image/png;base64,{}\" alt=\"{}\">", base64(png), escape_html(alt))
+}
+
+/// Writes the comparison report
+///
+/// # Arguments
+/// * `path` - Path of the HTML file to write
+/// * `input_name` - Name of the input image shown in the title
+/// * `input_size` - Input width and height in pixels
+/// * `input_png` - PNG-encoded thumbnail of the input
+/// * `device_name` - Name of the GPU adapter the presets ran on
+/// * `entries` - Result of each preset, in the order to show them
+pub fn write_report(path: &Path, input_name: &str, input_size: (u32, u32), input_png: &[u8], device_name: &str, entries: &[ComparisonEntry]) -> Result<(), Box<dyn std::error::Error>> {
+    let fastest = entries.iter().filter_map(|entry| entry.gpu_time_ms).min_by(f64::total_cmp);
+
+    let mut rows = String::new();
+    let mut figures = String::new();
+    for entry in entries {
+        let label = format!("{} {}", entry.preset.name(), entry.performance_preset.name());
+        let gpu_time = match (entry.gpu_time_ms, fastest) {
+            (Some(time), Some(fastest)) if fastest > 0.0 => format!("{time:.3} ms ({:.2}x)", time / fastest),
+            (Some(time), _) => format!("{time:.3} ms"),
+            (None, _) => "not measured".to_string(),
+        };
+        rows.push_str(&format!(
+            "<tr><td>{}</td><td>{}x{}</td><td>{}</td></tr>\n",
+            escape_html(&label),
+            entry.output_size.0,
+            entry.output_size.1,
+            gpu_time
+        ));
+        figures.push_str(&format!(
+            "<figure>{}<figcaption>{}</figcaption></figure>\n",
+            png_image(&entry.thumbnail_png, &label),
+            escape_html(&label)
+        ));
+    }
+
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Anime4K-wgpu preset comparison: {title}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+table {{ border-collapse: collapse; margin-bottom: 2em; }}
+th, td {{ border: 1px solid #ccc; padding: 0.3em 0.8em; text-align: left; }}
+.grid {{ display: flex; flex-wrap: wrap; gap: 1em; }}
+figure {{ margin: 0; }}
+img {{ display: block; max-width: {thumbnail_size}px; }}
+</style>
+</head>
+<body>
+<h1>Preset comparison: {title}</h1>
+<p>Input {input_width}x{input_height}, run on {device}. GPU times cover the Anime4K passes of a second run; thumbnails are reduced to at most {thumbnail_size} pixels.</p>
+<table>
+<tr><th>Preset</th><th>Output</th><th>GPU time</th></tr>
+{rows}</table>
+<div class="grid">
+<figure>{input_image}<figcaption>Input</figcaption></figure>
+{figures}</div>
+</body>
+</html>
+"#,
+        title = escape_html(input_name),
+        thumbnail_size = THUMBNAIL_SIZE,
+        input_width = input_size.0,
+        input_height = input_size.1,
+        device = escape_html(device_name),
+        input_image = png_image(input_png, "Input"),
+    );
+
+    std::fs::write(path, html)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that thumbnails fit within `THUMBNAIL_SIZE` and small images keep their size
+    #[test]
+    fn test_thumbnail_size() {
+        for ((width, height), expected) in [((1030, 20), (344, 7)), ((300, 1024), (150, 512)), ((64, 48), (64, 48))] {
+            let png = encode_thumbnail(&image::Rgba32FImage::new(width, height)).unwrap();
+            let thumbnail = image::load_from_memory_with_format(&png, image::ImageFormat::Png).unwrap();
+            assert_eq!((thumbnail.width(), thumbnail.height()), expected, "{width}x{height}");
+        }
+    }
+}
@@ -0,0 +1,21 @@
+// Amplified difference compute shader
+//
+// Writes 0.5 + amplification * (anime4k - bilinear) per color channel, so unchanged
+// texels are mid gray, texels Anime4K brightened are lighter, and darkened ones darker.
+
+@group(0) @binding(0) var anime4k_texture: texture_2d<f32>;
+@group(0) @binding(1) var bilinear_texture: texture_2d<f32>;
+@group(0) @binding(2) var<uniform> amplification: f32;
+@group(0) @binding(3) var output_texture: texture_storage_2d<rgba32float, write>;
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) global_id: vec3u) {
+    let dims = textureDimensions(output_texture);
+    if global_id.x >= dims.x || global_id.y >= dims.y {
+        return;
+    }
+
+    let pos = vec2i(global_id.xy);
+    let difference = textureLoad(anime4k_texture, pos, 0).rgb - textureLoad(bilinear_texture, pos, 0).rgb;
+    textureStore(output_texture, pos, vec4f(clamp(0.5 + amplification * difference, vec3f(0.0), vec3f(1.0)), 1.0));
+}
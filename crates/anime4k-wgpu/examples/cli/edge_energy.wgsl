@@ -0,0 +1,58 @@
+// Edge energy reduction compute shader
+//
+// Computes the squared Sobel gradient magnitude of the luma of every texel and sums it.
+// Each workgroup reduces a band of rows (rows workgroup_id, workgroup_id + num_workgroups, ...)
+// into one partial sum.
+
+const WORKGROUP_SIZE: u32 = 64u;
+
+@group(0) @binding(0) var input_texture: texture_2d<f32>;
+@group(0) @binding(1) var<storage, read_write> partials: array<f32>;
+
+var<workgroup> shared_sum: array<f32, WORKGROUP_SIZE>;
+
+// BT.601 luma of the texel at pos, clamped to the edge
+fn luma(pos: vec2i, max_pos: vec2i) -> f32 {
+    let color = textureLoad(input_texture, clamp(pos, vec2i(0), max_pos), 0);
+    return dot(clamp(color.rgb, vec3f(0.0), vec3f(1.0)), vec3f(0.299, 0.587, 0.114));
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(local_invocation_index) local_index: u32, @builtin(workgroup_id) workgroup_id: vec3u, @builtin(num_workgroups) num_workgroups: vec3u) {
+    let dims = textureDimensions(input_texture);
+    let max_pos = vec2i(dims) - 1;
+
+    var sum = 0.0;
+    for (var y = workgroup_id.x; y < dims.y; y += num_workgroups.x) {
+        for (var x = local_index; x < dims.x; x += WORKGROUP_SIZE) {
+            let pos = vec2i(vec2u(x, y));
+            let tl = luma(pos + vec2i(-1, -1), max_pos);
+            let t = luma(pos + vec2i(0, -1), max_pos);
+            let tr = luma(pos + vec2i(1, -1), max_pos);
+            let l = luma(pos + vec2i(-1, 0), max_pos);
+            let r = luma(pos + vec2i(1, 0), max_pos);
+            let bl = luma(pos + vec2i(-1, 1), max_pos);
+            let b = luma(pos + vec2i(0, 1), max_pos);
+            let br = luma(pos + vec2i(1, 1), max_pos);
+
+            let gx = (tr + 2.0 * r + br) - (tl + 2.0 * l + bl);
+            let gy = (bl + 2.0 * b + br) - (tl + 2.0 * t + tr);
+            sum += gx * gx + gy * gy;
+        }
+    }
+
+    shared_sum[local_index] = sum;
+    workgroupBarrier();
+
+    // Tree reduction within the workgroup
+    for (var stride = WORKGROUP_SIZE / 2u; stride > 0u; stride /= 2u) {
+        if local_index < stride {
+            shared_sum[local_index] += shared_sum[local_index + stride];
+        }
+        workgroupBarrier();
+    }
+
+    if local_index == 0u {
+        partials[workgroup_id.x] = shared_sum[0];
+    }
+}
@@ -0,0 +1,80 @@
+//! Human-readable dumps of pipeline structure
+//!
+//! This module describes the pipelines a preset expands to, pass by pass, with the
+//! textures each pass reads and writes and their scale factors, so the effect of a
+//! preset and scale factor can be inspected without processing an image.
+
+use anime4k_wgpu::{ExecutablePipeline, PhysicalTexture, ScaleFactor};
+use std::fmt::Write;
+
+/// Formats a scale factor as "n" or "n/d"
+fn format_scale_factor(scale_factor: ScaleFactor) -> String {
+    if scale_factor.denominator == 1 {
+        scale_factor.numerator.to_string()
+    } else {
+        format!("{}/{}", scale_factor.numerator, scale_factor.denominator)
+    }
+}
+
+/// Formats a physical texture as its name, component count, and scale factors
+fn format_texture(texture: &PhysicalTexture) -> String {
+    let name = if texture.is_source { "SOURCE".to_string() } else { format!("T{}", texture.id) };
+    format!(
+        "{name} ({}ch, {}x{})",
+        texture.components,
+        format_scale_factor(texture.scale_factor.0),
+        format_scale_factor(texture.scale_factor.1)
+    )
+}
+
+/// Describes a chain of pipelines pass by pass
+///
+/// Scale factors of textures are relative to the input of their pipeline, and each
+/// pipeline is annotated with its input scale relative to the original image.
+///
+/// # Arguments
+/// * `pipelines` - Pipelines in execution order
+///
+/// # Returns
+/// A multi-line description ending in a newline
+pub fn describe_pipelines(pipelines: &[&'static ExecutablePipeline]) -> String {
+    let mut description = String::new();
+    let mut input_scale = (1.0, 1.0);
+
+    for (pipeline_index, pipeline) in pipelines.iter().enumerate() {
+        let texture = |id: u32| pipeline.textures().iter().find(|texture| texture.id == id).unwrap();
+
+        writeln!(
+            description,
+            "[{}] {} ({}), input at {}x{}",
+            pipeline_index + 1,
+            pipeline.name(),
+            pipeline.id(),
+            input_scale.0,
+            input_scale.1
+        )
+        .unwrap();
+        for (pass_index, pass) in pipeline.passes().iter().enumerate() {
+            writeln!(description, "    Pass {}: {}", pass_index + 1, pass.name).unwrap();
+            for input in pass.input_textures {
+                writeln!(description, "        reads  @{} {}", input.binding, format_texture(texture(input.physical_texture_id))).unwrap();
+            }
+            for output in pass.output_textures {
+                writeln!(description, "        writes @{} {}", output.binding, format_texture(texture(output.physical_texture_id))).unwrap();
+            }
+            for sampler in pass.samplers {
+                writeln!(description, "        sampler @{} {:?}", sampler.binding, sampler.filter_mode).unwrap();
+            }
+        }
+
+        // Scale of the result texture, the first output of the last pass
+        if let Some(result) = pipeline.passes().last().and_then(|pass| pass.output_textures.first()) {
+            let result = texture(result.physical_texture_id);
+            input_scale.0 *= result.scale_factor.0.numerator as f64 / result.scale_factor.0.denominator as f64;
+            input_scale.1 *= result.scale_factor.1.numerator as f64 / result.scale_factor.1.denominator as f64;
+        }
+    }
+
+    writeln!(description, "Output at {}x{} of the input", input_scale.0, input_scale.1).unwrap();
+    description
+}
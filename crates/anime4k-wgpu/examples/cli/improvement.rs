@@ -0,0 +1,213 @@
+//! Comparison of the Anime4K output with a bilinear upscale
+//!
+//! This module upscales the input bilinearly to the Anime4K output size and measures the
+//! edge energy (mean squared Sobel gradient of the luma) of both on the GPU. Anime4K
+//! sharpens line art, so its output should have more edge energy than the bilinear one.
+
+/// Workgroup size of the bilinear pass in each dimension
+const WORKGROUP_SIZE: u32 = 8;
+
+/// Maximum number of workgroups dispatched for the edge energy reduction
+///
+/// Each workgroup produces one partial sum, so this bounds the size of the readback.
+const MAX_REDUCTION_WORKGROUPS: u32 = 64;
+
+/// Edge energy of the Anime4K output and of the bilinear upscale
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Improvement {
+    /// Mean squared Sobel gradient of the Anime4K output
+    pub anime4k_edge_energy: f64,
+    /// Mean squared Sobel gradient of the bilinear upscale
+    pub bilinear_edge_energy: f64,
+}
+
+impl Improvement {
+    /// Returns the relative change in edge energy over bilinear, in percent
+    ///
+    /// Returns `None` if the bilinear upscale has no edge energy, as for a flat input,
+    /// since there is nothing to compare against.
+    pub fn edge_energy_gain_percent(&self) -> Option<f64> {
+        (self.bilinear_edge_energy > 0.0).then(|| (self.anime4k_edge_energy / self.bilinear_edge_energy - 1.0) * 100.0)
+    }
+}
+
+/// Measures the Anime4K output against a bilinear upscale of the input
+///
+/// # Arguments
+/// * `device` - The wgpu device (must support FLOAT32_FILTERABLE)
+/// * `queue` - The wgpu command queue
+/// * `input_texture` - RGBA32Float input the Anime4K output was produced from
+/// * `output_texture` - The Anime4K output (must have TEXTURE_BINDING usage)
+///
+/// # Returns
+/// The edge energy of both upscales, or an error if a readback failed
+pub fn measure(device: &wgpu::Device, queue: &wgpu::Queue, input_texture: &wgpu::Texture, output_texture: &wgpu::Texture) -> Result<Improvement, Box<dyn std::error::Error>> {
+    let bilinear_texture = bilinear_upscale(device, queue, input_texture, (output_texture.width(), output_texture.height()));
+    Ok(Improvement {
+        anime4k_edge_energy: edge_energy(device, queue, output_texture)?,
+        bilinear_edge_energy: edge_energy(device, queue, &bilinear_texture)?,
+    })
+}
+
+/// Upscales a texture bilinearly on the GPU
+pub fn bilinear_upscale(device: &wgpu::Device, queue: &wgpu::Queue, input_texture: &wgpu::Texture, (width, height): (u32, u32)) -> wgpu::Texture {
+    let output_texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Bilinear Texture"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba32Float,
+        usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+
+    let shader_module = device.create_shader_module(wgpu::include_wgsl!("bilinear.wgsl"));
+    let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Bilinear"),
+        layout: None,
+        module: &shader_module,
+        entry_point: Some("main"),
+        compilation_options: Default::default(),
+        cache: None,
+    });
+
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        label: Some("Bilinear"),
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        ..Default::default()
+    });
+
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Bilinear"),
+        layout: &compute_pipeline.get_bind_group_layout(0),
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&input_texture.create_view(&Default::default())),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(&sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::TextureView(&output_texture.create_view(&Default::default())),
+            },
+        ],
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Bilinear") });
+    {
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Bilinear"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&compute_pipeline);
+        compute_pass.set_bind_group(0, &bind_group, &[]);
+        compute_pass.dispatch_workgroups(width.div_ceil(WORKGROUP_SIZE), height.div_ceil(WORKGROUP_SIZE), 1);
+    }
+    queue.submit(std::iter::once(encoder.finish()));
+
+    output_texture
+}
+
+/// Computes the mean squared Sobel gradient of the luma of a texture on the GPU
+///
+/// Submits a reduction compute pass and blocks until the partial sums are read back.
+fn edge_energy(device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) -> Result<f64, Box<dyn std::error::Error>> {
+    let workgroup_count = texture.height().clamp(1, MAX_REDUCTION_WORKGROUPS);
+    let buffer_size = workgroup_count as u64 * 4;
+
+    let shader_module = device.create_shader_module(wgpu::include_wgsl!("edge_energy.wgsl"));
+    let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Edge Energy"),
+        layout: None,
+        module: &shader_module,
+        entry_point: Some("main"),
+        compilation_options: Default::default(),
+        cache: None,
+    });
+
+    let partials_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Edge Energy Partials"),
+        size: buffer_size,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+
+    let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Edge Energy Readback"),
+        size: buffer_size,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Edge Energy"),
+        layout: &compute_pipeline.get_bind_group_layout(0),
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&texture.create_view(&Default::default())),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: partials_buffer.as_entire_binding(),
+            },
+        ],
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Edge Energy") });
+    {
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Edge Energy"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&compute_pipeline);
+        compute_pass.set_bind_group(0, &bind_group, &[]);
+        compute_pass.dispatch_workgroups(workgroup_count, 1, 1);
+    }
+    encoder.copy_buffer_to_buffer(&partials_buffer, 0, &readback_buffer, 0, buffer_size);
+    queue.submit(std::iter::once(encoder.finish()));
+
+    // Map the readback buffer and wait for completion
+    let buffer_slice = readback_buffer.slice(..);
+    let (sender, receiver) = std::sync::mpsc::channel();
+    buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    device.poll(wgpu::PollType::Wait)?;
+    receiver.recv()??;
+
+    // Partial sums are combined in f64 to limit precision loss
+    let data = buffer_slice.get_mapped_range();
+    let sum: f64 = data.chunks_exact(4).map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()) as f64).sum();
+    drop(data);
+    readback_buffer.unmap();
+
+    Ok(sum / (texture.width() as u64 * texture.height() as u64) as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests the gain against a bilinear upscale with and without edge energy
+    #[test]
+    fn test_edge_energy_gain_percent() {
+        let improvement = |anime4k_edge_energy, bilinear_edge_energy| Improvement {
+            anime4k_edge_energy,
+            bilinear_edge_energy,
+        };
+        assert_eq!(improvement(1.5, 1.0).edge_energy_gain_percent(), Some(50.0));
+        assert_eq!(improvement(0.5, 2.0).edge_energy_gain_percent(), Some(-75.0));
+        assert_eq!(improvement(0.0, 0.0).edge_energy_gain_percent(), None);
+        assert_eq!(improvement(0.1, 0.0).edge_energy_gain_percent(), None);
+    }
+}
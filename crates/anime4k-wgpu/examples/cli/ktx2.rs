@@ -0,0 +1,183 @@
+//! KTX2 container encoding
+//!
+//! This module writes uncompressed RGBA8 sRGB textures with a mip chain as KTX2 files,
+//! which game engines and texture tools can load directly. Block-compressed formats
+//! such as BC7 are not produced, as that would require a texture compressor.
+
+/// KTX2 file identifier
+const IDENTIFIER: [u8; 12] = [0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// `VK_FORMAT_R8G8B8A8_SRGB`
+const VK_FORMAT_R8G8B8A8_SRGB: u32 = 43;
+
+/// Size in bytes of the header, from the identifier up to the level index
+const HEADER_SIZE: usize = 80;
+
+/// Size in bytes of one level index entry
+const LEVEL_INDEX_ENTRY_SIZE: usize = 24;
+
+/// Builds the data format descriptor of RGBA8 sRGB texels
+///
+/// # Returns
+/// The descriptor as 32-bit words, including the leading total size
+fn rgba8_srgb_data_format_descriptor() -> Vec<u32> {
+    /// Descriptor block size: 24-byte block header plus four 16-byte samples
+    const BLOCK_SIZE: u32 = 24 + 4 * 16;
+    /// KHR_DF_MODEL_RGBSDA
+    const COLOR_MODEL_RGBSDA: u32 = 1;
+    /// KHR_DF_PRIMARIES_BT709
+    const PRIMARIES_BT709: u32 = 1;
+    /// KHR_DF_TRANSFER_SRGB
+    const TRANSFER_SRGB: u32 = 2;
+    /// KHR_DF_SAMPLE_DATATYPE_LINEAR, marking the alpha sample as not sRGB-encoded
+    const QUALIFIER_LINEAR: u32 = 0x10;
+
+    let mut words = vec![
+        4 + BLOCK_SIZE,
+        // Khronos vendor, basic descriptor type
+        0,
+        // Version 1.3 of the data format specification
+        2 | (BLOCK_SIZE << 16),
+        COLOR_MODEL_RGBSDA | (PRIMARIES_BT709 << 8) | (TRANSFER_SRGB << 16),
+        // 1x1x1x1 texel blocks
+        0,
+        // 4 bytes per texel in plane 0
+        4,
+        0,
+    ];
+
+    // Channels R, G, B, and A (15), 8 bits each
+    for (index, channel) in [0u32, 1, 2, 15].into_iter().enumerate() {
+        let qualifiers = if channel == 15 { QUALIFIER_LINEAR } else { 0 };
+        words.extend([(index as u32 * 8) | (7 << 16) | ((channel | qualifiers) << 24), 0, 0, 255]);
+    }
+
+    words
+}
+
+/// Encodes a mip chain of RGBA8 sRGB images as a KTX2 file
+///
+/// # Arguments
+/// * `levels` - Mip levels from the base level down, each half the size of the previous one
+///
+/// # Returns
+/// The encoded file, or an `InvalidInput` error if there are no levels
+pub fn encode_rgba8_srgb(levels: &[image::RgbaImage]) -> Result<Vec<u8>, std::io::Error> {
+    let Some(base) = levels.first() else {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "No mip levels to encode"));
+    };
+
+    let dfd: Vec<u8> = rgba8_srgb_data_format_descriptor().iter().flat_map(|word| word.to_le_bytes()).collect();
+    let dfd_offset = HEADER_SIZE + LEVEL_INDEX_ENTRY_SIZE * levels.len();
+
+    // Level data is stored from the smallest level to the base level, each aligned to 4 bytes
+    let mut offset = (dfd_offset + dfd.len()).next_multiple_of(4);
+    let mut level_offsets = vec![0; levels.len()];
+    for (index, level) in levels.iter().enumerate().rev() {
+        level_offsets[index] = offset;
+        offset = (offset + level.as_raw().len()).next_multiple_of(4);
+    }
+
+    let mut file = Vec::with_capacity(offset);
+    file.extend(IDENTIFIER);
+    for value in [
+        VK_FORMAT_R8G8B8A8_SRGB,
+        // Type size
+        1,
+        base.width(),
+        base.height(),
+        // Depth, layer count, face count, level count, supercompression scheme
+        0,
+        0,
+        1,
+        levels.len() as u32,
+        0,
+    ] {
+        file.extend(value.to_le_bytes());
+    }
+
+    // Data format descriptor, no key/value data, no supercompression global data
+    file.extend((dfd_offset as u32).to_le_bytes());
+    file.extend((dfd.len() as u32).to_le_bytes());
+    file.extend(0u32.to_le_bytes());
+    file.extend(0u32.to_le_bytes());
+    file.extend(0u64.to_le_bytes());
+    file.extend(0u64.to_le_bytes());
+
+    for (level, level_offset) in levels.iter().zip(&level_offsets) {
+        let length = level.as_raw().len() as u64;
+        file.extend((*level_offset as u64).to_le_bytes());
+        file.extend(length.to_le_bytes());
+        file.extend(length.to_le_bytes());
+    }
+
+    file.extend(&dfd);
+    for (index, level) in levels.iter().enumerate().rev() {
+        file.resize(level_offsets[index], 0);
+        file.extend(level.as_raw());
+    }
+
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reads a little-endian `u32` at a byte offset
+    fn read_u32(file: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(file[offset..offset + 4].try_into().unwrap())
+    }
+
+    /// Reads a little-endian `u64` at a byte offset
+    fn read_u64(file: &[u8], offset: usize) -> u64 {
+        u64::from_le_bytes(file[offset..offset + 8].try_into().unwrap())
+    }
+
+    /// Tests the header fields, the data format descriptor location, and the level index
+    /// of an encoded mip chain, and that each level reads back from its index entry
+    #[test]
+    fn test_header_layout() {
+        let levels: Vec<image::RgbaImage> = [(5, 3), (2, 1), (1, 1)]
+            .into_iter()
+            .enumerate()
+            .map(|(level, (width, height))| image::RgbaImage::from_fn(width, height, |x, y| image::Rgba([level as u8, x as u8, y as u8, 255])))
+            .collect();
+        let file = encode_rgba8_srgb(&levels).unwrap();
+
+        assert_eq!(file[..12], IDENTIFIER);
+        let header: Vec<u32> = (0..9).map(|index| read_u32(&file, 12 + index * 4)).collect();
+        assert_eq!(header, [VK_FORMAT_R8G8B8A8_SRGB, 1, 5, 3, 0, 0, 1, 3, 0]);
+
+        let (dfd_offset, dfd_length) = (read_u32(&file, 48) as usize, read_u32(&file, 52) as usize);
+        assert_eq!(dfd_offset, HEADER_SIZE + LEVEL_INDEX_ENTRY_SIZE * levels.len());
+        assert_eq!(read_u32(&file, dfd_offset) as usize, dfd_length);
+        assert_eq!(
+            file[dfd_offset..dfd_offset + dfd_length],
+            rgba8_srgb_data_format_descriptor().iter().flat_map(|word| word.to_le_bytes()).collect::<Vec<_>>()
+        );
+        // No key/value data or supercompression global data
+        assert_eq!([read_u32(&file, 56), read_u32(&file, 60)], [0, 0]);
+        assert_eq!([read_u64(&file, 64), read_u64(&file, 72)], [0, 0]);
+
+        let mut previous_offset = file.len();
+        for (index, level) in levels.iter().enumerate() {
+            let entry = HEADER_SIZE + LEVEL_INDEX_ENTRY_SIZE * index;
+            let (offset, length, uncompressed_length) = (read_u64(&file, entry) as usize, read_u64(&file, entry + 8) as usize, read_u64(&file, entry + 16));
+            assert_eq!(offset % 4, 0, "level {index}");
+            assert_eq!(length, level.as_raw().len(), "level {index}");
+            assert_eq!(uncompressed_length, length as u64, "level {index}");
+            // Smaller levels are stored first, after the data format descriptor
+            assert!(offset >= dfd_offset + dfd_length && offset + length <= previous_offset, "level {index}");
+            assert_eq!(file[offset..offset + length], *level.as_raw(), "level {index}");
+            previous_offset = offset;
+        }
+        assert_eq!(read_u64(&file, HEADER_SIZE) as usize + levels[0].as_raw().len(), file.len());
+    }
+
+    /// Tests that an empty mip chain is rejected
+    #[test]
+    fn test_no_levels() {
+        assert_eq!(encode_rgba8_srgb(&[]).unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
@@ -5,24 +5,69 @@
 //! settings to enhance image quality through neural network-based upscaling.
 //!
 //! # Features
-//! - Multiple Anime4K algorithm presets (A, B, C, AA, BB, CA)
+//! - Multiple Anime4K algorithm presets (A, B, C, AA, BB, CA), plus nearest-neighbor for pixel art
 //! - Configurable performance levels (Light, Medium, High, Ultra, Extreme)
 //! - GPU-accelerated processing using wgpu
 //! - Support for various image formats
 //! - Batch processing capability through command-line interface
+//! - KTX2 export with a GPU-generated mip chain for game engines
+//! - EXR export preserving out-of-range values with `--no-clamp`
 //!
 //! # Usage
 //! ```bash
-//! anime4k-cli input.png output.png --scale-factor 2.0 --preset a --performance high
+//! anime4k-cli input.png output.png --scale-factor 2.0 --preset a --performance high [--alpha-mode premultiplied] [--input-order bgra] [--roi 0,0,256,256] [--pre-denoise gaussian:1.0] [--format ktx2]
+//! anime4k-cli input.png output.png --auto-preset [--auto-preset-thresholds 720,1080]
+//! anime4k-cli input.png output.exr --no-clamp
+//! anime4k-cli input.png output.png --report-improvement
+//! anime4k-cli --list jobs.txt --preset a [--resume]
+//! anime4k-cli transparent.png output.jpg --flatten '#FFFFFF'
+//! anime4k-cli input.png output.png --report timings.json
+//! anime4k-cli --explain --preset aa --performance ultra --scale-factor 2
+//! anime4k-cli input.png diff.png --diff [--diff-amplification 8]
+//! anime4k-cli input.png output.png --serialize-passes
+//! anime4k-cli input.png preview.png --scale-factor 0.5 [--resample-filter box]
+//! anime4k-cli input.png report.html --compare a,b,c,nearest --profile
+//! anime4k-cli input.png graded.png --lut grade.cube
+//! anime4k-cli grayscale.png output.png [--no-grayscale]
 //! ```
 
 use anime4k_wgpu::{
-    PipelineExecutor,
-    presets::{Anime4KPerformancePreset, Anime4KPreset},
+    Anime4kExecutor, ChannelOrder, CubeLut, PipelineExecutor, PreDenoise,
+    presets::{Anime4KPerformancePreset, Anime4KPreset, PresetThresholds},
 };
 use clap::Parser;
 use image::{DynamicImage, GenericImageView};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// HTML reports comparing presets
+mod compare;
+
+/// Difference images against bilinear
+mod difference;
+
+/// Pipeline structure dumps
+mod explain;
+
+/// Bilinear comparison metrics
+mod improvement;
+
+/// KTX2 container encoding
+mod ktx2;
+
+/// Progress manifests of resumable batch runs
+mod manifest;
+
+/// Mip chain generation
+mod mipmap;
+
+/// Texture readback into images
+mod readback;
+
+/// Resampling to arbitrary output sizes
+mod resample;
+
+/// JSON performance reports
+mod report;
 
 /// Command-line arguments for the Anime4K image upscaler
 ///
@@ -32,33 +77,239 @@ use std::path::PathBuf;
 #[command(version, about = "CLI tool for upscaling images using Anime4K")]
 struct Args {
     /// Input image file path
-    input: PathBuf,
+    #[arg(required_unless_present_any = ["list", "explain"])]
+    input: Option<PathBuf>,
 
     /// Output image file path
-    output: PathBuf,
+    #[arg(required_unless_present_any = ["list", "explain"])]
+    output: Option<PathBuf>,
 
-    /// Scale factor (e.g., 2.0 for 2x upscaling)
-    /// Note: This program does not support downscaling. Scale factors are treated as powers of 2 greater than or equal to 2.
+    /// Process the jobs of a list file instead, one `input<TAB>output` pair per line
+    ///
+    /// Lines starting with `#` are comments. Jobs run in order on a shared GPU device,
+    /// and a failed job is reported without stopping the remaining ones.
+    #[arg(long, conflicts_with_all = ["input", "output"])]
+    list: Option<PathBuf>,
+
+    /// Print the passes, textures, and scale factors of the selected pipelines instead of processing an image
+    #[arg(long, conflicts_with_all = ["input", "output", "list", "auto_preset"])]
+    explain: bool,
+
+    /// Scale factor (e.g., 2.0 for 2x upscaling, 1.5, or 0.5 for a half-size preview)
+    ///
+    /// Powers of two from 2 up are produced by Anime4K alone. Any other factor runs Anime4K
+    /// at the next power of two (at least 2x) and resamples the result to the requested size
+    /// with --resample-filter, so 1.5 is a 2x upscale reduced to 75%, and 0.5 a 2x upscale
+    /// reduced to 25%. Output sizes are rounded to whole pixels.
     #[arg(long, short, default_value = "2.0")]
     scale_factor: f64,
 
-    /// Anime4K preset (a, b, c, aa, bb, ca)
+    /// Filter resampling the Anime4K output when the scale factor is not a power of two (box, lanczos)
+    ///
+    /// `box` averages the covered area in linear light and never rings, which suits large
+    /// reductions; `lanczos` is sharper for factors close to the Anime4K scale.
+    #[arg(long, default_value = "lanczos")]
+    resample_filter: resample::ResampleFilter,
+
+    /// Anime4K preset (a, b, c, aa, bb, ca, nearest)
     #[arg(long, short, default_value = "a")]
     preset: String,
 
     /// Performance preset (light, medium, high, ultra, extreme)
     #[arg(long, short = 'e', default_value = "high")]
     performance: String,
+
+    /// Pick the preset and performance level from the input resolution, overriding --preset and --performance
+    #[arg(long)]
+    auto_preset: bool,
+
+    /// Shorter-side thresholds for --auto-preset separating low, medium, and high resolution inputs (low,high)
+    #[arg(long, default_value = "720,1080")]
+    auto_preset_thresholds: String,
+
+    /// Alpha convention of the input image (straight, premultiplied)
+    #[arg(long, default_value = "straight")]
+    alpha_mode: String,
+
+    /// Region of interest to crop from the input before upscaling (x,y,w,h)
+    #[arg(long)]
+    roi: Option<String>,
+
+    /// Denoise the input before upscaling (gaussian:SIGMA, bilateral:SIGMA[,RANGE_SIGMA])
+    #[arg(long)]
+    pre_denoise: Option<String>,
+
+    /// Save the output of grayscale inputs as color images instead of grayscale
+    #[arg(long)]
+    no_grayscale: bool,
+
+    /// Grade the Anime4K output with a 3D LUT in the .cube format
+    #[arg(long, value_name = "FILE")]
+    lut: Option<PathBuf>,
+
+    /// Channel order of the input pixel data (rgba, bgra); the output is written in the same order
+    #[arg(long, default_value = "rgba")]
+    input_order: String,
+
+    /// Clamp output values to [0, 1] before saving (default)
+    #[arg(long, overrides_with = "no_clamp")]
+    clamp: bool,
+
+    /// Preserve out-of-range output values; only float formats (.exr) can store them
+    #[arg(long, overrides_with = "clamp")]
+    no_clamp: bool,
+
+    /// Output format (image: chosen by the output file extension, ktx2: RGBA8 sRGB with mipmaps)
+    #[arg(long, default_value = "image")]
+    format: String,
+
+    /// Compare the edge energy of the output against a bilinear upscale of the input
+    #[arg(long)]
+    report_improvement: bool,
+
+    /// Composite the output over a background color (#RRGGBB), making it opaque
+    #[arg(long, value_name = "#RRGGBB")]
+    flatten: Option<String>,
+
+    /// Write per-pass GPU timings as JSON (requires timestamp query support)
+    #[arg(long, value_name = "PATH", conflicts_with = "list")]
+    report: Option<PathBuf>,
+
+    /// Lower the performance preset, then the scale factor, until the estimated texture memory fits in this many MiB
+    #[arg(long, value_name = "MIB")]
+    memory_budget: Option<u64>,
+
+    /// Save an amplified difference between the Anime4K output and a bilinear upscale instead of the output
+    ///
+    /// Unchanged areas are mid gray, areas Anime4K brightened are lighter, and darkened ones darker.
+    #[arg(long)]
+    diff: bool,
+
+    /// Factor the difference saved by --diff is multiplied by
+    #[arg(long, default_value = "4.0", requires = "diff")]
+    diff_amplification: f32,
+
+    /// Run the pipeline again with every pass submitted and waited for separately, and compare it with the normal run
+    ///
+    /// A mismatch means some pass raced with an earlier one on the same texture. This is
+    /// slow and only meant for debugging custom pipelines; the serialized result is saved.
+    #[arg(long)]
+    serialize_passes: bool,
+
+    /// Run each of these presets (comma-separated, e.g. a,b,c,nearest) and write an HTML report to the output path instead of an image
+    ///
+    /// The report embeds the input and a thumbnail of every output as data URIs, so it is
+    /// a single self-contained file. The presets run at the --performance level and scale
+    /// factor; --roi applies, while the other processing options are ignored.
+    #[arg(long, value_name = "PRESETS", conflicts_with_all = ["list", "explain", "auto_preset", "diff", "report"])]
+    compare: Option<String>,
+
+    /// Measure the GPU time of each preset run by --compare and include it in the report (requires timestamp query support)
+    #[arg(long, requires = "compare")]
+    profile: bool,
+
+    /// Skip the jobs of the --list file that a previous run with the same settings completed
+    ///
+    /// Every run of a list records its completed jobs in `<list>.progress.json`. A job is
+    /// skipped if it is recorded there and its input and output are unchanged since.
+    #[arg(long, requires = "list")]
+    resume: bool,
+}
+
+/// Processing options shared by every image of a run
+struct Options {
+    /// Anime4K preset, unless picked per image by `auto_preset_thresholds`
+    preset: Anime4KPreset,
+    /// Performance preset, unless picked per image by `auto_preset_thresholds`
+    performance_preset: Anime4KPerformancePreset,
+    /// Thresholds to pick the presets from each input resolution, or None to use the fixed presets
+    auto_preset_thresholds: Option<PresetThresholds>,
+    /// Target scale factor
+    scale_factor: f64,
+    /// Filter resampling the Anime4K output to the exact size of `scale_factor`
+    resample_filter: resample::ResampleFilter,
+    /// Alpha convention of the input images
+    alpha_mode: AlphaMode,
+    /// Region of interest to crop from each input, as given on the command line
+    roi: Option<String>,
+    /// Filter to denoise the inputs with before upscaling
+    pre_denoise: Option<PreDenoise>,
+    /// Whether to save the output of grayscale inputs as grayscale
+    grayscale: bool,
+    /// Path and contents of the LUT to grade the outputs with
+    lut: Option<(PathBuf, CubeLut)>,
+    /// Channel order of the input and output pixel data
+    channel_order: ChannelOrder,
+    /// Whether to clamp output values to [0, 1]
+    clamp: bool,
+    /// Container the results are written as
+    output_format: OutputFormat,
+    /// Whether to compare the edge energy of each output against bilinear
+    report_improvement: bool,
+    /// Background color to composite the outputs over, or None to keep their alpha
+    flatten: Option<[f32; 3]>,
+    /// Path to write the performance report of the image to, or None to skip profiling
+    report: Option<PathBuf>,
+    /// Maximum estimated texture memory in bytes, or None for no limit
+    memory_budget: Option<u64>,
+    /// Amplification of the difference image to save instead of the output, or None to save the output
+    diff: Option<f32>,
+    /// Whether to rerun the pipeline one pass at a time and compare it with the batched run
+    serialize_passes: bool,
+}
+
+impl Options {
+    /// Describes the options that affect the saved results
+    ///
+    /// Batch runs record this in their progress manifest, so that resuming with other
+    /// settings reprocesses the jobs completed before.
+    fn settings(&self) -> String {
+        format!(
+            "preset={:?} performance={:?} auto_preset={:?} scale_factor={} resample_filter={:?} alpha_mode={:?} roi={:?} grayscale={} pre_denoise={:?} lut={:?} channel_order={:?} clamp={} format={:?} flatten={:?} memory_budget={:?} diff={:?}",
+            self.preset,
+            self.performance_preset,
+            self.auto_preset_thresholds,
+            self.scale_factor,
+            self.resample_filter,
+            self.alpha_mode,
+            self.roi,
+            self.grayscale,
+            self.pre_denoise,
+            self.lut.as_ref().map(|(path, _)| path),
+            self.channel_order,
+            self.clamp,
+            self.output_format,
+            self.flatten,
+            self.memory_budget,
+            self.diff,
+        )
+    }
+}
+
+/// Container the upscaled result is written as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    /// Any image format supported by the image crate, chosen by the output file extension
+    Image,
+    /// KTX2 texture with a full mip chain
+    Ktx2,
+}
+
+/// How color components of the input image relate to its alpha channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AlphaMode {
+    /// Color components are independent of alpha
+    Straight,
+    /// Color components are already multiplied by alpha
+    Premultiplied,
 }
 
 /// Main application entry point
 ///
 /// Orchestrates the complete image upscaling pipeline:
 /// 1. Parse command-line arguments and validate presets
-/// 2. Load and prepare the input image
-/// 3. Initialize GPU context and resources
-/// 4. Create and execute the Anime4K processing pipeline
-/// 5. Save the upscaled result
+/// 2. Initialize GPU context and resources
+/// 3. Process the input image, or every job of the `--list` file in turn
 ///
 /// # Returns
 /// `Ok(())` on successful completion, or an error if any step fails
@@ -73,43 +324,149 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
 
     // Parse and validate Anime4K algorithm preset
-    let preset = match args.preset.to_lowercase().as_str() {
-        "a" => Anime4KPreset::ModeA,
-        "b" => Anime4KPreset::ModeB,
-        "c" => Anime4KPreset::ModeC,
-        "aa" => Anime4KPreset::ModeAA,
-        "bb" => Anime4KPreset::ModeBB,
-        "ca" => Anime4KPreset::ModeCA,
+    let preset = args.preset.parse::<Anime4KPreset>().unwrap_or_else(|e| {
+        eprintln!("{e}");
+        std::process::exit(1);
+    });
+
+    // Parse and validate performance preset
+    let performance_preset = args.performance.parse::<Anime4KPerformancePreset>().unwrap_or_else(|e| {
+        eprintln!("{e}");
+        std::process::exit(1);
+    });
+
+    if !(args.scale_factor.is_finite() && args.scale_factor > 0.0) {
+        eprintln!("Invalid scale factor '{}': must be a positive number", args.scale_factor);
+        std::process::exit(1);
+    }
+
+    if args.explain {
+        let pipelines = preset.create_pipelines(performance_preset, args.scale_factor);
+        println!(
+            "Preset '{}' with performance '{}' at scale factor {}: {} pipelines",
+            preset.name(),
+            performance_preset.name(),
+            args.scale_factor,
+            pipelines.len()
+        );
+        print!("{}", explain::describe_pipelines(&pipelines));
+        return Ok(());
+    }
+
+    // Parse and validate alpha mode
+    let alpha_mode = match args.alpha_mode.to_lowercase().as_str() {
+        "straight" => AlphaMode::Straight,
+        "premultiplied" => AlphaMode::Premultiplied,
         _ => {
-            eprintln!("Invalid preset '{}'. Valid presets: a, b, c, aa, bb, ca", args.preset);
+            eprintln!("Invalid alpha mode '{}'. Valid modes: straight, premultiplied", args.alpha_mode);
             std::process::exit(1);
         }
     };
 
-    // Parse and validate performance preset
-    let performance_preset = match args.performance.to_lowercase().as_str() {
-        "light" => Anime4KPerformancePreset::Light,
-        "medium" => Anime4KPerformancePreset::Medium,
-        "high" => Anime4KPerformancePreset::High,
-        "ultra" => Anime4KPerformancePreset::Ultra,
-        "extreme" => Anime4KPerformancePreset::Extreme,
+    // Parse and validate input channel order
+    let channel_order = match args.input_order.to_lowercase().as_str() {
+        "rgba" => ChannelOrder::Rgba,
+        "bgra" => ChannelOrder::Bgra,
         _ => {
-            eprintln!("Invalid performance preset '{}'. Valid presets: light, medium, high, ultra, extreme", args.performance);
+            eprintln!("Invalid input order '{}'. Valid orders: rgba, bgra", args.input_order);
             std::process::exit(1);
         }
     };
 
-    // Load input image
-    println!("Loading image from: {}", args.input.display());
-    let input_image = image::open(&args.input)?;
-    let (input_width, input_height) = input_image.dimensions();
-    println!("Input image: {input_width}x{input_height}");
+    // Parse and validate the pre-denoise filter
+    let pre_denoise = args.pre_denoise.as_deref().map(|pre_denoise| {
+        pre_denoise.parse::<PreDenoise>().unwrap_or_else(|e| {
+            eprintln!("{e}");
+            std::process::exit(1);
+        })
+    });
+
+    // Load and validate the LUT
+    let lut = args.lut.map(|path| {
+        let lut = std::fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|text| text.parse::<CubeLut>()).unwrap_or_else(|e| {
+            eprintln!("Invalid LUT '{}': {e}", path.display());
+            std::process::exit(1);
+        });
+        (path, lut)
+    });
+
+    // Parse and validate output format
+    let output_format = match args.format.to_lowercase().as_str() {
+        "image" => OutputFormat::Image,
+        "ktx2" => OutputFormat::Ktx2,
+        _ => {
+            eprintln!("Invalid output format '{}'. Valid formats: image, ktx2", args.format);
+            std::process::exit(1);
+        }
+    };
 
-    // Calculate expected output dimensions based on scale factor
-    let scale_factor_u32 = args.scale_factor.ceil() as u32;
-    let expected_width = input_width * scale_factor_u32;
-    let expected_height = input_height * scale_factor_u32;
-    println!("Expected output: {}x{} (scale factor: {})", expected_width, expected_height, args.scale_factor);
+    // Parse and validate the flatten background color
+    let flatten = args.flatten.as_deref().map(|color| {
+        parse_hex_color(color).unwrap_or_else(|e| {
+            eprintln!("Invalid flatten color '{color}': {e}");
+            std::process::exit(1);
+        })
+    });
+
+    // Parse and validate auto preset thresholds
+    let auto_preset_thresholds = args.auto_preset.then(|| {
+        parse_thresholds(&args.auto_preset_thresholds).unwrap_or_else(|e| {
+            eprintln!("Invalid auto preset thresholds '{}': {e}", args.auto_preset_thresholds);
+            std::process::exit(1);
+        })
+    });
+
+    if !(args.diff_amplification.is_finite() && args.diff_amplification > 0.0) {
+        eprintln!("Invalid diff amplification '{}': must be a positive number", args.diff_amplification);
+        std::process::exit(1);
+    }
+
+    // Parse and validate the presets to compare
+    let compare_presets = args.compare.as_deref().map(|presets| {
+        presets
+            .split(',')
+            .map(|preset| preset.trim().parse::<Anime4KPreset>())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_or_else(|e| {
+                eprintln!("{e}");
+                std::process::exit(1);
+            })
+    });
+
+    // Read the job list up front so a malformed list fails before the GPU is initialized
+    let jobs = args.list.as_ref().map(|list| {
+        let jobs = std::fs::read_to_string(list).map_err(|e| e.to_string()).and_then(|text| parse_job_list(&text)).unwrap_or_else(|e| {
+            eprintln!("Invalid job list '{}': {e}", list.display());
+            std::process::exit(1);
+        });
+        if jobs.is_empty() {
+            eprintln!("Job list '{}' contains no jobs", list.display());
+            std::process::exit(1);
+        }
+        jobs
+    });
+
+    let options = Options {
+        preset,
+        performance_preset,
+        auto_preset_thresholds,
+        scale_factor: args.scale_factor,
+        resample_filter: args.resample_filter,
+        alpha_mode,
+        roi: args.roi,
+        pre_denoise,
+        grayscale: !args.no_grayscale,
+        lut,
+        channel_order,
+        clamp: args.clamp || !args.no_clamp,
+        output_format,
+        report_improvement: args.report_improvement,
+        flatten,
+        report: args.report,
+        memory_budget: args.memory_budget.map(|mib| mib * 1024 * 1024),
+        diff: args.diff.then_some(args.diff_amplification),
+        serialize_passes: args.serialize_passes,
+    };
 
     // Initialize wgpu context for GPU processing
     println!("Initializing GPU...");
@@ -125,49 +482,299 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         force_fallback_adapter: false,
     }))?;
 
+    // Profiling needs timestamps written around each compute pass
+    let mut additional_features = wgpu::Features::empty();
+    if options.report.is_some() || args.profile {
+        if !adapter.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            let option = if args.profile { "--profile" } else { "--report" };
+            return Err(format!("Adapter '{}' does not support timestamp queries, which {option} requires", adapter.get_info().name).into());
+        }
+        additional_features |= wgpu::Features::TIMESTAMP_QUERY;
+    }
+
     // Create device with required features for Anime4K processing
-    let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
-        label: None,
-        required_features: wgpu::Features::FLOAT32_FILTERABLE,
-        required_limits: wgpu::Limits::default(),
-        memory_hints: wgpu::MemoryHints::default(),
-        trace: Default::default(),
-    }))?;
+    let (device, queue) = pollster::block_on(PipelineExecutor::request_device(&adapter, additional_features))?;
 
+    let adapter_info = adapter.get_info();
     println!("GPU initialized successfully");
 
+    if let Some(presets) = compare_presets {
+        // Clap guarantees both paths are present with --compare, which conflicts with --list
+        return compare_presets_report(&device, &queue, &adapter_info, &options, &presets, args.profile, &args.input.unwrap(), &args.output.unwrap());
+    }
+
+    let Some(jobs) = jobs else {
+        // Clap guarantees both paths are present without --list
+        return process_image(&device, &queue, &adapter_info, &options, &args.input.unwrap(), &args.output.unwrap());
+    };
+
+    // Record completed jobs so an interrupted run can be resumed
+    let manifest_path = manifest::Manifest::path_for(args.list.as_deref().unwrap());
+    let mut manifest = if args.resume {
+        manifest::Manifest::resume(&manifest_path, options.settings()).map_err(|e| format!("Invalid progress manifest '{}': {e}", manifest_path.display()))?
+    } else {
+        manifest::Manifest::new(options.settings())
+    };
+
+    // Process the jobs sequentially on the shared device, continuing past failures
+    let mut failed = 0;
+    let mut skipped = 0;
+    for (index, (input, output)) in jobs.iter().enumerate() {
+        println!();
+        println!("[{}/{}] {} -> {}", index + 1, jobs.len(), input.display(), output.display());
+        if args.resume && manifest.is_completed(input, output) {
+            println!("[{}/{}] Already completed, skipping", index + 1, jobs.len());
+            skipped += 1;
+            continue;
+        }
+        if let Err(e) = process_image(&device, &queue, &adapter_info, &options, input, output) {
+            eprintln!("[{}/{}] Failed: {e}", index + 1, jobs.len());
+            failed += 1;
+            continue;
+        }
+        if let Err(e) = manifest.record(input, output).and_then(|()| manifest.save(&manifest_path)) {
+            eprintln!("[{}/{}] Failed to update the progress manifest '{}': {e}", index + 1, jobs.len(), manifest_path.display());
+        }
+    }
+
+    println!();
+    if skipped > 0 {
+        println!("Skipped {skipped} jobs completed by a previous run");
+    }
+    println!("Processed {} of {} jobs successfully", jobs.len() - failed, jobs.len());
+    if failed > 0 {
+        return Err(format!("{failed} of {} jobs failed", jobs.len()).into());
+    }
+
+    Ok(())
+}
+
+/// Upscales a single image with the shared device and saves the result
+///
+/// # Arguments
+/// * `device` - wgpu device for creating GPU resources
+/// * `queue` - Command queue for uploads, processing, and readback
+/// * `adapter_info` - Information on the adapter the device was created from
+/// * `options` - Processing options shared by every image of the run
+/// * `input` - Path of the image to upscale
+/// * `output` - Path to save the upscaled image to
+///
+/// # Returns
+/// `Ok(())` if the image was upscaled and saved, or an error describing the failure
+fn process_image(device: &wgpu::Device, queue: &wgpu::Queue, adapter_info: &wgpu::AdapterInfo, options: &Options, input: &Path, output: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    // Load input image
+    println!("Loading image from: {}", input.display());
+    let input_image = image::open(input)?;
+    let (input_width, input_height) = input_image.dimensions();
+    println!("Input image: {input_width}x{input_height}");
+    if input_width == 0 || input_height == 0 {
+        return Err(format!("Input image has no pixels ({input_width}x{input_height})").into());
+    }
+
+    // Grayscale inputs still run through the CNNs as RGB, and only the output is converted back to grayscale.
+    // The restore CNNs can move the channels slightly apart, which the conversion to luma averages out.
+    let grayscale = options.grayscale && !input_image.color().has_color();
+    let grayscale_alpha = grayscale && input_image.color().has_alpha();
+    if grayscale {
+        println!("Grayscale input{}", if grayscale_alpha { " with alpha" } else { "" });
+    }
+    // A LUT or a background color can add color, which a grayscale output would drop
+    let grayscale_output = grayscale && options.lut.is_none() && options.flatten.is_none();
+
+    // Swizzle BGRA data to RGBA so the CNN sees red and blue where it expects them
+    let input_image = if options.channel_order == ChannelOrder::Rgba {
+        input_image
+    } else {
+        let mut rgba_image = input_image.to_rgba32f();
+        options.channel_order.swizzle_rgba(&mut rgba_image);
+        DynamicImage::ImageRgba32F(rgba_image)
+    };
+
+    // Crop to the region of interest before uploading, so only that area is processed
+    let input_image = if let Some(roi) = &options.roi {
+        let (x, y, width, height) = parse_roi(roi, input_width, input_height).map_err(|e| format!("Invalid ROI '{roi}': {e}"))?;
+        println!("Cropping to ROI: {width}x{height} at ({x}, {y})");
+        input_image.crop_imm(x, y, width, height)
+    } else {
+        input_image
+    };
+    let (input_width, input_height) = input_image.dimensions();
+
+    // Pick the preset from the resolution actually processed, i.e. after cropping
+    let (preset, performance_preset) = if let Some(thresholds) = &options.auto_preset_thresholds {
+        let recommendation = Anime4KPreset::recommend_with_thresholds(input_width, input_height, options.scale_factor, thresholds);
+        println!("Auto preset: {} with performance {}", recommendation.0.name(), recommendation.1.name());
+        recommendation
+    } else {
+        (options.preset, options.performance_preset)
+    };
+
+    // Un-premultiply so the CNN sees true colors instead of colors darkened towards transparent edges
+    let input_image = if options.alpha_mode == AlphaMode::Premultiplied {
+        let mut rgba_image = input_image.to_rgba32f();
+        unpremultiply_alpha(&mut rgba_image);
+        DynamicImage::ImageRgba32F(rgba_image)
+    } else {
+        input_image
+    };
+
     // Convert input image to GPU texture format
     println!("Loading image to GPU texture...");
-    let input_texture = load_image_to_texture(&device, &queue, &input_image, wgpu::TextureFormat::Rgba32Float)?;
+    let input_texture = load_image_to_texture(device, queue, &input_image, wgpu::TextureFormat::Rgba32Float)?;
 
-    // Create processing pipelines for the selected configuration
-    println!("Setting up Anime4K pipeline with preset '{}' and performance '{}'", args.preset, args.performance);
-    let pipelines = preset.create_pipelines(performance_preset, args.scale_factor);
+    // Create processing pipelines for the selected configuration, lowered to fit the memory budget if needed
+    let budgeted = preset.create_pipelines_within_budget(performance_preset, options.scale_factor, input_width, input_height, options.memory_budget)?;
+    for downgrade in &budgeted.downgrades {
+        println!("Memory budget: {downgrade}");
+    }
+    let performance_preset = budgeted.performance_preset;
+    println!("Setting up Anime4K pipeline with preset '{}' and performance '{}'", preset.name(), performance_preset.name());
+    let pipelines = budgeted.pipelines;
     if pipelines.is_empty() {
         return Err("No pipelines generated for the selected preset".into());
     }
 
-    println!("Pipeline will use {} stages", pipelines.len());
+    println!(
+        "Pipeline will use {} stages (estimated {:.1} MiB of textures)",
+        pipelines.len(),
+        budgeted.estimated_memory as f64 / (1024.0 * 1024.0)
+    );
+
+    // Resolve the exact output dimensions from the pipelines themselves
+    let (expected_width, expected_height) = PipelineExecutor::output_dimensions(&pipelines, input_width, input_height);
+    println!("Expected output: {}x{} (scale factor: {})", expected_width, expected_height, budgeted.target_scale_factor);
+    // The budget only ever lowers the factor to a power of two, which needs no resampling
+    let (target_width, target_height) = resample::scaled_dimensions(input_width, input_height, budgeted.target_scale_factor);
+    let resample_to = ((target_width, target_height) != (expected_width, expected_height)).then_some((target_width, target_height));
+    if resample_to.is_some() {
+        println!("Resampling to {target_width}x{target_height} with the {:?} filter", options.resample_filter);
+    }
 
     // Create and configure the shader pipeline
-    let (pipeline, output_texture) = PipelineExecutor::new(&pipelines, &device, &input_texture);
+    let (pipeline, _) = if let Some(pre_denoise) = options.pre_denoise {
+        println!("Denoising before upscaling: {pre_denoise:?}");
+        PipelineExecutor::new_pre_denoised(&pipelines, device, &input_texture, pre_denoise)
+    } else {
+        PipelineExecutor::new(&pipelines, device, &input_texture)
+    };
+    let pipeline = match &options.lut {
+        Some((path, lut)) => {
+            println!("Grading with the {}³ LUT {}", lut.size, path.display());
+            pipeline.with_lut(device, queue, lut).0
+        }
+        None => pipeline,
+    };
 
     // Execute the Anime4K processing pipeline
     println!("Executing Anime4K pipeline...");
-    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Anime4K Pipeline") });
+    let output_texture = execute(device, queue, &pipeline)?;
+
+    // Rerun one pass at a time; a different output points to a synchronization hazard between passes
+    if options.serialize_passes {
+        let batched = pipeline.output_stats(device, queue)?;
+        pipeline.pass_serialized(device, queue)?;
+        let serialized = pipeline.output_stats(device, queue)?;
+        if serialized == batched {
+            println!("Serialized run matches the batched run");
+        } else {
+            println!("Warning: serialized run differs from the batched run, some pass may read a texture before it is fully written");
+            println!("  Batched:    {batched:?}");
+            println!("  Serialized: {serialized:?}");
+        }
+    }
 
-    pipeline.pass(&mut encoder);
+    // Reach scale factors that are not a power of two by resampling the Anime4K output
+    let resampled_texture;
+    let output_texture = if let Some(dimensions) = resample_to {
+        resampled_texture = resample::resample(device, queue, output_texture, dimensions, options.resample_filter);
+        &resampled_texture
+    } else {
+        output_texture
+    };
 
-    queue.submit(std::iter::once(encoder.finish()));
+    // Time a second run, so first-use overhead stays out of the report
+    if let Some(report_path) = &options.report {
+        let timings = pipeline.profile(device, queue)?;
+        let report = report::PerformanceReport::new(
+            adapter_info,
+            preset,
+            performance_preset,
+            (input_width, input_height),
+            (output_texture.width(), output_texture.height()),
+            &timings,
+        );
+        report.write(report_path)?;
+        println!("GPU time: {:.3} ms over {} passes, report written to {}", report.total_ms, report.passes.len(), report_path.display());
+    }
 
-    // Wait for GPU processing to complete
-    device.poll(wgpu::PollType::Wait)?;
+    if options.report_improvement {
+        let improvement = improvement::measure(device, queue, &input_texture, output_texture)?;
+        let gain = match improvement.edge_energy_gain_percent() {
+            Some(gain) => format!("{gain:+.1}%"),
+            None => "no edges to compare".to_string(),
+        };
+        println!("Edge energy: Anime4K {:.6}, bilinear {:.6} ({gain})", improvement.anime4k_edge_energy, improvement.bilinear_edge_energy);
+    }
+
+    // The difference image is opaque and saved in place of the output
+    let difference_texture;
+    let output_texture = if let Some(amplification) = options.diff {
+        println!("Saving the difference against bilinear, amplified {amplification}x");
+        difference_texture = difference::difference_image(device, queue, &input_texture, output_texture, amplification);
+        &difference_texture
+    } else {
+        output_texture
+    };
 
     // Convert result back to image format and save
-    println!("Saving result to: {}", args.output.display());
-    let output_image = save_texture_to_image(&device, &queue, &output_texture)?;
-    let output_rgba8 = DynamicImage::ImageRgba32F(output_image).to_rgba8();
-    output_rgba8.save(&args.output)?;
+    println!("Saving result to: {}", output.display());
+    let levels = match options.output_format {
+        OutputFormat::Image => vec![output_texture.clone()],
+        OutputFormat::Ktx2 => {
+            let levels = mipmap::generate_mip_chain(device, queue, output_texture);
+            println!("Generated {} mip levels", levels.len());
+            levels
+        }
+    };
+    // Only EXR stores floats; every other format quantizes to 8 bits, which clips anyway
+    let float_output = options.output_format == OutputFormat::Image && output.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("exr"));
+    if !options.clamp && !float_output {
+        eprintln!("Warning: --no-clamp has no effect on 8-bit output; save to .exr to keep out-of-range values");
+    }
+
+    let mut output_levels = Vec::with_capacity(levels.len());
+    for level in &levels {
+        let mut output_image = readback::save_texture_to_image(device, queue, level)?;
+        if options.alpha_mode == AlphaMode::Premultiplied && options.diff.is_none() {
+            // Restore the source convention using the source alpha resampled to the level size
+            let alpha = image::imageops::resize(&input_image.to_rgba32f(), output_image.width(), output_image.height(), image::imageops::FilterType::Triangle);
+            premultiply_alpha(&mut output_image, &alpha);
+        }
+        if let Some(background) = options.flatten {
+            flatten_alpha(&mut output_image, background, options.alpha_mode);
+        }
+        options.channel_order.swizzle_rgba(&mut output_image);
+        if options.clamp {
+            output_image.iter_mut().for_each(|value| *value = value.clamp(0.0, 1.0));
+        }
+        output_levels.push(output_image);
+    }
+    // Formats without an alpha channel drop it, leaving transparent areas with whatever color they hold
+    let alpha_output = output_format_supports_alpha(output);
+    if !alpha_output && options.flatten.is_none() && output_levels[0].pixels().any(|pixel| pixel[3] < 1.0) {
+        eprintln!("Warning: the output format has no alpha channel, so transparency is discarded; use --flatten '#RRGGBB' to composite over a background");
+    }
+    match options.output_format {
+        OutputFormat::Image if float_output => DynamicImage::ImageRgba32F(output_levels.swap_remove(0)).save(output)?,
+        OutputFormat::Image if grayscale_output && grayscale_alpha && alpha_output => DynamicImage::ImageRgba32F(output_levels.swap_remove(0)).to_luma_alpha8().save(output)?,
+        OutputFormat::Image if grayscale_output => DynamicImage::ImageRgba32F(output_levels.swap_remove(0)).to_luma8().save(output)?,
+        OutputFormat::Image if !alpha_output => DynamicImage::ImageRgba32F(output_levels.swap_remove(0)).to_rgb8().save(output)?,
+        OutputFormat::Image => DynamicImage::ImageRgba32F(output_levels.swap_remove(0)).to_rgba8().save(output)?,
+        OutputFormat::Ktx2 => {
+            let output_levels: Vec<_> = output_levels.into_iter().map(|level| DynamicImage::ImageRgba32F(level).to_rgba8()).collect();
+            std::fs::write(output, ktx2::encode_rgba8_srgb(&output_levels)?)?
+        }
+    }
 
     println!(
         "Successfully upscaled image from {}x{} to {}x{}",
@@ -180,6 +787,261 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Runs several presets on one image and writes an HTML report comparing them
+///
+/// # Arguments
+/// * `device` - wgpu device for creating GPU resources
+/// * `queue` - Command queue for uploads, processing, and readback
+/// * `adapter_info` - Information on the adapter the device was created from
+/// * `options` - Processing options; the performance preset, scale factor, resample filter, and ROI are used
+/// * `presets` - Anime4K presets to run, in the order of the report
+/// * `profile` - Whether to measure the GPU time of each preset
+/// * `input` - Path of the image to process
+/// * `output` - Path to write the HTML report to
+///
+/// # Returns
+/// `Ok(())` if every preset ran and the report was written, or an error describing the failure
+#[allow(clippy::too_many_arguments)]
+fn compare_presets_report(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    adapter_info: &wgpu::AdapterInfo,
+    options: &Options,
+    presets: &[Anime4KPreset],
+    profile: bool,
+    input: &Path,
+    output: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Loading image from: {}", input.display());
+    let input_image = image::open(input)?;
+    let (input_width, input_height) = input_image.dimensions();
+    if input_width == 0 || input_height == 0 {
+        return Err(format!("Input image has no pixels ({input_width}x{input_height})").into());
+    }
+    let input_image = if let Some(roi) = &options.roi {
+        let (x, y, width, height) = parse_roi(roi, input_width, input_height).map_err(|e| format!("Invalid ROI '{roi}': {e}"))?;
+        input_image.crop_imm(x, y, width, height)
+    } else {
+        input_image
+    };
+    let (input_width, input_height) = input_image.dimensions();
+    let input_texture = load_image_to_texture(device, queue, &input_image, wgpu::TextureFormat::Rgba32Float)?;
+
+    let mut entries = Vec::with_capacity(presets.len());
+    for &preset in presets {
+        println!("Running preset '{}' with performance '{}'", preset.name(), options.performance_preset.name());
+        let pipelines = preset.create_pipelines(options.performance_preset, options.scale_factor);
+        if pipelines.is_empty() {
+            return Err(format!("No pipelines generated for preset '{}'", preset.name()).into());
+        }
+        let (pipeline, _) = PipelineExecutor::new(&pipelines, device, &input_texture);
+        let output_texture = execute(device, queue, &pipeline)?;
+
+        // The first run above keeps first-use overhead out of the measurement
+        let gpu_time_ms = if profile {
+            let timings = pipeline.profile(device, queue)?;
+            Some(timings.iter().map(|timing| timing.duration.as_secs_f64() * 1000.0).sum::<f64>())
+        } else {
+            None
+        };
+
+        let target_size = resample::scaled_dimensions(input_width, input_height, options.scale_factor);
+        let resampled_texture;
+        let output_texture = if (output_texture.width(), output_texture.height()) != target_size {
+            resampled_texture = resample::resample(device, queue, output_texture, target_size, options.resample_filter);
+            &resampled_texture
+        } else {
+            output_texture
+        };
+
+        let output_image = readback::save_texture_to_image(device, queue, output_texture)?;
+        if let Some(gpu_time_ms) = gpu_time_ms {
+            println!("  {}x{}, GPU time {gpu_time_ms:.3} ms", output_image.width(), output_image.height());
+        }
+        entries.push(compare::ComparisonEntry {
+            preset,
+            performance_preset: options.performance_preset,
+            output_size: output_image.dimensions(),
+            gpu_time_ms,
+            thumbnail_png: compare::encode_thumbnail(&output_image)?,
+        });
+    }
+
+    let input_name = input.file_name().map_or_else(|| input.display().to_string(), |name| name.to_string_lossy().into_owned());
+    let input_png = compare::encode_thumbnail(&input_image.to_rgba32f())?;
+    compare::write_report(output, &input_name, (input_width, input_height), &input_png, &adapter_info.name, &entries)?;
+    println!("Comparison of {} presets written to {}", entries.len(), output.display());
+    Ok(())
+}
+
+/// Runs an executor once and waits for the GPU to finish
+///
+/// # Arguments
+/// * `device` - wgpu device to create the command encoder with
+/// * `queue` - Command queue to submit the processing to
+/// * `executor` - Executor bound to the uploaded input texture
+///
+/// # Returns
+/// The executor's output texture, or an error if waiting for the GPU failed
+fn execute<'a>(device: &wgpu::Device, queue: &wgpu::Queue, executor: &'a impl Anime4kExecutor) -> Result<&'a wgpu::Texture, Box<dyn std::error::Error>> {
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Anime4K Pipeline") });
+
+    executor.pass(&mut encoder);
+
+    queue.submit(std::iter::once(encoder.finish()));
+
+    // Wait for GPU processing to complete
+    device.poll(wgpu::PollType::Wait)?;
+
+    Ok(executor.output_texture())
+}
+
+/// Parses a job list of tab-separated input and output paths, one job per line
+///
+/// Blank lines and lines starting with `#` are skipped.
+///
+/// # Arguments
+/// * `text` - Contents of the job list file
+///
+/// # Returns
+/// The `(input, output)` path pairs in order, or a message naming the first malformed line
+fn parse_job_list(text: &str) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(index, line)| {
+            let (input, output) = line.split_once('\t').ok_or_else(|| format!("line {}: expected 'input<TAB>output'", index + 1))?;
+            let (input, output) = (input.trim(), output.trim());
+            if input.is_empty() || output.is_empty() || output.contains('\t') {
+                return Err(format!("line {}: expected exactly one input and one output path", index + 1));
+            }
+            Ok((PathBuf::from(input), PathBuf::from(output)))
+        })
+        .collect()
+}
+
+/// Parses and validates a region of interest in the form `x,y,w,h`
+///
+/// # Arguments
+/// * `roi` - Region string as given on the command line
+/// * `image_width` - Width of the input image
+/// * `image_height` - Height of the input image
+///
+/// # Returns
+/// The region as `(x, y, width, height)`, or a message describing why it is invalid
+fn parse_roi(roi: &str, image_width: u32, image_height: u32) -> Result<(u32, u32, u32, u32), String> {
+    let values = roi
+        .split(',')
+        .map(|value| value.trim().parse::<u32>().map_err(|_| format!("'{value}' is not a non-negative integer")))
+        .collect::<Result<Vec<_>, _>>()?;
+    let [x, y, width, height] = values[..] else {
+        return Err("expected four values: x,y,w,h".to_string());
+    };
+
+    if width == 0 || height == 0 {
+        return Err("width and height must be greater than zero".to_string());
+    }
+    if x as u64 + width as u64 > image_width as u64 || y as u64 + height as u64 > image_height as u64 {
+        return Err(format!("region {width}x{height} at ({x}, {y}) exceeds image bounds {image_width}x{image_height}"));
+    }
+
+    Ok((x, y, width, height))
+}
+
+/// Parses auto preset thresholds in the form `low,high`
+///
+/// # Arguments
+/// * `thresholds` - Thresholds string as given on the command line
+///
+/// # Returns
+/// The thresholds, or a message describing why they are invalid
+fn parse_thresholds(thresholds: &str) -> Result<PresetThresholds, String> {
+    let values = thresholds
+        .split(',')
+        .map(|value| value.trim().parse::<u32>().map_err(|_| format!("'{value}' is not a non-negative integer")))
+        .collect::<Result<Vec<_>, _>>()?;
+    let [low_resolution, high_resolution] = values[..] else {
+        return Err("expected two values: low,high".to_string());
+    };
+
+    if low_resolution > high_resolution {
+        return Err(format!("low threshold {low_resolution} exceeds high threshold {high_resolution}"));
+    }
+
+    Ok(PresetThresholds { low_resolution, high_resolution })
+}
+
+/// Converts a premultiplied-alpha image to straight alpha in place
+///
+/// Fully transparent pixels carry no color information and are left as zero.
+///
+/// # Arguments
+/// * `image` - Image with color components premultiplied by alpha
+fn unpremultiply_alpha(image: &mut image::Rgba32FImage) {
+    for pixel in image.pixels_mut() {
+        let alpha = pixel[3];
+        for i in 0..3 {
+            pixel[i] = if alpha > 0.0 { (pixel[i] / alpha).min(1.0) } else { 0.0 };
+        }
+    }
+}
+
+/// Converts a straight-alpha image to premultiplied alpha in place
+///
+/// # Arguments
+/// * `image` - Image with straight color components
+/// * `alpha` - Image of the same dimensions whose alpha channel is applied to `image`
+fn premultiply_alpha(image: &mut image::Rgba32FImage, alpha: &image::Rgba32FImage) {
+    for (pixel, alpha_pixel) in image.pixels_mut().zip(alpha.pixels()) {
+        let alpha = alpha_pixel[3];
+        for i in 0..3 {
+            pixel[i] *= alpha;
+        }
+        pixel[3] = alpha;
+    }
+}
+
+/// Composites an image over an opaque background color in place
+///
+/// # Arguments
+/// * `image` - Image to flatten, whose alpha becomes 1
+/// * `background` - Background color in the same (sRGB-encoded) space as the image
+/// * `alpha_mode` - Whether the color components of `image` are premultiplied by alpha
+fn flatten_alpha(image: &mut image::Rgba32FImage, background: [f32; 3], alpha_mode: AlphaMode) {
+    for pixel in image.pixels_mut() {
+        let alpha = pixel[3].clamp(0.0, 1.0);
+        for i in 0..3 {
+            let color = if alpha_mode == AlphaMode::Premultiplied { pixel[i] } else { pixel[i] * alpha };
+            pixel[i] = color + background[i] * (1.0 - alpha);
+        }
+        pixel[3] = 1.0;
+    }
+}
+
+/// Parses a color in the form `#RRGGBB` (the `#` is optional)
+///
+/// # Arguments
+/// * `color` - Color string as given on the command line
+///
+/// # Returns
+/// The color components in [0, 1], or a message describing why the color is invalid
+fn parse_hex_color(color: &str) -> Result<[f32; 3], String> {
+    let hex = color.strip_prefix('#').unwrap_or(color);
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("expected six hexadecimal digits: #RRGGBB".to_string());
+    }
+
+    let component = |index: usize| u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).unwrap() as f32 / 255.0;
+    Ok([component(0), component(1), component(2)])
+}
+
+/// Returns whether the image format chosen by a path's extension can store alpha
+///
+/// Unknown extensions are assumed to support alpha, so saving reports the real error.
+fn output_format_supports_alpha(path: &Path) -> bool {
+    !matches!(image::ImageFormat::from_path(path), Ok(image::ImageFormat::Jpeg | image::ImageFormat::Pnm))
+}
+
 /// Loads an image into a wgpu texture for GPU processing
 ///
 /// Converts the input image to RGBA32F format and uploads it to GPU memory
@@ -244,122 +1106,77 @@ fn load_image_to_texture(device: &wgpu::Device, queue: &wgpu::Queue, image: &Dyn
     Ok(texture)
 }
 
-/// Saves a wgpu texture back to an image format
-///
-/// Downloads texture data from GPU memory and converts it back to a standard
-/// image format. Handles different texture formats and expands them to RGBA
-/// as needed for compatibility with image saving libraries.
-///
-/// # Arguments
-/// * `device` - wgpu device for creating GPU resources
-/// * `queue` - Command queue for data transfer operations
-/// * `texture` - GPU texture containing the processed image data
-///
-/// # Returns
-/// An RGBA32F image ready for format conversion and saving
-///
-/// # Errors
-/// Returns an error if:
-/// - Texture format is unsupported
-/// - GPU memory mapping fails
-/// - Image reconstruction fails
-fn save_texture_to_image(device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) -> Result<image::Rgba32FImage, Box<dyn std::error::Error>> {
-    let wgpu::Extent3d {
-        width,
-        height,
-        depth_or_array_layers: _,
-    } = texture.size();
-    let format = texture.format();
-
-    // Determine format characteristics for proper data interpretation
-    let (components, bytes_per_component) = match format {
-        wgpu::TextureFormat::R32Float => (1, 4),
-        wgpu::TextureFormat::Rg32Float => (2, 4),
-        wgpu::TextureFormat::Rgba32Float => (4, 4),
-        _ => return Err(format!("Unsupported texture format for saving: {format:?}").into()),
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let buffer_size = (width * height * components * bytes_per_component) as u64;
-    let bytes_per_row = width * components * bytes_per_component;
+    /// Creates a one-row image from RGBA pixels
+    fn create_image(pixels: &[[f32; 4]]) -> image::Rgba32FImage {
+        image::Rgba32FImage::from_fn(pixels.len() as u32, 1, |x, _| image::Rgba(pixels[x as usize]))
+    }
 
-    // Create staging buffer for GPU-to-CPU data transfer
-    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
-        label: Some("Output Buffer"),
-        size: buffer_size,
-        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
-        mapped_at_creation: false,
-    });
+    /// Tests that premultiplying and then unpremultiplying restores straight colors
+    #[test]
+    fn test_premultiply_round_trip() {
+        let straight = create_image(&[[0.25, 0.5, 1.0, 1.0], [0.25, 0.5, 1.0, 0.5], [0.75, 0.125, 0.0, 0.25]]);
 
-    // Copy texture data to staging buffer
-    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Copy Encoder") });
+        let mut image = straight.clone();
+        premultiply_alpha(&mut image, &straight);
+        assert_eq!(image.get_pixel(0, 0).0, [0.25, 0.5, 1.0, 1.0]);
+        assert_eq!(image.get_pixel(1, 0).0, [0.125, 0.25, 0.5, 0.5]);
+        assert_eq!(image.get_pixel(2, 0).0, [0.1875, 0.03125, 0.0, 0.25]);
 
-    encoder.copy_texture_to_buffer(
-        wgpu::TexelCopyTextureInfo {
-            texture,
-            mip_level: 0,
-            origin: wgpu::Origin3d::ZERO,
-            aspect: wgpu::TextureAspect::All,
-        },
-        wgpu::TexelCopyBufferInfo {
-            buffer: &buffer,
-            layout: wgpu::TexelCopyBufferLayout {
-                offset: 0,
-                bytes_per_row: Some(bytes_per_row),
-                rows_per_image: Some(height),
-            },
-        },
-        wgpu::Extent3d {
-            width,
-            height,
-            depth_or_array_layers: 1,
-        },
-    );
+        unpremultiply_alpha(&mut image);
+        assert_eq!(image, straight);
+    }
 
-    queue.submit(std::iter::once(encoder.finish()));
+    /// Tests that fully transparent pixels premultiply to black and stay black when unpremultiplied
+    #[test]
+    fn test_premultiply_round_trip_transparent() {
+        let straight = create_image(&[[0.25, 0.5, 1.0, 0.0]]);
 
-    // Map buffer for CPU access and wait for completion
-    let buffer_slice = buffer.slice(..);
-    let (sender, receiver) = futures_intrusive::channel::shared::oneshot_channel();
-    buffer_slice.map_async(wgpu::MapMode::Read, move |v| sender.send(v).unwrap());
+        let mut image = straight.clone();
+        premultiply_alpha(&mut image, &straight);
+        assert_eq!(image.get_pixel(0, 0).0, [0.0, 0.0, 0.0, 0.0]);
 
-    device.poll(wgpu::PollType::Wait)?;
+        unpremultiply_alpha(&mut image);
+        assert_eq!(image.get_pixel(0, 0).0, [0.0, 0.0, 0.0, 0.0]);
+    }
 
-    pollster::block_on(receiver.receive()).ok_or("Failed to map buffer for reading")??;
-
-    // Convert raw bytes back to float data
-    let data = buffer_slice.get_mapped_range();
-    let float_data: &[f32] = bytemuck::cast_slice(&data);
-
-    // Convert data to RGBA format based on source format
-    let image = match components {
-        1 => {
-            // R32Float - expand single component to grayscale RGBA
-            let mut rgba_data = Vec::with_capacity((width * height * 4) as usize);
-            for &r in float_data {
-                rgba_data.push(r.abs());
-                rgba_data.push(r.abs());
-                rgba_data.push(r.abs());
-                rgba_data.push(1.0);
-            }
-            image::Rgba32FImage::from_raw(width, height, rgba_data).ok_or("Failed to create RGBA32F image from data")?
-        }
-        2 => {
-            // RG32Float - expand two components to RGBA with zero blue and full alpha
-            let mut rgba_data = Vec::with_capacity((width * height * 4) as usize);
-            for chunk in float_data.chunks(2) {
-                rgba_data.push(chunk[0].abs());
-                rgba_data.push(chunk[1].abs());
-                rgba_data.push(0.0);
-                rgba_data.push(1.0);
-            }
-            image::Rgba32FImage::from_raw(width, height, rgba_data).ok_or("Failed to create RGBA32F image from data")?
-        }
-        4 => {
-            // RGBA32Float - direct conversion, already in correct format
-            image::Rgba32FImage::from_raw(width, height, float_data.to_vec()).ok_or("Failed to create RGBA32F image from data")?
+    /// Tests that premultiplying takes alpha from the alpha image and that colors above alpha clamp to 1
+    #[test]
+    fn test_premultiply_alpha_source_and_clamp() {
+        let mut image = create_image(&[[1.0, 0.5, 0.25, 1.0]]);
+        premultiply_alpha(&mut image, &create_image(&[[0.0, 0.0, 0.0, 0.5]]));
+        assert_eq!(image.get_pixel(0, 0).0, [0.5, 0.25, 0.125, 0.5]);
+
+        // Upscaling can overshoot, leaving premultiplied colors above alpha
+        let mut image = create_image(&[[0.75, 0.5, 0.25, 0.5]]);
+        unpremultiply_alpha(&mut image);
+        assert_eq!(image.get_pixel(0, 0).0, [1.0, 1.0, 0.5, 0.5]);
+    }
+
+    /// Tests that a valid ROI is parsed, including one touching the image edges
+    #[test]
+    fn test_parse_roi() {
+        assert_eq!(parse_roi("10,20,30,40", 100, 100), Ok((10, 20, 30, 40)));
+        assert_eq!(parse_roi(" 0, 0, 100 ,50 ", 100, 50), Ok((0, 0, 100, 50)));
+        assert_eq!(parse_roi("99,49,1,1", 100, 50), Ok((99, 49, 1, 1)));
+    }
+
+    /// Tests that malformed ROI strings are rejected
+    #[test]
+    fn test_parse_roi_malformed() {
+        for roi in ["", "1,2,3", "1,2,3,4,5", "1,2,a,4", "-1,2,3,4", "1.5,2,3,4", "1;2;3;4"] {
+            assert!(parse_roi(roi, 100, 100).is_err(), "{roi:?} was accepted");
         }
-        _ => return Err(format!("Unsupported number of components: {components}").into()),
-    };
+    }
 
-    Ok(image)
+    /// Tests that zero-size ROIs and ROIs extending outside the image are rejected
+    #[test]
+    fn test_parse_roi_out_of_bounds() {
+        for roi in ["0,0,0,10", "0,0,10,0", "91,0,10,10", "0,51,10,50", "100,0,1,1", "4294967295,0,1,1"] {
+            assert!(parse_roi(roi, 100, 100).is_err(), "{roi:?} was accepted");
+        }
+    }
 }
@@ -0,0 +1,144 @@
+//! Progress manifests for resumable batch runs
+//!
+//! While the jobs of a list file are processed, every completed job is recorded in a
+//! JSON manifest next to the list, together with hashes of its input and output and a
+//! description of the settings it ran with. A rerun with `--resume` skips the jobs
+//! whose record still matches: the settings are unchanged, the output exists, and
+//! neither file has changed since. Any mismatch reprocesses the job.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Record of one completed job
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompletedJob {
+    /// Input path as given in the list file
+    pub input: PathBuf,
+    /// Output path as given in the list file
+    pub output: PathBuf,
+    /// FNV-1a hash of the input file contents, in hexadecimal
+    pub input_hash: String,
+    /// FNV-1a hash of the output file contents, in hexadecimal
+    pub output_hash: String,
+}
+
+/// Completed jobs of a batch run and the settings they ran with
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Description of the processing settings, compared as a whole on resume
+    pub settings: String,
+    /// Jobs completed so far, in completion order
+    pub completed: Vec<CompletedJob>,
+}
+
+impl Manifest {
+    /// Creates an empty manifest for a run with the given settings
+    ///
+    /// # Arguments
+    /// * `settings` - Description of the processing settings
+    pub fn new(settings: String) -> Self {
+        Self { settings, completed: Vec::new() }
+    }
+
+    /// Returns the path of the manifest belonging to a list file
+    ///
+    /// # Arguments
+    /// * `list` - Path of the job list file
+    ///
+    /// # Returns
+    /// The list path with `.progress.json` appended
+    pub fn path_for(list: &Path) -> PathBuf {
+        let mut path = list.as_os_str().to_owned();
+        path.push(".progress.json");
+        PathBuf::from(path)
+    }
+
+    /// Loads the manifest of a previous run to resume
+    ///
+    /// A missing manifest, or one recorded with different settings, yields an empty
+    /// manifest so that every job runs again.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the manifest file
+    /// * `settings` - Description of the processing settings of this run
+    ///
+    /// # Returns
+    /// The manifest to continue from, or an error if the file exists but cannot be read or parsed
+    pub fn resume(path: &Path, settings: String) -> Result<Self, Box<dyn std::error::Error>> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                println!("No progress manifest at {}, starting from the first job", path.display());
+                return Ok(Self::new(settings));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let manifest: Self = serde_json::from_str(&text)?;
+        if manifest.settings != settings {
+            println!("Settings differ from the progress manifest at {}, reprocessing every job", path.display());
+            return Ok(Self::new(settings));
+        }
+        Ok(manifest)
+    }
+
+    /// Returns whether a job is recorded as completed and its files are unchanged
+    ///
+    /// # Arguments
+    /// * `input` - Input path of the job
+    /// * `output` - Output path of the job
+    pub fn is_completed(&self, input: &Path, output: &Path) -> bool {
+        let Some(job) = self.completed.iter().find(|job| job.input == input && job.output == output) else {
+            return false;
+        };
+        match (hash_file(input), hash_file(output)) {
+            (Ok(input_hash), Ok(output_hash)) => input_hash == job.input_hash && output_hash == job.output_hash,
+            _ => false,
+        }
+    }
+
+    /// Records a completed job, replacing an earlier record of the same paths
+    ///
+    /// # Arguments
+    /// * `input` - Input path of the job
+    /// * `output` - Output path of the job, which must have been written
+    pub fn record(&mut self, input: &Path, output: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let job = CompletedJob {
+            input: input.to_path_buf(),
+            output: output.to_path_buf(),
+            input_hash: hash_file(input)?,
+            output_hash: hash_file(output)?,
+        };
+        self.completed.retain(|completed| completed.input != job.input || completed.output != job.output);
+        self.completed.push(job);
+        Ok(())
+    }
+
+    /// Writes the manifest
+    ///
+    /// The manifest is written to a temporary file that then replaces the previous one,
+    /// so an interrupted run never leaves a truncated manifest behind.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the manifest file
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        std::fs::write(&temporary, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&temporary, path)?;
+        Ok(())
+    }
+}
+
+/// Hashes the contents of a file with 64-bit FNV-1a
+///
+/// # Arguments
+/// * `path` - Path of the file to hash
+///
+/// # Returns
+/// The hash as 16 hexadecimal digits
+fn hash_file(path: &Path) -> std::io::Result<String> {
+    let hash = std::fs::read(path)?
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3));
+    Ok(format!("{hash:016x}"))
+}
@@ -0,0 +1,81 @@
+//! Mip chain generation
+//!
+//! This module downsamples an upscaled image into a full mip chain on the GPU, so the
+//! result can be exported as a GPU-ready texture with mipmaps.
+
+/// Workgroup size of the downsampling pass in each dimension
+const WORKGROUP_SIZE: u32 = 8;
+
+/// Generates every mip level of a texture down to 1x1
+///
+/// Each level halves the previous one (flooring, but never below one pixel) with a
+/// 2x2 box filter in linear light. Levels are separate textures so they can be read
+/// back individually.
+///
+/// # Arguments
+/// * `device` - The wgpu device
+/// * `queue` - The wgpu command queue
+/// * `texture` - RGBA32Float texture holding the base level (must have TEXTURE_BINDING usage)
+///
+/// # Returns
+/// The base texture followed by each smaller level
+pub fn generate_mip_chain(device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) -> Vec<wgpu::Texture> {
+    let shader_module = device.create_shader_module(wgpu::include_wgsl!("mipmap.wgsl"));
+    let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Mipmap"),
+        layout: None,
+        module: &shader_module,
+        entry_point: Some("main"),
+        compilation_options: Default::default(),
+        cache: None,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Mipmap") });
+    let mut levels = vec![texture.clone()];
+    while let Some(previous) = levels.last().filter(|level| level.width() > 1 || level.height() > 1) {
+        let level = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(&format!("Mip Level {}", levels.len())),
+            size: wgpu::Extent3d {
+                width: (previous.width() / 2).max(1),
+                height: (previous.height() / 2).max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba32Float,
+            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Mipmap"),
+            layout: &compute_pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&previous.create_view(&Default::default())),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&level.create_view(&Default::default())),
+                },
+            ],
+        });
+
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Mipmap"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&compute_pipeline);
+            compute_pass.set_bind_group(0, &bind_group, &[]);
+            compute_pass.dispatch_workgroups(level.width().div_ceil(WORKGROUP_SIZE), level.height().div_ceil(WORKGROUP_SIZE), 1);
+        }
+
+        levels.push(level);
+    }
+    queue.submit(std::iter::once(encoder.finish()));
+
+    levels
+}
@@ -0,0 +1,39 @@
+// Mip level downsampling compute shader
+//
+// Averages each 2x2 block of the previous level in linear light. Colors are stored
+// sRGB-encoded, so they are decoded before averaging and encoded again afterwards;
+// alpha is averaged as is.
+
+@group(0) @binding(0) var input_texture: texture_2d<f32>;
+@group(0) @binding(1) var output_texture: texture_storage_2d<rgba32float, write>;
+
+fn srgb_to_linear(c: vec3f) -> vec3f {
+    let c_clamped = clamp(c, vec3f(0.0), vec3f(1.0));
+    return select(pow((c_clamped + 0.055) / 1.055, vec3f(2.4)), c_clamped / 12.92, c_clamped <= vec3f(0.04045));
+}
+
+fn linear_to_srgb(c: vec3f) -> vec3f {
+    return select(1.055 * pow(c, vec3f(1.0 / 2.4)) - 0.055, c * 12.92, c <= vec3f(0.0031308));
+}
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) global_id: vec3u) {
+    let dims = textureDimensions(output_texture);
+    if global_id.x >= dims.x || global_id.y >= dims.y {
+        return;
+    }
+
+    // Clamp so a level with an odd size still reads its last row and column
+    let input_max = vec2i(textureDimensions(input_texture)) - 1;
+    let base = vec2i(global_id.xy) * 2;
+
+    var color = vec3f(0.0);
+    var alpha = 0.0;
+    for (var i = 0; i < 4; i++) {
+        let texel = textureLoad(input_texture, min(base + vec2i(i & 1, i >> 1), input_max), 0);
+        color += srgb_to_linear(texel.rgb);
+        alpha += texel.a;
+    }
+
+    textureStore(output_texture, vec2i(global_id.xy), vec4f(linear_to_srgb(color / 4.0), alpha / 4.0));
+}
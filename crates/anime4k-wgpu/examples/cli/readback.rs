@@ -0,0 +1,212 @@
+//! Texture readback into images
+//!
+//! This module downloads the 32-bit float textures the pipelines produce and expands
+//! them to RGBA images. Texture-to-buffer copies pad each row to
+//! `COPY_BYTES_PER_ROW_ALIGNMENT` bytes, which is stripped again after the download.
+
+/// Saves a wgpu texture back to an image format
+///
+/// Downloads texture data from GPU memory and converts it back to a standard
+/// image format. Handles different texture formats and expands them to RGBA
+/// as needed for compatibility with image saving libraries.
+///
+/// # Arguments
+/// * `device` - wgpu device for creating GPU resources
+/// * `queue` - Command queue for data transfer operations
+/// * `texture` - GPU texture containing the processed image data
+///
+/// # Returns
+/// An RGBA32F image ready for format conversion and saving
+///
+/// # Errors
+/// Returns an error if:
+/// - Texture format is unsupported
+/// - GPU memory mapping fails
+/// - Image reconstruction fails
+pub fn save_texture_to_image(device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) -> Result<image::Rgba32FImage, Box<dyn std::error::Error>> {
+    let wgpu::Extent3d {
+        width,
+        height,
+        depth_or_array_layers: _,
+    } = texture.size();
+    let format = texture.format();
+
+    // Determine format characteristics for proper data interpretation
+    let (components, bytes_per_component) = match format {
+        wgpu::TextureFormat::R32Float => (1, 4),
+        wgpu::TextureFormat::Rg32Float => (2, 4),
+        wgpu::TextureFormat::Rgba32Float => (4, 4),
+        _ => return Err(format!("Unsupported texture format for saving: {format:?}").into()),
+    };
+
+    // Rows of a texture-to-buffer copy must be aligned, so the padding is stripped after readback
+    let bytes_per_row = width * components * bytes_per_component;
+    let padded_bytes_per_row = bytes_per_row.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+    let buffer_size = padded_bytes_per_row as u64 * height as u64;
+
+    // Create staging buffer for GPU-to-CPU data transfer
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Output Buffer"),
+        size: buffer_size,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    // Copy texture data to staging buffer
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Copy Encoder") });
+
+    encoder.copy_texture_to_buffer(
+        wgpu::TexelCopyTextureInfo {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: Some(height),
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+
+    queue.submit(std::iter::once(encoder.finish()));
+
+    // Map buffer for CPU access and wait for completion
+    let buffer_slice = buffer.slice(..);
+    let (sender, receiver) = futures_intrusive::channel::shared::oneshot_channel();
+    buffer_slice.map_async(wgpu::MapMode::Read, move |v| sender.send(v).unwrap());
+
+    device.poll(wgpu::PollType::Wait)?;
+
+    pollster::block_on(receiver.receive()).ok_or("Failed to map buffer for reading")??;
+
+    // Convert raw bytes back to float data
+    let data = buffer_slice.get_mapped_range();
+    let float_data: Vec<f32> = data
+        .chunks(padded_bytes_per_row as usize)
+        .flat_map(|row| bytemuck::cast_slice::<u8, f32>(&row[..bytes_per_row as usize]).iter().copied())
+        .collect();
+
+    // Convert data to RGBA format based on source format
+    let image = match components {
+        1 => {
+            // R32Float - expand single component to grayscale RGBA
+            let mut rgba_data = Vec::with_capacity((width * height * 4) as usize);
+            for &r in &float_data {
+                rgba_data.push(r.abs());
+                rgba_data.push(r.abs());
+                rgba_data.push(r.abs());
+                rgba_data.push(1.0);
+            }
+            image::Rgba32FImage::from_raw(width, height, rgba_data).ok_or("Failed to create RGBA32F image from data")?
+        }
+        2 => {
+            // RG32Float - expand two components to RGBA with zero blue and full alpha
+            let mut rgba_data = Vec::with_capacity((width * height * 4) as usize);
+            for chunk in float_data.chunks(2) {
+                rgba_data.push(chunk[0].abs());
+                rgba_data.push(chunk[1].abs());
+                rgba_data.push(0.0);
+                rgba_data.push(1.0);
+            }
+            image::Rgba32FImage::from_raw(width, height, rgba_data).ok_or("Failed to create RGBA32F image from data")?
+        }
+        4 => {
+            // RGBA32Float - direct conversion, already in correct format
+            image::Rgba32FImage::from_raw(width, height, float_data).ok_or("Failed to create RGBA32F image from data")?
+        }
+        _ => return Err(format!("Unsupported number of components: {components}").into()),
+    };
+
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a device that supports the pipeline texture formats, if the system has one
+    fn create_device() -> Option<(wgpu::Device, wgpu::Queue)> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::PRIMARY,
+            ..Default::default()
+        });
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default())).ok()?;
+        pollster::block_on(anime4k_wgpu::PipelineExecutor::request_device(&adapter, wgpu::Features::empty())).ok()
+    }
+
+    /// Uploads `texels` to a new texture and reads it back as an image
+    fn round_trip(device: &wgpu::Device, queue: &wgpu::Queue, format: wgpu::TextureFormat, width: u32, height: u32, texels: &[f32]) -> image::Rgba32FImage {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            texture.as_image_copy(),
+            bytemuck::cast_slice(texels),
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(texels.len() as u32 / height * 4),
+                rows_per_image: Some(height),
+            },
+            texture.size(),
+        );
+        save_texture_to_image(device, queue, &texture).unwrap()
+    }
+
+    /// Tests an `Rgba32Float` texture whose rows (127 texels, 2032 bytes) need padding
+    #[test]
+    fn test_unaligned_rgba_rows() {
+        let Some((device, queue)) = create_device() else {
+            eprintln!("Skipping readback test: no GPU adapter with FLOAT32_FILTERABLE");
+            return;
+        };
+
+        let (width, height) = (127, 3);
+        // Every texel is unique, so a shifted row cannot go unnoticed
+        let texel = |x: u32, y: u32| [x as f32 / width as f32, y as f32 / height as f32, 0.25, 1.0];
+        let texels: Vec<f32> = (0..height).flat_map(|y| (0..width).flat_map(move |x| texel(x, y))).collect();
+
+        let image = round_trip(&device, &queue, wgpu::TextureFormat::Rgba32Float, width, height, &texels);
+        assert_eq!(image.dimensions(), (width, height));
+        for (x, y, pixel) in image.enumerate_pixels() {
+            assert_eq!(pixel.0, texel(x, y), "Texel ({x}, {y}) is misplaced");
+        }
+    }
+
+    /// Tests an `R32Float` texture whose rows (127 texels, 508 bytes) need padding
+    #[test]
+    fn test_unaligned_single_channel_rows() {
+        let Some((device, queue)) = create_device() else {
+            eprintln!("Skipping readback test: no GPU adapter with FLOAT32_FILTERABLE");
+            return;
+        };
+
+        let (width, height) = (127, 3);
+        let texels: Vec<f32> = (0..width * height).map(|index| index as f32 / (width * height) as f32).collect();
+
+        let image = round_trip(&device, &queue, wgpu::TextureFormat::R32Float, width, height, &texels);
+        for (x, y, pixel) in image.enumerate_pixels() {
+            let value = texels[(y * width + x) as usize];
+            assert_eq!(pixel.0, [value, value, value, 1.0], "Texel ({x}, {y}) is misplaced");
+        }
+    }
+}
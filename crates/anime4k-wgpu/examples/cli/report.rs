@@ -0,0 +1,89 @@
+//! Machine-readable performance reports
+//!
+//! This module collects the per-pass GPU timings of a run together with the device and
+//! configuration they were measured on, and writes them as JSON so that timings can be
+//! tracked across commits by CI tooling.
+
+use anime4k_wgpu::{
+    PassTiming,
+    presets::{Anime4KPerformancePreset, Anime4KPreset},
+};
+use serde::Serialize;
+use std::path::Path;
+
+/// GPU time of a single shader pass
+#[derive(Debug, Serialize)]
+pub struct PassReport {
+    /// Name of the shader pass
+    pub name: String,
+    /// GPU time in milliseconds
+    pub duration_ms: f64,
+}
+
+/// Timings of one upscale and the conditions they were measured under
+#[derive(Debug, Serialize)]
+pub struct PerformanceReport {
+    /// Name of the GPU adapter
+    pub device: String,
+    /// Graphics backend the adapter was used through
+    pub backend: String,
+    /// Anime4K preset name
+    pub preset: String,
+    /// Performance preset name
+    pub performance_preset: String,
+    /// Input width in pixels, after cropping to the region of interest
+    pub input_width: u32,
+    /// Input height in pixels, after cropping to the region of interest
+    pub input_height: u32,
+    /// Output width in pixels
+    pub output_width: u32,
+    /// Output height in pixels
+    pub output_height: u32,
+    /// Sum of the pass times in milliseconds
+    pub total_ms: f64,
+    /// Timing of every pass in execution order
+    pub passes: Vec<PassReport>,
+}
+
+impl PerformanceReport {
+    /// Creates a report from the pass timings of a run
+    ///
+    /// # Arguments
+    /// * `adapter_info` - Information on the adapter the timings were measured on
+    /// * `preset` - Anime4K preset that was run
+    /// * `performance_preset` - Performance preset that was run
+    /// * `input_size` - Input width and height in pixels
+    /// * `output_size` - Output width and height in pixels
+    /// * `timings` - Pass timings from `PipelineExecutor::profile`
+    pub fn new(adapter_info: &wgpu::AdapterInfo, preset: Anime4KPreset, performance_preset: Anime4KPerformancePreset, input_size: (u32, u32), output_size: (u32, u32), timings: &[PassTiming]) -> Self {
+        let passes: Vec<_> = timings
+            .iter()
+            .map(|timing| PassReport {
+                name: timing.name.to_string(),
+                duration_ms: timing.duration.as_secs_f64() * 1000.0,
+            })
+            .collect();
+
+        Self {
+            device: adapter_info.name.clone(),
+            backend: format!("{:?}", adapter_info.backend),
+            preset: preset.name().to_string(),
+            performance_preset: performance_preset.name().to_string(),
+            input_width: input_size.0,
+            input_height: input_size.1,
+            output_width: output_size.0,
+            output_height: output_size.1,
+            total_ms: passes.iter().map(|pass| pass.duration_ms).sum(),
+            passes,
+        }
+    }
+
+    /// Writes the report as pretty-printed JSON
+    ///
+    /// # Arguments
+    /// * `path` - Path of the JSON file to write
+    pub fn write(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
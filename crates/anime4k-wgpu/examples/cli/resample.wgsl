@@ -0,0 +1,106 @@
+// Arbitrary-size resampling compute shader
+//
+// Resamples the input to the output size with a filter whose footprint grows with the
+// reduction ratio, so downscaling by large factors averages every input texel instead
+// of skipping most of them. Texels outside the input are clamped to the edge.
+
+@group(0) @binding(0) var input_texture: texture_2d<f32>;
+@group(0) @binding(1) var output_texture: texture_storage_2d<rgba32float, write>;
+
+const PI: f32 = 3.14159265;
+
+fn srgb_to_linear(c: vec3f) -> vec3f {
+    let c_clamped = clamp(c, vec3f(0.0), vec3f(1.0));
+    return select(pow((c_clamped + 0.055) / 1.055, vec3f(2.4)), c_clamped / 12.92, c_clamped <= vec3f(0.04045));
+}
+
+fn linear_to_srgb(c: vec3f) -> vec3f {
+    return select(1.055 * pow(c, vec3f(1.0 / 2.4)) - 0.055, c * 12.92, c <= vec3f(0.0031308));
+}
+
+fn load_clamped(position: vec2i) -> vec4f {
+    return textureLoad(input_texture, clamp(position, vec2i(0), vec2i(textureDimensions(input_texture)) - 1), 0);
+}
+
+fn sinc(x: f32) -> f32 {
+    if abs(x) < 1e-5 {
+        return 1.0;
+    }
+    return sin(PI * x) / (PI * x);
+}
+
+fn lanczos3(x: f32) -> f32 {
+    if abs(x) >= 3.0 {
+        return 0.0;
+    }
+    return sinc(x) * sinc(x / 3.0);
+}
+
+// Output pixel center in input texel units, and input texels per output texel
+fn footprint(global_id: vec2u) -> array<vec2f, 2> {
+    let ratio = vec2f(textureDimensions(input_texture)) / vec2f(textureDimensions(output_texture));
+    return array<vec2f, 2>((vec2f(global_id) + 0.5) * ratio, ratio);
+}
+
+// Area average in linear light over the input region each output pixel covers,
+// weighting partially covered texels by their coverage
+@compute @workgroup_size(8, 8)
+fn box_filter(@builtin(global_invocation_id) global_id: vec3u) {
+    let dims = textureDimensions(output_texture);
+    if global_id.x >= dims.x || global_id.y >= dims.y {
+        return;
+    }
+
+    let region = footprint(global_id.xy);
+    // When enlarging, cover one input texel so the result interpolates between neighbors
+    let half_size = max(region[1], vec2f(1.0)) * 0.5;
+    let low = region[0] - half_size;
+    let high = region[0] + half_size;
+
+    var color = vec3f(0.0);
+    var alpha = 0.0;
+    var weight_sum = 0.0;
+    for (var y = i32(floor(low.y)); y < i32(ceil(high.y)); y++) {
+        let weight_y = min(high.y, f32(y + 1)) - max(low.y, f32(y));
+        for (var x = i32(floor(low.x)); x < i32(ceil(high.x)); x++) {
+            let weight = (min(high.x, f32(x + 1)) - max(low.x, f32(x))) * weight_y;
+            let texel = load_clamped(vec2i(x, y));
+            color += srgb_to_linear(texel.rgb) * weight;
+            alpha += texel.a * weight;
+            weight_sum += weight;
+        }
+    }
+
+    textureStore(output_texture, vec2i(global_id.xy), vec4f(linear_to_srgb(color / weight_sum), alpha / weight_sum));
+}
+
+// Lanczos-3 in the stored (sRGB-encoded) values, stretched by the reduction ratio when
+// downscaling; the negative lobes would turn into dark halos if applied in linear light
+@compute @workgroup_size(8, 8)
+fn lanczos(@builtin(global_invocation_id) global_id: vec3u) {
+    let dims = textureDimensions(output_texture);
+    if global_id.x >= dims.x || global_id.y >= dims.y {
+        return;
+    }
+
+    let region = footprint(global_id.xy);
+    let kernel_scale = max(region[1], vec2f(1.0));
+    let first = vec2i(floor(region[0] - 3.0 * kernel_scale));
+    let last = vec2i(ceil(region[0] + 3.0 * kernel_scale));
+
+    var sum = vec4f(0.0);
+    var weight_sum = 0.0;
+    for (var y = first.y; y <= last.y; y++) {
+        let weight_y = lanczos3((f32(y) + 0.5 - region[0].y) / kernel_scale.y);
+        if weight_y == 0.0 {
+            continue;
+        }
+        for (var x = first.x; x <= last.x; x++) {
+            let weight = lanczos3((f32(x) + 0.5 - region[0].x) / kernel_scale.x) * weight_y;
+            sum += load_clamped(vec2i(x, y)) * weight;
+            weight_sum += weight;
+        }
+    }
+
+    textureStore(output_texture, vec2i(global_id.xy), sum / weight_sum);
+}
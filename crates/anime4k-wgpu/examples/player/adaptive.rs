@@ -0,0 +1,165 @@
+//! Adaptive performance preset selection
+//!
+//! The GPU time of the Anime4K passes is sampled periodically with timestamp queries and
+//! compared with a budget derived from the video framerate. After several consecutive
+//! samples over budget the performance preset steps down (e.g. Extreme to Ultra), and
+//! after a longer run of samples well under budget it steps back up, never above the
+//! preset the user selected.
+//!
+//! The gap between the step-down and step-up thresholds and the differing sample counts
+//! keep the preset from flipping on every sample. Each time a preset turns out to be too
+//! heavy again shortly after stepping up to it, the number of samples required before the
+//! next step up doubles, so a preset that only barely fits is not retried every few seconds.
+
+use anime4k_wgpu::presets::Anime4KPerformancePreset;
+use std::time::{Duration, Instant};
+
+/// Interval between GPU time samples
+///
+/// Each sample runs the Anime4K passes one extra time and waits for the GPU, so sampling
+/// every frame would halve the throughput it is trying to measure.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Fraction of the frame interval the Anime4K passes may take
+///
+/// The remainder is left for YUV conversion, presentation, and decoding on the same GPU.
+const BUDGET_FRACTION: f64 = 0.75;
+
+/// Fraction of the budget under which a sample counts as headroom for a heavier preset
+///
+/// Heavier presets typically take well over 1.5x the time of the next lighter one, so
+/// stepping up with less headroom would likely step straight back down.
+const HEADROOM_FRACTION: f64 = 0.5;
+
+/// Consecutive samples over budget before stepping down
+const STEP_DOWN_SAMPLES: u32 = 3;
+
+/// Consecutive samples with headroom before stepping up, before any backoff
+const STEP_UP_SAMPLES: u32 = 8;
+
+/// Largest number of consecutive samples with headroom required before stepping up
+const MAX_STEP_UP_SAMPLES: u32 = 64;
+
+/// Time after stepping up within which stepping down again doubles the step-up requirement
+const OSCILLATION_WINDOW: Duration = Duration::from_secs(10);
+
+/// Steps the performance preset down or up based on measured GPU time
+#[derive(Debug, Clone)]
+pub struct AdaptiveQuality {
+    /// GPU time the Anime4K passes may take per frame
+    budget: Duration,
+    /// Heaviest preset the controller may step up to
+    ceiling: Anime4KPerformancePreset,
+    /// Consecutive samples over budget
+    over_budget: u32,
+    /// Consecutive samples with headroom
+    with_headroom: u32,
+    /// Consecutive samples with headroom currently required before stepping up
+    step_up_samples: u32,
+    /// When the last sample was taken, or None if none was taken since the last change
+    last_sample: Option<Instant>,
+    /// When the controller last stepped up, if it did
+    last_step_up: Option<Instant>,
+}
+
+impl AdaptiveQuality {
+    /// Creates a controller for a video framerate
+    ///
+    /// # Arguments
+    /// * `framerate` - Video framerate in frames per second, which sets the time budget
+    /// * `ceiling` - Heaviest preset to use, normally the one playback starts with
+    pub fn new(framerate: u32, ceiling: Anime4KPerformancePreset) -> Self {
+        Self {
+            budget: Duration::from_secs_f64(BUDGET_FRACTION / framerate.max(1) as f64),
+            ceiling,
+            over_budget: 0,
+            with_headroom: 0,
+            step_up_samples: STEP_UP_SAMPLES,
+            last_sample: None,
+            last_step_up: None,
+        }
+    }
+
+    /// Returns the GPU time budget for the Anime4K passes of one frame
+    pub fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    /// Sets the heaviest preset to use after the user selected one, and starts over
+    ///
+    /// # Arguments
+    /// * `ceiling` - The preset the user selected
+    pub fn set_ceiling(&mut self, ceiling: Anime4KPerformancePreset) {
+        self.ceiling = ceiling;
+        self.step_up_samples = STEP_UP_SAMPLES;
+        self.last_step_up = None;
+        self.restart();
+    }
+
+    /// Forgets the samples taken so far, e.g. after the pipeline was rebuilt
+    pub fn restart(&mut self) {
+        self.over_budget = 0;
+        self.with_headroom = 0;
+        self.last_sample = None;
+    }
+
+    /// Returns whether the next GPU time sample is due
+    ///
+    /// The first sample after a change is delayed by a full interval, so the measurement
+    /// does not include the one-time cost of the new pipeline.
+    pub fn sample_due(&mut self) -> bool {
+        let now = Instant::now();
+        match self.last_sample {
+            Some(last_sample) if now - last_sample < SAMPLE_INTERVAL => false,
+            Some(_) => {
+                self.last_sample = Some(now);
+                true
+            }
+            None => {
+                self.last_sample = Some(now);
+                false
+            }
+        }
+    }
+
+    /// Records a GPU time sample and decides whether to change the preset
+    ///
+    /// # Arguments
+    /// * `gpu_time` - Measured GPU time of the Anime4K passes for one frame
+    /// * `current` - The performance preset the sample was measured with
+    ///
+    /// # Returns
+    /// The preset to switch to, or None to keep the current one
+    pub fn observe(&mut self, gpu_time: Duration, current: Anime4KPerformancePreset) -> Option<Anime4KPerformancePreset> {
+        if gpu_time > self.budget {
+            self.over_budget += 1;
+            self.with_headroom = 0;
+        } else if gpu_time.as_secs_f64() < self.budget.as_secs_f64() * HEADROOM_FRACTION {
+            self.over_budget = 0;
+            self.with_headroom += 1;
+        } else {
+            self.over_budget = 0;
+            self.with_headroom = 0;
+        }
+
+        if self.over_budget >= STEP_DOWN_SAMPLES {
+            let lighter = current.lighter()?;
+            if self.last_step_up.is_some_and(|last_step_up| last_step_up.elapsed() < OSCILLATION_WINDOW) {
+                self.step_up_samples = (self.step_up_samples * 2).min(MAX_STEP_UP_SAMPLES);
+            }
+            self.restart();
+            return Some(lighter);
+        }
+
+        // The ceiling is reachable by stepping up only if it is heavier than the current preset
+        let below_ceiling = std::iter::successors(current.heavier(), Anime4KPerformancePreset::heavier).any(|preset| preset == self.ceiling);
+        if self.with_headroom >= self.step_up_samples && below_ceiling {
+            let heavier = current.heavier()?;
+            self.last_step_up = Some(Instant::now());
+            self.restart();
+            return Some(heavier);
+        }
+
+        None
+    }
+}
@@ -3,12 +3,22 @@
 //! This module contains the main application structure that handles window events,
 //! keyboard input, and coordinates the overall playback experience.
 
-use super::player::PlayerContext;
+use super::{
+    adaptive::AdaptiveQuality,
+    color::ColorConfig,
+    interlace::{DeinterlaceMethod, FieldOrder},
+    pacing::FramePacing,
+    player::{DisplayFilter, PlayerContext, Stage},
+    record::Recorder,
+};
 use anime4k_wgpu::presets::{Anime4KPerformancePreset, Anime4KPreset};
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
 use winit::{
     application::ApplicationHandler,
-    event::{ElementState, KeyEvent, WindowEvent},
+    event::{ElementState, KeyEvent, MouseButton, WindowEvent},
     event_loop::ActiveEventLoop,
     keyboard::{KeyCode, ModifiersState, PhysicalKey},
     window::WindowId,
@@ -22,8 +32,36 @@ pub struct VideoPlayerApp {
     framerate: u32,
     /// Whether the video starts in paused state
     start_paused: bool,
+    /// Anime4K preset to start with, or None to start with Anime4K disabled
+    start_preset: Option<Anime4KPreset>,
+    /// Anime4K performance preset to start with
+    start_performance_preset: Anime4KPerformancePreset,
+    /// Number of decoded frames to buffer ahead of the renderer
+    frame_buffer_length: usize,
+    /// Whether to submit compute stages separately from the present pass
+    split_submit: bool,
+    /// Requested surface present mode, or None for the surface's default
+    present_mode: Option<wgpu::PresentMode>,
+    /// Time range (start, end) to loop over, or None to play through once
+    loop_range: Option<(Duration, Duration)>,
+    /// YUV-to-RGB conversion matrix and sample range
+    color_config: ColorConfig,
+    /// How frame presentation is aligned with display refreshes
+    frame_pacing: FramePacing,
+    /// Filter resampling the final image to the window
+    display_filter: DisplayFilter,
+    /// Encoder for the displayed frames, handed to the player context when it is created
+    recorder: Option<Recorder>,
+    /// Controller adjusting the performance preset to the GPU load, handed to the player context when it is created
+    adaptive_quality: Option<AdaptiveQuality>,
+    /// Method and field order to deinterlace with, or None to show frames as decoded
+    deinterlace: Option<(DeinterlaceMethod, FieldOrder)>,
     /// Keyboard modifiers state
     modifiers: ModifiersState,
+    /// Last known cursor position in window pixels
+    cursor_position: (f64, f64),
+    /// Window position where the current comparison rectangle drag started, if dragging
+    drag_start: Option<(f64, f64)>,
     /// The application context containing window, playback state, and renderer
     context: Option<PlayerContext>,
 }
@@ -35,15 +73,58 @@ impl VideoPlayerApp {
     /// * `filename` - Path to the video file to play
     /// * `framerate` - Video framerate in frames per second
     /// * `start_paused` - Whether the video should start in paused state
+    /// * `start_preset` - Anime4K preset to start with, or None to start with Anime4K disabled
+    /// * `start_performance_preset` - Anime4K performance preset to start with
+    /// * `frame_buffer_length` - Number of decoded frames to buffer ahead of the renderer
+    /// * `split_submit` - Whether to submit compute stages separately from the present pass
+    /// * `present_mode` - Requested surface present mode, or None for the surface's default
+    /// * `loop_range` - Time range (start, end) to loop over, or None to play through once
+    /// * `color_config` - YUV-to-RGB conversion matrix and sample range
+    /// * `frame_pacing` - How frame presentation is aligned with display refreshes
+    /// * `display_filter` - Filter resampling the final image to the window
+    /// * `recorder` - Encoder to record the displayed frames with, or None to only play
+    /// * `adaptive_quality` - Controller adjusting the performance preset to the GPU load, or None to keep it fixed
+    /// * `deinterlace` - Method and field order to deinterlace with, or None to show frames as decoded
     ///
     /// # Returns
     /// A new `VideoPlayerApp` instance ready to be run in an event loop
-    pub fn new(filename: &Path, framerate: u32, start_paused: bool) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        filename: &Path,
+        framerate: u32,
+        start_paused: bool,
+        start_preset: Option<Anime4KPreset>,
+        start_performance_preset: Anime4KPerformancePreset,
+        frame_buffer_length: usize,
+        split_submit: bool,
+        present_mode: Option<wgpu::PresentMode>,
+        loop_range: Option<(Duration, Duration)>,
+        color_config: ColorConfig,
+        frame_pacing: FramePacing,
+        display_filter: DisplayFilter,
+        recorder: Option<Recorder>,
+        adaptive_quality: Option<AdaptiveQuality>,
+        deinterlace: Option<(DeinterlaceMethod, FieldOrder)>,
+    ) -> Self {
         Self {
             filename: filename.to_path_buf(),
             framerate,
             start_paused,
+            start_preset,
+            start_performance_preset,
+            frame_buffer_length,
+            split_submit,
+            present_mode,
+            loop_range,
+            color_config,
+            frame_pacing,
+            display_filter,
+            recorder,
+            adaptive_quality,
+            deinterlace,
             modifiers: ModifiersState::default(),
+            cursor_position: (0.0, 0.0),
+            drag_start: None,
             context: None,
         }
     }
@@ -55,20 +136,46 @@ impl ApplicationHandler for VideoPlayerApp {
     /// This is called when the application becomes active and creates the window,
     /// initializes video decoding, and displays keyboard shortcuts to the user.
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        let file = std::fs::File::open(&self.filename).unwrap_or_else(|_| panic!("Failed to open video file: {}", self.filename.display()));
-        self.context = Some(PlayerContext::new(event_loop, file, self.framerate, self.start_paused));
+        let mut context = PlayerContext::new(
+            event_loop,
+            &self.filename,
+            self.framerate,
+            self.start_paused,
+            self.start_preset,
+            self.start_performance_preset,
+            self.frame_buffer_length,
+            self.split_submit,
+            self.present_mode,
+            &self.color_config,
+            self.frame_pacing,
+            self.display_filter,
+            self.recorder.take(),
+            self.adaptive_quality.take(),
+            self.deinterlace,
+        );
+        if let Some((start, end)) = self.loop_range {
+            context.set_loop(start, end);
+        }
+        self.context = Some(context);
 
         println!();
         println!("Keyboard shortcuts:");
         println!("  - Esc: Quit");
         println!("  - Space: Pause/Resume video playback");
+        println!("  - F: Freeze/Unfreeze the displayed frame while playback continues");
+        println!("  - F12: Save snapshots of each rendering stage");
+        println!("  - P: Toggle passthrough comparison (bilinear at Anime4K output size while disabled)");
+        println!("  - I: Toggle info overlay (preset, resolution, FPS)");
+        println!("  - Mouse drag: Apply Anime4K only inside the dragged rectangle (click to clear)");
         println!("  - Ctrl+0: Disable Anime4K");
-        println!("  - Ctrl+1-6: Set Anime4K preset (A, B, C, AA, BB, CA)");
+        println!("  - Ctrl+1-7: Set Anime4K preset (A, B, C, AA, BB, CA, Nearest)");
         println!("  - Shift+1-5: Set Anime4K performance preset (Light, Medium, High, Ultra, Extreme)");
         println!();
 
         println!("NOTE:");
-        println!("  - Anime4K is disabled by default. Use Ctrl+1-6 to enable it.");
+        if self.start_preset.is_none() {
+            println!("  - Anime4K is disabled by default. Use Ctrl+1-7 or --preset to enable it.");
+        }
         if self.start_paused {
             println!("  - Video starts in paused state. Press Space to resume playback.");
         }
@@ -80,8 +187,12 @@ impl ApplicationHandler for VideoPlayerApp {
     /// This method processes user input for playback control and Anime4K preset changes:
     /// - Escape: Quit application
     /// - Space: Toggle pause/resume
+    /// - F: Toggle freezing the displayed frame
+    /// - P: Toggle passthrough comparison
+    /// - I: Toggle info overlay
+    /// - Left mouse drag: Set the comparison rectangle (a click clears it)
     /// - Ctrl+0: Disable Anime4K processing
-    /// - Ctrl+1-6: Set Anime4K presets (A, B, C, AA, BB, CA)
+    /// - Ctrl+1-7: Set Anime4K presets (A, B, C, AA, BB, CA, Nearest)
     /// - Shift+1-5: Set performance presets (Light, Medium, High, Ultra, Extreme)
     fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
         match event {
@@ -121,7 +232,96 @@ impl ApplicationHandler for VideoPlayerApp {
                 }
             }
 
-            // Handle Anime4K preset selection (Ctrl+0-6)
+            // Handle freeze toggle (F)
+            WindowEvent::KeyboardInput {
+                event: KeyEvent {
+                    state: ElementState::Pressed,
+                    physical_key: PhysicalKey::Code(KeyCode::KeyF),
+                    ..
+                },
+                ..
+            } if self.modifiers.is_empty() => {
+                if let Some(context) = self.context.as_mut() {
+                    if context.is_frozen() {
+                        context.unfreeze();
+                    } else {
+                        context.freeze();
+                    }
+                }
+            }
+
+            // Handle stage snapshot capture (F12)
+            WindowEvent::KeyboardInput {
+                event: KeyEvent {
+                    state: ElementState::Pressed,
+                    physical_key: PhysicalKey::Code(KeyCode::F12),
+                    ..
+                },
+                ..
+            } => {
+                if let Some(context) = self.context.as_ref() {
+                    for stage in Stage::ALL {
+                        let filename = format!("snapshot_{}.png", stage.name());
+                        match context.snapshot_stage(stage).and_then(|image| Ok(image::DynamicImage::ImageRgba32F(image).to_rgba8().save(&filename)?)) {
+                            Ok(()) => tracing::info!("Saved {} stage snapshot to {filename}", stage.name()),
+                            Err(e) => tracing::warn!("Failed to capture {} stage: {e}", stage.name()),
+                        }
+                    }
+                }
+            }
+
+            // Handle passthrough comparison toggle (P)
+            WindowEvent::KeyboardInput {
+                event: KeyEvent {
+                    state: ElementState::Pressed,
+                    physical_key: PhysicalKey::Code(KeyCode::KeyP),
+                    ..
+                },
+                ..
+            } if self.modifiers.is_empty() => {
+                if let Some(context) = self.context.as_mut() {
+                    context.toggle_passthrough_comparison();
+                }
+            }
+
+            // Handle info overlay toggle (I)
+            WindowEvent::KeyboardInput {
+                event: KeyEvent {
+                    state: ElementState::Pressed,
+                    physical_key: PhysicalKey::Code(KeyCode::KeyI),
+                    ..
+                },
+                ..
+            } if self.modifiers.is_empty() => {
+                if let Some(context) = self.context.as_mut() {
+                    context.toggle_overlay();
+                }
+            }
+
+            // Track the cursor, updating the comparison rectangle while dragging
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_position = (position.x, position.y);
+                if let (Some(start), Some(context)) = (self.drag_start, self.context.as_mut()) {
+                    context.set_compare_rect(Some((start, self.cursor_position)));
+                }
+            }
+
+            // Start or finish dragging the comparison rectangle (left mouse button)
+            WindowEvent::MouseInput { state, button: MouseButton::Left, .. } => match state {
+                ElementState::Pressed => {
+                    self.drag_start = Some(self.cursor_position);
+                }
+                ElementState::Released => {
+                    // A click without dragging clears the rectangle
+                    if let (Some(start), Some(context)) = (self.drag_start.take(), self.context.as_mut()) {
+                        let (x, y) = self.cursor_position;
+                        let is_click = (x - start.0).abs() < 2.0 && (y - start.1).abs() < 2.0;
+                        context.set_compare_rect(if is_click { None } else { Some((start, self.cursor_position)) });
+                    }
+                }
+            },
+
+            // Handle Anime4K preset selection (Ctrl+0-7)
             WindowEvent::KeyboardInput {
                 event: KeyEvent {
                     state: ElementState::Pressed,
@@ -139,6 +339,7 @@ impl ApplicationHandler for VideoPlayerApp {
                     KeyCode::Digit4 => Some(Some(Anime4KPreset::ModeAA)),
                     KeyCode::Digit5 => Some(Some(Anime4KPreset::ModeBB)),
                     KeyCode::Digit6 => Some(Some(Anime4KPreset::ModeCA)),
+                    KeyCode::Digit7 => Some(Some(Anime4KPreset::Nearest)),
                     _ => None,
                 };
 
@@ -178,7 +379,11 @@ impl ApplicationHandler for VideoPlayerApp {
             // Handle frame rendering and timing
             WindowEvent::RedrawRequested => {
                 if let Some(context) = self.context.as_mut() {
-                    context.handle_redraw();
+                    if let Err(e) = context.handle_redraw() {
+                        // Out of memory and other unrecoverable surface errors
+                        tracing::error!("Failed to render frame: {e}");
+                        event_loop.exit();
+                    }
                 }
             }
 
@@ -0,0 +1,22 @@
+// Bilinear resampling compute shader for the passthrough comparison
+
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var source_sampler: sampler;
+@group(0) @binding(2) var output_texture: texture_storage_2d<rgba32float, write>;
+
+fn process(pos: vec2i) {
+    let output_dims = textureDimensions(output_texture);
+    let source_pos = (vec2f(pos) + 0.5) / vec2f(output_dims);
+
+    textureStore(output_texture, pos, textureSampleLevel(source_texture, source_sampler, source_pos, 0.0));
+}
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) global_id: vec3u) {
+    let dims = textureDimensions(output_texture);
+    if global_id.x >= dims.x || global_id.y >= dims.y {
+        return;
+    }
+
+    process(vec2i(global_id.xy));
+}
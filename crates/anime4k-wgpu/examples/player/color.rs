@@ -0,0 +1,267 @@
+//! Custom YUV-to-RGB color conversion
+//!
+//! This module loads the matrix and range the player converts decoded YUV frames with,
+//! for sources whose colorimetry the built-in BT.709 full-range conversion gets wrong.
+//!
+//! A color config is a small text file of `key = value` lines, with `#` starting a comment:
+//!
+//! ```text
+//! # BT.601, limited range
+//! matrix = 1.0, 0.0, 1.402; 1.0, -0.344136, -0.714136; 1.0, 1.772, 0.0
+//! range = limited
+//! ```
+//!
+//! `matrix` holds the rows producing R, G, and B from Y, U, and V, with U and V centered
+//! on zero. `range` is `full` (default) or `limited` (Y in 16-235, UV in 16-240).
+//!
+//! The optional `chroma_upsampling` (`bilinear` or `bicubic`) and `chroma_siting` (`center`,
+//! `left`, or `top-left`) keys control how the half-resolution chroma plane of 4:2:0 frames
+//! is reconstructed. The default assumes center siting, as in MPEG-1 and JPEG; H.264 and HEVC
+//! streams without chroma location info use `left`.
+
+use std::{path::Path, str::FromStr};
+
+/// Smallest accepted absolute determinant of the conversion matrix
+///
+/// A near-singular matrix collapses distinct colors together, which is almost always a typo.
+const MIN_DETERMINANT: f32 = 1e-3;
+
+/// Largest accepted absolute matrix coefficient
+///
+/// Standard matrices stay below 2.1, so anything far beyond that is not a color conversion.
+const MAX_COEFFICIENT: f32 = 4.0;
+
+/// Quantization range of the YUV samples
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorRange {
+    /// Samples use the full 0-255 range
+    #[default]
+    Full,
+    /// Y uses 16-235 and U and V use 16-240
+    Limited,
+}
+
+impl FromStr for ColorRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "full" => Ok(ColorRange::Full),
+            "limited" => Ok(ColorRange::Limited),
+            _ => Err(format!("Unknown color range '{s}' (expected full or limited)")),
+        }
+    }
+}
+
+/// Filter reconstructing full-resolution chroma from the subsampled plane
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChromaUpsampling {
+    /// Linear interpolation between the 2x2 nearest chroma samples
+    #[default]
+    Bilinear,
+    /// Separable Catmull-Rom filter over the 4x4 nearest chroma samples
+    ///
+    /// Keeps thin colored lines sharper and reduces color bleeding into neighboring areas.
+    Bicubic,
+}
+
+impl FromStr for ChromaUpsampling {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "bilinear" => Ok(ChromaUpsampling::Bilinear),
+            "bicubic" => Ok(ChromaUpsampling::Bicubic),
+            _ => Err(format!("Unknown chroma upsampling '{s}' (expected bilinear or bicubic)")),
+        }
+    }
+}
+
+/// Position of each subsampled chroma sample relative to the luma samples it covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChromaSiting {
+    /// Centered between the luma samples in both directions (MPEG-1, JPEG)
+    #[default]
+    Center,
+    /// Aligned with the left luma column and centered vertically (MPEG-2, H.264, HEVC)
+    Left,
+    /// Aligned with the top-left luma sample (BT.2020 and BT.2100 content)
+    TopLeft,
+}
+
+impl ChromaSiting {
+    /// Returns the shift from center siting in chroma samples, per axis
+    fn offset(self) -> [f32; 2] {
+        // A chroma sample co-sited with luma column 2i sits a quarter chroma sample right of the center position
+        match self {
+            ChromaSiting::Center => [0.0, 0.0],
+            ChromaSiting::Left => [0.25, 0.0],
+            ChromaSiting::TopLeft => [0.25, 0.25],
+        }
+    }
+}
+
+impl FromStr for ChromaSiting {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "center" => Ok(ChromaSiting::Center),
+            "left" => Ok(ChromaSiting::Left),
+            "top-left" | "topleft" => Ok(ChromaSiting::TopLeft),
+            _ => Err(format!("Unknown chroma siting '{s}' (expected center, left, or top-left)")),
+        }
+    }
+}
+
+/// Uniform buffer data for the YUV to sRGB conversion
+///
+/// Matches `ColorUniforms` in `yuv_to_srgb.wgsl`, where each matrix column is padded to 16 bytes.
+#[derive(Debug, Clone, Copy, bytemuck::Zeroable, bytemuck::Pod)]
+#[repr(C)]
+pub struct ColorUniforms {
+    /// Columns of the matrix applied to the offset YUV samples
+    matrix: [[f32; 4]; 3],
+    /// Normalized Y, U, and V values subtracted before the matrix is applied
+    offset: [f32; 4],
+    /// Shift of the chroma sampling position in chroma samples
+    chroma_offset: [f32; 2],
+    /// Chroma filter, 0 for bilinear and 1 for bicubic
+    chroma_filter: u32,
+    /// Padding to the 16-byte struct alignment
+    _padding: u32,
+}
+
+/// YUV-to-RGB conversion matrix, sample range, and chroma reconstruction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorConfig {
+    /// Row-major matrix from Y, U, and V (U and V centered on zero) to R, G, and B
+    pub matrix: [[f32; 3]; 3],
+    /// Quantization range of the samples
+    pub range: ColorRange,
+    /// Filter used to upsample the chroma plane
+    pub chroma_upsampling: ChromaUpsampling,
+    /// Assumed chroma sample positions
+    pub chroma_siting: ChromaSiting,
+}
+
+impl Default for ColorConfig {
+    /// Returns the built-in BT.709 full-range conversion
+    fn default() -> Self {
+        Self {
+            matrix: [[1.0, 0.0, 1.5748], [1.0, -0.1873, -0.4681], [1.0, 1.8556, 0.0]],
+            range: ColorRange::Full,
+            chroma_upsampling: ChromaUpsampling::default(),
+            chroma_siting: ChromaSiting::default(),
+        }
+    }
+}
+
+impl FromStr for ColorConfig {
+    type Err = String;
+
+    /// Parses the contents of a color config file
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut matrix = None;
+        let mut range = ColorRange::default();
+        let mut chroma_upsampling = ChromaUpsampling::default();
+        let mut chroma_siting = ChromaSiting::default();
+
+        for (index, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (key, value) = line.split_once('=').ok_or_else(|| format!("Line {}: expected 'key = value'", index + 1))?;
+            match key.trim() {
+                "matrix" => matrix = Some(parse_matrix(value).map_err(|e| format!("Line {}: {e}", index + 1))?),
+                "range" => range = value.trim().parse().map_err(|e| format!("Line {}: {e}", index + 1))?,
+                "chroma_upsampling" => chroma_upsampling = value.trim().parse().map_err(|e| format!("Line {}: {e}", index + 1))?,
+                "chroma_siting" => chroma_siting = value.trim().parse().map_err(|e| format!("Line {}: {e}", index + 1))?,
+                key => return Err(format!("Line {}: unknown key '{key}' (expected matrix, range, chroma_upsampling, or chroma_siting)", index + 1)),
+            }
+        }
+
+        Ok(Self {
+            matrix: matrix.ok_or("Missing 'matrix' entry")?,
+            range,
+            chroma_upsampling,
+            chroma_siting,
+        })
+    }
+}
+
+impl ColorConfig {
+    /// Loads a color config file, falling back to the default if its matrix is not sane
+    ///
+    /// # Arguments
+    /// * `path` - Path to the color config file
+    ///
+    /// # Returns
+    /// The loaded config, the default config with a warning logged if the matrix fails
+    /// [`validate`](Self::validate), or an error if the file cannot be read or parsed
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let config: Self = std::fs::read_to_string(path)?.parse().map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        if let Err(e) = config.validate() {
+            tracing::warn!("Ignoring color config {}: {e}; using BT.709 full range", path.display());
+            return Ok(Self::default());
+        }
+        Ok(config)
+    }
+
+    /// Checks that the matrix is finite, invertible, and of plausible magnitude
+    ///
+    /// # Returns
+    /// An error describing the first problem found
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(coefficient) = self.matrix.iter().flatten().find(|c| !c.is_finite() || c.abs() > MAX_COEFFICIENT) {
+            return Err(format!("matrix coefficient {coefficient} is not finite or exceeds {MAX_COEFFICIENT}"));
+        }
+
+        let [[a, b, c], [d, e, f], [g, h, i]] = self.matrix;
+        let determinant = a * (e * i - f * h) - b * (d * i - f * g) + c * (d * h - e * g);
+        if determinant.abs() < MIN_DETERMINANT {
+            return Err(format!("matrix is not invertible (determinant {determinant})"));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the uniform buffer data with the range folded into the matrix and offset, plus the chroma settings
+    pub fn uniforms(&self) -> ColorUniforms {
+        let (offset, scale) = match self.range {
+            ColorRange::Full => ([0.0, 0.5, 0.5], [1.0, 1.0, 1.0]),
+            ColorRange::Limited => ([16.0 / 255.0, 128.0 / 255.0, 128.0 / 255.0], [255.0 / 219.0, 255.0 / 224.0, 255.0 / 224.0]),
+        };
+
+        let column = |index: usize| [0, 1, 2].map(|row| self.matrix[row][index] * scale[index]);
+        let [c0, c1, c2] = [column(0), column(1), column(2)];
+
+        ColorUniforms {
+            matrix: [[c0[0], c0[1], c0[2], 0.0], [c1[0], c1[1], c1[2], 0.0], [c2[0], c2[1], c2[2], 0.0]],
+            offset: [offset[0], offset[1], offset[2], 0.0],
+            chroma_offset: self.chroma_siting.offset(),
+            chroma_filter: match self.chroma_upsampling {
+                ChromaUpsampling::Bilinear => 0,
+                ChromaUpsampling::Bicubic => 1,
+            },
+            _padding: 0,
+        }
+    }
+}
+
+/// Parses three semicolon-separated rows of three comma-separated coefficients
+fn parse_matrix(value: &str) -> Result<[[f32; 3]; 3], String> {
+    let rows = value
+        .split(';')
+        .map(|row| {
+            let coefficients = row
+                .split(',')
+                .map(|coefficient| coefficient.trim().parse::<f32>().map_err(|e| format!("invalid matrix coefficient '{}': {e}", coefficient.trim())))
+                .collect::<Result<Vec<_>, _>>()?;
+            <[f32; 3]>::try_from(coefficients).map_err(|coefficients| format!("matrix row has {} coefficients (expected 3)", coefficients.len()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    <[[f32; 3]; 3]>::try_from(rows).map_err(|rows| format!("matrix has {} rows (expected 3)", rows.len()))
+}
@@ -4,6 +4,7 @@
 //! It decodes video frames from a byte stream and outputs them as wgpu textures
 //! with presentation timestamps for playback synchronization.
 
+use super::keyframe::Keyframe;
 use bytes::BytesMut;
 use std::{
     io::Read,
@@ -47,6 +48,8 @@ pub struct FrameWithPts {
 /// * `framerate` - Target framerate in frames per second for timestamp calculation
 /// * `vulkan_device` - Vulkan device instance for creating the decoder
 /// * `bytestream_reader` - Input stream containing encoded video data
+/// * `keyframe` - Where the stream starts, as returned by `find_keyframe_before` (`Keyframe::default()` for the beginning)
+/// * `start` - Frames before this timestamp are decoded but not sent
 ///
 /// # Behavior
 /// - Continues reading until the input stream ends (returns 0 bytes)
@@ -57,13 +60,13 @@ pub struct FrameWithPts {
 /// # Panics
 /// May panic if the Vulkan decoder creation or decoding operations fail.
 /// In production code, these should be handled with proper error propagation.
-pub fn run_decoder(tx: SyncSender<FrameWithPts>, framerate: u32, vulkan_device: Arc<VulkanDevice>, mut bytestream_reader: impl Read) {
+pub fn run_decoder(tx: SyncSender<FrameWithPts>, framerate: u32, vulkan_device: Arc<VulkanDevice>, mut bytestream_reader: impl Read, keyframe: Keyframe, start: Duration) {
     // Create a Vulkan Video decoder that outputs wgpu textures
     let mut decoder = vulkan_device.create_wgpu_textures_decoder().unwrap();
 
     // Calculate the time interval between frames based on framerate
     let frame_interval = 1.0 / (framerate as f64);
-    let mut frame_number = 0u64;
+    let mut frame_number = keyframe.frame_number;
 
     // Buffer for reading encoded data chunks
     let mut buffer = BytesMut::zeroed(4096);
@@ -78,6 +81,10 @@ pub fn run_decoder(tx: SyncSender<FrameWithPts>, framerate: u32, vulkan_device:
 
         *frame_number += 1;
 
+        // Frames between the keyframe and the seek target are only needed as references
+        if result.pts < start {
+            return Ok(());
+        }
         tx.send(result)
     };
 
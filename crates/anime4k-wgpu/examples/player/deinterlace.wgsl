@@ -0,0 +1,80 @@
+// Deinterlacing compute shader
+//
+// Keeps the lines of the first field of the woven frame and reconstructs the lines of
+// the second field by interpolating from the lines above and below, along whichever of
+// three directions matches best so diagonal edges do not turn into stairs. The adaptive
+// method keeps the woven lines where the frame is still compared with the previous one,
+// since weaving loses no vertical resolution when both fields show the same image.
+
+struct Params {
+    // Parity of the lines of the first field (0 for top field first, 1 for bottom)
+    first_field: u32,
+    // Whether to keep the woven lines in still areas
+    adaptive: u32,
+}
+
+@group(0) @binding(0) var current_texture: texture_2d<f32>;
+@group(0) @binding(1) var previous_texture: texture_2d<f32>;
+@group(0) @binding(2) var output_texture: texture_storage_2d<rgba32float, write>;
+@group(0) @binding(3) var<uniform> params: Params;
+
+// Per-channel difference from the previous frame below which an area counts as still
+const MOTION_LOW: f32 = 0.02;
+// Per-channel difference from the previous frame above which an area counts as moving
+const MOTION_HIGH: f32 = 0.08;
+
+fn load(texture: texture_2d<f32>, pos: vec2i) -> vec4f {
+    let dims = vec2i(textureDimensions(texture));
+    return textureLoad(texture, clamp(pos, vec2i(0), dims - 1), 0);
+}
+
+fn max_difference(a: vec4f, b: vec4f) -> f32 {
+    let difference = abs(a.rgb - b.rgb);
+    return max(difference.r, max(difference.g, difference.b));
+}
+
+// Interpolates a second-field line from the first-field lines above and below
+fn interpolate(pos: vec2i) -> vec4f {
+    var best = vec4f(0.0);
+    var best_cost = 1e9;
+    for (var direction = -1; direction <= 1; direction++) {
+        let above = load(current_texture, pos + vec2i(direction, -1));
+        let below = load(current_texture, pos + vec2i(-direction, 1));
+        // Prefer the vertical direction unless a diagonal is clearly better
+        let cost = max_difference(above, below) + select(0.01, 0.0, direction == 0);
+        if cost < best_cost {
+            best_cost = cost;
+            best = (above + below) * 0.5;
+        }
+    }
+    return best;
+}
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) global_id: vec3u) {
+    let dims = textureDimensions(output_texture);
+    if global_id.x >= dims.x || global_id.y >= dims.y {
+        return;
+    }
+
+    let pos = vec2i(global_id.xy);
+    let woven = load(current_texture, pos);
+    if (global_id.y & 1u) == params.first_field {
+        textureStore(output_texture, pos, woven);
+        return;
+    }
+
+    let interpolated = interpolate(pos);
+    if params.adaptive == 0u {
+        textureStore(output_texture, pos, interpolated);
+        return;
+    }
+
+    // Motion around the line, including the first-field lines next to it
+    var motion = 0.0;
+    for (var offset = -1; offset <= 1; offset++) {
+        let line = pos + vec2i(0, offset);
+        motion = max(motion, max_difference(load(current_texture, line), load(previous_texture, line)));
+    }
+    textureStore(output_texture, pos, mix(woven, interpolated, smoothstep(MOTION_LOW, MOTION_HIGH, motion)));
+}
@@ -0,0 +1,669 @@
+//! Container demuxing for the player
+//!
+//! Vulkan Video decodes H.264 Annex B bytestreams, while MP4 and Matroska files store
+//! the stream as length-prefixed (AVCC) NAL units with the parameter sets kept in the
+//! track header. This module extracts the H.264 track of such files and rewrites it as
+//! an Annex B bytestream. The parameter sets are repeated before every IDR picture, so
+//! `find_keyframe_before` can seek to any of them.
+//!
+//! Only progressive (non-fragmented) MP4 files and unlaced Matroska blocks are
+//! supported. HEVC tracks are recognized but rejected, as the decoder only handles H.264.
+
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+/// Annex B start code written before every NAL unit
+const START_CODE: [u8; 4] = [0, 0, 0, 1];
+
+/// Matroska element IDs, including their length marker bits
+mod ebml_id {
+    pub const SEGMENT: u64 = 0x1853_8067;
+    pub const TRACKS: u64 = 0x1654_AE6B;
+    pub const TRACK_ENTRY: u64 = 0xAE;
+    pub const TRACK_NUMBER: u64 = 0xD7;
+    pub const CODEC_ID: u64 = 0x86;
+    pub const CODEC_PRIVATE: u64 = 0x63A2;
+    pub const CLUSTER: u64 = 0x1F43_B675;
+    pub const BLOCK_GROUP: u64 = 0xA0;
+    pub const BLOCK: u64 = 0xA1;
+    pub const SIMPLE_BLOCK: u64 = 0xA3;
+}
+
+/// Container formats the demuxer can read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Container {
+    /// ISO base media file format (.mp4, .m4v, .mov)
+    Mp4,
+    /// Matroska or WebM (.mkv, .webm)
+    Matroska,
+}
+
+impl Container {
+    /// Detects the container of a file from its first bytes
+    ///
+    /// # Returns
+    /// The container, or None for anything else such as a raw Annex B bytestream
+    pub fn detect(reader: &mut impl Read) -> io::Result<Option<Self>> {
+        let mut header = Vec::with_capacity(8);
+        reader.by_ref().take(8).read_to_end(&mut header)?;
+
+        Ok(if header.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+            Some(Container::Matroska)
+        } else if header.len() == 8 && matches!(&header[4..8], b"ftyp" | b"moov" | b"mdat" | b"free" | b"wide") {
+            Some(Container::Mp4)
+        } else {
+            None
+        })
+    }
+}
+
+/// Temporary file that is removed when dropped
+///
+/// Dropping also runs when the player unwinds from a panic, so an extracted
+/// bytestream does not outlive a failed playback.
+pub struct TempFile(PathBuf);
+
+impl TempFile {
+    /// Returns the path of the file
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Extracts the H.264 track of an MP4 or Matroska file into a temporary Annex B bytestream
+///
+/// The bytestream is written to the system temporary directory and removed when the
+/// returned `TempFile` is dropped.
+///
+/// # Arguments
+/// * `input` - Path to the video file
+///
+/// # Returns
+/// The extracted bytestream, or None if the file is not a supported container and
+/// should be played as is
+pub fn extract_to_temp_file(input: &Path) -> io::Result<Option<TempFile>> {
+    let mut reader = BufReader::new(File::open(input)?);
+    let Some(container) = Container::detect(&mut reader)? else {
+        return Ok(None);
+    };
+    reader.seek(SeekFrom::Start(0))?;
+
+    let temp_file = TempFile(std::env::temp_dir().join(format!("anime4k-player-{}.h264", std::process::id())));
+    let mut output = BufWriter::new(File::create(temp_file.path())?);
+    match container {
+        Container::Mp4 => demux_mp4(&mut reader, &mut output)?,
+        Container::Matroska => demux_matroska(&mut reader, &mut output)?,
+    }
+    output.flush()?;
+    drop(output);
+
+    Ok(Some(temp_file))
+}
+
+/// Creates an error for malformed container data
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// H.264 decoder configuration from an `avcC` box or Matroska `CodecPrivate`
+struct AvcConfig {
+    /// Size in bytes of the length prefix of each NAL unit (1, 2, or 4)
+    length_size: usize,
+    /// Sequence and picture parameter sets, in that order
+    parameter_sets: Vec<Vec<u8>>,
+}
+
+impl AvcConfig {
+    /// Parses an `AVCDecoderConfigurationRecord`
+    fn parse(data: &[u8]) -> io::Result<Self> {
+        let truncated = || invalid("Truncated H.264 decoder configuration");
+        if data.first() != Some(&1) {
+            return Err(invalid("Unsupported H.264 decoder configuration version"));
+        }
+        let length_size = (*data.get(4).ok_or_else(truncated)? & 0x03) as usize + 1;
+
+        let mut parameter_sets = Vec::new();
+        let mut pos = 5;
+        // SPS count is in the low 5 bits, PPS count is a whole byte
+        for mask in [0x1f, 0xff] {
+            let count = *data.get(pos).ok_or_else(truncated)? & mask;
+            pos += 1;
+            for _ in 0..count {
+                let length = u16::from_be_bytes(data.get(pos..pos + 2).ok_or_else(truncated)?.try_into().unwrap()) as usize;
+                pos += 2;
+                parameter_sets.push(data.get(pos..pos + length).ok_or_else(truncated)?.to_vec());
+                pos += length;
+            }
+        }
+
+        Ok(Self { length_size, parameter_sets })
+    }
+
+    /// Writes one length-prefixed sample as Annex B
+    ///
+    /// Samples holding an IDR slice but no SPS of their own are preceded by the
+    /// parameter sets from the configuration.
+    fn write_sample(&self, sample: &[u8], output: &mut impl Write) -> io::Result<()> {
+        let mut nal_units = Vec::new();
+        let mut pos = 0;
+        while pos < sample.len() {
+            let prefix = sample.get(pos..pos + self.length_size).ok_or_else(|| invalid("Truncated NAL unit length"))?;
+            let length = prefix.iter().fold(0usize, |length, &byte| (length << 8) | byte as usize);
+            pos += self.length_size;
+            nal_units.push(sample.get(pos..pos + length).ok_or_else(|| invalid("NAL unit exceeds its sample"))?);
+            pos += length;
+        }
+
+        let has_nal_type = |nal_type: u8| nal_units.iter().any(|nal_unit| nal_unit.first().is_some_and(|header| header & 0x1f == nal_type));
+        if has_nal_type(5) && !has_nal_type(7) {
+            for parameter_set in &self.parameter_sets {
+                output.write_all(&START_CODE)?;
+                output.write_all(parameter_set)?;
+            }
+        }
+        for nal_unit in nal_units {
+            output.write_all(&START_CODE)?;
+            output.write_all(nal_unit)?;
+        }
+        Ok(())
+    }
+}
+
+/// Rejects a video codec other than H.264 with a descriptive error
+fn unsupported_codec(codec: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, format!("Video codec {codec} is not supported, only H.264 can be decoded"))
+}
+
+/// Reads a big-endian integer of `N` bytes from an in-memory box at `pos`
+fn read_be<const N: usize>(data: &[u8], pos: usize) -> io::Result<u64> {
+    let bytes = data.get(pos..pos + N).ok_or_else(|| invalid("Truncated MP4 box"))?;
+    Ok(bytes.iter().fold(0u64, |value, &byte| (value << 8) | byte as u64))
+}
+
+/// Splits the body of an in-memory MP4 box into its child boxes
+fn child_boxes(data: &[u8]) -> io::Result<Vec<([u8; 4], &[u8])>> {
+    let mut boxes = Vec::new();
+    let mut pos = 0;
+    while pos + 8 <= data.len() {
+        let box_type: [u8; 4] = data[pos + 4..pos + 8].try_into().unwrap();
+        let (header_size, size) = match read_be::<4>(data, pos)? {
+            1 => (16, read_be::<8>(data, pos + 8)? as usize),
+            0 => (8, data.len() - pos),
+            size => (8, size as usize),
+        };
+        if size < header_size || pos + size > data.len() {
+            return Err(invalid(format!("Invalid size of MP4 box {}", String::from_utf8_lossy(&box_type))));
+        }
+        boxes.push((box_type, &data[pos + header_size..pos + size]));
+        pos += size;
+    }
+    Ok(boxes)
+}
+
+/// Finds a descendant of an in-memory MP4 box by the types along its path
+fn find_box<'a>(data: &'a [u8], path: &[&[u8; 4]]) -> io::Result<Option<&'a [u8]>> {
+    let Some((first, rest)) = path.split_first() else {
+        return Ok(Some(data));
+    };
+    match child_boxes(data)?.into_iter().find(|(box_type, _)| box_type == *first) {
+        Some((_, body)) => find_box(body, rest),
+        None => Ok(None),
+    }
+}
+
+/// Converts the first H.264 video track of an MP4 file to Annex B
+///
+/// Only the `moov` box is read into memory; samples are read one at a time.
+fn demux_mp4(reader: &mut (impl Read + Seek), output: &mut impl Write) -> io::Result<()> {
+    let file_size = reader.seek(SeekFrom::End(0))?;
+
+    // Locate the movie box among the top-level boxes
+    let mut pos = 0;
+    let moov = loop {
+        if pos + 8 > file_size {
+            return Err(invalid("MP4 file has no moov box"));
+        }
+        reader.seek(SeekFrom::Start(pos))?;
+        let mut header = [0u8; 16];
+        reader.read_exact(&mut header[..8])?;
+        let (header_size, size) = match u32::from_be_bytes(header[..4].try_into().unwrap()) {
+            1 => {
+                reader.read_exact(&mut header[8..])?;
+                (16, u64::from_be_bytes(header[8..].try_into().unwrap()))
+            }
+            0 => (8, file_size - pos),
+            size => (8, size as u64),
+        };
+        if size < header_size {
+            return Err(invalid("Invalid size of top-level MP4 box"));
+        }
+        if &header[4..8] == b"moov" {
+            let mut moov = vec![0u8; (size - header_size) as usize];
+            reader.read_exact(&mut moov)?;
+            break moov;
+        }
+        pos += size;
+    };
+    if find_box(&moov, &[b"mvex"])?.is_some() {
+        return Err(io::Error::new(io::ErrorKind::Unsupported, "Fragmented MP4 files are not supported"));
+    }
+
+    for (box_type, trak) in child_boxes(&moov)? {
+        if &box_type != b"trak" {
+            continue;
+        }
+        let hdlr = find_box(trak, &[b"mdia", b"hdlr"])?.ok_or_else(|| invalid("MP4 track has no hdlr box"))?;
+        if hdlr.get(8..12) != Some(b"vide".as_slice()) {
+            continue;
+        }
+        let stbl = find_box(trak, &[b"mdia", b"minf", b"stbl"])?.ok_or_else(|| invalid("MP4 video track has no stbl box"))?;
+
+        // The sample description follows the version, flags, and entry count of stsd
+        let stsd = find_box(stbl, &[b"stsd"])?.ok_or_else(|| invalid("MP4 video track has no stsd box"))?;
+        let (entry_type, entry) = child_boxes(stsd.get(8..).ok_or_else(|| invalid("Truncated stsd box"))?)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| invalid("MP4 video track has no sample description"))?;
+        let config = match &entry_type {
+            // Child boxes of a visual sample entry follow 78 bytes of fixed fields
+            b"avc1" | b"avc3" => {
+                let avcc = find_box(entry.get(78..).ok_or_else(|| invalid("Truncated avc1 sample entry"))?, &[b"avcC"])?.ok_or_else(|| invalid("avc1 sample entry has no avcC box"))?;
+                AvcConfig::parse(avcc)?
+            }
+            b"hvc1" | b"hev1" => return Err(unsupported_codec("HEVC")),
+            other => return Err(unsupported_codec(&String::from_utf8_lossy(other))),
+        };
+
+        let stsz = find_box(stbl, &[b"stsz"])?.ok_or_else(|| invalid("MP4 video track has no stsz box"))?;
+        let sample_size = read_be::<4>(stsz, 4)?;
+        let sample_count = read_be::<4>(stsz, 8)? as usize;
+        let sample_sizes = if sample_size != 0 {
+            vec![sample_size; sample_count]
+        } else {
+            (0..sample_count).map(|i| read_be::<4>(stsz, 12 + i * 4)).collect::<io::Result<_>>()?
+        };
+
+        // (first chunk, samples per chunk) runs, each lasting until the next run's first chunk
+        let stsc = find_box(stbl, &[b"stsc"])?.ok_or_else(|| invalid("MP4 video track has no stsc box"))?;
+        let stsc_entries = (0..read_be::<4>(stsc, 4)? as usize)
+            .map(|i| Ok((read_be::<4>(stsc, 8 + i * 12)?, read_be::<4>(stsc, 12 + i * 12)?)))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let chunk_offsets = if let Some(stco) = find_box(stbl, &[b"stco"])? {
+            (0..read_be::<4>(stco, 4)? as usize).map(|i| read_be::<4>(stco, 8 + i * 4)).collect::<io::Result<Vec<_>>>()?
+        } else if let Some(co64) = find_box(stbl, &[b"co64"])? {
+            (0..read_be::<4>(co64, 4)? as usize).map(|i| read_be::<8>(co64, 8 + i * 8)).collect::<io::Result<Vec<_>>>()?
+        } else {
+            return Err(invalid("MP4 video track has no chunk offsets"));
+        };
+
+        let mut sample_sizes = sample_sizes.into_iter();
+        let mut sample = Vec::new();
+        for (chunk_index, &chunk_offset) in chunk_offsets.iter().enumerate() {
+            let chunk_number = chunk_index as u64 + 1;
+            let samples_per_chunk = stsc_entries.iter().rev().find(|(first_chunk, _)| *first_chunk <= chunk_number).map_or(0, |(_, samples)| *samples);
+
+            reader.seek(SeekFrom::Start(chunk_offset))?;
+            for size in sample_sizes.by_ref().take(samples_per_chunk as usize) {
+                sample.resize(size as usize, 0);
+                reader.read_exact(&mut sample)?;
+                config.write_sample(&sample, output)?;
+            }
+        }
+        return Ok(());
+    }
+
+    Err(invalid("MP4 file has no video track"))
+}
+
+/// Reads an EBML variable-length integer
+///
+/// Element IDs keep their length marker bit, element sizes do not.
+///
+/// # Returns
+/// The value and its length in bytes, or None at the end of the stream
+fn read_vint(reader: &mut impl Read, keep_marker: bool) -> io::Result<Option<(u64, u32)>> {
+    let mut first = [0u8];
+    if reader.read(&mut first)? == 0 {
+        return Ok(None);
+    }
+    let length = first[0].leading_zeros() + 1;
+    if length > 8 {
+        return Err(invalid("Invalid EBML variable-length integer"));
+    }
+
+    let mut value = if keep_marker {
+        first[0] as u64
+    } else {
+        (first[0] & 0xffu8.checked_shr(length).unwrap_or(0)) as u64
+    };
+    for _ in 1..length {
+        let mut byte = [0u8];
+        reader.read_exact(&mut byte)?;
+        value = (value << 8) | byte[0] as u64;
+    }
+    Ok(Some((value, length)))
+}
+
+/// A Matroska track, as far as it is needed to find the H.264 track
+#[derive(Default)]
+struct MatroskaTrack {
+    /// Number identifying the track in blocks
+    number: u64,
+    /// Codec ID, e.g. `V_MPEG4/ISO/AVC`
+    codec_id: String,
+    /// Codec private data, the `AVCDecoderConfigurationRecord` for H.264
+    codec_private: Vec<u8>,
+}
+
+/// Converts the first H.264 video track of a Matroska file to Annex B
+///
+/// Elements are scanned linearly, descending into the master elements that lead to
+/// tracks and blocks and skipping everything else, so live-recorded files whose
+/// segments and clusters have unknown sizes are supported.
+fn demux_matroska(reader: &mut (impl Read + Seek), output: &mut impl Write) -> io::Result<()> {
+    let mut tracks: Vec<MatroskaTrack> = Vec::new();
+    let mut video: Option<(u64, AvcConfig)> = None;
+
+    while let Some((id, _)) = read_vint(reader, true)? {
+        let (size, size_length) = read_vint(reader, false)?.ok_or_else(|| invalid("Truncated Matroska element"))?;
+        let unknown_size = size == (1 << (7 * size_length)) - 1;
+
+        match id {
+            ebml_id::SEGMENT | ebml_id::TRACKS | ebml_id::CLUSTER | ebml_id::BLOCK_GROUP => continue,
+            ebml_id::TRACK_ENTRY => {
+                tracks.push(MatroskaTrack::default());
+                continue;
+            }
+            _ if unknown_size => return Err(invalid(format!("Matroska element {id:#x} has an unknown size"))),
+            ebml_id::TRACK_NUMBER | ebml_id::CODEC_ID | ebml_id::CODEC_PRIVATE | ebml_id::BLOCK | ebml_id::SIMPLE_BLOCK => {}
+            _ => {
+                reader.seek(SeekFrom::Current(size as i64))?;
+                continue;
+            }
+        }
+
+        let mut body = vec![0u8; size as usize];
+        reader.read_exact(&mut body)?;
+        if matches!(id, ebml_id::BLOCK | ebml_id::SIMPLE_BLOCK) {
+            // Tracks precede the first cluster, so the video track is chosen at the first block
+            if video.is_none() {
+                video = Some(select_matroska_track(&tracks)?);
+            }
+            let (video_number, config) = video.as_ref().unwrap();
+
+            let mut block = body.as_slice();
+            let (track_number, track_number_length) = read_vint(&mut block, false)?.ok_or_else(|| invalid("Truncated Matroska block"))?;
+            if track_number != *video_number {
+                continue;
+            }
+            // Relative timestamp (2 bytes) and flags (1 byte) follow the track number
+            let header_size = track_number_length as usize + 3;
+            let flags = *body.get(header_size - 1).ok_or_else(|| invalid("Truncated Matroska block"))?;
+            if flags & 0x06 != 0 {
+                return Err(io::Error::new(io::ErrorKind::Unsupported, "Laced Matroska video blocks are not supported"));
+            }
+            config.write_sample(&body[header_size..], output)?;
+            continue;
+        }
+
+        let track = tracks.last_mut().ok_or_else(|| invalid("Matroska track element outside of a track entry"))?;
+        match id {
+            ebml_id::TRACK_NUMBER => track.number = body.iter().fold(0, |value, &byte| (value << 8) | byte as u64),
+            ebml_id::CODEC_ID => track.codec_id = String::from_utf8_lossy(&body).trim_end_matches('\0').to_string(),
+            _ => track.codec_private = body,
+        }
+    }
+
+    if video.is_none() {
+        select_matroska_track(&tracks)?;
+    }
+    Ok(())
+}
+
+/// Picks the first H.264 track, rejecting files whose only video is another codec
+fn select_matroska_track(tracks: &[MatroskaTrack]) -> io::Result<(u64, AvcConfig)> {
+    if let Some(track) = tracks.iter().find(|track| track.codec_id == "V_MPEG4/ISO/AVC") {
+        return Ok((track.number, AvcConfig::parse(&track.codec_private)?));
+    }
+    match tracks.iter().find(|track| track.codec_id.starts_with("V_")) {
+        Some(track) if track.codec_id == "V_MPEGH/ISO/HEVC" => Err(unsupported_codec("HEVC")),
+        Some(track) => Err(unsupported_codec(&track.codec_id)),
+        None => Err(invalid("Matroska file has no video track")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const SPS: [u8; 4] = [0x67, 0x64, 0x00, 0x1f];
+    const PPS: [u8; 3] = [0x68, 0xee, 0x3c];
+    const IDR: [u8; 3] = [0x65, 0x88, 0x84];
+    const SLICE: [u8; 2] = [0x41, 0x9a];
+
+    /// Builds an `AVCDecoderConfigurationRecord` with one SPS and one PPS
+    fn avc_config_record(length_size: u8) -> Vec<u8> {
+        let mut record = vec![1, 0x64, 0x00, 0x1f, 0xfc | (length_size - 1), 0xe0 | 1];
+        record.extend_from_slice(&(SPS.len() as u16).to_be_bytes());
+        record.extend_from_slice(&SPS);
+        record.push(1);
+        record.extend_from_slice(&(PPS.len() as u16).to_be_bytes());
+        record.extend_from_slice(&PPS);
+        record
+    }
+
+    /// Prefixes each NAL unit with its 4-byte length
+    fn avcc_sample(nal_units: &[&[u8]]) -> Vec<u8> {
+        nal_units
+            .iter()
+            .flat_map(|nal_unit| (nal_unit.len() as u32).to_be_bytes().into_iter().chain(nal_unit.iter().copied()))
+            .collect()
+    }
+
+    /// Joins NAL units into an Annex B bytestream
+    fn annex_b(nal_units: &[&[u8]]) -> Vec<u8> {
+        nal_units.iter().flat_map(|nal_unit| START_CODE.into_iter().chain(nal_unit.iter().copied())).collect()
+    }
+
+    /// Builds an MP4 box from its type and body
+    fn mp4_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        [(body.len() as u32 + 8).to_be_bytes().as_slice(), box_type, body].concat()
+    }
+
+    /// Builds a Matroska element with an 8-byte size
+    fn ebml_element(id: u64, body: &[u8]) -> Vec<u8> {
+        let id_bytes = id.to_be_bytes();
+        let id_bytes = &id_bytes[id.leading_zeros() as usize / 8..];
+        let mut size = (body.len() as u64).to_be_bytes();
+        size[0] = 0x01;
+        [id_bytes, &size, body].concat()
+    }
+
+    /// Builds a Matroska `SimpleBlock` for a track with a single-byte track number
+    fn simple_block(track_number: u8, flags: u8, sample: &[u8]) -> Vec<u8> {
+        ebml_element(ebml_id::SIMPLE_BLOCK, &[[0x80 | track_number, 0, 0, flags].as_slice(), sample].concat())
+    }
+
+    /// Builds a Matroska track entry
+    fn track_entry(number: u8, codec_id: &str, codec_private: &[u8]) -> Vec<u8> {
+        ebml_element(
+            ebml_id::TRACK_ENTRY,
+            &[
+                ebml_element(ebml_id::TRACK_NUMBER, &[number]),
+                ebml_element(ebml_id::CODEC_ID, codec_id.as_bytes()),
+                ebml_element(ebml_id::CODEC_PRIVATE, codec_private),
+            ]
+            .concat(),
+        )
+    }
+
+    /// Builds a Matroska file with an unknown-size segment from its tracks and blocks
+    fn matroska_file(tracks: &[Vec<u8>], blocks: &[Vec<u8>]) -> Vec<u8> {
+        let header = ebml_element(0x1A45_DFA3, &ebml_element(0x4282, b"matroska"));
+        let segment_header = [0x18, 0x53, 0x80, 0x67, 0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+        // A Void element is skipped without being read
+        let void = ebml_element(0xEC, &[0; 4]);
+        [
+            header,
+            segment_header.to_vec(),
+            void,
+            ebml_element(ebml_id::TRACKS, &tracks.concat()),
+            ebml_element(ebml_id::CLUSTER, &blocks.concat()),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_avc_config_parse() {
+        let config = AvcConfig::parse(&avc_config_record(4)).unwrap();
+        assert_eq!(config.length_size, 4);
+        assert_eq!(config.parameter_sets, vec![SPS.to_vec(), PPS.to_vec()]);
+        assert_eq!(AvcConfig::parse(&avc_config_record(2)).unwrap().length_size, 2);
+
+        let record = avc_config_record(4);
+        assert_eq!(AvcConfig::parse(&record[..record.len() - 1]).err().unwrap().kind(), io::ErrorKind::InvalidData);
+        assert!(AvcConfig::parse(&[0, 0x64, 0x00, 0x1f, 0xff, 0xe0, 0]).is_err());
+        assert!(AvcConfig::parse(&[]).is_err());
+    }
+
+    #[test]
+    fn test_write_sample() {
+        let config = AvcConfig::parse(&avc_config_record(4)).unwrap();
+        let write = |sample: &[u8]| {
+            let mut output = Vec::new();
+            config.write_sample(sample, &mut output).map(|()| output)
+        };
+
+        // IDR pictures without their own SPS get the parameter sets of the configuration
+        assert_eq!(write(&avcc_sample(&[&IDR])).unwrap(), annex_b(&[&SPS, &PPS, &IDR]));
+        assert_eq!(write(&avcc_sample(&[&SPS, &PPS, &IDR])).unwrap(), annex_b(&[&SPS, &PPS, &IDR]));
+        assert_eq!(write(&avcc_sample(&[&SLICE, &SLICE])).unwrap(), annex_b(&[&SLICE, &SLICE]));
+        assert_eq!(write(&[]).unwrap(), Vec::<u8>::new());
+
+        let sample = avcc_sample(&[&SLICE]);
+        assert!(write(&sample[..sample.len() - 1]).is_err());
+        assert!(write(&sample[..2]).is_err());
+
+        let config = AvcConfig::parse(&avc_config_record(2)).unwrap();
+        let mut output = Vec::new();
+        config.write_sample(&[0, 2, 0x41, 0x9a], &mut output).unwrap();
+        assert_eq!(output, annex_b(&[&SLICE]));
+    }
+
+    #[test]
+    fn test_read_vint() {
+        let read = |bytes: &[u8], keep_marker| read_vint(&mut Cursor::new(bytes), keep_marker);
+
+        assert_eq!(read(&[0x81], false).unwrap(), Some((1, 1)));
+        assert_eq!(read(&[0x81], true).unwrap(), Some((0x81, 1)));
+        assert_eq!(read(&[0x40, 0x02], false).unwrap(), Some((2, 2)));
+        assert_eq!(read(&[0x1A, 0x45, 0xDF, 0xA3], true).unwrap(), Some((0x1A45_DFA3, 4)));
+        assert_eq!(read(&[0x01, 0, 0, 0, 0, 0, 1, 0], false).unwrap(), Some((256, 8)));
+        assert_eq!(read(&[0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], false).unwrap(), Some(((1 << 56) - 1, 8)));
+        assert_eq!(read(&[], false).unwrap(), None);
+        assert!(read(&[0x00], false).is_err());
+        assert!(read(&[0x40], false).is_err());
+    }
+
+    #[test]
+    fn test_detect_container() {
+        let detect = |bytes: &[u8]| Container::detect(&mut Cursor::new(bytes)).unwrap();
+
+        assert_eq!(detect(&matroska_file(&[], &[])), Some(Container::Matroska));
+        assert_eq!(detect(&mp4_box(b"ftyp", b"isom")), Some(Container::Mp4));
+        assert_eq!(detect(&annex_b(&[&SPS])), None);
+        assert_eq!(detect(&[]), None);
+    }
+
+    #[test]
+    fn test_demux_mp4() {
+        let samples = [avcc_sample(&[&IDR]), avcc_sample(&[&SLICE]), avcc_sample(&[&IDR])];
+        let ftyp = mp4_box(b"ftyp", b"isom\0\0\0\0");
+        let mdat = mp4_box(b"mdat", &samples.concat());
+        // Two samples in the first chunk and one in the second
+        let first_chunk = (ftyp.len() + 8) as u32;
+        let second_chunk = first_chunk + (samples[0].len() + samples[1].len()) as u32;
+
+        let full_box = |box_type: &[u8; 4], fields: &[u32]| mp4_box(box_type, &[0u32].iter().chain(fields).flat_map(|field| field.to_be_bytes()).collect::<Vec<_>>());
+        let sample_entry = mp4_box(b"avc1", &[vec![0; 78], mp4_box(b"avcC", &avc_config_record(4))].concat());
+        let stbl = mp4_box(
+            b"stbl",
+            &[
+                mp4_box(b"stsd", &[vec![0; 4], 1u32.to_be_bytes().to_vec(), sample_entry].concat()),
+                full_box(b"stsz", &[0, 3, samples[0].len() as u32, samples[1].len() as u32, samples[2].len() as u32]),
+                full_box(b"stsc", &[2, 1, 2, 1, 2, 1, 1]),
+                full_box(b"stco", &[2, first_chunk, second_chunk]),
+            ]
+            .concat(),
+        );
+        let hdlr = mp4_box(b"hdlr", &[vec![0; 8], b"vide".to_vec(), vec![0; 13]].concat());
+        let mdia = mp4_box(b"mdia", &[hdlr, mp4_box(b"minf", &stbl)].concat());
+        let moov = mp4_box(b"moov", &mp4_box(b"trak", &mdia));
+
+        let mut output = Vec::new();
+        demux_mp4(&mut Cursor::new([ftyp.clone(), mdat.clone(), moov].concat()), &mut output).unwrap();
+        assert_eq!(output, annex_b(&[&SPS, &PPS, &IDR, &SLICE, &SPS, &PPS, &IDR]));
+
+        let fragmented = mp4_box(b"moov", &mp4_box(b"mvex", &[]));
+        assert_eq!(
+            demux_mp4(&mut Cursor::new([ftyp.clone(), fragmented].concat()), &mut Vec::new()).unwrap_err().kind(),
+            io::ErrorKind::Unsupported
+        );
+        assert!(demux_mp4(&mut Cursor::new([ftyp, mdat].concat()), &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_demux_matroska() {
+        let tracks = [track_entry(1, "A_OPUS", &[]), track_entry(2, "V_MPEG4/ISO/AVC", &avc_config_record(4))];
+        let blocks = [
+            simple_block(2, 0x80, &avcc_sample(&[&IDR])),
+            simple_block(1, 0x80, &[0xfc, 0xff]),
+            ebml_element(ebml_id::BLOCK_GROUP, &ebml_element(ebml_id::BLOCK, &[0x82, 0, 0, 0, 0, 0, 0, 2, 0x41, 0x9a])),
+        ];
+        let mut output = Vec::new();
+        demux_matroska(&mut Cursor::new(matroska_file(&tracks, &blocks)), &mut output).unwrap();
+        assert_eq!(output, annex_b(&[&SPS, &PPS, &IDR, &SLICE]));
+
+        let laced = [simple_block(2, 0x82, &avcc_sample(&[&IDR]))];
+        assert_eq!(
+            demux_matroska(&mut Cursor::new(matroska_file(&tracks, &laced)), &mut Vec::new()).unwrap_err().kind(),
+            io::ErrorKind::Unsupported
+        );
+
+        let hevc = [track_entry(1, "V_MPEGH/ISO/HEVC", &[])];
+        assert_eq!(
+            demux_matroska(&mut Cursor::new(matroska_file(&hevc, &[])), &mut Vec::new()).unwrap_err().kind(),
+            io::ErrorKind::Unsupported
+        );
+        assert_eq!(
+            demux_matroska(&mut Cursor::new(matroska_file(&[], &[])), &mut Vec::new()).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn test_extracted_file_is_removed_on_drop() {
+        let input = std::env::temp_dir().join(format!("anime4k-player-test-{}.mkv", std::process::id()));
+        let tracks = [track_entry(1, "V_MPEG4/ISO/AVC", &avc_config_record(4))];
+        std::fs::write(&input, matroska_file(&tracks, &[simple_block(1, 0x80, &avcc_sample(&[&IDR]))])).unwrap();
+
+        let extracted = extract_to_temp_file(&input).unwrap().unwrap();
+        std::fs::remove_file(&input).unwrap();
+        assert_eq!(std::fs::read(extracted.path()).unwrap(), annex_b(&[&SPS, &PPS, &IDR]));
+
+        let path = extracted.path().to_path_buf();
+        drop(extracted);
+        assert!(!path.exists());
+    }
+}
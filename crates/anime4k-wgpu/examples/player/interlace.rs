@@ -0,0 +1,300 @@
+//! Detection and deinterlacing of interlaced sources
+//!
+//! Interlaced H.264 streams clear `frame_mbs_only_flag` in their sequence parameter set,
+//! which the `sps` module reads from the start of the bytestream. Deinterlacing runs on
+//! the GPU between the YUV conversion and the Anime4K passes, since the Anime4K shaders
+//! turn the comb pattern of woven fields into sharp horizontal stripes.
+//!
+//! The output keeps the frame rate of the input: each frame is rebuilt from its first
+//! field, and the second field only contributes in still areas with the adaptive method.
+//! Whether field-coded pictures decode at all is up to the Vulkan Video decoder.
+
+use std::str::FromStr;
+
+use wgpu::util::DeviceExt;
+
+/// Deinterlacing requested on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Deinterlace {
+    /// Never deinterlace
+    #[default]
+    Off,
+    /// Deinterlace with the adaptive method if the stream is detected as interlaced
+    Auto,
+    /// Always deinterlace with the given method
+    Force(DeinterlaceMethod),
+}
+
+impl Deinterlace {
+    /// Decides the deinterlacing method for a stream
+    ///
+    /// # Arguments
+    /// * `interlaced` - Whether the stream was detected as interlaced, or None if unknown
+    ///
+    /// # Returns
+    /// The method to deinterlace with, or None to show frames as decoded
+    pub fn resolve(self, interlaced: Option<bool>) -> Option<DeinterlaceMethod> {
+        match self {
+            Deinterlace::Off => None,
+            Deinterlace::Auto => (interlaced == Some(true)).then_some(DeinterlaceMethod::Adaptive),
+            Deinterlace::Force(method) => Some(method),
+        }
+    }
+}
+
+impl FromStr for Deinterlace {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" => Ok(Deinterlace::Off),
+            "auto" => Ok(Deinterlace::Auto),
+            "bob" => Ok(Deinterlace::Force(DeinterlaceMethod::Bob)),
+            "adaptive" => Ok(Deinterlace::Force(DeinterlaceMethod::Adaptive)),
+            _ => Err(format!("Unknown deinterlace mode '{s}' (expected off, auto, bob, or adaptive)")),
+        }
+    }
+}
+
+/// Method reconstructing the lines of the second field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeinterlaceMethod {
+    /// Interpolate every second-field line from the first field
+    Bob,
+    /// Interpolate in moving areas and keep the woven lines in still areas
+    Adaptive,
+}
+
+impl std::fmt::Display for DeinterlaceMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeinterlaceMethod::Bob => write!(f, "bob"),
+            DeinterlaceMethod::Adaptive => write!(f, "adaptive"),
+        }
+    }
+}
+
+/// Field displayed first, whose lines are kept by deinterlacing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FieldOrder {
+    /// Top field (even lines) first
+    #[default]
+    TopFirst,
+    /// Bottom field (odd lines) first
+    BottomFirst,
+}
+
+impl FromStr for FieldOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "top" => Ok(FieldOrder::TopFirst),
+            "bottom" => Ok(FieldOrder::BottomFirst),
+            _ => Err(format!("Unknown field order '{s}' (expected top or bottom)")),
+        }
+    }
+}
+
+/// Uniforms selecting the field and method of the deinterlace pass
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct DeinterlaceUniforms {
+    /// Parity of the lines of the first field
+    first_field: u32,
+    /// Whether to keep the woven lines in still areas (0 or 1)
+    adaptive: u32,
+}
+
+/// GPU deinterlace pass between the YUV conversion and Anime4K
+///
+/// The YUV conversion writes the woven frame into one of two textures, alternating on
+/// every new frame so the previous frame stays available for motion detection.
+pub struct Deinterlacer {
+    /// Compute pipeline rebuilding the second field
+    pipeline: wgpu::ComputePipeline,
+    /// Uniform buffer holding the field order and method
+    uniform_buffer: wgpu::Buffer,
+    /// Method the pass was created with
+    method: DeinterlaceMethod,
+    /// Woven frames written by the YUV conversion, created on the first `prepare`
+    woven_textures: Option<[wgpu::Texture; 2]>,
+    /// Index of the woven texture holding the current frame
+    current: usize,
+}
+
+impl Deinterlacer {
+    /// Creates the deinterlace pipeline
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device
+    /// * `method` - How the lines of the second field are reconstructed
+    /// * `field_order` - Which field is displayed first
+    pub fn new(device: &wgpu::Device, method: DeinterlaceMethod, field_order: FieldOrder) -> Self {
+        let shader_module = device.create_shader_module(wgpu::include_wgsl!("deinterlace.wgsl"));
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Deinterlace pipeline"),
+            layout: None,
+            module: &shader_module,
+            entry_point: None,
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        let uniforms = DeinterlaceUniforms {
+            first_field: match field_order {
+                FieldOrder::TopFirst => 0,
+                FieldOrder::BottomFirst => 1,
+            },
+            adaptive: (method == DeinterlaceMethod::Adaptive) as u32,
+        };
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Deinterlace uniform buffer"),
+            contents: bytemuck::cast_slice(&[uniforms]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        Self {
+            pipeline,
+            uniform_buffer,
+            method,
+            woven_textures: None,
+            current: 0,
+        }
+    }
+
+    /// Returns the method the pass was created with
+    pub fn method(&self) -> DeinterlaceMethod {
+        self.method
+    }
+
+    /// Selects the woven texture for the frame about to be rendered
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device
+    /// * `width` - Frame width in pixels
+    /// * `height` - Frame height in pixels
+    /// * `new_frame` - Whether the frame differs from the one rendered last, as opposed to a redraw
+    pub fn prepare(&mut self, device: &wgpu::Device, width: u32, height: u32, new_frame: bool) {
+        let size_changed = match &self.woven_textures {
+            Some(textures) => textures[0].width() != width || textures[0].height() != height,
+            None => true,
+        };
+        if size_changed {
+            let create_texture = |label| {
+                device.create_texture(&wgpu::TextureDescriptor {
+                    label: Some(label),
+                    size: wgpu::Extent3d {
+                        width,
+                        height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: wgpu::TextureFormat::Rgba32Float,
+                    usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                })
+            };
+            self.woven_textures = Some([create_texture("Woven texture A"), create_texture("Woven texture B")]);
+            self.current = 0;
+        } else if new_frame {
+            self.current ^= 1;
+        }
+    }
+
+    /// Returns the texture the YUV conversion writes the current woven frame into
+    ///
+    /// # Returns
+    /// The woven texture, or None before the first `prepare`
+    pub fn woven_texture(&self) -> Option<&wgpu::Texture> {
+        self.woven_textures.as_ref().map(|textures| &textures[self.current])
+    }
+
+    /// Records the deinterlace pass
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device
+    /// * `command_encoder` - Encoder to record the pass into, after the YUV conversion
+    /// * `output` - Texture receiving the deinterlaced frame, of the same size as the woven frame
+    pub fn pass(&self, device: &wgpu::Device, command_encoder: &mut wgpu::CommandEncoder, output: &wgpu::Texture) {
+        let Some(textures) = &self.woven_textures else {
+            return;
+        };
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Deinterlace bind group"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&textures[self.current].create_view(&Default::default())),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&textures[self.current ^ 1].create_view(&Default::default())),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&output.create_view(&Default::default())),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: self.uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut compute_pass = command_encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Deinterlace compute pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&self.pipeline);
+        compute_pass.set_bind_group(0, &bind_group, &[]);
+        compute_pass.dispatch_workgroups(output.width().div_ceil(8), output.height().div_ceil(8), 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        for interlaced in [None, Some(false), Some(true)] {
+            assert_eq!(Deinterlace::Off.resolve(interlaced), None);
+            assert_eq!(Deinterlace::Force(DeinterlaceMethod::Bob).resolve(interlaced), Some(DeinterlaceMethod::Bob));
+            assert_eq!(Deinterlace::Force(DeinterlaceMethod::Adaptive).resolve(interlaced), Some(DeinterlaceMethod::Adaptive));
+        }
+        assert_eq!(Deinterlace::Auto.resolve(Some(true)), Some(DeinterlaceMethod::Adaptive));
+        assert_eq!(Deinterlace::Auto.resolve(Some(false)), None);
+        // Streams whose SPS could not be read are shown as decoded
+        assert_eq!(Deinterlace::Auto.resolve(None), None);
+    }
+
+    #[test]
+    fn test_parse_deinterlace() {
+        assert_eq!("off".parse(), Ok(Deinterlace::Off));
+        assert_eq!("Auto".parse(), Ok(Deinterlace::Auto));
+        assert_eq!("bob".parse(), Ok(Deinterlace::Force(DeinterlaceMethod::Bob)));
+        assert_eq!("ADAPTIVE".parse(), Ok(Deinterlace::Force(DeinterlaceMethod::Adaptive)));
+        assert!("yadif".parse::<Deinterlace>().unwrap_err().contains("yadif"));
+        assert!("".parse::<Deinterlace>().is_err());
+    }
+
+    #[test]
+    fn test_parse_field_order() {
+        assert_eq!("top".parse(), Ok(FieldOrder::TopFirst));
+        assert_eq!("Bottom".parse(), Ok(FieldOrder::BottomFirst));
+        assert!("tff".parse::<FieldOrder>().unwrap_err().contains("tff"));
+    }
+
+    #[test]
+    fn test_method_display_parses_back() {
+        for method in [DeinterlaceMethod::Bob, DeinterlaceMethod::Adaptive] {
+            assert_eq!(method.to_string().parse(), Ok(Deinterlace::Force(method)));
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! Keyframe lookup in H.264 Annex B bytestreams
+//!
+//! Seeking restarts the decoder at the last IDR picture before the target. This module
+//! finds that picture by scanning NAL unit headers, without decoding anything, so it is
+//! kept apart from the Vulkan Video decoder.
+
+use std::io::{BufReader, Read};
+
+/// Position of a keyframe in an H.264 Annex B bytestream
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Keyframe {
+    /// Byte offset where decoding can start (the parameter sets preceding the IDR picture)
+    pub offset: u64,
+    /// Number of frames output before this keyframe
+    pub frame_number: u64,
+}
+
+/// Finds the last keyframe at or before a frame in an H.264 Annex B bytestream
+///
+/// Raw bytestreams carry no index, so this scans NAL unit headers from the beginning.
+/// Pictures are counted by their first slice (`first_mb_in_slice` of zero), and only IDR
+/// pictures preceded by a sequence parameter set qualify, so the decoder can start
+/// there without state from earlier in the stream. As nothing before an IDR picture is
+/// displayed after it, the decode-order picture count equals the output frame number.
+///
+/// # Arguments
+/// * `reader` - The bytestream, read from its beginning
+/// * `target_frame` - Number of the frame to seek to
+///
+/// # Returns
+/// The keyframe to start decoding from, which is the beginning of the stream if no
+/// later keyframe qualifies
+pub fn find_keyframe_before(reader: impl Read, target_frame: u64) -> std::io::Result<Keyframe> {
+    let mut keyframe = Keyframe::default();
+    let mut frame_number = 0u64;
+    // Offset of the first sequence parameter set since the last picture
+    let mut parameter_set_offset = None;
+
+    // Offset of the start code of the NAL unit whose header is read next, and its type once read
+    let mut nal_start = None;
+    let mut nal_type = None;
+    let mut zeros = 0u32;
+    let mut offset = 0u64;
+
+    for byte in BufReader::new(reader).bytes() {
+        let byte = byte?;
+
+        if let Some(nal_type_value) = nal_type.take() {
+            // First byte of the slice header; its top bit is set iff first_mb_in_slice is zero
+            if matches!(nal_type_value, 1 | 5) && byte & 0x80 != 0 {
+                if nal_type_value == 5
+                    && let Some(parameter_set_offset) = parameter_set_offset
+                {
+                    keyframe = Keyframe {
+                        offset: parameter_set_offset,
+                        frame_number,
+                    };
+                }
+                parameter_set_offset = None;
+                frame_number += 1;
+                if frame_number > target_frame {
+                    break;
+                }
+            }
+        } else if let Some(start) = nal_start.take() {
+            // NAL unit header
+            let nal_type_value = byte & 0x1f;
+            if nal_type_value == 7 && parameter_set_offset.is_none() {
+                parameter_set_offset = Some(start);
+            }
+            nal_type = Some(nal_type_value);
+        }
+
+        if byte == 0x01 && zeros >= 2 {
+            nal_start = Some(offset - 2);
+            nal_type = None;
+        }
+        zeros = if byte == 0x00 { zeros + 1 } else { 0 };
+        offset += 1;
+    }
+
+    Ok(keyframe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Appends a NAL unit with a three-byte start code
+    fn push_nal(stream: &mut Vec<u8>, header: u8, payload: &[u8]) {
+        stream.extend_from_slice(&[0x00, 0x00, 0x01, header]);
+        stream.extend_from_slice(payload);
+    }
+
+    /// Appends a single-slice picture, preceded by parameter sets if `parameter_sets` is set
+    ///
+    /// # Returns
+    /// The offset of the first NAL unit appended
+    fn push_picture(stream: &mut Vec<u8>, idr: bool, parameter_sets: bool) -> u64 {
+        let offset = stream.len() as u64;
+        if parameter_sets {
+            push_nal(stream, 0x67, &[0x42, 0xc0, 0x1e]);
+            push_nal(stream, 0x68, &[0xce, 0x3c, 0x80]);
+        }
+        // first_mb_in_slice of zero sets the top bit of the slice header
+        push_nal(stream, if idr { 0x65 } else { 0x41 }, &[0x88, 0x84, 0x21]);
+        offset
+    }
+
+    /// Builds a stream of two leading P pictures, then keyframes at pictures 2 and 5
+    ///
+    /// # Returns
+    /// The stream and the offsets of the two keyframes
+    fn create_stream() -> (Vec<u8>, u64, u64) {
+        let mut stream = Vec::new();
+        push_picture(&mut stream, false, false);
+        push_picture(&mut stream, false, false);
+        let first = push_picture(&mut stream, true, true);
+        push_picture(&mut stream, false, false);
+        push_picture(&mut stream, false, false);
+        let second = push_picture(&mut stream, true, true);
+        push_picture(&mut stream, false, false);
+        (stream, first, second)
+    }
+
+    /// Tests that a frame before the first keyframe seeks to the beginning of the stream
+    #[test]
+    fn test_before_first_keyframe() {
+        let (stream, _, _) = create_stream();
+        assert_eq!(find_keyframe_before(stream.as_slice(), 0).unwrap(), Keyframe::default());
+        assert_eq!(find_keyframe_before(stream.as_slice(), 1).unwrap(), Keyframe::default());
+    }
+
+    /// Tests that a keyframe is found when it is the target frame itself
+    #[test]
+    fn test_on_keyframe() {
+        let (stream, first, second) = create_stream();
+        assert_eq!(find_keyframe_before(stream.as_slice(), 2).unwrap(), Keyframe { offset: first, frame_number: 2 });
+        assert_eq!(find_keyframe_before(stream.as_slice(), 5).unwrap(), Keyframe { offset: second, frame_number: 5 });
+    }
+
+    /// Tests that a frame between keyframes seeks to the earlier one
+    #[test]
+    fn test_between_keyframes() {
+        let (stream, first, second) = create_stream();
+        assert_eq!(find_keyframe_before(stream.as_slice(), 3).unwrap(), Keyframe { offset: first, frame_number: 2 });
+        assert_eq!(find_keyframe_before(stream.as_slice(), 4).unwrap(), Keyframe { offset: first, frame_number: 2 });
+        assert_eq!(find_keyframe_before(stream.as_slice(), 6).unwrap(), Keyframe { offset: second, frame_number: 5 });
+        assert_eq!(find_keyframe_before(stream.as_slice(), 100).unwrap(), Keyframe { offset: second, frame_number: 5 });
+    }
+
+    /// Tests that IDR pictures without parameter sets are skipped and later slices are not counted
+    #[test]
+    fn test_unqualified_keyframes() {
+        let mut stream = Vec::new();
+        push_picture(&mut stream, true, true);
+        // Second slice of picture 0, with a nonzero first_mb_in_slice
+        push_nal(&mut stream, 0x65, &[0x48, 0x84, 0x21]);
+        push_picture(&mut stream, false, false);
+        push_picture(&mut stream, true, false);
+        push_picture(&mut stream, false, false);
+
+        assert_eq!(find_keyframe_before(stream.as_slice(), 2).unwrap(), Keyframe { offset: 0, frame_number: 0 });
+        assert_eq!(find_keyframe_before(stream.as_slice(), 3).unwrap(), Keyframe { offset: 0, frame_number: 0 });
+    }
+}
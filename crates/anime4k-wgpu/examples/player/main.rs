@@ -6,42 +6,89 @@
 //!
 //! # Features
 //! - Hardware-accelerated H.264 video decoding using Vulkan Video
+//! - Playback of raw H.264 bytestreams and of H.264 tracks in MP4 and Matroska files
 //! - Real-time Anime4K upscaling for improved video quality
 //! - Multi-threaded architecture with separate decoder and renderer threads
 //! - Interactive playback controls (pause/unpause)
 //!
 //! # Requirements
 //! - Vulkan-capable GPU with video decode support
-//! - H.264 video files for input
+//! - H.264 video files for input (.h264, or .mp4/.mkv with an H.264 track)
 //!
 //! # Usage
 //! ```bash
-//! cargo run --example player -- video.h264 60 [--paused]
+//! cargo run --example player -- video.h264 [60] [--paused] [--preset a] [--performance high] [--buffer-frames 3] [--split-submit] [--present-mode fifo|mailbox|immediate] [--color-config bt601.txt] [--chroma-upsampling bicubic] [--chroma-siting left] [--frame-pacing off|snap|pulldown] [--display-filter bilinear|bicubic|lanczos] [--record out.mp4] [--record-rate 12] [--adaptive-quality] [--deinterlace [auto|bob|adaptive]] [--field-order top|bottom]
 //! ```
 
+/// Adaptive performance preset selection from measured GPU time
+#[cfg(vulkan)]
+mod adaptive;
+
 /// Application event handling and user interface
 #[cfg(vulkan)]
 mod app;
 
+/// Custom YUV-to-RGB color conversion
+#[cfg(vulkan)]
+mod color;
+
+/// Frame pacing against the display refresh rate
+#[cfg(vulkan)]
+mod pacing;
+
 /// Hardware video decoding with Vulkan Video
 #[cfg(vulkan)]
 mod decoder;
 
+/// Extraction of H.264 tracks from MP4 and Matroska files
+#[cfg(vulkan)]
+mod demux;
+
+/// Detection and deinterlacing of interlaced sources
+#[cfg(vulkan)]
+mod interlace;
+
+/// Keyframe lookup in H.264 bytestreams for seeking
+#[cfg(vulkan)]
+mod keyframe;
+
+/// On-screen text overlay for status information
+#[cfg(vulkan)]
+mod overlay;
+
 /// Core video playback and rendering pipeline
 #[cfg(vulkan)]
 mod player;
 
+/// Recording of the displayed frames to a video file
+#[cfg(vulkan)]
+mod record;
+
+/// Parsing of H.264 sequence parameter sets
+#[cfg(vulkan)]
+mod sps;
+
 /// Main entry point for Vulkan-enabled builds
 ///
 /// Runs the video player application when Vulkan support is available.
 /// This is the primary entry point that most users will encounter.
 #[cfg(vulkan)]
 fn main() -> Result<(), winit::error::EventLoopError> {
+    use crate::adaptive::AdaptiveQuality;
     use crate::app::VideoPlayerApp;
+    use crate::color::{ChromaSiting, ChromaUpsampling, ColorConfig};
+    use crate::interlace::{Deinterlace, FieldOrder};
+    use crate::pacing::FramePacing;
+    use crate::player::{DEFAULT_FRAME_BUFFER_LENGTH, DisplayFilter};
+    use crate::record::Recorder;
+    use anime4k_wgpu::presets::{Anime4KPerformancePreset, Anime4KPreset};
     use clap::Parser;
-    use std::path::PathBuf;
+    use std::{path::PathBuf, time::Duration};
     use winit::event_loop::{ControlFlow, EventLoop};
 
+    /// Framerate used when neither the command line nor the stream gives one
+    const DEFAULT_FRAMERATE: u32 = 30;
+
     /// Command-line arguments for the video player
     ///
     /// Defines the interface for controlling video playback parameters
@@ -49,15 +96,156 @@ fn main() -> Result<(), winit::error::EventLoopError> {
     #[derive(Parser)]
     #[command(version, about, long_about=None)]
     pub struct Args {
-        /// Path to the video file to play (.h264)
+        /// Path to the video file to play (.h264, or .mp4/.mkv with an H.264 track)
         filename: PathBuf,
 
-        /// Framerate of the video in frames per second
-        framerate: u32,
+        /// Framerate of the video in frames per second, overriding the one in the stream's timing information
+        framerate: Option<u32>,
 
         /// Start the video player in paused state
         #[arg(long, short)]
         paused: bool,
+
+        /// Anime4K preset to start with (a, b, c, aa, bb, ca, nearest); Anime4K starts disabled if omitted
+        #[arg(long)]
+        preset: Option<Anime4KPreset>,
+
+        /// Performance preset to start with (light, medium, high, ultra, extreme)
+        #[arg(long, short = 'e', default_value = "light")]
+        performance: Anime4KPerformancePreset,
+
+        /// Number of decoded frames to buffer ahead of the renderer
+        ///
+        /// Higher values smooth out bursty decoding at the cost of latency and memory.
+        /// Values below 1 are clamped to 1.
+        #[arg(long, short, default_value_t = DEFAULT_FRAME_BUFFER_LENGTH)]
+        buffer_frames: usize,
+
+        /// Submit the YUV conversion and Anime4K passes separately from the present pass
+        ///
+        /// Lets the upscale start while the renderer waits for the next swapchain image.
+        /// Not all devices benefit, so this is disabled by default.
+        #[arg(long)]
+        split_submit: bool,
+
+        /// Surface present mode (fifo, mailbox, or immediate)
+        ///
+        /// Use `immediate` to measure upscale throughput without VSync, or `fifo` for smooth viewing.
+        /// Falls back to another supported mode with a warning if the surface does not support it.
+        /// Defaults to the first mode reported by the surface.
+        #[arg(long, value_parser = parse_present_mode)]
+        present_mode: Option<wgpu::PresentMode>,
+
+        /// Loop playback over a time range, given as START,END in seconds (e.g. 12.5,20)
+        ///
+        /// Useful for comparing presets on the same clip. A range whose start is not
+        /// before its end disables looping.
+        #[arg(long = "loop", value_name = "START,END", value_parser = parse_loop_range)]
+        loop_range: Option<(Duration, Duration)>,
+
+        /// Color config file with a custom YUV-to-RGB matrix and sample range, replacing BT.709 full range
+        ///
+        /// Contains `matrix = r0c0, r0c1, r0c2; r1c0, ...; r2c0, ...` and optionally
+        /// `range = full|limited`. A matrix that is not invertible or has implausible
+        /// coefficients is ignored with a warning.
+        #[arg(long)]
+        color_config: Option<PathBuf>,
+
+        /// Chroma upsampling filter for 4:2:0 frames (bilinear or bicubic), overriding the color config
+        ///
+        /// `bicubic` uses a separable Catmull-Rom filter, which reduces color bleeding
+        /// around thin colored lines at the cost of 16 texture loads per pixel.
+        #[arg(long)]
+        chroma_upsampling: Option<ChromaUpsampling>,
+
+        /// Chroma sample siting of the source (center, left, or top-left), overriding the color config
+        ///
+        /// Defaults to `center`. Most H.264 and HEVC streams use `left`, and a wrong
+        /// siting shifts colors a quarter of a chroma sample against the luma.
+        #[arg(long)]
+        chroma_siting: Option<ChromaSiting>,
+
+        /// Frame pacing against the display refresh rate (off, snap, or pulldown)
+        ///
+        /// `snap` quantizes the playback clock to refresh intervals so frames are held for
+        /// consistent numbers of refreshes, and `pulldown` also adjusts the speed by up to
+        /// 0.5% for a steady cadence such as 3:2 for 24 fps on 60 Hz. Both reduce judder
+        /// at the cost of up to one refresh interval of added latency.
+        #[arg(long, default_value = "off")]
+        frame_pacing: FramePacing,
+
+        /// Filter resampling the final image to the window (bilinear, bicubic, or lanczos)
+        ///
+        /// `bicubic` (Catmull-Rom) and `lanczos` (Lanczos-3) give sharper results than
+        /// bilinear when the window is much larger than the upscaled video, at the cost of
+        /// 16 or 36 texture loads per screen pixel.
+        #[arg(long, default_value = "bilinear")]
+        display_filter: DisplayFilter,
+
+        /// Record the displayed frames (after Anime4K, before scaling to the window) to a video file
+        ///
+        /// Frames are encoded with H.264 by an `ffmpeg` executable on the PATH, and the
+        /// container follows the file extension. Reading back each frame slows playback,
+        /// and frames the player drops are filled in by repeating the previous one.
+        #[arg(long, value_name = "FILE")]
+        record: Option<PathBuf>,
+
+        /// Framerate of the recording, defaulting to the playback framerate
+        ///
+        /// A lower rate reduces the readback and encoding cost of recording.
+        #[arg(long, value_name = "FPS", requires = "record", value_parser = parse_record_rate)]
+        record_rate: Option<f64>,
+
+        /// Step the performance preset down when Anime4K exceeds the frame time budget, and back up with headroom
+        ///
+        /// The GPU time of the Anime4K passes is sampled twice a second with timestamp
+        /// queries and compared with 75% of the frame interval. The preset never goes above
+        /// the one selected with `--performance` or Shift+1-5. Each sample runs the
+        /// passes one extra time.
+        #[arg(long)]
+        adaptive_quality: bool,
+
+        /// Deinterlace before Anime4K (off, auto, bob, or adaptive); `--deinterlace` alone means auto
+        ///
+        /// `auto` deinterlaces with the adaptive method when the sequence parameter set marks
+        /// the stream as interlaced. `bob` interpolates every line of the second field, and
+        /// `adaptive` keeps the woven lines where the frame does not move. Frames are rebuilt
+        /// from their first field, so the frame rate stays that of the input.
+        #[arg(long, num_args = 0..=1, default_value = "off", default_missing_value = "auto")]
+        deinterlace: Deinterlace,
+
+        /// Field displayed first in interlaced frames (top or bottom)
+        #[arg(long, default_value = "top")]
+        field_order: FieldOrder,
+    }
+
+    /// Parses a present mode name from the command line
+    fn parse_present_mode(value: &str) -> Result<wgpu::PresentMode, String> {
+        match value.to_ascii_lowercase().as_str() {
+            "fifo" => Ok(wgpu::PresentMode::Fifo),
+            "mailbox" => Ok(wgpu::PresentMode::Mailbox),
+            "immediate" => Ok(wgpu::PresentMode::Immediate),
+            _ => Err(format!("unknown present mode '{value}' (expected fifo, mailbox, or immediate)")),
+        }
+    }
+
+    /// Parses a loop range in seconds from the command line
+    fn parse_loop_range(value: &str) -> Result<(Duration, Duration), String> {
+        let (start, end) = value.split_once(',').ok_or_else(|| format!("invalid loop range '{value}' (expected START,END in seconds)"))?;
+        let parse = |seconds: &str| {
+            let seconds = seconds.trim();
+            let value = seconds.parse::<f64>().map_err(|e| format!("invalid time '{seconds}': {e}"))?;
+            Duration::try_from_secs_f64(value).map_err(|e| format!("invalid time '{seconds}': {e}"))
+        };
+        Ok((parse(start)?, parse(end)?))
+    }
+
+    /// Parses a positive recording framerate from the command line
+    fn parse_record_rate(value: &str) -> Result<f64, String> {
+        match value.parse::<f64>() {
+            Ok(rate) if rate.is_finite() && rate > 0.0 => Ok(rate),
+            _ => Err(format!("invalid record rate '{value}' (expected a positive number of frames per second)")),
+        }
     }
 
     let args = Args::parse();
@@ -68,12 +256,84 @@ fn main() -> Result<(), winit::error::EventLoopError> {
 
     tracing::info!("Starting video player...");
 
+    let mut color_config = match &args.color_config {
+        Some(path) => ColorConfig::load(path).unwrap_or_else(|e| {
+            tracing::error!("Failed to load color config {}: {e}", path.display());
+            std::process::exit(1);
+        }),
+        None => ColorConfig::default(),
+    };
+    if let Some(chroma_upsampling) = args.chroma_upsampling {
+        color_config.chroma_upsampling = chroma_upsampling;
+    }
+    if let Some(chroma_siting) = args.chroma_siting {
+        color_config.chroma_siting = chroma_siting;
+    }
+
+    if !anime4k_wgpu::vulkan_video_available() {
+        tracing::error!("No Vulkan adapter found, hardware video decoding is unavailable");
+        std::process::exit(1);
+    }
+
+    // Containers are converted to a raw bytestream up front, which the decoder and seeking work on
+    let extracted = demux::extract_to_temp_file(&args.filename).unwrap_or_else(|e| {
+        tracing::error!("Failed to read the H.264 track of {}: {e}", args.filename.display());
+        std::process::exit(1);
+    });
+    if let Some(extracted) = &extracted {
+        tracing::info!("Extracted the H.264 track of {} to {}", args.filename.display(), extracted.path().display());
+    }
+    let filename = extracted.as_ref().map_or(args.filename.as_path(), demux::TempFile::path);
+
+    let sps = std::fs::File::open(filename).and_then(sps::detect_sequence_parameter_set).unwrap_or_else(|e| {
+        tracing::warn!("Failed to read {} to detect the stream properties: {e}", filename.display());
+        None
+    });
+    let interlaced = sps.map(|sps| sps.interlaced);
+
+    // Playback runs at whole frame rates, so fractional rates such as 23.976 are rounded
+    let detected_framerate = sps.and_then(|sps| sps.framerate).map(|framerate| framerate.round() as u32).filter(|&framerate| framerate > 0);
+    let framerate = match (args.framerate, detected_framerate) {
+        (Some(framerate), _) => framerate,
+        (None, Some(framerate)) => {
+            tracing::info!("Playing at {framerate} fps from the stream's timing information");
+            framerate
+        }
+        (None, None) => {
+            tracing::warn!("The stream has no timing information; playing at {DEFAULT_FRAMERATE} fps, pass a framerate to override");
+            DEFAULT_FRAMERATE
+        }
+    };
+    let deinterlace = args.deinterlace.resolve(interlaced);
+    match (interlaced, deinterlace) {
+        (Some(true), None) => tracing::warn!("The video is interlaced; use --deinterlace to remove combing artifacts"),
+        (_, Some(method)) => tracing::info!("Deinterlacing with the {method} method"),
+        _ => {}
+    }
+
     // Create window and event loop for user interface
     let event_loop = EventLoop::new().unwrap();
     event_loop.set_control_flow(ControlFlow::Poll);
 
     // Initialize and run the video player application
-    let mut app = VideoPlayerApp::new(&args.filename, args.framerate, args.paused);
+    let mut app = VideoPlayerApp::new(
+        filename,
+        framerate,
+        args.paused,
+        args.preset,
+        args.performance,
+        args.buffer_frames,
+        args.split_submit,
+        args.present_mode,
+        args.loop_range,
+        color_config,
+        args.frame_pacing,
+        args.display_filter,
+        args.record.as_deref().map(|path| Recorder::new(path, args.record_rate.unwrap_or(framerate as f64))),
+        args.adaptive_quality.then(|| AdaptiveQuality::new(framerate, args.performance)),
+        deinterlace.map(|method| (method, args.field_order)),
+    );
+    // The extracted bytestream is removed when `extracted` is dropped
     event_loop.run_app(&mut app)
 }
 
@@ -0,0 +1,273 @@
+//! On-screen text overlay
+//!
+//! This module renders a few lines of status text with a built-in 5x7 bitmap font.
+//! The text is rasterized on the CPU whenever it changes and composited over the
+//! final frame, so it shows up in screen recordings unlike the window title.
+
+/// Glyph width in font pixels
+const GLYPH_WIDTH: u32 = 5;
+/// Glyph height in font pixels
+const GLYPH_HEIGHT: u32 = 7;
+/// Horizontal distance between glyphs in font pixels
+const CELL_WIDTH: u32 = GLYPH_WIDTH + 1;
+/// Vertical distance between lines in font pixels
+const CELL_HEIGHT: u32 = GLYPH_HEIGHT + 2;
+/// Background margin around the text in font pixels
+const PADDING: u32 = 2;
+/// Screen pixels per font pixel
+const SCALE: u32 = 2;
+/// Distance of the overlay from the top-left corner of the surface in screen pixels
+const MARGIN: u32 = 8;
+
+/// Text color (RGBA)
+const TEXT_COLOR: [u8; 4] = [255, 255, 255, 255];
+/// Background color (RGBA), translucent so the frame stays visible behind the text
+const BACKGROUND_COLOR: [u8; 4] = [0, 0, 0, 160];
+
+/// Returns the rows of a glyph, top to bottom, with the leftmost pixel in bit 4
+///
+/// Lowercase letters are drawn as uppercase; unsupported characters are drawn as `?`.
+fn glyph(c: char) -> [u8; 7] {
+    match c.to_ascii_uppercase() {
+        'A' => [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x0A, 0x04, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        ' ' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        '>' => [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08],
+        _ => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    }
+}
+
+/// Rasterizes lines of text into an RGBA8 image
+///
+/// # Arguments
+/// * `lines` - Lines of text to draw
+///
+/// # Returns
+/// A tuple of (width, height, RGBA8 pixels) at one pixel per font pixel
+fn rasterize(lines: &[String]) -> (u32, u32, Vec<u8>) {
+    let columns = lines.iter().map(|line| line.chars().count() as u32).max().unwrap_or(0).max(1);
+    let width = columns * CELL_WIDTH - 1 + PADDING * 2;
+    let height = lines.len().max(1) as u32 * CELL_HEIGHT - (CELL_HEIGHT - GLYPH_HEIGHT) + PADDING * 2;
+
+    let mut pixels = BACKGROUND_COLOR.repeat((width * height) as usize);
+    for (row, line) in lines.iter().enumerate() {
+        for (column, c) in line.chars().enumerate() {
+            let origin_x = PADDING + column as u32 * CELL_WIDTH;
+            let origin_y = PADDING + row as u32 * CELL_HEIGHT;
+            for (y, bits) in glyph(c).into_iter().enumerate() {
+                for x in 0..GLYPH_WIDTH {
+                    if bits & (1 << (GLYPH_WIDTH - 1 - x)) != 0 {
+                        let index = (((origin_y + y as u32) * width + origin_x + x) * 4) as usize;
+                        pixels[index..index + 4].copy_from_slice(&TEXT_COLOR);
+                    }
+                }
+            }
+        }
+    }
+
+    (width, height, pixels)
+}
+
+/// Uniforms for positioning the overlay rectangle
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct OverlayUniforms {
+    /// Rectangle in normalized device coordinates (left, top, right, bottom)
+    rect: [f32; 4],
+}
+
+/// Text overlay composited over the final frame
+pub struct Overlay {
+    /// Render pipeline drawing the text texture with alpha blending
+    pipeline: wgpu::RenderPipeline,
+    /// Uniform buffer holding the overlay rectangle
+    uniform_buffer: wgpu::Buffer,
+    /// Rasterized text texture and its bind group, created on the first `set_lines`
+    texture: Option<(wgpu::Texture, wgpu::BindGroup)>,
+    /// Lines currently rasterized into the texture
+    lines: Vec<String>,
+}
+
+impl Overlay {
+    /// Creates the overlay pipeline
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device
+    /// * `format` - Format of the render target the overlay is drawn onto
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let shader_module = device.create_shader_module(wgpu::include_wgsl!("overlay.wgsl"));
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Overlay pipeline"),
+            layout: None,
+            cache: None,
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                buffers: &[],
+                compilation_options: Default::default(),
+                entry_point: None,
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+                entry_point: None,
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            depth_stencil: None,
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Overlay uniform buffer"),
+            size: std::mem::size_of::<OverlayUniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            pipeline,
+            uniform_buffer,
+            texture: None,
+            lines: Vec::new(),
+        }
+    }
+
+    /// Sets the text to display, rasterizing it again only if it changed
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device
+    /// * `queue` - The wgpu command queue
+    /// * `lines` - Lines of text to display
+    pub fn set_lines(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, lines: Vec<String>) {
+        if self.texture.is_some() && self.lines == lines {
+            return;
+        }
+
+        let (width, height, pixels) = rasterize(&lines);
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+
+        // Reuse the texture unless the text dimensions changed
+        let texture = match self.texture.take() {
+            Some((texture, bind_group)) if texture.size() == size => (texture, bind_group),
+            _ => {
+                let texture = device.create_texture(&wgpu::TextureDescriptor {
+                    label: Some("Overlay texture"),
+                    size,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: wgpu::TextureFormat::Rgba8Unorm,
+                    usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                    view_formats: &[],
+                });
+                let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Overlay bind group"),
+                    layout: &self.pipeline.get_bind_group_layout(0),
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(&texture.create_view(&Default::default())),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: self.uniform_buffer.as_entire_binding(),
+                        },
+                    ],
+                });
+                (texture, bind_group)
+            }
+        };
+
+        queue.write_texture(
+            texture.0.as_image_copy(),
+            &pixels,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(width * 4),
+                rows_per_image: Some(height),
+            },
+            size,
+        );
+
+        self.texture = Some(texture);
+        self.lines = lines;
+    }
+
+    /// Draws the overlay in the top-left corner of the render target
+    ///
+    /// # Arguments
+    /// * `queue` - The wgpu command queue, used to update the overlay position
+    /// * `render_pass` - Render pass targeting the surface, after the frame has been drawn
+    /// * `target_size` - Size of the render target in pixels
+    pub fn draw(&self, queue: &wgpu::Queue, render_pass: &mut wgpu::RenderPass<'_>, target_size: (u32, u32)) {
+        let Some((texture, bind_group)) = &self.texture else {
+            return;
+        };
+
+        let to_ndc_x = |x: u32| x as f32 / target_size.0 as f32 * 2.0 - 1.0;
+        let to_ndc_y = |y: u32| 1.0 - y as f32 / target_size.1 as f32 * 2.0;
+        let uniforms = OverlayUniforms {
+            rect: [
+                to_ndc_x(MARGIN),
+                to_ndc_y(MARGIN),
+                to_ndc_x(MARGIN + texture.width() * SCALE),
+                to_ndc_y(MARGIN + texture.height() * SCALE),
+            ],
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, bind_group, &[]);
+        render_pass.draw(0..6, 0..1);
+    }
+}
@@ -0,0 +1,34 @@
+// Text overlay shader, compositing a pre-rasterized text texture over the frame
+
+struct OverlayUniforms {
+    // Overlay rectangle in normalized device coordinates (left, top, right, bottom)
+    rect: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+
+@group(0) @binding(0) var overlay_texture: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> overlay_uniforms: OverlayUniforms;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    // Two triangles covering the rectangle, generated from the vertex index
+    let corners = array(vec2f(0.0, 0.0), vec2f(0.0, 1.0), vec2f(1.0, 0.0), vec2f(1.0, 0.0), vec2f(0.0, 1.0), vec2f(1.0, 1.0));
+    let corner = corners[vertex_index];
+
+    var output: VertexOutput;
+    output.position = vec4(mix(overlay_uniforms.rect.xy, overlay_uniforms.rect.zw, corner), 0.0, 1.0);
+    output.tex_coords = corner;
+    return output;
+}
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    // Nearest texel lookup keeps the bitmap font crisp at integer scales
+    let dims = textureDimensions(overlay_texture);
+    let texel = min(vec2u(input.tex_coords * vec2f(dims)), dims - 1);
+    return textureLoad(overlay_texture, texel, 0);
+}
@@ -0,0 +1,123 @@
+//! Frame pacing against the display refresh rate
+//!
+//! By default a frame is shown on the first redraw after its timestamp, so when the
+//! framerate does not divide the refresh rate, small variations in redraw timing decide
+//! whether a frame lands on one refresh or the next and frames are held for uneven
+//! numbers of refreshes (judder).
+//!
+//! Snapping quantizes the playback clock to refresh intervals, so the refresh a frame
+//! first appears on depends only on its timestamp. Pulldown additionally adjusts the
+//! playback speed slightly so that the refresh rate is an exact multiple of half the
+//! framerate, giving a steady cadence such as 3:2 for 24 fps on 60 Hz. Both show frames
+//! up to one refresh interval later than without pacing.
+
+use std::{str::FromStr, time::Duration};
+
+/// Refresh rate assumed when the monitor does not report one
+const DEFAULT_REFRESH_RATE: f64 = 60.0;
+
+/// Largest speed change pulldown applies, as a fraction of the original speed
+///
+/// Covers NTSC rates such as 23.976 fps on 60 Hz or 24 fps on 59.94 Hz.
+const MAX_PULLDOWN_SPEED_CHANGE: f64 = 0.005;
+
+/// Strategy for aligning frame presentation with display refreshes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FramePacing {
+    /// Show each frame on the first redraw after its timestamp
+    #[default]
+    Off,
+    /// Quantize the playback clock to refresh intervals
+    Snap,
+    /// Snap, and adjust the speed slightly for a steady repeat cadence such as 3:2
+    Pulldown,
+}
+
+impl FromStr for FramePacing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" => Ok(FramePacing::Off),
+            "snap" => Ok(FramePacing::Snap),
+            "pulldown" => Ok(FramePacing::Pulldown),
+            _ => Err(format!("Unknown frame pacing '{s}' (expected off, snap, or pulldown)")),
+        }
+    }
+}
+
+/// Maps the wall clock of playback to the media clock frames are scheduled on
+#[derive(Debug, Clone, Copy)]
+pub struct FramePacer {
+    /// Display refresh interval in media time, or None without snapping
+    refresh_interval: Option<Duration>,
+    /// Media time advanced per unit of wall time
+    speed: f64,
+}
+
+impl Default for FramePacer {
+    fn default() -> Self {
+        Self { refresh_interval: None, speed: 1.0 }
+    }
+}
+
+impl FramePacer {
+    /// Creates a pacer for a video framerate on a display
+    ///
+    /// Pulldown falls back to snapping with a warning when the refresh rate is not close
+    /// to a multiple of half the framerate.
+    ///
+    /// # Arguments
+    /// * `pacing` - The pacing strategy
+    /// * `refresh_rate` - Display refresh rate in Hz, or None if unknown
+    /// * `framerate` - Video framerate in frames per second
+    pub fn new(pacing: FramePacing, refresh_rate: Option<f64>, framerate: u32) -> Self {
+        if pacing == FramePacing::Off {
+            return Self::default();
+        }
+
+        let refresh_rate = refresh_rate.unwrap_or_else(|| {
+            tracing::warn!("Monitor refresh rate unknown, assuming {DEFAULT_REFRESH_RATE} Hz for frame pacing");
+            DEFAULT_REFRESH_RATE
+        });
+
+        let mut speed = 1.0;
+        if pacing == FramePacing::Pulldown {
+            // Refreshes per frame, rounded to a cadence of whole and half refreshes (e.g. 2.5 for 3:2)
+            let repeats = ((refresh_rate / framerate as f64) * 2.0).round().max(1.0) / 2.0;
+            speed = refresh_rate / repeats / framerate as f64;
+            if (speed - 1.0).abs() > MAX_PULLDOWN_SPEED_CHANGE {
+                tracing::warn!("No pulldown cadence for {framerate} fps on {refresh_rate:.3} Hz, snapping to refreshes instead");
+                speed = 1.0;
+            } else {
+                tracing::info!("Pulldown: {repeats} refreshes per frame at {:.3}% speed", speed * 100.0);
+            }
+        }
+
+        Self {
+            refresh_interval: Some(Duration::from_secs_f64(speed / refresh_rate)),
+            speed,
+        }
+    }
+
+    /// Converts elapsed playback wall time to the media time frames are due against
+    ///
+    /// # Arguments
+    /// * `wall_time` - Wall time played, excluding pauses
+    pub fn media_time(&self, wall_time: Duration) -> Duration {
+        let media_time = wall_time.mul_f64(self.speed);
+        match self.refresh_interval {
+            // Round down to the start of the current refresh interval
+            Some(interval) => interval * (media_time.as_nanos() / interval.as_nanos()) as u32,
+            None => media_time,
+        }
+    }
+
+    /// Converts a media time span back to wall time, for re-anchoring the clock
+    ///
+    /// # Arguments
+    /// * `media_time` - Span of media time
+    pub fn wall_time(&self, media_time: Duration) -> Duration {
+        media_time.div_f64(self.speed)
+    }
+}
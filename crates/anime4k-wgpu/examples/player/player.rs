@@ -4,14 +4,27 @@
 //! Vulkan-based video decoding, GPU-accelerated YUV-to-RGB conversion, and
 //! Anime4K upscaling integration.
 
-use super::decoder::{FrameWithPts, run_decoder};
+use super::{
+    adaptive::AdaptiveQuality,
+    color::ColorConfig,
+    decoder::{FrameWithPts, run_decoder},
+    interlace::{DeinterlaceMethod, Deinterlacer, FieldOrder},
+    keyframe::{Keyframe, find_keyframe_before},
+    overlay::Overlay,
+    pacing::{FramePacer, FramePacing},
+    record::Recorder,
+};
 use anime4k_wgpu::{
-    PipelineExecutor,
-    presets::{Anime4KPerformancePreset, Anime4KPreset},
+    Anime4kExecutor, PipelineExecutor, SamplerCache, SamplerKey,
+    presets::{Anime4KPerformancePreset, Anime4KPreset, Anime4kSettings},
 };
-use std::sync::{
-    Arc,
-    mpsc::{self, Receiver},
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        mpsc::{self, Receiver, TryRecvError},
+    },
+    time::Duration,
 };
 use vk_video::{VulkanDevice, VulkanInstance};
 use wgpu::util::DeviceExt;
@@ -21,12 +34,72 @@ use winit::{
     window::{Window, WindowAttributes},
 };
 
-/// Number of frames to buffer between decoder and renderer
+/// Default number of frames to buffer between decoder and renderer
 ///
 /// This provides a small buffer to smooth out timing variations between
-/// the decoder and renderer threads. A value of 3 provides good balance
-/// between latency and smooth playback.
-const FRAME_BUFFER_LENGTH: usize = 3;
+/// the decoder and renderer threads. Larger values absorb bursty decoding
+/// at the cost of added latency and memory for each buffered frame;
+/// a value of 3 provides good balance between latency and smooth playback.
+pub const DEFAULT_FRAME_BUFFER_LENGTH: usize = 3;
+
+/// Minimum number of frames to buffer between decoder and renderer
+///
+/// A zero-capacity channel would make every send a rendezvous with the renderer,
+/// stalling the decoder whenever a frame is not being polled, so at least one slot is kept.
+const MIN_FRAME_BUFFER_LENGTH: usize = 1;
+
+/// Interval between periodic frame statistics summaries in the log and window title
+const FRAME_STATS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Interval over which the frame rate shown in the overlay is averaged
+const OVERLAY_FPS_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Counts of decoded frames displayed and of frames that arrived late during playback
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    /// Frames that became the displayed frame
+    pub shown: u64,
+    /// Frames whose presentation time had already passed when they were received
+    ///
+    /// Late frames are still shown, one per redraw, so playback falls behind instead of skipping.
+    pub late: u64,
+}
+
+/// Progress of a seek started by `VideoPlayback::seek`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SeekOutcome {
+    /// The decoder has not reached the target yet
+    Pending,
+    /// The first frame at or after the target is now the current frame
+    Found,
+    /// The stream ended before the target, so the current frame is unchanged
+    PastEnd,
+}
+
+/// Rendering stage whose texture can be captured with `snapshot_stage`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Decoded video frame before color conversion (R = Y, G = U, B = V)
+    Yuv,
+    /// Intermediate sRGB texture before Anime4K processing
+    Srgb,
+    /// Anime4K output texture (only available while Anime4K is enabled)
+    Anime4kOutput,
+}
+
+impl Stage {
+    /// All capturable stages in pipeline order
+    pub const ALL: [Stage; 3] = [Stage::Yuv, Stage::Srgb, Stage::Anime4kOutput];
+
+    /// Returns a short identifier for this stage, suitable for file names
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::Yuv => "yuv",
+            Stage::Srgb => "srgb",
+            Stage::Anime4kOutput => "anime4k",
+        }
+    }
+}
 
 /// Workgroup size for the YUV to sRGB compute shader (X dimension)
 const YUV_COMPUTE_WORKGROUP_SIZE_X: u32 = 8;
@@ -65,6 +138,18 @@ pub struct PlayerContext {
     /// and cleared after successful rendering. Prevents unnecessary GPU work when
     /// no visual updates are required.
     needs_redraw: bool,
+
+    /// Encoder of the displayed frames, if recording
+    recorder: Option<Recorder>,
+
+    /// Controller stepping the performance preset with the measured GPU time, if enabled
+    adaptive_quality: Option<AdaptiveQuality>,
+
+    /// Frame kept on screen while frozen, if frozen
+    ///
+    /// Playback continues underneath, so unfreezing shows whichever frame is current
+    /// by then. Redraws (e.g. after a preset change) render this frame instead.
+    frozen_frame: Option<wgpu::Texture>,
 }
 
 impl PlayerContext {
@@ -75,13 +160,41 @@ impl PlayerContext {
     ///
     /// # Arguments
     /// * `event_loop` - The active event loop for window management
-    /// * `reader` - Input stream containing the video data
+    /// * `filename` - Path to the H.264 bytestream to play, reopened when seeking
     /// * `framerate` - Target playback framerate in FPS
     /// * `start_paused` - Whether to begin playback in paused state
+    /// * `start_preset` - Anime4K preset to start with, or None to start with Anime4K disabled
+    /// * `start_performance_preset` - Anime4K performance preset to start with
+    /// * `frame_buffer_length` - Number of decoded frames to buffer ahead of the renderer
+    /// * `split_submit` - Whether to submit compute stages separately from the present pass
+    /// * `present_mode` - Requested surface present mode, or None for the surface's default
+    /// * `color_config` - YUV-to-RGB conversion matrix and sample range
+    /// * `frame_pacing` - How frame presentation is aligned with display refreshes
+    /// * `display_filter` - Filter resampling the final image to the window
+    /// * `recorder` - Encoder to record the displayed frames with, or None to only play
+    /// * `adaptive_quality` - Controller adjusting the performance preset to the GPU load, or None to keep it fixed
+    /// * `deinterlace` - Method and field order to deinterlace with, or None to show frames as decoded
     ///
     /// # Returns
     /// A fully initialized player context ready for frame rendering
-    pub fn new(event_loop: &ActiveEventLoop, reader: impl std::io::Read + Send + 'static, framerate: u32, start_paused: bool) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        event_loop: &ActiveEventLoop,
+        filename: &Path,
+        framerate: u32,
+        start_paused: bool,
+        start_preset: Option<Anime4KPreset>,
+        start_performance_preset: Anime4KPerformancePreset,
+        frame_buffer_length: usize,
+        split_submit: bool,
+        present_mode: Option<wgpu::PresentMode>,
+        color_config: &ColorConfig,
+        frame_pacing: FramePacing,
+        display_filter: DisplayFilter,
+        recorder: Option<Recorder>,
+        adaptive_quality: Option<AdaptiveQuality>,
+        deinterlace: Option<(DeinterlaceMethod, FieldOrder)>,
+    ) -> Self {
         let window = Arc::new(
             event_loop
                 .create_window(WindowAttributes::default().with_resizable(true).with_visible(false).with_title("Anime4K-wgpu Video Player"))
@@ -89,19 +202,46 @@ impl PlayerContext {
         );
 
         // Initialize video playback and renderer
-        let (playback, surface) = VideoPlayback::new(reader, framerate, start_paused, window.clone());
-        let renderer = Renderer::new(surface, &playback.vulkan_device, window.clone());
+        let (mut playback, surface) = VideoPlayback::new(filename, framerate, start_paused, frame_buffer_length, adaptive_quality.is_some(), window.clone());
+        let refresh_rate = window
+            .current_monitor()
+            .and_then(|monitor| monitor.refresh_rate_millihertz())
+            .map(|millihertz| millihertz as f64 / 1000.0);
+        playback.pacer = FramePacer::new(frame_pacing, refresh_rate, framerate);
+        let mut renderer = Renderer::new(surface, &playback.vulkan_device, window.clone(), present_mode);
+        renderer.set_split_submit(split_submit);
+        renderer.set_color_config(color_config);
+        renderer.set_display_filter(display_filter);
+        renderer.set_deinterlace(deinterlace);
+
+        // GPU time is measured with timestamp queries, which the device may not support
+        let adaptive_quality = adaptive_quality.filter(|adaptive_quality| {
+            let supported = renderer.device.features().contains(wgpu::Features::TIMESTAMP_QUERY);
+            if supported {
+                tracing::info!("Adaptive quality enabled with a GPU time budget of {:?} per frame", adaptive_quality.budget());
+            } else {
+                tracing::warn!("The device does not support timestamp queries, adaptive quality is disabled");
+            }
+            supported
+        });
 
         // Set initial window size based on video dimensions
         let _ = window.request_inner_size(PhysicalSize::new(playback.current_frame.frame.size().width, playback.current_frame.frame.size().height));
 
-        let context = Self {
+        let mut context = Self {
             window,
             playback,
             renderer,
             needs_redraw: true, // Initial render needed
+            recorder,
+            adaptive_quality,
+            frozen_frame: None,
         };
 
+        // Apply the startup presets before the first frame is rendered
+        context.set_anime4k_performance_preset(start_performance_preset);
+        context.set_anime4k_preset(start_preset);
+
         context.update_window_title();
         context.window.set_visible(true);
         context.window.focus_window();
@@ -113,19 +253,43 @@ impl PlayerContext {
     /// Handles frame rendering and playback timing
     ///
     /// This method orchestrates the complete frame presentation pipeline:
-    /// - Receives new frames from the decoder when not paused
     /// - Calculates current playback time excluding pause duration
-    /// - Advances to the next frame when timing conditions are met
+    /// - Receives new frames from the decoder when not paused
+    /// - Advances to the next frame when it is due, counting frames that arrived too late
     /// - Triggers rendering of the current frame
     /// - Requests continued redraws for smooth playback
-    pub fn handle_redraw(&mut self) {
+    ///
+    /// Recoverable surface errors are handled here: a lost or outdated surface is
+    /// reconfigured and the frame is skipped, and a timeout is retried on the next redraw.
+    /// While the window is minimized to a zero size, nothing is rendered and no further
+    /// redraws are requested until it is resized.
+    ///
+    /// # Returns
+    /// An error if rendering failed unrecoverably and the player should exit
+    pub fn handle_redraw(&mut self) -> Result<(), wgpu::SurfaceError> {
+        // A minimized window has no surface to render to; the next resize requests a redraw again
+        let window_size = self.window.inner_size();
+        if window_size.width == 0 || window_size.height == 0 {
+            return Ok(());
+        }
+
         let mut frame_changed = false;
 
-        // Only receive new frames when not paused
-        if !self.playback.is_paused && self.playback.next_frame.is_none() {
-            if let Ok(frame) = self.playback.rx.try_recv() {
-                self.playback.next_frame = Some(frame);
+        // Keep showing the previous frame until a seek has decoded its first frame
+        match self.playback.poll_seek() {
+            Some(SeekOutcome::Pending) => {
+                self.window.request_redraw();
+                return Ok(());
             }
+            Some(SeekOutcome::Found) => self.needs_redraw = true,
+            Some(SeekOutcome::PastEnd) => {
+                // Looping again would only seek past the end again
+                if self.playback.loop_range.take().is_some() {
+                    tracing::warn!("The loop range starts after the end of the stream, looping disabled");
+                    self.update_window_title();
+                }
+            }
+            None => {}
         }
 
         // Calculate current playback time, excluding pause duration
@@ -139,29 +303,152 @@ impl PlayerContext {
             self.playback.total_pause_duration
         };
 
-        let current_pts = (std::time::Instant::now() - self.playback.start_timestamp) - current_pause_duration;
+        let current_pts = self.playback.pacer.media_time((std::time::Instant::now() - self.playback.start_timestamp) - current_pause_duration);
 
-        // Advance to next frame if it's time and not paused
+        // Advance to the next frame if it is due and not paused, receiving it as needed
         if !self.playback.is_paused {
+            if self.playback.next_frame.is_none() {
+                self.playback.next_frame = match self.playback.rx.try_recv() {
+                    Ok(frame) => {
+                        // Frames buffered while paused are re-anchored below rather than counted
+                        if frame.pts < current_pts && !self.playback.resync_after_resume {
+                            self.playback.frame_stats.late += 1;
+                            tracing::debug!("Late frame at {:?} ({:?} behind playback)", frame.pts, current_pts - frame.pts);
+                        }
+                        Some(frame)
+                    }
+                    // The end of the stream also ends the loop range
+                    Err(TryRecvError::Disconnected) if self.playback.loop_range.is_some() => {
+                        self.restart_loop();
+                        return Ok(());
+                    }
+                    Err(_) => None,
+                };
+            }
             if let Some(next_frame_pts) = self.playback.next_frame.as_ref().map(|f| f.pts) {
-                if next_frame_pts < current_pts {
+                let loop_end = self.playback.loop_range.map(|(_, loop_end)| loop_end).filter(|&loop_end| next_frame_pts >= loop_end);
+                if let Some(loop_end) = loop_end {
+                    // Hold the last frame of the range until its time is up, then start over
+                    if current_pts >= loop_end {
+                        self.restart_loop();
+                        return Ok(());
+                    }
+                } else if next_frame_pts < current_pts {
+                    if self.playback.resync_after_resume {
+                        // Frames buffered while paused are behind the clock by however long the first
+                        // redraw took; re-anchor the clock to them instead of racing through the backlog
+                        self.playback.resync_after_resume = false;
+                        self.playback.total_pause_duration += self.playback.pacer.wall_time(current_pts - next_frame_pts);
+                    }
                     self.playback.current_frame = self.playback.next_frame.take().unwrap();
                     frame_changed = true;
                 }
             }
         }
+        // While frozen, frames keep advancing with the clock but are not displayed
+        let frame_shown = frame_changed && self.frozen_frame.is_none();
+        if frame_shown {
+            self.playback.frame_stats.shown += 1;
+        }
+        if self.playback.frame_stats_logged_at.elapsed() >= FRAME_STATS_INTERVAL {
+            self.log_frame_stats();
+        }
 
         // Only render if we need to redraw (frame changed, preset changed, or forced redraw)
-        if self.needs_redraw || frame_changed {
-            // Render the current frame
-            self.renderer.render(&self.playback.current_frame.frame, &self.window).unwrap();
-            self.needs_redraw = false;
+        if self.needs_redraw || frame_shown {
+            // Render the current frame, or the frozen one
+            let frame = self.frozen_frame.as_ref().unwrap_or(&self.playback.current_frame.frame);
+            match self.renderer.render(frame, &self.window) {
+                Ok(()) => {
+                    self.needs_redraw = false;
+                    if frame_shown {
+                        self.record_current_frame();
+                        self.adapt_performance_preset();
+                    }
+                }
+                Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                    // Surface must be reconfigured (e.g. after minimize/restore); skip this frame
+                    tracing::warn!("Surface lost or outdated, reconfiguring");
+                    self.renderer.reconfigure_surface(self.window.inner_size());
+                    self.window.request_redraw();
+                }
+                Err(wgpu::SurfaceError::Timeout) => {
+                    // Transient; try again on the next redraw
+                    tracing::warn!("Timed out acquiring surface texture, retrying");
+                    self.window.request_redraw();
+                }
+                Err(e) => return Err(e),
+            }
         }
 
         // Continue the redraw loop only if video is playing
         if !self.playback.is_paused {
             self.window.request_redraw();
         }
+
+        Ok(())
+    }
+
+    /// Passes the frame just rendered to the recorder for each output frame it is due for
+    ///
+    /// The frame is only read back when it is due, so recording at a reduced rate also
+    /// reduces the readback cost.
+    fn record_current_frame(&mut self) {
+        let Some(recorder) = self.recorder.as_mut() else {
+            return;
+        };
+        let count = recorder.frames_due(self.playback.current_frame.pts);
+        if count == 0 {
+            return;
+        }
+        match self.renderer.capture_displayed_frame() {
+            Ok(image) => recorder.write(&image, count),
+            Err(e) => tracing::warn!("Failed to capture frame for recording: {e}"),
+        }
+    }
+
+    /// Samples the Anime4K GPU time when due and steps the performance preset accordingly
+    ///
+    /// A failed measurement disables the controller, since later ones would fail the same way.
+    fn adapt_performance_preset(&mut self) {
+        let Some(adaptive_quality) = self.adaptive_quality.as_mut() else {
+            return;
+        };
+        if self.renderer.anime4k_settings().preset().is_none() || !adaptive_quality.sample_due() {
+            return;
+        }
+
+        let gpu_time = match self.renderer.measure_anime4k_time() {
+            Some(Ok(gpu_time)) => gpu_time,
+            Some(Err(e)) => {
+                tracing::warn!("Failed to measure the Anime4K GPU time, adaptive quality is disabled: {e}");
+                self.adaptive_quality = None;
+                return;
+            }
+            None => return,
+        };
+
+        let current = self.renderer.anime4k_settings().performance_preset();
+        if let Some(performance_preset) = adaptive_quality.observe(gpu_time, current) {
+            tracing::info!(
+                "Anime4K took {gpu_time:?} of a {:?} budget, switching performance preset from {} to {}",
+                adaptive_quality.budget(),
+                current.name(),
+                performance_preset.name()
+            );
+            self.apply_anime4k_performance_preset(performance_preset);
+        }
+    }
+
+    /// Captures the texture of a rendering stage from the most recently rendered frame
+    ///
+    /// # Arguments
+    /// * `stage` - The rendering stage to capture
+    ///
+    /// # Returns
+    /// The stage's texture contents as an RGBA32F image
+    pub fn snapshot_stage(&self, stage: Stage) -> Result<image::Rgba32FImage, Box<dyn std::error::Error>> {
+        self.renderer.snapshot_stage(stage)
     }
 
     /// Handles window resize events by updating renderer and requesting redraw
@@ -183,7 +470,7 @@ impl PlayerContext {
     /// # Arguments
     /// * `preset` - The Anime4K preset to activate
     pub fn set_anime4k_preset(&mut self, preset: Option<Anime4KPreset>) {
-        if self.renderer.get_current_preset() == preset {
+        if !self.renderer.set_anime4k_preset(preset) {
             return;
         }
 
@@ -191,13 +478,15 @@ impl PlayerContext {
             tracing::info!(
                 "Anime4K preset set to: {} (Anime4K performance preset is {})",
                 preset.name(),
-                self.renderer.get_current_performance_preset().name()
+                self.renderer.anime4k_settings().performance_preset().name()
             );
         } else {
             tracing::info!("Anime4K disabled");
         }
 
-        self.renderer.set_anime4k_preset(preset);
+        if let Some(adaptive_quality) = self.adaptive_quality.as_mut() {
+            adaptive_quality.restart();
+        }
         self.update_window_title();
         self.request_redraw();
     }
@@ -205,26 +494,81 @@ impl PlayerContext {
     /// Sets the active Anime4K performance preset
     ///
     /// Adjusts the computational complexity vs. quality trade-off.
-    /// Skips processing if the preset is unchanged.
+    /// Skips processing if the preset is unchanged. With adaptive quality, the preset
+    /// also becomes the heaviest one the controller steps up to.
     ///
     /// # Arguments
     /// * `performance_preset` - The performance preset to activate
     pub fn set_anime4k_performance_preset(&mut self, performance_preset: Anime4KPerformancePreset) {
-        if self.renderer.get_current_performance_preset() == performance_preset {
+        if let Some(adaptive_quality) = self.adaptive_quality.as_mut() {
+            adaptive_quality.set_ceiling(performance_preset);
+        }
+        self.apply_anime4k_performance_preset(performance_preset);
+    }
+
+    /// Switches the performance preset without changing the adaptive quality ceiling
+    ///
+    /// # Arguments
+    /// * `performance_preset` - The performance preset to activate
+    fn apply_anime4k_performance_preset(&mut self, performance_preset: Anime4KPerformancePreset) {
+        if !self.renderer.set_anime4k_performance_preset(performance_preset) {
             return;
         }
 
         tracing::info!(
             "Anime4K performance preset set to: {} (Anime4K preset is {})",
             performance_preset.name(),
-            self.renderer.get_current_preset().map_or("None", |p| p.name())
+            self.renderer.anime4k_settings().preset().map_or("None", |p| p.name())
         );
 
-        self.renderer.set_anime4k_performance_preset(performance_preset);
         self.update_window_title();
         self.request_redraw();
     }
 
+    /// Toggles the passthrough comparison mode
+    ///
+    /// While enabled and Anime4K is off, the source is bilinearly resampled to the
+    /// same dimensions the Anime4K pipeline would produce, so toggling Anime4K only
+    /// changes the CNN processing and not the intermediate scale.
+    pub fn toggle_passthrough_comparison(&mut self) {
+        let enabled = !self.renderer.is_passthrough_comparison_enabled();
+        tracing::info!("Passthrough comparison {}", if enabled { "enabled" } else { "disabled" });
+
+        self.renderer.set_passthrough_comparison(enabled);
+        self.update_window_title();
+        self.request_redraw();
+    }
+
+    /// Logs a summary of the frame statistics and refreshes the window title with it
+    fn log_frame_stats(&mut self) {
+        let FrameStats { shown, late } = self.playback.frame_stats;
+        tracing::info!("Frames shown: {shown}, late: {late}");
+
+        self.playback.frame_stats_logged_at = std::time::Instant::now();
+        self.update_window_title();
+    }
+
+    /// Sets the comparison rectangle and redraws
+    ///
+    /// Inside the rectangle the Anime4K output is shown, outside it the plain bilinear
+    /// upscale, with a border marking the boundary.
+    ///
+    /// # Arguments
+    /// * `rect` - Two opposite corners in window pixels, or None to show the Anime4K output everywhere
+    pub fn set_compare_rect(&mut self, rect: Option<((f64, f64), (f64, f64))>) {
+        self.renderer.set_compare_rect(rect);
+        self.request_redraw();
+    }
+
+    /// Toggles the on-screen overlay showing the preset, resolution, and frame rate
+    pub fn toggle_overlay(&mut self) {
+        let enabled = !self.renderer.is_overlay_enabled();
+        self.renderer.set_overlay(enabled);
+        self.request_redraw();
+
+        tracing::info!("Info overlay {}", if enabled { "shown" } else { "hidden" });
+    }
+
     /// Returns whether video playback is currently paused
     pub fn is_paused(&self) -> bool {
         self.playback.is_paused
@@ -263,6 +607,7 @@ impl PlayerContext {
             self.playback.total_pause_duration += std::time::Instant::now() - pause_time;
         }
         self.playback.pause_start_time = None;
+        self.playback.resync_after_resume = true;
 
         self.playback.is_paused = false;
 
@@ -272,21 +617,99 @@ impl PlayerContext {
         tracing::info!("Video resumed");
     }
 
+    /// Returns whether the displayed frame is currently frozen
+    pub fn is_frozen(&self) -> bool {
+        self.frozen_frame.is_some()
+    }
+
+    /// Keeps the current frame on screen while playback and decoding continue
+    ///
+    /// Unlike `pause`, the playback clock keeps running, so there is no resynchronization
+    /// when unfreezing. Frames are neither recorded nor used to adapt the performance
+    /// preset while frozen.
+    pub fn freeze(&mut self) {
+        if self.frozen_frame.is_some() {
+            return;
+        }
+
+        self.frozen_frame = Some(self.playback.current_frame.frame.clone());
+        self.update_window_title();
+
+        tracing::info!("Display frozen at {:?}", self.playback.current_frame.pts);
+    }
+
+    /// Resumes displaying the current frame of playback
+    pub fn unfreeze(&mut self) {
+        if self.frozen_frame.take().is_none() {
+            return;
+        }
+
+        self.update_window_title();
+        self.request_redraw();
+
+        tracing::info!("Display unfrozen");
+    }
+
+    /// Loops playback over a time range, for comparing presets on the same clip
+    ///
+    /// When playback reaches `end` (or the end of the stream), decoding restarts at
+    /// the keyframe before `start` and playback resumes at `start`. If playback is
+    /// currently outside the range, it jumps to `start` immediately.
+    ///
+    /// # Arguments
+    /// * `start` - Start of the loop range
+    /// * `end` - End of the loop range; if it is not after `start`, looping is disabled
+    pub fn set_loop(&mut self, start: Duration, end: Duration) {
+        if start >= end {
+            if self.playback.loop_range.take().is_some() {
+                tracing::info!("Looping disabled");
+            }
+            return;
+        }
+
+        tracing::info!("Looping from {start:?} to {end:?}");
+        self.playback.loop_range = Some((start, end));
+
+        let current_pts = self.playback.current_frame.pts;
+        if current_pts < start || current_pts >= end {
+            self.restart_loop();
+        }
+    }
+
+    /// Restarts playback at the start of the loop range
+    fn restart_loop(&mut self) {
+        let Some((start, _)) = self.playback.loop_range else {
+            return;
+        };
+
+        self.playback.seek(start);
+        self.update_window_title();
+        self.request_redraw();
+    }
+
     /// Request a redraw and mark that we need to re-render
     fn request_redraw(&mut self) {
         self.needs_redraw = true;
         self.window.request_redraw();
     }
 
-    /// Updates the window title to reflect current Anime4K settings and pause state
+    /// Updates the window title to reflect current Anime4K settings, pause and freeze state, and frame statistics
     fn update_window_title(&self) {
-        let preset_text = if let Some(preset) = self.renderer.get_current_preset() {
-            &format!("{} {}", preset.name(), self.renderer.get_current_performance_preset().name())
+        let settings = self.renderer.anime4k_settings();
+        let preset_text = if let Some(preset) = settings.preset() {
+            &format!("{} {}", preset.name(), settings.performance_preset().name())
+        } else if self.renderer.is_passthrough_comparison_enabled() {
+            "OFF (bilinear)"
         } else {
             "OFF"
         };
 
-        let window_title = format!("Anime4K-wgpu Video Player [Anime4K {preset_text}]{}", if self.playback.is_paused { " [PAUSED]" } else { "" });
+        let FrameStats { shown, late } = self.playback.frame_stats;
+        let window_title = format!(
+            "Anime4K-wgpu Video Player [Anime4K {preset_text}] [{shown} shown, {late} late]{}{}",
+            if self.playback.is_paused { " [PAUSED]" } else { "" },
+            if self.frozen_frame.is_some() { " [FROZEN]" } else { "" }
+        );
         self.window.set_title(&window_title);
     }
 }
@@ -298,6 +721,12 @@ impl PlayerContext {
 struct VideoPlayback {
     /// Shared Vulkan device for hardware video decoding
     vulkan_device: Arc<VulkanDevice>,
+    /// Path of the video bytestream, reopened to restart decoding when seeking
+    filename: PathBuf,
+    /// Playback framerate, used to map timestamps to frame numbers
+    framerate: u32,
+    /// Number of decoded frames to buffer ahead of the renderer
+    frame_buffer_length: usize,
 
     /// Channel receiver for frames from the decoder thread
     rx: Receiver<FrameWithPts>,
@@ -305,6 +734,8 @@ struct VideoPlayback {
     current_frame: FrameWithPts,
     /// The next frame waiting to be displayed
     next_frame: Option<FrameWithPts>,
+    /// Timestamp of the seek waiting for its first frame, if any
+    seek_target: Option<Duration>,
 
     /// Timestamp when video playback started
     start_timestamp: std::time::Instant,
@@ -314,6 +745,19 @@ struct VideoPlayback {
     pause_start_time: Option<std::time::Instant>,
     /// Total accumulated pause time for timing calculations
     total_pause_duration: std::time::Duration,
+    /// Whether the clock should be re-anchored to the next due frame after resuming
+    resync_after_resume: bool,
+
+    /// Frames shown and received late so far
+    frame_stats: FrameStats,
+    /// Timestamp of the last frame statistics summary
+    frame_stats_logged_at: std::time::Instant,
+
+    /// Time range (start, end) to loop over, if looping is enabled
+    loop_range: Option<(Duration, Duration)>,
+
+    /// Mapping of the playback wall clock to the media clock frames are due against
+    pacer: FramePacer,
 }
 
 impl VideoPlayback {
@@ -324,51 +768,174 @@ impl VideoPlayback {
     /// for rendering output.
     ///
     /// # Arguments
-    /// * `reader` - Input stream containing video data
+    /// * `filename` - Path to the H.264 bytestream to play
     /// * `framerate` - Target playback framerate
     /// * `start_paused` - Whether to begin in paused state
+    /// * `frame_buffer_length` - Number of decoded frames to buffer ahead of the renderer (clamped to at least 1)
+    /// * `timestamp_queries` - Whether to request timestamp query support, if the device has it
     /// * `window` - Window handle for surface creation
     ///
     /// # Returns
     /// A tuple containing the initialized playback state and wgpu surface
-    pub fn new(reader: impl std::io::Read + Send + 'static, framerate: u32, start_paused: bool, window: Arc<Window>) -> (Self, wgpu::Surface<'static>) {
+    pub fn new(filename: &Path, framerate: u32, start_paused: bool, frame_buffer_length: usize, timestamp_queries: bool, window: Arc<Window>) -> (Self, wgpu::Surface<'static>) {
         // Initialize Vulkan instance for video decoding and graphics
         let vulkan_instance = VulkanInstance::new().unwrap();
 
         // Create wgpu surface for rendering to the window
         let surface = vulkan_instance.wgpu_instance().create_surface(window).unwrap();
 
-        // Create Vulkan device with required features for video and graphics
-        let vulkan_device = vulkan_instance.create_device(wgpu::Features::FLOAT32_FILTERABLE, wgpu::Limits::default(), Some(&surface)).unwrap();
-
-        // Create a bounded channel for frame communication between threads
-        let (tx, rx) = mpsc::sync_channel(FRAME_BUFFER_LENGTH);
-        let vulkan_device_clone = vulkan_device.clone();
+        // Create Vulkan device with required features for video and graphics, retrying
+        // without timestamp queries if the device does not support them
+        let vulkan_device = if timestamp_queries {
+            vulkan_instance
+                .create_device(wgpu::Features::FLOAT32_FILTERABLE | wgpu::Features::TIMESTAMP_QUERY, wgpu::Limits::default(), Some(&surface))
+                .ok()
+        } else {
+            None
+        };
+        let vulkan_device = vulkan_device.unwrap_or_else(|| vulkan_instance.create_device(wgpu::Features::FLOAT32_FILTERABLE, wgpu::Limits::default(), Some(&surface)).unwrap());
 
-        // Spawn decoder thread for hardware video decoding
-        std::thread::spawn(move || {
-            run_decoder(tx, framerate, vulkan_device_clone, reader);
-        });
+        if frame_buffer_length < MIN_FRAME_BUFFER_LENGTH {
+            tracing::warn!("Frame buffer length {frame_buffer_length} is too small, using {MIN_FRAME_BUFFER_LENGTH} instead");
+        }
+        let frame_buffer_length = frame_buffer_length.max(MIN_FRAME_BUFFER_LENGTH);
 
+        let rx = spawn_decoder(&vulkan_device, filename, framerate, frame_buffer_length, Keyframe::default(), Duration::ZERO);
         let initial_frame = rx.recv().unwrap();
         let start_timestamp = std::time::Instant::now();
 
         (
             Self {
                 vulkan_device,
+                filename: filename.to_path_buf(),
+                framerate,
+                frame_buffer_length,
 
                 rx,
                 current_frame: initial_frame,
                 next_frame: None,
+                seek_target: None,
 
                 start_timestamp,
                 is_paused: start_paused,
                 pause_start_time: if start_paused { Some(start_timestamp) } else { None },
                 total_pause_duration: std::time::Duration::ZERO,
+                resync_after_resume: false,
+
+                frame_stats: FrameStats::default(),
+                frame_stats_logged_at: start_timestamp,
+
+                loop_range: None,
+
+                pacer: FramePacer::default(),
             },
             surface,
         )
     }
+
+    /// Restarts decoding so that playback continues at a timestamp
+    ///
+    /// Decoding restarts at the keyframe before `target`, and frames before `target`
+    /// are decoded but not shown. This does not wait for the decoder; `poll_seek`
+    /// completes the seek once the first frame at or after `target` has been decoded.
+    ///
+    /// # Arguments
+    /// * `target` - Timestamp to continue playback at
+    fn seek(&mut self, target: Duration) {
+        let target_frame = (target.as_secs_f64() * self.framerate as f64).ceil() as u64;
+        let keyframe = std::fs::File::open(&self.filename).and_then(|file| find_keyframe_before(file, target_frame)).unwrap_or_else(|e| {
+            tracing::warn!("Failed to scan for keyframes, decoding from the beginning: {e}");
+            Keyframe::default()
+        });
+        tracing::debug!("Seeking to frame {target_frame} from keyframe at frame {} (byte {})", keyframe.frame_number, keyframe.offset);
+
+        // Replacing the receiver makes the previous decoder thread exit on its next send
+        self.rx = spawn_decoder(&self.vulkan_device, &self.filename, self.framerate, self.frame_buffer_length, keyframe, target);
+        self.next_frame = None;
+        self.seek_target = Some(target);
+    }
+
+    /// Completes a pending seek without blocking
+    ///
+    /// Once the first frame of the seek has been decoded, it becomes the current frame
+    /// and the clock is re-anchored so that it is due now, keeping the pause state.
+    ///
+    /// # Returns
+    /// The progress of the pending seek, or None if there is none
+    fn poll_seek(&mut self) -> Option<SeekOutcome> {
+        let target = self.seek_target?;
+        match self.rx.try_recv() {
+            Ok(frame) => self.current_frame = frame,
+            Err(TryRecvError::Empty) => return Some(SeekOutcome::Pending),
+            Err(TryRecvError::Disconnected) => {
+                tracing::warn!("No frames at or after {target:?}");
+                self.seek_target = None;
+                return Some(SeekOutcome::PastEnd);
+            }
+        }
+        self.seek_target = None;
+
+        // Re-anchor the clock so that the current frame is due now
+        let now = std::time::Instant::now();
+        self.start_timestamp = now - self.pacer.wall_time(self.current_frame.pts);
+        self.total_pause_duration = Duration::ZERO;
+        self.pause_start_time = self.is_paused.then_some(now);
+        self.resync_after_resume = false;
+        Some(SeekOutcome::Found)
+    }
+}
+
+/// Opens the bytestream at a keyframe and spawns a decoder thread for it
+///
+/// # Arguments
+/// * `vulkan_device` - Vulkan device for creating the decoder
+/// * `filename` - Path to the H.264 bytestream
+/// * `framerate` - Playback framerate
+/// * `frame_buffer_length` - Capacity of the frame channel
+/// * `keyframe` - Keyframe to start decoding at
+/// * `start` - Timestamp of the first frame to send
+///
+/// # Returns
+/// The receiving end of the decoded frame channel
+fn spawn_decoder(vulkan_device: &Arc<VulkanDevice>, filename: &Path, framerate: u32, frame_buffer_length: usize, keyframe: Keyframe, start: Duration) -> Receiver<FrameWithPts> {
+    let mut file = std::fs::File::open(filename).unwrap_or_else(|_| panic!("Failed to open video file: {}", filename.display()));
+    std::io::Seek::seek(&mut file, std::io::SeekFrom::Start(keyframe.offset)).unwrap_or_else(|e| panic!("Failed to seek video file: {e}"));
+
+    // Create a bounded channel for frame communication between threads
+    let (tx, rx) = mpsc::sync_channel(frame_buffer_length);
+    let vulkan_device = vulkan_device.clone();
+
+    // Spawn decoder thread for hardware video decoding
+    std::thread::spawn(move || {
+        run_decoder(tx, framerate, vulkan_device, file, keyframe, start);
+    });
+
+    rx
+}
+
+/// Selects the surface present mode, falling back when the requested mode is unsupported
+///
+/// Falls back to `Fifo`, which every surface is required to support, or to the first
+/// supported mode if the capabilities do not list it.
+///
+/// # Arguments
+/// * `supported` - Present modes supported by the surface
+/// * `requested` - Requested present mode, or None for the first supported mode
+///
+/// # Returns
+/// The present mode to configure the surface with
+fn select_present_mode(supported: &[wgpu::PresentMode], requested: Option<wgpu::PresentMode>) -> wgpu::PresentMode {
+    let default = supported[0];
+    let Some(requested) = requested else {
+        return default;
+    };
+    if supported.contains(&requested) {
+        return requested;
+    }
+
+    let fallback = if supported.contains(&wgpu::PresentMode::Fifo) { wgpu::PresentMode::Fifo } else { default };
+    tracing::warn!("Present mode {requested:?} is not supported by the surface (supported: {supported:?}), using {fallback:?} instead");
+    fallback
 }
 
 /// Vertex data structure for rendering geometry
@@ -395,6 +962,40 @@ struct ScaleUniforms {
     scale: [f32; 2],
     /// Offset values for centering (currently unused, always [0,0])
     offset: [f32; 2],
+    /// Comparison rectangle in texture coordinates (left, top, right, bottom), all zero when disabled
+    compare_rect: [f32; 4],
+    /// Reconstruction filter used to resample the video to the window, as `DisplayFilter as u32`
+    display_filter: u32,
+    /// Padding to the 16-byte size alignment of uniform structs
+    _padding: [u32; 3],
+}
+
+/// Reconstruction filter for resampling the final image to the window
+///
+/// This only affects the display pass, after any Anime4K upscaling, and matters most
+/// when the window is much larger or smaller than the upscaled video.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DisplayFilter {
+    /// Hardware bilinear filtering over the 2x2 nearest texels
+    #[default]
+    Bilinear = 0,
+    /// Separable Catmull-Rom filter over the 4x4 nearest texels
+    Bicubic = 1,
+    /// Separable Lanczos-3 filter over the 6x6 nearest texels
+    Lanczos = 2,
+}
+
+impl std::str::FromStr for DisplayFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "bilinear" => Ok(DisplayFilter::Bilinear),
+            "bicubic" => Ok(DisplayFilter::Bicubic),
+            "lanczos" => Ok(DisplayFilter::Lanczos),
+            _ => Err(format!("Unknown display filter '{s}' (expected bilinear, bicubic, or lanczos)")),
+        }
+    }
 }
 
 impl Vertex {
@@ -453,24 +1054,53 @@ struct Renderer {
     queue: wgpu::Queue,
     surface_configuration: wgpu::SurfaceConfiguration,
 
+    // Samplers shared by the stages of the renderer, created on first use
+    sampler_cache: SamplerCache,
+
     // YUV to sRGB conversion pipeline resources
-    yuv_sampler: wgpu::Sampler,
+    yuv_uniform_buffer: wgpu::Buffer,
     yuv_pipeline: wgpu::ComputePipeline,
 
     // sRGB to Screen rendering pipeline resources
-    rgb_sampler: wgpu::Sampler,
     rgb_vertex_buffer: wgpu::Buffer,
     rgb_index_buffer: wgpu::Buffer,
     rgb_uniform_buffer: wgpu::Buffer,
     rgb_pipeline: wgpu::RenderPipeline,
 
+    // Most recently rendered YUV420 frame, kept for stage snapshots
+    last_frame: Option<wgpu::Texture>,
+
     // Intermediate sRGB texture between YUV conversion and Anime4K processing
     rgb_texture: Option<wgpu::Texture>,
 
-    // Anime4K upscaling pipeline and its output texture
-    anime4k_pipeline: Option<(PipelineExecutor, wgpu::Texture)>,
-    current_preset: Option<Anime4KPreset>,
-    current_performance_preset: Anime4KPerformancePreset,
+    // Deinterlace pass between YUV conversion and Anime4K processing, if enabled
+    deinterlacer: Option<Deinterlacer>,
+
+    // Anime4K upscaling executor, which owns its output texture
+    anime4k_pipeline: Option<Box<dyn Anime4kExecutor>>,
+    anime4k_settings: Anime4kSettings,
+
+    // Bilinear resampling pipeline and its output texture, used when Anime4K is off
+    // so the source is shown at the same intermediate scale as the Anime4K output
+    passthrough_pipeline: wgpu::ComputePipeline,
+    passthrough_texture: Option<wgpu::Texture>,
+    passthrough_comparison: bool,
+
+    // Whether compute stages are submitted separately from the present pass
+    split_submit: bool,
+
+    // Comparison rectangle in window pixels (left, top, right, bottom), Anime4K-processed inside only
+    compare_rect: Option<[f64; 4]>,
+
+    // Filter resampling the final image to the window
+    display_filter: DisplayFilter,
+
+    // On-screen info overlay, its visibility, and the frame rate it shows
+    overlay: Overlay,
+    overlay_enabled: bool,
+    overlay_fps: f64,
+    overlay_fps_frames: u32,
+    overlay_fps_since: std::time::Instant,
 
     // Video dimensions for pipeline setup
     video_dimensions: (u32, u32),
@@ -487,18 +1117,25 @@ impl Renderer {
     /// * `surface` - The wgpu surface to render to
     /// * `vulkan_device` - Vulkan device wrapper for GPU access
     /// * `window` - The window being rendered to
+    /// * `present_mode` - Requested present mode, or None for the first mode the surface supports
     ///
     /// # Returns
     /// A fully initialized renderer ready for frame rendering
-    fn new(surface: wgpu::Surface<'static>, vulkan_device: &VulkanDevice, window: Arc<Window>) -> Self {
+    fn new(surface: wgpu::Surface<'static>, vulkan_device: &VulkanDevice, window: Arc<Window>, present_mode: Option<wgpu::PresentMode>) -> Self {
         // Get wgpu device and queue from Vulkan wrapper
         let device = vulkan_device.wgpu_device();
         let queue = vulkan_device.wgpu_queue();
         let size = window.inner_size();
 
+        // Panicking rather than exiting unwinds through main, which removes the extracted bytestream
+        if let Err(e) = PipelineExecutor::check_format_support(&vulkan_device.wgpu_adapter()) {
+            panic!("{e}");
+        }
+
         // Configure surface for rendering
         let surface_capabilities = surface.get_capabilities(&vulkan_device.wgpu_adapter());
         let surface_texture_format = surface_capabilities.formats.iter().find(|f| f.is_srgb()).copied().unwrap_or(surface_capabilities.formats[0]);
+        let present_mode = select_present_mode(&surface_capabilities.present_modes, present_mode);
 
         let surface_configuration = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
@@ -507,7 +1144,7 @@ impl Renderer {
             format: surface_texture_format,
             view_formats: vec![surface_texture_format, surface_texture_format.remove_srgb_suffix()],
             alpha_mode: surface_capabilities.alpha_modes[0],
-            present_mode: surface_capabilities.present_modes[0],
+            present_mode,
             desired_maximum_frame_latency: 2,
         };
 
@@ -570,19 +1207,25 @@ impl Renderer {
                     binding: 3,
                     visibility: wgpu::ShaderStages::COMPUTE,
                 },
+                // Color conversion matrix and offset uniform buffer
+                wgpu::BindGroupLayoutEntry {
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                },
             ],
         });
 
-        // Create linear sampler for YUV texture sampling
-        let yuv_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            label: Some("YUV sampler"),
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Linear,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
+        // Create uniform buffer for the color conversion, starting with BT.709 full range
+        let yuv_uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("YUV color uniform buffer"),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            contents: bytemuck::cast_slice(&[ColorConfig::default().uniforms()]),
         });
 
         // Create YUV conversion pipeline layout and shaders
@@ -604,6 +1247,59 @@ impl Renderer {
             cache: None,
         });
 
+        // Set up bilinear resampling pipeline for the passthrough comparison
+        let passthrough_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Passthrough bind group layout"),
+            entries: &[
+                // Source RGB texture
+                wgpu::BindGroupLayoutEntry {
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                },
+                // Linear sampler for bilinear resampling
+                wgpu::BindGroupLayoutEntry {
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                },
+                // Resampled output texture
+                wgpu::BindGroupLayoutEntry {
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format: wgpu::TextureFormat::Rgba32Float,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                },
+            ],
+        });
+
+        let passthrough_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Passthrough pipeline layout"),
+            bind_group_layouts: &[&passthrough_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let passthrough_shader_module = device.create_shader_module(wgpu::include_wgsl!("bilinear_resample.wgsl"));
+
+        let passthrough_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Passthrough compute pipeline"),
+            layout: Some(&passthrough_pipeline_layout),
+            module: &passthrough_shader_module,
+            entry_point: None,
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
         // Set up RGB to Screen rendering pipeline
         let rgb_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("RGB bind group layout"),
@@ -628,7 +1324,7 @@ impl Renderer {
                     },
                     count: None,
                     binding: 1,
-                    visibility: wgpu::ShaderStages::VERTEX,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                 },
                 // Texture sampler for final rendering
                 wgpu::BindGroupLayoutEntry {
@@ -637,21 +1333,20 @@ impl Renderer {
                     binding: 2,
                     visibility: wgpu::ShaderStages::FRAGMENT,
                 },
+                // Plain RGB texture shown outside the comparison rectangle
+                wgpu::BindGroupLayoutEntry {
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                },
             ],
         });
 
-        // Create linear sampler for final RGB rendering
-        let rgb_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            label: Some("RGB sampler"),
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Linear,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        });
-
         // Create uniform buffer for scale and offset values
         let rgb_uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("RGB scale uniform buffer"),
@@ -708,22 +1403,36 @@ impl Renderer {
             depth_stencil: None,
         });
 
+        let overlay = Overlay::new(&device, surface_configuration.format.remove_srgb_suffix());
+
         Self {
             surface,
             device,
             queue,
             surface_configuration,
-            yuv_sampler,
+            sampler_cache: SamplerCache::new(),
+            yuv_uniform_buffer,
             yuv_pipeline,
-            rgb_sampler,
             rgb_vertex_buffer: vertex_buffer,
             rgb_index_buffer: index_buffer,
             rgb_uniform_buffer,
             rgb_pipeline,
+            last_frame: None,
             rgb_texture: None,
+            deinterlacer: None,
             anime4k_pipeline: None,
-            current_preset: None,
-            current_performance_preset: Anime4KPerformancePreset::Medium,
+            anime4k_settings: Anime4kSettings::default(),
+            passthrough_pipeline,
+            passthrough_texture: None,
+            passthrough_comparison: false,
+            split_submit: false,
+            compare_rect: None,
+            display_filter: DisplayFilter::default(),
+            overlay,
+            overlay_enabled: false,
+            overlay_fps: 0.0,
+            overlay_fps_frames: 0,
+            overlay_fps_since: std::time::Instant::now(),
             video_dimensions: (size.width, size.height),
         }
     }
@@ -747,6 +1456,21 @@ impl Renderer {
         }
     }
 
+    /// Reconfigures the surface after it has been lost or become outdated
+    ///
+    /// Falls back to a full resize if the window size changed, so the Anime4K
+    /// pipeline is rebuilt for the new target dimensions.
+    ///
+    /// # Arguments
+    /// * `size` - Current window size in physical pixels
+    fn reconfigure_surface(&mut self, size: PhysicalSize<u32>) {
+        if size.width != self.surface_configuration.width || size.height != self.surface_configuration.height {
+            self.resize(size);
+        } else if size.width > 0 && size.height > 0 {
+            self.surface.configure(&self.device, &self.surface_configuration);
+        }
+    }
+
     /// Creates an intermediate RGB texture for YUV conversion output
     ///
     /// This texture serves as the input to the Anime4K pipeline and uses
@@ -767,11 +1491,161 @@ impl Renderer {
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::Rgba32Float, // High precision for Anime4K processing
-            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC,
             view_formats: &[],
         }));
     }
 
+    /// Captures the texture of a rendering stage from the most recently rendered frame
+    ///
+    /// # Arguments
+    /// * `stage` - The rendering stage to capture
+    ///
+    /// # Returns
+    /// The stage's texture contents as an RGBA32F image, or an error if the stage has not been rendered
+    fn snapshot_stage(&self, stage: Stage) -> Result<image::Rgba32FImage, Box<dyn std::error::Error>> {
+        match stage {
+            Stage::Yuv => {
+                let frame = self.last_frame.as_ref().ok_or("No frame has been rendered yet")?;
+                let (width, height) = (frame.width(), frame.height());
+
+                // NV12: full resolution Y plane and half resolution interleaved UV plane
+                let y_plane = self.read_texture(frame, wgpu::TextureAspect::Plane0, (width, height), 1)?;
+                let uv_plane = self.read_texture(frame, wgpu::TextureAspect::Plane1, (width / 2, height / 2), 2)?;
+
+                Ok(image::Rgba32FImage::from_fn(width, height, |x, y| {
+                    let uv_index = (((y / 2) * (width / 2) + x / 2) * 2) as usize;
+                    image::Rgba([
+                        y_plane[(y * width + x) as usize] as f32 / 255.0,
+                        uv_plane[uv_index] as f32 / 255.0,
+                        uv_plane[uv_index + 1] as f32 / 255.0,
+                        1.0,
+                    ])
+                }))
+            }
+            Stage::Srgb | Stage::Anime4kOutput => {
+                let texture = if stage == Stage::Srgb {
+                    self.rgb_texture.as_ref().ok_or("No frame has been rendered yet")?
+                } else {
+                    self.anime4k_pipeline.as_ref().ok_or("Anime4K is disabled")?.output_texture()
+                };
+                self.read_float_texture(texture)
+            }
+        }
+    }
+
+    /// Measures the GPU time of the Anime4K passes on the current frame
+    ///
+    /// Runs the passes once more with timestamp queries and waits for the result, so this
+    /// stalls rendering for about as long as the time it measures.
+    ///
+    /// # Returns
+    /// The total GPU time of the passes, or None if Anime4K is disabled
+    fn measure_anime4k_time(&self) -> Option<Result<Duration, Box<dyn std::error::Error>>> {
+        let pipeline = self.anime4k_pipeline.as_ref()?;
+        Some(pipeline.profile(&self.device, &self.queue).map(|timings| timings.iter().map(|timing| timing.duration).sum()))
+    }
+
+    /// Captures the texture shown by the display pass in the most recently rendered frame
+    ///
+    /// This is the Anime4K output when enabled, otherwise the passthrough or sRGB texture,
+    /// before it is resampled to the window.
+    ///
+    /// # Returns
+    /// The texture contents converted to 8-bit RGBA, or an error if no frame has been rendered
+    fn capture_displayed_frame(&self) -> Result<image::RgbaImage, Box<dyn std::error::Error>> {
+        let texture = if let Some(pipeline) = &self.anime4k_pipeline {
+            pipeline.output_texture()
+        } else if let Some(passthrough_texture) = &self.passthrough_texture {
+            passthrough_texture
+        } else {
+            self.rgb_texture.as_ref().ok_or("No frame has been rendered yet")?
+        };
+        Ok(image::DynamicImage::ImageRgba32F(self.read_float_texture(texture)?).to_rgba8())
+    }
+
+    /// Reads back a 32-bit float texture as an RGBA32F image
+    ///
+    /// R32Float and Rg32Float textures are expanded to RGBA with unused components set to 0 (RGB) and 1 (A).
+    ///
+    /// # Arguments
+    /// * `texture` - Texture to read (must have COPY_SRC usage)
+    ///
+    /// # Returns
+    /// The texture contents as an RGBA32F image
+    fn read_float_texture(&self, texture: &wgpu::Texture) -> Result<image::Rgba32FImage, Box<dyn std::error::Error>> {
+        let (width, height) = (texture.width(), texture.height());
+        let components = texture.format().components() as usize;
+        let data = self.read_texture(texture, wgpu::TextureAspect::All, (width, height), components as u32 * 4)?;
+        let values: &[f32] = bytemuck::cast_slice(&data);
+        Ok(image::Rgba32FImage::from_fn(width, height, |x, y| {
+            let index = (y * width + x) as usize * components;
+            let mut rgba = [0.0, 0.0, 0.0, 1.0];
+            rgba[..components].copy_from_slice(&values[index..index + components]);
+            image::Rgba(rgba)
+        }))
+    }
+
+    /// Reads back one aspect of a texture into tightly packed bytes
+    ///
+    /// # Arguments
+    /// * `texture` - Texture to read (must have COPY_SRC usage)
+    /// * `aspect` - Texture aspect (plane) to copy
+    /// * `size` - Dimensions of the aspect in texels
+    /// * `bytes_per_texel` - Size of one texel of the aspect in bytes
+    ///
+    /// # Returns
+    /// The texel data with row padding removed
+    fn read_texture(&self, texture: &wgpu::Texture, aspect: wgpu::TextureAspect, size: (u32, u32), bytes_per_texel: u32) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let (width, height) = size;
+        let unpadded_bytes_per_row = width * bytes_per_texel;
+        let padded_bytes_per_row = unpadded_bytes_per_row.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Snapshot buffer"),
+            size: (padded_bytes_per_row * height) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Snapshot encoder") });
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        // Map the buffer and wait for the copy to complete
+        let buffer_slice = buffer.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::PollType::Wait)?;
+        receiver.recv()??;
+
+        let mapped = buffer_slice.get_mapped_range();
+        let data = mapped.chunks(padded_bytes_per_row as usize).flat_map(|row| &row[..unpadded_bytes_per_row as usize]).copied().collect();
+        Ok(data)
+    }
+
     /// Updates or creates the Anime4K processing pipeline for new video dimensions
     ///
     /// Reconfigures the Anime4K shader pipeline when video dimensions change or when
@@ -782,29 +1656,51 @@ impl Renderer {
             "Setting up Anime4K for {video_width}x{video_height} (target={}x{}), current_preset={}, anime4k_pipeline={}",
             self.surface_configuration.width,
             self.surface_configuration.height,
-            self.current_preset.as_ref().map_or("None", |p| p.name()),
+            self.anime4k_settings.preset().map_or("None", |p| p.name()),
             self.anime4k_pipeline.is_some(),
         );
 
-        if let Some(preset) = self.current_preset {
-            let target_width = self.surface_configuration.width;
-            let target_height = self.surface_configuration.height;
-
-            if let Some(rgb_texture) = &self.rgb_texture {
-                // Calculate target scale factor to fit video in window
-                let target_scale_factor = (target_width as f64 / video_width as f64).max(target_height as f64 / video_height as f64);
+        // Calculate target scale factor to fit video in window
+        let target_width = self.surface_configuration.width;
+        let target_height = self.surface_configuration.height;
+        let target_scale_factor = (target_width as f64 / video_width as f64).max(target_height as f64 / video_height as f64);
 
-                // Create Anime4K pipelines with appropriate settings
-                let pipelines = preset.create_pipelines(self.current_performance_preset, target_scale_factor);
+        self.passthrough_texture = None;
 
+        if let Some(pipelines) = self.anime4k_settings.create_pipelines(target_scale_factor) {
+            if let Some(rgb_texture) = &self.rgb_texture {
                 // Initialize the Anime4K shader pipeline
-                let (pipeline, output_texture) = PipelineExecutor::new(&pipelines, &self.device, rgb_texture);
+                let (pipeline, _) = PipelineExecutor::new(&pipelines, &self.device, rgb_texture);
+
+                // Compile the passes now rather than on the next frame, which would hitch
+                if let Err(e) = pipeline.warm_up(&self.device, &self.queue) {
+                    tracing::warn!("Failed to warm up the Anime4K pipeline: {e}");
+                }
 
-                self.anime4k_pipeline = Some((pipeline, output_texture));
+                self.anime4k_pipeline = Some(Box::new(pipeline));
             }
         } else {
             // Disable pipeline if Anime4K is turned off
             self.anime4k_pipeline = None;
+
+            // Resample to the size Anime4K would produce so the comparison differs only in the CNN
+            if self.passthrough_comparison {
+                let scale_factor = Anime4KPreset::output_scale_factor(target_scale_factor);
+                self.passthrough_texture = Some(self.device.create_texture(&wgpu::TextureDescriptor {
+                    label: Some("Passthrough texture"),
+                    size: wgpu::Extent3d {
+                        width: video_width * scale_factor,
+                        height: video_height * scale_factor,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: wgpu::TextureFormat::Rgba32Float,
+                    usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC,
+                    view_formats: &[],
+                }));
+            }
         }
     }
 
@@ -838,9 +1734,19 @@ impl Renderer {
             ((video_width * scale) / window_width, 1.0)
         };
 
+        // Map the comparison rectangle from window pixels to texture coordinates of the letterboxed video
+        let compare_rect = self.compare_rect.map_or([0.0; 4], |[left, top, right, bottom]| {
+            let to_u = |x: f64| ((x as f32 / window_width * 2.0 - 1.0) / scale_x + 1.0) / 2.0;
+            let to_v = |y: f64| ((y as f32 / window_height * 2.0 - 1.0) / scale_y + 1.0) / 2.0;
+            [to_u(left), to_v(top), to_u(right), to_v(bottom)]
+        });
+
         ScaleUniforms {
             scale: [scale_x, scale_y],
             offset: [0.0, 0.0], // Center the video (offset currently unused in shader)
+            compare_rect,
+            display_filter: self.display_filter as u32,
+            _padding: [0; 3],
         }
     }
 
@@ -860,6 +1766,11 @@ impl Renderer {
     fn render(&mut self, frame: &wgpu::Texture, window: &Window) -> Result<(), wgpu::SurfaceError> {
         let video_width = frame.width();
         let video_height = frame.height();
+        let new_frame = self.last_frame.as_ref() != Some(frame);
+        self.last_frame = Some(frame.clone());
+
+        // Linear sampler for the chroma planes, the passthrough resampling, and the display pass
+        let linear_sampler = self.sampler_cache.get(&self.device, SamplerKey::clamp_to_edge(wgpu::FilterMode::Linear));
 
         // Create or update RGB intermediate texture if needed
         if self.rgb_texture.is_none() || self.video_dimensions != (video_width, video_height) {
@@ -870,18 +1781,19 @@ impl Renderer {
             self.update_anime4k_pipeline(video_width, video_height);
         }
 
-        let device = &self.device;
-        let surface = self.surface.get_current_texture()?;
-        let surface_view = surface.texture.create_view(&wgpu::TextureViewDescriptor {
-            format: Some(surface.texture.format().remove_srgb_suffix()), // Linear format for proper color handling
-            ..Default::default()
-        });
+        if let Some(deinterlacer) = &mut self.deinterlacer {
+            deinterlacer.prepare(&self.device, video_width, video_height, new_frame);
+        }
 
+        let device = &self.device;
         let mut command_encoder = device.create_command_encoder(&Default::default());
 
         // Stage 1: Convert YUV420 to sRGB
-        if let Some(rgb_texture) = &self.rgb_texture {
+        let surface = if let Some(rgb_texture) = &self.rgb_texture {
             let rgb_texture_view = rgb_texture.create_view(&Default::default());
+            // When deinterlacing, the conversion writes the woven frame and the deinterlace pass fills the RGB texture
+            let woven_texture = self.deinterlacer.as_ref().and_then(Deinterlacer::woven_texture);
+            let yuv_output_view = woven_texture.map_or_else(|| rgb_texture_view.clone(), |texture| texture.create_view(&Default::default()));
 
             // Create bind group for YUV input textures, sampler, and output texture
             let yuv_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
@@ -913,12 +1825,17 @@ impl Renderer {
                     // Linear sampler for UV texture sampling
                     wgpu::BindGroupEntry {
                         binding: 2,
-                        resource: wgpu::BindingResource::Sampler(&self.yuv_sampler),
+                        resource: wgpu::BindingResource::Sampler(&linear_sampler),
                     },
                     // Output RGB texture
                     wgpu::BindGroupEntry {
                         binding: 3,
-                        resource: wgpu::BindingResource::TextureView(&rgb_texture_view),
+                        resource: wgpu::BindingResource::TextureView(&yuv_output_view),
+                    },
+                    // Color conversion matrix and offset
+                    wgpu::BindGroupEntry {
+                        binding: 4,
+                        resource: self.yuv_uniform_buffer.as_entire_binding(),
                     },
                 ],
             });
@@ -935,16 +1852,71 @@ impl Renderer {
                 yuv_pass.dispatch_workgroups(video_width.div_ceil(YUV_COMPUTE_WORKGROUP_SIZE_X), video_height.div_ceil(YUV_COMPUTE_WORKGROUP_SIZE_Y), 1);
             }
 
+            // Stage 1b: Rebuild the second field of interlaced frames
+            if let Some(deinterlacer) = &self.deinterlacer {
+                deinterlacer.pass(device, &mut command_encoder, rgb_texture);
+            }
+
             // Stage 2: Apply Anime4K processing if enabled
-            let texture_to_render = if let Some((ref pipeline, ref output_texture)) = self.anime4k_pipeline {
+            let texture_to_render = if let Some(pipeline) = &self.anime4k_pipeline {
                 // Execute Anime4K compute shaders
                 pipeline.pass(&mut command_encoder);
-                output_texture
+                pipeline.output_texture()
+            } else if let Some(passthrough_texture) = &self.passthrough_texture {
+                // Bilinearly resample to the Anime4K output size for comparison
+                let passthrough_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Passthrough bind group"),
+                    layout: &self.passthrough_pipeline.get_bind_group_layout(0),
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(&rgb_texture_view),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Sampler(&linear_sampler),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: wgpu::BindingResource::TextureView(&passthrough_texture.create_view(&Default::default())),
+                        },
+                    ],
+                });
+
+                {
+                    let mut passthrough_pass = command_encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                        label: Some("Passthrough compute pass"),
+                        timestamp_writes: None,
+                    });
+
+                    passthrough_pass.set_pipeline(&self.passthrough_pipeline);
+                    passthrough_pass.set_bind_group(0, &passthrough_bind_group, &[]);
+                    passthrough_pass.dispatch_workgroups(
+                        passthrough_texture.width().div_ceil(YUV_COMPUTE_WORKGROUP_SIZE_X),
+                        passthrough_texture.height().div_ceil(YUV_COMPUTE_WORKGROUP_SIZE_Y),
+                        1,
+                    );
+                }
+
+                passthrough_texture
             } else {
                 // Use original RGB texture without Anime4K processing
                 rgb_texture
             };
 
+            // With split submission, submit the compute stages on their own so the GPU can start
+            // on them while we block on acquiring the next swapchain image
+            if self.split_submit {
+                let compute_encoder = std::mem::replace(&mut command_encoder, device.create_command_encoder(&Default::default()));
+                self.queue.submit(Some(compute_encoder.finish()));
+            }
+
+            let surface = self.surface.get_current_texture()?;
+            let surface_view = surface.texture.create_view(&wgpu::TextureViewDescriptor {
+                format: Some(surface.texture.format().remove_srgb_suffix()), // Linear format for proper color handling
+                ..Default::default()
+            });
+
             // Stage 3: Render final result to screen with proper scaling
             let final_width = texture_to_render.width();
             let final_height = texture_to_render.height();
@@ -953,6 +1925,19 @@ impl Renderer {
             // Update uniform buffer with current scale values
             self.queue.write_buffer(&self.rgb_uniform_buffer, 0, bytemuck::cast_slice(&[scale_uniforms]));
 
+            // Update the overlay text, measuring the rate at which frames are rendered
+            self.overlay_fps_frames += 1;
+            let fps_elapsed = self.overlay_fps_since.elapsed();
+            if fps_elapsed >= OVERLAY_FPS_INTERVAL {
+                self.overlay_fps = self.overlay_fps_frames as f64 / fps_elapsed.as_secs_f64();
+                self.overlay_fps_frames = 0;
+                self.overlay_fps_since = std::time::Instant::now();
+            }
+            if self.overlay_enabled {
+                let lines = self.overlay_lines((final_width, final_height));
+                self.overlay.set_lines(device, &self.queue, lines);
+            }
+
             // Create bind group for final rendering pass
             let rgb_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
                 label: Some("RGB bind group"),
@@ -975,7 +1960,12 @@ impl Renderer {
                     // Linear sampler for final rendering
                     wgpu::BindGroupEntry {
                         binding: 2,
-                        resource: wgpu::BindingResource::Sampler(&self.rgb_sampler),
+                        resource: wgpu::BindingResource::Sampler(&linear_sampler),
+                    },
+                    // Plain RGB texture, upscaled bilinearly outside the comparison rectangle
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: wgpu::BindingResource::TextureView(&rgb_texture_view),
                     },
                 ],
             });
@@ -1000,8 +1990,17 @@ impl Renderer {
                 rgb_pass.set_vertex_buffer(0, self.rgb_vertex_buffer.slice(..));
                 rgb_pass.set_index_buffer(self.rgb_index_buffer.slice(..), wgpu::IndexFormat::Uint16);
                 rgb_pass.draw_indexed(0..INDICES.len() as u32, 0, 0..1);
+
+                // Stage 4: Composite the info overlay over the scaled frame
+                if self.overlay_enabled {
+                    self.overlay.draw(&self.queue, &mut rgb_pass, (self.surface_configuration.width, self.surface_configuration.height));
+                }
             }
-        }
+
+            surface
+        } else {
+            self.surface.get_current_texture()?
+        };
 
         // Submit all commands to GPU and present the frame
         self.queue.submit(Some(command_encoder.finish()));
@@ -1014,19 +2013,21 @@ impl Renderer {
     /// Sets the current Anime4K preset and updates the pipeline
     ///
     /// Changes the Anime4K processing mode and recreates the shader pipeline
-    /// with the new settings. Updates console output to show current state.
+    /// with the new settings.
     ///
     /// # Arguments
     /// * `preset` - The new Anime4K preset to use
-    pub fn set_anime4k_preset(&mut self, preset: Option<Anime4KPreset>) {
-        if self.current_preset == preset {
-            return;
+    ///
+    /// # Returns
+    /// Whether the preset changed; the pipeline is left as is if it did not
+    pub fn set_anime4k_preset(&mut self, preset: Option<Anime4KPreset>) -> bool {
+        if !self.anime4k_settings.set_preset(preset) {
+            return false;
         }
 
-        self.current_preset = preset;
-
         // Recreate pipeline with new preset
         self.update_anime4k_pipeline(self.video_dimensions.0, self.video_dimensions.1);
+        true
     }
 
     /// Sets the current Anime4K performance preset and updates the pipeline
@@ -1036,24 +2037,138 @@ impl Renderer {
     ///
     /// # Arguments
     /// * `performance_preset` - The new performance preset to use
-    pub fn set_anime4k_performance_preset(&mut self, performance_preset: Anime4KPerformancePreset) {
-        if self.current_performance_preset == performance_preset {
-            return;
+    ///
+    /// # Returns
+    /// Whether the performance preset changed; the pipeline is left as is if it did not
+    pub fn set_anime4k_performance_preset(&mut self, performance_preset: Anime4KPerformancePreset) -> bool {
+        if !self.anime4k_settings.set_performance_preset(performance_preset) {
+            return false;
         }
 
-        self.current_performance_preset = performance_preset;
-
         // Recreate pipeline with new performance preset
         self.update_anime4k_pipeline(self.video_dimensions.0, self.video_dimensions.1);
+        true
+    }
+
+    /// Enables or disables the passthrough comparison and updates the pipeline
+    ///
+    /// # Arguments
+    /// * `enabled` - Whether to resample the source to the Anime4K output size while Anime4K is off
+    pub fn set_passthrough_comparison(&mut self, enabled: bool) {
+        if self.passthrough_comparison == enabled {
+            return;
+        }
+
+        self.passthrough_comparison = enabled;
+        self.update_anime4k_pipeline(self.video_dimensions.0, self.video_dimensions.1);
+    }
+
+    /// Returns whether the passthrough comparison is enabled
+    pub fn is_passthrough_comparison_enabled(&self) -> bool {
+        self.passthrough_comparison
+    }
+
+    /// Enables or disables split submission of the compute stages
+    ///
+    /// When enabled, the YUV conversion and Anime4K passes are submitted in their own
+    /// command buffer before the swapchain image is acquired, and the present pass is
+    /// submitted afterwards. This lets the GPU run the upscale while the renderer is
+    /// blocked on presentation, and frees the render thread to receive the next decoded
+    /// frame sooner.
+    ///
+    /// # Synchronization
+    /// Both submissions go to the single queue that `vk_video` shares with wgpu. Queue
+    /// submission order guarantees the render pass observes the Anime4K output, and the
+    /// present waits on the swapchain acquire semaphore that wgpu manages, so no extra
+    /// fences or semaphores are needed.
+    ///
+    /// Running the upscale truly concurrently with decode would require a dedicated
+    /// compute queue, which neither `vk_video::VulkanDevice` nor wgpu currently expose.
+    /// That would need a timeline semaphore signaled by the compute submission and waited
+    /// on by the graphics submission, plus a queue family ownership transfer of the Anime4K
+    /// output texture unless it is created with concurrent sharing.
+    ///
+    /// Devices with a single hardware queue gain little from this, so it is off by default.
+    ///
+    /// # Arguments
+    /// * `enabled` - Whether to submit compute stages separately
+    pub fn set_split_submit(&mut self, enabled: bool) {
+        self.split_submit = enabled;
+    }
+
+    /// Sets the YUV-to-RGB conversion used for subsequent frames
+    ///
+    /// # Arguments
+    /// * `color_config` - Conversion matrix, sample range, and chroma reconstruction
+    pub fn set_color_config(&mut self, color_config: &ColorConfig) {
+        self.queue.write_buffer(&self.yuv_uniform_buffer, 0, bytemuck::cast_slice(&[color_config.uniforms()]));
     }
 
-    /// Returns the current Anime4K preset
-    pub fn get_current_preset(&self) -> Option<Anime4KPreset> {
-        self.current_preset
+    /// Sets the comparison rectangle, outside of which the plain bilinear upscale is shown
+    ///
+    /// # Arguments
+    /// * `rect` - Two opposite corners in window pixels, or None to show the processed output everywhere
+    pub fn set_compare_rect(&mut self, rect: Option<((f64, f64), (f64, f64))>) {
+        self.compare_rect = rect.map(|((x0, y0), (x1, y1))| [x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1)]);
+    }
+
+    /// Sets the filter used to resample the final image to the window
+    ///
+    /// # Arguments
+    /// * `display_filter` - Reconstruction filter for the display pass
+    pub fn set_display_filter(&mut self, display_filter: DisplayFilter) {
+        self.display_filter = display_filter;
+    }
+
+    /// Enables or disables the deinterlace pass
+    ///
+    /// # Arguments
+    /// * `deinterlace` - Method and field order to deinterlace with, or None to show frames as decoded
+    pub fn set_deinterlace(&mut self, deinterlace: Option<(DeinterlaceMethod, FieldOrder)>) {
+        self.deinterlacer = deinterlace.map(|(method, field_order)| Deinterlacer::new(&self.device, method, field_order));
+    }
+
+    /// Shows or hides the info overlay
+    ///
+    /// # Arguments
+    /// * `enabled` - Whether to draw the overlay over the final frame
+    pub fn set_overlay(&mut self, enabled: bool) {
+        self.overlay_enabled = enabled;
+    }
+
+    /// Returns whether the info overlay is shown
+    pub fn is_overlay_enabled(&self) -> bool {
+        self.overlay_enabled
+    }
+
+    /// Builds the lines of the info overlay
+    ///
+    /// # Arguments
+    /// * `output_dimensions` - Dimensions of the texture being presented
+    fn overlay_lines(&self, output_dimensions: (u32, u32)) -> Vec<String> {
+        let preset_text = if let Some(preset) = self.anime4k_settings.preset() {
+            format!("{} {}", preset.name(), self.anime4k_settings.performance_preset().name())
+        } else if self.passthrough_comparison {
+            "OFF (bilinear)".to_string()
+        } else {
+            "OFF".to_string()
+        };
+        let (video_width, video_height) = self.video_dimensions;
+        let (output_width, output_height) = output_dimensions;
+
+        let mut lines = vec![
+            format!("Anime4K: {preset_text}"),
+            format!("Resolution: {video_width}x{video_height} -> {output_width}x{output_height}"),
+            format!("FPS: {:.1}", self.overlay_fps),
+        ];
+        if let Some(deinterlacer) = &self.deinterlacer {
+            lines.push(format!("Deinterlace: {}", deinterlacer.method()));
+        }
+        lines
     }
 
-    /// Returns the current Anime4K performance preset
-    pub fn get_current_performance_preset(&self) -> Anime4KPerformancePreset {
-        self.current_performance_preset
+    /// Returns the current Anime4K preset and performance preset
+    pub fn anime4k_settings(&self) -> Anime4kSettings {
+        self.anime4k_settings
     }
 }
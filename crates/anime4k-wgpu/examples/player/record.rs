@@ -0,0 +1,142 @@
+//! Recording of the displayed frames to a video file
+//!
+//! Frames are piped as raw RGBA to an `ffmpeg` process, which encodes them with H.264
+//! and muxes them into the container chosen by the output file extension. Recording runs
+//! on the media clock at a fixed rate, which may be lower than the playback framerate:
+//! each output frame repeats the latest displayed frame at or before its timestamp, so
+//! frames the player did not render are filled in and the output keeps the source timing.
+
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, Command, Stdio},
+    time::Duration,
+};
+
+/// An `ffmpeg` encoder process and the size of the frames it accepts
+struct Encoder {
+    child: Child,
+    stdin: ChildStdin,
+    dimensions: (u32, u32),
+}
+
+/// Encodes displayed frames to a video file at a fixed rate
+pub struct Recorder {
+    /// Output video file
+    path: PathBuf,
+    /// Output framerate in frames per second
+    rate: f64,
+    /// Encoder, started with the dimensions of the first recorded frame
+    encoder: Option<Encoder>,
+    /// Number of output frames written so far
+    written: u64,
+    /// Whether recording stopped after an error
+    failed: bool,
+}
+
+impl Recorder {
+    /// Creates a recorder; the encoder is started when the first frame arrives
+    ///
+    /// # Arguments
+    /// * `path` - Output video file, whose extension selects the container (e.g. `.mp4`, `.mkv`)
+    /// * `rate` - Output framerate in frames per second
+    pub fn new(path: &Path, rate: f64) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            rate,
+            encoder: None,
+            written: 0,
+            failed: false,
+        }
+    }
+
+    /// Returns how many output frames are due up to a media timestamp
+    ///
+    /// # Arguments
+    /// * `pts` - Timestamp of the frame about to be recorded
+    ///
+    /// # Returns
+    /// The number of output frames whose timestamps are at or before `pts` and have not been
+    /// written yet, or 0 if recording has stopped
+    pub fn frames_due(&self, pts: Duration) -> u64 {
+        if self.failed {
+            return 0;
+        }
+        let due = (pts.as_secs_f64() * self.rate).floor() as u64 + 1;
+        due.saturating_sub(self.written)
+    }
+
+    /// Writes a frame one or more times
+    ///
+    /// Frames with different dimensions than the first recorded frame (e.g. after switching
+    /// to a preset with another scale factor) are skipped with a warning. Errors stop the
+    /// recording and are logged, so playback continues.
+    ///
+    /// # Arguments
+    /// * `image` - Frame to encode
+    /// * `count` - Number of output frames to fill with it
+    pub fn write(&mut self, image: &image::RgbaImage, count: u64) {
+        if self.failed || count == 0 {
+            return;
+        }
+        if let Err(e) = self.try_write(image, count) {
+            tracing::error!("Recording to {} stopped: {e}", self.path.display());
+            self.failed = true;
+        }
+    }
+
+    /// Starts the encoder if needed and writes a frame `count` times
+    fn try_write(&mut self, image: &image::RgbaImage, count: u64) -> std::io::Result<()> {
+        if self.encoder.is_none() {
+            self.encoder = Some(Self::spawn_encoder(&self.path, self.rate, image.dimensions())?);
+        }
+        let encoder = self.encoder.as_mut().unwrap();
+        if encoder.dimensions != image.dimensions() {
+            tracing::warn!(
+                "Skipping {}x{} frame while recording at {}x{}",
+                image.width(),
+                image.height(),
+                encoder.dimensions.0,
+                encoder.dimensions.1
+            );
+            return Ok(());
+        }
+
+        for _ in 0..count {
+            encoder.stdin.write_all(image.as_raw())?;
+        }
+        self.written += count;
+        Ok(())
+    }
+
+    /// Spawns `ffmpeg` reading raw RGBA frames of the given size from its standard input
+    fn spawn_encoder(path: &Path, rate: f64, dimensions: (u32, u32)) -> std::io::Result<Encoder> {
+        let (width, height) = dimensions;
+        let mut child = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error", "-f", "rawvideo", "-pix_fmt", "rgba"])
+            .args(["-video_size", &format!("{width}x{height}"), "-framerate", &rate.to_string(), "-i", "-"])
+            // 4:2:0 output needs even dimensions, so pad odd sizes by one pixel
+            .args(["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2", "-c:v", "libx264", "-crf", "16", "-pix_fmt", "yuv420p"])
+            .arg(path)
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|e| std::io::Error::new(e.kind(), format!("failed to start ffmpeg: {e}")))?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        tracing::info!("Recording {width}x{height} at {rate} fps to {}", path.display());
+        Ok(Encoder { child, stdin, dimensions })
+    }
+}
+
+impl Drop for Recorder {
+    /// Closes the encoder input and waits for `ffmpeg` to finalize the file
+    fn drop(&mut self) {
+        if let Some(Encoder { mut child, stdin, .. }) = self.encoder.take() {
+            drop(stdin);
+            match child.wait() {
+                Ok(status) if status.success() => tracing::info!("Recorded {} frames to {}", self.written, self.path.display()),
+                Ok(status) => tracing::error!("ffmpeg exited with {status} while finalizing {}", self.path.display()),
+                Err(e) => tracing::error!("Failed to wait for ffmpeg: {e}"),
+            }
+        }
+    }
+}
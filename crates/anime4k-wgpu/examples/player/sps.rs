@@ -0,0 +1,329 @@
+//! Parsing of H.264 sequence parameter sets
+//!
+//! The first sequence parameter set of a bytestream tells whether the stream may contain
+//! field pictures (`frame_mbs_only_flag`) and, when its VUI carries timing information,
+//! the frame rate the stream was encoded at. Only the fields up to the VUI timing
+//! information are parsed; everything else is skipped.
+
+use std::io::{self, Read};
+
+/// Number of bytes read from the start of a stream to look for a sequence parameter set
+const DETECTION_READ_LIMIT: u64 = 1 << 20;
+
+/// Reads exp-Golomb coded values from an RBSP
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn bit(&mut self) -> Option<u32> {
+        let byte = self.data.get(self.position / 8)?;
+        let bit = (byte >> (7 - self.position % 8)) & 1;
+        self.position += 1;
+        Some(bit as u32)
+    }
+
+    fn bits(&mut self, count: u32) -> Option<u32> {
+        (0..count).try_fold(0, |value, _| Some((value << 1) | self.bit()?))
+    }
+
+    /// Reads an unsigned exp-Golomb value (ue(v))
+    fn ue(&mut self) -> Option<u32> {
+        let mut leading_zeros = 0;
+        while self.bit()? == 0 {
+            leading_zeros += 1;
+            if leading_zeros > 31 {
+                return None;
+            }
+        }
+        Some((1u32 << leading_zeros) - 1 + self.bits(leading_zeros)?)
+    }
+
+    /// Reads a signed exp-Golomb value (se(v))
+    fn se(&mut self) -> Option<i32> {
+        let value = self.ue()? as i64;
+        let signed = if value % 2 == 1 { (value + 1) / 2 } else { -value / 2 };
+        Some(signed as i32)
+    }
+}
+
+/// Properties of a stream read from its sequence parameter set
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SequenceParameterSet {
+    /// Whether the sequence may contain field pictures
+    pub interlaced: bool,
+    /// Frame rate from the VUI timing information, if present
+    pub framerate: Option<f64>,
+}
+
+impl SequenceParameterSet {
+    /// Parses a sequence parameter set RBSP (after the NAL header)
+    ///
+    /// # Returns
+    /// The parsed properties, or None if the SPS is truncated before `frame_mbs_only_flag`.
+    /// A truncated or missing VUI only leaves `framerate` unset.
+    fn parse(rbsp: &[u8]) -> Option<Self> {
+        let mut reader = BitReader::new(rbsp);
+        let profile_idc = reader.bits(8)?;
+        reader.bits(16)?; // constraint flags and level_idc
+        reader.ue()?; // seq_parameter_set_id
+
+        if matches!(profile_idc, 100 | 110 | 122 | 244 | 44 | 83 | 86 | 118 | 128 | 138 | 139 | 134 | 135) {
+            let chroma_format_idc = reader.ue()?;
+            if chroma_format_idc == 3 {
+                reader.bit()?; // separate_colour_plane_flag
+            }
+            reader.ue()?; // bit_depth_luma_minus8
+            reader.ue()?; // bit_depth_chroma_minus8
+            reader.bit()?; // qpprime_y_zero_transform_bypass_flag
+            if reader.bit()? == 1 {
+                // seq_scaling_matrix_present_flag
+                let list_count = if chroma_format_idc == 3 { 12 } else { 8 };
+                for list in 0..list_count {
+                    if reader.bit()? == 1 {
+                        let size = if list < 6 { 16 } else { 64 };
+                        let mut last_scale = 8;
+                        let mut next_scale = 8;
+                        for _ in 0..size {
+                            if next_scale != 0 {
+                                next_scale = (last_scale + reader.se()? + 256) % 256;
+                            }
+                            if next_scale != 0 {
+                                last_scale = next_scale;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        reader.ue()?; // log2_max_frame_num_minus4
+        match reader.ue()? {
+            0 => {
+                reader.ue()?; // log2_max_pic_order_cnt_lsb_minus4
+            }
+            1 => {
+                reader.bit()?; // delta_pic_order_always_zero_flag
+                reader.se()?; // offset_for_non_ref_pic
+                reader.se()?; // offset_for_top_to_bottom_field
+                for _ in 0..reader.ue()? {
+                    reader.se()?; // offset_for_ref_frame
+                }
+            }
+            _ => {}
+        }
+
+        reader.ue()?; // max_num_ref_frames
+        reader.bit()?; // gaps_in_frame_num_value_allowed_flag
+        reader.ue()?; // pic_width_in_mbs_minus1
+        reader.ue()?; // pic_height_in_map_units_minus1
+        let interlaced = reader.bit()? == 0;
+
+        Some(Self {
+            interlaced,
+            framerate: Self::parse_framerate(&mut reader, interlaced),
+        })
+    }
+
+    /// Reads the frame rate from the VUI, continuing after `frame_mbs_only_flag`
+    ///
+    /// # Returns
+    /// `time_scale / (2 * num_units_in_tick)`, or None if the SPS has no timing information
+    fn parse_framerate(reader: &mut BitReader, interlaced: bool) -> Option<f64> {
+        if interlaced {
+            reader.bit()?; // mb_adaptive_frame_field_flag
+        }
+        reader.bit()?; // direct_8x8_inference_flag
+        if reader.bit()? == 1 {
+            // frame_cropping_flag
+            for _ in 0..4 {
+                reader.ue()?; // frame_crop_{left,right,top,bottom}_offset
+            }
+        }
+        if reader.bit()? == 0 {
+            // vui_parameters_present_flag
+            return None;
+        }
+
+        if reader.bit()? == 1 {
+            // aspect_ratio_info_present_flag
+            if reader.bits(8)? == 255 {
+                reader.bits(32)?; // Extended_SAR sar_width and sar_height
+            }
+        }
+        if reader.bit()? == 1 {
+            // overscan_info_present_flag
+            reader.bit()?; // overscan_appropriate_flag
+        }
+        if reader.bit()? == 1 {
+            // video_signal_type_present_flag
+            reader.bits(4)?; // video_format and video_full_range_flag
+            if reader.bit()? == 1 {
+                reader.bits(24)?; // colour_primaries, transfer_characteristics, and matrix_coefficients
+            }
+        }
+        if reader.bit()? == 1 {
+            // chroma_loc_info_present_flag
+            reader.ue()?; // chroma_sample_loc_type_top_field
+            reader.ue()?; // chroma_sample_loc_type_bottom_field
+        }
+        if reader.bit()? == 0 {
+            // timing_info_present_flag
+            return None;
+        }
+
+        let num_units_in_tick = reader.bits(32)?;
+        let time_scale = reader.bits(32)?;
+        if num_units_in_tick == 0 || time_scale == 0 {
+            return None;
+        }
+        // A frame lasts two ticks, one per field
+        Some(time_scale as f64 / (2.0 * num_units_in_tick as f64))
+    }
+}
+
+/// Reads the first sequence parameter set of an H.264 bytestream
+///
+/// # Arguments
+/// * `reader` - Annex B bytestream, read from its current position
+///
+/// # Returns
+/// The properties of the stream, or None if no complete SPS was found near its start
+pub fn detect_sequence_parameter_set(reader: impl Read) -> io::Result<Option<SequenceParameterSet>> {
+    let mut data = Vec::new();
+    reader.take(DETECTION_READ_LIMIT).read_to_end(&mut data)?;
+
+    let Some(start) = data.windows(4).position(|window| window[..3] == [0, 0, 1] && window[3] & 0x1f == 7) else {
+        return Ok(None);
+    };
+
+    // Remove emulation prevention bytes up to the next start code
+    let mut rbsp = Vec::new();
+    let mut zeros = 0;
+    for &byte in &data[start + 4..] {
+        if zeros >= 2 && byte <= 1 {
+            break;
+        }
+        if zeros >= 2 && byte == 3 {
+            zeros = 0;
+            continue;
+        }
+        zeros = if byte == 0 { zeros + 1 } else { 0 };
+        rbsp.push(byte);
+    }
+
+    Ok(SequenceParameterSet::parse(&rbsp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// High profile, progressive, 1920x1080, 24000/1001 fps, with a scaling matrix
+    const HIGH_PROGRESSIVE: [u8; 24] = [
+        0x64, 0x00, 0x28, 0xad, 0x84, 0x01, 0x08, 0x0d, 0x94, 0x07, 0x80, 0x22, 0x7e, 0x5c, 0x04, 0x40, 0x00, 0x00, 0xfa, 0x40, 0x00, 0x2e, 0xe0, 0x21,
+    ];
+
+    /// Main profile, interlaced (`frame_mbs_only_flag` = 0), 720x576, 25 fps, with `pic_order_cnt_type` 1
+    ///
+    /// The timing information contains `00 00 0x` sequences, so the bytestream form
+    /// needs emulation prevention bytes.
+    const MAIN_INTERLACED: [u8; 21] = [
+        0x4d, 0x40, 0x28, 0xd0, 0xa6, 0x69, 0x94, 0x0b, 0x42, 0x4d, 0x80, 0x88, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x01, 0x94, 0x20,
+    ];
+
+    /// `MAIN_INTERLACED` with emulation prevention bytes, as it appears in a bytestream
+    const MAIN_INTERLACED_EBSP: [u8; 23] = [
+        0x4d, 0x40, 0x28, 0xd0, 0xa6, 0x69, 0x94, 0x0b, 0x42, 0x4d, 0x80, 0x88, 0x00, 0x00, 0x03, 0x00, 0x08, 0x00, 0x00, 0x03, 0x01, 0x94, 0x20,
+    ];
+
+    /// Constrained baseline profile, progressive, 640x480, without VUI
+    const BASELINE_NO_VUI: [u8; 8] = [0x42, 0xc0, 0x1e, 0xda, 0x02, 0x80, 0xf6, 0x40];
+
+    /// Joins NAL units into an Annex B bytestream with 4-byte start codes
+    fn annex_b(nal_units: &[&[u8]]) -> Vec<u8> {
+        nal_units.iter().flat_map(|nal_unit| [0, 0, 0, 1].iter().chain(nal_unit.iter()).copied()).collect()
+    }
+
+    #[test]
+    fn test_bit_reader() {
+        // 1 | 010 | 011 | 00100: ue(v) 0, 1, 2, 3
+        let mut reader = BitReader::new(&[0b1010_0110, 0b0100_0000]);
+        assert_eq!(reader.ue(), Some(0));
+        assert_eq!(reader.ue(), Some(1));
+        assert_eq!(reader.ue(), Some(2));
+        assert_eq!(reader.ue(), Some(3));
+        assert_eq!(reader.bits(4), Some(0));
+        assert_eq!(reader.bit(), None);
+
+        // 010 | 011 | 00100 | 00101: se(v) 1, -1, 2, -2
+        let mut reader = BitReader::new(&[0b0100_1100, 0b1000_0101]);
+        assert_eq!(reader.se(), Some(1));
+        assert_eq!(reader.se(), Some(-1));
+        assert_eq!(reader.se(), Some(2));
+        assert_eq!(reader.se(), Some(-2));
+
+        let mut reader = BitReader::new(&[0xa5, 0x0f]);
+        assert_eq!(reader.bits(12), Some(0xa50));
+        assert_eq!(reader.bits(5), None);
+
+        // More than 31 leading zeros cannot be a 32-bit value
+        assert_eq!(BitReader::new(&[0; 5]).ue(), None);
+    }
+
+    #[test]
+    fn test_parse_high_progressive() {
+        let sps = SequenceParameterSet::parse(&HIGH_PROGRESSIVE).unwrap();
+        assert!(!sps.interlaced);
+        assert_eq!(sps.framerate, Some(24000.0 / 1001.0));
+    }
+
+    #[test]
+    fn test_parse_interlaced() {
+        let sps = SequenceParameterSet::parse(&MAIN_INTERLACED).unwrap();
+        assert!(sps.interlaced);
+        assert_eq!(sps.framerate, Some(25.0));
+    }
+
+    #[test]
+    fn test_parse_without_timing() {
+        let sps = SequenceParameterSet::parse(&BASELINE_NO_VUI).unwrap();
+        assert!(!sps.interlaced);
+        assert_eq!(sps.framerate, None);
+    }
+
+    #[test]
+    fn test_parse_truncated() {
+        // Cut inside the VUI: the scan type is known, the frame rate is not
+        let sps = SequenceParameterSet::parse(&HIGH_PROGRESSIVE[..16]).unwrap();
+        assert!(!sps.interlaced);
+        assert_eq!(sps.framerate, None);
+        // Cut before frame_mbs_only_flag
+        assert_eq!(SequenceParameterSet::parse(&BASELINE_NO_VUI[..4]), None);
+        assert_eq!(SequenceParameterSet::parse(&[]), None);
+    }
+
+    #[test]
+    fn test_detect_sequence_parameter_set() {
+        let access_unit_delimiter: &[u8] = &[0x09, 0xf0];
+        let sps = [[0x67].as_slice(), &MAIN_INTERLACED_EBSP].concat();
+        let pps: &[u8] = &[0x68, 0xce, 0x3c, 0x80];
+        let stream = annex_b(&[access_unit_delimiter, &sps, pps]);
+        let detected = detect_sequence_parameter_set(Cursor::new(stream)).unwrap().unwrap();
+        assert!(detected.interlaced);
+        assert_eq!(detected.framerate, Some(25.0));
+
+        let sps = [[0x67].as_slice(), &HIGH_PROGRESSIVE].concat();
+        let detected = detect_sequence_parameter_set(Cursor::new(annex_b(&[&sps, pps]))).unwrap().unwrap();
+        assert_eq!(detected.framerate, Some(24000.0 / 1001.0));
+
+        assert_eq!(detect_sequence_parameter_set(Cursor::new(annex_b(&[access_unit_delimiter, pps]))).unwrap(), None);
+        assert_eq!(detect_sequence_parameter_set(Cursor::new(Vec::new())).unwrap(), None);
+    }
+}
@@ -1,4 +1,8 @@
 // sRGB to screen shader with scaling
+//
+// The video is resampled to the window with hardware bilinear filtering by default, or with
+// a separable Catmull-Rom or Lanczos-3 filter evaluated from texel loads, selected by
+// `display_filter`.
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) tex_coords: vec2<f32>,
@@ -12,6 +16,10 @@ struct VertexOutput {
 struct ScaleUniforms {
     scale: vec2<f32>,
     offset: vec2<f32>,
+    // Comparison rectangle in texture coordinates (left, top, right, bottom), empty when disabled
+    compare_rect: vec4<f32>,
+    // 0 = bilinear, 1 = bicubic (Catmull-Rom), 2 = Lanczos-3
+    display_filter: u32,
 }
 
 @group(0) @binding(1) var<uniform> scale_uniforms: ScaleUniforms;
@@ -30,8 +38,99 @@ fn vs_main(input: VertexInput) -> VertexOutput {
 
 @group(0) @binding(0) var rgb_texture: texture_2d<f32>;
 @group(0) @binding(2) var input_sampler: sampler;
+@group(0) @binding(3) var plain_texture: texture_2d<f32>;
+
+// Color of the comparison rectangle border
+const BORDER_COLOR = vec4<f32>(1.0, 0.8, 0.0, 1.0);
+
+const PI = 3.14159265358979;
+
+// Catmull-Rom kernel, nonzero for |x| < 2
+fn catmull_rom_kernel(x: f32) -> f32 {
+    let a = abs(x);
+    if a < 1.0 {
+        return (1.5 * a - 2.5) * a * a + 1.0;
+    }
+    if a < 2.0 {
+        return ((-0.5 * a + 2.5) * a - 4.0) * a + 2.0;
+    }
+    return 0.0;
+}
+
+// Lanczos kernel with three lobes, nonzero for |x| < 3
+fn lanczos3_kernel(x: f32) -> f32 {
+    let a = abs(x);
+    if a < 1e-5 {
+        return 1.0;
+    }
+    if a >= 3.0 {
+        return 0.0;
+    }
+    let px = PI * a;
+    return 3.0 * sin(px) * sin(px / 3.0) / (px * px);
+}
+
+// Resamples a texture with a separable kernel of the given radius in texels
+//
+// Weights are normalized, as Lanczos weights only approximately sum to one.
+fn sample_separable(texture: texture_2d<f32>, coords: vec2<f32>, radius: i32) -> vec4<f32> {
+    let dims = vec2i(textureDimensions(texture));
+    let position = coords * vec2f(dims) - 0.5;
+    let base = floor(position);
+    let fraction = position - base;
+
+    var sum = vec4f(0.0);
+    var weight_sum = 0.0;
+    for (var j = 1 - radius; j <= radius; j++) {
+        let y = f32(j) - fraction.y;
+        let weight_y = select(catmull_rom_kernel(y), lanczos3_kernel(y), radius == 3);
+        for (var i = 1 - radius; i <= radius; i++) {
+            let x = f32(i) - fraction.x;
+            let weight = weight_y * select(catmull_rom_kernel(x), lanczos3_kernel(x), radius == 3);
+            let texel = clamp(vec2i(base) + vec2i(i, j), vec2i(0), dims - 1);
+            sum += weight * textureLoad(texture, texel, 0);
+            weight_sum += weight;
+        }
+    }
+
+    // Both kernels have negative lobes, so keep the ringing within the displayable range
+    return clamp(sum / weight_sum, vec4f(0.0), vec4f(1.0));
+}
+
+// Samples a texture at texture coordinates with the selected display filter
+fn sample_display(texture: texture_2d<f32>, coords: vec2<f32>) -> vec4<f32> {
+    switch scale_uniforms.display_filter {
+        case 1u: {
+            return sample_separable(texture, coords, 2);
+        }
+        case 2u: {
+            return sample_separable(texture, coords, 3);
+        }
+        default: {
+            return textureSampleLevel(texture, input_sampler, coords, 0.0);
+        }
+    }
+}
 
 @fragment
 fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSampleLevel(rgb_texture, input_sampler, input.tex_coords, 0.0);
+    // Border width of two screen pixels in texture coordinates (derivatives need uniform control flow)
+    let border = fwidth(input.tex_coords) * 2.0;
+
+    let color = sample_display(rgb_texture, input.tex_coords);
+    let rect = scale_uniforms.compare_rect;
+    if rect.x >= rect.z || rect.y >= rect.w {
+        return color;
+    }
+
+    // Show the processed output inside the rectangle and the plain upscale outside it
+    let inside = all(input.tex_coords >= rect.xy) && all(input.tex_coords <= rect.zw);
+    let inner = all(input.tex_coords >= rect.xy + border) && all(input.tex_coords <= rect.zw - border);
+    if inside && !inner {
+        return BORDER_COLOR;
+    }
+    if inside {
+        return color;
+    }
+    return sample_display(plain_texture, input.tex_coords);
 }
@@ -1,26 +1,80 @@
-// YUV to sRGB conversion compute shader (BT.709)
+// YUV to sRGB conversion compute shader
+//
+// The matrix and offset come from the player's color config (BT.709 full range by default),
+// with the sample range already folded in.
+//
+// Chroma is reconstructed from the subsampled UV plane either bilinearly or with a separable
+// Catmull-Rom filter. `chroma_offset` shifts the sampling position (in chroma samples) from
+// center siting, where each chroma sample sits in the middle of the luma samples it covers,
+// to the siting of the source, such as left siting for H.264.
+
+struct ColorUniforms {
+    matrix: mat3x3<f32>,
+    offset: vec3<f32>,
+    chroma_offset: vec2<f32>,
+    // 0 = bilinear, 1 = bicubic (Catmull-Rom)
+    chroma_filter: u32,
+}
 
 @group(0) @binding(0) var y_texture: texture_2d<f32>;
 @group(0) @binding(1) var uv_texture: texture_2d<f32>;
 @group(0) @binding(2) var uv_sampler: sampler;
 @group(0) @binding(3) var output_texture: texture_storage_2d<rgba32float, write>;
+@group(0) @binding(4) var<uniform> color: ColorUniforms;
+
+// Catmull-Rom weights for the four samples around a fractional position
+fn catmull_rom_weights(t: f32) -> vec4f {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    return vec4f(
+        -0.5 * t3 + t2 - 0.5 * t,
+        1.5 * t3 - 2.5 * t2 + 1.0,
+        -1.5 * t3 + 2.0 * t2 + 0.5 * t,
+        0.5 * t3 - 0.5 * t2,
+    );
+}
+
+// Samples the UV plane with a 4x4 Catmull-Rom filter at a position in chroma samples
+fn sample_uv_bicubic(position: vec2f) -> vec2f {
+    let uv_dims = vec2i(textureDimensions(uv_texture));
+    let base = floor(position);
+    let fraction = position - base;
+    let weights_x = catmull_rom_weights(fraction.x);
+    let weights_y = catmull_rom_weights(fraction.y);
+
+    var uv = vec2f(0.0);
+    for (var j = 0; j < 4; j++) {
+        var row = vec2f(0.0);
+        for (var i = 0; i < 4; i++) {
+            let texel = clamp(vec2i(base) + vec2i(i - 1, j - 1), vec2i(0), uv_dims - 1);
+            row += weights_x[i] * textureLoad(uv_texture, texel, 0).rg;
+        }
+        uv += weights_y[j] * row;
+    }
+
+    return uv;
+}
 
 fn process(pos: vec2i) {
     let output_dims = textureDimensions(output_texture);
-    let uv_pos = (vec2f(pos) + 0.5) / vec2f(output_dims);
+    let uv_dims = vec2f(textureDimensions(uv_texture));
+
+    // Position of this pixel in chroma samples, where integer values are chroma sample centers
+    let chroma_pos = (vec2f(pos) + 0.5) * uv_dims / vec2f(output_dims) - 0.5 + color.chroma_offset;
 
     let y = textureLoad(y_texture, pos, 0).r;
 
-    let uv = textureSampleLevel(uv_texture, uv_sampler, uv_pos, 0.0).rg;
-    let u = uv.x - 0.5; // Center U around 0
-    let v = uv.y - 0.5; // Center V around 0
+    var uv: vec2f;
+    if color.chroma_filter == 1u {
+        uv = sample_uv_bicubic(chroma_pos);
+    } else {
+        uv = textureSampleLevel(uv_texture, uv_sampler, (chroma_pos + 0.5) / uv_dims, 0.0).rg;
+    }
 
-    // BT.709 YUV to RGB conversion (assuming full range input)
-    let r = y + 1.5748 * v;
-    let g = y - 0.1873 * u - 0.4681 * v;
-    let b = y + 1.8556 * u;
+    // Remove the range offsets (centering U and V around 0) and convert to RGB
+    let rgb = color.matrix * (vec3f(y, uv) - color.offset);
 
-    textureStore(output_texture, pos, vec4f(clamp(r, 0.0, 1.0), clamp(g, 0.0, 1.0), clamp(b, 0.0, 1.0), 1.0));
+    textureStore(output_texture, pos, vec4f(clamp(rgb, vec3f(0.0), vec3f(1.0)), 1.0));
 }
 
 @compute @workgroup_size(8, 8)
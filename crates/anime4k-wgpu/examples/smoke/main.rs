@@ -0,0 +1,188 @@
+//! Anime4K GPU Smoke Test
+//!
+//! A quick check that the GPU path works on this machine. It creates a device, runs
+//! every preset on a small test pattern generated in memory, and checks that each
+//! output has the expected size, contains no NaN or infinite values, and is not all
+//! black. Unlike the golden-image tests, it does not compare against reference
+//! outputs, so it needs no test data and runs on any GPU wgpu supports.
+//!
+//! # Usage
+//! ```bash
+//! cargo run --example smoke
+//! ```
+//!
+//! Prints PASS or FAIL for every preset and exits with a non-zero status if any failed.
+
+use anime4k_wgpu::{
+    PipelineExecutor,
+    presets::{Anime4KPerformancePreset, Anime4KPreset},
+};
+
+/// Width of the test pattern in pixels, deliberately not a multiple of the workgroup size
+const PATTERN_WIDTH: u32 = 60;
+
+/// Height of the test pattern in pixels
+const PATTERN_HEIGHT: u32 = 34;
+
+/// Scale factor every preset is run at
+const SCALE_FACTOR: f64 = 2.0;
+
+/// Presets to check, in the order they are reported
+const PRESETS: [Anime4KPreset; 7] = [
+    Anime4KPreset::ModeA,
+    Anime4KPreset::ModeB,
+    Anime4KPreset::ModeC,
+    Anime4KPreset::ModeAA,
+    Anime4KPreset::ModeBB,
+    Anime4KPreset::ModeCA,
+    Anime4KPreset::Nearest,
+];
+
+/// Main entry point
+///
+/// Runs every preset and reports the results.
+///
+/// # Returns
+/// `Ok(())` if every preset passed, or an error if the GPU could not be initialized or any preset failed
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::PRIMARY,
+        ..Default::default()
+    });
+    let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+        power_preference: wgpu::PowerPreference::HighPerformance,
+        compatible_surface: None,
+        force_fallback_adapter: false,
+    }))?;
+    let info = adapter.get_info();
+    println!("Adapter: {} ({:?})", info.name, info.backend);
+    let (device, queue) = pollster::block_on(PipelineExecutor::request_device(&adapter, wgpu::Features::empty()))?;
+
+    let source_texture = create_test_pattern(&device, &queue);
+    let performance_preset = Anime4KPerformancePreset::Light;
+
+    let mut failed = 0;
+    for preset in PRESETS {
+        let label = format!("{} {} {SCALE_FACTOR}x", preset.name(), performance_preset.name());
+        match check_preset(&device, &queue, &source_texture, preset, performance_preset) {
+            Ok(()) => println!("PASS  {label}"),
+            Err(e) => {
+                println!("FAIL  {label}: {e}");
+                failed += 1;
+            }
+        }
+    }
+
+    println!();
+    if failed > 0 {
+        return Err(format!("{failed} of {} presets failed", PRESETS.len()).into());
+    }
+    println!("All {} presets passed", PRESETS.len());
+    Ok(())
+}
+
+/// Creates the test pattern texture
+///
+/// The pattern combines color gradients, hard-edged diagonal stripes, and a dark
+/// outlined disc, so both flat areas and the line art the CNNs target are present.
+///
+/// # Arguments
+/// * `device` - The wgpu device
+/// * `queue` - The wgpu command queue, used to upload the pattern
+///
+/// # Returns
+/// An `Rgba32Float` texture holding the pattern
+fn create_test_pattern(device: &wgpu::Device, queue: &wgpu::Queue) -> wgpu::Texture {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Smoke Test Pattern"),
+        size: wgpu::Extent3d {
+            width: PATTERN_WIDTH,
+            height: PATTERN_HEIGHT,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba32Float,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+
+    let (center_x, center_y, radius) = (PATTERN_WIDTH as f32 * 0.7, PATTERN_HEIGHT as f32 * 0.5, PATTERN_HEIGHT as f32 * 0.3);
+    let texels: Vec<f32> = (0..PATTERN_WIDTH * PATTERN_HEIGHT)
+        .flat_map(|index| {
+            let (x, y) = ((index % PATTERN_WIDTH) as f32, (index / PATTERN_WIDTH) as f32);
+            let stripe = if ((x + y) as u32 / 4).is_multiple_of(2) { 0.2 } else { 0.0 };
+            let distance = ((x - center_x).powi(2) + (y - center_y).powi(2)).sqrt();
+            let color = [x / PATTERN_WIDTH as f32 * 0.8 + stripe, y / PATTERN_HEIGHT as f32 * 0.8 + stripe, 0.5];
+            let color = if (distance - radius).abs() < 1.0 {
+                [0.05; 3]
+            } else if distance < radius {
+                [0.95, 0.85, 0.7]
+            } else {
+                color
+            };
+            [color[0], color[1], color[2], 1.0]
+        })
+        .collect();
+    queue.write_texture(
+        texture.as_image_copy(),
+        &texels.iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<_>>(),
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(PATTERN_WIDTH * 16),
+            rows_per_image: Some(PATTERN_HEIGHT),
+        },
+        texture.size(),
+    );
+    texture
+}
+
+/// Runs one preset on the test pattern and checks its output
+///
+/// # Arguments
+/// * `device` - The wgpu device
+/// * `queue` - The wgpu command queue
+/// * `source_texture` - The test pattern
+/// * `preset` - Anime4K preset to run
+/// * `performance_preset` - Performance preset to run it at
+///
+/// # Returns
+/// `Ok(())` if the output passed every check, or an error describing the first failure
+fn check_preset(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    source_texture: &wgpu::Texture,
+    preset: Anime4KPreset,
+    performance_preset: Anime4KPerformancePreset,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let pipelines = preset.create_pipelines(performance_preset, SCALE_FACTOR);
+    if pipelines.is_empty() {
+        return Err("no pipelines generated".into());
+    }
+
+    // Catch validation errors, which wgpu would otherwise report through a panic
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let (executor, output_texture) = PipelineExecutor::new(&pipelines, device, source_texture);
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Smoke Test") });
+    executor.pass(&mut encoder);
+    queue.submit(std::iter::once(encoder.finish()));
+    if let Some(error) = pollster::block_on(device.pop_error_scope()) {
+        return Err(format!("validation error: {error}").into());
+    }
+
+    let expected_size = ((PATTERN_WIDTH as f64 * SCALE_FACTOR) as u32, (PATTERN_HEIGHT as f64 * SCALE_FACTOR) as u32);
+    let output_size = (output_texture.width(), output_texture.height());
+    if output_size != expected_size {
+        return Err(format!("output is {}x{}, expected {}x{}", output_size.0, output_size.1, expected_size.0, expected_size.1).into());
+    }
+
+    let stats = executor.output_stats(device, queue)?;
+    if stats.has_non_finite() {
+        return Err(format!("output contains non-finite values ({:?} per channel)", stats.non_finite_count).into());
+    }
+    if stats.is_all_black() {
+        return Err("output is all black".into());
+    }
+    Ok(())
+}
@@ -4,6 +4,25 @@
 //! ExecutablePipeline represents a fully compiled and optimized shader pipeline with pre-allocated
 //! resources, embedded shader code, and optimized texture binding layouts.
 
+use std::{borrow::Cow, collections::HashMap};
+
+/// Compute shader workgroup size in X dimension
+pub(crate) const COMPUTE_WORKGROUP_SIZE_X: u32 = 8;
+/// Compute shader workgroup size in Y dimension
+pub(crate) const COMPUTE_WORKGROUP_SIZE_Y: u32 = 8;
+
+/// Floors a scaled dimension to whole pixels
+///
+/// A non-empty dimension never floors below one pixel, so downscaled textures and
+/// dispatches of tiny inputs are still valid.
+///
+/// # Arguments
+/// * `size` - Dimension before scaling
+/// * `scaled` - Dimension after scaling, before flooring
+fn floor_dimension(size: u32, scaled: f64) -> u32 {
+    (scaled.floor() as u32).max(size.min(1))
+}
+
 /// Represents a rational scale factor as a fraction
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ScaleFactor {
@@ -13,30 +32,322 @@ pub struct ScaleFactor {
     pub denominator: u32,
 }
 
+impl ScaleFactor {
+    /// Applies the scale factor to a dimension, flooring to an integer size
+    ///
+    /// A non-empty dimension never scales below one pixel (see `floor_dimension`).
+    pub(crate) fn apply(&self, size: u32) -> u32 {
+        floor_dimension(size, size as f64 * self.numerator as f64 / self.denominator as f64)
+    }
+
+    /// Returns the product of two scale factors, reduced to lowest terms
+    fn multiply(self, other: ScaleFactor) -> ScaleFactor {
+        let gcd = |mut a: u64, mut b: u64| {
+            while b != 0 {
+                (a, b) = (b, a % b);
+            }
+            a
+        };
+        let numerator = self.numerator as u64 * other.numerator as u64;
+        let denominator = self.denominator as u64 * other.denominator as u64;
+        let divisor = gcd(numerator, denominator).max(1);
+        ScaleFactor {
+            numerator: (numerator / divisor) as u32,
+            denominator: (denominator / divisor) as u32,
+        }
+    }
+}
+
 /// Texture sampling filter modes
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum SamplerFilterMode {
     /// Nearest neighbor sampling - sharp, pixelated
-    #[allow(unused)]
     Nearest,
     /// Linear interpolation sampling - smooth, blurred
     Linear,
 }
 
+/// What a pipeline or chain of pipelines does to its input, for labeling in user interfaces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PipelineClass {
+    /// Increases the resolution without processing at the input resolution
+    Upscale,
+    /// Processes at the input resolution (restore, denoise, or filter) without changing it
+    Restore,
+    /// Both processes at the input resolution and increases the resolution
+    UpscaleAndRestore,
+}
+
+impl PipelineClass {
+    /// Classifies a chain of pipelines executed in sequence
+    ///
+    /// The chain restores if any pipeline restores and upscales if any pipeline upscales,
+    /// so a preset such as Restore → Upscale is classified as [`UpscaleAndRestore`](Self::UpscaleAndRestore).
+    ///
+    /// # Arguments
+    /// * `pipelines` - Pipelines in execution order
+    ///
+    /// # Returns
+    /// The combined class, or `None` for an empty chain
+    pub fn of_chain(pipelines: &[&ExecutablePipeline]) -> Option<Self> {
+        pipelines.iter().map(|pipeline| pipeline.classification()).reduce(Self::combine)
+    }
+
+    /// Returns the class of running a pipeline of class `other` after one of this class
+    pub fn combine(self, other: Self) -> Self {
+        if self == other { self } else { Self::UpscaleAndRestore }
+    }
+
+    /// Returns whether this class increases the resolution
+    pub fn upscales(&self) -> bool {
+        matches!(self, Self::Upscale | Self::UpscaleAndRestore)
+    }
+
+    /// Returns whether this class processes at the input resolution
+    pub fn restores(&self) -> bool {
+        matches!(self, Self::Restore | Self::UpscaleAndRestore)
+    }
+}
+
 /// A complete analyzed pipeline manifest ready for execution
 ///
 /// This structure contains all the information needed to execute a shader pipeline
 /// on the GPU, with optimized resource allocation and embedded shader code.
 #[derive(Debug, Clone)]
 pub struct ExecutablePipeline {
+    /// Identifier of the pipeline as given by its manifest or shader
+    pub(crate) id: &'static str,
     /// Human-readable name for debugging
     pub(crate) name: &'static str,
-    /// Physical textures used by this pipeline
-    pub(crate) textures: &'static [PhysicalTexture],
+    /// Optional description from the pipeline manifest
+    pub(crate) description: Option<&'static str>,
+    /// Physical textures used by this pipeline, owned only by rescaled copies
+    pub(crate) textures: Cow<'static, [PhysicalTexture]>,
     /// Sampler filter modes required by this pipeline
     pub(crate) samplers: &'static [SamplerFilterMode],
-    /// Shader passes to execute in sequence
-    pub(crate) passes: &'static [ExecutablePass],
+    /// Shader passes to execute in sequence, owned only by rescaled copies
+    pub(crate) passes: Cow<'static, [ExecutablePass]>,
+}
+
+impl ExecutablePipeline {
+    /// Returns the identifier of this pipeline
+    ///
+    /// For CNN pipelines this is derived from the GLSL shader, for auxiliary pipelines
+    /// it is the `id` of the WGSL manifest.
+    pub fn id(&self) -> &'static str {
+        self.id
+    }
+
+    /// Returns the human-readable name of this pipeline, also used in GPU debug labels
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the description of this pipeline, if its manifest has one
+    pub fn description(&self) -> Option<&'static str> {
+        self.description
+    }
+
+    /// Returns the shader passes of this pipeline in execution order
+    pub fn passes(&self) -> &[ExecutablePass] {
+        &self.passes
+    }
+
+    /// Returns the physical textures allocated for this pipeline
+    pub fn textures(&self) -> &[PhysicalTexture] {
+        &self.textures
+    }
+
+    /// Returns the sampler filter modes this pipeline needs
+    ///
+    /// The list is derived at build time from the sampler bindings of every pass,
+    /// deduplicated and kept in order of first use. `PipelineExecutor` creates exactly
+    /// one sampler per entry, so callers can use this to check filtering support
+    /// (e.g. `FLOAT32_FILTERABLE` for `Linear` on float textures) before creating a device.
+    ///
+    /// # Returns
+    /// The distinct filter modes used by the pipeline's passes
+    pub fn sampler_requirements(&self) -> &'static [SamplerFilterMode] {
+        self.samplers
+    }
+
+    /// Calculates the exact output dimensions for a given input size
+    ///
+    /// Resolves the scale factor of the result texture (the first output of the last
+    /// pass) against the input size, flooring exactly like `PipelineExecutor` does when
+    /// allocating textures. Use this to size surfaces or buffers before execution.
+    ///
+    /// # Arguments
+    /// * `input_width` - Input width in pixels
+    /// * `input_height` - Input height in pixels
+    ///
+    /// # Returns
+    /// The output (width, height)
+    pub fn output_dimensions(&self, input_width: u32, input_height: u32) -> (u32, u32) {
+        self.result_texture().dimensions((input_width, input_height))
+    }
+
+    /// Lists the compute dispatch of every pass for a given input size
+    ///
+    /// The workgroup counts are exactly those `PipelineExecutor` dispatches, so this shows
+    /// how much work each pass does at a resolution, and how much of the last row and
+    /// column of workgroups is wasted on bounds-checked invocations.
+    ///
+    /// # Arguments
+    /// * `input_width` - Input width of this pipeline in pixels
+    /// * `input_height` - Input height of this pipeline in pixels
+    ///
+    /// # Returns
+    /// The pass id and workgroup counts in X and Y of each pass, in execution order
+    pub fn dispatch_plan(&self, input_width: u32, input_height: u32) -> Vec<(String, u32, u32)> {
+        self.passes
+            .iter()
+            .map(|pass| {
+                let (width, height) = pass.compute_dimensions((input_width, input_height));
+                (pass.id.to_string(), width.div_ceil(COMPUTE_WORKGROUP_SIZE_X), height.div_ceil(COMPUTE_WORKGROUP_SIZE_Y))
+            })
+            .collect()
+    }
+
+    /// Classifies this pipeline by whether it upscales or restores at the input resolution
+    ///
+    /// A pipeline upscales if its result texture is larger than the input in either
+    /// direction, and restores otherwise. The native-resolution passes of an upscaler only
+    /// extract features for its upscaling step, and the denoising of the Upscale+Denoise
+    /// models is not visible in their structure, so a single pipeline is never classified
+    /// as [`UpscaleAndRestore`](PipelineClass::UpscaleAndRestore); use
+    /// [`PipelineClass::of_chain`] to classify a preset.
+    ///
+    /// # Returns
+    /// [`Upscale`](PipelineClass::Upscale) or [`Restore`](PipelineClass::Restore)
+    pub fn classification(&self) -> PipelineClass {
+        let (scale_x, scale_y) = self.result_texture().scale_factor;
+        if scale_x.numerator > scale_x.denominator || scale_y.numerator > scale_y.denominator {
+            PipelineClass::Upscale
+        } else {
+            PipelineClass::Restore
+        }
+    }
+
+    /// Returns a copy of this pipeline with every size scaled by a factor
+    ///
+    /// Multiplies the scale factor of every physical texture, including the source, and
+    /// the compute dispatch scale of every pass, so the copy describes this pipeline running
+    /// on an input `scale_factor` times the size the scale factors are relative to. For
+    /// example, sizing a restore pipeline that runs after a 2x upscale against the original
+    /// video size. The ratios between textures are unchanged, which is what the embedded
+    /// WGSL depends on, so the shaders and bindings are shared with this pipeline rather
+    /// than regenerated; only the texture and pass lists are copied.
+    ///
+    /// This cannot change the factor a pipeline upscales by, which is fixed by its
+    /// shaders. `PipelineExecutor` sizes textures relative to its input texture, so it
+    /// expects the unscaled pipeline.
+    ///
+    /// # Arguments
+    /// * `scale_factor` - Factor to scale every size by
+    ///
+    /// # Returns
+    /// The rescaled pipeline
+    pub fn with_scale_factor(&self, scale_factor: ScaleFactor) -> ExecutablePipeline {
+        let textures = self
+            .textures
+            .iter()
+            .map(|texture| PhysicalTexture {
+                scale_factor: (texture.scale_factor.0.multiply(scale_factor), texture.scale_factor.1.multiply(scale_factor)),
+                ..texture.clone()
+            })
+            .collect::<Vec<_>>();
+        let scale = scale_factor.numerator as f64 / scale_factor.denominator as f64;
+        let passes = self
+            .passes
+            .iter()
+            .map(|pass| ExecutablePass {
+                compute_scale_factors: (pass.compute_scale_factors.0 * scale, pass.compute_scale_factors.1 * scale),
+                ..pass.clone()
+            })
+            .collect::<Vec<_>>();
+
+        ExecutablePipeline {
+            id: self.id,
+            name: self.name,
+            description: self.description,
+            textures: Cow::Owned(textures),
+            samplers: self.samplers,
+            passes: Cow::Owned(passes),
+        }
+    }
+
+    /// Renders the passes and textures of this pipeline as a Mermaid flowchart
+    ///
+    /// The result embeds in Markdown (e.g. in a ```` ```mermaid ```` block on GitHub) to
+    /// document a pipeline. Every pass is a node labeled with its name and dispatch
+    /// scale, linked from the textures it reads and to the textures it writes. Texture
+    /// nodes show the physical texture id, the channel count, and the scale factor
+    /// relative to the input. A physical texture that is written again after earlier
+    /// passes used it gets a new node marked as reused, so the edges follow the data
+    /// rather than the allocation. The source and result textures are highlighted.
+    ///
+    /// # Returns
+    /// The flowchart source, starting with `flowchart TD`
+    pub fn to_mermaid(&self) -> String {
+        let result_id = self.result_texture().id;
+        let texture_node = |id: u32, version: u32| format!("t{id}_{version}");
+
+        let mut lines = vec!["flowchart TD".to_string()];
+        // Latest node of every physical texture, advanced by each write
+        let mut versions: HashMap<u32, u32> = HashMap::new();
+        let declare_texture = |lines: &mut Vec<String>, versions: &mut HashMap<u32, u32>, id: u32| {
+            let version = versions.get(&id).map_or(0, |version| version + 1);
+            versions.insert(id, version);
+            let label = match self.textures.iter().find(|texture| texture.id == id) {
+                Some(texture) => texture.mermaid_label(version > 0),
+                None => format!("t{id}"),
+            };
+            lines.push(format!("    {}([\"{label}\"])", texture_node(id, version)));
+            version
+        };
+
+        for texture in self.textures.iter().filter(|texture| texture.is_source) {
+            declare_texture(&mut lines, &mut versions, texture.id);
+        }
+
+        for (index, pass) in self.passes.iter().enumerate() {
+            let (scale_x, scale_y) = pass.compute_scale_factors;
+            let dispatch = if scale_x == scale_y { format!("{scale_x}x") } else { format!("{scale_x}x by {scale_y}x") };
+            lines.push(format!("    p{index}[\"{}<br/>dispatch {dispatch}\"]", pass.name.replace('"', "#quot;")));
+
+            for input in pass.input_textures {
+                let id = input.physical_texture_id;
+                let version = match versions.get(&id) {
+                    Some(&version) => version,
+                    None => declare_texture(&mut lines, &mut versions, id),
+                };
+                lines.push(format!("    {} --> p{index}", texture_node(id, version)));
+            }
+            for output in pass.output_textures {
+                let id = output.physical_texture_id;
+                let version = declare_texture(&mut lines, &mut versions, id);
+                lines.push(format!("    p{index} --> {}", texture_node(id, version)));
+            }
+        }
+
+        lines.push("    classDef source stroke-width:3px".to_string());
+        lines.push("    classDef result stroke-width:3px,stroke-dasharray:4".to_string());
+        for texture in self.textures.iter().filter(|texture| texture.is_source) {
+            lines.push(format!("    class {} source", texture_node(texture.id, 0)));
+        }
+        if let Some(&version) = versions.get(&result_id) {
+            lines.push(format!("    class {} result", texture_node(result_id, version)));
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    /// Returns the physical texture written by the first output of the last pass
+    pub(crate) fn result_texture(&self) -> &PhysicalTexture {
+        let result_id = self.passes.last().unwrap().output_textures.first().unwrap().physical_texture_id;
+        self.textures.iter().find(|texture| texture.id == result_id).unwrap()
+    }
 }
 
 /// Represents a physical texture resource in the GPU
@@ -52,9 +363,42 @@ pub struct PhysicalTexture {
     pub is_source: bool,
 }
 
+impl PhysicalTexture {
+    /// Calculates the dimensions of this texture for a given input size
+    pub(crate) fn dimensions(&self, input_size: (u32, u32)) -> (u32, u32) {
+        (self.scale_factor.0.apply(input_size.0), self.scale_factor.1.apply(input_size.1))
+    }
+
+    /// Builds the label of this texture in `ExecutablePipeline::to_mermaid`
+    ///
+    /// # Arguments
+    /// * `reused` - Whether earlier passes already used this physical texture
+    fn mermaid_label(&self, reused: bool) -> String {
+        // Multiplying by one reduces the fraction, e.g. 4/2 to 2
+        let one = ScaleFactor { numerator: 1, denominator: 1 };
+        let scale = |factor: ScaleFactor| match factor.multiply(one) {
+            ScaleFactor { numerator, denominator: 1 } => format!("{numerator}x"),
+            ScaleFactor { numerator, denominator } => format!("{numerator}/{denominator}x"),
+        };
+        let (scale_x, scale_y) = (scale(self.scale_factor.0), scale(self.scale_factor.1));
+        let scale = if scale_x == scale_y { scale_x } else { format!("{scale_x} by {scale_y}") };
+
+        let mut label = format!("t{}", self.id);
+        if self.is_source {
+            label += " source";
+        }
+        if reused {
+            label += " reused";
+        }
+        format!("{label}<br/>{} ch, {scale}", self.components)
+    }
+}
+
 /// A single shader pass within a pipeline
 #[derive(Debug, Clone)]
 pub struct ExecutablePass {
+    /// Identifier of the pass within its pipeline
+    pub id: &'static str,
     /// Human-readable name for debugging
     pub name: &'static str,
     /// WGSL shader source code
@@ -69,6 +413,19 @@ pub struct ExecutablePass {
     pub samplers: &'static [SamplerBinding],
 }
 
+impl ExecutablePass {
+    /// Calculates the compute dispatch size of this pass in invocations
+    ///
+    /// Floors like texture allocation does, but never below one invocation for a
+    /// non-empty input, so passes of tiny inputs still dispatch a workgroup.
+    pub(crate) fn compute_dimensions(&self, input_size: (u32, u32)) -> (u32, u32) {
+        (
+            floor_dimension(input_size.0, input_size.0 as f64 * self.compute_scale_factors.0),
+            floor_dimension(input_size.1, input_size.1 as f64 * self.compute_scale_factors.1),
+        )
+    }
+}
+
 /// Binding information for an input texture
 #[derive(Debug, Clone)]
 pub struct InputTextureBinding {
@@ -0,0 +1,39 @@
+//! Executor abstraction
+//!
+//! This module defines the interface the examples drive Anime4K processing through, so
+//! an alternative executor (e.g. one that batches passes differently) can be swapped in
+//! for `PipelineExecutor` without changing the surrounding flow.
+
+use crate::PassTiming;
+
+/// Records Anime4K processing of the source texture it was bound to
+///
+/// Implementations are created for a fixed source texture and size, and own the texture
+/// the result is written to.
+pub trait Anime4kExecutor {
+    /// Records the processing commands
+    ///
+    /// Called once per frame, so implementations should not create GPU resources here.
+    ///
+    /// # Arguments
+    /// * `encoder` - The command encoder to record commands into
+    fn pass(&self, encoder: &mut wgpu::CommandEncoder);
+
+    /// Returns the texture the processed result is written to
+    fn output_texture(&self) -> &wgpu::Texture;
+
+    /// Runs the processing once and measures the GPU time of each pass
+    ///
+    /// The default implementation reports that profiling is unsupported.
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device, with `wgpu::Features::TIMESTAMP_QUERY` enabled
+    /// * `queue` - The wgpu command queue
+    ///
+    /// # Returns
+    /// The timing of every pass in execution order
+    fn profile(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<Vec<PassTiming>, Box<dyn std::error::Error>> {
+        let _ = (device, queue);
+        Err(Box::new(std::io::Error::new(std::io::ErrorKind::Unsupported, "This executor does not support profiling")))
+    }
+}
@@ -5,10 +5,30 @@
 //! various quality presets and performance levels to balance quality and speed.
 
 pub(crate) mod executable_pipeline;
+mod executor;
+mod lut;
+mod nan_guard;
+mod output_stats;
+mod padding;
 mod pipeline_executor;
+mod platform;
+mod pre_denoise;
+mod preview;
+mod raw;
+mod sampler_cache;
+mod tiled;
 
 pub mod pipelines;
 pub mod presets;
 
-pub use executable_pipeline::ExecutablePipeline;
-pub use pipeline_executor::PipelineExecutor;
+pub use executable_pipeline::{ExecutablePass, ExecutablePipeline, InputTextureBinding, OutputTextureBinding, PhysicalTexture, PipelineClass, SamplerBinding, SamplerFilterMode, ScaleFactor};
+pub use executor::Anime4kExecutor;
+pub use lut::{CubeLut, MAX_LUT_SIZE};
+pub use output_stats::ChannelStats;
+pub use pipeline_executor::{PassTiming, PipelineExecutor};
+pub use platform::{VULKAN_VIDEO_COMPILED, vulkan_video_available};
+pub use pre_denoise::PreDenoise;
+pub use preview::{PreviewExecutor, Quality};
+pub use raw::{ChannelOrder, upscale_raw_f32};
+pub use sampler_cache::{SamplerCache, SamplerKey};
+pub use tiled::{TileResult, upscale_raw_f32_tiled_streaming};
@@ -0,0 +1,269 @@
+//! 3D color lookup tables applied after upscaling
+//!
+//! This module parses LUTs in the `.cube` format and grades the output of the pipelines
+//! with them in a final pass, so footage can be upscaled and color graded without a
+//! round trip through another tool. The LUT is uploaded as a 3D texture and sampled
+//! trilinearly; inputs outside the LUT domain are clamped to its edges.
+
+/// Workgroup size of the LUT pass in each dimension
+const WORKGROUP_SIZE: u32 = 8;
+
+/// Largest LUT size accepted along each axis
+pub const MAX_LUT_SIZE: u32 = 256;
+
+/// A 3D color lookup table
+#[derive(Debug, Clone, PartialEq)]
+pub struct CubeLut {
+    /// Title given in the file, if any
+    pub title: Option<String>,
+    /// Number of entries along each axis
+    pub size: u32,
+    /// Input value mapped to the first entry of each axis
+    pub domain_min: [f32; 3],
+    /// Input value mapped to the last entry of each axis
+    pub domain_max: [f32; 3],
+    /// Output colors, with red varying fastest, then green, then blue
+    pub table: Vec<[f32; 3]>,
+}
+
+impl CubeLut {
+    /// Creates the identity LUT of a given size, useful as a starting point and for testing
+    ///
+    /// # Arguments
+    /// * `size` - Number of entries along each axis (2 to `MAX_LUT_SIZE`)
+    ///
+    /// # Panics
+    /// Panics if `size` is outside 2 to `MAX_LUT_SIZE`
+    pub fn identity(size: u32) -> Self {
+        assert!((2..=MAX_LUT_SIZE).contains(&size), "LUT size {size} is outside 2 to {MAX_LUT_SIZE}");
+        let step = 1.0 / (size - 1) as f32;
+        let table = (0..size * size * size)
+            .map(|index| [(index % size) as f32 * step, (index / size % size) as f32 * step, (index / (size * size)) as f32 * step])
+            .collect();
+        Self {
+            title: None,
+            size,
+            domain_min: [0.0; 3],
+            domain_max: [1.0; 3],
+            table,
+        }
+    }
+}
+
+/// Parses a line of three numbers
+fn parse_triple(values: &[&str], line_number: usize) -> Result<[f32; 3], String> {
+    match values {
+        [r, g, b] => {
+            let parse = |value: &str| match value.parse::<f32>() {
+                Ok(value) if value.is_finite() => Ok(value),
+                _ => Err(format!("line {line_number}: invalid number '{value}'")),
+            };
+            Ok([parse(r)?, parse(g)?, parse(b)?])
+        }
+        _ => Err(format!("line {line_number}: expected three numbers")),
+    }
+}
+
+impl std::str::FromStr for CubeLut {
+    type Err = String;
+
+    /// Parses the contents of a `.cube` file
+    ///
+    /// Supports `TITLE`, `LUT_3D_SIZE`, `DOMAIN_MIN`, `DOMAIN_MAX`, and `#` comment lines. 1D
+    /// LUTs are rejected. The number of entries must match the size, and every domain
+    /// maximum must exceed its minimum.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut title = None;
+        let mut size = None;
+        let mut domain_min = [0.0; 3];
+        let mut domain_max = [1.0; 3];
+        let mut table = Vec::new();
+
+        for (index, line) in s.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap_or_default();
+            let values: Vec<_> = words.collect();
+            match keyword {
+                "TITLE" => title = Some(line["TITLE".len()..].trim().trim_matches('"').to_string()),
+                "LUT_3D_SIZE" => {
+                    let value = match values.as_slice() {
+                        [value] => value.parse::<u32>().ok().filter(|size| (2..=MAX_LUT_SIZE).contains(size)),
+                        _ => None,
+                    };
+                    size = Some(value.ok_or_else(|| format!("line {line_number}: LUT_3D_SIZE must be between 2 and {MAX_LUT_SIZE}"))?);
+                }
+                "LUT_1D_SIZE" => return Err(format!("line {line_number}: 1D LUTs are not supported")),
+                "DOMAIN_MIN" => domain_min = parse_triple(&values, line_number)?,
+                "DOMAIN_MAX" => domain_max = parse_triple(&values, line_number)?,
+                _ if keyword.starts_with(|c: char| c.is_ascii_alphabetic()) => {
+                    // Other keywords (e.g. LUT_IN_VIDEO_RANGE) do not change the table
+                }
+                _ => {
+                    if size.is_none() {
+                        return Err(format!("line {line_number}: table data before LUT_3D_SIZE"));
+                    }
+                    let mut entry = vec![keyword];
+                    entry.extend(values);
+                    table.push(parse_triple(&entry, line_number)?);
+                }
+            }
+        }
+
+        let size = size.ok_or("Missing LUT_3D_SIZE")?;
+        let expected = (size as usize).pow(3);
+        if table.len() != expected {
+            return Err(format!("Expected {expected} entries for a LUT of size {size}, got {}", table.len()));
+        }
+        if (0..3).any(|channel| domain_max[channel] <= domain_min[channel]) {
+            return Err(format!("DOMAIN_MAX {domain_max:?} must exceed DOMAIN_MIN {domain_min:?} in every channel"));
+        }
+
+        Ok(Self {
+            title,
+            size,
+            domain_min,
+            domain_max,
+            table,
+        })
+    }
+}
+
+/// The LUT dispatch, the uploaded LUT, and the texture it writes
+#[derive(Debug)]
+pub(crate) struct LutPass {
+    /// The compute pipeline with the LUT domain baked in
+    compute_pipeline: wgpu::ComputePipeline,
+    /// Bind group with the input, LUT, and output textures
+    bind_group: wgpu::BindGroup,
+    /// Texture the pass grades
+    input_texture: wgpu::Texture,
+    /// Graded copy of the input
+    output_texture: wgpu::Texture,
+}
+
+impl LutPass {
+    /// Uploads a LUT and creates the pass grading an input texture with it
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device
+    /// * `queue` - The wgpu command queue, used to upload the LUT
+    /// * `input_texture` - Texture to grade (must have TEXTURE_BINDING usage)
+    /// * `lut` - LUT to apply
+    /// * `sampler` - Linear sampler clamping to the edge
+    pub(crate) fn new(device: &wgpu::Device, queue: &wgpu::Queue, input_texture: &wgpu::Texture, lut: &CubeLut, sampler: &wgpu::Sampler) -> Self {
+        let lut_size = wgpu::Extent3d {
+            width: lut.size,
+            height: lut.size,
+            depth_or_array_layers: lut.size,
+        };
+        let lut_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("LUT Texture"),
+            size: lut_size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D3,
+            format: wgpu::TextureFormat::Rgba32Float,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let texels: Vec<u8> = lut.table.iter().flat_map(|&[r, g, b]| [r, g, b, 1.0]).flat_map(f32::to_le_bytes).collect();
+        queue.write_texture(
+            lut_texture.as_image_copy(),
+            &texels,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(lut.size * 16),
+                rows_per_image: Some(lut.size),
+            },
+            lut_size,
+        );
+
+        let output_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("LUT Output Texture"),
+            size: input_texture.size(),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba32Float,
+            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        let vec3 = |values: [f32; 3]| format!("vec3f({:.9}, {:.9}, {:.9})", values[0], values[1], values[2]);
+        let shader = include_str!("lut.wgsl")
+            .replace("{{ LUT_SIZE }}", &format!("{:.1}", lut.size as f32))
+            .replace("{{ DOMAIN_MIN }}", &vec3(lut.domain_min))
+            .replace("{{ DOMAIN_MAX }}", &vec3(lut.domain_max));
+
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("LUT"),
+            source: wgpu::ShaderSource::Wgsl(shader.into()),
+        });
+
+        let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("LUT"),
+            layout: None,
+            module: &shader_module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("LUT"),
+            layout: &compute_pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&input_texture.create_view(&Default::default())),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&lut_texture.create_view(&Default::default())),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(&output_texture.create_view(&Default::default())),
+                },
+            ],
+        });
+
+        Self {
+            compute_pipeline,
+            bind_group,
+            input_texture: input_texture.clone(),
+            output_texture,
+        }
+    }
+
+    /// Returns the texture the pass grades
+    pub(crate) fn input_texture(&self) -> &wgpu::Texture {
+        &self.input_texture
+    }
+
+    /// Returns the graded texture
+    pub(crate) fn output_texture(&self) -> &wgpu::Texture {
+        &self.output_texture
+    }
+
+    /// Records the LUT dispatch
+    pub(crate) fn pass(&self, encoder: &mut wgpu::CommandEncoder) {
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("LUT"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&self.compute_pipeline);
+        compute_pass.set_bind_group(0, &self.bind_group, &[]);
+        compute_pass.dispatch_workgroups(self.output_texture.width().div_ceil(WORKGROUP_SIZE), self.output_texture.height().div_ceil(WORKGROUP_SIZE), 1);
+    }
+}
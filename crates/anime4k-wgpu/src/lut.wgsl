@@ -0,0 +1,31 @@
+// 3D LUT compute shader
+//
+// Grades each texel of the input by sampling a 3D color lookup table trilinearly. Colors
+// are normalized to the LUT domain and clamped to its edges, and the sample positions
+// are inset by half a texel so the first and last entries map to the domain bounds.
+// Alpha is passed through. The LUT size and domain are substituted when the shader is
+// created.
+
+const LUT_SIZE: f32 = {{ LUT_SIZE }};
+const DOMAIN_MIN: vec3f = {{ DOMAIN_MIN }};
+const DOMAIN_MAX: vec3f = {{ DOMAIN_MAX }};
+
+@group(0) @binding(0) var input_texture: texture_2d<f32>;
+@group(0) @binding(1) var lut_texture: texture_3d<f32>;
+@group(0) @binding(2) var lut_sampler: sampler;
+@group(0) @binding(3) var output_texture: texture_storage_2d<rgba32float, write>;
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) global_id: vec3u) {
+    let dims = textureDimensions(input_texture);
+    if global_id.x >= dims.x || global_id.y >= dims.y {
+        return;
+    }
+
+    let pos = vec2i(global_id.xy);
+    let color = textureLoad(input_texture, pos, 0);
+    let normalized = clamp((color.rgb - DOMAIN_MIN) / (DOMAIN_MAX - DOMAIN_MIN), vec3f(0.0), vec3f(1.0));
+    let coords = (normalized * (LUT_SIZE - 1.0) + 0.5) / LUT_SIZE;
+    let graded = textureSampleLevel(lut_texture, lut_sampler, coords, 0.0).rgb;
+    textureStore(output_texture, pos, vec4f(graded, color.a));
+}
@@ -0,0 +1,196 @@
+//! Debug guard against non-finite pass outputs
+//!
+//! This module inserts a check after each shader pass that replaces NaN and infinite
+//! texels with magenta and records which passes produced them, so the first misbehaving
+//! pass can be found without bisecting the pipeline by hand.
+
+/// Byte distance between flags in the flag buffer
+///
+/// Each guard binds its own flag, so flags are spaced by the default minimum storage
+/// buffer offset alignment.
+const FLAG_STRIDE: u64 = 256;
+
+/// Guard workgroup size in each dimension
+const WORKGROUP_SIZE: u32 = 8;
+
+/// Shared state of the guards of a pipeline executor
+///
+/// Holds one flag per guarded pass. Flags are sticky: once a pass has produced a
+/// non-finite value, it stays reported for the lifetime of the executor.
+#[derive(Debug)]
+pub(crate) struct NanGuard {
+    /// Flags set to 1 by the guard shader, one every `FLAG_STRIDE` bytes
+    flags_buffer: wgpu::Buffer,
+    /// Names of the guarded passes, indexed like the flags
+    pass_names: Vec<String>,
+}
+
+/// A guard check recorded after a single pass output
+#[derive(Debug)]
+pub(crate) struct NanGuardPass {
+    /// The guard compute pipeline for the texture's format
+    compute_pipeline: wgpu::ComputePipeline,
+    /// Bind group with the guarded texture, scratch texture, and flag
+    bind_group: wgpu::BindGroup,
+    /// Texture receiving the sanitized copy
+    scratch_texture: wgpu::Texture,
+    /// The guarded pass output, overwritten with the sanitized copy
+    target_texture: wgpu::Texture,
+}
+
+impl NanGuard {
+    /// Creates the guard state for a list of passes
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device
+    /// * `pass_names` - Names of all passes that will be guarded, in execution order
+    pub(crate) fn new(device: &wgpu::Device, pass_names: Vec<String>) -> Self {
+        let flags_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("NaN Guard Flags"),
+            size: pass_names.len().max(1) as u64 * FLAG_STRIDE,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        Self { flags_buffer, pass_names }
+    }
+
+    /// Creates a guard for one output texture of a pass
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device
+    /// * `pass_index` - Index of the pass in the list given to `new`
+    /// * `texture` - Output texture to guard (must have TEXTURE_BINDING and COPY_DST usage)
+    pub(crate) fn create_pass(&self, device: &wgpu::Device, pass_index: usize, texture: &wgpu::Texture) -> NanGuardPass {
+        let format = texture.format();
+        let format_name = match format {
+            wgpu::TextureFormat::R32Float => "r32float",
+            wgpu::TextureFormat::Rg32Float => "rg32float",
+            _ => "rgba32float",
+        };
+        let label = format!("NaN Guard {}", self.pass_names[pass_index]);
+
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(&label),
+            source: wgpu::ShaderSource::Wgsl(include_str!("nan_guard.wgsl").replace("{{ FORMAT }}", format_name).into()),
+        });
+
+        let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some(&label),
+            layout: None,
+            module: &shader_module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        let scratch_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(&label),
+            size: texture.size(),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(&label),
+            layout: &compute_pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&texture.create_view(&wgpu::TextureViewDescriptor::default())),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&scratch_texture.create_view(&wgpu::TextureViewDescriptor::default())),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &self.flags_buffer,
+                        offset: pass_index as u64 * FLAG_STRIDE,
+                        size: wgpu::BufferSize::new(4),
+                    }),
+                },
+            ],
+        });
+
+        NanGuardPass {
+            compute_pipeline,
+            bind_group,
+            scratch_texture,
+            target_texture: texture.clone(),
+        }
+    }
+
+    /// Reads back the flags and returns the names of passes that produced non-finite values
+    ///
+    /// Blocks until all previously submitted work has completed.
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device
+    /// * `queue` - The wgpu command queue
+    ///
+    /// # Returns
+    /// Names of the flagged passes in execution order, or an error if the readback failed
+    pub(crate) fn flagged_passes(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("NaN Guard Flags Readback"),
+            size: self.flags_buffer.size(),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("NaN Guard Flags") });
+        encoder.copy_buffer_to_buffer(&self.flags_buffer, 0, &readback_buffer, 0, self.flags_buffer.size());
+        queue.submit(std::iter::once(encoder.finish()));
+
+        // Map the readback buffer and wait for completion
+        let buffer_slice = readback_buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::PollType::Wait)?;
+        receiver.recv()??;
+
+        let data = buffer_slice.get_mapped_range();
+        let flagged = self
+            .pass_names
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| {
+                let offset = *i * FLAG_STRIDE as usize;
+                u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()) != 0
+            })
+            .map(|(_, name)| name.clone())
+            .collect();
+        drop(data);
+        readback_buffer.unmap();
+
+        Ok(flagged)
+    }
+}
+
+impl NanGuardPass {
+    /// Records the guard check and the copy of the sanitized texture back over the output
+    ///
+    /// # Arguments
+    /// * `encoder` - The command encoder to record commands into
+    pub(crate) fn pass(&self, encoder: &mut wgpu::CommandEncoder) {
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("NaN Guard"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&self.compute_pipeline);
+            compute_pass.set_bind_group(0, &self.bind_group, &[]);
+            compute_pass.dispatch_workgroups(self.target_texture.width().div_ceil(WORKGROUP_SIZE), self.target_texture.height().div_ceil(WORKGROUP_SIZE), 1);
+        }
+
+        encoder.copy_texture_to_texture(self.scratch_texture.as_image_copy(), self.target_texture.as_image_copy(), self.target_texture.size());
+    }
+}
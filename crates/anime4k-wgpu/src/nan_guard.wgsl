@@ -0,0 +1,31 @@
+// Non-finite value guard compute shader
+//
+// Copies a pass output into a scratch texture, replacing texels with any NaN or infinite
+// component by magenta and raising the flag of the pass. The storage format placeholder
+// is substituted at runtime with the format of the guarded texture.
+
+@group(0) @binding(0) var input_texture: texture_2d<f32>;
+@group(0) @binding(1) var output_texture: texture_storage_2d<{{ FORMAT }}, write>;
+@group(0) @binding(2) var<storage, read_write> flag: atomic<u32>;
+
+// Checks the exponent bits directly, as comparisons against NaN may be optimized away
+fn is_finite(value: vec4f) -> bool {
+    return all((bitcast<vec4u>(value) & vec4u(0x7f800000u)) != vec4u(0x7f800000u));
+}
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) global_id: vec3u) {
+    let dims = textureDimensions(input_texture);
+    if global_id.x >= dims.x || global_id.y >= dims.y {
+        return;
+    }
+
+    let pos = vec2i(global_id.xy);
+    let value = textureLoad(input_texture, pos, 0);
+    if is_finite(value) {
+        textureStore(output_texture, pos, value);
+    } else {
+        textureStore(output_texture, pos, vec4f(1.0, 0.0, 1.0, 1.0));
+        atomicStore(&flag, 1u);
+    }
+}
@@ -0,0 +1,156 @@
+//! GPU-side statistics of pipeline output textures
+//!
+//! This module reduces a texture to per-channel min/max/mean on the GPU and reads back
+//! only the reduced values, as a cheap sanity check for broken or NaN-producing shaders.
+
+/// Maximum number of workgroups dispatched for the reduction
+///
+/// Each workgroup produces one partial result, so this bounds the size of the readback.
+const MAX_REDUCTION_WORKGROUPS: u32 = 64;
+
+/// Size in bytes of one partial result (min, max, sum, non-finite count as 4-component vectors)
+const PARTIAL_SIZE: u64 = 4 * 16;
+
+/// Per-channel statistics of a texture
+///
+/// Channels absent from the texture format read as 0 for RGB and 1 for alpha.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelStats {
+    /// Minimum finite value of each channel (RGBA)
+    pub min: [f32; 4],
+    /// Maximum finite value of each channel (RGBA)
+    pub max: [f32; 4],
+    /// Mean of the finite values of each channel (RGBA)
+    pub mean: [f32; 4],
+    /// Number of NaN or infinite values in each channel (RGBA)
+    pub non_finite_count: [u32; 4],
+}
+
+impl ChannelStats {
+    /// Returns true if any channel contains NaN or infinite values
+    pub fn has_non_finite(&self) -> bool {
+        self.non_finite_count.iter().any(|&count| count > 0)
+    }
+
+    /// Returns true if every color channel is zero everywhere
+    pub fn is_all_black(&self) -> bool {
+        (0..3).all(|i| self.min[i] == 0.0 && self.max[i] == 0.0)
+    }
+}
+
+/// Computes per-channel statistics of a texture on the GPU
+///
+/// Submits a reduction compute pass and blocks until the partial results are read back.
+///
+/// # Arguments
+/// * `device` - The wgpu device
+/// * `queue` - The wgpu command queue
+/// * `texture` - Float texture to analyze (must have TEXTURE_BINDING usage)
+///
+/// # Returns
+/// The statistics of all texels, or an error if the readback failed
+pub(crate) fn compute_channel_stats(device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) -> Result<ChannelStats, Box<dyn std::error::Error>> {
+    let workgroup_count = texture.height().clamp(1, MAX_REDUCTION_WORKGROUPS);
+    let buffer_size = workgroup_count as u64 * PARTIAL_SIZE;
+
+    let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Output Stats"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("output_stats.wgsl").into()),
+    });
+
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Output Stats"),
+        layout: None,
+        module: &shader_module,
+        entry_point: Some("main"),
+        compilation_options: Default::default(),
+        cache: None,
+    });
+
+    let partials_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Output Stats Partials"),
+        size: buffer_size,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+
+    let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Output Stats Readback"),
+        size: buffer_size,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Output Stats"),
+        layout: &pipeline.get_bind_group_layout(0),
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&texture_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: partials_buffer.as_entire_binding(),
+            },
+        ],
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Output Stats") });
+    {
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Output Stats"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&pipeline);
+        compute_pass.set_bind_group(0, &bind_group, &[]);
+        compute_pass.dispatch_workgroups(workgroup_count, 1, 1);
+    }
+    encoder.copy_buffer_to_buffer(&partials_buffer, 0, &readback_buffer, 0, buffer_size);
+    queue.submit(std::iter::once(encoder.finish()));
+
+    // Map the readback buffer and wait for completion
+    let buffer_slice = readback_buffer.slice(..);
+    let (sender, receiver) = std::sync::mpsc::channel();
+    buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    device.poll(wgpu::PollType::Wait)?;
+    receiver.recv()??;
+
+    // Combine the partial results; sums are accumulated in f64 to limit precision loss
+    let data = buffer_slice.get_mapped_range();
+    let words: Vec<u32> = data.chunks_exact(4).map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap())).collect();
+    drop(data);
+    readback_buffer.unmap();
+
+    let mut min = [f32::INFINITY; 4];
+    let mut max = [f32::NEG_INFINITY; 4];
+    let mut sum = [0.0f64; 4];
+    let mut non_finite_count = [0u32; 4];
+    for partial in words.chunks_exact(16) {
+        for c in 0..4 {
+            min[c] = min[c].min(f32::from_bits(partial[c]));
+            max[c] = max[c].max(f32::from_bits(partial[4 + c]));
+            sum[c] += f32::from_bits(partial[8 + c]) as f64;
+            non_finite_count[c] += partial[12 + c];
+        }
+    }
+
+    let texel_count = texture.width() as u64 * texture.height() as u64;
+    let mut mean = [0.0f32; 4];
+    for c in 0..4 {
+        let finite_count = texel_count - non_finite_count[c] as u64;
+        if finite_count > 0 {
+            mean[c] = (sum[c] / finite_count as f64) as f32;
+        } else {
+            // No finite values at all; report NaN rather than the reduction's sentinel values
+            min[c] = f32::NAN;
+            max[c] = f32::NAN;
+            mean[c] = f32::NAN;
+        }
+    }
+
+    Ok(ChannelStats { min, max, mean, non_finite_count })
+}
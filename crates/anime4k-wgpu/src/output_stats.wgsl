@@ -0,0 +1,72 @@
+// Per-channel statistics reduction compute shader
+//
+// Each workgroup reduces a band of rows (rows workgroup_id, workgroup_id + num_workgroups, ...)
+// into one partial result. Non-finite values are counted and excluded from min/max/sum.
+
+struct Partial {
+    min_value: vec4f,
+    max_value: vec4f,
+    sum: vec4f,
+    non_finite: vec4u,
+}
+
+const WORKGROUP_SIZE: u32 = 64u;
+
+@group(0) @binding(0) var input_texture: texture_2d<f32>;
+@group(0) @binding(1) var<storage, read_write> partials: array<Partial>;
+
+var<workgroup> shared_min: array<vec4f, WORKGROUP_SIZE>;
+var<workgroup> shared_max: array<vec4f, WORKGROUP_SIZE>;
+var<workgroup> shared_sum: array<vec4f, WORKGROUP_SIZE>;
+var<workgroup> shared_non_finite: array<vec4u, WORKGROUP_SIZE>;
+
+// Checks the exponent bits directly, as comparisons against NaN may be optimized away
+fn is_finite(value: f32) -> bool {
+    return (bitcast<u32>(value) & 0x7f800000u) != 0x7f800000u;
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(local_invocation_index) local_index: u32, @builtin(workgroup_id) workgroup_id: vec3u, @builtin(num_workgroups) num_workgroups: vec3u) {
+    let dims = textureDimensions(input_texture);
+
+    var min_value = vec4f(3.40282347e+38);
+    var max_value = vec4f(-3.40282347e+38);
+    var sum = vec4f(0.0);
+    var non_finite = vec4u(0u);
+
+    for (var y = workgroup_id.x; y < dims.y; y += num_workgroups.x) {
+        for (var x = local_index; x < dims.x; x += WORKGROUP_SIZE) {
+            let value = textureLoad(input_texture, vec2u(x, y), 0);
+            for (var c = 0u; c < 4u; c++) {
+                if is_finite(value[c]) {
+                    min_value[c] = min(min_value[c], value[c]);
+                    max_value[c] = max(max_value[c], value[c]);
+                    sum[c] += value[c];
+                } else {
+                    non_finite[c] += 1u;
+                }
+            }
+        }
+    }
+
+    shared_min[local_index] = min_value;
+    shared_max[local_index] = max_value;
+    shared_sum[local_index] = sum;
+    shared_non_finite[local_index] = non_finite;
+    workgroupBarrier();
+
+    // Tree reduction within the workgroup
+    for (var stride = WORKGROUP_SIZE / 2u; stride > 0u; stride /= 2u) {
+        if local_index < stride {
+            shared_min[local_index] = min(shared_min[local_index], shared_min[local_index + stride]);
+            shared_max[local_index] = max(shared_max[local_index], shared_max[local_index + stride]);
+            shared_sum[local_index] += shared_sum[local_index + stride];
+            shared_non_finite[local_index] += shared_non_finite[local_index + stride];
+        }
+        workgroupBarrier();
+    }
+
+    if local_index == 0u {
+        partials[workgroup_id.x] = Partial(shared_min[0], shared_max[0], shared_sum[0], shared_non_finite[0]);
+    }
+}
@@ -0,0 +1,19 @@
+// Edge-replicating pad compute shader
+//
+// Copies the source into the top-left corner of a larger texture and fills the rest
+// with the nearest edge texel, matching what clamp-to-edge sampling reads outside the
+// source.
+
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var output_texture: texture_storage_2d<rgba32float, write>;
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) global_id: vec3u) {
+    let dims = textureDimensions(output_texture);
+    if global_id.x >= dims.x || global_id.y >= dims.y {
+        return;
+    }
+
+    let source_pos = min(vec2i(global_id.xy), vec2i(textureDimensions(source_texture)) - 1);
+    textureStore(output_texture, vec2i(global_id.xy), textureLoad(source_texture, source_pos, 0));
+}
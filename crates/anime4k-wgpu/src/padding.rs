@@ -0,0 +1,192 @@
+//! Padding inputs to a multiple of the workgroup size
+//!
+//! Every generated shader has a bounds-checked `main` and an unchecked `main_unchecked`
+//! entry point, and the executor only dispatches the unchecked one for passes whose
+//! dispatch size is a multiple of the workgroup size. This module pads the source with
+//! its edge texels to the smallest size at which every pass qualifies, and copies the
+//! part of the output that corresponds to the unpadded source out afterwards.
+
+use crate::{
+    ExecutablePipeline,
+    executable_pipeline::{COMPUTE_WORKGROUP_SIZE_X, COMPUTE_WORKGROUP_SIZE_Y},
+};
+
+/// Workgroup size of the pad pass in each dimension
+const WORKGROUP_SIZE: u32 = 8;
+
+/// Largest number of texels added along each axis
+///
+/// Pipelines whose scale factors need more than this to line up keep the checked
+/// entry point on that axis rather than growing the textures further.
+const MAX_PADDING: u32 = 64;
+
+/// Returns the smallest padded input size along one axis for which every pass dispatches a multiple of `workgroup_size`
+///
+/// # Arguments
+/// * `size` - Unpadded input size along the axis
+/// * `workgroup_size` - Workgroup size along the axis
+/// * `dispatch_sizes` - Dispatch sizes of every pass along the axis for a given input size
+fn padded_size(size: u32, workgroup_size: u32, dispatch_sizes: impl Fn(u32) -> Vec<u32>) -> u32 {
+    (size..=size + MAX_PADDING)
+        .find(|&candidate| dispatch_sizes(candidate).iter().all(|dispatch_size| dispatch_size % workgroup_size == 0))
+        .unwrap_or(size)
+}
+
+/// Returns the dispatch sizes of every pass of a chain of pipelines for an input size
+fn dispatch_sizes(executable_pipeline: &[&'static ExecutablePipeline], input_size: (u32, u32)) -> Vec<(u32, u32)> {
+    let mut input_size = input_size;
+    let mut sizes = Vec::new();
+    for pipeline in executable_pipeline {
+        sizes.extend(pipeline.passes.iter().map(|pass| pass.compute_dimensions(input_size)));
+        input_size = pipeline.output_dimensions(input_size.0, input_size.1);
+    }
+    sizes
+}
+
+/// Returns the input size to pad to so that every pass can skip its bounds check
+///
+/// # Arguments
+/// * `executable_pipeline` - Array of executable pipelines to chain together
+/// * `input_width` - Unpadded input width in pixels
+/// * `input_height` - Unpadded input height in pixels
+///
+/// # Returns
+/// The padded (width, height), which equals the input size along an axis that needs
+/// no padding or would need more than `MAX_PADDING` texels
+pub(crate) fn padded_dimensions(executable_pipeline: &[&'static ExecutablePipeline], input_width: u32, input_height: u32) -> (u32, u32) {
+    let width = padded_size(input_width, COMPUTE_WORKGROUP_SIZE_X, |width| {
+        dispatch_sizes(executable_pipeline, (width, input_height)).into_iter().map(|(width, _)| width).collect()
+    });
+    let height = padded_size(input_height, COMPUTE_WORKGROUP_SIZE_Y, |height| {
+        dispatch_sizes(executable_pipeline, (input_width, height)).into_iter().map(|(_, height)| height).collect()
+    });
+    (width, height)
+}
+
+/// The pad dispatch before the pipelines and the output copy after them
+#[derive(Debug)]
+pub(crate) struct Padding {
+    /// The compute pipeline replicating edge texels
+    compute_pipeline: wgpu::ComputePipeline,
+    /// Bind group with the source and padded textures
+    bind_group: wgpu::BindGroup,
+    /// Padded copy of the source, fed to the pipelines
+    padded_texture: wgpu::Texture,
+    /// Output of the pipelines for the padded source, set by `set_output`
+    padded_output: Option<wgpu::Texture>,
+}
+
+impl Padding {
+    /// Creates the pad pass for a source texture
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device
+    /// * `source_texture` - Texture to pad (must have TEXTURE_BINDING usage)
+    /// * `padded_dimensions` - Size to pad to, at least the size of the source
+    pub(crate) fn new(device: &wgpu::Device, source_texture: &wgpu::Texture, padded_dimensions: (u32, u32)) -> Self {
+        let padded_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Padded Source Texture"),
+            size: wgpu::Extent3d {
+                width: padded_dimensions.0,
+                height: padded_dimensions.1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba32Float,
+            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Pad"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("pad.wgsl").into()),
+        });
+
+        let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Pad"),
+            layout: None,
+            module: &shader_module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Pad"),
+            layout: &compute_pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&source_texture.create_view(&Default::default())),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&padded_texture.create_view(&Default::default())),
+                },
+            ],
+        });
+
+        Self {
+            compute_pipeline,
+            bind_group,
+            padded_texture,
+            padded_output: None,
+        }
+    }
+
+    /// Returns the padded texture to run the pipelines on
+    pub(crate) fn padded_texture(&self) -> &wgpu::Texture {
+        &self.padded_texture
+    }
+
+    /// Creates the texture receiving the unpadded part of the pipelines' output
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device
+    /// * `padded_output` - Output of the pipelines for the padded texture (must have COPY_SRC usage)
+    /// * `output_dimensions` - Output size of the pipelines for the unpadded source
+    ///
+    /// # Returns
+    /// The output texture, of the same format and usages as `padded_output`
+    pub(crate) fn set_output(&mut self, device: &wgpu::Device, padded_output: &wgpu::Texture, output_dimensions: (u32, u32)) -> wgpu::Texture {
+        self.padded_output = Some(padded_output.clone());
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Unpadded Output Texture"),
+            size: wgpu::Extent3d {
+                width: output_dimensions.0,
+                height: output_dimensions.1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: padded_output.format(),
+            usage: padded_output.usage() | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        })
+    }
+
+    /// Records the pad dispatch
+    pub(crate) fn pad(&self, encoder: &mut wgpu::CommandEncoder) {
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Pad"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&self.compute_pipeline);
+        compute_pass.set_bind_group(0, &self.bind_group, &[]);
+        compute_pass.dispatch_workgroups(self.padded_texture.width().div_ceil(WORKGROUP_SIZE), self.padded_texture.height().div_ceil(WORKGROUP_SIZE), 1);
+    }
+
+    /// Records the copy of the unpadded part of the output
+    ///
+    /// # Arguments
+    /// * `encoder` - The command encoder to record commands into
+    /// * `output_texture` - Texture returned by `set_output`
+    pub(crate) fn crop(&self, encoder: &mut wgpu::CommandEncoder, output_texture: &wgpu::Texture) {
+        if let Some(padded_output) = &self.padded_output {
+            encoder.copy_texture_to_texture(padded_output.as_image_copy(), output_texture.as_image_copy(), output_texture.size());
+        }
+    }
+}
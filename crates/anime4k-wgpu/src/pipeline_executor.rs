@@ -3,12 +3,57 @@
 //! This module contains the core pipeline execution logic that binds shader passes
 //! to wgpu resources and executes them in sequence.
 
-use crate::{ExecutablePipeline, executable_pipeline::SamplerFilterMode};
+use crate::{
+    Anime4kExecutor, ChannelStats, ExecutablePipeline, SamplerFilterMode,
+    executable_pipeline::{COMPUTE_WORKGROUP_SIZE_X, COMPUTE_WORKGROUP_SIZE_Y},
+    lut::{CubeLut, LutPass},
+    nan_guard::{NanGuard, NanGuardPass},
+    output_stats::compute_channel_stats,
+    padding::{Padding, padded_dimensions},
+    pre_denoise::{PreDenoise, PreDenoisePass},
+    sampler_cache::SamplerCache,
+};
 
-/// Compute shader workgroup size in X dimension
-const COMPUTE_WORKGROUP_SIZE_X: u32 = 8;
-/// Compute shader workgroup size in Y dimension
-const COMPUTE_WORKGROUP_SIZE_Y: u32 = 8;
+/// Texture formats the executor binds as storage textures, by component count
+const STORAGE_FORMATS: [wgpu::TextureFormat; 3] = [wgpu::TextureFormat::R32Float, wgpu::TextureFormat::Rg32Float, wgpu::TextureFormat::Rgba32Float];
+
+/// Returns the storage format of a texture with the given number of components
+fn storage_format(components: u32) -> wgpu::TextureFormat {
+    match components {
+        1 => wgpu::TextureFormat::R32Float,
+        2 => wgpu::TextureFormat::Rg32Float,
+        // There is no 3-component storage format, so 3-component textures carry a
+        // zeroed fourth component (enforced when pipelines are compiled)
+        _ => wgpu::TextureFormat::Rgba32Float,
+    }
+}
+
+/// Checks that a source texture is not empty and can be sampled by the first pass
+///
+/// Usages beyond `TEXTURE_BINDING` are left to the caller, so a source texture can also
+/// be a render target or copy source without being recreated.
+fn validate_source_texture(source_texture: &wgpu::Texture) -> Result<(), Box<dyn std::error::Error>> {
+    if source_texture.width() == 0 || source_texture.height() == 0 {
+        return Err(Box::new(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Cannot execute a pipeline on a {}x{} source texture", source_texture.width(), source_texture.height()),
+        )));
+    }
+    if !source_texture.usage().contains(wgpu::TextureUsages::TEXTURE_BINDING) {
+        return Err(Box::new(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Source texture usage {:?} lacks TEXTURE_BINDING, which the first pass samples it through", source_texture.usage()),
+        )));
+    }
+    Ok(())
+}
+
+/// Asserts that a source texture passes `validate_source_texture`
+fn assert_source_texture(source_texture: &wgpu::Texture) {
+    if let Err(error) = validate_source_texture(source_texture) {
+        panic!("{error}");
+    }
+}
 
 /// A pipeline bound to wgpu resources, ready for execution
 #[derive(Debug)]
@@ -28,6 +73,36 @@ struct BoundExecutablePass {
     compute_pipeline: wgpu::ComputePipeline,
     /// Bind group containing all resources for this pass
     bind_group: wgpu::BindGroup,
+    /// Non-finite value guards run on the outputs after the pass (debug executors only)
+    nan_guards: Vec<NanGuardPass>,
+}
+
+impl BoundExecutablePass {
+    /// Records the dispatch of this pass followed by its non-finite value guards
+    ///
+    /// # Arguments
+    /// * `encoder` - The command encoder to record commands into
+    /// * `timestamp_writes` - Timestamp queries to write at the start and end of the pass
+    fn record(&self, encoder: &mut wgpu::CommandEncoder, timestamp_writes: Option<wgpu::ComputePassTimestampWrites>) {
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some(self.name),
+            timestamp_writes,
+        });
+
+        compute_pass.set_pipeline(&self.compute_pipeline);
+        compute_pass.set_bind_group(0, &self.bind_group, &[]);
+
+        let (compute_width, compute_height) = self.compute_dimensions;
+        let workgroup_x = compute_width.div_ceil(COMPUTE_WORKGROUP_SIZE_X);
+        let workgroup_y = compute_height.div_ceil(COMPUTE_WORKGROUP_SIZE_Y);
+
+        compute_pass.dispatch_workgroups(workgroup_x, workgroup_y, 1);
+        drop(compute_pass);
+
+        for nan_guard in &self.nan_guards {
+            nan_guard.pass(encoder);
+        }
+    }
 }
 
 impl BoundPipeline {
@@ -40,11 +115,25 @@ impl BoundPipeline {
     /// * `pipeline` - The executable pipeline to bind
     /// * `device` - The wgpu device for resource creation
     /// * `input_texture` - The source texture for the pipeline
+    /// * `nan_guard` - Guard state and the flag index of this pipeline's first pass, to guard every pass output
+    /// * `output_texture` - Texture to use as the result texture instead of creating one, already validated
+    /// * `sampler_cache` - Samplers shared with the other pipelines of the executor
+    /// * `spare_textures` - Textures no longer used by earlier stages, taken instead of
+    ///   creating a texture of the same size and format
     ///
     /// # Returns
     /// A tuple of (bound pipeline, final output texture)
-    pub fn new(pipeline: &'static ExecutablePipeline, device: &wgpu::Device, input_texture: &wgpu::Texture) -> (Self, wgpu::Texture) {
+    pub fn new(
+        pipeline: &'static ExecutablePipeline,
+        device: &wgpu::Device,
+        input_texture: &wgpu::Texture,
+        nan_guard: Option<(&NanGuard, usize)>,
+        output_texture: Option<&wgpu::Texture>,
+        sampler_cache: &mut SamplerCache,
+        spare_textures: &mut Vec<wgpu::Texture>,
+    ) -> (Self, wgpu::Texture) {
         let input_size = (input_texture.width(), input_texture.height());
+        let result_id = pipeline.result_texture().id;
 
         let physical_texture_map = pipeline
             .textures
@@ -53,22 +142,26 @@ impl BoundPipeline {
                 let texture = if pt.is_source {
                     // Use the input texture directly for source textures
                     input_texture.clone()
+                } else if let Some(output_texture) = output_texture.filter(|_| pt.id == result_id) {
+                    output_texture.clone()
+                } else if let Some(index) = spare_textures
+                    .iter()
+                    .position(|spare| (spare.width(), spare.height()) == pt.dimensions(input_size) && spare.format() == storage_format(pt.components))
+                {
+                    spare_textures.swap_remove(index)
                 } else {
+                    let (width, height) = pt.dimensions(input_size);
                     device.create_texture(&wgpu::TextureDescriptor {
                         label: Some(&format!("Physical Texture {}", pt.id)),
                         size: wgpu::Extent3d {
-                            width: (input_size.0 as f64 * pt.scale_factor.0.numerator as f64 / pt.scale_factor.0.denominator as f64) as u32,
-                            height: (input_size.1 as f64 * pt.scale_factor.1.numerator as f64 / pt.scale_factor.1.denominator as f64) as u32,
+                            width,
+                            height,
                             depth_or_array_layers: 1,
                         },
                         mip_level_count: 1,
                         sample_count: 1,
                         dimension: wgpu::TextureDimension::D2,
-                        format: match pt.components {
-                            1 => wgpu::TextureFormat::R32Float,
-                            2 => wgpu::TextureFormat::Rg32Float,
-                            _ => wgpu::TextureFormat::Rgba32Float,
-                        },
+                        format: storage_format(pt.components),
                         usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
                         view_formats: &[],
                     })
@@ -82,38 +175,13 @@ impl BoundPipeline {
         let sampler_map = pipeline
             .samplers
             .iter()
-            .map(|filter_mode| {
-                let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-                    label: Some(&format!("Sampler {filter_mode:?}")),
-                    address_mode_u: wgpu::AddressMode::ClampToEdge,
-                    address_mode_v: wgpu::AddressMode::ClampToEdge,
-                    address_mode_w: wgpu::AddressMode::ClampToEdge,
-                    mag_filter: match filter_mode {
-                        SamplerFilterMode::Nearest => wgpu::FilterMode::Nearest,
-                        SamplerFilterMode::Linear => wgpu::FilterMode::Linear,
-                    },
-                    min_filter: match filter_mode {
-                        SamplerFilterMode::Nearest => wgpu::FilterMode::Nearest,
-                        SamplerFilterMode::Linear => wgpu::FilterMode::Linear,
-                    },
-                    mipmap_filter: wgpu::FilterMode::Nearest,
-                    lod_min_clamp: 0.0,
-                    lod_max_clamp: 0.0,
-                    compare: None,
-                    anisotropy_clamp: 1,
-                    border_color: None,
-                });
-                (filter_mode.clone(), sampler)
-            })
+            .map(|filter_mode| (filter_mode.clone(), sampler_cache.get(device, filter_mode.clone())))
             .collect::<std::collections::HashMap<_, _>>();
 
         let mut passes = Vec::new();
 
-        for shader_pass in pipeline.passes.iter() {
-            let compute_dimensions = (
-                (input_size.0 as f64 * shader_pass.compute_scale_factors.0).floor() as u32,
-                (input_size.1 as f64 * shader_pass.compute_scale_factors.1).floor() as u32,
-            );
+        for (pass_index, shader_pass) in pipeline.passes.iter().enumerate() {
+            let compute_dimensions = shader_pass.compute_dimensions(input_size);
             let skip_bound_check = compute_dimensions.0 % COMPUTE_WORKGROUP_SIZE_X == 0 && compute_dimensions.1 % COMPUTE_WORKGROUP_SIZE_Y == 0;
 
             let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
@@ -222,19 +290,25 @@ impl BoundPipeline {
                 entries: &bind_group_entries,
             });
 
+            let nan_guards = match nan_guard {
+                Some((nan_guard, first_flag)) => shader_pass
+                    .output_textures
+                    .iter()
+                    .map(|output| nan_guard.create_pass(device, first_flag + pass_index, &physical_texture_map.get(&output.physical_texture_id).unwrap().0))
+                    .collect(),
+                None => Vec::new(),
+            };
+
             passes.push(BoundExecutablePass {
                 name: shader_pass.name,
                 compute_dimensions,
                 compute_pipeline: pipeline,
                 bind_group,
+                nan_guards,
             });
         }
 
-        let output_texture = physical_texture_map
-            .get(&pipeline.passes.last().unwrap().output_textures.first().unwrap().physical_texture_id)
-            .unwrap()
-            .0
-            .clone();
+        let output_texture = physical_texture_map.get(&pipeline.result_texture().id).unwrap().0.clone();
 
         (BoundPipeline { passes }, output_texture)
     }
@@ -244,20 +318,101 @@ impl BoundPipeline {
     /// # Arguments
     /// * `encoder` - The command encoder to record commands into
     pub fn pass(&self, encoder: &mut wgpu::CommandEncoder) {
+        self.record(encoder, None);
+    }
+
+    /// Records all passes, optionally writing timestamps around each one
+    ///
+    /// # Arguments
+    /// * `encoder` - The command encoder to record commands into
+    /// * `timestamps` - Query set and the names of the passes timed so far; each pass
+    ///   writes its start and end timestamps to the next two queries and appends its name
+    fn record(&self, encoder: &mut wgpu::CommandEncoder, mut timestamps: Option<(&wgpu::QuerySet, &mut Vec<&'static str>)>) {
+        for pass in self.passes.iter() {
+            let timestamp_writes = timestamps.as_mut().map(|(query_set, names)| {
+                let index = names.len() as u32 * 2;
+                names.push(pass.name);
+                wgpu::ComputePassTimestampWrites {
+                    query_set,
+                    beginning_of_pass_write_index: Some(index),
+                    end_of_pass_write_index: Some(index + 1),
+                }
+            });
+            pass.record(encoder, timestamp_writes);
+        }
+    }
+
+    /// Submits every pass in its own command buffer and waits for each to finish
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device used to create this pipeline
+    /// * `queue` - The wgpu command queue
+    ///
+    /// # Returns
+    /// An error if waiting for the GPU failed
+    fn pass_serialized(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<(), Box<dyn std::error::Error>> {
+        for pass in self.passes.iter() {
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some(pass.name) });
+            pass.record(&mut encoder, None);
+            queue.submit(std::iter::once(encoder.finish()));
+            device.poll(wgpu::PollType::Wait)?;
+        }
+        Ok(())
+    }
+
+    /// Records a single-workgroup dispatch of every pass, to make drivers compile its pipeline
+    ///
+    /// # Arguments
+    /// * `encoder` - The command encoder to record commands into
+    fn record_warm_up(&self, encoder: &mut wgpu::CommandEncoder) {
         for pass in self.passes.iter() {
             let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
                 label: Some(pass.name),
                 timestamp_writes: None,
             });
-
             compute_pass.set_pipeline(&pass.compute_pipeline);
             compute_pass.set_bind_group(0, &pass.bind_group, &[]);
+            compute_pass.dispatch_workgroups(1, 1, 1);
+        }
+    }
+}
+
+/// GPU execution time of a single shader pass, measured by [`PipelineExecutor::profile`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PassTiming {
+    /// Name of the shader pass
+    pub name: &'static str,
+    /// Time between the start and end timestamps of the pass
+    pub duration: std::time::Duration,
+}
 
-            let (compute_width, compute_height) = pass.compute_dimensions;
-            let workgroup_x = compute_width.div_ceil(COMPUTE_WORKGROUP_SIZE_X);
-            let workgroup_y = compute_height.div_ceil(COMPUTE_WORKGROUP_SIZE_Y);
+/// A step of a frame, in the order `PipelineExecutor` records them
+#[derive(Clone, Copy)]
+enum Stage<'a> {
+    /// Padding the source with its edge texels
+    Pad(&'a Padding),
+    /// Denoising the source
+    PreDenoise(&'a PreDenoisePass),
+    /// Running the passes of one Anime4K pipeline
+    Pipeline(&'a BoundPipeline),
+    /// Copying the part of the padded output belonging to the source out of the given texture
+    Crop(&'a Padding, &'a wgpu::Texture),
+    /// Grading the output with a LUT
+    Lut(&'a LutPass),
+}
 
-            compute_pass.dispatch_workgroups(workgroup_x, workgroup_y, 1);
+impl Stage<'_> {
+    /// Records the commands of this stage
+    ///
+    /// # Arguments
+    /// * `encoder` - The command encoder to record commands into
+    fn record(self, encoder: &mut wgpu::CommandEncoder) {
+        match self {
+            Stage::Pad(padding) => padding.pad(encoder),
+            Stage::PreDenoise(pre_denoise) => pre_denoise.pass(encoder),
+            Stage::Pipeline(bound_pipeline) => bound_pipeline.pass(encoder),
+            Stage::Crop(padding, padded_output) => padding.crop(encoder, padded_output),
+            Stage::Lut(lut) => lut.pass(encoder),
         }
     }
 }
@@ -266,10 +421,29 @@ impl BoundPipeline {
 ///
 /// Manages the execution of a series of shader pipelines in sequence,
 /// handling resource management and intermediate texture passing.
+///
+/// All textures, bind groups, and compute pipelines are created up front, so an
+/// executor can be reused for any number of frames of the same dimensions: write
+/// each new frame into the source texture (or use `process_frame`) and record
+/// `pass` again. Neither allocates GPU resources.
 #[derive(Debug)]
 pub struct PipelineExecutor {
     /// Collection of bound pipelines to execute in sequence
     bound_pipelines: Vec<BoundPipeline>,
+    /// Initial input texture the executor was bound to
+    source_texture: wgpu::Texture,
+    /// Final output texture of the last pipeline
+    output_texture: wgpu::Texture,
+    /// Non-finite value guard state, present for executors created with `new_debug`
+    nan_guard: Option<NanGuard>,
+    /// Filter run on the source before the pipelines, present for executors created with `new_pre_denoised`
+    pre_denoise: Option<PreDenoisePass>,
+    /// Pad pass and output copy around the pipelines, present for executors created with `new_padded` that needed padding
+    padding: Option<Padding>,
+    /// Color grading pass after the pipelines, present for executors extended with `with_lut`
+    lut: Option<LutPass>,
+    /// Samplers shared by all passes, including the LUT
+    sampler_cache: SamplerCache,
 }
 
 impl PipelineExecutor {
@@ -281,31 +455,729 @@ impl PipelineExecutor {
     /// # Arguments
     /// * `executable_pipeline` - Array of executable pipelines to chain together
     /// * `device` - The wgpu device for resource creation
-    /// * `source_texture` - The initial input texture
+    /// * `source_texture` - The initial input texture, used as-is with whatever usages it was created with
     ///
     /// # Returns
     /// A tuple of (pipeline executor, final output texture)
+    ///
+    /// # Panics
+    /// Panics if the source texture has a zero width or height, or lacks `TEXTURE_BINDING` usage;
+    /// `try_new` returns an error instead
     pub fn new(executable_pipeline: &[&'static ExecutablePipeline], device: &wgpu::Device, source_texture: &wgpu::Texture) -> (Self, wgpu::Texture) {
+        Self::bind(executable_pipeline, device, source_texture, None, None, None)
+    }
+
+    /// Creates a new shader pipeline, returning an error instead of panicking on an invalid source
+    ///
+    /// Behaves like `new`, but checks the source texture first, so callers handling
+    /// arbitrary input (such as a decoded image that turned out to be empty) can report
+    /// the problem instead of aborting.
+    ///
+    /// # Arguments
+    /// * `executable_pipeline` - Array of executable pipelines to chain together
+    /// * `device` - The wgpu device for resource creation
+    /// * `source_texture` - The initial input texture
+    ///
+    /// # Returns
+    /// A tuple of (pipeline executor, final output texture), or an error if the source
+    /// texture has a zero width or height, or lacks `TEXTURE_BINDING` usage
+    pub fn try_new(executable_pipeline: &[&'static ExecutablePipeline], device: &wgpu::Device, source_texture: &wgpu::Texture) -> Result<(Self, wgpu::Texture), Box<dyn std::error::Error>> {
+        validate_source_texture(source_texture)?;
+        Ok(Self::new(executable_pipeline, device, source_texture))
+    }
+
+    /// Creates a shader pipeline that guards every pass output against NaN and infinity
+    ///
+    /// After each pass, texels with any non-finite component are replaced with magenta
+    /// and the pass is flagged, so `non_finite_passes` reveals which pass first went bad
+    /// and later passes run on sanitized input. This adds a compute pass and a texture
+    /// copy per pass output, so it is meant for debugging only.
+    ///
+    /// # Arguments
+    /// * `executable_pipeline` - Array of executable pipelines to chain together
+    /// * `device` - The wgpu device for resource creation
+    /// * `source_texture` - The initial input texture
+    ///
+    /// # Returns
+    /// A tuple of (pipeline executor, final output texture)
+    ///
+    /// # Panics
+    /// Panics if the source texture has a zero width or height, or lacks `TEXTURE_BINDING` usage
+    pub fn new_debug(executable_pipeline: &[&'static ExecutablePipeline], device: &wgpu::Device, source_texture: &wgpu::Texture) -> (Self, wgpu::Texture) {
+        let pass_names = executable_pipeline
+            .iter()
+            .flat_map(|pipeline| pipeline.passes.iter().map(move |pass| format!("{}/{}", pipeline.name, pass.name)))
+            .collect();
+        Self::bind(executable_pipeline, device, source_texture, Some(NanGuard::new(device, pass_names)), None, None)
+    }
+
+    /// Creates a shader pipeline that denoises the source before processing it
+    ///
+    /// The filter writes a denoised copy of the source into an `Rgba32Float` texture,
+    /// which the first pipeline reads as its source. Once the first pipeline is done with
+    /// it, later pipelines reuse that texture for a texture of the same size instead of
+    /// creating one, so with a chain of pipelines that work at the source size (such as
+    /// Restore followed by Upscale) denoising needs no extra memory. The output has the
+    /// same dimensions as with `new`.
+    ///
+    /// # Arguments
+    /// * `executable_pipeline` - Array of executable pipelines to chain together
+    /// * `device` - The wgpu device for resource creation
+    /// * `source_texture` - The initial input texture
+    /// * `pre_denoise` - Filter to apply to the source
+    ///
+    /// # Returns
+    /// A tuple of (pipeline executor, final output texture)
+    ///
+    /// # Panics
+    /// Panics if the source texture has a zero width or height, or lacks `TEXTURE_BINDING` usage
+    pub fn new_pre_denoised(executable_pipeline: &[&'static ExecutablePipeline], device: &wgpu::Device, source_texture: &wgpu::Texture, pre_denoise: PreDenoise) -> (Self, wgpu::Texture) {
+        Self::bind(executable_pipeline, device, source_texture, None, None, Some(pre_denoise))
+    }
+
+    /// Creates a shader pipeline that pads the source so every pass skips its bounds check
+    ///
+    /// Passes whose dispatch size is a multiple of the workgroup size already use the
+    /// unchecked entry point with `new`. This pads the source with its edge texels to the
+    /// smallest size (at most 64 texels larger per axis) at which that holds for every
+    /// pass, runs the pipelines on it, and copies out the part of the output belonging to
+    /// the unpadded source, so the output has the same dimensions as with `new`. Texels
+    /// near the right and bottom edges can differ slightly from `new`, since later passes
+    /// see computed values past the edge instead of clamped ones. If no padding is needed,
+    /// this is the same as `new`.
+    ///
+    /// The pad pass and the copy cost a read and write of the source and the output,
+    /// so this only pays off where the bounds checks are a measurable part of the cost.
+    /// On llvmpipe at 958x538 (padded to 960x544), the padded ModeA and ModeAA Light
+    /// pipelines took about 2% and 6% longer than with `new`; the extra texels, pad
+    /// pass, and copy outweighed the skipped checks. Profile on the target GPU before
+    /// preferring this over `new`.
+    ///
+    /// # Arguments
+    /// * `executable_pipeline` - Array of executable pipelines to chain together
+    /// * `device` - The wgpu device for resource creation
+    /// * `source_texture` - The initial input texture
+    ///
+    /// # Returns
+    /// A tuple of (pipeline executor, final output texture)
+    ///
+    /// # Panics
+    /// Panics if the source texture has a zero width or height, or lacks `TEXTURE_BINDING` usage
+    pub fn new_padded(executable_pipeline: &[&'static ExecutablePipeline], device: &wgpu::Device, source_texture: &wgpu::Texture) -> (Self, wgpu::Texture) {
+        assert_source_texture(source_texture);
+
+        let input_size = (source_texture.width(), source_texture.height());
+        let padded_size = Self::padded_dimensions(executable_pipeline, input_size.0, input_size.1);
+        if padded_size == input_size {
+            return Self::new(executable_pipeline, device, source_texture);
+        }
+
+        let mut padding = Padding::new(device, source_texture, padded_size);
+        let (mut executor, padded_output) = Self::bind(executable_pipeline, device, padding.padded_texture(), None, None, None);
+        let output_texture = padding.set_output(device, &padded_output, Self::output_dimensions(executable_pipeline, input_size.0, input_size.1));
+
+        executor.source_texture = source_texture.clone();
+        executor.output_texture = output_texture.clone();
+        executor.padding = Some(padding);
+        (executor, output_texture)
+    }
+
+    /// Grades the output of the pipelines with a 3D LUT in a final pass
+    ///
+    /// The LUT is uploaded as a 3D texture and sampled trilinearly over the output,
+    /// with colors outside its domain clamped to the edges and alpha passed through.
+    /// The graded result is written to a new `Rgba32Float` texture of the same size,
+    /// which becomes the output of the executor; the previous output texture keeps
+    /// receiving the ungraded result. This works with executors from any constructor.
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device used to create this executor
+    /// * `queue` - The wgpu command queue, used to upload the LUT
+    /// * `lut` - LUT to apply
+    ///
+    /// # Returns
+    /// A tuple of (pipeline executor, graded output texture)
+    ///
+    /// # Panics
+    /// Panics if the executor already applies a LUT
+    pub fn with_lut(mut self, device: &wgpu::Device, queue: &wgpu::Queue, lut: &CubeLut) -> (Self, wgpu::Texture) {
+        assert!(self.lut.is_none(), "The executor already applies a LUT");
+
+        let sampler = self.sampler_cache.get(device, SamplerFilterMode::Linear);
+        let lut = LutPass::new(device, queue, &self.output_texture, lut, &sampler);
+        let output_texture = lut.output_texture().clone();
+
+        self.output_texture = output_texture.clone();
+        self.lut = Some(lut);
+        (self, output_texture)
+    }
+
+    /// Returns the texture holding the result of the pipelines, before any LUT grading
+    fn pipelines_output_texture(&self) -> &wgpu::Texture {
+        self.lut.as_ref().map_or(&self.output_texture, LutPass::input_texture)
+    }
+
+    /// Returns the source size `new_padded` pads to for a chain of pipelines
+    ///
+    /// # Arguments
+    /// * `executable_pipeline` - Array of executable pipelines to chain together
+    /// * `input_width` - Input width in pixels
+    /// * `input_height` - Input height in pixels
+    ///
+    /// # Returns
+    /// The padded (width, height), equal to the input size along an axis that needs no
+    /// padding or more than 64 texels of it
+    pub fn padded_dimensions(executable_pipeline: &[&'static ExecutablePipeline], input_width: u32, input_height: u32) -> (u32, u32) {
+        padded_dimensions(executable_pipeline, input_width, input_height)
+    }
+
+    /// Creates a shader pipeline whose final pass writes into a caller-provided texture
+    ///
+    /// Behaves like `new`, but the last pipeline writes its result into `output_texture`
+    /// instead of a texture of its own, so the output can go straight into a texture
+    /// another renderer samples, without a copy. Within the last pipeline, the result
+    /// texture may also hold intermediate results before the final pass writes it.
+    ///
+    /// The texture must have exactly the size reported by `output_dimensions`, the
+    /// storage format of the result (`Rgba32Float` for the built-in presets), and
+    /// `STORAGE_BINDING` usage. It also needs `TEXTURE_BINDING` usage if the last
+    /// pipeline reads back intermediate results from it.
+    ///
+    /// # Arguments
+    /// * `executable_pipeline` - Array of executable pipelines to chain together
+    /// * `device` - The wgpu device for resource creation
+    /// * `source_texture` - The initial input texture
+    /// * `output_texture` - The texture to write the final output into
+    ///
+    /// # Returns
+    /// The pipeline executor, or an error naming the mismatch if `output_texture` does
+    /// not meet the requirements above, there is no pipeline to write it, or the source
+    /// texture has a zero width or height or lacks `TEXTURE_BINDING` usage
+    pub fn new_with_output(
+        executable_pipeline: &[&'static ExecutablePipeline],
+        device: &wgpu::Device,
+        source_texture: &wgpu::Texture,
+        output_texture: &wgpu::Texture,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let invalid = |message: String| -> Box<dyn std::error::Error> { Box::new(std::io::Error::new(std::io::ErrorKind::InvalidInput, message)) };
+
+        validate_source_texture(source_texture)?;
+
+        let Some(last_pipeline) = executable_pipeline.last() else {
+            return Err(invalid("Cannot write into an output texture without any pipeline".to_string()));
+        };
+
+        let expected_size = Self::output_dimensions(executable_pipeline, source_texture.width(), source_texture.height());
+        if (output_texture.width(), output_texture.height()) != expected_size {
+            return Err(invalid(format!(
+                "Output texture is {}x{}, but the pipelines produce {}x{}",
+                output_texture.width(),
+                output_texture.height(),
+                expected_size.0,
+                expected_size.1
+            )));
+        }
+
+        let result_texture = last_pipeline.result_texture();
+        let expected_format = storage_format(result_texture.components);
+        if output_texture.format() != expected_format {
+            return Err(invalid(format!("Output texture format is {:?}, but the pipelines write {expected_format:?}", output_texture.format())));
+        }
+
+        let mut required_usage = wgpu::TextureUsages::STORAGE_BINDING;
+        if last_pipeline
+            .passes
+            .iter()
+            .any(|pass| pass.input_textures.iter().any(|input| input.physical_texture_id == result_texture.id))
+        {
+            required_usage |= wgpu::TextureUsages::TEXTURE_BINDING;
+        }
+        if !output_texture.usage().contains(required_usage) {
+            return Err(invalid(format!("Output texture usage {:?} lacks the required {required_usage:?}", output_texture.usage())));
+        }
+
+        let (executor, _) = Self::bind(executable_pipeline, device, source_texture, None, Some(output_texture), None);
+        Ok(executor)
+    }
+
+    /// Binds all pipelines in sequence, guarding pass outputs if `nan_guard` is given
+    ///
+    /// The last pipeline writes into `final_output_texture` if given, which must have been validated by the caller.
+    /// With `pre_denoise`, the first pipeline reads the denoised source, and the denoised
+    /// texture is handed to the later pipelines once the first one no longer needs it.
+    fn bind(
+        executable_pipeline: &[&'static ExecutablePipeline],
+        device: &wgpu::Device,
+        source_texture: &wgpu::Texture,
+        nan_guard: Option<NanGuard>,
+        final_output_texture: Option<&wgpu::Texture>,
+        pre_denoise: Option<PreDenoise>,
+    ) -> (Self, wgpu::Texture) {
+        assert_source_texture(source_texture);
+
+        let pre_denoise = pre_denoise.map(|pre_denoise| PreDenoisePass::new(device, source_texture, pre_denoise));
         let mut bound_pipelines = Vec::new();
-        let mut current_input_texture = source_texture.clone();
+        let mut current_input_texture = pre_denoise.as_ref().map_or(source_texture, PreDenoisePass::output_texture).clone();
+        let mut first_flag = 0;
+        let mut sampler_cache = SamplerCache::new();
+        let mut spare_textures = Vec::new();
 
-        for pipeline in executable_pipeline {
-            let (bound_pipeline, output_texture) = BoundPipeline::new(pipeline, device, &current_input_texture);
+        for (index, pipeline) in executable_pipeline.iter().enumerate() {
+            let pipeline_output_texture = final_output_texture.filter(|_| index == executable_pipeline.len() - 1);
+            let (bound_pipeline, output_texture) = BoundPipeline::new(
+                pipeline,
+                device,
+                &current_input_texture,
+                nan_guard.as_ref().map(|nan_guard| (nan_guard, first_flag)),
+                pipeline_output_texture,
+                &mut sampler_cache,
+                &mut spare_textures,
+            );
             current_input_texture = output_texture;
+            first_flag += pipeline.passes.len();
 
             bound_pipelines.push(bound_pipeline);
+
+            // Only the first pipeline reads the denoised source
+            if index == 0 {
+                spare_textures.extend(pre_denoise.as_ref().map(|pre_denoise| pre_denoise.output_texture().clone()));
+            }
         }
 
-        (Self { bound_pipelines }, current_input_texture)
+        let output_texture = current_input_texture.clone();
+        (
+            Self {
+                bound_pipelines,
+                source_texture: source_texture.clone(),
+                output_texture,
+                nan_guard,
+                pre_denoise,
+                padding: None,
+                lut: None,
+                sampler_cache,
+            },
+            current_input_texture,
+        )
+    }
+
+    /// Calculates the exact output dimensions of a chain of pipelines
+    ///
+    /// Each pipeline's output size becomes the next pipeline's input size, so this
+    /// matches the size of the texture returned by `new` for the same chain.
+    ///
+    /// # Arguments
+    /// * `executable_pipeline` - Array of executable pipelines to chain together
+    /// * `input_width` - Input width in pixels
+    /// * `input_height` - Input height in pixels
+    ///
+    /// # Returns
+    /// The final output (width, height)
+    pub fn output_dimensions(executable_pipeline: &[&'static ExecutablePipeline], input_width: u32, input_height: u32) -> (u32, u32) {
+        executable_pipeline
+            .iter()
+            .fold((input_width, input_height), |(width, height), pipeline| pipeline.output_dimensions(width, height))
+    }
+
+    /// Estimates the GPU memory of the textures `new` allocates for a chain of pipelines
+    ///
+    /// Counts every texture the pipelines create at its storage format, but not the
+    /// source texture, which belongs to the caller. Textures added by
+    /// `new_pre_denoised`, `new_padded`, `new_debug`, or `with_lut`, and driver alignment and padding, are not
+    /// included, so treat the result as a lower bound.
+    ///
+    /// # Arguments
+    /// * `executable_pipeline` - Array of executable pipelines to chain together
+    /// * `input_width` - Input width in pixels
+    /// * `input_height` - Input height in pixels
+    ///
+    /// # Returns
+    /// The estimated texture memory in bytes
+    pub fn estimate_memory(executable_pipeline: &[&'static ExecutablePipeline], input_width: u32, input_height: u32) -> u64 {
+        let mut input_size = (input_width, input_height);
+        let mut total = 0;
+        for pipeline in executable_pipeline {
+            for texture in pipeline.textures.iter().filter(|texture| !texture.is_source) {
+                let (width, height) = texture.dimensions(input_size);
+                let texel_size = storage_format(texture.components).block_copy_size(None).unwrap();
+                total += width as u64 * height as u64 * texel_size as u64;
+            }
+            input_size = pipeline.output_dimensions(input_size.0, input_size.1);
+        }
+        total
+    }
+
+    /// Returns every stage of a frame in execution order
+    ///
+    /// `pass`, `pass_serialized`, and `profile` all record this list, so they cannot
+    /// disagree on the order of the helper passes around the pipelines.
+    fn stages(&self) -> impl Iterator<Item = Stage<'_>> {
+        let padding = self.padding.as_ref();
+        padding
+            .map(Stage::Pad)
+            .into_iter()
+            .chain(self.pre_denoise.as_ref().map(Stage::PreDenoise))
+            .chain(self.bound_pipelines.iter().map(Stage::Pipeline))
+            .chain(padding.map(|padding| Stage::Crop(padding, self.pipelines_output_texture())))
+            .chain(self.lut.as_ref().map(Stage::Lut))
     }
 
     /// Executes the entire shader pipeline
     ///
+    /// Only records commands into `encoder`; it can be called once per frame on the
+    /// same executor without creating any GPU resources.
+    ///
     /// # Arguments
     /// * `encoder` - The command encoder to record commands into
     pub fn pass(&self, encoder: &mut wgpu::CommandEncoder) {
+        for stage in self.stages() {
+            stage.record(encoder);
+        }
+    }
+
+    /// Executes the entire shader pipeline one pass at a time, waiting for each pass to finish
+    ///
+    /// Every pass, including the pre-denoise filter, the
+    /// padding and its output copy, and the LUT, is submitted in its own command buffer followed by `device.poll(Wait)`, so no two
+    /// passes can overlap on the GPU. This is a debugging aid for custom pipelines: if
+    /// the output differs from the one recorded by `pass`, some pass reads a texture
+    /// before an earlier pass has finished writing it, which points to a missing barrier
+    /// in the backend or driver rather than a bug in the shaders. It is much slower than
+    /// `pass` and should not be used for regular processing.
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device used to create this executor
+    /// * `queue` - The wgpu command queue
+    ///
+    /// # Returns
+    /// An error if waiting for the GPU failed
+    pub fn pass_serialized(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<(), Box<dyn std::error::Error>> {
+        let submit_and_wait = |record: &dyn Fn(&mut wgpu::CommandEncoder)| -> Result<(), Box<dyn std::error::Error>> {
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            record(&mut encoder);
+            queue.submit(std::iter::once(encoder.finish()));
+            device.poll(wgpu::PollType::Wait)?;
+            Ok(())
+        };
+
+        for stage in self.stages() {
+            match stage {
+                Stage::Pipeline(bound_pipeline) => bound_pipeline.pass_serialized(device, queue)?,
+                stage => submit_and_wait(&|encoder| stage.record(encoder))?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the source texture the executor reads each frame from
+    ///
+    /// This is the texture passed at construction, also for executors whose first pass
+    /// reads a derived texture (pre-denoise or padding), so writing a frame
+    /// into it with `queue.write_texture` before recording `pass` processes that frame.
+    pub fn source_texture(&self) -> &wgpu::Texture {
+        &self.source_texture
+    }
+
+    /// Uploads a new frame into the source texture and records the pipeline for it
+    ///
+    /// The upload is queued with `queue.write_texture`, which wgpu executes before the
+    /// command buffers of the next submission, so submitting `encoder` afterwards
+    /// processes this frame. The source texture must have `COPY_DST` usage. Apart from
+    /// wgpu's internal staging memory for the upload, no resources are created, so this
+    /// is suitable for processing a video frame by frame.
+    ///
+    /// # Arguments
+    /// * `queue` - The wgpu command queue
+    /// * `encoder` - The command encoder to record commands into
+    /// * `source_data` - Tightly packed rows of texels in the source texture's format
+    ///
+    /// # Returns
+    /// An error if the source texture lacks `COPY_DST` usage or `source_data` does not
+    /// match its size
+    pub fn process_frame(&self, queue: &wgpu::Queue, encoder: &mut wgpu::CommandEncoder, source_data: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.source_texture.usage().contains(wgpu::TextureUsages::COPY_DST) {
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Cannot upload frames to a source texture without COPY_DST usage (has {:?})", self.source_texture.usage()),
+            )));
+        }
+
+        let size = self.source_texture.size();
+        let Some(bytes_per_texel) = self.source_texture.format().block_copy_size(None) else {
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Cannot upload frames to a {:?} source texture", self.source_texture.format()),
+            )));
+        };
+        let bytes_per_row = size.width * bytes_per_texel;
+        let expected_len = bytes_per_row as usize * size.height as usize;
+        if source_data.len() != expected_len {
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Expected {expected_len} bytes for a {}x{} frame, got {}", size.width, size.height, source_data.len()),
+            )));
+        }
+
+        queue.write_texture(
+            self.source_texture.as_image_copy(),
+            source_data,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(bytes_per_row),
+                rows_per_image: Some(size.height),
+            },
+            wgpu::Extent3d { depth_or_array_layers: 1, ..size },
+        );
+
+        self.pass(encoder);
+        Ok(())
+    }
+
+    /// Computes per-channel statistics of the final output texture on the GPU
+    ///
+    /// Only the reduced statistics are read back, so this is a cheap check for
+    /// all-black or NaN outputs before running a full image comparison.
+    /// Call after the commands recorded by `pass` have been submitted.
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device used to create this executor
+    /// * `queue` - The wgpu command queue
+    ///
+    /// # Returns
+    /// Per-channel min, max, mean, and non-finite counts of the output
+    pub fn output_stats(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<ChannelStats, Box<dyn std::error::Error>> {
+        compute_channel_stats(device, queue, &self.output_texture)
+    }
+
+    /// Returns the passes that have produced NaN or infinite values, in execution order
+    ///
+    /// Passes are named `pipeline/pass`. Flags accumulate over every execution since the
+    /// executor was created. Always empty for executors not created with `new_debug`.
+    /// Call after the commands recorded by `pass` have been submitted.
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device used to create this executor
+    /// * `queue` - The wgpu command queue
+    ///
+    /// # Returns
+    /// Names of the flagged passes, or an error if the readback failed
+    pub fn non_finite_passes(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        match &self.nan_guard {
+            Some(nan_guard) => nan_guard.flagged_passes(device, queue),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Returns whether any pass has produced NaN or infinite values
+    ///
+    /// See `non_finite_passes` for which passes are checked and when to call this.
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device used to create this executor
+    /// * `queue` - The wgpu command queue
+    pub fn had_nan(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<bool, Box<dyn std::error::Error>> {
+        Ok(!self.non_finite_passes(device, queue)?.is_empty())
+    }
+
+    /// Dispatches every shader pass once and waits, so later frames do not pay for shader compilation
+    ///
+    /// Some drivers compile or specialize compute pipelines lazily on their first dispatch,
+    /// which shows up as a hitch on the first frame after the executor is created. Each
+    /// pass runs a single workgroup, so this is cheap; it only writes a corner of the
+    /// intermediate textures, which the next `pass` overwrites. Calling it is optional and
+    /// makes no difference on drivers that compile pipelines at creation. Helper passes
+    /// such as the pre-denoise filter are not warmed up.
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device used to create this executor
+    /// * `queue` - The wgpu command queue
+    ///
+    /// # Returns
+    /// An error if waiting for the GPU failed
+    pub fn warm_up(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<(), Box<dyn std::error::Error>> {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Anime4K Warm-up") });
         for bound_pipeline in &self.bound_pipelines {
-            bound_pipeline.pass(encoder);
+            bound_pipeline.record_warm_up(&mut encoder);
+        }
+        queue.submit(std::iter::once(encoder.finish()));
+        device.poll(wgpu::PollType::Wait)?;
+        Ok(())
+    }
+
+    /// Runs the pipeline once and measures the GPU time of each shader pass
+    ///
+    /// Only the passes of the Anime4K pipelines are timed; helper passes such as the
+    /// pre-denoise filter, the padding, the LUT, and the non-finite value
+    /// checks run as usual but are not reported. The pipeline processes whatever the source texture
+    /// currently holds, so run it once beforehand to exclude first-use overhead.
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device used to create this executor, with `wgpu::Features::TIMESTAMP_QUERY` enabled
+    /// * `queue` - The wgpu command queue
+    ///
+    /// # Returns
+    /// The timing of every pass in execution order, or an error if the device lacks
+    /// timestamp query support or the readback failed
+    pub fn profile(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<Vec<PassTiming>, Box<dyn std::error::Error>> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "Profiling requires a device with wgpu::Features::TIMESTAMP_QUERY enabled",
+            )));
+        }
+
+        let pass_count = self.bound_pipelines.iter().map(|bound_pipeline| bound_pipeline.passes.len() as u32).sum::<u32>();
+        if pass_count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Pass Timestamps"),
+            ty: wgpu::QueryType::Timestamp,
+            count: pass_count * 2,
+        });
+        let buffer_size = pass_count as u64 * 2 * std::mem::size_of::<u64>() as u64;
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Pass Timestamp Resolve Buffer"),
+            size: buffer_size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Pass Timestamp Readback Buffer"),
+            size: buffer_size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Anime4K Profile") });
+        let mut names = Vec::with_capacity(pass_count as usize);
+        for stage in self.stages() {
+            match stage {
+                Stage::Pipeline(bound_pipeline) => bound_pipeline.record(&mut encoder, Some((&query_set, &mut names))),
+                stage => stage.record(&mut encoder),
+            }
+        }
+        encoder.resolve_query_set(&query_set, 0..pass_count * 2, &resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(&resolve_buffer, 0, &readback_buffer, 0, buffer_size);
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let buffer_slice = readback_buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::PollType::Wait)?;
+        receiver.recv()??;
+
+        // Timestamps are in ticks of the queue's timestamp period, in nanoseconds
+        let period = queue.get_timestamp_period() as f64;
+        let data = buffer_slice.get_mapped_range();
+        let timestamps: Vec<u64> = data.chunks_exact(8).map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap())).collect();
+        drop(data);
+        readback_buffer.unmap();
+
+        Ok(names
+            .into_iter()
+            .zip(timestamps.chunks_exact(2))
+            .map(|(name, timestamps)| PassTiming {
+                name,
+                duration: std::time::Duration::from_nanos((timestamps[1].saturating_sub(timestamps[0]) as f64 * period) as u64),
+            })
+            .collect())
+    }
+
+    /// Device features the executor needs on every adapter
+    ///
+    /// The shaders sample 32-bit float textures with linear filtering.
+    pub const REQUIRED_FEATURES: wgpu::Features = wgpu::Features::FLOAT32_FILTERABLE;
+
+    /// Requests a device suitable for the executor from a caller-selected adapter
+    ///
+    /// Applications that enumerate adapters with their own logic can pass the chosen one
+    /// here instead of letting a helper request a possibly different adapter. The adapter
+    /// is checked with [`Self::check_format_support`] and must support
+    /// [`Self::REQUIRED_FEATURES`].
+    ///
+    /// # Arguments
+    /// * `adapter` - The adapter to create the device from
+    /// * `additional_features` - Features the caller needs on top of the required ones (e.g. `TIMESTAMP_QUERY`)
+    ///
+    /// # Returns
+    /// The device and queue
+    ///
+    /// # Errors
+    /// Returns an `Unsupported` error if the adapter lacks a required or additional feature or
+    /// a storage format, or the error from the device request
+    pub async fn request_device(adapter: &wgpu::Adapter, additional_features: wgpu::Features) -> Result<(wgpu::Device, wgpu::Queue), Box<dyn std::error::Error>> {
+        let required_features = Self::REQUIRED_FEATURES | additional_features;
+        let missing = required_features - adapter.features();
+        if !missing.is_empty() {
+            let info = adapter.get_info();
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("Adapter '{}' ({:?}) does not support {missing:?}", info.name, info.backend),
+            )));
         }
+        Self::check_format_support(adapter)?;
+
+        let (device, queue) = adapter
+            .request_device(&wgpu::DeviceDescriptor {
+                label: Some("Anime4K-wgpu"),
+                required_features,
+                required_limits: wgpu::Limits::default(),
+                memory_hints: wgpu::MemoryHints::default(),
+                trace: Default::default(),
+            })
+            .await?;
+        Ok((device, queue))
+    }
+
+    /// Checks that an adapter can bind every format the executor writes as a storage texture
+    ///
+    /// Without this check, an adapter lacking storage support for one of the 32-bit float
+    /// formats only fails once pipelines are created, with a validation error that does
+    /// not name the format. The shaders declare their storage formats, so there is no
+    /// lower-precision fallback and the adapter must be rejected instead.
+    ///
+    /// # Arguments
+    /// * `adapter` - The wgpu adapter the device will be requested from
+    ///
+    /// # Returns
+    /// `Ok(())` if every format is supported, or an error naming the unsupported formats
+    pub fn check_format_support(adapter: &wgpu::Adapter) -> Result<(), Box<dyn std::error::Error>> {
+        let unsupported = STORAGE_FORMATS
+            .into_iter()
+            .filter(|&format| !adapter.get_texture_format_features(format).allowed_usages.contains(wgpu::TextureUsages::STORAGE_BINDING))
+            .map(|format| format!("{format:?}"))
+            .collect::<Vec<_>>();
+        if unsupported.is_empty() {
+            return Ok(());
+        }
+
+        let info = adapter.get_info();
+        Err(Box::new(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            format!(
+                "Adapter '{}' ({:?}) does not support storage binding for {}, which Anime4K-wgpu requires; try another GPU or graphics backend",
+                info.name,
+                info.backend,
+                unsupported.join(", ")
+            ),
+        )))
+    }
+}
+
+impl Anime4kExecutor for PipelineExecutor {
+    fn pass(&self, encoder: &mut wgpu::CommandEncoder) {
+        PipelineExecutor::pass(self, encoder);
+    }
+
+    fn output_texture(&self) -> &wgpu::Texture {
+        &self.output_texture
+    }
+
+    fn profile(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<Vec<PassTiming>, Box<dyn std::error::Error>> {
+        PipelineExecutor::profile(self, device, queue)
     }
 }
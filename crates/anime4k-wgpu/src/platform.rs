@@ -0,0 +1,40 @@
+//! Platform capability detection
+//!
+//! This module tells library consumers whether the hardware video decode path used by
+//! the player example can work, so an application can decide whether to offer it.
+//! Availability is decided in three steps, each narrower than the previous one:
+//!
+//! 1. [`VULKAN_VIDEO_COMPILED`]: the target is one where Vulkan (and therefore
+//!    `vk-video`) can be used at all. This is the same `vulkan` cfg that gates the
+//!    player example.
+//! 2. [`vulkan_video_available`]: a Vulkan adapter is actually present at runtime.
+//! 3. Whether that adapter can decode H.264 is only known when `vk-video` creates its
+//!    device, which fails if no queue family supports video decode. Treat an error
+//!    from `VulkanInstance::create_device` as the final answer.
+
+/// Whether this build targets a platform with Vulkan video support
+///
+/// True on Windows and on Unix platforms other than macOS, iOS, and Emscripten.
+pub const VULKAN_VIDEO_COMPILED: bool = cfg!(vulkan);
+
+/// Returns whether a Vulkan adapter is available for hardware video decoding
+///
+/// Always false when [`VULKAN_VIDEO_COMPILED`] is false. Otherwise enumerates the
+/// Vulkan adapters, which loads the Vulkan driver and may take a moment, so call this
+/// once and cache the result. A `true` result does not guarantee H.264 decode support;
+/// see the module documentation.
+pub fn vulkan_video_available() -> bool {
+    #[cfg(vulkan)]
+    {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::VULKAN,
+            ..Default::default()
+        });
+        !instance.enumerate_adapters(wgpu::Backends::VULKAN).is_empty()
+    }
+
+    #[cfg(not(vulkan))]
+    {
+        false
+    }
+}
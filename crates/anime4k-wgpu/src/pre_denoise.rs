@@ -0,0 +1,162 @@
+//! Denoising before upscaling
+//!
+//! This module smooths the source with a Gaussian or bilateral filter before the
+//! pipelines run. Anime4K's restore CNNs sharpen whatever they are given, so on noisy or
+//! heavily compressed sources a light pre-pass keeps the noise from being amplified.
+
+/// Workgroup size of the pre-denoise pass in each dimension
+const WORKGROUP_SIZE: u32 = 8;
+
+/// Filter applied to the source before the pipelines
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PreDenoise {
+    /// Gaussian blur
+    Gaussian {
+        /// Standard deviation of the kernel in texels
+        sigma: f32,
+    },
+    /// Edge-preserving bilateral filter
+    Bilateral {
+        /// Standard deviation of the spatial kernel in texels
+        sigma: f32,
+        /// Standard deviation of the color difference weighting, in normalized color units
+        range_sigma: f32,
+    },
+}
+
+impl PreDenoise {
+    /// Range sigma used when `bilateral` is given without one
+    pub const DEFAULT_RANGE_SIGMA: f32 = 0.1;
+
+    /// Largest kernel radius in texels, bounding the cost of large sigmas
+    pub const MAX_RADIUS: u32 = 8;
+
+    /// Returns the kernel radius in texels, covering three standard deviations
+    ///
+    /// The radius is at least 1 and at most `MAX_RADIUS`.
+    pub fn radius(&self) -> u32 {
+        let (Self::Gaussian { sigma } | Self::Bilateral { sigma, .. }) = *self;
+        ((sigma * 3.0).ceil() as u32).clamp(1, Self::MAX_RADIUS)
+    }
+}
+
+impl std::str::FromStr for PreDenoise {
+    type Err = String;
+
+    /// Parses `gaussian:SIGMA`, `bilateral:SIGMA`, or `bilateral:SIGMA,RANGE_SIGMA`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, values) = s
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid pre-denoise filter: {s} (expected gaussian:SIGMA or bilateral:SIGMA[,RANGE_SIGMA])"))?;
+        let values = values
+            .split(',')
+            .map(|value| match value.trim().parse::<f32>() {
+                Ok(value) if value.is_finite() && value > 0.0 => Ok(value),
+                _ => Err(format!("Invalid pre-denoise sigma '{value}' (expected a positive number)")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match (kind.to_ascii_lowercase().as_str(), values.as_slice()) {
+            ("gaussian", &[sigma]) => Ok(Self::Gaussian { sigma }),
+            ("bilateral", &[sigma]) => Ok(Self::Bilateral {
+                sigma,
+                range_sigma: Self::DEFAULT_RANGE_SIGMA,
+            }),
+            ("bilateral", &[sigma, range_sigma]) => Ok(Self::Bilateral { sigma, range_sigma }),
+            _ => Err(format!("Invalid pre-denoise filter: {s} (expected gaussian:SIGMA or bilateral:SIGMA[,RANGE_SIGMA])")),
+        }
+    }
+}
+
+/// The pre-denoise dispatch and the texture it writes
+#[derive(Debug)]
+pub(crate) struct PreDenoisePass {
+    /// The compute pipeline with the filter parameters baked in
+    compute_pipeline: wgpu::ComputePipeline,
+    /// Bind group with the source and output textures
+    bind_group: wgpu::BindGroup,
+    /// Denoised copy of the source, fed to the pipelines
+    output_texture: wgpu::Texture,
+}
+
+impl PreDenoisePass {
+    /// Creates the pre-denoise pass for a source texture
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device
+    /// * `source_texture` - Texture to denoise (must have TEXTURE_BINDING usage)
+    /// * `pre_denoise` - Filter to apply
+    pub(crate) fn new(device: &wgpu::Device, source_texture: &wgpu::Texture, pre_denoise: PreDenoise) -> Self {
+        let output_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Pre-Denoise Texture"),
+            size: source_texture.size(),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba32Float,
+            // Same usages as the pipeline textures, which may reuse this one
+            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        let (sigma, range_factor) = match pre_denoise {
+            PreDenoise::Gaussian { sigma } => (sigma, 0.0),
+            PreDenoise::Bilateral { sigma, range_sigma } => (sigma, -1.0 / (2.0 * range_sigma * range_sigma)),
+        };
+        let shader = include_str!("pre_denoise.wgsl")
+            .replace("{{ RADIUS }}", &pre_denoise.radius().to_string())
+            .replace("{{ SPATIAL_FACTOR }}", &format!("{:.9}", -1.0 / (2.0 * sigma * sigma)))
+            .replace("{{ RANGE_FACTOR }}", &format!("{range_factor:.9}"));
+
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Pre-Denoise"),
+            source: wgpu::ShaderSource::Wgsl(shader.into()),
+        });
+
+        let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Pre-Denoise"),
+            layout: None,
+            module: &shader_module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Pre-Denoise"),
+            layout: &compute_pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&source_texture.create_view(&Default::default())),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&output_texture.create_view(&Default::default())),
+                },
+            ],
+        });
+
+        Self {
+            compute_pipeline,
+            bind_group,
+            output_texture,
+        }
+    }
+
+    /// Returns the denoised texture to run the pipelines on
+    pub(crate) fn output_texture(&self) -> &wgpu::Texture {
+        &self.output_texture
+    }
+
+    /// Records the pre-denoise dispatch
+    pub(crate) fn pass(&self, encoder: &mut wgpu::CommandEncoder) {
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Pre-Denoise"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&self.compute_pipeline);
+        compute_pass.set_bind_group(0, &self.bind_group, &[]);
+        compute_pass.dispatch_workgroups(self.output_texture.width().div_ceil(WORKGROUP_SIZE), self.output_texture.height().div_ceil(WORKGROUP_SIZE), 1);
+    }
+}
@@ -0,0 +1,41 @@
+// Pre-denoise compute shader
+//
+// Smooths the source before the CNN passes with a Gaussian kernel, optionally weighted
+// by color similarity to the center texel (bilateral), so compression noise is not
+// amplified as detail. Parameters are substituted when the shader is created.
+
+const RADIUS: i32 = {{ RADIUS }};
+// -1 / (2 * sigma^2) of the spatial kernel
+const SPATIAL_FACTOR: f32 = {{ SPATIAL_FACTOR }};
+// -1 / (2 * sigma^2) of the range kernel, or 0 for a plain Gaussian
+const RANGE_FACTOR: f32 = {{ RANGE_FACTOR }};
+
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var output_texture: texture_storage_2d<rgba32float, write>;
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) global_id: vec3u) {
+    let dims = textureDimensions(source_texture);
+    if global_id.x >= dims.x || global_id.y >= dims.y {
+        return;
+    }
+
+    let pos = vec2i(global_id.xy);
+    let max_pos = vec2i(dims) - 1;
+    let center = textureLoad(source_texture, pos, 0);
+
+    var sum = vec4f(0.0);
+    var weight_sum = 0.0;
+    for (var dy = -RADIUS; dy <= RADIUS; dy++) {
+        for (var dx = -RADIUS; dx <= RADIUS; dx++) {
+            // Clamp to the edge so border texels are not darkened
+            let color = textureLoad(source_texture, clamp(pos + vec2i(dx, dy), vec2i(0), max_pos), 0);
+            let difference = color.rgb - center.rgb;
+            let weight = exp(f32(dx * dx + dy * dy) * SPATIAL_FACTOR + dot(difference, difference) * RANGE_FACTOR);
+            sum += color * weight;
+            weight_sum += weight;
+        }
+    }
+
+    textureStore(output_texture, pos, sum / weight_sum);
+}
@@ -4,7 +4,7 @@
 //! performance levels for common use cases.
 
 use crate::{
-    ExecutablePipeline,
+    ExecutablePipeline, PipelineExecutor,
     pipelines::{aux, cnn},
 };
 
@@ -25,6 +25,22 @@ pub enum Anime4KPerformancePreset {
     Extreme,
 }
 
+impl std::str::FromStr for Anime4KPerformancePreset {
+    type Err = String;
+
+    /// Parses a performance preset name as accepted by the examples (light, medium, high, ultra, extreme)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "light" => Ok(Self::Light),
+            "medium" => Ok(Self::Medium),
+            "high" => Ok(Self::High),
+            "ultra" => Ok(Self::Ultra),
+            "extreme" => Ok(Self::Extreme),
+            _ => Err(format!("Invalid performance preset '{s}'. Valid presets: light, medium, high, ultra, extreme")),
+        }
+    }
+}
+
 impl Anime4KPerformancePreset {
     /// Returns the human-readable name of this performance preset
     pub fn name(&self) -> &'static str {
@@ -37,6 +53,28 @@ impl Anime4KPerformancePreset {
         }
     }
 
+    /// Returns the next lighter performance preset, or None for `Light`
+    pub fn lighter(&self) -> Option<Self> {
+        match self {
+            Anime4KPerformancePreset::Light => None,
+            Anime4KPerformancePreset::Medium => Some(Anime4KPerformancePreset::Light),
+            Anime4KPerformancePreset::High => Some(Anime4KPerformancePreset::Medium),
+            Anime4KPerformancePreset::Ultra => Some(Anime4KPerformancePreset::High),
+            Anime4KPerformancePreset::Extreme => Some(Anime4KPerformancePreset::Ultra),
+        }
+    }
+
+    /// Returns the next heavier performance preset, or None for `Extreme`
+    pub fn heavier(&self) -> Option<Self> {
+        match self {
+            Anime4KPerformancePreset::Light => Some(Anime4KPerformancePreset::Medium),
+            Anime4KPerformancePreset::Medium => Some(Anime4KPerformancePreset::High),
+            Anime4KPerformancePreset::High => Some(Anime4KPerformancePreset::Ultra),
+            Anime4KPerformancePreset::Ultra => Some(Anime4KPerformancePreset::Extreme),
+            Anime4KPerformancePreset::Extreme => None,
+        }
+    }
+
     /// Returns the restore CNN pipeline for the initial pass
     fn for_initial_restore(&self) -> &'static ExecutablePipeline {
         match self {
@@ -115,6 +153,42 @@ impl Anime4KPerformancePreset {
     }
 }
 
+/// Resolution thresholds used by `Anime4KPreset::recommend`
+///
+/// Inputs are classified by their shorter side, so portrait and landscape images of the
+/// same resolution get the same recommendation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PresetThresholds {
+    /// Shorter sides below this are treated as low resolution (default 720, i.e. SD content)
+    pub low_resolution: u32,
+    /// Shorter sides at or above this are treated as already large (default 1080)
+    pub high_resolution: u32,
+}
+
+impl Default for PresetThresholds {
+    fn default() -> Self {
+        Self {
+            low_resolution: 720,
+            high_resolution: 1080,
+        }
+    }
+}
+
+/// Pipelines chosen by `Anime4KPreset::create_pipelines_within_budget`
+#[derive(Debug, Clone)]
+pub struct BudgetedPipelines {
+    /// Pipelines to run in sequence
+    pub pipelines: Vec<&'static ExecutablePipeline>,
+    /// Performance preset the pipelines were created with
+    pub performance_preset: Anime4KPerformancePreset,
+    /// Target scale factor the pipelines were created with
+    pub target_scale_factor: f64,
+    /// Estimated texture memory of the pipelines in bytes, see `PipelineExecutor::estimate_memory`
+    pub estimated_memory: u64,
+    /// Steps taken to fit the budget, in order, as human-readable messages for logging
+    pub downgrades: Vec<String>,
+}
+
 /// Anime4K algorithm presets that define the processing pipeline
 ///
 /// Each mode represents a different approach to upscaling with varying
@@ -133,6 +207,26 @@ pub enum Anime4KPreset {
     ModeBB,
     /// C + A: Upscale Denoise → Restore
     ModeCA,
+    /// Integer nearest-neighbor scaling without any CNN, for pixel-art content
+    Nearest,
+}
+
+impl std::str::FromStr for Anime4KPreset {
+    type Err = String;
+
+    /// Parses a preset name as accepted by the examples (a, b, c, aa, bb, ca, nearest)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "a" => Ok(Self::ModeA),
+            "b" => Ok(Self::ModeB),
+            "c" => Ok(Self::ModeC),
+            "aa" => Ok(Self::ModeAA),
+            "bb" => Ok(Self::ModeBB),
+            "ca" => Ok(Self::ModeCA),
+            "nearest" => Ok(Self::Nearest),
+            _ => Err(format!("Invalid preset '{s}'. Valid presets: a, b, c, aa, bb, ca, nearest")),
+        }
+    }
 }
 
 impl Anime4KPreset {
@@ -145,6 +239,7 @@ impl Anime4KPreset {
             Anime4KPreset::ModeAA => "Mode AA",
             Anime4KPreset::ModeBB => "Mode BB",
             Anime4KPreset::ModeCA => "Mode CA",
+            Anime4KPreset::Nearest => "Nearest",
         }
     }
 
@@ -177,6 +272,7 @@ impl Anime4KPreset {
                 performance_preset.for_subsequent_restore_soft(),
             ],
             Anime4KPreset::ModeCA => vec![&aux::CLAMP_HIGHLIGHTS, performance_preset.for_initial_upscale_denoise_2x(), performance_preset.for_subsequent_restore()],
+            Anime4KPreset::Nearest => return Self::create_nearest_pipelines(target_scale_factor),
         };
 
         let mut current_scale_factor = 2.0;
@@ -187,4 +283,255 @@ impl Anime4KPreset {
 
         base
     }
+
+    /// Creates the processing pipeline for this preset, downgrading it to fit a memory budget
+    ///
+    /// When the textures of the requested configuration are estimated to exceed
+    /// `memory_budget`, the configuration is lowered one step at a time until it fits:
+    ///
+    /// 1. The performance preset is lowered one level at a time (Extreme, Ultra, High,
+    ///    Medium) down to Light, using smaller CNNs at the same output size.
+    /// 2. At Light, the target scale factor is halved down to 2x, dropping one 2x
+    ///    upscaling pass per step.
+    ///
+    /// Every step is recorded in `downgrades` so the caller can log it. The library does
+    /// not log by itself.
+    ///
+    /// # Arguments
+    /// * `performance_preset` - Requested computational complexity and model sizes
+    /// * `target_scale_factor` - Requested output scale factor (e.g., 2.0 for 2x upscaling)
+    /// * `input_width` - Width of the input in pixels
+    /// * `input_height` - Height of the input in pixels
+    /// * `memory_budget` - Maximum estimated texture memory in bytes, or None for no limit
+    ///
+    /// # Returns
+    /// The pipelines of the first configuration within budget
+    ///
+    /// # Errors
+    /// Returns an error if even 2x at Light exceeds the budget
+    pub fn create_pipelines_within_budget(
+        &self,
+        performance_preset: Anime4KPerformancePreset,
+        target_scale_factor: f64,
+        input_width: u32,
+        input_height: u32,
+        memory_budget: Option<u64>,
+    ) -> Result<BudgetedPipelines, Box<dyn std::error::Error>> {
+        let mut performance_preset = performance_preset;
+        let mut target_scale_factor = target_scale_factor;
+        let mut downgrades = Vec::new();
+
+        loop {
+            let pipelines = self.create_pipelines(performance_preset, target_scale_factor);
+            let estimated_memory = PipelineExecutor::estimate_memory(&pipelines, input_width, input_height);
+            let Some(memory_budget) = memory_budget.filter(|&memory_budget| estimated_memory > memory_budget) else {
+                return Ok(BudgetedPipelines {
+                    pipelines,
+                    performance_preset,
+                    target_scale_factor,
+                    estimated_memory,
+                    downgrades,
+                });
+            };
+
+            let output_scale_factor = Self::output_scale_factor(target_scale_factor);
+            // Nearest has no CNN, so a lighter performance preset would not save memory
+            if let Some(lighter) = performance_preset.lighter().filter(|_| *self != Anime4KPreset::Nearest) {
+                downgrades.push(format!(
+                    "Estimated {estimated_memory} bytes exceeds the budget of {memory_budget} bytes, lowering performance from {} to {}",
+                    performance_preset.name(),
+                    lighter.name()
+                ));
+                performance_preset = lighter;
+            } else if output_scale_factor > 2 {
+                downgrades.push(format!(
+                    "Estimated {estimated_memory} bytes exceeds the budget of {memory_budget} bytes, lowering scale factor from {output_scale_factor}x to {}x",
+                    output_scale_factor / 2
+                ));
+                target_scale_factor = (output_scale_factor / 2) as f64;
+            } else {
+                return Err(Box::new(std::io::Error::new(
+                    std::io::ErrorKind::OutOfMemory,
+                    format!("Estimated {estimated_memory} bytes for {input_width}x{input_height} at 2x exceeds the budget of {memory_budget} bytes even at the lightest settings"),
+                )));
+            }
+        }
+    }
+
+    /// Creates the nearest-neighbor pipeline for the `Nearest` preset
+    ///
+    /// Uses a single nearest-sample pass for the output scale factor (2x, 4x, or 8x),
+    /// chaining further 2x passes only beyond 8x. Since every pass is an integer scale,
+    /// the result is identical to one nearest-neighbor resample.
+    fn create_nearest_pipelines(target_scale_factor: f64) -> Vec<&'static ExecutablePipeline> {
+        match Self::output_scale_factor(target_scale_factor) {
+            2 => vec![&aux::UPSCALE_NEAREST_X2],
+            4 => vec![&aux::UPSCALE_NEAREST_X4],
+            scale_factor => {
+                let mut base = vec![&aux::UPSCALE_NEAREST_X8];
+                let mut current_scale_factor = 8;
+                while current_scale_factor < scale_factor {
+                    base.push(&aux::UPSCALE_NEAREST_X2);
+                    current_scale_factor *= 2;
+                }
+                base
+            }
+        }
+    }
+
+    /// Recommends a preset and performance level for an input resolution
+    ///
+    /// Uses the default `PresetThresholds`; see `recommend_with_thresholds`.
+    ///
+    /// # Arguments
+    /// * `input_width` - Width of the input in pixels
+    /// * `input_height` - Height of the input in pixels
+    /// * `target_scale_factor` - Desired output scale factor (e.g., 2.0 for 2x upscaling)
+    ///
+    /// # Returns
+    /// The recommended preset and performance level
+    pub fn recommend(input_width: u32, input_height: u32, target_scale_factor: f64) -> (Anime4KPreset, Anime4KPerformancePreset) {
+        Self::recommend_with_thresholds(input_width, input_height, target_scale_factor, &PresetThresholds::default())
+    }
+
+    /// Recommends a preset and performance level for an input resolution using custom thresholds
+    ///
+    /// Low-resolution inputs get the heavier AA chain at Ultra, since they usually carry
+    /// the most blur and the output stays small. Inputs in between get Mode A at High.
+    /// Already-large inputs get the single-CNN Mode C at Medium, as they need little
+    /// restoration and produce large outputs. Each 2x pass beyond the first quadruples
+    /// the output size, so the performance level drops one step for each.
+    ///
+    /// # Arguments
+    /// * `input_width` - Width of the input in pixels
+    /// * `input_height` - Height of the input in pixels
+    /// * `target_scale_factor` - Desired output scale factor (e.g., 2.0 for 2x upscaling)
+    /// * `thresholds` - Resolution thresholds separating low, medium, and high resolution inputs
+    ///
+    /// # Returns
+    /// The recommended preset and performance level
+    pub fn recommend_with_thresholds(input_width: u32, input_height: u32, target_scale_factor: f64, thresholds: &PresetThresholds) -> (Anime4KPreset, Anime4KPerformancePreset) {
+        let short_side = input_width.min(input_height);
+        let (preset, performance_preset) = if short_side < thresholds.low_resolution {
+            (Anime4KPreset::ModeAA, Anime4KPerformancePreset::Ultra)
+        } else if short_side < thresholds.high_resolution {
+            (Anime4KPreset::ModeA, Anime4KPerformancePreset::High)
+        } else {
+            (Anime4KPreset::ModeC, Anime4KPerformancePreset::Medium)
+        };
+
+        let extra_passes = Self::output_scale_factor(target_scale_factor).trailing_zeros() - 1;
+        let performance_preset = (0..extra_passes).fold(performance_preset, |performance_preset, _| performance_preset.lighter().unwrap_or(performance_preset));
+
+        (preset, performance_preset)
+    }
+
+    /// Returns the scale factor of the output produced by `create_pipelines`
+    ///
+    /// Every preset upscales by 2x once and then doubles until the target scale
+    /// factor is reached, so the result is the smallest power of two that is at
+    /// least 2 and at least `target_scale_factor`. Useful for producing a plain
+    /// scaled reference at exactly the same size as the Anime4K output.
+    ///
+    /// # Arguments
+    /// * `target_scale_factor` - Desired output scale factor (e.g., 2.0 for 2x upscaling)
+    ///
+    /// # Returns
+    /// The actual output scale factor
+    pub fn output_scale_factor(target_scale_factor: f64) -> u32 {
+        let mut current_scale_factor = 2;
+        while (current_scale_factor as f64) < target_scale_factor {
+            current_scale_factor *= 2;
+        }
+
+        current_scale_factor
+    }
+}
+
+/// The active Anime4K preset and performance preset of a frontend
+///
+/// Frontends rebuild their pipelines when either changes. The setters report whether
+/// the value changed, so setting the current value again can be skipped consistently.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Anime4kSettings {
+    /// Active preset, or None while Anime4K is disabled
+    preset: Option<Anime4KPreset>,
+    /// Active performance preset, kept while Anime4K is disabled
+    performance_preset: Anime4KPerformancePreset,
+}
+
+impl Default for Anime4kSettings {
+    /// Anime4K disabled, at the `Medium` performance preset
+    fn default() -> Self {
+        Self::new(None, Anime4KPerformancePreset::Medium)
+    }
+}
+
+impl Anime4kSettings {
+    /// Creates settings with the given preset and performance preset
+    ///
+    /// # Arguments
+    /// * `preset` - Active preset, or None to disable Anime4K
+    /// * `performance_preset` - Active performance preset
+    pub fn new(preset: Option<Anime4KPreset>, performance_preset: Anime4KPerformancePreset) -> Self {
+        Self { preset, performance_preset }
+    }
+
+    /// Returns the active preset, or None while Anime4K is disabled
+    pub fn preset(&self) -> Option<Anime4KPreset> {
+        self.preset
+    }
+
+    /// Returns the active performance preset
+    pub fn performance_preset(&self) -> Anime4KPerformancePreset {
+        self.performance_preset
+    }
+
+    /// Sets the active preset
+    ///
+    /// # Arguments
+    /// * `preset` - Preset to activate, or None to disable Anime4K
+    ///
+    /// # Returns
+    /// Whether the preset changed
+    pub fn set_preset(&mut self, preset: Option<Anime4KPreset>) -> bool {
+        self.set(preset, self.performance_preset)
+    }
+
+    /// Sets the active performance preset
+    ///
+    /// # Arguments
+    /// * `performance_preset` - Performance preset to activate
+    ///
+    /// # Returns
+    /// Whether the performance preset changed
+    pub fn set_performance_preset(&mut self, performance_preset: Anime4KPerformancePreset) -> bool {
+        self.set(self.preset, performance_preset)
+    }
+
+    /// Sets the active preset and performance preset together
+    ///
+    /// # Arguments
+    /// * `preset` - Preset to activate, or None to disable Anime4K
+    /// * `performance_preset` - Performance preset to activate
+    ///
+    /// # Returns
+    /// Whether either value changed
+    pub fn set(&mut self, preset: Option<Anime4KPreset>, performance_preset: Anime4KPerformancePreset) -> bool {
+        let settings = Self::new(preset, performance_preset);
+        let changed = *self != settings;
+        *self = settings;
+        changed
+    }
+
+    /// Creates the pipelines for the active settings
+    ///
+    /// # Arguments
+    /// * `target_scale_factor` - Desired output scale factor (e.g., 2.0 for 2x upscaling)
+    ///
+    /// # Returns
+    /// The pipelines of `Anime4KPreset::create_pipelines`, or None while Anime4K is disabled
+    pub fn create_pipelines(&self, target_scale_factor: f64) -> Option<Vec<&'static ExecutablePipeline>> {
+        self.preset.map(|preset| preset.create_pipelines(self.performance_preset, target_scale_factor))
+    }
 }
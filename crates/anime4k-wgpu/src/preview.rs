@@ -0,0 +1,120 @@
+//! Switching between a fast preview and a full-quality render
+//!
+//! Interactive tools want a light pipeline while the user scrubs and the full one once
+//! input settles. This module binds both chains to the same source and the same output
+//! texture, so the display keeps sampling one texture whichever chain last wrote it,
+//! and switching is a field update rather than a rebind.
+
+use crate::{Anime4kExecutor, ExecutablePipeline, PassTiming, PipelineExecutor};
+
+/// Which chain of a [`PreviewExecutor`] records its passes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Quality {
+    /// The lighter chain, for scrubbing and other rapid updates
+    Preview,
+    /// The full chain, for the frame shown once input settles
+    #[default]
+    Final,
+}
+
+/// A preview and a final executor writing into one shared output texture
+///
+/// Both executors are created up front, so switching does not create GPU resources.
+/// Only the chain selected by `set_quality` runs in `pass`; after switching back to
+/// `Quality::Final`, record `pass` again to replace the preview in the output.
+#[derive(Debug)]
+pub struct PreviewExecutor {
+    /// Executor of the lighter chain
+    preview: PipelineExecutor,
+    /// Executor of the full chain, which owns the shared output texture
+    final_executor: PipelineExecutor,
+    /// Chain recorded by `pass`
+    quality: Quality,
+}
+
+impl PreviewExecutor {
+    /// Binds a preview and a final chain of pipelines to the same source
+    ///
+    /// The final chain is bound like `PipelineExecutor::new`, and the preview chain
+    /// writes into its output texture. A typical preview runs the same preset at a
+    /// lower performance level, e.g. `Light` against `Ultra`.
+    ///
+    /// # Arguments
+    /// * `preview_pipelines` - Pipelines run while previewing
+    /// * `final_pipelines` - Pipelines run for the final render
+    /// * `device` - The wgpu device for resource creation
+    /// * `source_texture` - The initial input texture
+    ///
+    /// # Returns
+    /// A tuple of (executor, shared output texture), or an error if the chains produce
+    /// outputs of different sizes or formats
+    ///
+    /// # Panics
+    /// Panics if the source texture has a zero width or height, or lacks `TEXTURE_BINDING` usage
+    pub fn new(
+        preview_pipelines: &[&'static ExecutablePipeline],
+        final_pipelines: &[&'static ExecutablePipeline],
+        device: &wgpu::Device,
+        source_texture: &wgpu::Texture,
+    ) -> Result<(Self, wgpu::Texture), Box<dyn std::error::Error>> {
+        let (final_executor, output_texture) = PipelineExecutor::new(final_pipelines, device, source_texture);
+        let preview = PipelineExecutor::new_with_output(preview_pipelines, device, source_texture, &output_texture).map_err(|e| format!("Preview pipelines cannot share the final output: {e}"))?;
+
+        Ok((
+            Self {
+                preview,
+                final_executor,
+                quality: Quality::Final,
+            },
+            output_texture,
+        ))
+    }
+
+    /// Returns the chain `pass` records
+    pub fn quality(&self) -> Quality {
+        self.quality
+    }
+
+    /// Selects the chain `pass` records from now on
+    ///
+    /// # Arguments
+    /// * `quality` - Chain to run
+    pub fn set_quality(&mut self, quality: Quality) {
+        self.quality = quality;
+    }
+
+    /// Returns the executor of the selected chain
+    pub fn active(&self) -> &PipelineExecutor {
+        match self.quality {
+            Quality::Preview => &self.preview,
+            Quality::Final => &self.final_executor,
+        }
+    }
+
+    /// Returns the source texture both chains read from
+    pub fn source_texture(&self) -> &wgpu::Texture {
+        self.final_executor.source_texture()
+    }
+
+    /// Records the selected chain
+    ///
+    /// # Arguments
+    /// * `encoder` - The command encoder to record commands into
+    pub fn pass(&self, encoder: &mut wgpu::CommandEncoder) {
+        self.active().pass(encoder);
+    }
+}
+
+impl Anime4kExecutor for PreviewExecutor {
+    fn pass(&self, encoder: &mut wgpu::CommandEncoder) {
+        PreviewExecutor::pass(self, encoder);
+    }
+
+    fn output_texture(&self) -> &wgpu::Texture {
+        self.final_executor.output_texture()
+    }
+
+    fn profile(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<Vec<PassTiming>, Box<dyn std::error::Error>> {
+        self.active().profile(device, queue)
+    }
+}
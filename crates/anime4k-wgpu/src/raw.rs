@@ -0,0 +1,223 @@
+//! Upscaling of raw floating-point image data
+//!
+//! This module runs a preset on RGBA `f32` data already in memory and returns the
+//! result in the same layout, without any 8-bit conversion on the way in or out.
+//! Data in BGRA order is swizzled to RGBA for processing and back afterwards.
+
+use crate::{
+    PipelineExecutor,
+    presets::{Anime4KPerformancePreset, Anime4KPreset},
+};
+
+/// Size in bytes of one RGBA32Float texel
+const BYTES_PER_TEXEL: u32 = 4 * 4;
+
+/// Order of the four channels of each texel in caller-provided data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChannelOrder {
+    /// Red, green, blue, alpha
+    #[default]
+    Rgba,
+    /// Blue, green, red, alpha, as produced by many capture APIs
+    Bgra,
+}
+
+impl ChannelOrder {
+    /// Converts texels between this order and RGBA in place
+    ///
+    /// Swapping red and blue is its own inverse, so the same call converts data in
+    /// this order to RGBA and RGBA results back to this order.
+    ///
+    /// # Arguments
+    /// * `texels` - Row-major texels, 4 values per pixel
+    pub fn swizzle_rgba<T>(self, texels: &mut [T]) {
+        if self == ChannelOrder::Bgra {
+            for texel in texels.chunks_exact_mut(4) {
+                texel.swap(0, 2);
+            }
+        }
+    }
+}
+
+/// Upscales raw RGBA `f32` image data with an Anime4K preset
+///
+/// The data is uploaded directly to an `Rgba32Float` texture, processed, and read back,
+/// blocking until the GPU has finished. The output dimensions are those returned by
+/// `PipelineExecutor::output_dimensions` for the pipelines of the preset.
+///
+/// The device must support `wgpu::Features::FLOAT32_FILTERABLE`, as the pipelines
+/// sample float textures with linear filtering.
+///
+/// # Arguments
+/// * `device` - The wgpu device
+/// * `queue` - The wgpu command queue
+/// * `data` - Row-major texels in `channel_order`, 4 values per pixel
+/// * `channel_order` - Channel order of `data` and of the returned texels
+/// * `width` - Input width in pixels
+/// * `height` - Input height in pixels
+/// * `preset` - Anime4K preset to apply
+/// * `performance_preset` - Performance preset controlling the CNN size
+/// * `target_scale_factor` - Desired upscaling factor
+///
+/// # Returns
+/// Row-major texels of the output image in `channel_order`, or an error if the input size is invalid
+/// or the readback failed
+#[allow(clippy::too_many_arguments)]
+pub fn upscale_raw_f32(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    data: &[f32],
+    channel_order: ChannelOrder,
+    width: u32,
+    height: u32,
+    preset: Anime4KPreset,
+    performance_preset: Anime4KPerformancePreset,
+    target_scale_factor: f64,
+) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
+    validate_input(data, width, height)?;
+
+    let input_texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Raw Input Texture"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba32Float,
+        // COPY_SRC is needed when the preset has no pipelines and the input is read back as-is
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+
+    queue.write_texture(
+        wgpu::TexelCopyTextureInfo {
+            texture: &input_texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        &{
+            let mut data = data.to_vec();
+            channel_order.swizzle_rgba(&mut data);
+            data.iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<_>>()
+        },
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(width * BYTES_PER_TEXEL),
+            rows_per_image: Some(height),
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+
+    let pipelines = preset.create_pipelines(performance_preset, target_scale_factor);
+    let (executor, output_texture) = PipelineExecutor::new(&pipelines, device, &input_texture);
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Raw Upscale") });
+    executor.pass(&mut encoder);
+    let mut output = read_rgba32f_region(device, queue, encoder, &output_texture, (0, 0), (output_texture.width(), output_texture.height()))?;
+    channel_order.swizzle_rgba(&mut output);
+
+    Ok(output)
+}
+
+/// Checks that raw RGBA data is non-empty and matches its dimensions
+///
+/// # Returns
+/// An `InvalidInput` error if the size is empty or the data length does not match it
+pub(crate) fn validate_input(data: &[f32], width: u32, height: u32) -> Result<(), Box<dyn std::error::Error>> {
+    if width == 0 || height == 0 {
+        return Err(Box::new(std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("Invalid input size {width}x{height}"))));
+    }
+    let expected_len = width as usize * height as usize * 4;
+    if data.len() != expected_len {
+        return Err(Box::new(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Expected {expected_len} values for a {width}x{height} RGBA image, got {}", data.len()),
+        )));
+    }
+    Ok(())
+}
+
+/// Submits an encoder followed by a readback of a region of an RGBA32Float texture
+///
+/// Blocks until the GPU has finished.
+///
+/// # Arguments
+/// * `device` - The wgpu device
+/// * `queue` - The wgpu command queue
+/// * `encoder` - Commands to submit before the copy
+/// * `texture` - Texture to read (must have COPY_SRC usage)
+/// * `origin` - Top-left corner of the region in pixels
+/// * `size` - Size of the region in pixels
+///
+/// # Returns
+/// Row-major RGBA texels of the region, or an error if the readback failed
+pub(crate) fn read_rgba32f_region(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    mut encoder: wgpu::CommandEncoder,
+    texture: &wgpu::Texture,
+    origin: (u32, u32),
+    (width, height): (u32, u32),
+) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
+    // Rows of a texture-to-buffer copy must be aligned, so the padding is stripped after readback
+    let unpadded_bytes_per_row = width * BYTES_PER_TEXEL;
+    let padded_bytes_per_row = unpadded_bytes_per_row.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+
+    let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Raw Output Readback"),
+        size: padded_bytes_per_row as u64 * height as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    encoder.copy_texture_to_buffer(
+        wgpu::TexelCopyTextureInfo {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d { x: origin.0, y: origin.1, z: 0 },
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::TexelCopyBufferInfo {
+            buffer: &readback_buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: Some(height),
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    // Map the readback buffer and wait for completion
+    let buffer_slice = readback_buffer.slice(..);
+    let (sender, receiver) = std::sync::mpsc::channel();
+    buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    device.poll(wgpu::PollType::Wait)?;
+    receiver.recv()??;
+
+    let mapped = buffer_slice.get_mapped_range();
+    let output: Vec<f32> = mapped
+        .chunks_exact(padded_bytes_per_row as usize)
+        .flat_map(|row| row[..unpadded_bytes_per_row as usize].chunks_exact(4))
+        .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+        .collect();
+    drop(mapped);
+    readback_buffer.unmap();
+
+    Ok(output)
+}
@@ -0,0 +1,106 @@
+//! Shared samplers keyed by filter and address mode
+//!
+//! Every sampler Anime4K-wgpu creates differs only in its filter and address modes, so
+//! a cache keyed by those hands out one sampler per combination instead of one per
+//! pipeline, pass, or renderer. Samplers are created with a single mip level and no
+//! anisotropy, which is all the textures of a pipeline have.
+
+use std::collections::HashMap;
+
+use crate::SamplerFilterMode;
+
+/// Filter and address modes identifying a sampler in a [`SamplerCache`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SamplerKey {
+    /// Magnification and minification filter
+    pub filter: wgpu::FilterMode,
+    /// Address mode along the horizontal axis
+    pub address_mode_u: wgpu::AddressMode,
+    /// Address mode along the vertical axis
+    pub address_mode_v: wgpu::AddressMode,
+    /// Address mode along the depth axis
+    pub address_mode_w: wgpu::AddressMode,
+}
+
+impl SamplerKey {
+    /// Creates a key for a sampler clamping coordinates to the edge on all axes
+    ///
+    /// # Arguments
+    /// * `filter` - Magnification and minification filter
+    pub fn clamp_to_edge(filter: wgpu::FilterMode) -> Self {
+        Self {
+            filter,
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+        }
+    }
+}
+
+impl From<SamplerFilterMode> for SamplerKey {
+    /// Returns the key of the sampler shader passes get for a filter mode
+    fn from(filter_mode: SamplerFilterMode) -> Self {
+        Self::clamp_to_edge(match filter_mode {
+            SamplerFilterMode::Nearest => wgpu::FilterMode::Nearest,
+            SamplerFilterMode::Linear => wgpu::FilterMode::Linear,
+        })
+    }
+}
+
+/// Memoizes samplers by filter and address modes
+///
+/// A cache belongs to the device its samplers were created on; passing another device
+/// to `get` returns samplers of the first one.
+#[derive(Debug, Default)]
+pub struct SamplerCache {
+    /// Samplers created so far
+    samplers: HashMap<SamplerKey, wgpu::Sampler>,
+}
+
+impl SamplerCache {
+    /// Creates an empty sampler cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the sampler for a key, creating it on first use
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device to create the sampler on
+    /// * `key` - Filter and address modes of the sampler
+    ///
+    /// # Returns
+    /// A handle to the cached sampler
+    pub fn get(&mut self, device: &wgpu::Device, key: impl Into<SamplerKey>) -> wgpu::Sampler {
+        let key = key.into();
+        self.samplers
+            .entry(key)
+            .or_insert_with(|| {
+                device.create_sampler(&wgpu::SamplerDescriptor {
+                    label: Some(&format!("Sampler {:?} {:?}/{:?}/{:?}", key.filter, key.address_mode_u, key.address_mode_v, key.address_mode_w)),
+                    address_mode_u: key.address_mode_u,
+                    address_mode_v: key.address_mode_v,
+                    address_mode_w: key.address_mode_w,
+                    mag_filter: key.filter,
+                    min_filter: key.filter,
+                    mipmap_filter: wgpu::FilterMode::Nearest,
+                    lod_min_clamp: 0.0,
+                    lod_max_clamp: 0.0,
+                    compare: None,
+                    anisotropy_clamp: 1,
+                    border_color: None,
+                })
+            })
+            .clone()
+    }
+
+    /// Returns the number of samplers created so far
+    pub fn len(&self) -> usize {
+        self.samplers.len()
+    }
+
+    /// Returns whether no sampler has been created yet
+    pub fn is_empty(&self) -> bool {
+        self.samplers.is_empty()
+    }
+}
@@ -0,0 +1,177 @@
+//! Tiled upscaling with per-tile results
+//!
+//! This module upscales raw RGBA `f32` data one tile at a time and hands each upscaled
+//! tile to a callback as soon as it has been read back, so a viewer can show huge images
+//! progressively. Each tile is processed with a margin of surrounding input pixels, so
+//! the CNNs see the same neighborhood as without tiling and no seams appear.
+
+use crate::{
+    ChannelOrder, PipelineExecutor,
+    presets::{Anime4KPerformancePreset, Anime4KPreset},
+    raw::{read_rgba32f_region, validate_input},
+};
+
+/// Input pixels processed around each tile so the CNNs see its full neighborhood
+///
+/// This covers the receptive field of the largest preset chains at 2x.
+const TILE_MARGIN: u32 = 32;
+
+/// An upscaled tile passed to the callback of [`upscale_raw_f32_tiled_streaming`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileResult {
+    /// Left edge of the tile in the output image, in pixels
+    pub x: u32,
+    /// Top edge of the tile in the output image, in pixels
+    pub y: u32,
+    /// Width of the tile in pixels
+    pub width: u32,
+    /// Height of the tile in pixels
+    pub height: u32,
+    /// Row-major texels of the tile in the requested channel order, 4 values per pixel
+    pub data: Vec<f32>,
+}
+
+/// A tile along one axis and the input window it is processed in
+#[derive(Debug, Clone, Copy)]
+struct AxisTile {
+    /// First input pixel of the tile
+    start: u32,
+    /// Number of input pixels in the tile
+    length: u32,
+    /// First input pixel of the processing window
+    window_start: u32,
+}
+
+/// Splits one axis into tiles, each inside a window of the same size
+///
+/// Windows are shifted inwards at the image edges instead of shrinking, so every tile
+/// can be processed by the same executor.
+///
+/// # Returns
+/// The window size and the tiles in order
+fn plan_axis(size: u32, tile_size: u32) -> (u32, Vec<AxisTile>) {
+    let window_size = (tile_size + 2 * TILE_MARGIN).min(size);
+    let tiles = (0..size)
+        .step_by(tile_size as usize)
+        .map(|start| AxisTile {
+            start,
+            length: tile_size.min(size - start),
+            window_start: start.saturating_sub(TILE_MARGIN).min(size - window_size),
+        })
+        .collect();
+    (window_size, tiles)
+}
+
+/// Upscales raw RGBA `f32` image data tile by tile, reporting each tile as it completes
+///
+/// Tiles are processed in row-major order, and `on_tile` is called with each one right
+/// after its readback, before the next tile is submitted. Together the tiles cover the
+/// output image exactly once. Only one tile-sized window is on the GPU at a time, so
+/// this also bounds GPU memory use for inputs too large to process at once.
+///
+/// The preset must upscale by an integer factor, which holds for all built-in presets.
+/// The device must support `wgpu::Features::FLOAT32_FILTERABLE`.
+///
+/// # Arguments
+/// * `device` - The wgpu device
+/// * `queue` - The wgpu command queue
+/// * `data` - Row-major texels in `channel_order`, 4 values per pixel
+/// * `channel_order` - Channel order of `data` and of the texels passed to `on_tile`
+/// * `width` - Input width in pixels
+/// * `height` - Input height in pixels
+/// * `tile_size` - Width and height of the input covered by each tile, in pixels
+/// * `preset` - Anime4K preset to apply
+/// * `performance_preset` - Performance preset controlling the CNN size
+/// * `target_scale_factor` - Desired upscaling factor
+/// * `on_tile` - Called with each upscaled tile
+///
+/// # Returns
+/// `Ok(())` once every tile has been reported, or an error if the input or tile size is
+/// invalid, the preset does not scale by an integer factor, or a readback failed
+#[allow(clippy::too_many_arguments)]
+pub fn upscale_raw_f32_tiled_streaming(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    data: &[f32],
+    channel_order: ChannelOrder,
+    width: u32,
+    height: u32,
+    tile_size: u32,
+    preset: Anime4KPreset,
+    performance_preset: Anime4KPerformancePreset,
+    target_scale_factor: f64,
+    mut on_tile: impl FnMut(TileResult),
+) -> Result<(), Box<dyn std::error::Error>> {
+    validate_input(data, width, height)?;
+    if tile_size == 0 {
+        return Err(Box::new(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Tile size must be greater than zero")));
+    }
+
+    let (window_width, columns) = plan_axis(width, tile_size);
+    let (window_height, rows) = plan_axis(height, tile_size);
+
+    let source_texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Tile Input Texture"),
+        size: wgpu::Extent3d {
+            width: window_width,
+            height: window_height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba32Float,
+        // COPY_SRC is needed when the preset has no pipelines and the input is read back as-is
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+
+    let pipelines = preset.create_pipelines(performance_preset, target_scale_factor);
+    let (executor, output_texture) = PipelineExecutor::new(&pipelines, device, &source_texture);
+    if output_texture.width() % window_width != 0 || output_texture.height() % window_height != 0 {
+        return Err(Box::new(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "Tiling requires an integer scale factor, but a {window_width}x{window_height} tile upscales to {}x{}",
+                output_texture.width(),
+                output_texture.height()
+            ),
+        )));
+    }
+    let scale = (output_texture.width() / window_width, output_texture.height() / window_height);
+
+    let mut data = data.to_vec();
+    channel_order.swizzle_rgba(&mut data);
+
+    for row in &rows {
+        for column in &columns {
+            // Gather the window's rows from the full image
+            let window_bytes: Vec<u8> = (row.window_start..row.window_start + window_height)
+                .flat_map(|y| {
+                    let start = (y as usize * width as usize + column.window_start as usize) * 4;
+                    &data[start..start + window_width as usize * 4]
+                })
+                .flat_map(|value| value.to_le_bytes())
+                .collect();
+
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Tiled Upscale") });
+            executor.process_frame(queue, &mut encoder, &window_bytes)?;
+
+            // Read back only the tile, dropping the upscaled margin
+            let tile_size = (column.length * scale.0, row.length * scale.1);
+            let offset = ((column.start - column.window_start) * scale.0, (row.start - row.window_start) * scale.1);
+            let mut tile = read_rgba32f_region(device, queue, encoder, &output_texture, offset, tile_size)?;
+            channel_order.swizzle_rgba(&mut tile);
+
+            on_tile(TileResult {
+                x: column.start * scale.0,
+                y: row.start * scale.1,
+                width: tile_size.0,
+                height: tile_size.1,
+                data: tile,
+            });
+        }
+    }
+
+    Ok(())
+}
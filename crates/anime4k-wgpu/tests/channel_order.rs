@@ -0,0 +1,57 @@
+//! Tests for upscaling input in BGRA channel order
+//!
+//! The channel swizzle is checked on its own, and a BGRA input is upscaled against an RGBA baseline.
+
+use anime4k_wgpu::{
+    ChannelOrder,
+    presets::{Anime4KPerformancePreset, Anime4KPreset},
+    upscale_raw_f32,
+};
+
+mod common;
+
+/// Width and height of the input image in pixels
+const INPUT_SIZE: u32 = 16;
+
+/// Generates an input with distinct red, green, and blue gradients in RGBA order
+fn rgba_input() -> Vec<f32> {
+    (0..INPUT_SIZE * INPUT_SIZE)
+        .flat_map(|index| {
+            let (x, y) = ((index % INPUT_SIZE) as f32 / INPUT_SIZE as f32, (index / INPUT_SIZE) as f32 / INPUT_SIZE as f32);
+            [0.9 * x, 0.5, 0.9 * y, 1.0]
+        })
+        .collect()
+}
+
+/// Tests that swizzling swaps red and blue only, and undoes itself
+#[test]
+fn test_swizzle_rgba() {
+    let mut texels = [1, 2, 3, 4, 5, 6, 7, 8];
+    ChannelOrder::Bgra.swizzle_rgba(&mut texels);
+    assert_eq!(texels, [3, 2, 1, 4, 7, 6, 5, 8]);
+    ChannelOrder::Bgra.swizzle_rgba(&mut texels);
+    assert_eq!(texels, [1, 2, 3, 4, 5, 6, 7, 8]);
+
+    ChannelOrder::Rgba.swizzle_rgba(&mut texels);
+    assert_eq!(texels, [1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
+/// Tests that a BGRA input upscales to the BGRA form of the RGBA baseline
+#[test]
+fn test_bgra_matches_rgba() {
+    let (device, queue) = common::device_or_skip!("BGRA test");
+
+    let upscale = |data: &[f32], channel_order| upscale_raw_f32(&device, &queue, data, channel_order, INPUT_SIZE, INPUT_SIZE, Anime4KPreset::ModeA, Anime4KPerformancePreset::Light, 2.0).unwrap();
+
+    let rgba_input = rgba_input();
+    let mut bgra_input = rgba_input.clone();
+    ChannelOrder::Bgra.swizzle_rgba(&mut bgra_input);
+
+    let rgba_output = upscale(&rgba_input, ChannelOrder::Rgba);
+    let mut bgra_output = upscale(&bgra_input, ChannelOrder::Bgra);
+    ChannelOrder::Bgra.swizzle_rgba(&mut bgra_output);
+
+    assert_eq!(rgba_output.len(), bgra_output.len());
+    let max_diff = rgba_output.iter().zip(&bgra_output).map(|(a, b)| (a - b).abs()).fold(0.0, f32::max);
+    assert!(max_diff < 1e-5, "BGRA output differs from the RGBA baseline by {max_diff}");
+}
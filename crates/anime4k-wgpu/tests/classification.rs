@@ -0,0 +1,33 @@
+//! Tests for classifying pipelines and presets as upscaling, restoring, or both
+//!
+//! Both single predefined pipelines and chains of them are classified.
+
+use anime4k_wgpu::{
+    PipelineClass,
+    pipelines::{aux, cnn},
+    presets::{Anime4KPerformancePreset, Anime4KPreset},
+};
+
+/// Tests the classification of individual predefined pipelines
+#[test]
+fn test_pipeline_classification() {
+    assert_eq!(cnn::RESTORE_CNN_S.classification(), PipelineClass::Restore);
+    assert_eq!(aux::CLAMP_HIGHLIGHTS.classification(), PipelineClass::Restore);
+    assert_eq!(cnn::UPSCALE_CNN_X2_S.classification(), PipelineClass::Upscale);
+    assert_eq!(cnn::UPSCALE_DENOISE_CNN_X2_S.classification(), PipelineClass::Upscale);
+    assert_eq!(aux::UPSCALE_NEAREST_X4.classification(), PipelineClass::Upscale);
+}
+
+/// Tests that chains combine the classes of their pipelines
+#[test]
+fn test_chain_classification() {
+    assert_eq!(PipelineClass::of_chain(&[]), None);
+    assert_eq!(PipelineClass::of_chain(&[&cnn::UPSCALE_CNN_X2_S, &aux::UPSCALE_NEAREST_X2]), Some(PipelineClass::Upscale));
+    assert_eq!(PipelineClass::of_chain(&[&cnn::RESTORE_CNN_S, &cnn::UPSCALE_CNN_X2_S]), Some(PipelineClass::UpscaleAndRestore));
+
+    for preset in [Anime4KPreset::ModeA, Anime4KPreset::ModeBB, Anime4KPreset::ModeCA] {
+        let pipelines = preset.create_pipelines(Anime4KPerformancePreset::Light, 2.0);
+        let class = PipelineClass::of_chain(&pipelines).unwrap();
+        assert!(class.upscales() && class.restores(), "{preset:?} should upscale and restore, got {class:?}");
+    }
+}
@@ -0,0 +1,114 @@
+//! Helpers shared by the integration tests
+
+// Each test binary compiles this module but uses only some of the helpers
+#![allow(dead_code)]
+
+/// Creates a device suitable for running the pipelines, if the system has one
+///
+/// Returns `None` without a GPU adapter supporting `FLOAT32_FILTERABLE`. Tests needing a
+/// device get it through [`device_or_skip`], so on such systems they pass after printing
+/// that they were skipped instead of failing.
+pub fn create_device() -> Option<(wgpu::Device, wgpu::Queue)> {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::PRIMARY,
+        ..Default::default()
+    });
+    let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default())).ok()?;
+    pollster::block_on(anime4k_wgpu::PipelineExecutor::request_device(&adapter, wgpu::Features::empty())).ok()
+}
+
+/// Creates a device with [`create_device`], or returns from the test after printing why it is skipped
+///
+/// Takes a description of the skipped test as `format!` arguments and evaluates to the
+/// device and queue.
+macro_rules! device_or_skip {
+    ($($test:tt)*) => {
+        match $crate::common::create_device() {
+            Some(device_and_queue) => device_and_queue,
+            None => {
+                eprintln!("Skipping {}: no GPU adapter with FLOAT32_FILTERABLE", format_args!($($test)*));
+                return;
+            }
+        }
+    };
+}
+
+pub(crate) use device_or_skip;
+
+/// Creates an `Rgba32Float` source texture holding a gradient
+///
+/// Red increases to the right and green downwards, so a misplaced texel changes the
+/// output. Blue is 0.5 and alpha is 1.
+///
+/// # Arguments
+/// * `width` - Texture width in pixels
+/// * `height` - Texture height in pixels
+pub fn create_source_texture(device: &wgpu::Device, queue: &wgpu::Queue, width: u32, height: u32) -> wgpu::Texture {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba32Float,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    let texels: Vec<f32> = (0..width * height)
+        .flat_map(|index| [(index % width) as f32 / width as f32, (index / width) as f32 / height as f32, 0.5, 1.0])
+        .collect();
+    queue.write_texture(
+        texture.as_image_copy(),
+        &texels.iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<_>>(),
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(width * 16),
+            rows_per_image: Some(height),
+        },
+        texture.size(),
+    );
+    texture
+}
+
+/// Reads back an `Rgba32Float` texture with `COPY_SRC` usage
+///
+/// # Returns
+/// Row-major RGBA texels, with the row alignment padding of the copy stripped
+pub fn read_texture(device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) -> Vec<f32> {
+    let unpadded_bytes_per_row = texture.width() * 16;
+    let padded_bytes_per_row = unpadded_bytes_per_row.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: (padded_bytes_per_row * texture.height()) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: Some(texture.height()),
+            },
+        },
+        texture.size(),
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    buffer.slice(..).map_async(wgpu::MapMode::Read, |result| result.unwrap());
+    device.poll(wgpu::PollType::Wait).unwrap();
+    let mapped = buffer.slice(..).get_mapped_range();
+    mapped
+        .chunks_exact(padded_bytes_per_row as usize)
+        .flat_map(|row| row[..unpadded_bytes_per_row as usize].chunks_exact(4))
+        .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+        .collect()
+}
@@ -0,0 +1,155 @@
+//! Golden-image regression tests for every preset
+//!
+//! Each preset upscales a small procedurally generated input through the real
+//! `PipelineExecutor`, and the result is compared against a checked-in 16-bit PNG in
+//! `tests/golden` by PSNR. The threshold tolerates the float differences between GPUs
+//! and drivers while still catching changes to the pipelines or presets.
+//!
+//! Run with `ANIME4K_UPDATE_GOLDEN=1` to (re)generate the golden images after an
+//! intended change.
+//!
+//! The golden images come from the default CNN pipelines. The `fused-cnn` and
+//! `mirror-edges` features embed different ones, so the tests are compiled out with them.
+
+#![cfg(not(any(feature = "fused-cnn", feature = "mirror-edges")))]
+
+use anime4k_wgpu::{
+    ChannelOrder,
+    presets::{Anime4KPerformancePreset, Anime4KPreset},
+    upscale_raw_f32,
+};
+use std::path::PathBuf;
+
+mod common;
+
+/// Width and height of the input image in pixels
+const INPUT_SIZE: u32 = 32;
+
+/// Minimum PSNR in dB between an output and its golden image
+const PSNR_THRESHOLD: f64 = 40.0;
+
+/// Environment variable that makes the tests write golden images instead of comparing
+const UPDATE_ENV: &str = "ANIME4K_UPDATE_GOLDEN";
+
+/// Generates the input image: diagonal line art over a gradient, with a soft circle
+///
+/// # Returns
+/// Row-major RGBA texels of an `INPUT_SIZE` x `INPUT_SIZE` image
+fn input_image() -> Vec<f32> {
+    let size = INPUT_SIZE as f32;
+    (0..INPUT_SIZE * INPUT_SIZE)
+        .flat_map(|index| {
+            let (x, y) = ((index % INPUT_SIZE) as f32 + 0.5, (index / INPUT_SIZE) as f32 + 0.5);
+            let background = [0.2 + 0.6 * x / size, 0.3 + 0.4 * y / size, 0.8 - 0.5 * x / size];
+
+            // Dark outline along the diagonal, like anime line art
+            let line = ((x - y).abs() - 1.0).clamp(0.0, 1.0);
+            // Flat-shaded circle with a one-pixel anti-aliased edge
+            let distance = ((x - size * 0.65).powi(2) + (y - size * 0.3).powi(2)).sqrt();
+            let circle = (size * 0.2 - distance + 0.5).clamp(0.0, 1.0);
+
+            let [r, g, b] = background.map(|value| value * line);
+            [r + (0.95 - r) * circle, g + (0.85 - g) * circle, b + (0.4 - b) * circle, 1.0]
+        })
+        .collect()
+}
+
+/// Computes the PSNR between two images with values in [0, 1]
+///
+/// # Returns
+/// The PSNR in dB, or infinity if the images are identical
+fn psnr(a: &[f32], b: &[f32]) -> f64 {
+    let mse = a.iter().zip(b).map(|(a, b)| (*a as f64 - *b as f64).powi(2)).sum::<f64>() / a.len() as f64;
+    -10.0 * mse.log10()
+}
+
+/// Runs a preset on the input image and checks the result against its golden image
+///
+/// # Arguments
+/// * `preset` - Preset to test
+/// * `file_name` - File name of the golden image within `tests/golden`
+fn check_golden(preset: Anime4KPreset, file_name: &str) {
+    let (device, queue) = common::device_or_skip!("golden test for {}", preset.name());
+
+    let output = upscale_raw_f32(
+        &device,
+        &queue,
+        &input_image(),
+        ChannelOrder::Rgba,
+        INPUT_SIZE,
+        INPUT_SIZE,
+        preset,
+        Anime4KPerformancePreset::Light,
+        2.0,
+    )
+    .unwrap();
+    let output: Vec<f32> = output.iter().map(|value| value.clamp(0.0, 1.0)).collect();
+    let output_size = INPUT_SIZE * 2;
+    assert_eq!(output.len(), (output_size * output_size * 4) as usize);
+
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(file_name);
+    let output_image = image::Rgba32FImage::from_raw(output_size, output_size, output).unwrap();
+    if std::env::var_os(UPDATE_ENV).is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        image::DynamicImage::ImageRgba32F(output_image).into_rgba16().save(&path).unwrap();
+        return;
+    }
+
+    let golden = image::open(&path)
+        .unwrap_or_else(|e| panic!("Failed to load golden image {}: {e} (run with {UPDATE_ENV}=1 to generate it)", path.display()))
+        .into_rgba32f();
+    assert_eq!(golden.dimensions(), output_image.dimensions(), "Golden image {} has a different size", path.display());
+
+    // Compare after the same 16-bit quantization the golden image went through
+    let output_image = image::DynamicImage::ImageRgba32F(output_image).into_rgba16();
+    let output_image = image::DynamicImage::ImageRgba16(output_image).into_rgba32f();
+    let psnr = psnr(output_image.as_raw(), golden.as_raw());
+    assert!(
+        psnr >= PSNR_THRESHOLD,
+        "{} output differs from {}: PSNR {psnr:.2} dB < {PSNR_THRESHOLD} dB",
+        preset.name(),
+        path.display()
+    );
+}
+
+/// Tests Mode A against its golden image
+#[test]
+fn golden_mode_a() {
+    check_golden(Anime4KPreset::ModeA, "mode_a.png");
+}
+
+/// Tests Mode B against its golden image
+#[test]
+fn golden_mode_b() {
+    check_golden(Anime4KPreset::ModeB, "mode_b.png");
+}
+
+/// Tests Mode C against its golden image
+#[test]
+fn golden_mode_c() {
+    check_golden(Anime4KPreset::ModeC, "mode_c.png");
+}
+
+/// Tests Mode AA against its golden image
+#[test]
+fn golden_mode_aa() {
+    check_golden(Anime4KPreset::ModeAA, "mode_aa.png");
+}
+
+/// Tests Mode BB against its golden image
+#[test]
+fn golden_mode_bb() {
+    check_golden(Anime4KPreset::ModeBB, "mode_bb.png");
+}
+
+/// Tests Mode CA against its golden image
+#[test]
+fn golden_mode_ca() {
+    check_golden(Anime4KPreset::ModeCA, "mode_ca.png");
+}
+
+/// Tests the nearest-neighbor preset against its golden image
+#[test]
+fn golden_nearest() {
+    check_golden(Anime4KPreset::Nearest, "nearest.png");
+}
@@ -0,0 +1,95 @@
+//! Tests for parsing `.cube` LUTs and grading the output with them
+//!
+//! The parsing tests need no GPU.
+
+use anime4k_wgpu::{
+    CubeLut, PipelineExecutor,
+    presets::{Anime4KPerformancePreset, Anime4KPreset},
+};
+
+mod common;
+
+/// Largest difference allowed between statistics of trilinearly sampled results
+const TOLERANCE: f32 = 1e-3;
+
+/// A 2x2x2 LUT inverting every channel
+const INVERT_CUBE: &str = r#"# Inverts every channel
+TITLE "Invert"
+LUT_3D_SIZE 2
+
+1.0 1.0 1.0
+0.0 1.0 1.0
+1.0 0.0 1.0
+0.0 0.0 1.0
+1.0 1.0 0.0
+0.0 1.0 0.0
+1.0 0.0 0.0
+0.0 0.0 0.0
+"#;
+
+/// Tests that a well-formed LUT is parsed with red varying fastest
+#[test]
+fn test_parse_cube() {
+    let lut: CubeLut = INVERT_CUBE.parse().unwrap();
+    assert_eq!(lut.title.as_deref(), Some("Invert"));
+    assert_eq!(lut.size, 2);
+    assert_eq!(lut.domain_min, [0.0; 3]);
+    assert_eq!(lut.domain_max, [1.0; 3]);
+    assert_eq!(lut.table.len(), 8);
+    assert_eq!(lut.table[1], [0.0, 1.0, 1.0]);
+    assert_eq!(lut.table[4], [1.0, 1.0, 0.0]);
+
+    let identity = CubeLut::identity(3);
+    assert_eq!(identity.table[1], [0.5, 0.0, 0.0]);
+    assert_eq!(identity.table[3], [0.0, 0.5, 0.0]);
+    assert_eq!(identity.table[26], [1.0, 1.0, 1.0]);
+}
+
+/// Tests that malformed LUTs are rejected
+#[test]
+fn test_parse_cube_rejects_invalid() {
+    assert!("LUT_3D_SIZE 2\n0 0 0\n".parse::<CubeLut>().unwrap_err().contains("Expected 8 entries"));
+    assert!("LUT_3D_SIZE 1\n0 0 0\n".parse::<CubeLut>().is_err());
+    assert!("LUT_3D_SIZE 300\n".parse::<CubeLut>().is_err());
+    assert!("LUT_1D_SIZE 16\n".parse::<CubeLut>().unwrap_err().contains("1D"));
+    assert!("0 0 0\nLUT_3D_SIZE 2\n".parse::<CubeLut>().unwrap_err().contains("before LUT_3D_SIZE"));
+    assert!("".parse::<CubeLut>().unwrap_err().contains("Missing LUT_3D_SIZE"));
+    assert!(INVERT_CUBE.replace("0.0 0.0 0.0", "0.0 0.0").parse::<CubeLut>().is_err());
+    assert!(format!("DOMAIN_MIN 0 0 1\nDOMAIN_MAX 1 1 1\n{INVERT_CUBE}").parse::<CubeLut>().unwrap_err().contains("DOMAIN_MAX"));
+}
+
+/// Tests that the identity LUT keeps the output and the inverting LUT inverts it
+#[test]
+fn test_lut_grades_output() {
+    let (device, queue) = common::device_or_skip!("LUT test");
+
+    let source_texture = common::create_source_texture(&device, &queue, 16, 16);
+    let pipelines = Anime4KPreset::Nearest.create_pipelines(Anime4KPerformancePreset::Light, 2.0);
+    let (baseline, baseline_output) = PipelineExecutor::new(&pipelines, &device, &source_texture);
+    let (identity, identity_output) = PipelineExecutor::new(&pipelines, &device, &source_texture).0.with_lut(&device, &queue, &CubeLut::identity(17));
+    let (inverted, _) = PipelineExecutor::new(&pipelines, &device, &source_texture).0.with_lut(&device, &queue, &INVERT_CUBE.parse().unwrap());
+    assert_eq!(identity_output.size(), baseline_output.size());
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    baseline.pass(&mut encoder);
+    identity.pass(&mut encoder);
+    inverted.pass(&mut encoder);
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let baseline_stats = baseline.output_stats(&device, &queue).unwrap();
+    let identity_stats = identity.output_stats(&device, &queue).unwrap();
+    let inverted_stats = inverted.output_stats(&device, &queue).unwrap();
+    for channel in 0..3 {
+        assert!(
+            (identity_stats.mean[channel] - baseline_stats.mean[channel]).abs() < TOLERANCE,
+            "identity LUT changed channel {channel}"
+        );
+        assert!(
+            (inverted_stats.mean[channel] - (1.0 - baseline_stats.mean[channel])).abs() < TOLERANCE,
+            "inverting LUT did not invert channel {channel}"
+        );
+        assert!((inverted_stats.min[channel] - (1.0 - baseline_stats.max[channel])).abs() < TOLERANCE);
+    }
+    // Alpha is passed through
+    assert_eq!(inverted_stats.mean[3], baseline_stats.mean[3]);
+}
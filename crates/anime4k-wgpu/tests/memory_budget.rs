@@ -0,0 +1,69 @@
+//! Tests for texture memory estimation and budget-aware pipeline creation
+//!
+//! The estimates are checked against the input size and preset, and budgets that fit,
+//! force a downgrade, or cannot be met at all are applied to preset creation.
+
+use anime4k_wgpu::{
+    PipelineExecutor,
+    presets::{Anime4KPerformancePreset, Anime4KPreset},
+};
+
+/// Tests that the estimate grows with the input size and the performance preset
+#[test]
+fn test_estimate_memory() {
+    let light = Anime4KPreset::ModeA.create_pipelines(Anime4KPerformancePreset::Light, 2.0);
+    let ultra = Anime4KPreset::ModeA.create_pipelines(Anime4KPerformancePreset::Ultra, 2.0);
+
+    let small = PipelineExecutor::estimate_memory(&light, 64, 64);
+    let large = PipelineExecutor::estimate_memory(&light, 128, 128);
+    assert!(small > 0);
+    assert_eq!(large, small * 4, "texture memory should scale with the pixel count");
+    assert!(PipelineExecutor::estimate_memory(&ultra, 64, 64) > small);
+
+    // The 2x output alone takes 16 bytes per texel
+    assert!(small >= 128 * 128 * 16);
+}
+
+/// Tests that no budget, or a budget that fits, keeps the requested configuration
+#[test]
+fn test_within_budget_keeps_configuration() {
+    let unlimited = Anime4KPreset::ModeA.create_pipelines_within_budget(Anime4KPerformancePreset::Ultra, 4.0, 64, 64, None).unwrap();
+    assert_eq!(unlimited.performance_preset, Anime4KPerformancePreset::Ultra);
+    assert_eq!(unlimited.target_scale_factor, 4.0);
+    assert!(unlimited.downgrades.is_empty());
+
+    let fitting = Anime4KPreset::ModeA
+        .create_pipelines_within_budget(Anime4KPerformancePreset::Ultra, 4.0, 64, 64, Some(unlimited.estimated_memory))
+        .unwrap();
+    assert_eq!(fitting.performance_preset, Anime4KPerformancePreset::Ultra);
+    assert!(fitting.downgrades.is_empty());
+}
+
+/// Tests that the performance preset is lowered before the scale factor
+#[test]
+fn test_downgrade_ladder() {
+    let light_4x = Anime4KPreset::ModeA.create_pipelines(Anime4KPerformancePreset::Light, 4.0);
+    let budget = PipelineExecutor::estimate_memory(&light_4x, 64, 64);
+    let budgeted = Anime4KPreset::ModeA.create_pipelines_within_budget(Anime4KPerformancePreset::Ultra, 4.0, 64, 64, Some(budget)).unwrap();
+    assert_eq!(budgeted.target_scale_factor, 4.0);
+    assert!(!budgeted.downgrades.is_empty());
+    assert!(budgeted.downgrades.iter().all(|downgrade| downgrade.contains("lowering performance")), "{:?}", budgeted.downgrades);
+    assert!(budgeted.estimated_memory <= budget);
+
+    // One byte less no longer fits at 4x, so the scale factor is halved as well
+    let budgeted = Anime4KPreset::ModeA
+        .create_pipelines_within_budget(Anime4KPerformancePreset::Ultra, 4.0, 64, 64, Some(budget - 1))
+        .unwrap();
+    assert_eq!(budgeted.performance_preset, Anime4KPerformancePreset::Light);
+    assert_eq!(budgeted.target_scale_factor, 2.0);
+    assert!(budgeted.downgrades.last().unwrap().contains("4x to 2x"), "{:?}", budgeted.downgrades);
+}
+
+/// Tests that a budget too small for 2x at Light is an error rather than an allocation failure
+#[test]
+fn test_budget_too_small() {
+    let error = Anime4KPreset::ModeA
+        .create_pipelines_within_budget(Anime4KPerformancePreset::High, 2.0, 64, 64, Some(1024))
+        .unwrap_err();
+    assert!(error.to_string().contains("lightest settings"), "Unexpected error: {error}");
+}
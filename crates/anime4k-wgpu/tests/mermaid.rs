@@ -0,0 +1,45 @@
+//! Tests for rendering pipelines as Mermaid flowcharts with `ExecutablePipeline::to_mermaid`
+//!
+//! The flowcharts are checked for their nodes, edges, and texture scale labels.
+
+use std::collections::HashSet;
+
+use anime4k_wgpu::pipelines::{aux, cnn};
+
+/// Tests that every pass gets a node and every edge connects declared nodes
+#[test]
+fn test_to_mermaid_structure() {
+    for pipeline in [&cnn::UPSCALE_CNN_X2_M, &cnn::RESTORE_CNN_S, &aux::UPSCALE_NEAREST_X4] {
+        let mermaid = pipeline.to_mermaid();
+        let mut lines = mermaid.lines();
+        assert_eq!(lines.next(), Some("flowchart TD"), "{}", pipeline.name());
+
+        let mut nodes = HashSet::new();
+        let mut edges = 0;
+        for line in lines.map(str::trim) {
+            if let Some((from, to)) = line.split_once(" --> ") {
+                assert!(nodes.contains(from) && nodes.contains(to), "{}: edge to an undeclared node: {line}", pipeline.name());
+                edges += 1;
+            } else if let Some(index) = line.find(['(', '[']) {
+                assert!(nodes.insert(line[..index].to_string()), "{}: node declared twice: {line}", pipeline.name());
+            }
+        }
+
+        for index in 0..pipeline.passes().len() {
+            assert!(nodes.contains(&format!("p{index}")), "{}: pass {index} has no node", pipeline.name());
+        }
+        let bindings: usize = pipeline.passes().iter().map(|pass| pass.input_textures.len() + pass.output_textures.len()).sum();
+        assert_eq!(edges, bindings, "{}", pipeline.name());
+        assert!(mermaid.contains(" source\n"), "{}: source texture is not highlighted", pipeline.name());
+        assert!(mermaid.contains(" result\n"), "{}: result texture is not highlighted", pipeline.name());
+    }
+}
+
+/// Tests that texture labels carry the scale factor relative to the input
+#[test]
+fn test_to_mermaid_scale_labels() {
+    let mermaid = cnn::UPSCALE_CNN_X2_M.to_mermaid();
+    assert!(mermaid.contains("ch, 1x\"]"));
+    assert!(mermaid.contains("ch, 2x\"]"));
+    assert!(aux::UPSCALE_NEAREST_X4.to_mermaid().contains("ch, 4x\"]"));
+}
@@ -0,0 +1,70 @@
+//! Tests for the non-finite value guard of debug executors
+//!
+//! A finite input must raise no flags, and a NaN in the input must be flagged and kept out of the output.
+
+use anime4k_wgpu::{
+    PipelineExecutor,
+    presets::{Anime4KPerformancePreset, Anime4KPreset},
+};
+
+mod common;
+
+/// Width and height of the input image in pixels
+const INPUT_SIZE: u32 = 16;
+
+/// Runs ModeA through a debug executor once
+///
+/// # Returns
+/// The executor, after its commands have been submitted
+fn run_debug(device: &wgpu::Device, queue: &wgpu::Queue, source_texture: &wgpu::Texture) -> PipelineExecutor {
+    let pipelines = Anime4KPreset::ModeA.create_pipelines(Anime4KPerformancePreset::Light, 2.0);
+    let (executor, _) = PipelineExecutor::new_debug(&pipelines, device, source_texture);
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    executor.pass(&mut encoder);
+    queue.submit(std::iter::once(encoder.finish()));
+    executor
+}
+
+/// Tests that a finite input raises no flags
+#[test]
+fn test_clean_input_has_no_nan() {
+    let (device, queue) = common::device_or_skip!("NaN guard test");
+
+    let source_texture = common::create_source_texture(&device, &queue, INPUT_SIZE, INPUT_SIZE);
+    let executor = run_debug(&device, &queue, &source_texture);
+
+    assert!(!executor.had_nan(&device, &queue).unwrap());
+    assert_eq!(executor.non_finite_passes(&device, &queue).unwrap(), Vec::<String>::new());
+}
+
+/// Tests that a NaN texel in the input is flagged and replaced before reaching the output
+#[test]
+fn test_nan_input_is_flagged() {
+    let (device, queue) = common::device_or_skip!("NaN guard test");
+
+    let source_texture = common::create_source_texture(&device, &queue, INPUT_SIZE, INPUT_SIZE);
+    queue.write_texture(
+        wgpu::TexelCopyTextureInfo {
+            origin: wgpu::Origin3d { x: 5, y: 7, z: 0 },
+            ..source_texture.as_image_copy()
+        },
+        &[f32::NAN; 4].map(f32::to_le_bytes).concat(),
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: None,
+            rows_per_image: None,
+        },
+        wgpu::Extent3d {
+            width: 1,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+    );
+    let executor = run_debug(&device, &queue, &source_texture);
+
+    assert!(executor.had_nan(&device, &queue).unwrap());
+    let passes = executor.non_finite_passes(&device, &queue).unwrap();
+    assert!(!passes.is_empty() && passes.iter().all(|pass| pass.contains('/')), "{passes:?}");
+    assert_eq!(executor.output_stats(&device, &queue).unwrap().non_finite_count, [0; 4]);
+}
@@ -0,0 +1,71 @@
+//! Tests for the per-channel statistics of the pipeline output
+
+use anime4k_wgpu::{PipelineExecutor, pipelines::aux};
+
+mod common;
+
+/// Width of the output in pixels, wider than a reduction workgroup
+const WIDTH: u32 = 70;
+/// Height of the output in pixels, so several workgroups produce partial results
+const HEIGHT: u32 = 3;
+
+/// Tests that the statistics of known output contents are exact
+///
+/// The output goes into a caller-provided texture that is filled directly instead of
+/// running the pipeline, so the expected values are known exactly.
+#[test]
+fn test_known_output_stats() {
+    let (device, queue) = common::device_or_skip!("output stats test");
+
+    let source_texture = common::create_source_texture(&device, &queue, WIDTH, HEIGHT);
+    let output_texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width: WIDTH,
+            height: HEIGHT,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba32Float,
+        usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    let executor = PipelineExecutor::new_with_output(&[&aux::EFFECTS_DARKEN_FAST], &device, &source_texture, &output_texture).unwrap();
+
+    // Red is the column and green the negated row; blue is 0.25 apart from a NaN and
+    // two infinities on the diagonal, and alpha is NaN everywhere
+    let texels: Vec<f32> = (0..WIDTH * HEIGHT)
+        .flat_map(|index| {
+            let (x, y) = (index % WIDTH, index / WIDTH);
+            let blue = match (x, y) {
+                (0, 0) => f32::NAN,
+                (1, 1) => f32::INFINITY,
+                (2, 2) => f32::NEG_INFINITY,
+                _ => 0.25,
+            };
+            [x as f32, -(y as f32), blue, f32::NAN]
+        })
+        .collect();
+    queue.write_texture(
+        output_texture.as_image_copy(),
+        &texels.iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<_>>(),
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(WIDTH * 16),
+            rows_per_image: Some(HEIGHT),
+        },
+        output_texture.size(),
+    );
+
+    let stats = executor.output_stats(&device, &queue).unwrap();
+    assert_eq!(stats.min[..3], [0.0, -2.0, 0.25]);
+    assert_eq!(stats.max[..3], [69.0, 0.0, 0.25]);
+    assert_eq!(stats.mean[..3], [34.5, -1.0, 0.25]);
+    assert_eq!(stats.non_finite_count, [0, 0, 3, WIDTH * HEIGHT]);
+    // A channel without any finite value has no meaningful min, max, or mean
+    assert!(stats.min[3].is_nan() && stats.max[3].is_nan() && stats.mean[3].is_nan(), "{stats:?}");
+    assert!(stats.has_non_finite());
+    assert!(!stats.is_all_black());
+}
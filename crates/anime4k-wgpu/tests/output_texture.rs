@@ -0,0 +1,89 @@
+//! Tests for writing the pipeline output into a caller-provided texture
+//!
+//! The output must match that of the executor's own texture, and textures of the wrong
+//! size, format, or usage must be rejected.
+
+use anime4k_wgpu::{
+    PipelineExecutor,
+    presets::{Anime4KPerformancePreset, Anime4KPreset},
+};
+
+mod common;
+
+/// Width and height of the input image in pixels
+const INPUT_SIZE: u32 = 16;
+
+/// Creates an `Rgba32Float` texture with the given size and usages
+fn create_texture(device: &wgpu::Device, size: u32, usage: wgpu::TextureUsages) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba32Float,
+        usage,
+        view_formats: &[],
+    })
+}
+
+/// Tests that writing into a caller texture gives the same output as the executor's own texture
+#[test]
+fn test_output_matches_internal_texture() {
+    let (device, queue) = common::device_or_skip!("output texture test");
+
+    let source_texture = common::create_source_texture(&device, &queue, INPUT_SIZE, INPUT_SIZE);
+    let pipelines = Anime4KPreset::ModeA.create_pipelines(Anime4KPerformancePreset::Light, 2.0);
+    let output_texture = create_texture(&device, INPUT_SIZE * 2, wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING);
+
+    let (baseline, _) = PipelineExecutor::new(&pipelines, &device, &source_texture);
+    let executor = PipelineExecutor::new_with_output(&pipelines, &device, &source_texture, &output_texture).unwrap();
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    baseline.pass(&mut encoder);
+    executor.pass(&mut encoder);
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let baseline_stats = baseline.output_stats(&device, &queue).unwrap();
+    assert_eq!(executor.output_stats(&device, &queue).unwrap(), baseline_stats);
+    assert!(baseline_stats.max[0] > 0.0, "Output is unexpectedly black");
+}
+
+/// Tests that output textures of the wrong size, format, or usage are rejected
+#[test]
+fn test_invalid_output_texture() {
+    let (device, queue) = common::device_or_skip!("output texture test");
+
+    let source_texture = common::create_source_texture(&device, &queue, INPUT_SIZE, INPUT_SIZE);
+    let pipelines = Anime4KPreset::ModeA.create_pipelines(Anime4KPerformancePreset::Light, 2.0);
+    let usage = wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING;
+
+    let wrong_size = create_texture(&device, INPUT_SIZE, usage);
+    let error = PipelineExecutor::new_with_output(&pipelines, &device, &source_texture, &wrong_size).unwrap_err();
+    assert!(error.to_string().contains("16x16"), "Unexpected error: {error}");
+
+    let wrong_format = device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width: INPUT_SIZE * 2,
+            height: INPUT_SIZE * 2,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba16Float,
+        usage,
+        view_formats: &[],
+    });
+    let error = PipelineExecutor::new_with_output(&pipelines, &device, &source_texture, &wrong_format).unwrap_err();
+    assert!(error.to_string().contains("Rgba16Float"), "Unexpected error: {error}");
+
+    let wrong_usage = create_texture(&device, INPUT_SIZE * 2, wgpu::TextureUsages::TEXTURE_BINDING);
+    let error = PipelineExecutor::new_with_output(&pipelines, &device, &source_texture, &wrong_usage).unwrap_err();
+    assert!(error.to_string().contains("STORAGE_BINDING"), "Unexpected error: {error}");
+}
@@ -0,0 +1,95 @@
+//! Tests for padding the source so every pass uses its unchecked entry point
+//!
+//! The dimension tests only evaluate the pipeline structure, and the execution tests
+//! compare the padded output against that of `new`.
+
+use anime4k_wgpu::{
+    PipelineExecutor,
+    pipelines::cnn,
+    presets::{Anime4KPerformancePreset, Anime4KPreset},
+};
+
+mod common;
+
+/// Tests that sources are padded up to the next multiple of the workgroup size
+#[test]
+fn test_padded_dimensions() {
+    let pipelines = [&cnn::UPSCALE_CNN_X2_M, &cnn::RESTORE_CNN_S];
+    assert_eq!(PipelineExecutor::padded_dimensions(&pipelines, 1920, 1080), (1920, 1080));
+    assert_eq!(PipelineExecutor::padded_dimensions(&pipelines, 1918, 1077), (1920, 1080));
+    assert_eq!(PipelineExecutor::padded_dimensions(&pipelines, 1, 9), (8, 16));
+    assert_eq!(PipelineExecutor::padded_dimensions(&[], 13, 7), (13, 7));
+}
+
+/// Tests that padding does not change the output of a pipeline without neighborhood sampling
+#[test]
+fn test_padded_nearest_matches_unpadded() {
+    let (device, queue) = common::device_or_skip!("padding test");
+
+    let source_texture = common::create_source_texture(&device, &queue, 13, 7);
+    let pipelines = Anime4KPreset::Nearest.create_pipelines(Anime4KPerformancePreset::Light, 2.0);
+    let (baseline, baseline_output) = PipelineExecutor::new(&pipelines, &device, &source_texture);
+    let (executor, output) = PipelineExecutor::new_padded(&pipelines, &device, &source_texture);
+    assert_eq!(output.size(), baseline_output.size());
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    baseline.pass(&mut encoder);
+    executor.pass(&mut encoder);
+    queue.submit(std::iter::once(encoder.finish()));
+
+    assert_eq!(executor.output_stats(&device, &queue).unwrap(), baseline.output_stats(&device, &queue).unwrap());
+}
+
+/// Tests that a padded CNN pipeline produces an output of the unpadded size
+#[test]
+fn test_padded_cnn_output_size() {
+    let (device, queue) = common::device_or_skip!("padding test");
+
+    let source_texture = common::create_source_texture(&device, &queue, 12, 12);
+    let pipelines = Anime4KPreset::ModeA.create_pipelines(Anime4KPerformancePreset::Light, 2.0);
+    let (executor, output) = PipelineExecutor::new_padded(&pipelines, &device, &source_texture);
+    assert_eq!((output.width(), output.height()), PipelineExecutor::output_dimensions(&pipelines, 12, 12));
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    executor.pass(&mut encoder);
+    queue.submit(std::iter::once(encoder.finish()));
+
+    assert!(!executor.output_stats(&device, &queue).unwrap().has_non_finite());
+}
+
+/// Tests that padding a CNN pipeline changes its output only near the right and bottom edges
+///
+/// Texels at least `EDGE_BAND` output texels away from those edges see the same inputs
+/// either way and must match to within float rounding (`INTERIOR_TOLERANCE`). Closer to
+/// the edges, the CNNs read computed values from the padding instead of clamped ones;
+/// the largest difference measured there was about 0.0064, checked against `EDGE_TOLERANCE`.
+#[test]
+fn test_padded_mode_a_matches_unpadded() {
+    const EDGE_BAND: u32 = 16;
+    const INTERIOR_TOLERANCE: f32 = 1e-5;
+    const EDGE_TOLERANCE: f32 = 1.0 / 64.0;
+
+    let (device, queue) = common::device_or_skip!("padding test");
+
+    // Padded to 64x40, so both axes get padding
+    let source_texture = common::create_source_texture(&device, &queue, 61, 37);
+    let pipelines = Anime4KPreset::ModeA.create_pipelines(Anime4KPerformancePreset::Light, 2.0);
+    let (baseline, baseline_output) = PipelineExecutor::new(&pipelines, &device, &source_texture);
+    let (executor, output) = PipelineExecutor::new_padded(&pipelines, &device, &source_texture);
+    assert_eq!(output.size(), baseline_output.size());
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    baseline.pass(&mut encoder);
+    executor.pass(&mut encoder);
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let expected = common::read_texture(&device, &queue, &baseline_output);
+    let actual = common::read_texture(&device, &queue, &output);
+    let (width, height) = (output.width(), output.height());
+    for (index, (actual, expected)) in actual.chunks_exact(4).zip(expected.chunks_exact(4)).enumerate() {
+        let (x, y) = (index as u32 % width, index as u32 / width);
+        let tolerance = if x + EDGE_BAND < width && y + EDGE_BAND < height { INTERIOR_TOLERANCE } else { EDGE_TOLERANCE };
+        let difference = actual.iter().zip(expected).map(|(a, b)| (a - b).abs()).fold(0.0, f32::max);
+        assert!(difference <= tolerance, "Output texel ({x}, {y}) differs by {difference}, more than {tolerance}");
+    }
+}
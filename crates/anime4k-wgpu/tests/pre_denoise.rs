@@ -0,0 +1,84 @@
+//! Tests for the pre-denoise filter
+//!
+//! Parsing and radius selection are checked without a GPU, and the denoised texture must be
+//! reused by the later pipelines.
+//! The test counts textures through the `counters` feature of wgpu.
+
+use anime4k_wgpu::{
+    PipelineExecutor, PreDenoise,
+    presets::{Anime4KPerformancePreset, Anime4KPreset},
+};
+
+mod common;
+
+/// Tests parsing the filter forms accepted on the command line
+#[test]
+fn test_parse_pre_denoise() {
+    assert_eq!("gaussian:1.5".parse(), Ok(PreDenoise::Gaussian { sigma: 1.5 }));
+    assert_eq!("Gaussian:2".parse(), Ok(PreDenoise::Gaussian { sigma: 2.0 }));
+    assert_eq!(
+        "bilateral:1.0".parse(),
+        Ok(PreDenoise::Bilateral {
+            sigma: 1.0,
+            range_sigma: PreDenoise::DEFAULT_RANGE_SIGMA,
+        })
+    );
+    assert_eq!("BILATERAL:0.8, 0.2".parse(), Ok(PreDenoise::Bilateral { sigma: 0.8, range_sigma: 0.2 }));
+}
+
+/// Tests that malformed filters and non-positive sigmas are rejected
+#[test]
+fn test_parse_pre_denoise_invalid() {
+    for input in [
+        "",
+        "gaussian",
+        "gaussian:",
+        "gaussian:0",
+        "gaussian:-1",
+        "gaussian:nan",
+        "gaussian:inf",
+        "gaussian:1,0.1",
+        "bilateral:1,0",
+        "bilateral:1,0.1,0.1",
+        "median:1",
+    ] {
+        assert!(input.parse::<PreDenoise>().is_err(), "{input:?} should not parse");
+    }
+}
+
+/// Tests that the radius covers three standard deviations within the allowed range
+#[test]
+fn test_radius() {
+    assert_eq!(PreDenoise::Gaussian { sigma: 0.01 }.radius(), 1);
+    assert_eq!(PreDenoise::Gaussian { sigma: 0.5 }.radius(), 2);
+    assert_eq!(PreDenoise::Gaussian { sigma: 1.0 }.radius(), 3);
+    assert_eq!(PreDenoise::Bilateral { sigma: 1.0, range_sigma: 0.5 }.radius(), 3);
+    assert_eq!(PreDenoise::Gaussian { sigma: 2.5 }.radius(), PreDenoise::MAX_RADIUS);
+    assert_eq!(PreDenoise::Gaussian { sigma: 100.0 }.radius(), PreDenoise::MAX_RADIUS);
+}
+
+/// Tests that the pipelines after the first reuse the denoised texture instead of
+/// allocating one more texture than `new`
+#[test]
+fn test_pre_denoise_shares_texture() {
+    let (device, queue) = common::device_or_skip!("pre-denoise texture sharing test");
+    // Counts the textures the device has created and not yet destroyed
+    let texture_count = || device.get_internal_counters().hal.textures.read();
+
+    let source_texture = common::create_source_texture(&device, &queue, 24, 16);
+    let pipelines = Anime4KPreset::ModeA.create_pipelines(Anime4KPerformancePreset::Light, 2.0);
+    let before = texture_count();
+
+    let (executor, _) = PipelineExecutor::new(&pipelines, &device, &source_texture);
+    let plain = texture_count() - before;
+    drop(executor);
+    device.poll(wgpu::PollType::Wait).unwrap();
+
+    let (executor, _) = PipelineExecutor::new_pre_denoised(&pipelines, &device, &source_texture, PreDenoise::Gaussian { sigma: 1.0 });
+    assert_eq!(texture_count() - before, plain);
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    executor.pass(&mut encoder);
+    queue.submit(std::iter::once(encoder.finish()));
+    assert_eq!(executor.output_stats(&device, &queue).unwrap().non_finite_count, [0; 4]);
+}
@@ -0,0 +1,55 @@
+//! Tests for switching between a preview and a final chain sharing one output
+//!
+//! Each quality must write its own chain's output, and chains of different output sizes must be rejected.
+
+use anime4k_wgpu::{
+    Anime4kExecutor, PipelineExecutor, PreviewExecutor, Quality,
+    presets::{Anime4KPerformancePreset, Anime4KPreset},
+};
+
+mod common;
+
+/// Tests that each quality writes the output of its own chain into the shared texture
+#[test]
+fn test_quality_selects_chain() {
+    let (device, queue) = common::device_or_skip!("preview test");
+
+    let source_texture = common::create_source_texture(&device, &queue, 16, 16);
+    let preview_pipelines = Anime4KPreset::ModeA.create_pipelines(Anime4KPerformancePreset::Light, 2.0);
+    let final_pipelines = Anime4KPreset::ModeA.create_pipelines(Anime4KPerformancePreset::Ultra, 2.0);
+    let (mut executor, output_texture) = PreviewExecutor::new(&preview_pipelines, &final_pipelines, &device, &source_texture).unwrap();
+    assert_eq!(executor.quality(), Quality::Final);
+    assert_eq!(executor.output_texture(), &output_texture);
+    assert_eq!(executor.source_texture(), &source_texture);
+
+    let run = |executor: &PreviewExecutor| {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        executor.pass(&mut encoder);
+        queue.submit(std::iter::once(encoder.finish()));
+        executor.active().output_stats(&device, &queue).unwrap()
+    };
+    let run_alone = |pipelines: &[_]| {
+        let (executor, _) = PipelineExecutor::new(pipelines, &device, &source_texture);
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        executor.pass(&mut encoder);
+        queue.submit(std::iter::once(encoder.finish()));
+        executor.output_stats(&device, &queue).unwrap()
+    };
+
+    executor.set_quality(Quality::Preview);
+    assert_eq!(run(&executor), run_alone(&preview_pipelines[..]));
+    executor.set_quality(Quality::Final);
+    assert_eq!(run(&executor), run_alone(&final_pipelines[..]));
+}
+
+/// Tests that chains with different output sizes are rejected
+#[test]
+fn test_mismatched_chains_are_rejected() {
+    let (device, queue) = common::device_or_skip!("preview test");
+
+    let source_texture = common::create_source_texture(&device, &queue, 16, 16);
+    let preview_pipelines = Anime4KPreset::ModeA.create_pipelines(Anime4KPerformancePreset::Light, 2.0);
+    let final_pipelines = Anime4KPreset::ModeA.create_pipelines(Anime4KPerformancePreset::Light, 4.0);
+    let error = PreviewExecutor::new(&preview_pipelines, &final_pipelines, &device, &source_texture).unwrap_err();
+    assert!(error.to_string().contains("Preview pipelines"), "{error}");
+}
@@ -0,0 +1,51 @@
+//! Tests for reusing an executor across frames
+//!
+//! Processing many frames with one executor must allocate nothing after the first frame.
+//! Resources are counted through the `counters` feature of wgpu.
+
+use anime4k_wgpu::{
+    PipelineExecutor,
+    presets::{Anime4KPerformancePreset, Anime4KPreset},
+};
+
+mod common;
+
+/// Returns the number of live resources of each kind the executor could allocate
+fn resource_counts(device: &wgpu::Device) -> [(&'static str, isize); 7] {
+    let hal = device.get_internal_counters().hal;
+    [
+        ("buffers", hal.buffers.read()),
+        ("textures", hal.textures.read()),
+        ("texture views", hal.texture_views.read()),
+        ("samplers", hal.samplers.read()),
+        ("bind groups", hal.bind_groups.read()),
+        ("compute pipelines", hal.compute_pipelines.read()),
+        ("query sets", hal.query_sets.read()),
+    ]
+}
+
+/// Tests that processing 100 frames allocates no resources after the first frame
+#[test]
+fn test_process_frame_allocates_nothing() {
+    let (device, queue) = common::device_or_skip!("process frame test");
+
+    let (width, height) = (24, 16);
+    let source_texture = common::create_source_texture(&device, &queue, width, height);
+    let pipelines = Anime4KPreset::ModeA.create_pipelines(Anime4KPerformancePreset::Light, 2.0);
+    let (executor, _) = PipelineExecutor::new(&pipelines, &device, &source_texture);
+
+    let process = |frame_index: u32| {
+        let frame: Vec<u8> = (0..width * height * 4).map(|index| ((index + frame_index) % 256) as f32 / 255.0).flat_map(f32::to_le_bytes).collect();
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        executor.process_frame(&queue, &mut encoder, &frame).unwrap();
+        queue.submit(std::iter::once(encoder.finish()));
+        device.poll(wgpu::PollType::Wait).unwrap();
+    };
+
+    process(0);
+    let before = resource_counts(&device);
+    for frame_index in 1..=100 {
+        process(frame_index);
+    }
+    assert_eq!(resource_counts(&device), before);
+}
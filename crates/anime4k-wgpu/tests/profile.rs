@@ -0,0 +1,56 @@
+//! Tests for per-pass GPU profiling
+//!
+//! The shared test device is created without `TIMESTAMP_QUERY`, which covers the error
+//! path; the success path requests its own device with it and is skipped without
+//! `TIMESTAMP_QUERY`.
+
+use anime4k_wgpu::{
+    CubeLut, PipelineExecutor,
+    presets::{Anime4KPerformancePreset, Anime4KPreset},
+};
+
+mod common;
+
+/// Creates a device with timestamp queries enabled, if the system has one
+fn create_timestamp_device() -> Option<(wgpu::Device, wgpu::Queue)> {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::PRIMARY,
+        ..Default::default()
+    });
+    let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default())).ok()?;
+    pollster::block_on(PipelineExecutor::request_device(&adapter, wgpu::Features::TIMESTAMP_QUERY)).ok()
+}
+
+/// Tests that profiling on a device without timestamp queries is an error, not a panic
+#[test]
+fn test_profile_requires_timestamp_query() {
+    let (device, queue) = common::device_or_skip!("profile test");
+
+    let source_texture = common::create_source_texture(&device, &queue, 8, 8);
+    let pipelines = Anime4KPreset::ModeA.create_pipelines(Anime4KPerformancePreset::Light, 2.0);
+    let (executor, _) = PipelineExecutor::new(&pipelines, &device, &source_texture);
+
+    let error = executor.profile(&device, &queue).unwrap_err();
+    assert!(error.to_string().contains("TIMESTAMP_QUERY"), "Unexpected error: {error}");
+}
+
+/// Tests that profiling times every pass of the pipelines in order, but none of the
+/// padding and LUT passes recorded around them
+#[test]
+fn test_profile_reports_pipeline_passes() {
+    let Some((device, queue)) = create_timestamp_device() else {
+        eprintln!("Skipping profile test: no GPU adapter with FLOAT32_FILTERABLE and TIMESTAMP_QUERY");
+        return;
+    };
+
+    // An odd size, so the executor pads the source and crops the output
+    let source_texture = common::create_source_texture(&device, &queue, 61, 37);
+    let pipelines = Anime4KPreset::ModeA.create_pipelines(Anime4KPerformancePreset::Light, 2.0);
+    let (executor, _) = PipelineExecutor::new_padded(&pipelines, &device, &source_texture);
+    let (executor, _) = executor.with_lut(&device, &queue, &CubeLut::identity(2));
+
+    let timings = executor.profile(&device, &queue).unwrap();
+    let names: Vec<_> = timings.iter().map(|timing| timing.name).collect();
+    let expected: Vec<_> = pipelines.iter().flat_map(|pipeline| pipeline.passes().iter().map(|pass| pass.name)).collect();
+    assert_eq!(names, expected);
+}
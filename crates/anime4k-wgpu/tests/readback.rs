@@ -0,0 +1,53 @@
+//! Tests for reading back outputs whose rows are not 256-byte aligned
+//!
+//! Texture-to-buffer copies require rows padded to `COPY_BYTES_PER_ROW_ALIGNMENT`, so a
+//! readback that does not strip the padding shears the image. The nearest-neighbor preset
+//! makes every output texel predictable, so any misplaced row or texel is caught exactly.
+
+use anime4k_wgpu::{
+    ChannelOrder,
+    presets::{Anime4KPerformancePreset, Anime4KPreset},
+    upscale_raw_f32,
+};
+
+mod common;
+
+/// Upscales an input of the given size 2x with nearest-neighbor and checks every texel
+///
+/// # Arguments
+/// * `width` - Input width in pixels
+/// * `height` - Input height in pixels
+fn check_nearest_readback(width: u32, height: u32) {
+    let (device, queue) = common::device_or_skip!("readback test for {width}x{height}");
+
+    // Every texel is unique, so a shifted row or column cannot go unnoticed
+    let texel = |x: u32, y: u32| [x as f32 / width as f32, y as f32 / height as f32, ((x + y) % 7) as f32 / 7.0, 1.0];
+    let input: Vec<f32> = (0..height).flat_map(|y| (0..width).flat_map(move |x| texel(x, y))).collect();
+
+    let output = upscale_raw_f32(&device, &queue, &input, ChannelOrder::Rgba, width, height, Anime4KPreset::Nearest, Anime4KPerformancePreset::Light, 2.0).unwrap();
+
+    let (output_width, output_height) = (width * 2, height * 2);
+    assert_eq!(output.len(), (output_width * output_height * 4) as usize);
+    for (index, actual) in output.chunks_exact(4).enumerate() {
+        let (x, y) = (index as u32 % output_width, index as u32 / output_width);
+        assert_eq!(actual, texel(x / 2, y / 2), "Output texel ({x}, {y}) of a {width}x{height} input is misplaced");
+    }
+}
+
+/// Tests an input whose output rows need padding (254 texels, 4064 bytes per row)
+#[test]
+fn test_unaligned_width() {
+    check_nearest_readback(127, 5);
+}
+
+/// Tests a single-pixel-wide input, whose output rows are far shorter than the alignment
+#[test]
+fn test_single_column() {
+    check_nearest_readback(1, 3);
+}
+
+/// Tests an input whose output rows are already aligned (32 texels, 512 bytes per row)
+#[test]
+fn test_aligned_width() {
+    check_nearest_readback(16, 4);
+}
@@ -0,0 +1,50 @@
+//! Tests for requesting a device from a caller-selected adapter
+//!
+//! These use the default adapter directly instead of the shared test device, so adapters
+//! lacking the required features are covered too; only a system without any adapter skips them.
+
+use anime4k_wgpu::PipelineExecutor;
+
+/// Requests the default adapter, if the system has one
+fn request_adapter() -> Option<wgpu::Adapter> {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::PRIMARY,
+        ..Default::default()
+    });
+    pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default())).ok()
+}
+
+/// Tests that the device is created from the given adapter with the required features, or
+/// that an adapter lacking them is rejected
+#[test]
+fn test_request_device_applies_required_features() {
+    let Some(adapter) = request_adapter() else {
+        eprintln!("Skipping test: no GPU adapter");
+        return;
+    };
+
+    let supported = adapter.features().contains(PipelineExecutor::REQUIRED_FEATURES) && PipelineExecutor::check_format_support(&adapter).is_ok();
+    match pollster::block_on(PipelineExecutor::request_device(&adapter, wgpu::Features::empty())) {
+        Ok((device, _queue)) => {
+            assert!(supported);
+            assert!(device.features().contains(PipelineExecutor::REQUIRED_FEATURES));
+        }
+        Err(e) => assert!(!supported, "suitable adapter was rejected: {e}"),
+    }
+}
+
+/// Tests that additional features the adapter lacks are reported instead of requested
+#[test]
+fn test_request_device_rejects_missing_additional_features() {
+    let Some(adapter) = request_adapter() else {
+        eprintln!("Skipping test: no GPU adapter");
+        return;
+    };
+    let Some(missing) = wgpu::Features::all().iter().find(|&feature| !adapter.features().contains(feature)) else {
+        eprintln!("Skipping test: adapter supports every feature");
+        return;
+    };
+
+    let error = pollster::block_on(PipelineExecutor::request_device(&adapter, missing)).unwrap_err();
+    assert_eq!(error.downcast_ref::<std::io::Error>().map(std::io::Error::kind), Some(std::io::ErrorKind::Unsupported));
+}
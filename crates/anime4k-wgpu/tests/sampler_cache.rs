@@ -0,0 +1,35 @@
+//! Tests for sharing samplers through a sampler cache
+//!
+//! Samplers must be created once per combination of filter and address mode.
+
+use anime4k_wgpu::{SamplerCache, SamplerFilterMode, SamplerKey};
+
+mod common;
+
+/// Tests that samplers are created once per filter and address mode combination
+#[test]
+fn test_sampler_cache_memoizes_by_key() {
+    let (device, _queue) = common::device_or_skip!("sampler cache test");
+
+    let mut cache = SamplerCache::new();
+    assert!(cache.is_empty());
+
+    let linear = cache.get(&device, SamplerFilterMode::Linear);
+    let linear_again = cache.get(&device, SamplerKey::clamp_to_edge(wgpu::FilterMode::Linear));
+    assert_eq!(linear, linear_again);
+    assert_eq!(cache.len(), 1);
+
+    let nearest = cache.get(&device, SamplerFilterMode::Nearest);
+    assert_ne!(linear, nearest);
+    assert_eq!(cache.len(), 2);
+
+    let repeating = cache.get(
+        &device,
+        SamplerKey {
+            address_mode_u: wgpu::AddressMode::Repeat,
+            ..SamplerKey::clamp_to_edge(wgpu::FilterMode::Linear)
+        },
+    );
+    assert_ne!(linear, repeating);
+    assert_eq!(cache.len(), 3);
+}
@@ -0,0 +1,51 @@
+//! Tests for rescaling pipelines with `ExecutablePipeline::with_scale_factor`
+//!
+//! Rescaled pipelines must size their textures, including the source, like the original on a scaled input.
+
+use anime4k_wgpu::{
+    ScaleFactor,
+    pipelines::{aux, cnn},
+};
+
+/// Tests that a rescaled pipeline sizes its textures like the original on a scaled input
+#[test]
+fn test_with_scale_factor_dimensions() {
+    let double = ScaleFactor { numerator: 2, denominator: 1 };
+    for pipeline in [&cnn::UPSCALE_CNN_X2_M, &cnn::RESTORE_CNN_S, &aux::UPSCALE_NEAREST_X4] {
+        let rescaled = pipeline.with_scale_factor(double);
+        assert_eq!(rescaled.passes().len(), pipeline.passes().len());
+        for (original, scaled) in pipeline.textures().iter().zip(rescaled.textures()) {
+            assert_eq!(original.id, scaled.id);
+            for (original_factor, scaled_factor) in [(original.scale_factor.0, scaled.scale_factor.0), (original.scale_factor.1, scaled.scale_factor.1)] {
+                assert_eq!(
+                    scaled_factor.numerator * original_factor.denominator,
+                    original_factor.numerator * 2 * scaled_factor.denominator,
+                    "{} texture {}",
+                    pipeline.name(),
+                    original.id
+                );
+            }
+        }
+        for (width, height) in [(1, 1), (7, 5), (1920, 1080)] {
+            assert_eq!(
+                rescaled.output_dimensions(width, height),
+                pipeline.output_dimensions(width * 2, height * 2),
+                "{} at {width}x{height}",
+                pipeline.name()
+            );
+        }
+        for (original, scaled) in pipeline.dispatch_plan(640, 360).iter().zip(rescaled.dispatch_plan(320, 180)) {
+            assert_eq!(*original, scaled, "{}", pipeline.name());
+        }
+    }
+}
+
+/// Tests that the source texture is rescaled too, so texture ratios are preserved
+#[test]
+fn test_with_scale_factor_source() {
+    let half = ScaleFactor { numerator: 1, denominator: 2 };
+    let rescaled = cnn::UPSCALE_CNN_X2_S.with_scale_factor(half);
+    let source = rescaled.textures().iter().find(|texture| texture.is_source).unwrap();
+    assert_eq!(source.scale_factor, (half, half));
+    assert_eq!(rescaled.output_dimensions(1920, 1080), (1920, 1080));
+}
@@ -0,0 +1,29 @@
+//! Tests for running pipelines one pass at a time
+//!
+//! Running one pass per submission must give the same output as recording all passes at once.
+
+use anime4k_wgpu::{
+    PipelineExecutor, PreDenoise,
+    presets::{Anime4KPerformancePreset, Anime4KPreset},
+};
+
+mod common;
+
+/// Tests that the serialized run produces the same output as the batched one
+#[test]
+fn test_serialized_matches_batched() {
+    let (device, queue) = common::device_or_skip!("serialized pass test");
+
+    let source_texture = common::create_source_texture(&device, &queue, 24, 16);
+
+    let pipelines = Anime4KPreset::ModeAA.create_pipelines(Anime4KPerformancePreset::Light, 2.0);
+    let (executor, _) = PipelineExecutor::new_pre_denoised(&pipelines, &device, &source_texture, PreDenoise::Gaussian { sigma: 1.0 });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    executor.pass(&mut encoder);
+    queue.submit(std::iter::once(encoder.finish()));
+    let batched = executor.output_stats(&device, &queue).unwrap();
+
+    executor.pass_serialized(&device, &queue).unwrap();
+    assert_eq!(executor.output_stats(&device, &queue).unwrap(), batched);
+}
@@ -0,0 +1,41 @@
+//! Tests for tracking the active presets with `Anime4kSettings`
+//!
+//! The setters must report changes, and the created pipelines must follow the active settings.
+
+use anime4k_wgpu::presets::{Anime4KPerformancePreset, Anime4KPreset, Anime4kSettings};
+
+/// Tests that the setters report changes and setting the current value again is a no-op
+#[test]
+fn test_settings_change_detection() {
+    let mut settings = Anime4kSettings::default();
+    assert_eq!(settings.preset(), None);
+    assert_eq!(settings.performance_preset(), Anime4KPerformancePreset::Medium);
+
+    assert!(settings.set_preset(Some(Anime4KPreset::ModeA)));
+    assert!(!settings.set_preset(Some(Anime4KPreset::ModeA)));
+    assert!(settings.set_performance_preset(Anime4KPerformancePreset::High));
+    assert!(!settings.set_performance_preset(Anime4KPerformancePreset::High));
+    assert_eq!(settings, Anime4kSettings::new(Some(Anime4KPreset::ModeA), Anime4KPerformancePreset::High));
+
+    assert!(!settings.set(Some(Anime4KPreset::ModeA), Anime4KPerformancePreset::High));
+    assert!(settings.set(Some(Anime4KPreset::ModeA), Anime4KPerformancePreset::Light));
+    assert!(settings.set(None, Anime4KPerformancePreset::Light));
+    assert!(!settings.set_preset(None));
+    // The performance preset is kept while Anime4K is disabled
+    assert_eq!(settings.performance_preset(), Anime4KPerformancePreset::Light);
+}
+
+/// Tests that the pipelines follow the active settings
+#[test]
+fn test_settings_create_pipelines() {
+    let mut settings = Anime4kSettings::default();
+    assert!(settings.create_pipelines(2.0).is_none());
+
+    settings.set(Some(Anime4KPreset::ModeB), Anime4KPerformancePreset::Ultra);
+    let pipelines = settings.create_pipelines(4.0).unwrap();
+    let expected = Anime4KPreset::ModeB.create_pipelines(Anime4KPerformancePreset::Ultra, 4.0);
+    assert_eq!(pipelines.len(), expected.len());
+    for (pipeline, expected) in pipelines.iter().zip(&expected) {
+        assert!(std::ptr::eq(*pipeline, *expected), "{} != {}", pipeline.name(), expected.name());
+    }
+}
@@ -0,0 +1,90 @@
+//! Tests for caller-created source textures
+//!
+//! Source textures with extra usages or without `COPY_DST` are checked, as is that every
+//! constructor exposes the given texture.
+
+use anime4k_wgpu::{
+    PipelineExecutor,
+    presets::{Anime4KPerformancePreset, Anime4KPreset},
+};
+
+mod common;
+
+/// Creates an 8x8 `Rgba32Float` texture with the given usages
+fn create_source_texture(device: &wgpu::Device, usage: wgpu::TextureUsages) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width: 8,
+            height: 8,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba32Float,
+        usage,
+        view_formats: &[],
+    })
+}
+
+/// Tests that a source texture with usages beyond those required can be processed
+#[test]
+fn test_extra_usages() {
+    let (device, queue) = common::device_or_skip!("source texture test");
+
+    let usage = wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::RENDER_ATTACHMENT;
+    let source_texture = create_source_texture(&device, usage);
+    let pipelines = Anime4KPreset::ModeA.create_pipelines(Anime4KPerformancePreset::Light, 2.0);
+    let (executor, _) = PipelineExecutor::new(&pipelines, &device, &source_texture);
+
+    let frame = vec![0u8; 8 * 8 * 16];
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    executor.process_frame(&queue, &mut encoder, &frame).unwrap();
+    queue.submit(std::iter::once(encoder.finish()));
+    device.poll(wgpu::PollType::Wait).unwrap();
+}
+
+/// Tests that uploading a frame to a source texture without `COPY_DST` is an error
+#[test]
+fn test_process_frame_requires_copy_dst() {
+    let (device, queue) = common::device_or_skip!("source texture test");
+
+    let source_texture = create_source_texture(&device, wgpu::TextureUsages::TEXTURE_BINDING);
+    let pipelines = Anime4KPreset::ModeA.create_pipelines(Anime4KPerformancePreset::Light, 2.0);
+    let (executor, _) = PipelineExecutor::new(&pipelines, &device, &source_texture);
+
+    let frame = vec![0u8; 8 * 8 * 16];
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    let error = executor.process_frame(&queue, &mut encoder, &frame).unwrap_err();
+    assert!(error.to_string().contains("COPY_DST"), "Unexpected error: {error}");
+}
+
+/// Tests that every constructor exposes the texture it was given, not a derived one
+#[test]
+fn test_source_texture_is_the_given_texture() {
+    let (device, _queue) = common::device_or_skip!("source texture test");
+
+    // An odd size makes `new_padded` insert its pad pass
+    let source_texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width: 13,
+            height: 7,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba32Float,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    let pipelines = Anime4KPreset::ModeA.create_pipelines(Anime4KPerformancePreset::Light, 2.0);
+
+    let (executor, _) = PipelineExecutor::new(&pipelines, &device, &source_texture);
+    assert_eq!(executor.source_texture(), &source_texture);
+
+    let (executor, _) = PipelineExecutor::new_padded(&pipelines, &device, &source_texture);
+    assert_eq!(executor.source_texture(), &source_texture);
+}
@@ -0,0 +1,116 @@
+//! Tests for tiled upscaling with per-tile results
+//!
+//! The nearest-neighbor preset makes the untiled output exactly predictable, so the
+//! stitched tiles must match it bit for bit. ModeA tiles must match the untiled output
+//! to within float rounding, also at seams inside the image.
+
+use anime4k_wgpu::{
+    ChannelOrder,
+    presets::{Anime4KPerformancePreset, Anime4KPreset},
+    upscale_raw_f32, upscale_raw_f32_tiled_streaming,
+};
+
+mod common;
+
+/// Generates an input whose texels are all distinct
+fn input(width: u32, height: u32) -> Vec<f32> {
+    (0..width * height)
+        .flat_map(|index| [(index % width) as f32 / width as f32, (index / width) as f32 / height as f32, (index % 11) as f32 / 11.0, 1.0])
+        .collect()
+}
+
+/// Upscales an input tile by tile and stitches the tiles into one output
+///
+/// Asserts that the tiles cover the output exactly once.
+///
+/// # Returns
+/// The stitched output and the number of tiles
+fn stitch_tiles(device: &wgpu::Device, queue: &wgpu::Queue, data: &[f32], width: u32, height: u32, tile_size: u32, preset: Anime4KPreset) -> (Vec<f32>, u32) {
+    let (output_width, output_height) = (width * 2, height * 2);
+    let mut stitched = vec![f32::NAN; (output_width * output_height * 4) as usize];
+    let mut covered = vec![0u32; (output_width * output_height) as usize];
+    let mut tile_count = 0;
+    upscale_raw_f32_tiled_streaming(
+        device,
+        queue,
+        data,
+        ChannelOrder::Bgra,
+        width,
+        height,
+        tile_size,
+        preset,
+        Anime4KPerformancePreset::Light,
+        2.0,
+        |tile| {
+            assert_eq!(tile.data.len(), (tile.width * tile.height * 4) as usize);
+            for y in 0..tile.height {
+                for x in 0..tile.width {
+                    let pixel = ((tile.y + y) * output_width + tile.x + x) as usize;
+                    let texel = ((y * tile.width + x) * 4) as usize;
+                    covered[pixel] += 1;
+                    stitched[pixel * 4..pixel * 4 + 4].copy_from_slice(&tile.data[texel..texel + 4]);
+                }
+            }
+            tile_count += 1;
+        },
+    )
+    .unwrap();
+
+    assert!(covered.iter().all(|&count| count == 1), "Tiles do not cover the output exactly once");
+    (stitched, tile_count)
+}
+
+/// Tests that the tiles cover the output exactly once and stitch to the untiled output
+#[test]
+fn test_tiles_match_untiled() {
+    let (device, queue) = common::device_or_skip!("tiled test");
+
+    // Not a multiple of the tile size, so the last row and column of tiles are partial
+    let (width, height) = (150, 90);
+    let data = input(width, height);
+
+    let expected = upscale_raw_f32(&device, &queue, &data, ChannelOrder::Bgra, width, height, Anime4KPreset::Nearest, Anime4KPerformancePreset::Light, 2.0).unwrap();
+    let (stitched, tile_count) = stitch_tiles(&device, &queue, &data, width, height, 64, Anime4KPreset::Nearest);
+    assert_eq!(tile_count, 3 * 2);
+    assert_eq!(stitched, expected);
+}
+
+/// Tests that CNN tiles stitch to the untiled output without seams
+#[test]
+fn test_cnn_tiles_match_untiled() {
+    let (device, queue) = common::device_or_skip!("tiled test");
+
+    // 104x104 windows inside a 150x120 input, with seams at 40 and 80 on both axes and at 120 horizontally
+    let (width, height) = (150, 120);
+    let data = input(width, height);
+
+    let expected = upscale_raw_f32(&device, &queue, &data, ChannelOrder::Bgra, width, height, Anime4KPreset::ModeA, Anime4KPerformancePreset::Light, 2.0).unwrap();
+    let (stitched, tile_count) = stitch_tiles(&device, &queue, &data, width, height, 40, Anime4KPreset::ModeA);
+    assert_eq!(tile_count, 4 * 3);
+    // Differences beyond float rounding (about 1e-5 measured) would mean a tile saw a cut-off neighborhood
+    for (index, (actual, expected)) in stitched.iter().zip(&expected).enumerate() {
+        assert!((actual - expected).abs() <= 1e-4, "Output value {index} differs: {actual} vs {expected}");
+    }
+}
+
+/// Tests that a zero tile size is rejected
+#[test]
+fn test_zero_tile_size() {
+    let (device, queue) = common::device_or_skip!("tiled test");
+
+    let data = input(8, 8);
+    let result = upscale_raw_f32_tiled_streaming(
+        &device,
+        &queue,
+        &data,
+        ChannelOrder::Rgba,
+        8,
+        8,
+        0,
+        Anime4KPreset::Nearest,
+        Anime4KPerformancePreset::Light,
+        2.0,
+        |_| {},
+    );
+    assert!(result.is_err());
+}
@@ -0,0 +1,82 @@
+//! Tests for inputs smaller than a workgroup
+//!
+//! 1x1 and 3x3 inputs must run through a pipeline with half-size textures, and
+//! `try_new` must reject empty sources instead of panicking.
+
+use anime4k_wgpu::{PipelineExecutor, pipelines::aux};
+
+mod common;
+
+/// Tests that 1x1 and 3x3 inputs run through a pipeline with half-size textures
+#[test]
+fn test_tiny_input_downscaling_pipeline() {
+    let (device, queue) = common::device_or_skip!("tiny input test");
+
+    let pipeline = &aux::EFFECTS_DARKEN_FAST;
+    assert!(
+        pipeline.textures().iter().any(|texture| texture.scale_factor.0.numerator < texture.scale_factor.0.denominator),
+        "{} has no downscaled texture",
+        pipeline.name()
+    );
+
+    for size in [1, 3] {
+        let source_texture = common::create_source_texture(&device, &queue, size, size);
+        let (executor, output_texture) = PipelineExecutor::new(&[pipeline], &device, &source_texture);
+        assert_eq!((output_texture.width(), output_texture.height()), (size, size));
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        executor.pass(&mut encoder);
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let output = common::read_texture(&device, &queue, &output_texture);
+        assert_eq!(output.len(), (size * size * 4) as usize);
+        // The gradient has no lines to darken, so the output matches the input
+        for (index, texel) in output.chunks(4).enumerate() {
+            let (x, y) = (index as u32 % size, index as u32 / size);
+            let expected = [x as f32 / size as f32, y as f32 / size as f32, 0.5, 1.0];
+            assert!(
+                texel.iter().zip(expected).all(|(value, expected)| (value - expected).abs() < 1e-2),
+                "{size}x{size} at ({x}, {y}): {texel:?}"
+            );
+        }
+    }
+}
+
+/// Tests that `try_new` rejects a zero-size source and one that cannot be sampled, and accepts a valid one
+#[test]
+fn test_try_new_invalid_source() {
+    let (device, queue) = common::device_or_skip!("tiny input test");
+    let pipeline = &aux::EFFECTS_DARKEN_FAST;
+
+    let create_texture = |width, usage| {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width,
+                height: 4,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba32Float,
+            usage,
+            view_formats: &[],
+        })
+    };
+
+    // wgpu rejects zero-size textures itself, so catch that to get hold of one
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let empty_texture = create_texture(0, wgpu::TextureUsages::TEXTURE_BINDING);
+    assert!(pollster::block_on(device.pop_error_scope()).is_some());
+    let error = PipelineExecutor::try_new(&[pipeline], &device, &empty_texture).unwrap_err();
+    assert!(error.to_string().contains("0x4"), "{error}");
+
+    let unsampled_texture = create_texture(4, wgpu::TextureUsages::COPY_DST);
+    let error = PipelineExecutor::try_new(&[pipeline], &device, &unsampled_texture).unwrap_err();
+    assert!(error.to_string().contains("TEXTURE_BINDING"), "{error}");
+
+    let source_texture = common::create_source_texture(&device, &queue, 4, 4);
+    let (_, output_texture) = PipelineExecutor::try_new(&[pipeline], &device, &source_texture).unwrap();
+    assert_eq!((output_texture.width(), output_texture.height()), (4, 4));
+}
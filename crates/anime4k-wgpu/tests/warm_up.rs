@@ -0,0 +1,31 @@
+//! Tests for warming up pipelines before the first frame
+//!
+//! Warming up must not change the output of the run that follows.
+
+use anime4k_wgpu::{
+    PipelineExecutor,
+    presets::{Anime4KPerformancePreset, Anime4KPreset},
+};
+
+mod common;
+
+/// Tests that warming up does not change the output of the following run
+#[test]
+fn test_warm_up_keeps_output() {
+    let (device, queue) = common::device_or_skip!("warm-up test");
+
+    // 12x12 is not a multiple of the workgroup size, so the bounds-checked entry points are warmed up too
+    let source_texture = common::create_source_texture(&device, &queue, 12, 12);
+
+    let pipelines = Anime4KPreset::ModeA.create_pipelines(Anime4KPerformancePreset::Light, 2.0);
+    let (baseline, _) = PipelineExecutor::new(&pipelines, &device, &source_texture);
+    let (executor, _) = PipelineExecutor::new(&pipelines, &device, &source_texture);
+    executor.warm_up(&device, &queue).unwrap();
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    baseline.pass(&mut encoder);
+    executor.pass(&mut encoder);
+    queue.submit(std::iter::once(encoder.finish()));
+
+    assert_eq!(executor.output_stats(&device, &queue).unwrap(), baseline.output_stats(&device, &queue).unwrap());
+}
@@ -4,6 +4,7 @@
 //! and CNN/GAN GLSL files) and dumps them to a single JSON file with type information.
 
 use anime4k_wgpu_build::{
+    cnn::EdgeMode,
     cnn_glsl_to_executable_pipeline,
     pipelines::ExecutablePipeline,
     predefined::{PREDEFINED_PIPELINES_AUX, PREDEFINED_PIPELINES_CNN},
@@ -27,18 +28,42 @@ enum PipelineType {
 fn main() {
     let args: Vec<String> = env::args().collect();
 
-    if args.len() != 3 && args.len() != 4 {
-        eprintln!("Usage: {} <project_root> <output_file> [--minify]", args[0]);
+    let usage = || {
+        eprintln!("Usage: {} <project_root> <output_file> [--minify] [--annotate-source] [--edge-mode <clamp|mirror>]", args[0]);
         eprintln!("Processes all predefined pipelines and dumps them to a JSON file");
         eprintln!("  project_root: Path to the anime4k-wgpu project root");
         eprintln!("  output_file:  Path to the output JSON file");
         eprintln!("  --minify:     Optional flag to minify WGSL code (default is false)");
+        eprintln!("  --annotate-source: Optional flag to precede each CNN statement with its GLSL line as a comment (removed by --minify)");
+        eprintln!("  --edge-mode:  How CNN convolutions read texels outside the image (default is clamp)");
         process::exit(1);
+    };
+
+    if args.len() < 3 {
+        usage();
     }
 
     let project_root = Path::new(&args[1]);
     let output_file = &args[2];
-    let minify = args.get(3).is_some_and(|arg| arg == "--minify");
+    let mut minify = false;
+    let mut annotate_source = false;
+    let mut edge_mode = EdgeMode::Clamp;
+    let mut options = args[3..].iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--minify" => minify = true,
+            "--annotate-source" => annotate_source = true,
+            "--edge-mode" => match options.next().map(|value| value.parse()) {
+                Some(Ok(mode)) => edge_mode = mode,
+                Some(Err(e)) => {
+                    eprintln!("Error: {e}");
+                    process::exit(1);
+                }
+                None => usage(),
+            },
+            _ => usage(),
+        }
+    }
 
     if !project_root.exists() {
         eprintln!("Error: Project root '{}' does not exist", project_root.display());
@@ -62,7 +87,7 @@ fn main() {
     // Process predefined CNN/GAN pipelines
     let helpers_dir = project_root.join("wgsl").join("helpers");
     if helpers_dir.exists() {
-        match load_predefined_cnn_pipelines(project_root, &helpers_dir, minify) {
+        match load_predefined_cnn_pipelines(project_root, &helpers_dir, minify, annotate_source, edge_mode) {
             Ok(cnn_pipelines) => {
                 println!("Found {} CNN/GAN pipelines", cnn_pipelines.len());
                 pipelines.extend(cnn_pipelines);
@@ -117,14 +142,20 @@ fn load_predefined_auxiliary_pipelines(project_root: &Path, minify: bool) -> Res
 }
 
 /// Loads predefined CNN/GAN GLSL pipelines
-fn load_predefined_cnn_pipelines(project_root: &Path, helpers_dir: &Path, minify: bool) -> Result<HashMap<String, PipelineType>, Box<dyn std::error::Error>> {
+fn load_predefined_cnn_pipelines(
+    project_root: &Path,
+    helpers_dir: &Path,
+    minify: bool,
+    annotate_source: bool,
+    edge_mode: EdgeMode,
+) -> Result<HashMap<String, PipelineType>, Box<dyn std::error::Error>> {
     let mut pipelines = HashMap::new();
 
     for (name, path) in PREDEFINED_PIPELINES_CNN {
         let glsl_path = project_root.join(path);
         println!("Processing CNN/GAN pipeline: {name} ({path})");
 
-        match cnn_glsl_to_executable_pipeline(glsl_path.to_str().unwrap(), helpers_dir.to_str().unwrap(), minify) {
+        match cnn_glsl_to_executable_pipeline(glsl_path.to_str().unwrap(), helpers_dir.to_str().unwrap(), minify, annotate_source, edge_mode) {
             Ok(pipeline) => {
                 let pipeline_with_type = PipelineType::Cnn(pipeline);
                 pipelines.insert(name.to_string(), pipeline_with_type);
@@ -3,21 +3,52 @@
 //! This module handles the conversion of mpv-style GLSL hooks used in the original
 //! Anime4K implementation to WGSL compute shaders for wgpu.
 
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::LazyLock};
 
 use regex::Regex;
 
+use crate::mpv::{ScaleOp, parse_dimension_expr};
+
 /// Workgroup size for 2D convolution compute shaders (X dimension)
 const COMPUTE_WORKGROUP_SIZE_X: u32 = 8;
 /// Workgroup size for 2D convolution compute shaders (Y dimension)
 const COMPUTE_WORKGROUP_SIZE_Y: u32 = 8;
 
+/// GO macro: `#define GO(x_off, y_off) (texture_texOff(vec2(x_off, y_off) * 0.5))`
+///
+/// Offset-based texture access with optional fractional scaling and ReLU.
+pub(super) static RE_GO_MACRO: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^#define (?<name>\w+)\(x_off, y_off\) \((?:max\((?<sign>-?)\()?(?<texture>\w+)_texOff\(vec2\(x_off, y_off\)(?: \* (?<fraction>0\.\d+))?\)(?:\), 0.0\))?\)$").unwrap()
+});
+
+/// G macro with ReLU: `#define G (max(-(texture_tex(pos)), 0.0))`
+pub(super) static RE_G_MACRO_RELU: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^#define (?<name>\w+) \(max\((?<sign>-?)\((?<texture>\w+)_tex\(\w+\)\), 0.0\)\)$").unwrap());
+
+/// Start of the `hook()` entry point
+pub(super) static RE_ENTRYPOINT_BEGIN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^vec4 hook\(\) \{$").unwrap());
+
+/// End of the `hook()` entry point
+pub(super) static RE_ENTRYPOINT_END: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\}$").unwrap());
+
+/// Matrix-vector multiplication: `result += mat4(...) * GO(1.0, 0.0);`
+pub(super) static RE_RESULT_ADD_PROD: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?<decl>vec4 )?result \+?= mat4\((?<weights>[^)]+)\) \* (?<func>\w+)(?:\((?<x_offset>1|0|-1)\.0, (?<y_offset>1|0|-1)\.0\))?;$").unwrap());
+
+/// Bias addition: `result += vec4(...);`
+pub(super) static RE_RESULT_ADD_VEC: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^result \+= vec4\((?<weights>[^)]+)\);$").unwrap());
+
+/// Plain return: `return result;`
+pub(super) static RE_RETURN_AS_IS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^return result;$").unwrap());
+
+/// Residual return: `return result * 0.5 + MAIN_tex(MAIN_pos);`
+pub(super) static RE_RETURN_OVERLAY: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^return result(?<factor>(?: \* 0\.\d+)?) \+ MAIN_tex\(MAIN_pos\);$").unwrap());
+
 /// Type of shader stage in the CNN pipeline
 ///
 /// Anime4K uses different types of processing stages, each requiring
 /// different shader generation approaches.
 #[derive(Debug, Clone, PartialEq, Eq)]
-enum ConvolutionStageType {
+pub(super) enum ConvolutionStageType {
     /// Convolutional layer that applies learned filters to input textures
     ///
     /// These stages perform the core CNN operations including:
@@ -41,21 +72,21 @@ enum ConvolutionStageType {
 #[derive(Debug, Clone)]
 pub struct MpvHook {
     /// Human-readable description from DESC directive
-    name: String,
+    pub(super) name: String,
     /// Upscaling factor (1, 2, 3, or 4) relative to the source texture
-    scale_factor: u32,
+    pub(super) scale_factor: u32,
     /// Whether the hook needs a texture sampler for interpolated access
-    needs_sampler: bool,
+    pub(super) needs_sampler: bool,
     /// Whether the hook needs bounds checking for texture coordinates
     needs_bound: bool,
     /// Input texture names from BIND directives
-    inputs: Vec<String>,
+    pub(crate) inputs: Vec<String>,
     /// Output texture name from SAVE directive
-    output: String,
+    pub(super) output: String,
     /// The type of shader stage (Conv or DepthToSpace)
-    r#type: ConvolutionStageType,
+    pub(super) r#type: ConvolutionStageType,
     /// The original GLSL shader code (without comment directives)
-    code: String,
+    pub(super) code: String,
 }
 
 impl MpvHook {
@@ -146,21 +177,15 @@ impl MpvHook {
         let mut output = String::new();
         let mut code = String::new();
 
-        let scale_factor_re = Regex::new(r"^//!(?:WIDTH|HEIGHT) (\w+)\.[wh](?: (\d+) \*)?$").unwrap();
-
         for line in source.lines() {
             if let Some(content) = line.strip_prefix("//!DESC ").map(str::trim) {
                 name = content.to_string();
-            } else if line.starts_with("//!WIDTH ") || line.starts_with("//!HEIGHT ") {
-                let current_match = scale_factor_re
-                    .captures(line)
-                    .ok_or_else(|| std::boxed::Box::new(std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid scale factor line")))?;
-                let base_texture_name = &current_match[1];
-                let ratio = current_match.get(2).map(|m| m.as_str().parse::<u32>().unwrap()).unwrap_or(1);
-                let base_texture_scale_factor = scale_factor_map
-                    .get(base_texture_name)
-                    .ok_or_else(|| std::boxed::Box::new(std::io::Error::new(std::io::ErrorKind::InvalidData, "Unknown base texture name")))?;
-                let current_scale_factor = base_texture_scale_factor * ratio;
+            } else if let Some(content) = line.strip_prefix("//!WIDTH ").or_else(|| line.strip_prefix("//!HEIGHT ")) {
+                let expr = parse_dimension_expr(content, scale_factor_map)?;
+                if expr.op == ScaleOp::Divide {
+                    return Err(std::boxed::Box::new(std::io::Error::new(std::io::ErrorKind::InvalidData, "Downscaling hooks are not supported")));
+                }
+                let current_scale_factor = expr.apply(scale_factor_map[&expr.texture]);
                 if scale_factor == 0 {
                     scale_factor = current_scale_factor;
                 } else if scale_factor != current_scale_factor {
@@ -261,6 +286,84 @@ pub enum WgslStageShaderType {
     },
 }
 
+/// How convolutions read texels outside the texture
+///
+/// The original GLSL relies on the sampler clamping coordinates at the edges, which
+/// repeats the border pixel under the whole kernel. Mirroring reflects the coordinates
+/// instead, which avoids smearing strong borders such as letterbox edges.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EdgeMode {
+    /// Repeat the outermost pixel, matching the original GLSL shaders
+    #[default]
+    Clamp,
+    /// Reflect around the outermost pixel without repeating it
+    ///
+    /// Textures must be at least 2 pixels wide and high for the reflected
+    /// coordinates to stay in bounds.
+    Mirror,
+}
+
+impl EdgeMode {
+    /// Generates the WGSL expression for an offset texel coordinate
+    ///
+    /// Only the sides an offset can cross are handled, so unused checks are not emitted.
+    ///
+    /// # Arguments
+    /// * `x_offset` - Horizontal offset as a WGSL integer literal (-1, 0 or 1)
+    /// * `y_offset` - Vertical offset as a WGSL integer literal (-1, 0 or 1)
+    ///
+    /// # Returns
+    /// An expression using `pos` and, if an offset is positive, `bound`
+    pub(super) fn offset_coordinate(self, x_offset: &str, y_offset: &str) -> String {
+        let needs_neg_check = x_offset.starts_with("-") || y_offset.starts_with("-");
+        let needs_pos_check = (!x_offset.starts_with("-") && x_offset != "0") || (!y_offset.starts_with("-") && y_offset != "0");
+        let coordinate = format!("pos + vec2i({x_offset}, {y_offset})");
+        match (self, needs_neg_check, needs_pos_check) {
+            (_, false, false) => "pos".to_string(),
+            (EdgeMode::Clamp, true, true) => format!("clamp({coordinate}, vec2i(0), bound)"),
+            (EdgeMode::Clamp, true, false) => format!("max({coordinate}, vec2i(0))"),
+            (EdgeMode::Clamp, false, true) => format!("min({coordinate}, bound)"),
+            (EdgeMode::Mirror, true, true) => format!("bound - abs(bound - abs({coordinate}))"),
+            (EdgeMode::Mirror, true, false) => format!("abs({coordinate})"),
+            (EdgeMode::Mirror, false, true) => format!("bound - abs(bound - ({coordinate}))"),
+        }
+    }
+
+    /// Generates the WGSL statements defining an offset sampling coordinate
+    ///
+    /// Used by taps that read a texture at a different scale through the sampler.
+    /// Clamping is left to the sampler, while mirroring reflects the coordinate around
+    /// the outermost texel centers like the integer taps do.
+    ///
+    /// # Arguments
+    /// * `texture` - Name of the sampled texture without the `_tex` suffix
+    /// * `fraction` - Scale of the offset in texels of the sampled texture
+    ///
+    /// # Returns
+    /// Indented statements using `uv_pos` and `offset` that define `coords`
+    pub(super) fn offset_uv(self, texture: &str, fraction: &str) -> String {
+        let coordinate = format!("uv_pos + vec2f(offset) * {fraction} / vec2f(textureDimensions({texture}_tex))");
+        match self {
+            EdgeMode::Clamp => format!("    let coords = {coordinate};\n"),
+            EdgeMode::Mirror => {
+                format!("    let first = 0.5 / vec2f(textureDimensions({texture}_tex));\n    let last = 1.0 - first;\n    let coords = last - abs(last - (first + abs({coordinate} - first)));\n")
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for EdgeMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "clamp" => Ok(EdgeMode::Clamp),
+            "mirror" => Ok(EdgeMode::Mirror),
+            _ => Err(format!("Unknown edge mode: {s} (expected clamp or mirror)")),
+        }
+    }
+}
+
 /// A WGSL compute shader stage converted from an mpv hook
 ///
 /// Contains all the information needed to generate a complete WGSL compute shader
@@ -302,10 +405,29 @@ impl WgslStageShader {
     /// # Errors
     /// Returns an error if GLSL to WGSL translation fails for convolutional layers
     pub fn new(source: MpvHook, scale_factor_map: &HashMap<String, u32>) -> Result<Self, std::boxed::Box<dyn std::error::Error>> {
+        Self::new_with_options(source, scale_factor_map, false, EdgeMode::Clamp)
+    }
+
+    /// Creates a new WGSL stage shader from an mpv hook with conversion options
+    ///
+    /// Behaves like `new`, but can annotate the generated WGSL with the GLSL lines
+    /// it was translated from and read past the texture edges with a different mode.
+    /// Annotations are plain comments, so they are removed by `minify_wgsl` and only
+    /// show up in unminified output.
+    ///
+    /// # Arguments
+    /// * `source` - The parsed mpv hook to convert
+    /// * `scale_factor_map` - Mapping of texture names to their scale factors
+    /// * `annotate_source` - Whether to emit `// from GLSL: <line>` above each translated statement
+    /// * `edge_mode` - How convolutions read texels outside the texture
+    ///
+    /// # Returns
+    /// A WGSL shader stage ready for code generation
+    pub fn new_with_options(source: MpvHook, scale_factor_map: &HashMap<String, u32>, annotate_source: bool, edge_mode: EdgeMode) -> Result<Self, std::boxed::Box<dyn std::error::Error>> {
         let name = if source.output == "dest" { "result".to_string() } else { source.output.clone() };
         let r#type = match source.r#type {
             ConvolutionStageType::Conv => WgslStageShaderType::Conv {
-                code: Self::convert_conv_hook_code(&source, scale_factor_map)?,
+                code: Self::convert_conv_hook_code(&source, scale_factor_map, annotate_source, edge_mode)?,
             },
             ConvolutionStageType::DepthToSpace => {
                 let components = source.inputs.len() as u32;
@@ -354,6 +476,8 @@ impl WgslStageShader {
     /// # Arguments
     /// * `source` - The mpv hook containing GLSL code to translate
     /// * `scale_factor_map` - Mapping of texture names to scale factors for proper sampling
+    /// * `annotate_source` - Whether to emit each source GLSL line as a comment above its translation
+    /// * `edge_mode` - How offset texel coordinates are kept inside the texture
     ///
     /// # Returns
     /// Complete WGSL compute shader source code
@@ -364,7 +488,7 @@ impl WgslStageShader {
     /// - Texture references cannot be resolved
     /// - Scale factor mismatches are detected
     /// - Macro definitions are malformed
-    fn convert_conv_hook_code(source: &MpvHook, scale_factor_map: &HashMap<String, u32>) -> Result<String, std::boxed::Box<dyn std::error::Error>> {
+    fn convert_conv_hook_code(source: &MpvHook, scale_factor_map: &HashMap<String, u32>, annotate_source: bool, edge_mode: EdgeMode) -> Result<String, std::boxed::Box<dyn std::error::Error>> {
         let output_texture = &source.output;
 
         let mut code = String::new();
@@ -386,39 +510,21 @@ impl WgslStageShader {
         }
         code.push('\n');
 
-        // Regex patterns for parsing different GLSL constructs
-
-        // GO macro: #define GO(x_off, y_off) (texture_texOff(vec2(x_off, y_off) * 0.5))
-        // Handles offset-based texture access with optional fractional scaling
-        let re_go_macro =
-            Regex::new(r"^#define (?<name>\w+)\(x_off, y_off\) \((?:max\((?<sign>-?)\()?(?<texture>\w+)_texOff\(vec2\(x_off, y_off\)(?: \* (?<fraction>0\.\d+))?\)(?:\), 0.0\))?\)$").unwrap();
-
-        // G macro with ReLU: #define G (max(-(texture_tex(pos)), 0.0))
-        // Handles simple texture access with ReLU activation
-        let re_g_macro_relu = Regex::new(r"^#define (?<name>\w+) \(max\((?<sign>-?)\((?<texture>\w+)_tex\(\w+\)\), 0.0\)\)$").unwrap();
-
-        // Entry point patterns
-        let re_entrypoint_begin = Regex::new(r"^vec4 hook\(\) \{$").unwrap();
-        let re_entrypoint_end = Regex::new(r"^\}$").unwrap();
-
-        // Matrix-vector multiplication: result += mat4(...) * GO(1.0, 0.0);
-        let re_result_add_prod = Regex::new(r"^(?<decl>vec4 )?result \+?= mat4\((?<weights>[^)]+)\) \* (?<func>\w+)(?:\((?<x_offset>1|0|-1)\.0, (?<y_offset>1|0|-1)\.0\))?;$").unwrap();
-
-        // Bias addition: result += vec4(...);
-        let re_result_add_vec = Regex::new(r"^result \+= vec4\((?<weights>[^)]+)\);$").unwrap();
-
-        // Return statements
-        let re_return_as_is = Regex::new(r"^return result;$").unwrap();
-        let re_return_overlay = Regex::new(r"^return result(?<factor>(?: \* 0\.\d+)?) \+ MAIN_tex\(MAIN_pos\);$").unwrap();
-
         let mut func_to_scale_factor = HashMap::new();
+        let mut in_entrypoint = false;
 
         // Process the GLSL source code line by line, converting each construct to WGSL
         for line in source.code.lines() {
             let line = line.trim();
 
+            // Record the originating GLSL line, indented to match the statement it precedes
+            if annotate_source && !line.is_empty() && !line.starts_with("//") {
+                let indent = if in_entrypoint && !RE_ENTRYPOINT_END.is_match(line) { "    " } else { "" };
+                code.push_str(&format!("{indent}// from GLSL: {line}\n"));
+            }
+
             // Handle GO macro definitions for offset-based texture access
-            if let Some(caps) = re_go_macro.captures(line) {
+            if let Some(caps) = RE_GO_MACRO.captures(line) {
                 let func_name = &caps["name"];
                 let texture_name = if &caps["texture"] == "MAIN" { "source" } else { &caps["texture"] };
                 let fraction = caps.name("fraction").map(|m| m.as_str());
@@ -438,7 +544,7 @@ impl WgslStageShader {
                         }
 
                         code.push_str(&format!("fn {func_name}(uv_pos: vec2f, offset: vec2i) -> vec4f {{\n",));
-                        code.push_str(&format!("    let coords = uv_pos + vec2f(offset) * {fraction} / vec2f(textureDimensions({texture_name}_tex));\n"));
+                        code.push_str(&edge_mode.offset_uv(texture_name, fraction));
                         code.push_str(&format!("    let value = textureSampleLevel({texture_name}_tex, input_sampler, coords, 0.0);\n"));
                     }
                     None => {
@@ -463,7 +569,7 @@ impl WgslStageShader {
                 func_to_scale_factor.insert(func_name.to_string(), target_scale_factor);
 
             // Handle G macro definitions for simple texture access with ReLU
-            } else if let Some(caps) = re_g_macro_relu.captures(line) {
+            } else if let Some(caps) = RE_G_MACRO_RELU.captures(line) {
                 let func_name = &caps["name"];
                 let texture_name = if &caps["texture"] == "MAIN" { "source" } else { &caps["texture"] };
                 let sign = &caps["sign"];
@@ -487,7 +593,7 @@ impl WgslStageShader {
                 func_to_scale_factor.insert(func_name.to_string(), target_scale_factor);
 
             // Handle entry point conversion from GLSL hook() to WGSL compute shader
-            } else if re_entrypoint_begin.is_match(line) {
+            } else if RE_ENTRYPOINT_BEGIN.is_match(line) {
                 // Generate bounds-checked compute shader entry point
                 code.push_str(&format!("@compute @workgroup_size({COMPUTE_WORKGROUP_SIZE_X}, {COMPUTE_WORKGROUP_SIZE_Y})\n"));
                 code.push_str("fn main(@builtin(global_invocation_id) pixel: vec3u) {\n");
@@ -506,11 +612,13 @@ impl WgslStageShader {
                 code.push('\n');
 
                 code.push_str("fn process(pos: vec2i) {\n");
-            } else if re_entrypoint_end.is_match(line) {
+                in_entrypoint = true;
+            } else if RE_ENTRYPOINT_END.is_match(line) {
                 code.push_str("}\n");
+                in_entrypoint = false;
 
             // Handle matrix-vector multiplication for convolution operations
-            } else if let Some(caps) = re_result_add_prod.captures(line) {
+            } else if let Some(caps) = RE_RESULT_ADD_PROD.captures(line) {
                 let weights = &caps["weights"];
                 let func = &caps["func"];
                 let is_decl = caps.name("decl").is_some();
@@ -535,17 +643,7 @@ impl WgslStageShader {
                         if func_scale_factor != source.scale_factor {
                             code.push_str(&format!("    result += mat4x4f({weights}) * {func}(uv_pos, vec2i({x_offset}, {y_offset}));\n"));
                         } else {
-                            let needs_neg_check = x_offset.starts_with("-") || y_offset.starts_with("-");
-                            let needs_pos_check = (!x_offset.starts_with("-") && x_offset != "0") || (!y_offset.starts_with("-") && y_offset != "0");
-                            let bound_checked = if needs_neg_check && needs_pos_check {
-                                &format!("clamp(pos + vec2i({x_offset}, {y_offset}), vec2i(0), bound)")
-                            } else if needs_neg_check {
-                                &format!("max(pos + vec2i({x_offset}, {y_offset}), vec2i(0))")
-                            } else if needs_pos_check {
-                                &format!("min(pos + vec2i({x_offset}, {y_offset}), bound)")
-                            } else {
-                                "pos"
-                            };
+                            let bound_checked = edge_mode.offset_coordinate(x_offset, y_offset);
                             code.push_str(&format!("    result += mat4x4f({weights}) * {func}({bound_checked});\n"));
                         }
                     }
@@ -561,16 +659,16 @@ impl WgslStageShader {
                 }
 
             // Handle bias addition (vector constants)
-            } else if let Some(caps) = re_result_add_vec.captures(line) {
+            } else if let Some(caps) = RE_RESULT_ADD_VEC.captures(line) {
                 let weights = &caps["weights"];
                 code.push_str(&format!("    result += vec4f({weights});\n"));
 
             // Handle direct result output
-            } else if re_return_as_is.is_match(line) {
+            } else if RE_RETURN_AS_IS.is_match(line) {
                 code.push_str(&format!("    textureStore({output_texture}_tex, pos, result);\n"));
 
             // Handle overlay/residual connections
-            } else if let Some(caps) = re_return_overlay.captures(line) {
+            } else if let Some(caps) = RE_RETURN_OVERLAY.captures(line) {
                 let factor = &caps["factor"];
                 if source.scale_factor == 1 {
                     code.push_str(&format!("    textureStore({output_texture}_tex, pos, result{factor} + textureLoad(source_tex, pos, 0));\n"));
@@ -594,3 +692,103 @@ impl WgslStageShader {
         Ok(code)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cnn::validate_wgsl;
+
+    /// Converts every convolutional stage of an Upscale shader with the given edge mode
+    fn convert_conv_stages(name: &str, edge_mode: EdgeMode) -> Vec<String> {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(format!("../../anime4k-glsl/Upscale/{name}.glsl"));
+        let source = std::fs::read_to_string(path).unwrap();
+
+        let mut scale_factor_map = MpvHook::new_scale_factor_map();
+        let mut stages = Vec::new();
+        for pass_source in MpvHook::parse_mpv_hooks(&source) {
+            let hook = MpvHook::new(&pass_source, &mut scale_factor_map).unwrap();
+            if let WgslStageShaderType::Conv { code } = WgslStageShader::new_with_options(hook, &scale_factor_map, false, edge_mode).unwrap().r#type {
+                stages.push(code);
+            }
+        }
+        stages
+    }
+
+    /// Tests that both edge modes emit valid WGSL and only mirroring uses reflected coordinates
+    #[test]
+    fn test_edge_mode_codegen() {
+        let clamp_stages = convert_conv_stages("Anime4K_Upscale_CNN_x2_S", EdgeMode::Clamp);
+        let mirror_stages = convert_conv_stages("Anime4K_Upscale_CNN_x2_S", EdgeMode::Mirror);
+        assert_eq!(clamp_stages.len(), mirror_stages.len());
+
+        for (clamp, mirror) in clamp_stages.iter().zip(&mirror_stages) {
+            validate_wgsl(clamp).unwrap();
+            validate_wgsl(mirror).unwrap();
+            assert!(clamp.contains("max(pos + vec2i(-1, -1), vec2i(0))"));
+            assert!(clamp.contains("clamp(pos + vec2i(-1, 1), vec2i(0), bound)"));
+            assert!(mirror.contains("abs(pos + vec2i(-1, -1))"));
+            assert!(mirror.contains("bound - abs(bound - abs(pos + vec2i(-1, 1)))"));
+            assert!(!mirror.contains("clamp(pos"));
+            assert!(!mirror.contains("max(pos"));
+        }
+
+        assert_eq!(EdgeMode::Clamp.offset_coordinate("0", "0"), "pos");
+        assert_eq!(EdgeMode::Mirror.offset_coordinate("-1", "0"), "abs(pos + vec2i(-1, 0))");
+        assert_eq!(EdgeMode::Mirror.offset_coordinate("1", "0"), "bound - abs(bound - (pos + vec2i(1, 0)))");
+        assert_eq!("Mirror".parse::<EdgeMode>(), Ok(EdgeMode::Mirror));
+        assert!("wrap".parse::<EdgeMode>().is_err());
+    }
+
+    /// Tests that taps reading a texture at a different scale are only reflected when mirroring
+    #[test]
+    fn test_edge_mode_codegen_fraction() {
+        let clamp_stages = convert_conv_stages("Anime4K_Upscale_GAN_x2_S", EdgeMode::Clamp);
+        let mirror_stages = convert_conv_stages("Anime4K_Upscale_GAN_x2_S", EdgeMode::Mirror);
+
+        let sampled = |stages: &[String]| stages.iter().filter(|code| code.contains("textureSampleLevel")).cloned().collect::<Vec<_>>();
+        let clamp_sampled = sampled(&clamp_stages);
+        let mirror_sampled = sampled(&mirror_stages);
+        assert!(!clamp_sampled.is_empty());
+        assert_eq!(clamp_sampled.len(), mirror_sampled.len());
+
+        for (clamp, mirror) in clamp_sampled.iter().zip(&mirror_sampled) {
+            validate_wgsl(clamp).unwrap();
+            validate_wgsl(mirror).unwrap();
+            assert!(!clamp.contains("let last"));
+            assert!(mirror.contains("let coords = last - abs(last - (first + abs(uv_pos + vec2f(offset) * 0.5 / "));
+        }
+    }
+
+    /// Tests that annotated WGSL carries each GLSL statement as a comment and still validates
+    #[test]
+    fn test_annotate_source() {
+        let manifest_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+        let glsl_path = manifest_dir.join("../../anime4k-glsl/Upscale/Anime4K_Upscale_CNN_x2_S.glsl");
+        let helpers_dir = manifest_dir.join("../../wgsl/helpers");
+        let convert = |annotate_source| crate::cnn_glsl_to_pipeline_spec(glsl_path.to_str().unwrap(), helpers_dir.to_str().unwrap(), false, annotate_source, EdgeMode::Clamp).unwrap();
+        let (plain_spec, plain_files) = convert(false);
+        let (annotated_spec, annotated_files) = convert(true);
+        assert_eq!(plain_spec.passes.len(), annotated_spec.passes.len());
+
+        let conv_passes: Vec<_> = annotated_spec.passes.iter().filter(|pass| pass.file.starts_with("pass_")).collect();
+        assert!(!conv_passes.is_empty());
+        for pass in conv_passes {
+            let plain = plain_files.load(&pass.file).unwrap();
+            let annotated = annotated_files.load(&pass.file).unwrap();
+            validate_wgsl(&annotated).unwrap();
+            assert!(!plain.contains("// from GLSL:"));
+            assert!(annotated.contains("// from GLSL: #define go_0(x_off, y_off) "), "{}", pass.file);
+            assert!(annotated.contains("// from GLSL: vec4 hook() {"), "{}", pass.file);
+            assert!(annotated.contains("    // from GLSL: vec4 result = mat4("), "{}", pass.file);
+            assert!(annotated.contains("    // from GLSL: return result;"), "{}", pass.file);
+
+            // Without the annotations, the translation is unchanged
+            let stripped: String = annotated
+                .lines()
+                .filter(|line| !line.trim_start().starts_with("// from GLSL: "))
+                .map(|line| format!("{line}\n"))
+                .collect();
+            assert_eq!(stripped, plain, "{}", pass.file);
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! Programmatic depth-to-space shader generation
+//!
+//! Generates WGSL depth-to-space stages for scale factor and input combinations
+//! that have no hand-written helper in `wgsl/helpers`.
+
+/// Returns the overlay texture and component holding a sub-pixel value
+///
+/// Overlay values are packed contiguously per color channel: color `c` at
+/// sub-pixel `k = dy * scale + dx` is stored at flat index `c * scale² + k`,
+/// four values per texture. For scale 2 this matches the hand-written helpers
+/// (one texture per color, one component per sub-pixel).
+///
+/// # Arguments
+/// * `color` - Color channel index (0 for grayscale, 0..3 for RGB)
+/// * `sub_pixel` - Sub-pixel index within the `scale`x`scale` block, in row-major order
+/// * `scale` - Upscaling factor
+///
+/// # Returns
+/// A tuple of (overlay texture index, component index)
+pub fn depth_to_space_source_index(color: u32, sub_pixel: u32, scale: u32) -> (u32, u32) {
+    let flat_index = color * scale * scale + sub_pixel;
+    (flat_index / 4, flat_index % 4)
+}
+
+/// Determines the number of color channels encoded by a set of overlay textures
+///
+/// # Arguments
+/// * `overlays` - Number of overlay textures (excluding the source texture)
+/// * `scale` - Upscaling factor
+///
+/// # Returns
+/// 1 for grayscale overlays (broadcast to RGB), 3 for RGB overlays, or `None` if the combination is unsupported
+fn depth_to_space_colors(overlays: u32, scale: u32) -> Option<u32> {
+    [1, 3].into_iter().find(|colors| (colors * scale * scale).div_ceil(4) == overlays)
+}
+
+/// Generates a WGSL depth-to-space compute shader
+///
+/// The generated shader follows the same binding layout as the helpers in
+/// `wgsl/helpers`: the source texture, then the overlay textures, then the
+/// output storage texture, then the sampler. The rearranged overlay is added to
+/// the bilinearly sampled source.
+///
+/// # Arguments
+/// * `overlays` - Number of overlay textures (excluding the source texture)
+/// * `scale` - Upscaling factor (at least 2)
+///
+/// # Returns
+/// The WGSL source code of the depth-to-space stage
+///
+/// # Errors
+/// Returns an error if the number of overlays does not encode a whole number of
+/// grayscale or RGB channels at the given scale
+pub fn generate_depth_to_space_wgsl(overlays: u32, scale: u32) -> Result<String, std::boxed::Box<dyn std::error::Error>> {
+    let colors = if scale >= 2 { depth_to_space_colors(overlays, scale) } else { None };
+    let Some(colors) = colors else {
+        return Err(std::boxed::Box::new(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Unsupported depth-to-space configuration: {overlays} overlays at scale {scale}"),
+        )));
+    };
+
+    let mut code = String::new();
+    code.push_str("@group(0) @binding(0) var source_texture: texture_2d<f32>;\n");
+    for i in 0..overlays {
+        code.push_str(&format!("@group(0) @binding({}) var overlay_{i}_texture: texture_2d<f32>;\n", i + 1));
+    }
+    code.push_str(&format!("@group(0) @binding({}) var output_texture: texture_storage_2d<rgba32float, write>;\n", overlays + 1));
+    code.push_str(&format!("@group(0) @binding({}) var source_sampler: sampler;\n\n", overlays + 2));
+
+    // Reads the overlay value for a flat index by dispatching to the right texture
+    code.push_str("fn load_overlay(overlay_pos: vec2i, flat_index: i32) -> f32 {\n");
+    code.push_str("    switch flat_index / 4 {\n");
+    for i in 0..overlays {
+        let case = if i + 1 == overlays { "default".to_string() } else { format!("case {i}") };
+        code.push_str(&format!("        {case}: {{ return textureLoad(overlay_{i}_texture, overlay_pos, 0)[flat_index % 4]; }}\n"));
+    }
+    code.push_str("    }\n");
+    code.push_str("}\n\n");
+
+    code.push_str("fn process(pos: vec2i) {\n");
+    code.push_str(&format!("    let overlay_pos = vec2i(pos.x / {scale}, pos.y / {scale});\n"));
+    code.push_str(&format!("    let sub_pixel = (pos.y % {scale}) * {scale} + (pos.x % {scale});\n"));
+    if colors == 1 {
+        code.push_str("    let overlay_scalar = load_overlay(overlay_pos, sub_pixel);\n");
+        code.push_str("    let overlay_color = vec4f(overlay_scalar, overlay_scalar, overlay_scalar, 0.0);\n");
+    } else {
+        let block = scale * scale;
+        code.push_str("    let overlay_color = vec4f(\n");
+        code.push_str("        load_overlay(overlay_pos, sub_pixel),\n");
+        code.push_str(&format!("        load_overlay(overlay_pos, {block} + sub_pixel),\n"));
+        code.push_str(&format!("        load_overlay(overlay_pos, {} + sub_pixel),\n", block * 2));
+        code.push_str("        0.0\n");
+        code.push_str("    );\n");
+    }
+    code.push_str("    let source_color = textureSampleLevel(source_texture, source_sampler, (vec2f(pos) + vec2f(0.5)) / vec2f(textureDimensions(output_texture)), 0.0);\n");
+    code.push_str("    textureStore(output_texture, pos, source_color + overlay_color);\n");
+    code.push_str("}\n\n");
+
+    code.push_str(
+        r#"@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) pixel: vec3u) {
+    let out_dim: vec2u = textureDimensions(output_texture);
+    if pixel.x < out_dim.x && pixel.y < out_dim.y {
+        process(vec2i(pixel.xy));
+    }
+}
+
+@compute @workgroup_size(8, 8)
+fn main_unchecked(@builtin(global_invocation_id) pixel: vec3u) {
+    process(vec2i(pixel.xy));
+}
+"#,
+    );
+
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale_3_grayscale_arrangement() {
+        // 9 sub-pixels spread across 3 overlay textures, row-major within the 3x3 block
+        let arrangement: Vec<_> = (0..9).map(|k| depth_to_space_source_index(0, k, 3)).collect();
+        assert_eq!(arrangement, vec![(0, 0), (0, 1), (0, 2), (0, 3), (1, 0), (1, 1), (1, 2), (1, 3), (2, 0)]);
+        assert_eq!(depth_to_space_colors(3, 3), Some(1));
+    }
+
+    #[test]
+    fn scale_3_rgb_arrangement() {
+        assert_eq!(depth_to_space_colors(7, 3), Some(3));
+        // Green starts right after the 9 red values, blue after the 9 green values
+        assert_eq!(depth_to_space_source_index(1, 0, 3), (2, 1));
+        assert_eq!(depth_to_space_source_index(2, 8, 3), (6, 2));
+    }
+
+    #[test]
+    fn scale_2_matches_helpers() {
+        for color in 0..3 {
+            for k in 0..4 {
+                assert_eq!(depth_to_space_source_index(color, k, 2), (color, k));
+            }
+        }
+    }
+
+    #[test]
+    fn generated_shader_is_valid_wgsl() {
+        for (overlays, scale) in [(1, 2), (3, 2), (3, 3), (7, 3)] {
+            let code = generate_depth_to_space_wgsl(overlays, scale).unwrap();
+            let module = naga::front::wgsl::parse_str(&code).unwrap();
+            assert_eq!(module.global_variables.len() as u32, overlays + 3);
+        }
+    }
+
+    #[test]
+    fn unsupported_configuration_is_rejected() {
+        assert!(generate_depth_to_space_wgsl(2, 3).is_err());
+        assert!(generate_depth_to_space_wgsl(1, 1).is_err());
+    }
+}
@@ -0,0 +1,393 @@
+//! Single-pass fusion of same-resolution CNN shaders
+//!
+//! Restore CNNs run every layer at the source resolution, so the whole network can be
+//! evaluated by one compute shader: each workgroup loads the source once, computes every
+//! intermediate layer for its tile plus the halo the following layers need into workgroup
+//! memory, and writes only the final result. This removes the texture round trips between
+//! passes at the cost of recomputing the halo texels in every workgroup.
+
+use std::collections::HashMap;
+
+use super::convert::{
+    ConvolutionStageType, EdgeMode, MpvHook, RE_ENTRYPOINT_BEGIN, RE_ENTRYPOINT_END, RE_G_MACRO_RELU, RE_GO_MACRO, RE_RESULT_ADD_PROD, RE_RESULT_ADD_VEC, RE_RETURN_AS_IS, RE_RETURN_OVERLAY,
+};
+
+/// Side length of the tile of output pixels computed by one workgroup
+const TILE_SIZE: u32 = 8;
+
+/// Workgroup memory budget for intermediate layers, in bytes
+///
+/// This is the minimum `max_compute_workgroup_storage_size` guaranteed by WebGPU.
+const MAX_WORKGROUP_STORAGE_SIZE: u32 = 16384;
+
+/// Size of one RGBA32F texel in workgroup memory, in bytes
+const TEXEL_SIZE: u32 = 16;
+
+/// A GO or G macro reading a texture with an optional ReLU
+struct MacroFunction {
+    /// Macro name in the GLSL source
+    name: String,
+    /// Texture the macro reads, with MAIN renamed to `source`
+    texture: String,
+    /// Sign applied before the ReLU, or `None` if the value is returned as is
+    relu_sign: Option<String>,
+}
+
+/// A statement accumulating into `result`
+enum Term {
+    /// `result += mat4(...) * func(x, y)`, where `offset` is `None` for 1x1 macros
+    Product { weights: String, func: String, offset: Option<(String, String)> },
+    /// `result += vec4(...)`
+    Bias { weights: String },
+}
+
+/// A convolution layer parsed for fusion
+struct FusedStage {
+    /// Name of the texture the layer writes
+    output: String,
+    /// Macros defined by the layer
+    functions: Vec<MacroFunction>,
+    /// Accumulation statements in source order
+    terms: Vec<Term>,
+    /// Residual factor (e.g. ` * 0.5`) if the layer adds the source to its result
+    overlay: Option<String>,
+}
+
+impl FusedStage {
+    /// Parses the GLSL code of a convolution hook
+    ///
+    /// # Returns
+    /// The parsed layer, or `None` if it uses constructs that cannot be fused
+    fn parse(hook: &MpvHook) -> Result<Option<Self>, std::boxed::Box<dyn std::error::Error>> {
+        let mut functions = Vec::new();
+        let mut terms = Vec::new();
+        let mut overlay = None;
+
+        for line in hook.code.lines() {
+            let line = line.trim();
+            if let Some(caps) = RE_GO_MACRO.captures(line) {
+                if caps.name("fraction").is_some() {
+                    return Ok(None);
+                }
+                functions.push(MacroFunction {
+                    name: caps["name"].to_string(),
+                    texture: if &caps["texture"] == "MAIN" { "source".to_string() } else { caps["texture"].to_string() },
+                    relu_sign: caps.name("sign").map(|m| m.as_str().to_string()),
+                });
+            } else if let Some(caps) = RE_G_MACRO_RELU.captures(line) {
+                functions.push(MacroFunction {
+                    name: caps["name"].to_string(),
+                    texture: if &caps["texture"] == "MAIN" { "source".to_string() } else { caps["texture"].to_string() },
+                    relu_sign: Some(caps["sign"].to_string()),
+                });
+            } else if let Some(caps) = RE_RESULT_ADD_PROD.captures(line) {
+                let offset = match (caps.name("x_offset"), caps.name("y_offset")) {
+                    (Some(x_offset), Some(y_offset)) => Some((x_offset.as_str().to_string(), y_offset.as_str().to_string())),
+                    _ => None,
+                };
+                terms.push(Term::Product {
+                    weights: caps["weights"].to_string(),
+                    func: caps["func"].to_string(),
+                    offset,
+                });
+            } else if let Some(caps) = RE_RESULT_ADD_VEC.captures(line) {
+                terms.push(Term::Bias { weights: caps["weights"].to_string() });
+            } else if RE_RETURN_AS_IS.is_match(line) {
+                // The result is stored as is
+            } else if let Some(caps) = RE_RETURN_OVERLAY.captures(line) {
+                overlay = Some(caps["factor"].to_string());
+            } else if RE_ENTRYPOINT_BEGIN.is_match(line) || RE_ENTRYPOINT_END.is_match(line) || line.starts_with("//") || line.is_empty() {
+                // Structure, comments and empty lines carry no computation
+            } else {
+                return Err(std::boxed::Box::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Unexpected line in {} shader code: {line}", hook.name),
+                )));
+            }
+        }
+
+        Ok(Some(Self {
+            output: hook.output.clone(),
+            functions,
+            terms,
+            overlay,
+        }))
+    }
+
+    /// Looks up the texture read by a macro of this layer
+    fn texture_of(&self, func: &str) -> Result<&str, std::boxed::Box<dyn std::error::Error>> {
+        self.functions
+            .iter()
+            .find(|f| f.name == func)
+            .map(|f| f.texture.as_str())
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Unknown function: {func}")).into())
+    }
+
+    /// Generates the WGSL functions for the macros of this layer, prefixed with `s{index}_`
+    fn emit_functions(&self, index: usize, code: &mut String) {
+        for function in &self.functions {
+            code.push_str(&format!("fn s{index}_{}(p: vec2i) -> vec4f {{\n", function.name));
+            code.push_str(&format!("    let value = load_{}(p);\n", function.texture));
+            match &function.relu_sign {
+                Some(sign) => code.push_str(&format!("    return max({sign}value, vec4f());\n")),
+                None => code.push_str("    return value;\n"),
+            }
+            code.push_str("}\n\n");
+        }
+    }
+
+    /// Generates the WGSL statement for a product term, reading around `pos`
+    fn emit_product(index: usize, weights: &str, func: &str, offset: &Option<(String, String)>, edge_mode: EdgeMode) -> String {
+        let coordinate = match offset {
+            Some((x_offset, y_offset)) => edge_mode.offset_coordinate(x_offset, y_offset),
+            None => "pos".to_string(),
+        };
+        format!("result += mat4x4f({weights}) * s{index}_{func}({coordinate});\n")
+    }
+}
+
+/// Fuses the convolution hooks of a same-resolution CNN into a single compute shader
+///
+/// The generated shader binds the source texture at binding 0 and the result at binding 1,
+/// and processes 8x8 tiles. Intermediate layers are kept in workgroup memory, with slots
+/// reused once every later reader is done, and the final layer is accumulated per pixel
+/// as soon as each of its inputs is available. Halo texels are recomputed by every
+/// workgroup that needs them, which adds ALU work in exchange for fewer memory passes.
+///
+/// Restore CNN S and M fit in the workgroup memory budget; wider networks such as UL,
+/// and any network with upscaling or depth-to-space layers, cannot be fused.
+///
+/// # Arguments
+/// * `hooks` - Parsed hooks of the whole CNN, in execution order
+/// * `edge_mode` - How convolutions read texels outside the texture
+///
+/// # Returns
+/// The fused WGSL shader, or `None` if the network changes resolution, uses unsupported
+/// constructs, or needs more workgroup memory than is guaranteed to be available
+///
+/// # Errors
+/// Returns an error if a hook contains GLSL that cannot be parsed or references an unknown texture
+pub fn fuse_conv_hooks(hooks: &[MpvHook], edge_mode: EdgeMode) -> Result<Option<String>, std::boxed::Box<dyn std::error::Error>> {
+    let Some(last_hook) = hooks.last() else {
+        return Ok(None);
+    };
+    if last_hook.output != "dest" || hooks.iter().any(|hook| hook.scale_factor != 1 || hook.r#type != ConvolutionStageType::Conv) {
+        return Ok(None);
+    }
+
+    let mut stages = Vec::new();
+    for hook in hooks {
+        match FusedStage::parse(hook)? {
+            Some(stage) => stages.push(stage),
+            None => return Ok(None),
+        }
+    }
+    let last = stages.len() - 1;
+
+    // Intermediate layers are addressed by name, so every one must be written exactly once
+    let mut producers = HashMap::new();
+    for (index, stage) in stages[..last].iter().enumerate() {
+        if stage.overlay.is_some() || stage.output == "source" || stage.output == "dest" || producers.insert(stage.output.as_str(), index).is_some() {
+            return Ok(None);
+        }
+    }
+    for (index, stage) in stages.iter().enumerate() {
+        for function in &stage.functions {
+            let is_available = function.texture == "source" || producers.get(function.texture.as_str()).is_some_and(|&producer| producer < index);
+            if !is_available {
+                return Err(std::boxed::Box::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Unknown texture: {}", function.texture),
+                )));
+            }
+        }
+    }
+
+    // Walk backwards to find how far beyond the tile each layer must be computed
+    let mut halos: HashMap<&str, u32> = HashMap::new();
+    for (index, stage) in stages.iter().enumerate().rev() {
+        let output_halo = if index == last { 0 } else { halos.get(stage.output.as_str()).copied().unwrap_or(0) };
+        for term in &stage.terms {
+            if let Term::Product { func, offset, .. } = term {
+                let radius = match offset {
+                    Some((x_offset, y_offset)) if x_offset != "0" || y_offset != "0" => 1,
+                    _ => 0,
+                };
+                let halo = halos.entry(stage.texture_of(func)?).or_insert(0);
+                *halo = (*halo).max(output_halo + radius);
+            }
+        }
+    }
+    let region_size = |texture: &str| TILE_SIZE + 2 * halos.get(texture).copied().unwrap_or(0);
+
+    // A layer's slot can be overwritten once its last reader has passed the next barrier;
+    // the final layer reads each input right after the barrier following its producer
+    let mut last_uses: HashMap<&str, usize> = HashMap::new();
+    for (index, stage) in stages.iter().enumerate() {
+        for function in &stage.functions {
+            if let Some(&producer) = producers.get(function.texture.as_str()) {
+                let use_index = if index == last { producer + 1 } else { index };
+                let last_use = last_uses.entry(function.texture.as_str()).or_insert(producer);
+                *last_use = (*last_use).max(use_index);
+            }
+        }
+    }
+    let mut slots: Vec<(u32, usize)> = Vec::new();
+    let mut slot_of = Vec::new();
+    for (index, stage) in stages[..last].iter().enumerate() {
+        let texels = region_size(&stage.output).pow(2);
+        let free_after = last_uses.get(stage.output.as_str()).copied().unwrap_or(index);
+        match slots.iter().position(|&(_, busy_until)| busy_until < index) {
+            Some(slot) => {
+                slots[slot] = (slots[slot].0.max(texels), free_after);
+                slot_of.push(slot);
+            }
+            None => {
+                slots.push((texels, free_after));
+                slot_of.push(slots.len() - 1);
+            }
+        }
+    }
+    if slots.iter().map(|&(texels, _)| texels * TEXEL_SIZE).sum::<u32>() > MAX_WORKGROUP_STORAGE_SIZE {
+        return Ok(None);
+    }
+
+    let mut code = String::new();
+    code.push_str(&format!("// Fused: {} layers\n", stages.len()));
+    code.push_str(&format!(
+        "// Tile: {TILE_SIZE}x{TILE_SIZE}, workgroup memory: {} bytes\n",
+        slots.iter().map(|&(texels, _)| texels * TEXEL_SIZE).sum::<u32>()
+    ));
+    code.push('\n');
+    code.push_str("@group(0) @binding(0) var source_tex: texture_2d<f32>;\n");
+    code.push_str("@group(0) @binding(1) var result_tex: texture_storage_2d<rgba32float, write>;\n");
+    code.push('\n');
+    for (slot, &(texels, _)) in slots.iter().enumerate() {
+        code.push_str(&format!("var<workgroup> slot_{slot}: array<vec4f, {texels}>;\n"));
+    }
+    code.push_str("var<private> tile_origin: vec2i;\n");
+    code.push('\n');
+
+    // Loaders resolve an image position to the texel of the tile region holding it
+    code.push_str("fn load_source(p: vec2i) -> vec4f {\n");
+    code.push_str("    return textureLoad(source_tex, p, 0);\n");
+    code.push_str("}\n\n");
+    for (index, stage) in stages[..last].iter().enumerate() {
+        let halo = halos.get(stage.output.as_str()).copied().unwrap_or(0);
+        code.push_str(&format!("fn load_{}(p: vec2i) -> vec4f {{\n", stage.output));
+        code.push_str(&format!("    let local = p - tile_origin + {halo};\n"));
+        code.push_str(&format!("    return slot_{}[local.y * {} + local.x];\n", slot_of[index], region_size(&stage.output)));
+        code.push_str("}\n\n");
+    }
+
+    for (index, stage) in stages.iter().enumerate() {
+        stage.emit_functions(index, &mut code);
+        if index == last {
+            continue;
+        }
+        code.push_str(&format!("fn stage_{index}(pos: vec2i, bound: vec2i) -> vec4f {{\n"));
+        code.push_str("    var result = vec4f();\n");
+        for term in &stage.terms {
+            match term {
+                Term::Product { weights, func, offset } => code.push_str(&format!("    {}", FusedStage::emit_product(index, weights, func, offset, edge_mode))),
+                Term::Bias { weights } => code.push_str(&format!("    result += vec4f({weights});\n")),
+            }
+        }
+        code.push_str("    return result;\n");
+        code.push_str("}\n\n");
+    }
+
+    // Every invocation must reach the barriers, so bounds are only checked when storing
+    for entry_point in ["main", "main_unchecked"] {
+        code.push_str(&format!("@compute @workgroup_size({TILE_SIZE}, {TILE_SIZE})\n"));
+        code.push_str(&format!(
+            "fn {entry_point}(@builtin(workgroup_id) tile: vec3u, @builtin(local_invocation_id) local: vec3u, @builtin(local_invocation_index) index: u32) {{\n"
+        ));
+        code.push_str("    process(vec2i(tile.xy), vec2i(local.xy), index);\n");
+        code.push_str("}\n\n");
+    }
+
+    let final_stage = &stages[last];
+    let emit_final_terms = |code: &mut String, texture: &str| -> Result<(), std::boxed::Box<dyn std::error::Error>> {
+        for term in &final_stage.terms {
+            if let Term::Product { weights, func, offset } = term
+                && final_stage.texture_of(func)? == texture
+            {
+                code.push_str(&format!("    {}", FusedStage::emit_product(last, weights, func, offset, edge_mode)));
+            }
+        }
+        Ok(())
+    };
+
+    code.push_str("fn process(tile: vec2i, local: vec2i, index: u32) {\n");
+    code.push_str(&format!("    tile_origin = tile * {TILE_SIZE};\n"));
+    code.push_str("    let bound = vec2i(textureDimensions(source_tex)) - 1;\n");
+    code.push_str("    let pixel = tile_origin + local;\n");
+    code.push_str("    let pos = min(pixel, bound);\n");
+    code.push_str("    var result = vec4f();\n");
+    emit_final_terms(&mut code, "source")?;
+    for (index, stage) in stages[..last].iter().enumerate() {
+        let halo = halos.get(stage.output.as_str()).copied().unwrap_or(0);
+        let size = region_size(&stage.output);
+        code.push_str(&format!("    for (var i = index; i < {}u; i += {}u) {{\n", size * size, TILE_SIZE * TILE_SIZE));
+        code.push_str(&format!("        let p = tile_origin - {halo} + vec2i(i32(i % {size}u), i32(i / {size}u));\n"));
+        code.push_str(&format!("        slot_{}[i] = stage_{index}(clamp(p, vec2i(0), bound), bound);\n", slot_of[index]));
+        code.push_str("    }\n");
+        code.push_str("    workgroupBarrier();\n");
+        emit_final_terms(&mut code, &stage.output)?;
+    }
+    for term in &final_stage.terms {
+        if let Term::Bias { weights } = term {
+            code.push_str(&format!("    result += vec4f({weights});\n"));
+        }
+    }
+    code.push_str("    if (all(pixel <= bound)) {\n");
+    match &final_stage.overlay {
+        Some(factor) => code.push_str(&format!("        textureStore(result_tex, pixel, result{factor} + textureLoad(source_tex, pos, 0));\n")),
+        None => code.push_str("        textureStore(result_tex, pixel, result);\n"),
+    }
+    code.push_str("    }\n");
+    code.push_str("}\n");
+
+    Ok(Some(code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cnn::validate_wgsl;
+
+    /// Parses every hook of a bundled GLSL shader
+    fn load_hooks(relative_path: &str) -> Vec<MpvHook> {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../anime4k-glsl").join(relative_path);
+        let source = std::fs::read_to_string(path).unwrap();
+
+        let mut scale_factor_map = MpvHook::new_scale_factor_map();
+        MpvHook::parse_mpv_hooks(&source)
+            .iter()
+            .map(|pass_source| MpvHook::new(pass_source, &mut scale_factor_map).unwrap())
+            .collect()
+    }
+
+    /// Tests that the small and medium restore CNNs fuse into valid WGSL in both edge modes
+    #[test]
+    fn test_fuse_restore() {
+        for path in ["Restore/Anime4K_Restore_CNN_S.glsl", "Restore/Anime4K_Restore_CNN_M.glsl"] {
+            let hooks = load_hooks(path);
+            for edge_mode in [EdgeMode::Clamp, EdgeMode::Mirror] {
+                let code = fuse_conv_hooks(&hooks, edge_mode).unwrap().unwrap_or_else(|| panic!("{path} should be fusable"));
+                validate_wgsl(&code).unwrap_or_else(|e| panic!("{path} fused shader is invalid: {e}\n{code}"));
+                assert_eq!(code.matches("workgroupBarrier()").count(), hooks.len() - 1);
+            }
+        }
+    }
+
+    /// Tests that networks changing resolution or exceeding the memory budget are not fused
+    #[test]
+    fn test_fuse_fallback() {
+        for path in ["Upscale/Anime4K_Upscale_CNN_x2_S.glsl", "Restore/Anime4K_Restore_CNN_UL.glsl"] {
+            let hooks = load_hooks(path);
+            assert!(fuse_conv_hooks(&hooks, EdgeMode::Clamp).unwrap().is_none(), "{path} should not be fusable");
+        }
+    }
+}
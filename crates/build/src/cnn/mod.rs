@@ -4,5 +4,15 @@
 //! Anime4K implementation to WGSL format suitable for wgpu execution.
 
 mod convert;
+mod depth_to_space;
+mod fuse;
+#[cfg(feature = "spirv")]
+mod spirv;
+mod validate;
 
 pub use convert::*;
+pub use depth_to_space::*;
+pub use fuse::fuse_conv_hooks;
+#[cfg(feature = "spirv")]
+pub use spirv::wgsl_to_spirv;
+pub use validate::{dump_wgsl_ir, validate_wgsl};
@@ -0,0 +1,111 @@
+//! naga validation of converted CNN shaders
+//!
+//! This module runs the WGSL of converted stages through naga's parser and validator
+//! and reports failures as the full rendered diagnostic, including the source spans,
+//! instead of the one-line summary wgpu gives when pipeline creation fails.
+
+use super::{WgslStageShader, WgslStageShaderType, generate_depth_to_space_wgsl};
+
+impl WgslStageShader {
+    /// Returns the complete WGSL source of this stage
+    ///
+    /// Depth-to-space stages are generated, which is equivalent to the hand-written helpers.
+    ///
+    /// # Returns
+    /// The WGSL source, or an error if a depth-to-space stage has an unsupported layout
+    pub fn wgsl_code(&self) -> Result<String, std::boxed::Box<dyn std::error::Error>> {
+        match &self.r#type {
+            WgslStageShaderType::Conv { code } => Ok(code.clone()),
+            WgslStageShaderType::DepthToSpace { components } => generate_depth_to_space_wgsl(components - 1, self.scale_factor.parse()?),
+        }
+    }
+
+    /// Parses and validates this stage with naga
+    ///
+    /// # Returns
+    /// `Ok(())` if the shader is valid, or the rendered diagnostic with source spans
+    pub fn validate(&self) -> Result<(), String> {
+        validate_wgsl(&self.wgsl_code().map_err(|e| format!("{}: {e}", self.name))?)
+    }
+
+    /// Dumps the naga IR of this stage
+    ///
+    /// # Returns
+    /// The pretty-printed naga module, or the rendered diagnostic if the shader is invalid
+    pub fn naga_ir(&self) -> Result<String, String> {
+        dump_wgsl_ir(&self.wgsl_code().map_err(|e| format!("{}: {e}", self.name))?)
+    }
+}
+
+/// Parses and validates a WGSL shader with naga
+///
+/// # Arguments
+/// * `code` - WGSL shader source
+///
+/// # Returns
+/// The parsed module, or the rendered parse or validation diagnostic with source spans
+fn parse_and_validate(code: &str) -> Result<naga::Module, String> {
+    let module = naga::front::wgsl::parse_str(code).map_err(|e| e.emit_to_string(code))?;
+    naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::default())
+        .validate(&module)
+        .map_err(|e| e.emit_to_string(code))?;
+
+    Ok(module)
+}
+
+/// Parses and validates a WGSL shader with naga
+///
+/// # Arguments
+/// * `code` - WGSL shader source
+///
+/// # Returns
+/// `Ok(())` if the shader is valid, or the rendered diagnostic with source spans
+pub fn validate_wgsl(code: &str) -> Result<(), String> {
+    parse_and_validate(code).map(|_| ())
+}
+
+/// Dumps the naga IR of a WGSL shader
+///
+/// The shader is validated first, so the dump always describes a valid module.
+///
+/// # Arguments
+/// * `code` - WGSL shader source
+///
+/// # Returns
+/// The pretty-printed naga module, or the rendered diagnostic if the shader is invalid
+pub fn dump_wgsl_ir(code: &str) -> Result<String, String> {
+    parse_and_validate(code).map(|module| format!("{module:#?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cnn::MpvHook;
+
+    /// Tests that every stage of a CNN, including depth-to-space, passes validation
+    #[test]
+    fn test_validate_cnn_stages() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../anime4k-glsl/Upscale/Anime4K_Upscale_CNN_x2_S.glsl");
+        let source = std::fs::read_to_string(path).unwrap();
+
+        let mut scale_factor_map = MpvHook::new_scale_factor_map();
+        for pass_source in MpvHook::parse_mpv_hooks(&source) {
+            let hook = MpvHook::new(&pass_source, &mut scale_factor_map).unwrap();
+            let shader = WgslStageShader::new(hook, &scale_factor_map).unwrap();
+
+            shader.validate().unwrap();
+            assert!(shader.naga_ir().unwrap().contains("entry_points"), "missing entry points in IR of {}", shader.name);
+        }
+    }
+
+    /// Tests that syntax and type errors are reported with their source location
+    #[test]
+    fn test_validate_reports_spans() {
+        let syntax_error = validate_wgsl("@compute @workgroup_size(8, 8)\nfn main() {\n    let x = ;\n}\n").unwrap_err();
+        assert!(syntax_error.contains("wgsl:3:"), "{syntax_error}");
+
+        let type_error = validate_wgsl("@compute @workgroup_size(8, 8)\nfn main() {\n    let x: f32 = 1u;\n}\n").unwrap_err();
+        assert!(type_error.contains("wgsl:3:"), "{type_error}");
+        assert!(dump_wgsl_ir("fn main(").is_err());
+    }
+}
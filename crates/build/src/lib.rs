@@ -7,36 +7,44 @@
 mod minify;
 
 pub mod cnn;
+pub mod mpv;
 pub mod pipelines;
 pub mod predefined;
 
 pub use minify::minify_wgsl;
 
-/// Converts a CNN/GAN GLSL shader file to an optimized ExecutablePipeline
+/// Converts a CNN/GAN GLSL shader file to a pipeline manifest and its WGSL shaders
 ///
 /// This function processes mpv-style GLSL hooks used in original Anime4K implementations
-/// and converts them to WGSL compute shaders with optimized resource allocation.
+/// and converts them to WGSL compute shaders along with the manifest describing their passes.
 ///
 /// # Arguments
 /// * `glsl_filepath` - Path to the GLSL shader file containing mpv hooks
 /// * `helpers_dir` - Directory containing WGSL helper functions
 /// * `minify` - Whether to minify the WGSL code
+/// * `annotate_source` - Whether to emit `// from GLSL: <line>` above each translated statement;
+///   minifying removes these comments again
+/// * `edge_mode` - How convolutions read texels outside the texture
 ///
 /// # Returns
-/// An ExecutablePipeline ready for GPU execution
-pub fn cnn_glsl_to_executable_pipeline(glsl_filepath: &str, helpers_dir: &str, minify: bool) -> Result<pipelines::ExecutablePipeline, std::boxed::Box<dyn std::error::Error>> {
-    use std::collections::HashMap;
-
+/// The pipeline specification and an asset map containing every shader it references
+pub fn cnn_glsl_to_pipeline_spec(
+    glsl_filepath: &str,
+    helpers_dir: &str,
+    minify: bool,
+    annotate_source: bool,
+    edge_mode: cnn::EdgeMode,
+) -> Result<(pipelines::PipelineSpec, pipelines::AssetMap), std::boxed::Box<dyn std::error::Error>> {
     let mpv_hook_source = std::fs::read_to_string(glsl_filepath)?;
     let pass_sources = cnn::MpvHook::parse_mpv_hooks(&mpv_hook_source);
 
-    let mut files = HashMap::new();
+    let mut files = pipelines::AssetMap::new();
     let mut passes = Vec::new();
     let mut scale_factor_map = cnn::MpvHook::new_scale_factor_map();
     for (pass_index, pass_source) in pass_sources.iter().enumerate() {
         // Parse the pass source to create a WGSL shader
         let hook = cnn::MpvHook::new(pass_source, &mut scale_factor_map)?;
-        let wgsl_shader = cnn::WgslStageShader::new(hook, &scale_factor_map)?;
+        let wgsl_shader = cnn::WgslStageShader::new_with_options(hook, &scale_factor_map, annotate_source, edge_mode)?;
 
         // Generate the filename and code for the WGSL shader
         let (filename, code) = if let cnn::WgslStageShaderType::Conv { code } = &wgsl_shader.r#type {
@@ -44,7 +52,13 @@ pub fn cnn_glsl_to_executable_pipeline(glsl_filepath: &str, helpers_dir: &str, m
             (filename, code.clone())
         } else {
             let filename = format!("depth_to_space_in{}x{}.wgsl", wgsl_shader.inputs.len() - 1, wgsl_shader.scale_factor);
-            let code = std::fs::read_to_string(format!("{helpers_dir}/{filename}"))?;
+            let helper_path = std::path::Path::new(helpers_dir).join(&filename);
+            // Fall back to a generated shader when no hand-written helper exists (e.g. scale 3)
+            let code = if helper_path.exists() {
+                std::fs::read_to_string(helper_path)?
+            } else {
+                cnn::generate_depth_to_space_wgsl(wgsl_shader.inputs.len() as u32 - 1, wgsl_shader.scale_factor.parse()?)?
+            };
             (filename, code)
         };
 
@@ -58,10 +72,18 @@ pub fn cnn_glsl_to_executable_pipeline(glsl_filepath: &str, helpers_dir: &str, m
         passes.push(pipelines::Pass {
             id: format!("Pass {}", pass_index + 1),
             file: filename,
+            // Declare the scale each hook was translated for, so validation catches a mismatch with the producing pass
             inputs: wgsl_shader
                 .inputs
                 .iter()
-                .map(|(binding, id)| pipelines::TextureBindingSpec { binding: *binding, id: id.clone() })
+                .map(|(binding, id)| {
+                    let input_scale_factor = scale_factor_map[&wgsl_shader.source.inputs[*binding as usize]];
+                    pipelines::TextureBindingSpec {
+                        binding: *binding,
+                        id: id.clone(),
+                        scale_factor: Some([pipelines::ScaleFactor::new(input_scale_factor, 1); 2]),
+                    }
+                })
                 .collect(),
             outputs: vec![pipelines::TextureOutput {
                 binding: wgsl_shader.output.0,
@@ -91,19 +113,213 @@ pub fn cnn_glsl_to_executable_pipeline(glsl_filepath: &str, helpers_dir: &str, m
         id: "anime4k_cnn".to_string(),
         name: "Anime4K CNN".to_string(),
         description: None,
+        constants: Default::default(),
         passes,
     };
 
-    let pipeline = pipelines::ExecutablePipeline::from_raw(spec, |filename: &str| {
-        files
-            .get(filename)
-            .cloned()
-            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, format!("File not found: {filename}")))
-    })?;
+    Ok((spec, files))
+}
+
+/// Converts a CNN/GAN GLSL shader file to an optimized ExecutablePipeline
+///
+/// This function processes mpv-style GLSL hooks used in original Anime4K implementations
+/// and converts them to WGSL compute shaders with optimized resource allocation.
+///
+/// # Arguments
+/// * `glsl_filepath` - Path to the GLSL shader file containing mpv hooks
+/// * `helpers_dir` - Directory containing WGSL helper functions
+/// * `minify` - Whether to minify the WGSL code
+/// * `annotate_source` - Whether to annotate the WGSL with the GLSL lines it was translated from
+/// * `edge_mode` - How convolutions read texels outside the texture
+///
+/// # Returns
+/// An ExecutablePipeline ready for GPU execution
+pub fn cnn_glsl_to_executable_pipeline(
+    glsl_filepath: &str,
+    helpers_dir: &str,
+    minify: bool,
+    annotate_source: bool,
+    edge_mode: cnn::EdgeMode,
+) -> Result<pipelines::ExecutablePipeline, std::boxed::Box<dyn std::error::Error>> {
+    let (spec, files) = cnn_glsl_to_pipeline_spec(glsl_filepath, helpers_dir, minify, annotate_source, edge_mode)?;
+    spec.validate()?;
+    let pipeline = pipelines::ExecutablePipeline::from_raw(spec, |filename: &str| files.load(filename))?;
+    pipeline.validate_shader_bindings()?;
 
     Ok(pipeline)
 }
 
+/// Converts a same-resolution CNN GLSL shader file to a single-pass pipeline manifest
+///
+/// All hooks are fused into one compute shader with `cnn::fuse_conv_hooks`, which reads
+/// SOURCE once and writes RESULT without intermediate textures.
+///
+/// # Arguments
+/// * `glsl_filepath` - Path to the GLSL shader file containing mpv hooks
+/// * `minify` - Whether to minify the WGSL code
+/// * `edge_mode` - How convolutions read texels outside the texture
+///
+/// # Returns
+/// The pipeline specification and its fused shader, or `None` if the CNN cannot be fused
+pub fn cnn_glsl_to_fused_pipeline_spec(
+    glsl_filepath: &str,
+    minify: bool,
+    edge_mode: cnn::EdgeMode,
+) -> Result<Option<(pipelines::PipelineSpec, pipelines::AssetMap)>, std::boxed::Box<dyn std::error::Error>> {
+    let mpv_hook_source = std::fs::read_to_string(glsl_filepath)?;
+    let mut scale_factor_map = cnn::MpvHook::new_scale_factor_map();
+    let hooks = cnn::MpvHook::parse_mpv_hooks(&mpv_hook_source)
+        .iter()
+        .map(|pass_source| cnn::MpvHook::new(pass_source, &mut scale_factor_map))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let Some(code) = cnn::fuse_conv_hooks(&hooks, edge_mode)? else {
+        return Ok(None);
+    };
+    let code = if minify { minify_wgsl(&code)? } else { code };
+
+    let filename = "fused.wgsl".to_string();
+    let mut files = pipelines::AssetMap::new();
+    files.insert(filename.clone(), code);
+
+    let spec = pipelines::PipelineSpec {
+        id: "anime4k_cnn".to_string(),
+        name: "Anime4K CNN".to_string(),
+        description: None,
+        constants: Default::default(),
+        passes: vec![pipelines::Pass {
+            id: "Fused".to_string(),
+            file: filename,
+            inputs: vec![pipelines::TextureBindingSpec {
+                binding: 0,
+                id: "SOURCE".to_string(),
+                scale_factor: Some([pipelines::ScaleFactor::new(1, 1); 2]),
+            }],
+            outputs: vec![pipelines::TextureOutput {
+                binding: 1,
+                id: "RESULT".to_string(),
+                components: 4,
+                scale_factor: [pipelines::ScaleFactor::new(1, 1); 2],
+            }],
+            samplers: vec![],
+        }],
+    };
+
+    Ok(Some((spec, files)))
+}
+
+/// Converts a CNN/GAN GLSL shader file to an ExecutablePipeline, fusing it when possible
+///
+/// Same-resolution CNNs become a single pass as with `cnn_glsl_to_fused_pipeline_spec`;
+/// every other shader falls back to `cnn_glsl_to_executable_pipeline`.
+///
+/// # Arguments
+/// * `glsl_filepath` - Path to the GLSL shader file containing mpv hooks
+/// * `helpers_dir` - Directory containing WGSL helper functions, used by the fallback
+/// * `minify` - Whether to minify the WGSL code
+/// * `annotate_source` - Whether the fallback annotates the WGSL with the GLSL lines it was
+///   translated from; fused shaders have no line-by-line translation to annotate
+/// * `edge_mode` - How convolutions read texels outside the texture
+///
+/// # Returns
+/// An ExecutablePipeline ready for GPU execution
+pub fn cnn_glsl_to_executable_pipeline_fused(
+    glsl_filepath: &str,
+    helpers_dir: &str,
+    minify: bool,
+    annotate_source: bool,
+    edge_mode: cnn::EdgeMode,
+) -> Result<pipelines::ExecutablePipeline, std::boxed::Box<dyn std::error::Error>> {
+    let Some((spec, files)) = cnn_glsl_to_fused_pipeline_spec(glsl_filepath, minify, edge_mode)? else {
+        return cnn_glsl_to_executable_pipeline(glsl_filepath, helpers_dir, minify, annotate_source, edge_mode);
+    };
+    spec.validate()?;
+    let pipeline = pipelines::ExecutablePipeline::from_raw(spec, |filename: &str| files.load(filename))?;
+    pipeline.validate_shader_bindings()?;
+
+    Ok(pipeline)
+}
+
+/// Converts every CNN/GAN GLSL shader in a directory tree to manifests and WGSL files
+///
+/// Each `.glsl` file found under `src_dir` is written to `out_dir`, mirroring its relative
+/// location, as a directory named after the file stem containing `manifest.yaml` and the
+/// WGSL shaders it references. Files that fail to convert are logged as `tracing` warnings
+/// and skipped, so a single unsupported shader does not abort the batch. Progress is logged
+/// at the info level.
+///
+/// # Arguments
+/// * `src_dir` - Directory to search recursively for GLSL shader files
+/// * `helpers_dir` - Directory containing WGSL helper functions
+/// * `out_dir` - Directory to write the converted pipelines into
+/// * `minify` - Whether to minify the WGSL code
+/// * `annotate_source` - Whether to annotate the WGSL with the GLSL lines it was translated from
+/// * `edge_mode` - How convolutions read texels outside the texture
+///
+/// # Returns
+/// The paths of all manifests that were written, or an error if a directory could not be read or written
+pub fn convert_glsl_dir(
+    src_dir: impl AsRef<std::path::Path>,
+    helpers_dir: &str,
+    out_dir: impl AsRef<std::path::Path>,
+    minify: bool,
+    annotate_source: bool,
+    edge_mode: cnn::EdgeMode,
+) -> Result<Vec<std::path::PathBuf>, std::boxed::Box<dyn std::error::Error>> {
+    let src_dir = src_dir.as_ref();
+    let out_dir = out_dir.as_ref();
+
+    // Collect GLSL files up front so the conversion order is deterministic
+    let mut glsl_files = Vec::new();
+    let mut pending_dirs = vec![src_dir.to_path_buf()];
+    while let Some(dir) = pending_dirs.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending_dirs.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "glsl") {
+                glsl_files.push(path);
+            }
+        }
+    }
+    glsl_files.sort();
+
+    let mut manifests = Vec::new();
+    let mut failed_count = 0;
+    for glsl_file in &glsl_files {
+        let relative_path = glsl_file.strip_prefix(src_dir)?;
+        let stem = glsl_file.file_stem().unwrap().to_string_lossy().to_string();
+
+        let (mut spec, files) = match cnn_glsl_to_pipeline_spec(&glsl_file.to_string_lossy(), helpers_dir, minify, annotate_source, edge_mode) {
+            Ok(result) => result,
+            Err(e) => {
+                tracing::warn!("Failed to convert {}: {e}", relative_path.display());
+                failed_count += 1;
+                continue;
+            }
+        };
+        spec.id = stem.clone();
+        spec.name = stem.clone();
+
+        // Write the referenced shaders next to the manifest
+        let pipeline_dir = out_dir.join(relative_path.with_file_name(&stem));
+        std::fs::create_dir_all(&pipeline_dir)?;
+        for pass in &spec.passes {
+            std::fs::write(pipeline_dir.join(&pass.file), files.load(&pass.file)?)?;
+        }
+
+        let manifest_path = pipeline_dir.join("manifest.yaml");
+        std::fs::write(&manifest_path, spec.to_yaml()?)?;
+
+        tracing::info!("Converted {} -> {}", relative_path.display(), manifest_path.display());
+        manifests.push(manifest_path);
+    }
+
+    tracing::info!("Converted {} of {} GLSL files ({failed_count} failed)", manifests.len(), glsl_files.len());
+
+    Ok(manifests)
+}
+
 /// Converts a WGSL manifest file to an optimized ExecutablePipeline
 ///
 /// Loads a YAML pipeline manifest and compiles it into an ExecutablePipeline
@@ -117,19 +333,20 @@ pub fn cnn_glsl_to_executable_pipeline(glsl_filepath: &str, helpers_dir: &str, m
 /// An ExecutablePipeline ready for GPU execution
 pub fn wgsl_to_executable_pipeline(wgsl_manifest_filepath: &str, minify: bool) -> Result<pipelines::ExecutablePipeline, std::boxed::Box<dyn std::error::Error>> {
     let dir = std::path::Path::new(wgsl_manifest_filepath).parent().unwrap();
-    pipelines::ExecutablePipeline::from_file(wgsl_manifest_filepath, |filename: &str| {
+    let spec = pipelines::PipelineSpec::from_file(wgsl_manifest_filepath)?;
+    spec.validate()?;
+    let mut pipeline = spec.compile(|filename: &str| {
         let path = dir.join(filename);
-        let code = std::fs::read_to_string(&path).inspect_err(|e| {
-            eprintln!("Error reading file {path:?}: {e}");
-        })?;
-        let code = if minify {
-            minify_wgsl(&code).map_err(|e| {
-                eprintln!("Error minifying WGSL code in file {path:?}: {e}");
-                std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Failed to minify WGSL code in file {path:?}"))
-            })?
-        } else {
-            code
-        };
-        Ok(code)
-    })
+        // Errors carry the file path, as the caller only sees the error returned by `compile`
+        std::fs::read_to_string(&path).map_err(|e| std::io::Error::new(e.kind(), format!("Failed to read file {path:?}: {e}")))
+    })?;
+    // `compile` substitutes the manifest constants, whose placeholders are not valid WGSL, so minification comes after it
+    if minify {
+        for pass in &mut pipeline.passes {
+            pass.shader = minify_wgsl(&pass.shader).map_err(|e| format!("Failed to minify WGSL code of pass {}: {e}", pass.id))?;
+        }
+    }
+    pipeline.validate_shader_bindings()?;
+
+    Ok(pipeline)
 }
@@ -0,0 +1,166 @@
+//! Parsing of mpv user shader directives
+//!
+//! This module parses the dimension expressions of mpv `//!WIDTH` and `//!HEIGHT`
+//! directives, shared by the CNN converter and the GLSL reference engine so both
+//! accept exactly the same forms.
+
+use regex::Regex;
+use std::{collections::HashMap, sync::LazyLock};
+
+/// Matches `texture.w`, `texture.h`, and either followed by `factor *` or `factor /`
+static DIMENSION_EXPR_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*(\w+)\.([wh])(?:\s+(\d+)\s*([*/]))?\s*$").unwrap());
+
+/// Dimension of a texture referenced by an expression
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dimension {
+    /// The texture width (`.w`)
+    Width,
+    /// The texture height (`.h`)
+    Height,
+}
+
+/// Operator applied to the referenced dimension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleOp {
+    /// The dimension is multiplied by the factor (`*`)
+    Multiply,
+    /// The dimension is divided by the factor (`/`)
+    Divide,
+}
+
+/// A parsed mpv dimension expression such as `HOOKED.h 2 *`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScaleExpr {
+    /// Name of the referenced texture
+    pub texture: String,
+    /// Referenced dimension of the texture
+    pub dimension: Dimension,
+    /// Factor applied to the dimension (1 when the expression has none)
+    pub factor: u32,
+    /// Operator applied with the factor
+    pub op: ScaleOp,
+}
+
+impl ScaleExpr {
+    /// Applies the expression to the size of the referenced dimension
+    ///
+    /// Division floors like mpv, but never takes a non-empty dimension below one pixel.
+    ///
+    /// # Arguments
+    /// * `base_size` - Size of the referenced texture dimension in pixels
+    ///
+    /// # Returns
+    /// The resulting size in pixels
+    pub fn apply(&self, base_size: u32) -> u32 {
+        match self.op {
+            ScaleOp::Multiply => base_size * self.factor,
+            ScaleOp::Divide => (base_size / self.factor).max(base_size.min(1)),
+        }
+    }
+}
+
+/// Parses an mpv `//!WIDTH` or `//!HEIGHT` expression
+///
+/// Supported forms are `TEX.w`, `TEX.h`, and either followed by a factor and `*` or `/`,
+/// e.g. `MAIN.w`, `HOOKED.h 2 *`, `MAIN.h 2 /`. Surrounding whitespace is ignored.
+///
+/// # Arguments
+/// * `expr` - The expression, without the directive name
+/// * `scale_map` - Textures that may be referenced, keyed by name
+///
+/// # Returns
+/// The parsed expression, or an `InvalidData` error if the expression is malformed,
+/// has a zero factor, or references a texture missing from `scale_map`
+pub fn parse_dimension_expr<V>(expr: &str, scale_map: &HashMap<String, V>) -> Result<ScaleExpr, Box<dyn std::error::Error>> {
+    let invalid = |message: String| Box::new(std::io::Error::new(std::io::ErrorKind::InvalidData, message));
+
+    let captures = DIMENSION_EXPR_RE.captures(expr).ok_or_else(|| invalid(format!("Invalid dimension expression: '{expr}'")))?;
+
+    let texture = captures[1].to_string();
+    if !scale_map.contains_key(&texture) {
+        return Err(invalid(format!("Unknown texture '{texture}' in dimension expression '{expr}'")));
+    }
+
+    let dimension = if &captures[2] == "w" { Dimension::Width } else { Dimension::Height };
+
+    let (factor, op) = match (captures.get(3), captures.get(4)) {
+        (Some(factor), Some(op)) => (
+            factor.as_str().parse::<u32>().map_err(|e| invalid(format!("Invalid factor in dimension expression '{expr}': {e}")))?,
+            if op.as_str() == "*" { ScaleOp::Multiply } else { ScaleOp::Divide },
+        ),
+        _ => (1, ScaleOp::Multiply),
+    };
+    if factor == 0 {
+        return Err(invalid(format!("Factor cannot be zero in dimension expression '{expr}'")));
+    }
+
+    Ok(ScaleExpr { texture, dimension, factor, op })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a scale map with the textures referenced by the tests
+    fn scale_map() -> HashMap<String, u32> {
+        HashMap::from([("MAIN".to_string(), 1), ("HOOKED".to_string(), 1), ("conv2d_tf".to_string(), 2)])
+    }
+
+    /// Tests a bare texture dimension
+    #[test]
+    fn test_parse_plain() {
+        let expr = parse_dimension_expr("MAIN.w", &scale_map()).unwrap();
+        assert_eq!(
+            expr,
+            ScaleExpr {
+                texture: "MAIN".to_string(),
+                dimension: Dimension::Width,
+                factor: 1,
+                op: ScaleOp::Multiply,
+            }
+        );
+        assert_eq!(expr.apply(640), 640);
+    }
+
+    /// Tests a multiplied dimension
+    #[test]
+    fn test_parse_multiply() {
+        let expr = parse_dimension_expr("HOOKED.h 2 *", &scale_map()).unwrap();
+        assert_eq!(expr.texture, "HOOKED");
+        assert_eq!(expr.dimension, Dimension::Height);
+        assert_eq!((expr.factor, expr.op), (2, ScaleOp::Multiply));
+        assert_eq!(expr.apply(360), 720);
+    }
+
+    /// Tests a divided dimension, which floors but never reaches zero
+    #[test]
+    fn test_parse_divide() {
+        let expr = parse_dimension_expr("MAIN.h 2 /", &scale_map()).unwrap();
+        assert_eq!((expr.factor, expr.op), (2, ScaleOp::Divide));
+        assert_eq!(expr.apply(7), 3);
+        assert_eq!(expr.apply(1), 1);
+        assert_eq!(expr.apply(0), 0);
+    }
+
+    /// Tests that whitespace around and between tokens is accepted
+    #[test]
+    fn test_parse_whitespace() {
+        let expected = parse_dimension_expr("conv2d_tf.w 3 *", &scale_map()).unwrap();
+        assert_eq!(parse_dimension_expr("  conv2d_tf.w   3  * ", &scale_map()).unwrap(), expected);
+        assert_eq!(parse_dimension_expr("conv2d_tf.w 3*", &scale_map()).unwrap(), expected);
+    }
+
+    /// Tests rejection of malformed expressions, zero factors, and unknown textures
+    #[test]
+    fn test_parse_errors() {
+        let scale_map = scale_map();
+        assert!(parse_dimension_expr("MAIN.x", &scale_map).is_err());
+        assert!(parse_dimension_expr("MAIN.w 2", &scale_map).is_err());
+        assert!(parse_dimension_expr("MAIN.w 2 + ", &scale_map).is_err());
+        assert!(parse_dimension_expr("MAIN.w 0 /", &scale_map).is_err());
+        assert!(parse_dimension_expr("MAIN.w 2 * extra", &scale_map).is_err());
+
+        let error = parse_dimension_expr("OTHER.w", &scale_map).unwrap_err();
+        assert!(error.to_string().contains("OTHER"), "{error}");
+    }
+}
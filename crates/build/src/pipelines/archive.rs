@@ -0,0 +1,108 @@
+//! Loading pipelines from zip archives
+//!
+//! This module lets a custom pipeline be distributed as a single zip file holding
+//! its manifest and shaders. It is only available with the `archive` feature.
+
+use super::{ExecutablePipeline, PipelineSpec};
+use std::{cell::RefCell, io::Read};
+
+impl ExecutablePipeline {
+    /// Creates an ExecutablePipeline from a zip archive
+    ///
+    /// The archive must contain exactly one manifest (an entry ending in `.yaml` or `.yml`).
+    /// Shader files referenced by the manifest are resolved relative to the manifest's
+    /// directory within the archive, the same way `from_file` resolves them on disk.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the zip archive
+    ///
+    /// # Returns
+    /// An optimized ExecutablePipeline ready for GPU execution, or an error naming the
+    /// archive entry that could not be found or read
+    pub fn from_archive<P: AsRef<std::path::Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(path.as_ref())?)?;
+
+        let manifests: Vec<String> = archive.file_names().filter(|name| name.ends_with(".yaml") || name.ends_with(".yml")).map(str::to_string).collect();
+        let manifest_name = match manifests.as_slice() {
+            [name] => name.clone(),
+            [] => return Err(format!("Archive {} does not contain a manifest (.yaml or .yml entry)", path.as_ref().display()).into()),
+            _ => return Err(format!("Archive {} contains multiple manifests: {}", path.as_ref().display(), manifests.join(", ")).into()),
+        };
+        let base_dir = manifest_name.rsplit_once('/').map(|(dir, _)| format!("{dir}/")).unwrap_or_default();
+
+        let spec = PipelineSpec::from_yaml(&read_entry(&mut archive, &manifest_name)?)?;
+
+        let archive = RefCell::new(archive);
+        Ok(spec.compile(|file| read_entry(&mut archive.borrow_mut(), &format!("{base_dir}{}", file.replace('\\', "/").trim_start_matches("./"))))?)
+    }
+}
+
+/// Reads an archive entry as UTF-8 text
+///
+/// # Returns
+/// The entry content, or a `NotFound` error naming the expected entry if it is missing
+fn read_entry(archive: &mut zip::ZipArchive<std::fs::File>, name: &str) -> Result<String, std::io::Error> {
+    let mut entry = archive.by_name(name).map_err(|e| match e {
+        zip::result::ZipError::FileNotFound => std::io::Error::new(std::io::ErrorKind::NotFound, format!("Archive entry not found: {name}")),
+        e => std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Failed to read archive entry {name}: {e}")),
+    })?;
+
+    let mut content = String::new();
+    entry.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const MANIFEST: &str = r#"
+id: test_pipeline
+name: Test Pipeline
+passes:
+  - id: pass1
+    file: pass1.wgsl
+    inputs:
+      - id: SOURCE
+        binding: 0
+    outputs:
+      - id: RESULT
+        binding: 1
+        components: 4
+        scale_factor: ["2", "2"]
+"#;
+
+    /// Writes a zip archive with the given entries to a temporary file
+    fn write_archive(name: &str, entries: &[(&str, &str)]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("anime4k_wgpu_build_{name}_{}.zip", std::process::id()));
+        let mut writer = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        for (entry, content) in entries {
+            writer.start_file(*entry, zip::write::SimpleFileOptions::default()).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+        path
+    }
+
+    /// Tests loading a pipeline whose manifest lives in a subdirectory of the archive
+    #[test]
+    fn test_from_archive() {
+        let path = write_archive("valid", &[("custom/manifest.yaml", MANIFEST), ("custom/pass1.wgsl", "// pass1")]);
+        let pipeline = ExecutablePipeline::from_archive(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(pipeline.id, "test_pipeline");
+        assert_eq!(pipeline.passes[0].shader, "// pass1");
+    }
+
+    /// Tests that a missing shader is reported with the expected entry name
+    #[test]
+    fn test_from_archive_missing_shader() {
+        let path = write_archive("missing", &[("custom/manifest.yaml", MANIFEST)]);
+        let error = ExecutablePipeline::from_archive(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(error.to_string().contains("custom/pass1.wgsl"), "{error}");
+    }
+}
@@ -0,0 +1,112 @@
+//! In-memory asset storage for pipeline manifests and shaders
+//!
+//! This module provides a simple name-to-content map that can stand in for the
+//! filesystem when compiling pipelines, allowing fully embedded binaries to load
+//! manifests and WGSL shaders without any file I/O.
+
+use std::collections::HashMap;
+
+/// A set of named text assets (manifests, WGSL shaders) held in memory
+///
+/// Asset names are normalized on insertion and lookup so that `./pass1.wgsl`,
+/// `pass1.wgsl`, and Windows-style separators all refer to the same entry.
+/// Names are otherwise matched verbatim: there is no implicit directory, so a
+/// manifest that references `pass1.wgsl` requires an asset registered as `pass1.wgsl`.
+#[derive(Debug, Clone, Default)]
+pub struct AssetMap {
+    /// Asset contents keyed by normalized name
+    assets: HashMap<String, String>,
+}
+
+impl AssetMap {
+    /// Creates an empty asset map
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers an asset, returning the previous content if the name was already present
+    ///
+    /// # Arguments
+    /// * `name` - Asset name as referenced from manifests
+    /// * `content` - Text content of the asset
+    pub fn insert(&mut self, name: impl AsRef<str>, content: impl Into<String>) -> Option<String> {
+        self.assets.insert(Self::normalize_name(name.as_ref()), content.into())
+    }
+
+    /// Returns the content of an asset, or None if it is not registered
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.assets.get(&Self::normalize_name(name)).map(String::as_str)
+    }
+
+    /// Returns whether an asset with the given name is registered
+    pub fn contains(&self, name: &str) -> bool {
+        self.assets.contains_key(&Self::normalize_name(name))
+    }
+
+    /// Returns the number of registered assets
+    pub fn len(&self) -> usize {
+        self.assets.len()
+    }
+
+    /// Returns true if no assets are registered
+    pub fn is_empty(&self) -> bool {
+        self.assets.is_empty()
+    }
+
+    /// Iterates over all registered assets as (normalized name, content) in arbitrary order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.assets.iter().map(|(name, content)| (name.as_str(), content.as_str()))
+    }
+
+    /// Loads an asset in the form expected by shader loader closures
+    ///
+    /// # Returns
+    /// The asset content, or an `std::io::ErrorKind::NotFound` error if it is not registered
+    pub fn load(&self, name: &str) -> Result<String, std::io::Error> {
+        self.get(name)
+            .map(str::to_string)
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, format!("Asset not found: {name}")))
+    }
+
+    /// Normalizes an asset name for storage and lookup
+    ///
+    /// Converts backslashes to forward slashes and strips any leading `./` segments.
+    fn normalize_name(name: &str) -> String {
+        let mut normalized = name.replace('\\', "/");
+        while let Some(stripped) = normalized.strip_prefix("./") {
+            normalized = stripped.to_string();
+        }
+        normalized
+    }
+}
+
+impl<K: AsRef<str>, V: Into<String>> FromIterator<(K, V)> for AssetMap {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut assets = Self::new();
+        for (name, content) in iter {
+            assets.insert(name, content);
+        }
+        assets
+    }
+}
+
+impl From<HashMap<String, String>> for AssetMap {
+    fn from(assets: HashMap<String, String>) -> Self {
+        assets.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_asset_name_normalization() {
+        let assets: AssetMap = [("./shaders\\pass1.wgsl", "content")].into_iter().collect();
+
+        assert_eq!(assets.get("shaders/pass1.wgsl"), Some("content"));
+        assert_eq!(assets.get("./shaders/pass1.wgsl"), Some("content"));
+        assert!(assets.get("pass1.wgsl").is_none());
+        assert_eq!(assets.load("missing.wgsl").unwrap_err().kind(), std::io::ErrorKind::NotFound);
+    }
+}
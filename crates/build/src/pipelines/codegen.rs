@@ -0,0 +1,161 @@
+//! Rust code generation for embedding converted pipelines
+//!
+//! This module turns converted pipelines into a Rust source file that embeds their
+//! manifests and shaders as `const` byte arrays, so `build.rs` scripts can bake
+//! custom shader sets into a binary without runtime file loading.
+
+use super::{AssetMap, PipelineSpec};
+
+/// Converts bytes into a Rust byte string literal
+fn byte_string_literal(bytes: &[u8]) -> String {
+    format!("b\"{}\"", bytes.escape_ascii())
+}
+
+/// Generates a Rust module embedding the given pipelines
+///
+/// For every pipeline, the manifest (serialized from its spec) and each shader asset
+/// are emitted as `const` byte arrays. The module exposes `PIPELINE_IDS` and a
+/// `load_pipeline(id)` function that compiles the requested pipeline on demand via
+/// `ExecutablePipeline::from_manifest_str`, so nothing is compiled until it is used.
+///
+/// The output is meant to be written to `OUT_DIR` and pulled in with `include!`.
+///
+/// # Arguments
+/// * `pipelines` - Pipelines to embed as (id, spec, shader assets)
+/// * `crate_path` - Path of this crate as seen from the generated code (usually `anime4k_wgpu_build`)
+///
+/// # Returns
+/// The generated Rust source, or an error if a spec cannot be serialized or ids are duplicated
+pub fn generate_embedded_pipelines_module(pipelines: &[(&str, &PipelineSpec, &AssetMap)], crate_path: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let mut code = String::new();
+    code.push_str("// This file is generated by anime4k_wgpu_build::pipelines::generate_embedded_pipelines_module.\n\n");
+
+    for (index, (id, spec, assets)) in pipelines.iter().enumerate() {
+        if pipelines[..index].iter().any(|(other, _, _)| other == id) {
+            return Err(format!("Duplicate embedded pipeline id: {id}").into());
+        }
+
+        code.push_str(&format!("/// Manifest of embedded pipeline `{id}`\n"));
+        code.push_str(&format!("const PIPELINE_{index}_MANIFEST: &[u8] = {};\n\n", byte_string_literal(spec.to_yaml()?.as_bytes())));
+
+        // Sort assets so the generated file is deterministic
+        let mut entries: Vec<_> = assets.iter().collect();
+        entries.sort_unstable_by_key(|(name, _)| *name);
+
+        code.push_str(&format!("/// Shader assets of embedded pipeline `{id}`\n"));
+        code.push_str(&format!("const PIPELINE_{index}_ASSETS: &[(&str, &[u8])] = &[\n"));
+        for (name, content) in entries {
+            code.push_str(&format!("    ({name:?}, {}),\n", byte_string_literal(content.as_bytes())));
+        }
+        code.push_str("];\n\n");
+    }
+
+    code.push_str("/// IDs of all embedded pipelines\n");
+    code.push_str("pub const PIPELINE_IDS: &[&str] = &[\n");
+    for (id, _, _) in pipelines {
+        code.push_str(&format!("    {id:?},\n"));
+    }
+    code.push_str("];\n\n");
+
+    code.push_str("/// Compiles an embedded pipeline by ID, or returns None if no pipeline has that ID\n");
+    code.push_str(&format!(
+        "pub fn load_pipeline(id: &str) -> Option<Result<{crate_path}::pipelines::ExecutablePipeline, std::boxed::Box<dyn std::error::Error>>> {{\n"
+    ));
+    code.push_str("    let (manifest, assets) = match id {\n");
+    for (index, (id, _, _)) in pipelines.iter().enumerate() {
+        code.push_str(&format!("        {id:?} => (PIPELINE_{index}_MANIFEST, PIPELINE_{index}_ASSETS),\n"));
+    }
+    code.push_str("        _ => return None,\n");
+    code.push_str("    };\n\n");
+    code.push_str(&format!(
+        "    let compile = || -> Result<{crate_path}::pipelines::ExecutablePipeline, std::boxed::Box<dyn std::error::Error>> {{\n"
+    ));
+    code.push_str(&format!("        let mut asset_map = {crate_path}::pipelines::AssetMap::new();\n"));
+    code.push_str("        for (name, content) in assets {\n");
+    code.push_str("            asset_map.insert(name, std::str::from_utf8(content)?);\n");
+    code.push_str("        }\n");
+    code.push_str(&format!(
+        "        {crate_path}::pipelines::ExecutablePipeline::from_manifest_str(std::str::from_utf8(manifest)?, &asset_map)\n"
+    ));
+    code.push_str("    };\n");
+    code.push_str("    Some(compile())\n");
+    code.push_str("}\n");
+
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipelines::ExecutablePipeline;
+
+    /// Module generated from `MANIFEST` and `SHADER` below, checked in so the test proves it compiles
+    ///
+    /// `test_checked_in_module_is_up_to_date` keeps it identical to the generator output.
+    mod embedded {
+        include!("testdata/embedded_pipelines.rs");
+    }
+
+    const MANIFEST: &str = r#"
+id: test_pipeline
+name: Test Pipeline
+passes:
+  - id: pass1
+    file: pass1.wgsl
+    inputs:
+      - id: SOURCE
+        binding: 0
+    outputs:
+      - id: RESULT
+        binding: 1
+        components: 4
+        scale_factor: ["2", "2"]
+"#;
+
+    const SHADER: &str = "// \"quoted\" \\ shader\n@compute @workgroup_size(8, 8)\nfn main() {}\n";
+
+    /// Tests that the checked-in generated module compiles and round-trips the pipeline
+    #[test]
+    fn test_embedded_module_round_trip() {
+        let assets: AssetMap = [("pass1.wgsl", SHADER)].into_iter().collect();
+        let expected = ExecutablePipeline::from_manifest_str(MANIFEST, &assets).unwrap();
+
+        assert_eq!(embedded::PIPELINE_IDS, &["test_pipeline"]);
+        let loaded = embedded::load_pipeline("test_pipeline").unwrap().unwrap();
+        assert_eq!(serde_json::to_string(&loaded).unwrap(), serde_json::to_string(&expected).unwrap());
+        assert!(embedded::load_pipeline("missing").is_none());
+    }
+
+    /// Tests that generated code embeds every asset and a lookup arm per pipeline
+    #[test]
+    fn test_generate_embedded_pipelines_module() {
+        let spec = PipelineSpec::from_yaml(MANIFEST).unwrap();
+        let assets: AssetMap = [("pass1.wgsl", SHADER)].into_iter().collect();
+
+        let code = generate_embedded_pipelines_module(&[("test_pipeline", &spec, &assets)], "crate").unwrap();
+        assert!(code.contains(r#"("pass1.wgsl", b"// \"quoted\" \\ shader\n@compute @workgroup_size(8, 8)\nfn main() {}\n"),"#));
+        assert!(code.contains(r#""test_pipeline" => (PIPELINE_0_MANIFEST, PIPELINE_0_ASSETS),"#));
+
+        assert!(generate_embedded_pipelines_module(&[("a", &spec, &assets), ("a", &spec, &assets)], "crate").is_err());
+    }
+
+    /// Tests that the checked-in module is exactly what the generator produces
+    ///
+    /// Set `ANIME4K_UPDATE_EMBEDDED_TESTDATA` to rewrite the file after changing the generator.
+    #[test]
+    fn test_checked_in_module_is_up_to_date() {
+        let spec = PipelineSpec::from_yaml(MANIFEST).unwrap();
+        let assets: AssetMap = [("pass1.wgsl", SHADER)].into_iter().collect();
+        let code = generate_embedded_pipelines_module(&[("test_pipeline", &spec, &assets)], "crate").unwrap();
+
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/src/pipelines/testdata/embedded_pipelines.rs");
+        if std::env::var_os("ANIME4K_UPDATE_EMBEDDED_TESTDATA").is_some() {
+            std::fs::write(path, &code).unwrap();
+            return;
+        }
+        assert!(
+            code == include_str!("testdata/embedded_pipelines.rs"),
+            "{path} is out of date; rerun with ANIME4K_UPDATE_EMBEDDED_TESTDATA=1 to regenerate it"
+        );
+    }
+}
@@ -4,7 +4,10 @@
 //! human-readable pipeline specifications into GPU-optimized ExecutablePipeline
 //! structures with pre-allocated resources and optimal memory layouts.
 
-use super::{PhysicalTexture, PipelineSpec, SamplerBinding, SamplerFilterMode, ScaleFactor, TextureLifetime, physical_texture::assign_physical_textures};
+use super::{
+    AssetMap, PaddingViolation, PhysicalTexture, PipelineSpec, SamplerBinding, SamplerFilterMode, ScaleFactor, ShaderBindingKind, TextureLifetime, find_padding_violation, floor_dimension,
+    physical_texture::assign_physical_textures, reflect_shader_bindings, storage_format_for_components, substitute_constants,
+};
 use serde::Serialize;
 use std::{
     collections::{HashMap, HashSet},
@@ -28,6 +31,10 @@ pub struct ExecutablePipeline {
     /// Executable passes with resolved bindings
     pub passes: Vec<ExecutablePass>,
     /// Required sampler filter modes
+    ///
+    /// Collected from the sampler bindings of every pass, deduplicated and kept in
+    /// order of first use. Executors create one sampler per entry and share it
+    /// between all passes binding that filter mode.
     pub required_samplers: Vec<SamplerFilterMode>,
 }
 
@@ -66,6 +73,26 @@ pub struct PhysicalTextureBinding {
     pub scale_factor: (ScaleFactor, ScaleFactor),
 }
 
+impl ExecutablePass {
+    /// Calculates the compute dispatch size of this pass for a given input size
+    ///
+    /// Floors like physical texture allocation does, but never below one invocation
+    /// for a non-empty input, so passes of tiny inputs still dispatch a workgroup.
+    ///
+    /// # Arguments
+    /// * `input_width` - Input image width in pixels
+    /// * `input_height` - Input image height in pixels
+    ///
+    /// # Returns
+    /// The number of invocations (width, height)
+    pub fn compute_dimensions(&self, input_width: u32, input_height: u32) -> (u32, u32) {
+        (
+            floor_dimension(input_width, input_width as f64 * self.compute_scale_factors.0),
+            floor_dimension(input_height, input_height as f64 * self.compute_scale_factors.1),
+        )
+    }
+}
+
 impl ExecutablePipeline {
     /// Creates an ExecutablePipeline from a raw pipeline specification
     ///
@@ -111,6 +138,78 @@ impl ExecutablePipeline {
         Ok(raw.compile(load_shader_file)?)
     }
 
+    /// Creates an ExecutablePipeline from YAML content with shaders taken from an asset map
+    ///
+    /// Each pass's `file` entry is looked up in `assets` by name, without touching the filesystem.
+    /// Names resolve verbatim after normalization (backslashes become `/`, a leading `./` is dropped);
+    /// they are not made relative to any manifest location, so assets must be registered under
+    /// exactly the names the manifest uses.
+    ///
+    /// # Arguments
+    /// * `yaml_content` - YAML pipeline specification content
+    /// * `assets` - Asset map containing every shader referenced by the manifest
+    ///
+    /// # Returns
+    /// An optimized ExecutablePipeline ready for GPU execution
+    pub fn from_manifest_str(yaml_content: &str, assets: &AssetMap) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_yaml(yaml_content, |file| assets.load(file))
+    }
+
+    /// Checks that every pass's shader declares exactly the bindings the manifest expects
+    ///
+    /// Parses each shader with naga and compares the resources declared in bind group 0
+    /// against the pass's inputs (sampled textures), outputs (storage textures with matching
+    /// component count), and samplers. This reports authoring mistakes with a clear message
+    /// instead of a validation error from wgpu at pipeline creation time.
+    ///
+    /// Outputs must be declared in the format `storage_format_for_components` gives for their
+    /// component count, since that is the format the texture is allocated in. Other storage
+    /// formats are reported as `UnsupportedStorageFormat`, even where a backend could write them.
+    ///
+    /// Textures with 3 components are additionally checked with `find_padding_violation`,
+    /// so their unused fourth component is always zero and never reaches another pass.
+    ///
+    /// # Returns
+    /// Ok(()) if all bindings match, or the first mismatch found
+    pub fn validate_shader_bindings(&self) -> Result<(), PipelineValidationError> {
+        for (i, pass) in self.passes.iter().enumerate() {
+            let declared = reflect_shader_bindings(&pass.shader).map_err(|e| PipelineValidationError::ShaderParseFailed(i, e))?;
+
+            let mut expected = std::collections::BTreeMap::new();
+            for input in &pass.input_textures {
+                expected.insert(input.binding, ShaderBindingKind::SampledTexture);
+            }
+            for output in &pass.output_textures {
+                // Textures with 3 components are backed by 4-component storage
+                let components = if output.components <= 2 { output.components } else { 4 };
+                if let Some(ShaderBindingKind::UnsupportedStorageTexture(format)) = declared.get(&output.binding) {
+                    return Err(PipelineValidationError::UnsupportedStorageFormat(i, output.binding, format.clone(), output.components));
+                }
+                expected.insert(output.binding, ShaderBindingKind::StorageTexture(components));
+            }
+            for sampler in &pass.samplers {
+                expected.insert(sampler.binding, ShaderBindingKind::Sampler);
+            }
+
+            for binding in expected.keys().chain(declared.keys()).collect::<std::collections::BTreeSet<_>>() {
+                let expected_kind = expected.get(binding).map(ToString::to_string).unwrap_or_else(|| "nothing".to_string());
+                let declared_kind = declared.get(binding).map(ToString::to_string).unwrap_or_else(|| "nothing".to_string());
+                if expected_kind != declared_kind {
+                    return Err(PipelineValidationError::BindingMismatch(i, *binding, expected_kind, declared_kind));
+                }
+            }
+
+            let padded_bindings: Vec<u32> = pass.input_textures.iter().chain(&pass.output_textures).filter(|t| t.components == 3).map(|t| t.binding).collect();
+            if !padded_bindings.is_empty()
+                && let Some(violation) = find_padding_violation(&pass.shader, &padded_bindings).map_err(|e| PipelineValidationError::ShaderParseFailed(i, e))?
+            {
+                return Err(PipelineValidationError::PaddingViolation(i, violation));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Gets the physical texture ID of the source texture
     ///
     /// The source texture is the input image that the pipeline processes.
@@ -150,6 +249,49 @@ impl ExecutablePipeline {
             .and_then(|pass| pass.output_textures.iter().find(|output| output.logical_id == "RESULT"))
             .map(|output| output.scale_factor)
     }
+
+    /// Calculates the exact output dimensions for a given input size
+    ///
+    /// Resolves the result texture's scale factor against the input dimensions,
+    /// flooring exactly like physical texture allocation does, so this matches the
+    /// size of the texture produced by executing the pipeline.
+    ///
+    /// # Arguments
+    /// * `input_width` - Input image width in pixels
+    /// * `input_height` - Input image height in pixels
+    ///
+    /// # Returns
+    /// The output (width, height), or None if the pipeline has no result texture
+    pub fn output_dimensions(&self, input_width: u32, input_height: u32) -> Option<(u32, u32)> {
+        self.get_final_scale_factor()
+            .map(|(width_scale, height_scale)| (width_scale.apply(input_width), height_scale.apply(input_height)))
+    }
+
+    /// Returns the passes that write a logical texture
+    ///
+    /// A logical texture is normally written by a single pass, but a manifest may
+    /// write the same ID more than once, so all writers are returned. Together with
+    /// `consumers`, this traces the data flow through shared physical textures.
+    ///
+    /// # Arguments
+    /// * `logical_id` - Logical texture ID as used in the manifest (e.g. "RESULT")
+    ///
+    /// # Returns
+    /// The passes with `logical_id` among their outputs, in execution order
+    pub fn producers(&self, logical_id: &str) -> Vec<&ExecutablePass> {
+        self.passes.iter().filter(|pass| pass.output_textures.iter().any(|output| output.logical_id == logical_id)).collect()
+    }
+
+    /// Returns the passes that read a logical texture
+    ///
+    /// # Arguments
+    /// * `logical_id` - Logical texture ID as used in the manifest (e.g. "SOURCE")
+    ///
+    /// # Returns
+    /// The passes with `logical_id` among their inputs, in execution order
+    pub fn consumers(&self, logical_id: &str) -> Vec<&ExecutablePass> {
+        self.passes.iter().filter(|pass| pass.input_textures.iter().any(|input| input.logical_id == logical_id)).collect()
+    }
 }
 
 impl PipelineSpec {
@@ -197,6 +339,13 @@ impl PipelineSpec {
             }
         }
 
+        // Check that every output maps to a storage-capable format
+        for (i, pass) in self.passes.iter().enumerate() {
+            if let Some(output) = pass.outputs.iter().find(|output| storage_format_for_components(output.components).is_none()) {
+                return Err(PipelineValidationError::InvalidComponents(i, output.id.clone(), output.components));
+            }
+        }
+
         // Check binding uniqueness within each pass
         for (i, pass) in self.passes.iter().enumerate() {
             let mut used_bindings = std::collections::HashSet::new();
@@ -243,21 +392,35 @@ impl PipelineSpec {
             }
         }
 
-        // Check that input textures exist
-        let mut available_textures = HashSet::new();
-        available_textures.insert("SOURCE".to_string());
+        // Check that input textures exist and are read at the scale they are written at
+        let mut available_textures = HashMap::new();
+        available_textures.insert("SOURCE".to_string(), (None, [ScaleFactor::new(1, 1); 2]));
 
         for (i, pass) in self.passes.iter().enumerate() {
             // Check all inputs are available
             for input in &pass.inputs {
-                if !available_textures.contains(&input.id) {
+                let Some((producer, produced_scale_factor)) = available_textures.get(&input.id) else {
                     return Err(PipelineValidationError::InputTextureNotFound(i, input.id.clone()));
+                };
+
+                // Equal fractions convert to the same f64, so 2/2 matches 1/1
+                let mismatch = input
+                    .scale_factor
+                    .filter(|declared| declared.iter().zip(produced_scale_factor).any(|(declared, produced)| declared.to_f64() != produced.to_f64()));
+                if let Some(declared_scale_factor) = mismatch {
+                    return Err(PipelineValidationError::InputScaleFactorMismatch(
+                        pass.id.clone(),
+                        input.id.clone(),
+                        producer.clone(),
+                        declared_scale_factor,
+                        *produced_scale_factor,
+                    ));
                 }
             }
 
             // Add outputs to available textures for next passes
             for output in &pass.outputs {
-                available_textures.insert(output.id.clone());
+                available_textures.insert(output.id.clone(), (Some(pass.id.clone()), output.scale_factor));
             }
         }
 
@@ -350,7 +513,7 @@ impl PipelineCompiler {
     /// Creates executable passes with optimized resource bindings
     ///
     /// Converts raw pass specifications into executable passes with physical texture
-    /// assignments and loaded shader code.
+    /// assignments and loaded shader code, with manifest constants substituted.
     fn create_executable_passes(&self, texture_assignments: &HashMap<String, u32>, load_shader_file: impl Fn(&str) -> Result<String, std::io::Error>) -> Result<Vec<ExecutablePass>, std::io::Error> {
         self.raw
             .passes
@@ -396,7 +559,7 @@ impl PipelineCompiler {
 
                 Ok(ExecutablePass {
                     id: pass.id.clone(),
-                    shader: load_shader_file(&pass.file)?,
+                    shader: substitute_constants(&load_shader_file(&pass.file)?, &self.raw.constants).map_err(|e| std::io::Error::new(e.kind(), format!("{e} (in {})", pass.file)))?,
                     compute_scale_factors,
                     input_textures,
                     output_textures,
@@ -460,6 +623,20 @@ pub enum PipelineValidationError {
     TextureOverwritten(usize, String),
     /// An input texture was not created by any previous pass (pass index, texture ID)
     InputTextureNotFound(usize, String),
+    /// A pass's shader could not be parsed (pass index, parser message)
+    ShaderParseFailed(usize, String),
+    /// A shader binding does not match the manifest (pass index, binding, expected resource, declared resource)
+    BindingMismatch(usize, u32, String, String),
+    /// A shader writes or reads the padding component of a 3-component texture (pass index, violation)
+    PaddingViolation(usize, PaddingViolation),
+    /// An output declares a component count with no storage format (pass index, texture ID, components)
+    InvalidComponents(usize, String, u32),
+    /// A shader declares an output in a storage format it is not allocated in
+    /// (pass index, binding, declared format, components in the manifest)
+    UnsupportedStorageFormat(usize, u32, String, u32),
+    /// An input declares a different scale factor than its texture is written at
+    /// (consuming pass ID, texture ID, producing pass ID or None for SOURCE, declared scale factors, written scale factors)
+    InputScaleFactorMismatch(String, String, Option<String>, [ScaleFactor; 2], [ScaleFactor; 2]),
 }
 
 impl fmt::Display for PipelineValidationError {
@@ -482,6 +659,35 @@ impl fmt::Display for PipelineValidationError {
             Self::InputTextureNotFound(pass, texture) => {
                 write!(f, "Input texture '{texture}' in pass {pass} was not created by any previous pass or is not SOURCE")
             }
+            Self::ShaderParseFailed(pass, message) => {
+                write!(f, "Shader for pass {pass} failed to parse: {message}")
+            }
+            Self::BindingMismatch(pass, binding, expected, declared) => {
+                write!(f, "Binding {binding} in pass {pass}: manifest expects {expected}, but shader declares {declared}")
+            }
+            Self::PaddingViolation(pass, violation) => write!(f, "Pass {pass}: {violation}"),
+            Self::InvalidComponents(pass, texture, components) => {
+                write!(f, "Output '{texture}' in pass {pass} declares {components} components, but outputs must have 1 to 4")
+            }
+            Self::UnsupportedStorageFormat(pass, binding, format, components) => {
+                // Components were checked by `validate`, so the format is always known
+                let expected = storage_format_for_components(*components).unwrap_or("rgba32float");
+                write!(
+                    f,
+                    "Binding {binding} in pass {pass} is declared as a {format} storage texture, but outputs with {components} components are allocated as {expected}"
+                )
+            }
+            Self::InputScaleFactorMismatch(pass, texture, producer, declared, written) => {
+                let producer = match producer {
+                    Some(producer) => format!("pass '{producer}' writes it"),
+                    None => "the source is".to_string(),
+                };
+                write!(
+                    f,
+                    "Pass '{pass}' expects input '{texture}' at scale {}x{}, but {producer} at {}x{}",
+                    declared[0], declared[1], written[0], written[1]
+                )
+            }
         }
     }
 }
@@ -532,6 +738,302 @@ passes:
         assert_eq!(result_texture.scale_factor, (ScaleFactor::new(2, 1), ScaleFactor::new(2, 1)));
     }
 
+    /// Tests finding the passes that write and read each logical texture
+    #[test]
+    fn test_producers_and_consumers() {
+        let yaml = r#"
+id: test_pipeline
+name: Test Pipeline
+passes:
+  - id: pass1
+    file: pass1.wgsl
+    inputs:
+      - id: SOURCE
+        binding: 0
+    outputs:
+      - id: TEMP
+        binding: 1
+        components: 4
+        scale_factor: ["1", "1"]
+  - id: pass2
+    file: pass2.wgsl
+    inputs:
+      - id: SOURCE
+        binding: 0
+      - id: TEMP
+        binding: 1
+    outputs:
+      - id: RESULT
+        binding: 2
+        components: 4
+        scale_factor: ["2", "2"]
+"#;
+
+        let executable = ExecutablePipeline::from_yaml(yaml, |file| Ok(format!("// {file}"))).unwrap();
+        let ids = |passes: Vec<&ExecutablePass>| passes.iter().map(|pass| pass.id.clone()).collect::<Vec<_>>();
+
+        assert_eq!(ids(executable.producers("TEMP")), ["pass1"]);
+        assert_eq!(ids(executable.consumers("TEMP")), ["pass2"]);
+        assert_eq!(ids(executable.producers("RESULT")), ["pass2"]);
+        assert!(executable.consumers("RESULT").is_empty());
+        assert!(executable.producers("SOURCE").is_empty());
+        assert_eq!(ids(executable.consumers("SOURCE")), ["pass1", "pass2"]);
+        assert!(executable.producers("MISSING").is_empty());
+    }
+
+    /// Tests that output dimensions floor fractional scale factors like texture allocation
+    #[test]
+    fn test_output_dimensions() {
+        let yaml = r#"
+id: test_pipeline
+name: Test Pipeline
+passes:
+  - id: pass1
+    file: pass1.wgsl
+    inputs:
+      - id: SOURCE
+        binding: 0
+    outputs:
+      - id: RESULT
+        binding: 1
+        components: 4
+        scale_factor: ["3/2", "1/2"]
+"#;
+
+        let executable = ExecutablePipeline::from_yaml(yaml, |_| Ok(String::new())).unwrap();
+        assert_eq!(executable.output_dimensions(101, 101), Some((151, 50)));
+    }
+
+    /// Tests that 1x1 and 3x3 inputs yield non-empty textures and dispatches
+    #[test]
+    fn test_tiny_input_dimensions() {
+        let yaml = r#"
+id: test_pipeline
+name: Test Pipeline
+passes:
+  - id: downscale
+    file: downscale.wgsl
+    inputs:
+      - id: SOURCE
+        binding: 0
+    outputs:
+      - id: half
+        binding: 1
+        components: 4
+        scale_factor: ["1/2", "1/2"]
+  - id: upscale
+    file: upscale.wgsl
+    inputs:
+      - id: half
+        binding: 0
+    outputs:
+      - id: RESULT
+        binding: 1
+        components: 4
+        scale_factor: ["2", "2"]
+"#;
+
+        let executable = ExecutablePipeline::from_yaml(yaml, |_| Ok(String::new())).unwrap();
+        assert_eq!(executable.output_dimensions(1, 1), Some((2, 2)));
+        assert_eq!(executable.output_dimensions(3, 3), Some((6, 6)));
+        assert_eq!(executable.passes[0].compute_dimensions(1, 1), (1, 1));
+        assert_eq!(executable.passes[0].compute_dimensions(3, 3), (1, 1));
+        assert_eq!(executable.passes[1].compute_dimensions(1, 1), (2, 2));
+
+        // A single 8x8 workgroup covers both inputs
+        for size in [1, 3] {
+            let (width, height) = executable.passes[0].compute_dimensions(size, size);
+            assert_eq!((width.div_ceil(8), height.div_ceil(8)), (1, 1));
+        }
+    }
+
+    /// Tests that shaders are resolved from an asset map instead of the filesystem
+    #[test]
+    fn test_executable_pipeline_from_asset_map() {
+        let yaml = r#"
+id: test_pipeline
+name: Test Pipeline
+passes:
+  - id: pass1
+    file: ./pass1.wgsl
+    inputs:
+      - id: SOURCE
+        binding: 0
+    outputs:
+      - id: RESULT
+        binding: 1
+        components: 4
+        scale_factor: ["1", "1"]
+"#;
+
+        let assets: AssetMap = [("pass1.wgsl", "Embedded shader")].into_iter().collect();
+        let executable = ExecutablePipeline::from_manifest_str(yaml, &assets).unwrap();
+        assert_eq!(executable.passes[0].shader, "Embedded shader");
+
+        assert!(ExecutablePipeline::from_manifest_str(yaml, &AssetMap::new()).is_err());
+    }
+
+    /// Tests that shader bindings are checked against the manifest
+    #[test]
+    fn test_validate_shader_bindings() {
+        let yaml = r#"
+id: test_pipeline
+name: Test Pipeline
+passes:
+  - id: pass1
+    file: pass1.wgsl
+    inputs:
+      - id: SOURCE
+        binding: 0
+    outputs:
+      - id: RESULT
+        binding: 1
+        components: 4
+        scale_factor: ["1", "1"]
+"#;
+
+        let shader = |output_binding: u32| {
+            format!(
+                "@group(0) @binding(0) var source_tex: texture_2d<f32>;\n\
+                 @group(0) @binding({output_binding}) var result_tex: texture_storage_2d<rgba32float, write>;\n\
+                 @compute @workgroup_size(8, 8)\n\
+                 fn main(@builtin(global_invocation_id) pixel: vec3u) {{\n\
+                     textureStore(result_tex, vec2i(pixel.xy), textureLoad(source_tex, vec2i(pixel.xy), 0));\n\
+                 }}\n"
+            )
+        };
+
+        let matching = ExecutablePipeline::from_yaml(yaml, |_| Ok(shader(1))).unwrap();
+        assert!(matching.validate_shader_bindings().is_ok());
+
+        let mismatched = ExecutablePipeline::from_yaml(yaml, |_| Ok(shader(2))).unwrap();
+        assert!(matches!(mismatched.validate_shader_bindings(), Err(PipelineValidationError::BindingMismatch(0, 1, _, _))));
+
+        let unparsable = ExecutablePipeline::from_yaml(yaml, |_| Ok("not wgsl".to_string())).unwrap();
+        assert!(matches!(unparsable.validate_shader_bindings(), Err(PipelineValidationError::ShaderParseFailed(0, _))));
+    }
+
+    /// Tests that outputs must use the storage format their component count is allocated in
+    #[test]
+    fn test_validate_storage_formats() {
+        let yaml = |components: u32| {
+            format!(
+                r#"
+id: test_pipeline
+name: Test Pipeline
+passes:
+  - id: pass1
+    file: pass1.wgsl
+    inputs:
+      - id: SOURCE
+        binding: 0
+    outputs:
+      - id: RESULT
+        binding: 1
+        components: {components}
+        scale_factor: ["1", "1"]
+"#
+            )
+        };
+
+        let shader = |format: &str, value: &str| {
+            format!(
+                "@group(0) @binding(0) var source_tex: texture_2d<f32>;\n\
+                 @group(0) @binding(1) var result_tex: texture_storage_2d<{format}, write>;\n\
+                 @compute @workgroup_size(8, 8)\n\
+                 fn main(@builtin(global_invocation_id) pixel: vec3u) {{\n\
+                     textureStore(result_tex, vec2i(pixel.xy), {value});\n\
+                 }}\n"
+            )
+        };
+
+        assert_eq!(storage_format_for_components(3), Some("rgba32float"));
+
+        let half_float = ExecutablePipeline::from_yaml(&yaml(4), |_| Ok(shader("rgba16float", "textureLoad(source_tex, vec2i(pixel.xy), 0)"))).unwrap();
+        let error = half_float.validate_shader_bindings().unwrap_err();
+        assert!(matches!(&error, PipelineValidationError::UnsupportedStorageFormat(0, 1, format, 4) if format == "rgba16float"));
+        assert!(error.to_string().contains("rgba32float"));
+
+        let single = ExecutablePipeline::from_yaml(&yaml(1), |_| Ok(shader("r32float", "vec4f(textureLoad(source_tex, vec2i(pixel.xy), 0).r)"))).unwrap();
+        assert!(single.validate_shader_bindings().is_ok());
+
+        let too_many = PipelineSpec::from_yaml(&yaml(5)).unwrap();
+        assert!(matches!(too_many.validate(), Err(PipelineValidationError::InvalidComponents(0, _, 5))));
+    }
+
+    /// Tests that the padding component of 3-component textures is zeroed and never read
+    #[test]
+    fn test_validate_padding_component() {
+        let yaml = r#"
+id: test_pipeline
+name: Test Pipeline
+passes:
+  - id: pass1
+    file: pass1.wgsl
+    inputs:
+      - id: SOURCE
+        binding: 0
+    outputs:
+      - id: features
+        binding: 1
+        components: 3
+        scale_factor: ["1", "1"]
+  - id: pass2
+    file: pass2.wgsl
+    inputs:
+      - id: features
+        binding: 0
+    outputs:
+      - id: RESULT
+        binding: 1
+        components: 4
+        scale_factor: ["1", "1"]
+"#;
+
+        let shaders = |padding: &str, read: &str| {
+            let pass1 = format!(
+                "@group(0) @binding(0) var source_tex: texture_2d<f32>;\n\
+                 @group(0) @binding(1) var features_tex: texture_storage_2d<rgba32float, write>;\n\
+                 @compute @workgroup_size(8, 8)\n\
+                 fn main(@builtin(global_invocation_id) pixel: vec3u) {{\n\
+                     let color = textureLoad(source_tex, vec2i(pixel.xy), 0);\n\
+                     textureStore(features_tex, vec2i(pixel.xy), vec4f(color.rgb, {padding}));\n\
+                 }}\n"
+            );
+            let pass2 = format!(
+                "@group(0) @binding(0) var features_tex: texture_2d<f32>;\n\
+                 @group(0) @binding(1) var result_tex: texture_storage_2d<rgba32float, write>;\n\
+                 @compute @workgroup_size(8, 8)\n\
+                 fn main(@builtin(global_invocation_id) pixel: vec3u) {{\n\
+                     let features = textureLoad(features_tex, vec2i(pixel.xy), 0);\n\
+                     textureStore(result_tex, vec2i(pixel.xy), {read});\n\
+                 }}\n"
+            );
+            move |file: &str| Ok::<_, std::io::Error>(if file == "pass1.wgsl" { pass1.clone() } else { pass2.clone() })
+        };
+
+        let valid = ExecutablePipeline::from_yaml(yaml, shaders("0.0", "vec4f(features.rgb, 1.0)")).unwrap();
+        assert!(valid.validate_shader_bindings().is_ok());
+
+        let not_zeroed = ExecutablePipeline::from_yaml(yaml, shaders("1.0", "vec4f(features.rgb, 1.0)")).unwrap();
+        assert!(matches!(
+            not_zeroed.validate_shader_bindings(),
+            Err(PipelineValidationError::PaddingViolation(0, PaddingViolation::NotZeroed(1)))
+        ));
+
+        let swizzle_read = ExecutablePipeline::from_yaml(yaml, shaders("0.0", "vec4f(features.rgb, features.a)")).unwrap();
+        assert!(matches!(
+            swizzle_read.validate_shader_bindings(),
+            Err(PipelineValidationError::PaddingViolation(1, PaddingViolation::Read(0)))
+        ));
+
+        let whole_read = ExecutablePipeline::from_yaml(yaml, shaders("0.0", "features * 2.0")).unwrap();
+        assert!(matches!(
+            whole_read.validate_shader_bindings(),
+            Err(PipelineValidationError::PaddingViolation(1, PaddingViolation::Read(0)))
+        ));
+    }
+
     /// Tests validation of a correctly structured pipeline spec
     ///
     /// Verifies that a valid pipeline passes all validation checks
@@ -558,4 +1060,47 @@ passes:
         let raw = PipelineSpec::from_yaml(yaml).unwrap();
         assert!(raw.validate().is_ok());
     }
+
+    /// Tests that declared input scale factors must match the scale their texture is written at
+    #[test]
+    fn test_validate_input_scale_factors() {
+        let yaml = r#"
+id: test_pipeline
+name: Test Pipeline
+passes:
+  - id: upscale
+    file: upscale.wgsl
+    inputs:
+      - id: SOURCE
+        binding: 0
+        scale_factor: ["1", "1"]
+    outputs:
+      - id: TEMP
+        binding: 1
+        components: 4
+        scale_factor: ["2", "2"]
+  - id: refine
+    file: refine.wgsl
+    inputs:
+      - id: TEMP
+        binding: 0
+        scale_factor: ["4/2", "2"]
+    outputs:
+      - id: RESULT
+        binding: 1
+        components: 4
+        scale_factor: ["2", "2"]
+"#;
+
+        let mut raw = PipelineSpec::from_yaml(yaml).unwrap();
+        assert!(raw.validate().is_ok());
+
+        raw.passes[1].inputs[0].scale_factor = Some([ScaleFactor::new(2, 1), ScaleFactor::new(1, 1)]);
+        let error = raw.validate().unwrap_err();
+        assert!(matches!(&error, PipelineValidationError::InputScaleFactorMismatch(pass, texture, Some(producer), _, _) if pass == "refine" && texture == "TEMP" && producer == "upscale"));
+        assert_eq!(error.to_string(), "Pass 'refine' expects input 'TEMP' at scale 2x1, but pass 'upscale' writes it at 2x2");
+
+        raw.passes[0].inputs[0].scale_factor = Some([ScaleFactor::new(1, 2); 2]);
+        assert!(matches!(raw.validate().unwrap_err(), PipelineValidationError::InputScaleFactorMismatch(_, _, None, _, _)));
+    }
 }
@@ -4,10 +4,22 @@
 //! pipeline specifications into optimized, GPU-ready executable pipelines.
 //! It handles resource allocation, texture lifetime analysis, and GPU resource binding.
 
+#[cfg(feature = "archive")]
+mod archive;
+mod asset_map;
+mod codegen;
 mod executable_pipeline;
 mod physical_texture;
+mod pipeline_diff;
 mod pipeline_specs;
+mod shader_bindings;
+mod shader_constants;
 
+pub use asset_map::AssetMap;
+pub use codegen::generate_embedded_pipelines_module;
 pub use executable_pipeline::*;
 pub use physical_texture::{PhysicalTexture, TextureLifetime};
+pub use pipeline_diff::{PipelineChange, PipelineDiff};
 pub use pipeline_specs::*;
+pub use shader_bindings::{PaddingViolation, ShaderBindingKind, find_padding_violation, reflect_shader_bindings, storage_format_for_components};
+pub use shader_constants::substitute_constants;
@@ -13,6 +13,9 @@ pub struct TextureLifetime {
     /// Logical identifier for this texture
     pub logical_id: String,
     /// Number of color components (1, 2, 3, or 4)
+    ///
+    /// 3-component textures are allocated as `rgba32float` because wgpu has no
+    /// 3-component storage format; their fourth component is always zero.
     pub components: u32,
     /// Scale factors relative to input dimensions
     pub scale_factor: (ScaleFactor, ScaleFactor),
@@ -0,0 +1,233 @@
+//! Structural comparison of compiled pipelines
+//!
+//! This module compares two ExecutablePipelines pass by pass, so the effect of a
+//! manifest edit on bindings, scale factors, and physical texture assignments can be reviewed.
+
+use super::{ExecutablePass, ExecutablePipeline, PhysicalTextureBinding, ScaleFactor};
+use std::{collections::BTreeMap, fmt};
+
+/// A single structural difference between two pipelines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PipelineChange {
+    /// A pass exists only in the new pipeline (pass ID)
+    PassAdded(String),
+    /// A pass exists only in the old pipeline (pass ID)
+    PassRemoved(String),
+    /// The resource at a binding point changed (pass ID, binding, old resource, new resource)
+    BindingChanged(String, u32, String, String),
+    /// The scale factor of a texture changed (pass ID, logical texture ID, old scale factor, new scale factor)
+    ScaleFactorChanged(String, String, (ScaleFactor, ScaleFactor), (ScaleFactor, ScaleFactor)),
+    /// A texture was assigned a different physical texture (pass ID, logical texture ID, old physical ID, new physical ID)
+    PhysicalTextureChanged(String, String, u32, u32),
+    /// The shader source of a pass changed (pass ID)
+    ShaderChanged(String),
+    /// The number of allocated physical textures changed (old count, new count)
+    PhysicalTextureCountChanged(usize, usize),
+}
+
+impl fmt::Display for PipelineChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PassAdded(pass) => write!(f, "+ pass {pass}"),
+            Self::PassRemoved(pass) => write!(f, "- pass {pass}"),
+            Self::BindingChanged(pass, binding, old, new) => write!(f, "~ pass {pass}: binding {binding}: {old} -> {new}"),
+            Self::ScaleFactorChanged(pass, texture, old, new) => {
+                write!(f, "~ pass {pass}: texture {texture}: scale factor {}x{} -> {}x{}", old.0, old.1, new.0, new.1)
+            }
+            Self::PhysicalTextureChanged(pass, texture, old, new) => write!(f, "~ pass {pass}: texture {texture}: physical texture {old} -> {new}"),
+            Self::ShaderChanged(pass) => write!(f, "~ pass {pass}: shader changed"),
+            Self::PhysicalTextureCountChanged(old, new) => write!(f, "~ physical textures: {old} -> {new}"),
+        }
+    }
+}
+
+/// Structural differences between two pipelines, as returned by `ExecutablePipeline::diff`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PipelineDiff {
+    /// Differences in pipeline order: physical texture count, removed passes, then added and changed passes
+    pub changes: Vec<PipelineChange>,
+}
+
+impl PipelineDiff {
+    /// Returns true if the pipelines are structurally identical
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl fmt::Display for PipelineDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.changes.is_empty() {
+            return writeln!(f, "No differences");
+        }
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+impl ExecutablePipeline {
+    /// Compares this pipeline with another version of it
+    ///
+    /// Passes are matched by ID. For matched passes, the resources at each binding point
+    /// are compared, and textures are matched by logical ID to compare their scale factors
+    /// and physical texture assignments. Pipeline metadata (name, description) is ignored.
+    ///
+    /// # Arguments
+    /// * `other` - The new version of the pipeline
+    ///
+    /// # Returns
+    /// The differences from `self` to `other`
+    pub fn diff(&self, other: &Self) -> PipelineDiff {
+        let mut changes = Vec::new();
+
+        if self.physical_textures.len() != other.physical_textures.len() {
+            changes.push(PipelineChange::PhysicalTextureCountChanged(self.physical_textures.len(), other.physical_textures.len()));
+        }
+
+        for pass in &self.passes {
+            if !other.passes.iter().any(|p| p.id == pass.id) {
+                changes.push(PipelineChange::PassRemoved(pass.id.clone()));
+            }
+        }
+
+        for new_pass in &other.passes {
+            let Some(old_pass) = self.passes.iter().find(|p| p.id == new_pass.id) else {
+                changes.push(PipelineChange::PassAdded(new_pass.id.clone()));
+                continue;
+            };
+            diff_pass(old_pass, new_pass, &mut changes);
+        }
+
+        PipelineDiff { changes }
+    }
+}
+
+/// Describes the resources bound at each binding point of a pass
+///
+/// Scale factors and physical IDs are left out, as they are compared separately per texture.
+fn describe_bindings(pass: &ExecutablePass) -> BTreeMap<u32, String> {
+    let mut bindings = BTreeMap::new();
+    for input in &pass.input_textures {
+        bindings.insert(input.binding, format!("input {}", input.logical_id));
+    }
+    for output in &pass.output_textures {
+        bindings.insert(output.binding, format!("output {} ({} components)", output.logical_id, output.components));
+    }
+    for sampler in &pass.samplers {
+        bindings.insert(sampler.binding, format!("sampler ({:?})", sampler.filter_mode));
+    }
+    bindings
+}
+
+/// Appends the differences between two versions of the same pass
+fn diff_pass(old: &ExecutablePass, new: &ExecutablePass, changes: &mut Vec<PipelineChange>) {
+    let old_bindings = describe_bindings(old);
+    let new_bindings = describe_bindings(new);
+    for binding in old_bindings.keys().chain(new_bindings.keys()).collect::<std::collections::BTreeSet<_>>() {
+        let old_resource = old_bindings.get(binding).map(String::as_str).unwrap_or("nothing");
+        let new_resource = new_bindings.get(binding).map(String::as_str).unwrap_or("nothing");
+        if old_resource != new_resource {
+            changes.push(PipelineChange::BindingChanged(new.id.clone(), *binding, old_resource.to_string(), new_resource.to_string()));
+        }
+    }
+
+    let textures = |pass: &ExecutablePass| -> Vec<PhysicalTextureBinding> { pass.input_textures.iter().chain(&pass.output_textures).cloned().collect() };
+    let old_textures = textures(old);
+    for new_texture in textures(new) {
+        let Some(old_texture) = old_textures.iter().find(|t| t.logical_id == new_texture.logical_id) else {
+            continue;
+        };
+        if old_texture.scale_factor != new_texture.scale_factor {
+            changes.push(PipelineChange::ScaleFactorChanged(
+                new.id.clone(),
+                new_texture.logical_id.clone(),
+                old_texture.scale_factor,
+                new_texture.scale_factor,
+            ));
+        }
+        if old_texture.physical_id != new_texture.physical_id {
+            changes.push(PipelineChange::PhysicalTextureChanged(
+                new.id.clone(),
+                new_texture.logical_id.clone(),
+                old_texture.physical_id,
+                new_texture.physical_id,
+            ));
+        }
+    }
+
+    if old.shader != new.shader {
+        changes.push(PipelineChange::ShaderChanged(new.id.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"
+id: test_pipeline
+name: Test Pipeline
+passes:
+  - id: pass1
+    file: pass1.wgsl
+    inputs:
+      - id: SOURCE
+        binding: 0
+    outputs:
+      - id: features
+        binding: 1
+        components: 4
+        scale_factor: ["1", "1"]
+  - id: pass2
+    file: pass2.wgsl
+    inputs:
+      - id: features
+        binding: 0
+    outputs:
+      - id: RESULT
+        binding: 1
+        components: 4
+        scale_factor: ["2", "2"]
+"#;
+
+    fn compile(yaml: &str) -> ExecutablePipeline {
+        ExecutablePipeline::from_yaml(yaml, |file| Ok(format!("// {file}"))).unwrap()
+    }
+
+    /// Tests that identical pipelines produce an empty diff
+    #[test]
+    fn test_diff_identical() {
+        let diff = compile(MANIFEST).diff(&compile(MANIFEST));
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "No differences\n");
+    }
+
+    /// Tests that pass, binding, and scale factor changes are reported
+    #[test]
+    fn test_diff_changes() {
+        let edited = MANIFEST
+            .replace("scale_factor: [\"2\", \"2\"]", "scale_factor: [\"3\", \"3\"]")
+            .replace(
+                "      - id: features\n        binding: 0\n",
+                "      - id: features\n        binding: 0\n      - id: SOURCE\n        binding: 2\n",
+            )
+            .replace("id: pass1", "id: pass0");
+
+        let diff = compile(MANIFEST).diff(&compile(&edited));
+        assert!(diff.changes.contains(&PipelineChange::PassRemoved("pass1".to_string())));
+        assert!(diff.changes.contains(&PipelineChange::PassAdded("pass0".to_string())));
+        assert!(
+            diff.changes
+                .contains(&PipelineChange::BindingChanged("pass2".to_string(), 2, "nothing".to_string(), "input SOURCE".to_string()))
+        );
+        assert!(diff.changes.contains(&PipelineChange::ScaleFactorChanged(
+            "pass2".to_string(),
+            "RESULT".to_string(),
+            (ScaleFactor::new(2, 1), ScaleFactor::new(2, 1)),
+            (ScaleFactor::new(3, 1), ScaleFactor::new(3, 1))
+        )));
+        assert!(diff.to_string().contains("~ pass pass2: texture RESULT: scale factor 2x2 -> 3x3"));
+    }
+}
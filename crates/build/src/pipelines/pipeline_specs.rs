@@ -4,10 +4,23 @@
 //! shader pipeline configurations. The parser handles scale factors in formats like
 //! "1/2", "2", etc. and validates pipeline structure.
 
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
 use std::fmt;
 use std::str::FromStr;
 
+/// Floors a scaled dimension to whole pixels
+///
+/// A non-empty dimension never floors below one pixel, so downscaled textures and
+/// dispatches of tiny inputs are still valid.
+///
+/// # Arguments
+/// * `size` - Dimension before scaling
+/// * `scaled` - Dimension after scaling, before flooring
+pub(crate) fn floor_dimension(size: u32, scaled: f64) -> u32 {
+    (scaled.floor() as u32).max(size.min(1))
+}
+
 /// Represents a rational scale factor as a fraction
 ///
 /// Used to express scaling relationships between textures in the pipeline,
@@ -31,6 +44,14 @@ impl ScaleFactor {
         self.numerator as f64 / self.denominator as f64
     }
 
+    /// Applies the scale factor to a dimension, flooring to an integer size
+    ///
+    /// This is how physical texture dimensions are derived from the input size.
+    /// A non-empty dimension never scales below one pixel (see `floor_dimension`).
+    pub fn apply(&self, size: u32) -> u32 {
+        floor_dimension(size, size as f64 * self.to_f64())
+    }
+
     /// Returns true if this scale factor equals 1.0 (no scaling)
     pub fn is_unity(&self) -> bool {
         self.numerator == self.denominator
@@ -93,6 +114,25 @@ impl<'de> Deserialize<'de> for ScaleFactor {
     }
 }
 
+/// Serializes scale factors in the manifest string form ("n" or "n/d")
+fn serialize_scale_factors<S>(scale_factors: &[ScaleFactor; 2], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.collect_seq(scale_factors.iter().map(ToString::to_string))
+}
+
+/// Serializes optional scale factors in the manifest string form, if present
+fn serialize_optional_scale_factors<S>(scale_factors: &Option<[ScaleFactor; 2]>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match scale_factors {
+        Some(scale_factors) => serialize_scale_factors(scale_factors, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
 /// Error types for scale factor parsing
 #[derive(Debug, Clone)]
 pub enum ScaleFactorParseError {
@@ -120,12 +160,18 @@ impl fmt::Display for ScaleFactorParseError {
 impl std::error::Error for ScaleFactorParseError {}
 
 /// Binding of a logical texture to a shader binding point
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TextureBindingSpec {
     /// Logical texture identifier
     pub id: String,
     /// Shader binding point index
     pub binding: u32,
+    /// Scale factors [width_scale, height_scale] the pass expects the texture at (optional)
+    ///
+    /// When present, validation checks it against the scale the texture is written at,
+    /// so a pass cannot silently sample a texture at the wrong resolution.
+    #[serde(default, skip_serializing_if = "Option::is_none", serialize_with = "serialize_optional_scale_factors")]
+    pub scale_factor: Option<[ScaleFactor; 2]>,
 }
 
 /// Texture sampling filter modes
@@ -151,7 +197,7 @@ pub struct SamplerBinding {
 }
 
 /// Output texture specification for a shader pass
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TextureOutput {
     /// Logical texture identifier
     pub id: String,
@@ -160,11 +206,12 @@ pub struct TextureOutput {
     /// Number of color components in this texture
     pub components: u32,
     /// Scale factors [width_scale, height_scale] relative to input
+    #[serde(serialize_with = "serialize_scale_factors")]
     pub scale_factor: [ScaleFactor; 2],
 }
 
 /// A single shader pass in the pipeline
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Pass {
     /// Unique identifier for this pass
     pub id: String,
@@ -183,15 +230,18 @@ pub struct Pass {
 ///
 /// Contains the unprocessed pipeline specification before analysis
 /// and optimization.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct PipelineSpec {
     /// Unique pipeline identifier
     pub id: String,
     /// Human-readable pipeline name
     pub name: String,
     /// Optional description
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Constant values substituted for `{{NAME}}` placeholders in pass shaders
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub constants: BTreeMap<String, String>,
     /// Sequence of shader passes
     pub passes: Vec<Pass>,
 }
@@ -213,6 +263,13 @@ impl PipelineSpec {
         let content = std::fs::read_to_string(path)?;
         Ok(Self::from_yaml(&content)?)
     }
+
+    /// Serializes the pipeline manifest to YAML content
+    ///
+    /// The output uses the same format accepted by `from_yaml`.
+    pub fn to_yaml(&self) -> Result<String, serde_norway::Error> {
+        serde_norway::to_string(self)
+    }
 }
 
 #[cfg(test)]
@@ -235,6 +292,17 @@ mod tests {
         assert!("invalid".parse::<ScaleFactor>().is_err());
     }
 
+    /// Tests that tiny dimensions never floor to zero, while zero stays zero
+    #[test]
+    fn test_scale_factor_apply_tiny() {
+        assert_eq!(ScaleFactor::new(1, 2).apply(1), 1);
+        assert_eq!(ScaleFactor::new(1, 2).apply(3), 1);
+        assert_eq!(ScaleFactor::new(1, 4).apply(3), 1);
+        assert_eq!(ScaleFactor::new(2, 1).apply(1), 2);
+        assert_eq!(ScaleFactor::new(3, 2).apply(3), 4);
+        assert_eq!(ScaleFactor::new(1, 2).apply(0), 0);
+    }
+
     #[test]
     fn test_scale_factor_properties() {
         let unity = ScaleFactor::new(1, 1);
@@ -287,4 +355,215 @@ passes:
         assert_eq!(output.scale_factor[0], ScaleFactor::new(2, 1));
         assert_eq!(output.scale_factor[1], ScaleFactor::new(2, 1));
     }
+
+    /// Tests that declared input scale factors parse and are omitted when absent
+    #[test]
+    fn test_input_scale_factor_parsing() {
+        let yaml = r#"
+id: test_pipeline
+name: Test Pipeline
+passes:
+  - id: pass1
+    file: pass1.wgsl
+    inputs:
+      - id: SOURCE
+        binding: 0
+        scale_factor: ["1", "1"]
+    outputs:
+      - id: RESULT
+        binding: 1
+        components: 4
+        scale_factor: ["2", "2"]
+"#;
+
+        let raw_pipeline = PipelineSpec::from_yaml(yaml).unwrap();
+        assert_eq!(raw_pipeline.passes[0].inputs[0].scale_factor, Some([ScaleFactor::new(1, 1); 2]));
+
+        let mut undeclared = raw_pipeline.clone();
+        undeclared.passes[0].inputs[0].scale_factor = None;
+        assert_eq!(undeclared.to_yaml().unwrap().matches("scale_factor").count(), 1);
+        assert_eq!(
+            PipelineSpec::from_yaml(&raw_pipeline.to_yaml().unwrap()).unwrap().passes[0].inputs[0].scale_factor,
+            Some([ScaleFactor::new(1, 1); 2])
+        );
+    }
+
+    #[test]
+    fn test_raw_pipeline_yaml_roundtrip() {
+        let yaml = r#"
+id: test_pipeline
+name: Test Pipeline
+passes:
+  - id: pass1
+    file: pass1.wgsl
+    inputs:
+      - id: SOURCE
+        binding: 0
+    outputs:
+      - id: RESULT
+        binding: 1
+        components: 4
+        scale_factor: ["1/2", "2"]
+    samplers:
+      - binding: 2
+        filter_mode: nearest
+"#;
+
+        let raw_pipeline = PipelineSpec::from_yaml(yaml).unwrap();
+        let reparsed = PipelineSpec::from_yaml(&raw_pipeline.to_yaml().unwrap()).unwrap();
+        assert_eq!(reparsed.id, "test_pipeline");
+        assert!(reparsed.description.is_none());
+
+        let pass = &reparsed.passes[0];
+        assert_eq!(pass.outputs[0].scale_factor, [ScaleFactor::new(1, 2), ScaleFactor::new(2, 1)]);
+        assert_eq!(pass.samplers[0].filter_mode, SamplerFilterMode::Nearest);
+    }
+
+    /// Per-pass parameters drawn by the generator, resolved into a valid pass by `build_spec`
+    #[derive(Debug, Clone)]
+    struct PassShape {
+        /// Picks among the textures available to the pass, deduplicated
+        inputs: Vec<proptest::sample::Index>,
+        /// Components and scale factors of each output
+        outputs: Vec<(u32, [ScaleFactor; 2])>,
+        /// Filter modes of the pass's samplers
+        samplers: Vec<SamplerFilterMode>,
+        /// Whether inputs declare the scale factor they are written at
+        declare_input_scales: bool,
+    }
+
+    /// Generates a scale factor with a small numerator and denominator
+    fn scale_factor_strategy() -> impl proptest::strategy::Strategy<Value = ScaleFactor> {
+        use proptest::prelude::*;
+        (1u32..=4, 1u32..=4).prop_map(|(numerator, denominator)| ScaleFactor::new(numerator, denominator))
+    }
+
+    /// Generates the parameters of one pass
+    fn pass_shape_strategy() -> impl proptest::strategy::Strategy<Value = PassShape> {
+        use proptest::prelude::*;
+        let output = (1u32..=4, [scale_factor_strategy(), scale_factor_strategy()]);
+        let filter_mode = prop_oneof![Just(SamplerFilterMode::Nearest), Just(SamplerFilterMode::Linear)];
+        (
+            prop::collection::vec(any::<proptest::sample::Index>(), 1..=3),
+            prop::collection::vec(output, 1..=2),
+            prop::collection::vec(filter_mode, 0..=2),
+            any::<bool>(),
+        )
+            .prop_map(|(inputs, outputs, samplers, declare_input_scales)| PassShape {
+                inputs,
+                outputs,
+                samplers,
+                declare_input_scales,
+            })
+    }
+
+    /// Resolves pass shapes into a spec that passes `PipelineSpec::validate`
+    ///
+    /// Each pass reads textures written by earlier passes (or SOURCE), optionally
+    /// declaring their scale factors, and writes new ones. The last pass writes RESULT
+    /// first, and bindings are numbered inputs first, then outputs, then samplers.
+    fn build_spec(id: String, name: String, description: Option<String>, constants: BTreeMap<String, String>, shapes: Vec<PassShape>) -> PipelineSpec {
+        let mut available = vec!["SOURCE".to_string()];
+        let mut scales = std::collections::HashMap::from([("SOURCE".to_string(), [ScaleFactor::new(1, 1); 2])]);
+        let pass_count = shapes.len();
+        let passes = shapes
+            .into_iter()
+            .enumerate()
+            .map(|(pass_index, shape)| {
+                let mut binding = 0..;
+
+                let mut input_ids: Vec<String> = shape.inputs.iter().map(|index| available[index.index(available.len())].clone()).collect();
+                input_ids.sort();
+                input_ids.dedup();
+                let inputs = input_ids
+                    .into_iter()
+                    .map(|id| TextureBindingSpec {
+                        scale_factor: shape.declare_input_scales.then(|| scales[&id]),
+                        id,
+                        binding: binding.next().unwrap(),
+                    })
+                    .collect();
+
+                let outputs: Vec<TextureOutput> = shape
+                    .outputs
+                    .into_iter()
+                    .enumerate()
+                    .map(|(output_index, (components, scale_factor))| TextureOutput {
+                        id: if pass_index == pass_count - 1 && output_index == 0 {
+                            "RESULT".to_string()
+                        } else {
+                            format!("TEX{pass_index}_{output_index}")
+                        },
+                        binding: binding.next().unwrap(),
+                        components,
+                        scale_factor,
+                    })
+                    .collect();
+                available.extend(outputs.iter().map(|output| output.id.clone()));
+                scales.extend(outputs.iter().map(|output| (output.id.clone(), output.scale_factor)));
+
+                let samplers = shape
+                    .samplers
+                    .into_iter()
+                    .map(|filter_mode| SamplerBinding {
+                        binding: binding.next().unwrap(),
+                        filter_mode,
+                    })
+                    .collect();
+
+                Pass {
+                    id: format!("pass{pass_index}"),
+                    file: format!("shaders/pass{pass_index}.wgsl"),
+                    inputs,
+                    outputs,
+                    samplers,
+                }
+            })
+            .collect();
+
+        PipelineSpec {
+            id,
+            name,
+            description,
+            constants,
+            passes,
+        }
+    }
+
+    /// Generates random valid pipeline specs
+    fn pipeline_spec_strategy() -> impl proptest::strategy::Strategy<Value = PipelineSpec> {
+        use proptest::prelude::*;
+        (
+            "[a-z][a-z0-9_]{0,15}",
+            "[A-Za-z][A-Za-z0-9 _-]{0,20}[A-Za-z0-9]",
+            proptest::option::of("[A-Za-z][A-Za-z0-9 .,]{0,40}"),
+            prop::collection::btree_map("[A-Z][A-Z0-9_]{0,7}", "[0-9]{1,3}\\.[0-9]{1,3}", 0..=3),
+            prop::collection::vec(pass_shape_strategy(), 1..=6),
+        )
+            .prop_map(|(id, name, description, constants, shapes)| build_spec(id, name, description, constants, shapes))
+    }
+
+    proptest::proptest! {
+        /// Tests that random valid specs survive a YAML round trip unchanged, before and after compilation
+        #[test]
+        fn test_pipeline_spec_roundtrip_property(spec in pipeline_spec_strategy()) {
+            use crate::pipelines::ExecutablePipeline;
+
+            proptest::prop_assert!(spec.validate().is_ok(), "Generator produced an invalid spec: {:?}", spec.validate());
+
+            let yaml = spec.to_yaml().unwrap();
+            let reparsed = PipelineSpec::from_yaml(&yaml).unwrap();
+            proptest::prop_assert_eq!(&reparsed.to_yaml().unwrap(), &yaml);
+
+            // Every shader references every constant, so substitution is exercised too
+            let constants = spec.constants.keys().map(|name| format!("{{{{{name}}}}}")).collect::<Vec<_>>().join(" ");
+            let load_shader_file = |file: &str| -> Result<String, std::io::Error> { Ok(format!("// {file} {constants}")) };
+
+            let original = ExecutablePipeline::from_raw(spec, load_shader_file).unwrap();
+            let roundtripped = ExecutablePipeline::from_yaml(&yaml, load_shader_file).unwrap();
+            let diff = original.diff(&roundtripped);
+            proptest::prop_assert!(diff.is_empty(), "Compiled pipelines differ:\n{}", diff);
+            proptest::prop_assert_eq!(serde_json::to_string(&original).unwrap(), serde_json::to_string(&roundtripped).unwrap());
+        }
+    }
 }
@@ -0,0 +1,248 @@
+//! Shader resource binding reflection
+//!
+//! This module parses WGSL with naga and extracts the resources declared in bind group 0,
+//! so they can be checked against the bindings declared in a pipeline manifest.
+
+use std::collections::BTreeMap;
+
+/// Kind of resource declared at a shader binding point
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShaderBindingKind {
+    /// Sampled texture (`texture_2d<f32>`), used for pass inputs
+    SampledTexture,
+    /// Write-only storage texture with the given number of components, used for pass outputs
+    StorageTexture(u32),
+    /// Storage texture in a format pass outputs are never allocated in (WGSL format name)
+    UnsupportedStorageTexture(String),
+    /// Texture sampler
+    Sampler,
+    /// Any other resource type (buffers, depth textures, etc.)
+    Other,
+}
+
+impl std::fmt::Display for ShaderBindingKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SampledTexture => write!(f, "sampled texture"),
+            Self::StorageTexture(components) => write!(f, "storage texture with {components} components"),
+            Self::UnsupportedStorageTexture(format) => write!(f, "storage texture in {format}"),
+            Self::Sampler => write!(f, "sampler"),
+            Self::Other => write!(f, "unsupported resource"),
+        }
+    }
+}
+
+/// Returns the WGSL storage format pass outputs with a number of components are allocated in
+///
+/// Only 32-bit float formats are used, since they are storage-capable on every backend the
+/// runtime supports. Textures with 3 components are backed by `rgba32float`, as there is no
+/// 3-component storage format.
+///
+/// # Arguments
+/// * `components` - Number of components declared in the manifest
+///
+/// # Returns
+/// The format name as written in `texture_storage_2d<...>`, or None for counts outside 1 to 4
+pub fn storage_format_for_components(components: u32) -> Option<&'static str> {
+    match components {
+        1 => Some("r32float"),
+        2 => Some("rg32float"),
+        3 | 4 => Some("rgba32float"),
+        _ => None,
+    }
+}
+
+/// Extracts the resources declared in bind group 0 of a WGSL shader
+///
+/// # Arguments
+/// * `shader` - WGSL shader source
+///
+/// # Returns
+/// A map from binding index to resource kind, or the parser's error message
+pub fn reflect_shader_bindings(shader: &str) -> Result<BTreeMap<u32, ShaderBindingKind>, String> {
+    let module = naga::front::wgsl::parse_str(shader).map_err(|e| e.emit_to_string(shader))?;
+
+    let mut bindings = BTreeMap::new();
+    for (_, variable) in module.global_variables.iter() {
+        let Some(binding) = &variable.binding else {
+            continue;
+        };
+        if binding.group != 0 {
+            continue;
+        }
+
+        let kind = match &module.types[variable.ty].inner {
+            naga::TypeInner::Image {
+                class: naga::ImageClass::Sampled { .. },
+                ..
+            } => ShaderBindingKind::SampledTexture,
+            naga::TypeInner::Image {
+                class: naga::ImageClass::Storage { format, .. },
+                ..
+            } => match format {
+                naga::StorageFormat::R32Float => ShaderBindingKind::StorageTexture(1),
+                naga::StorageFormat::Rg32Float => ShaderBindingKind::StorageTexture(2),
+                naga::StorageFormat::Rgba32Float => ShaderBindingKind::StorageTexture(4),
+                // naga's variant names are the WGSL format names in camel case
+                format => ShaderBindingKind::UnsupportedStorageTexture(format!("{format:?}").to_ascii_lowercase()),
+            },
+            naga::TypeInner::Sampler { .. } => ShaderBindingKind::Sampler,
+            _ => ShaderBindingKind::Other,
+        };
+        bindings.insert(binding.binding, kind);
+    }
+
+    Ok(bindings)
+}
+
+/// Misuse of the padding component of a 3-component texture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaddingViolation {
+    /// A store to the texture at this binding does not write a literal zero to the fourth component
+    NotZeroed(u32),
+    /// A texel fetched from the texture at this binding is used through its fourth component
+    Read(u32),
+}
+
+impl std::fmt::Display for PaddingViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotZeroed(binding) => write!(f, "3-component texture at binding {binding} must be stored with a zero fourth component"),
+            Self::Read(binding) => write!(f, "fourth component of 3-component texture at binding {binding} must not be read"),
+        }
+    }
+}
+
+/// Checks that a shader keeps the padding component of 3-component textures inert
+///
+/// wgpu has no 3-component storage format, so textures declared with 3 components are
+/// backed by `rgba32float` and carry an unused fourth component. Writers must store a
+/// literal zero there so the texture contents are deterministic, and readers may only use
+/// the first three components so the padding cannot leak into downstream passes.
+///
+/// Reads are checked conservatively: a fetched texel that is passed on as a whole vector
+/// counts as a read of the padding, even if the receiver ignores it.
+///
+/// # Arguments
+/// * `shader` - WGSL shader source
+/// * `padded_bindings` - Bindings in group 0 of textures declared with 3 components
+///
+/// # Returns
+/// The first violation found, `None` if the padding is handled correctly, or the parser's error message
+pub fn find_padding_violation(shader: &str, padded_bindings: &[u32]) -> Result<Option<PaddingViolation>, String> {
+    let module = naga::front::wgsl::parse_str(shader).map_err(|e| e.emit_to_string(shader))?;
+
+    let functions = module
+        .functions
+        .iter()
+        .map(|(_, function)| function)
+        .chain(module.entry_points.iter().map(|entry_point| &entry_point.function));
+    for function in functions {
+        let expressions = &function.expressions;
+        let padded_binding = |handle: naga::Handle<naga::Expression>| -> Option<u32> {
+            let naga::Expression::GlobalVariable(global) = expressions[handle] else {
+                return None;
+            };
+            let binding = module.global_variables[global].binding.as_ref()?;
+            (binding.group == 0 && padded_bindings.contains(&binding.binding)).then_some(binding.binding)
+        };
+
+        // Texels fetched from padded textures, keyed by the fetching expression
+        let fetched: std::collections::HashMap<_, _> = expressions
+            .iter()
+            .filter_map(|(handle, expression)| match *expression {
+                naga::Expression::ImageLoad { image, .. } | naga::Expression::ImageSample { image, .. } => padded_binding(image).map(|binding| (handle, binding)),
+                _ => None,
+            })
+            .collect();
+
+        for (_, expression) in expressions.iter() {
+            let operands = match expression {
+                naga::Expression::AccessIndex { index, .. } if *index < 3 => continue,
+                naga::Expression::Swizzle { size, pattern, .. } if !pattern[..*size as usize].contains(&naga::SwizzleComponent::W) => continue,
+                _ => expression_operands(expression),
+            };
+            if let Some(binding) = operands.iter().find_map(|operand| fetched.get(operand)) {
+                return Ok(Some(PaddingViolation::Read(*binding)));
+            }
+        }
+
+        let mut violation = None;
+        for_each_statement(&function.body, &mut |statement| {
+            let operands = match statement {
+                naga::Statement::ImageStore { image, value, .. } => {
+                    if let Some(binding) = padded_binding(*image)
+                        && !is_zero_padded(expressions, *value)
+                    {
+                        violation.get_or_insert(PaddingViolation::NotZeroed(binding));
+                    }
+                    vec![*value]
+                }
+                naga::Statement::Store { value, .. } => vec![*value],
+                naga::Statement::Return { value } => value.iter().copied().collect(),
+                naga::Statement::Call { arguments, .. } => arguments.clone(),
+                _ => Vec::new(),
+            };
+            if let Some(binding) = operands.iter().find_map(|operand| fetched.get(operand)) {
+                violation.get_or_insert(PaddingViolation::Read(*binding));
+            }
+        });
+        if violation.is_some() {
+            return Ok(violation);
+        }
+    }
+
+    Ok(None)
+}
+
+/// Returns the operands of an expression that can hold a texel
+fn expression_operands(expression: &naga::Expression) -> Vec<naga::Handle<naga::Expression>> {
+    match expression {
+        naga::Expression::Access { base, index } => vec![*base, *index],
+        naga::Expression::AccessIndex { base, .. } => vec![*base],
+        naga::Expression::Swizzle { vector, .. } => vec![*vector],
+        naga::Expression::Splat { value, .. } => vec![*value],
+        naga::Expression::Compose { components, .. } => components.clone(),
+        naga::Expression::Unary { expr, .. } | naga::Expression::As { expr, .. } => vec![*expr],
+        naga::Expression::Binary { left, right, .. } => vec![*left, *right],
+        naga::Expression::Select { condition, accept, reject } => vec![*condition, *accept, *reject],
+        naga::Expression::Relational { argument, .. } => vec![*argument],
+        naga::Expression::Math { arg, arg1, arg2, arg3, .. } => [Some(*arg), *arg1, *arg2, *arg3].into_iter().flatten().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Returns whether the last component of a stored value is a literal zero
+fn is_zero_padded(expressions: &naga::Arena<naga::Expression>, value: naga::Handle<naga::Expression>) -> bool {
+    match &expressions[value] {
+        naga::Expression::Compose { components, .. } => components.last().is_some_and(|last| is_zero_padded(expressions, *last)),
+        naga::Expression::Splat { value, .. } => is_zero_padded(expressions, *value),
+        naga::Expression::Literal(naga::Literal::F32(value)) => *value == 0.0,
+        naga::Expression::ZeroValue(_) => true,
+        _ => false,
+    }
+}
+
+/// Visits every statement of a block, including those in nested blocks
+fn for_each_statement<F: FnMut(&naga::Statement)>(block: &naga::Block, visit: &mut F) {
+    for statement in block.iter() {
+        visit(statement);
+        match statement {
+            naga::Statement::Block(inner) => for_each_statement(inner, visit),
+            naga::Statement::If { accept, reject, .. } => {
+                for_each_statement(accept, visit);
+                for_each_statement(reject, visit);
+            }
+            naga::Statement::Switch { cases, .. } => {
+                for case in cases {
+                    for_each_statement(&case.body, visit);
+                }
+            }
+            naga::Statement::Loop { body, continuing, .. } => {
+                for_each_statement(body, visit);
+                for_each_statement(continuing, visit);
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! Shader constant substitution
+//!
+//! This module replaces `{{CONST_NAME}}` placeholders in WGSL source with values
+//! declared in the pipeline manifest, so tunable parameters can be set per pipeline.
+
+use regex::Regex;
+use std::{collections::BTreeMap, sync::LazyLock};
+
+/// Matches a `{{CONST_NAME}}` placeholder, allowing whitespace inside the braces: `{{ NAME }}`
+static RE_PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").unwrap());
+
+/// Replaces `{{CONST_NAME}}` placeholders in shader source with declared constant values
+///
+/// Substitution is plain string replacement; values are inserted verbatim.
+/// Sources without placeholders are returned unchanged.
+///
+/// # Arguments
+/// * `source` - WGSL shader source
+/// * `constants` - Constant values declared in the pipeline manifest
+///
+/// # Returns
+/// The shader source with all placeholders replaced, or an `InvalidData` error naming the first undeclared placeholder
+pub fn substitute_constants(source: &str, constants: &BTreeMap<String, String>) -> Result<String, std::io::Error> {
+    if let Some(undeclared) = RE_PLACEHOLDER.captures_iter(source).map(|caps| caps[1].to_string()).find(|name| !constants.contains_key(name)) {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Undeclared shader constant: {undeclared}")));
+    }
+
+    Ok(RE_PLACEHOLDER.replace_all(source, |caps: &regex::Captures| constants[&caps[1]].clone()).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substitute_constants() {
+        let constants = BTreeMap::from([("STRENGTH".to_string(), "0.6".to_string())]);
+
+        let source = "const strength: f32 = {{STRENGTH}};\nconst other: f32 = {{ STRENGTH }};";
+        assert_eq!(substitute_constants(source, &constants).unwrap(), "const strength: f32 = 0.6;\nconst other: f32 = 0.6;");
+
+        // Sources without placeholders pass through unchanged
+        assert_eq!(substitute_constants("let x = 1.0;", &BTreeMap::new()).unwrap(), "let x = 1.0;");
+
+        // Undeclared placeholders are rejected
+        let error = substitute_constants("let x = {{MISSING}};", &constants).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("MISSING"));
+    }
+}
@@ -0,0 +1,31 @@
+// This file is generated by anime4k_wgpu_build::pipelines::generate_embedded_pipelines_module.
+
+/// Manifest of embedded pipeline `test_pipeline`
+const PIPELINE_0_MANIFEST: &[u8] = b"id: test_pipeline\nname: Test Pipeline\npasses:\n- id: pass1\n  file: pass1.wgsl\n  inputs:\n  - id: SOURCE\n    binding: 0\n  outputs:\n  - id: RESULT\n    binding: 1\n    components: 4\n    scale_factor:\n    - \'2\'\n    - \'2\'\n  samplers: []\n";
+
+/// Shader assets of embedded pipeline `test_pipeline`
+const PIPELINE_0_ASSETS: &[(&str, &[u8])] = &[
+    ("pass1.wgsl", b"// \"quoted\" \\ shader\n@compute @workgroup_size(8, 8)\nfn main() {}\n"),
+];
+
+/// IDs of all embedded pipelines
+pub const PIPELINE_IDS: &[&str] = &[
+    "test_pipeline",
+];
+
+/// Compiles an embedded pipeline by ID, or returns None if no pipeline has that ID
+pub fn load_pipeline(id: &str) -> Option<Result<crate::pipelines::ExecutablePipeline, std::boxed::Box<dyn std::error::Error>>> {
+    let (manifest, assets) = match id {
+        "test_pipeline" => (PIPELINE_0_MANIFEST, PIPELINE_0_ASSETS),
+        _ => return None,
+    };
+
+    let compile = || -> Result<crate::pipelines::ExecutablePipeline, std::boxed::Box<dyn std::error::Error>> {
+        let mut asset_map = crate::pipelines::AssetMap::new();
+        for (name, content) in assets {
+            asset_map.insert(name, std::str::from_utf8(content)?);
+        }
+        crate::pipelines::ExecutablePipeline::from_manifest_str(std::str::from_utf8(manifest)?, &asset_map)
+    };
+    Some(compile())
+}
@@ -29,6 +29,10 @@ pub const PREDEFINED_PIPELINES_AUX: &[(&str, &str)] = &[
     // Alternative upscaling algorithms
     ("UPSCALE_DOG_X2", "wgsl/auxiliary/upscale_dog_x2_manifest.yaml"),
     ("UPSCALE_ORIGINAL_X2", "wgsl/auxiliary/upscale_original_x2_manifest.yaml"),
+    // Integer nearest-neighbor upscaling for pixel-art content
+    ("UPSCALE_NEAREST_X2", "wgsl/auxiliary/upscale_nearest_x2_manifest.yaml"),
+    ("UPSCALE_NEAREST_X4", "wgsl/auxiliary/upscale_nearest_x4_manifest.yaml"),
+    ("UPSCALE_NEAREST_X8", "wgsl/auxiliary/upscale_nearest_x8_manifest.yaml"),
 ];
 
 /// A list of predefined CNN (Convolutional Neural Network) pipelines, mapping a name to its GLSL source file.
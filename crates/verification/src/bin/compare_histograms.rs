@@ -0,0 +1,52 @@
+//! Histogram comparison binary
+//!
+//! This binary compares the pass output histograms written by the GLSL and WGSL
+//! reference engines with `--histograms`, printing the distance of every channel
+//! and the first pass output where the distributions diverge.
+
+use anime4k_wgpu_verification::histogram::{first_divergence, read_histograms};
+
+/// Default largest total variation distance considered a match
+const DEFAULT_THRESHOLD: f64 = 0.01;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.len() != 3 && args.len() != 4 {
+        eprintln!("Usage: {} <reference_histograms.json> <histograms.json> [threshold]", args[0]);
+        return Ok(());
+    }
+
+    let reference = read_histograms(&args[1])?;
+    let histograms = read_histograms(&args[2])?;
+    let threshold: f64 = args.get(3).map(|s| s.parse()).transpose().map_err(|e| format!("Invalid threshold: {e}"))?.unwrap_or(DEFAULT_THRESHOLD);
+
+    if reference.len() != histograms.len() {
+        eprintln!(
+            "Warning: {} reference outputs but {} outputs, comparing the first {}",
+            reference.len(),
+            histograms.len(),
+            reference.len().min(histograms.len())
+        );
+    }
+
+    for (index, (reference_texture, texture)) in reference.iter().zip(&histograms).enumerate() {
+        let distances = reference_texture
+            .channels
+            .iter()
+            .zip(&texture.channels)
+            .map(|(a, b)| a.distance(b).map_or("layout differs".to_string(), |distance| format!("{distance:.4}")))
+            .collect::<Vec<_>>();
+        println!("{index}: {} / {}: {}", reference_texture.label, texture.label, distances.join(", "));
+    }
+
+    match first_divergence(&reference, &histograms, threshold) {
+        Some(divergence) => println!(
+            "✗ First divergence at output {} ({} / {}), channel {}: distance {:.4}",
+            divergence.index, divergence.reference_label, divergence.label, divergence.channel, divergence.distance
+        ),
+        None => println!("✓ All compared outputs are within a distance of {threshold}"),
+    }
+
+    Ok(())
+}
@@ -3,13 +3,19 @@
 //! This binary processes images using original GLSL shaders to generate
 //! reference output for verification purposes.
 
-use anime4k_wgpu_verification::glsl_reference_engine::{GlslReferenceEngine, ImageProcessor, analyze_shader};
+use anime4k_wgpu_verification::{
+    glsl_reference_engine::{GlslReferenceEngine, ImageProcessor, analyze_shader},
+    histogram::HistogramConfig,
+};
 use std::path::Path;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args: Vec<String> = std::env::args().collect();
 
+    // Show the engine's per-pass progress; per-binding details are only logged at the trace level
+    tracing_subscriber::fmt().with_max_level(tracing::Level::DEBUG).init();
+
     // Check if analysis mode is requested
     if args.len() > 1 && args[1] == "--analyze" {
         if args.len() != 3 {
@@ -21,10 +27,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         return analyze_shader(shader_path).await;
     }
 
-    if args.len() != 4 {
-        eprintln!("Usage: {} <shader.glsl> <input_image> <output_image>", args[0]);
-        return Ok(());
-    }
+    let histogram_path = match (args.len(), args.get(4).map(String::as_str)) {
+        (6, Some("--histograms")) => Some(&args[5]),
+        (4, _) => None,
+        _ => {
+            eprintln!("Usage: {} <shader.glsl> <input_image> <output_image> [--histograms <histograms.json|csv>]", args[0]);
+            return Ok(());
+        }
+    };
 
     let shader_path = &args[1];
     let input_path = &args[2];
@@ -47,8 +57,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let engine = GlslReferenceEngine::new().await?;
     let mut processor = ImageProcessor::new(engine);
 
-    // Process the image
-    processor.process_shader_pipeline(shader_path, input_path, output_path, true)?;
+    // Process the image, writing histograms of the hook outputs instead of intermediate images if requested
+    if let Some(histogram_path) = histogram_path {
+        processor.process_shader_pipeline_with_histograms(shader_path, input_path, output_path, histogram_path, HistogramConfig::default())?;
+    } else {
+        processor.process_shader_pipeline(shader_path, input_path, output_path, true)?;
+    }
 
     println!("Processing completed successfully!");
 
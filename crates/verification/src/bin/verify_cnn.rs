@@ -4,7 +4,7 @@
 //! Anime4K shaders to verify correctness of the conversion.
 
 use anime4k_wgpu_verification::{
-    compare::{CompareResult, compare_images},
+    compare::{ColorSpace, CompareResult, DEFAULT_TOLERANCE, compare_images_within, diff},
     glsl_reference_engine::{GlslReferenceEngine, ImageProcessor},
     wgsl_reference_engine::{PipelineProcessor, WgslReferenceEngine},
 };
@@ -15,11 +15,19 @@ include!(concat!(env!("OUT_DIR"), "/converted_cnns/cnns.rs"));
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args: Vec<String> = std::env::args().collect();
 
-    if args.len() != 2 {
-        eprintln!("Usage: {} <input_image>", args[0]);
+    if !(2..=4).contains(&args.len()) {
+        eprintln!("Usage: {} <input_image> [gamma|linear] [tolerance]", args[0]);
         return Ok(());
     }
 
+    // Color space in which outputs are compared against the tolerance
+    let color_space: ColorSpace = args.get(2).map(|s| s.parse()).transpose()?.unwrap_or_default();
+    let tolerance: f32 = args
+        .get(3)
+        .map(|s| s.parse().map_err(|e| format!("Invalid tolerance {s}: {e}")))
+        .transpose()?
+        .unwrap_or(DEFAULT_TOLERANCE);
+
     let input_path = &args[1];
     let input_image = image::open(input_path).map_err(|e| format!("Failed to open input image: {e}"))?;
 
@@ -54,8 +62,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         };
 
-        // Compare outputs
-        let result = compare_images(&glsl_output, &wgsl_output);
+        // Compare outputs within the tolerance in the chosen color space
+        let result = compare_images_within(&glsl_output, &wgsl_output, color_space, tolerance);
         match result {
             CompareResult::Match => {
                 println!("✓ Outputs match for shader {name} (GLSL: {glsl_duration:.2?}, WGSL: {wgsl_duration:.2?})");
@@ -70,6 +78,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 a_matched,
             } => {
                 eprintln!("✗ Pixel mismatch for shader {name}: R {r_matched}, G {g_matched}, B {b_matched}, A {a_matched}");
+                if let Some(d) = diff(&glsl_output, &wgsl_output, color_space) {
+                    eprintln!("  {color_space:?} max diff: {:?}, mean diff: {:?}", d.max_abs, d.mean_abs);
+                }
             }
         }
     }
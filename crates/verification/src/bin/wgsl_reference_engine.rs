@@ -4,21 +4,32 @@
 //! reference output for verification purposes. It executes pre-compiled
 //! pipeline manifests and outputs processed images for comparison testing.
 
-use anime4k_wgpu_verification::wgsl_reference_engine::{PipelineProcessor, WgslReferenceEngine};
+use anime4k_wgpu_verification::{
+    histogram::HistogramConfig,
+    wgsl_reference_engine::{PipelineProcessor, WgslReferenceEngine},
+};
 use std::path::Path;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args: Vec<String> = std::env::args().collect();
 
-    if args.len() != 4 {
-        eprintln!("Usage: {} <pipeline.yaml> <input_image> <output_image>", args[0]);
-        return Ok(());
-    }
+    // Show the engine's per-pass progress; per-binding details are only logged at the trace level
+    tracing_subscriber::fmt().with_max_level(tracing::Level::DEBUG).init();
+
+    let histogram_path = match (args.len(), args.get(4).map(String::as_str)) {
+        (6, Some("--histograms")) => Some(&args[5]),
+        (4 | 5, trace_path) if trace_path != Some("--histograms") => None,
+        _ => {
+            eprintln!("Usage: {} <pipeline.yaml> <input_image> <output_image> [trace.tiff | --histograms <histograms.json|csv>]", args[0]);
+            return Ok(());
+        }
+    };
 
     let pipeline_path = &args[1];
     let input_path = &args[2];
     let output_path = &args[3];
+    let trace_path = args.get(4).filter(|_| histogram_path.is_none());
 
     println!("WGSL Reference Engine Starting...");
     println!("- Pipeline: {pipeline_path}");
@@ -39,9 +50,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize processor with all resources pre-allocated
     let mut processor = PipelineProcessor::new_from_file(engine, pipeline_path, input_path, true)?;
 
-    // Execute the pre-prepared pipeline
-    let output_path_base = Path::new(output_path).with_extension("").to_str().unwrap().to_string();
-    processor.execute_pipeline(output_path, Some(&output_path_base))?;
+    // Execute the pre-prepared pipeline, tracing intermediate outputs to a TIFF or histograms if requested
+    if let Some(histogram_path) = histogram_path {
+        processor.execute_pipeline_with_histograms(output_path, histogram_path, HistogramConfig::default())?;
+    } else if let Some(trace_path) = trace_path {
+        processor.execute_pipeline_with_tiff_trace(output_path, trace_path)?;
+    } else {
+        let output_path_base = Path::new(output_path).with_extension("").to_str().unwrap().to_string();
+        processor.execute_pipeline(output_path, Some(&output_path_base))?;
+    }
 
     println!("Processing completed successfully!");
 
@@ -1,7 +1,10 @@
 //! Image comparison utilities for verification
 //!
 //! This module provides functions for comparing GLSL and WGSL output images
-//! to verify implementation correctness.
+//! to verify implementation correctness. Besides exact comparison, images can be
+//! diffed and compared within a tolerance in gamma space (as stored) or in linear
+//! space, so differences that only come from how an engine handles the transfer
+//! function can be told apart from genuine algorithmic divergence.
 
 /// Result of comparing two images
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -75,3 +78,233 @@ pub fn compare_images(glsl_output: &image::Rgba32FImage, wgsl_output: &image::Rg
         }
     }
 }
+
+/// Largest per-component difference the verification binaries accept by default
+///
+/// Well below one 8-bit step, so only floating-point rounding passes.
+pub const DEFAULT_TOLERANCE: f32 = 1e-4;
+
+/// Color space in which per-pixel differences are measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSpace {
+    /// Values as stored in the images (sRGB-encoded)
+    #[default]
+    Gamma,
+    /// Values decoded to linear light with the sRGB transfer function (alpha is left as is)
+    Linear,
+}
+
+impl std::str::FromStr for ColorSpace {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "gamma" => Ok(Self::Gamma),
+            "linear" => Ok(Self::Linear),
+            _ => Err(format!("Invalid color space: {s} (expected gamma or linear)")),
+        }
+    }
+}
+
+/// Per-component differences between two images
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageDiff {
+    /// Color space the differences were measured in
+    pub color_space: ColorSpace,
+    /// Largest absolute difference of each RGBA component
+    pub max_abs: [f32; 4],
+    /// Mean absolute difference of each RGBA component
+    pub mean_abs: [f32; 4],
+}
+
+impl ImageDiff {
+    /// Returns whether every component differs by at most `tolerance`
+    pub fn is_within(&self, tolerance: f32) -> bool {
+        self.max_abs.iter().all(|&d| d <= tolerance)
+    }
+}
+
+/// Converts an sRGB-encoded component to linear light
+///
+/// Values outside [0, 1] are extended symmetrically so out-of-range CNN outputs
+/// still produce comparable differences.
+fn srgb_to_linear(value: f32) -> f32 {
+    let magnitude = value.abs();
+    let linear = if magnitude <= 0.04045 { magnitude / 12.92 } else { ((magnitude + 0.055) / 1.055).powf(2.4) };
+    linear.copysign(value)
+}
+
+/// Computes per-pixel differences between two images in the given color space
+///
+/// # Arguments
+/// * `glsl_output` - Reference image from GLSL implementation
+/// * `wgsl_output` - Test image from WGSL implementation
+/// * `color_space` - Color space the differences are measured in
+///
+/// # Returns
+/// The per-component differences, or `None` if the image dimensions differ
+pub fn diff(glsl_output: &image::Rgba32FImage, wgsl_output: &image::Rgba32FImage, color_space: ColorSpace) -> Option<ImageDiff> {
+    if glsl_output.dimensions() != wgsl_output.dimensions() {
+        return None;
+    }
+
+    let convert = |value: f32, component: usize| match color_space {
+        ColorSpace::Linear if component < 3 => srgb_to_linear(value),
+        _ => value,
+    };
+
+    let mut max_abs = [0.0f32; 4];
+    let mut sum_abs = [0.0f64; 4];
+    for (glsl_pixel, wgsl_pixel) in glsl_output.pixels().zip(wgsl_output.pixels()) {
+        for i in 0..4 {
+            let d = (convert(glsl_pixel[i], i) - convert(wgsl_pixel[i], i)).abs();
+            // `f32::max` ignores NaN, but a non-finite output must never look like a match
+            max_abs[i] = if d.is_nan() || max_abs[i].is_nan() { f32::NAN } else { max_abs[i].max(d) };
+            sum_abs[i] += d as f64;
+        }
+    }
+
+    let pixel_count = (glsl_output.width() as u64 * glsl_output.height() as u64).max(1) as f64;
+    Some(ImageDiff {
+        color_space,
+        max_abs,
+        mean_abs: sum_abs.map(|sum| (sum / pixel_count) as f32),
+    })
+}
+
+/// Compares two RGBA32F images within a tolerance in the given color space
+///
+/// A component matches if no pixel differs in it by more than `tolerance` after
+/// converting both images to `color_space`.
+///
+/// # Arguments
+/// * `glsl_output` - Reference image from GLSL implementation
+/// * `wgsl_output` - Test image from WGSL implementation
+/// * `color_space` - Color space the differences are measured in
+/// * `tolerance` - Largest absolute difference accepted per component
+///
+/// # Returns
+/// A `CompareResult` indicating whether the images match and details about any differences
+pub fn compare_images_within(glsl_output: &image::Rgba32FImage, wgsl_output: &image::Rgba32FImage, color_space: ColorSpace, tolerance: f32) -> CompareResult {
+    let Some(image_diff) = diff(glsl_output, wgsl_output, color_space) else {
+        return CompareResult::DimensionMismatch {
+            glsl_dimensions: glsl_output.dimensions(),
+            wgsl_dimensions: wgsl_output.dimensions(),
+        };
+    };
+
+    if image_diff.is_within(tolerance) {
+        return CompareResult::Match;
+    }
+    let matched = image_diff.max_abs.map(|d| d <= tolerance);
+    CompareResult::PixelMismatch {
+        r_matched: matched[0],
+        g_matched: matched[1],
+        b_matched: matched[2],
+        a_matched: matched[3],
+    }
+}
+
+/// Computes per-pixel differences between two images as stored (gamma space)
+///
+/// See [`diff`] for details.
+pub fn diff_gamma(glsl_output: &image::Rgba32FImage, wgsl_output: &image::Rgba32FImage) -> Option<ImageDiff> {
+    diff(glsl_output, wgsl_output, ColorSpace::Gamma)
+}
+
+/// Computes per-pixel differences between two images after decoding both to linear light
+///
+/// See [`diff`] for details.
+pub fn diff_linear(glsl_output: &image::Rgba32FImage, wgsl_output: &image::Rgba32FImage) -> Option<ImageDiff> {
+    diff(glsl_output, wgsl_output, ColorSpace::Linear)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a 2x1 image from two pixels
+    fn image_of(pixels: [[f32; 4]; 2]) -> image::Rgba32FImage {
+        image::Rgba32FImage::from_fn(2, 1, |x, _| image::Rgba(pixels[x as usize]))
+    }
+
+    /// Tests that the same gamma-space difference is smaller in linear space near black
+    #[test]
+    fn test_diff_linear_vs_gamma() {
+        let a = image_of([[0.02, 0.5, 1.0, 1.0], [0.0, 0.0, 0.0, 0.5]]);
+        let b = image_of([[0.03, 0.5, 1.0, 1.0], [0.0, 0.0, 0.0, 0.5]]);
+
+        let gamma = diff_gamma(&a, &b).unwrap();
+        let linear = diff_linear(&a, &b).unwrap();
+        assert!((gamma.max_abs[0] - 0.01).abs() < 1e-6);
+        assert!(linear.max_abs[0] < gamma.max_abs[0]);
+        assert!((gamma.mean_abs[0] - 0.005).abs() < 1e-6);
+        assert_eq!(linear.max_abs[1..], [0.0; 3]);
+        assert!(linear.is_within(0.001));
+        assert!(!gamma.is_within(0.001));
+    }
+
+    /// Tests that alpha is never converted and mismatched dimensions are rejected
+    #[test]
+    fn test_diff_alpha_and_dimensions() {
+        let a = image_of([[0.5, 0.5, 0.5, 0.2], [0.5, 0.5, 0.5, 0.2]]);
+        let b = image_of([[0.5, 0.5, 0.5, 0.4], [0.5, 0.5, 0.5, 0.2]]);
+        assert_eq!(diff_linear(&a, &b).unwrap().max_abs[3], diff_gamma(&a, &b).unwrap().max_abs[3]);
+
+        let c = image::Rgba32FImage::new(1, 1);
+        assert_eq!(diff_linear(&a, &c), None);
+    }
+
+    /// Tests that the color space decides the verdict when the spaces disagree
+    #[test]
+    fn test_compare_images_within_color_space() {
+        // A difference near black shrinks in linear light, one near white grows
+        let dark = (image_of([[0.02, 0.0, 0.0, 1.0]; 2]), image_of([[0.03, 0.0, 0.0, 1.0]; 2]));
+        assert_eq!(
+            compare_images_within(&dark.0, &dark.1, ColorSpace::Gamma, 0.005),
+            CompareResult::PixelMismatch {
+                r_matched: false,
+                g_matched: true,
+                b_matched: true,
+                a_matched: true,
+            }
+        );
+        assert_eq!(compare_images_within(&dark.0, &dark.1, ColorSpace::Linear, 0.005), CompareResult::Match);
+
+        let bright = (image_of([[0.0, 0.9, 0.0, 1.0]; 2]), image_of([[0.0, 0.904, 0.0, 1.0]; 2]));
+        assert_eq!(compare_images_within(&bright.0, &bright.1, ColorSpace::Gamma, 0.005), CompareResult::Match);
+        assert_eq!(
+            compare_images_within(&bright.0, &bright.1, ColorSpace::Linear, 0.005),
+            CompareResult::PixelMismatch {
+                r_matched: true,
+                g_matched: false,
+                b_matched: true,
+                a_matched: true,
+            }
+        );
+    }
+
+    /// Tests that non-finite values and mismatched dimensions never match
+    #[test]
+    fn test_compare_images_within_rejects() {
+        let a = image_of([[0.5, 0.5, 0.5, 1.0]; 2]);
+        let b = image_of([[f32::NAN, 0.5, 0.5, 1.0], [0.5, 0.5, 0.5, 1.0]]);
+        assert!(matches!(compare_images_within(&a, &b, ColorSpace::Gamma, 1.0), CompareResult::PixelMismatch { r_matched: false, .. }));
+        assert_eq!(
+            compare_images_within(&a, &image::Rgba32FImage::new(1, 1), ColorSpace::Linear, 1.0),
+            CompareResult::DimensionMismatch {
+                glsl_dimensions: (2, 1),
+                wgsl_dimensions: (1, 1),
+            }
+        );
+    }
+
+    /// Tests the endpoints of the sRGB transfer function
+    #[test]
+    fn test_srgb_to_linear() {
+        assert_eq!(srgb_to_linear(0.0), 0.0);
+        assert!((srgb_to_linear(1.0) - 1.0).abs() < 1e-6);
+        assert!((srgb_to_linear(-1.0) + 1.0).abs() < 1e-6);
+        assert!((srgb_to_linear(0.5) - 0.21404).abs() < 1e-4);
+    }
+}
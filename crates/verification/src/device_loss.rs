@@ -0,0 +1,87 @@
+//! Device loss detection for the reference engines
+//!
+//! Lets long verification runs tell a lost GPU device apart from ordinary failures,
+//! so the caller can recreate the engine and retry the remaining work.
+
+use std::sync::{Arc, Mutex};
+
+/// Error returned when an engine's wgpu device has been lost (e.g. timeout or reset)
+#[derive(Debug, Clone)]
+pub struct DeviceLostError {
+    /// Reason reported by wgpu
+    pub reason: wgpu::DeviceLostReason,
+    /// Message reported by wgpu
+    pub message: String,
+}
+
+impl std::fmt::Display for DeviceLostError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GPU device lost ({:?}): {}", self.reason, self.message)
+    }
+}
+
+impl std::error::Error for DeviceLostError {}
+
+/// Returns whether an error returned by a reference engine was caused by device loss
+///
+/// # Arguments
+/// * `error` - The error to inspect
+///
+/// # Returns
+/// `true` if the engine should be recreated before retrying
+pub fn is_device_lost(error: &(dyn std::error::Error + 'static)) -> bool {
+    error.downcast_ref::<DeviceLostError>().is_some()
+}
+
+/// Records the lost state of a wgpu device via its device lost callback
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DeviceLossMonitor {
+    /// The loss reported by the callback, if any
+    lost: Arc<Mutex<Option<DeviceLostError>>>,
+}
+
+impl DeviceLossMonitor {
+    /// Installs a device lost callback on `device` and returns a monitor observing it
+    pub(crate) fn install(device: &wgpu::Device) -> Self {
+        let monitor = Self::default();
+        let lost = monitor.lost.clone();
+        device.set_device_lost_callback(move |reason, message| {
+            *lost.lock().unwrap() = Some(DeviceLostError { reason, message });
+        });
+        monitor
+    }
+
+    /// Returns the recorded device loss, if any
+    pub(crate) fn lost(&self) -> Option<DeviceLostError> {
+        self.lost.lock().unwrap().clone()
+    }
+
+    /// Replaces the outcome of an engine operation with `DeviceLostError` if the device was lost
+    ///
+    /// Errors raised while the device is lost are usually opaque symptoms (failed buffer
+    /// mappings, validation errors), so the loss takes precedence over them.
+    pub(crate) fn check_result<T>(&self, result: Result<T, Box<dyn std::error::Error>>) -> Result<T, Box<dyn std::error::Error>> {
+        match self.lost() {
+            Some(error) => Err(Box::new(error)),
+            None => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loss_takes_precedence_over_result() {
+        let monitor = DeviceLossMonitor::default();
+        assert_eq!(monitor.check_result(Ok::<_, Box<dyn std::error::Error>>(1)).unwrap(), 1);
+
+        *monitor.lost.lock().unwrap() = Some(DeviceLostError {
+            reason: wgpu::DeviceLostReason::Unknown,
+            message: "reset".to_string(),
+        });
+        let error = monitor.check_result(Err::<(), _>("map failed".into())).unwrap_err();
+        assert!(is_device_lost(error.as_ref()));
+    }
+}
@@ -3,9 +3,13 @@
 //! This module provides a reference implementation engine that processes
 //! original GLSL shaders to generate reference output for verification.
 
+use crate::device_loss::DeviceLossMonitor;
+use crate::histogram::{HistogramCollector, HistogramConfig, write_histograms};
 use crate::wgpu_helpers::*;
-use anime4k_wgpu_build::pipelines::SamplerFilterMode;
-use regex::Regex;
+use anime4k_wgpu_build::{
+    mpv::{Dimension, parse_dimension_expr},
+    pipelines::SamplerFilterMode,
+};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
@@ -50,12 +54,16 @@ pub struct MpvHook {
 /// Processes original GLSL shaders and executes them to generate
 /// reference images for verification against WGSL implementations.
 pub struct GlslReferenceEngine {
+    /// The adapter the device was created from, reused when the device is recreated
+    adapter: wgpu::Adapter,
     /// The wgpu device
     device: wgpu::Device,
     /// The wgpu command queue
     queue: wgpu::Queue,
     /// Cache of compiled shader modules
     shader_cache: HashMap<String, wgpu::ShaderModule>,
+    /// Observes the device lost callback
+    device_loss: DeviceLossMonitor,
 }
 
 /// Image processor that manages texture state during pipeline execution
@@ -176,41 +184,15 @@ impl MpvHook {
     }
 
     fn calculate_output_size(&self, available_textures: &HashMap<String, wgpu::Texture>) -> Result<(u32, u32), Box<dyn std::error::Error>> {
-        // Helper function to parse mpv dimension expressions
-        // Supports formats like "MAIN.w", "HOOKED.h", "intermediate.w 2 *", "MAIN.h 2 /"
+        // Resolves an mpv dimension expression such as "MAIN.w" or "HOOKED.h 2 /" against the textures
         let parse_dimension_expression = |expr: &str, available_textures: &HashMap<String, wgpu::Texture>| -> Result<u32, Box<dyn std::error::Error>> {
-            // Regex to match: texture_name.dimension [factor] [operator]
-            let texture_dimension_regex = Regex::new(r"(\w+)\.([wh])(?:\s+(\d+)\s*([*/]))?").unwrap();
-            let captures = texture_dimension_regex.captures(expr).ok_or_else(|| format!("Invalid dimension expression: '{expr}'"))?;
-
-            let texture_name = &captures[1];
-            let dimension = &captures[2];
-
-            let (factor, op) = if let (Some(factor), Some(op)) = (captures.get(3), captures.get(4)) {
-                (factor.as_str().parse::<u32>()?, op.as_str())
-            } else {
-                (1, "*")
-            };
-            if factor == 0 {
-                return Err("Factor cannot be zero in dimension expression".into());
-            }
-
-            let texture = available_textures
-                .get(texture_name)
-                .ok_or_else(|| format!("Texture '{texture_name}' not found in available textures"))?;
-
-            let base_size = match dimension {
-                "w" => texture.size().width,
-                "h" => texture.size().height,
-                _ => return Err(format!("Invalid dimension '{dimension}', expected 'w' or 'h'").into()),
-            };
-
-            let result = match op {
-                "*" => base_size * factor,
-                "/" => base_size / factor,
-                _ => return Err(format!("Invalid operator '{op}', expected '*' or '/'").into()),
+            let expr = parse_dimension_expr(expr, available_textures)?;
+            let texture = &available_textures[&expr.texture];
+            let base_size = match expr.dimension {
+                Dimension::Width => texture.size().width,
+                Dimension::Height => texture.size().height,
             };
-            Ok(result)
+            Ok(expr.apply(base_size))
         };
 
         // Calculate width using helper function
@@ -484,6 +466,22 @@ impl GlslReferenceEngine {
             })
             .await?;
 
+        Self::from_adapter(adapter).await
+    }
+
+    /// Creates an engine on an adapter the caller has already selected
+    ///
+    /// Lets the GLSL reference run on the same adapter as a [`crate::wgsl_reference_engine::WgslReferenceEngine`]
+    /// instead of whichever adapter [`Self::new`] would request.
+    ///
+    /// # Arguments
+    /// * `adapter` - The adapter to create the device from
+    ///
+    /// # Returns
+    /// A new engine instance or an error if the adapter is unsuitable
+    pub async fn from_adapter(adapter: wgpu::Adapter) -> Result<Self, Box<dyn std::error::Error>> {
+        check_storage_format_support(&adapter)?;
+
         // Request device with features required for GLSL shader processing
         let (device, queue) = adapter
             .request_device(&wgpu::DeviceDescriptor {
@@ -496,6 +494,8 @@ impl GlslReferenceEngine {
             .await?;
 
         Ok(Self {
+            adapter,
+            device_loss: DeviceLossMonitor::install(&device),
             device,
             queue,
             shader_cache: HashMap::new(), // Initialize empty cache for compiled shaders
@@ -546,6 +546,22 @@ impl ImageProcessor {
         }
     }
 
+    /// Rebuilds the engine's device after it has been lost
+    ///
+    /// Creates a fresh engine and drops all intermediate textures and cached shaders,
+    /// which belonged to the lost device. Call this after an operation failed with
+    /// `DeviceLostError`, then retry the interrupted pipeline and continue with the
+    /// remaining work.
+    ///
+    /// # Returns
+    /// Result indicating whether a new device could be created
+    pub async fn recreate(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.engine = GlslReferenceEngine::from_adapter(self.engine.adapter.clone()).await?;
+        self.intermediate_textures.clear();
+
+        Ok(())
+    }
+
     /// Initializes the pipeline texture state with the input image
     ///
     /// Sets up the initial HOOKED and MAIN textures that hooks can reference.
@@ -757,13 +773,19 @@ impl ImageProcessor {
     /// * `hook` - The mpv hook to process
     /// * `hook_index` - Index of this hook for debugging and labeling
     /// * `output_path` - Optional path to save intermediate output for debugging
-    /// * `log` - Whether to enable debug logging
+    /// * `log` - Whether to emit `tracing` events describing each step
     ///
     /// # Returns
-    /// Result indicating success or failure of the processing
+    /// Result indicating success or failure of the processing, or `DeviceLostError` if the device was lost
     pub fn process_single_hook(&mut self, hook: &MpvHook, hook_index: usize, output_path: Option<&str>, log: bool) -> Result<(), Box<dyn std::error::Error>> {
+        let result = self.run_single_hook(hook, hook_index, output_path, log);
+        self.engine.device_loss.check_result(result)
+    }
+
+    /// Implementation of `process_single_hook` without device loss detection
+    fn run_single_hook(&mut self, hook: &MpvHook, hook_index: usize, output_path: Option<&str>, log: bool) -> Result<(), Box<dyn std::error::Error>> {
         if log {
-            println!("Processing hook {}: {}", hook_index, hook.desc);
+            tracing::debug!("Processing hook {}: {}", hook_index, hook.desc);
         }
 
         // Calculate output dimensions based on hook's WIDTH/HEIGHT directives
@@ -803,7 +825,7 @@ impl ImageProcessor {
         if let Some(output_path) = output_path {
             save_texture_as_image_file(&self.engine.device, &self.engine.queue, &output_texture, output_path)?;
             if log {
-                println!("- Pass {hook_index} output saved to: {output_path}");
+                tracing::info!("- Pass {hook_index} output saved to: {output_path}");
             }
         }
 
@@ -811,7 +833,7 @@ impl ImageProcessor {
         self.update_intermediate_textures(hook, output_texture);
 
         if log {
-            println!("- Hook {hook_index} completed: {output_width}x{output_height}");
+            tracing::debug!("- Hook {hook_index} completed: {output_width}x{output_height}");
         }
 
         Ok(())
@@ -830,16 +852,22 @@ impl ImageProcessor {
     /// * `save_intermediate_textures` - Whether to save intermediate outputs from each hook
     ///
     /// # Returns
-    /// Result indicating success or failure of the entire pipeline
+    /// Result indicating success or failure of the entire pipeline, or `DeviceLostError` if the device was lost
     pub fn process_shader_pipeline(&mut self, shader_path: &str, input_path: &str, output_path: &str, save_intermediate_textures: bool) -> Result<(), Box<dyn std::error::Error>> {
+        let result = self.run_shader_pipeline(shader_path, input_path, output_path, save_intermediate_textures);
+        self.engine.device_loss.check_result(result)
+    }
+
+    /// Implementation of `process_shader_pipeline` without device loss detection
+    fn run_shader_pipeline(&mut self, shader_path: &str, input_path: &str, output_path: &str, save_intermediate_textures: bool) -> Result<(), Box<dyn std::error::Error>> {
         // Load shader file and parse all mpv hooks from the GLSL source
         let shader_source = fs::read_to_string(shader_path)?;
         let hooks = MpvHook::parse_from_glsl(&shader_source)?;
 
         // Log information about the discovered hooks
-        println!("Found {} hooks in shader", hooks.len());
+        tracing::info!("Found {} hooks in shader", hooks.len());
         for (i, hook) in hooks.iter().enumerate() {
-            println!("- Hook {i}: {} ({})", hook.desc, hook.hook);
+            tracing::debug!("- Hook {i}: {} ({})", hook.desc, hook.hook);
         }
 
         // Load input image
@@ -857,13 +885,77 @@ impl ImageProcessor {
             } else {
                 None
             };
-            self.process_single_hook(hook, hook_index, pass_output_path.as_deref(), true)?;
+            self.run_single_hook(hook, hook_index, pass_output_path.as_deref(), true)?;
         }
 
         // Save the final result from the MAIN texture
         if let Some(final_texture) = self.intermediate_textures.get("MAIN") {
             save_texture_as_image_file(&self.engine.device, &self.engine.queue, final_texture, output_path)?;
-            println!("Final result saved to: {output_path}");
+            tracing::info!("Final result saved to: {output_path}");
+        } else {
+            return Err("No final output texture found".into());
+        }
+
+        Ok(())
+    }
+
+    /// Processes a complete shader pipeline from file, writing a histogram of every hook output
+    ///
+    /// Behaves like `process_shader_pipeline` without intermediate images, but counts the
+    /// raw values of every channel of every hook output into histograms, written as CSV
+    /// or JSON depending on the extension of `histogram_path`.
+    ///
+    /// # Arguments
+    /// * `shader_path` - Path to the GLSL shader file containing mpv hooks
+    /// * `input_path` - Path to the input image file
+    /// * `output_path` - Path where to save the final processed image
+    /// * `histogram_path` - Path of the CSV or JSON file to write the histograms to
+    /// * `config` - Bin layout of the histograms
+    ///
+    /// # Returns
+    /// Result indicating success or failure of the entire pipeline, or `DeviceLostError` if the device was lost
+    pub fn process_shader_pipeline_with_histograms(
+        &mut self,
+        shader_path: &str,
+        input_path: &str,
+        output_path: &str,
+        histogram_path: &str,
+        config: HistogramConfig,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let result = self.run_shader_pipeline_with_histograms(shader_path, input_path, output_path, histogram_path, config);
+        self.engine.device_loss.check_result(result)
+    }
+
+    /// Implementation of `process_shader_pipeline_with_histograms` without device loss detection
+    fn run_shader_pipeline_with_histograms(&mut self, shader_path: &str, input_path: &str, output_path: &str, histogram_path: &str, config: HistogramConfig) -> Result<(), Box<dyn std::error::Error>> {
+        let shader_source = fs::read_to_string(shader_path)?;
+        let hooks = MpvHook::parse_from_glsl(&shader_source)?;
+        tracing::info!("Found {} hooks in shader", hooks.len());
+
+        let input_texture = load_image_file_as_texture(&self.engine.device, &self.engine.queue, input_path)?;
+        self.initialize_pipeline_textures(input_texture);
+
+        let collector = HistogramCollector::new(&self.engine.device, config);
+        let mut histograms = Vec::new();
+        for (hook_index, hook) in hooks.iter().enumerate() {
+            self.run_single_hook(hook, hook_index, None, true)?;
+
+            // The hook's output is stored under its save name, MAIN if it has none
+            let save_name = hook.save.as_deref().unwrap_or("MAIN");
+            let texture = self
+                .intermediate_textures
+                .get(save_name)
+                .ok_or_else(|| format!("Output texture {save_name} of hook {hook_index} not found"))?;
+            let label = format!("hook{} {}", hook_index + 1, hook.desc);
+            histograms.push(collector.collect(&self.engine.device, &self.engine.queue, texture, &label)?);
+        }
+
+        write_histograms(histogram_path, &histograms)?;
+        tracing::info!("Histograms of {} hook outputs saved to: {histogram_path}", histograms.len());
+
+        if let Some(final_texture) = self.intermediate_textures.get("MAIN") {
+            save_texture_as_image_file(&self.engine.device, &self.engine.queue, final_texture, output_path)?;
+            tracing::info!("Final result saved to: {output_path}");
         } else {
             return Err("No final output texture found".into());
         }
@@ -882,8 +974,14 @@ impl ImageProcessor {
     /// * `input_image` - Input image to process
     ///
     /// # Returns
-    /// Tuple of (processed image, processing duration) or error
+    /// Tuple of (processed image, processing duration), or an error (`DeviceLostError` if the device was lost)
     pub fn process_shader_pipeline_no_io(&mut self, shader_source: &str, input_image: &image::DynamicImage) -> Result<(image::Rgba32FImage, std::time::Duration), Box<dyn std::error::Error>> {
+        let result = self.run_shader_pipeline_no_io(shader_source, input_image);
+        self.engine.device_loss.check_result(result)
+    }
+
+    /// Implementation of `process_shader_pipeline_no_io` without device loss detection
+    fn run_shader_pipeline_no_io(&mut self, shader_source: &str, input_image: &image::DynamicImage) -> Result<(image::Rgba32FImage, std::time::Duration), Box<dyn std::error::Error>> {
         // Parse mpv hooks from the provided GLSL source
         let hooks = MpvHook::parse_from_glsl(shader_source)?;
 
@@ -898,7 +996,7 @@ impl ImageProcessor {
 
         // Process each hook in sequence without saving intermediate outputs
         for (hook_index, hook) in hooks.iter().enumerate() {
-            self.process_single_hook(hook, hook_index, None, false)?;
+            self.run_single_hook(hook, hook_index, None, false)?;
         }
 
         // Extract the final result from the MAIN texture
@@ -0,0 +1,492 @@
+//! Per-channel histograms of pass outputs
+//!
+//! Intermediate CNN feature maps hold values far outside 0-1, which the PNG and TIFF
+//! dumps clamp or normalize away. A histogram of every channel of every pass output,
+//! computed on the GPU from the raw float values, shows where the value distribution of
+//! a converted pipeline starts to diverge from the GLSL reference even when the
+//! divergence is not visible in a pixel diff.
+//!
+//! Histograms are written as JSON (with the finite minimum and maximum of each channel)
+//! or as CSV with one row per bin, chosen by the file extension.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Workgroup size of the histogram shader in each dimension
+const WORKGROUP_SIZE: u32 = 8;
+
+/// Counters per channel besides the bins: below, above, non-finite, minimum key, maximum key
+const EXTRA_COUNTERS: u32 = 5;
+
+/// Bin layout shared by every histogram of a run
+///
+/// Histograms are only comparable with the same layout, so the GLSL and WGSL runs of a
+/// comparison must use the same configuration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistogramConfig {
+    /// Number of equal-width bins between `range_min` and `range_max`
+    pub bins: u32,
+    /// Lower bound of the first bin
+    pub range_min: f32,
+    /// Upper bound of the last bin
+    pub range_max: f32,
+}
+
+impl Default for HistogramConfig {
+    /// 256 bins over -8 to 8, which covers the activations of the Anime4K CNNs
+    fn default() -> Self {
+        Self {
+            bins: 256,
+            range_min: -8.0,
+            range_max: 8.0,
+        }
+    }
+}
+
+/// Histogram of one channel of a texture
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelHistogram {
+    /// Lower bound of the first bin
+    pub range_min: f32,
+    /// Upper bound of the last bin
+    pub range_max: f32,
+    /// Number of values in each bin
+    pub counts: Vec<u32>,
+    /// Number of finite values below `range_min`
+    pub below: u32,
+    /// Number of finite values above `range_max`
+    pub above: u32,
+    /// Number of NaN or infinite values
+    pub non_finite: u32,
+    /// Smallest finite value, or None if the channel has none
+    pub min: Option<f32>,
+    /// Largest finite value, or None if the channel has none
+    pub max: Option<f32>,
+}
+
+impl ChannelHistogram {
+    /// Returns the number of values counted, including those outside the range
+    pub fn total(&self) -> u64 {
+        self.counts.iter().map(|&count| count as u64).sum::<u64>() + self.below as u64 + self.above as u64 + self.non_finite as u64
+    }
+
+    /// Calculates the total variation distance to another histogram
+    ///
+    /// Both histograms are normalized to their totals, so textures of different sizes
+    /// can be compared. The out-of-range and non-finite counters are treated as bins.
+    ///
+    /// # Arguments
+    /// * `other` - Histogram to compare with
+    ///
+    /// # Returns
+    /// The distance from 0 (identical distributions) to 1 (disjoint), or None if the
+    /// histograms have different bin layouts
+    pub fn distance(&self, other: &ChannelHistogram) -> Option<f64> {
+        if self.counts.len() != other.counts.len() || self.range_min != other.range_min || self.range_max != other.range_max {
+            return None;
+        }
+
+        let (total, other_total) = (self.total().max(1) as f64, other.total().max(1) as f64);
+        fn bins(histogram: &ChannelHistogram) -> impl Iterator<Item = u32> + '_ {
+            histogram.counts.iter().copied().chain([histogram.below, histogram.above, histogram.non_finite])
+        }
+        let sum = bins(self).zip(bins(other)).map(|(a, b)| (a as f64 / total - b as f64 / other_total).abs()).sum::<f64>();
+        Some(sum / 2.0)
+    }
+}
+
+/// Histograms of every channel of one texture
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextureHistogram {
+    /// Pass or hook that wrote the texture, and the texture within it
+    pub label: String,
+    /// Width of the texture in pixels
+    pub width: u32,
+    /// Height of the texture in pixels
+    pub height: u32,
+    /// One histogram per channel of the texture format
+    pub channels: Vec<ChannelHistogram>,
+}
+
+/// A channel of a pass output whose distribution diverges between two runs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    /// Index of the pass output in both runs
+    pub index: usize,
+    /// Label of the pass output in the reference run
+    pub reference_label: String,
+    /// Label of the pass output in the compared run
+    pub label: String,
+    /// Channel index
+    pub channel: usize,
+    /// Total variation distance between the two histograms
+    pub distance: f64,
+}
+
+/// Finds the first pass output whose histograms diverge between two runs
+///
+/// Pass outputs are matched by position, as the converted CNN writes one texture per
+/// GLSL hook in the same order. Outputs with a different channel count or bin layout
+/// count as divergent with a distance of 1.
+///
+/// # Arguments
+/// * `reference` - Histograms of the reference run, typically the GLSL engine
+/// * `histograms` - Histograms of the run to check
+/// * `threshold` - Largest distance still considered a match
+///
+/// # Returns
+/// The first divergent channel, or None if every matched output is within the threshold
+pub fn first_divergence(reference: &[TextureHistogram], histograms: &[TextureHistogram], threshold: f64) -> Option<Divergence> {
+    reference.iter().zip(histograms).enumerate().find_map(|(index, (reference_texture, texture))| {
+        let divergence = |channel: usize, distance: f64| Divergence {
+            index,
+            reference_label: reference_texture.label.clone(),
+            label: texture.label.clone(),
+            channel,
+            distance,
+        };
+        if reference_texture.channels.len() != texture.channels.len() {
+            return Some(divergence(0, 1.0));
+        }
+        reference_texture
+            .channels
+            .iter()
+            .zip(&texture.channels)
+            .map(|(a, b)| a.distance(b).unwrap_or(1.0))
+            .enumerate()
+            .find(|&(_, distance)| distance > threshold)
+            .map(|(channel, distance)| divergence(channel, distance))
+    })
+}
+
+/// Computes histograms of textures on the GPU
+pub struct HistogramCollector {
+    /// Bin layout of every histogram
+    config: HistogramConfig,
+    /// Layout of the texture, parameter, and counter bindings
+    bind_group_layout: wgpu::BindGroupLayout,
+    /// The histogram compute pipeline
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl HistogramCollector {
+    /// Creates a collector and compiles its shader
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device the textures belong to
+    /// * `config` - Bin layout of every histogram
+    pub fn new(device: &wgpu::Device, config: HistogramConfig) -> Self {
+        let shader_module = device.create_shader_module(wgpu::include_wgsl!("histogram.wgsl"));
+        // Explicit layout, as 32-bit float textures are only filterable with FLOAT32_FILTERABLE
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Histogram"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Histogram"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Histogram"),
+            layout: Some(&pipeline_layout),
+            module: &shader_module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        Self { config, bind_group_layout, pipeline }
+    }
+
+    /// Computes the histogram of every channel of a texture
+    ///
+    /// Waits for the GPU, so the texture holds the output of every previously submitted pass.
+    ///
+    /// # Arguments
+    /// * `device` - The wgpu device
+    /// * `queue` - The wgpu command queue
+    /// * `texture` - R32Float, Rg32Float, or Rgba32Float texture with TEXTURE_BINDING usage
+    /// * `label` - Label recorded in the result
+    ///
+    /// # Returns
+    /// The histograms of the texture's channels, or an error for other texture formats
+    pub fn collect(&self, device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture, label: &str) -> Result<TextureHistogram, Box<dyn std::error::Error>> {
+        let components = match texture.format() {
+            wgpu::TextureFormat::R32Float => 1,
+            wgpu::TextureFormat::Rg32Float => 2,
+            wgpu::TextureFormat::Rgba32Float => 4,
+            format => return Err(format!("Unsupported texture format for histograms: {format:?}").into()),
+        };
+
+        let HistogramConfig { bins, range_min, range_max } = self.config;
+        let stride = bins + EXTRA_COUNTERS;
+        let counter_size = (components * stride) as u64 * 4;
+
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Histogram Parameters"),
+            size: 16,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let params_data = [range_min.to_le_bytes(), range_max.to_le_bytes(), bins.to_le_bytes(), components.to_le_bytes()].concat();
+        queue.write_buffer(&params, 0, &params_data);
+
+        // The minimum keys start at the largest key and everything else at zero
+        let counters = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Histogram Counters"),
+            size: counter_size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let initial_counters = (0..components * stride)
+            .flat_map(|index| (if index % stride == bins + 3 { u32::MAX } else { 0 }).to_le_bytes())
+            .collect::<Vec<_>>();
+        queue.write_buffer(&counters, 0, &initial_counters);
+
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Histogram Readback"),
+            size: counter_size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Histogram"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&texture.create_view(&Default::default())),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: counters.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Histogram") });
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Histogram"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&self.pipeline);
+            compute_pass.set_bind_group(0, &bind_group, &[]);
+            compute_pass.dispatch_workgroups(texture.width().div_ceil(WORKGROUP_SIZE), texture.height().div_ceil(WORKGROUP_SIZE), 1);
+        }
+        encoder.copy_buffer_to_buffer(&counters, 0, &readback, 0, counter_size);
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let buffer_slice = readback.slice(..);
+        let (sender, receiver) = futures_intrusive::channel::shared::oneshot_channel();
+        buffer_slice.map_async(wgpu::MapMode::Read, move |v| sender.send(v).unwrap());
+        device.poll(wgpu::PollType::Wait)?;
+        pollster::block_on(receiver.receive()).ok_or("Failed to map buffer for reading")??;
+
+        let data = buffer_slice.get_mapped_range();
+        let values: &[u32] = bytemuck::cast_slice(&data);
+        let channels = values
+            .chunks(stride as usize)
+            .map(|channel| {
+                let bins = bins as usize;
+                let non_finite = channel[bins + 2];
+                let has_finite = channel[..bins + 2].iter().any(|&count| count > 0);
+                ChannelHistogram {
+                    range_min,
+                    range_max,
+                    counts: channel[..bins].to_vec(),
+                    below: channel[bins],
+                    above: channel[bins + 1],
+                    non_finite,
+                    min: has_finite.then(|| decode_order_key(channel[bins + 3])),
+                    max: has_finite.then(|| decode_order_key(channel[bins + 4])),
+                }
+            })
+            .collect();
+
+        Ok(TextureHistogram {
+            label: label.to_string(),
+            width: texture.width(),
+            height: texture.height(),
+            channels,
+        })
+    }
+}
+
+/// Decodes a float from the order-preserving key used by the histogram shader
+fn decode_order_key(key: u32) -> f32 {
+    f32::from_bits(if key & 0x8000_0000 != 0 { key & 0x7fff_ffff } else { !key })
+}
+
+/// Formats histograms as CSV with one row per bin
+///
+/// The out-of-range and non-finite counters are rows with the bin `below`, `above`, and
+/// `non_finite`; the minimum and maximum are only written to JSON.
+fn to_csv(histograms: &[TextureHistogram]) -> String {
+    let mut csv = String::from("label,width,height,channel,bin,lower,upper,count\n");
+    for texture in histograms {
+        let prefix = format!("\"{}\",{},{}", texture.label.replace('"', "\"\""), texture.width, texture.height);
+        for (channel, histogram) in texture.channels.iter().enumerate() {
+            let width = (histogram.range_max - histogram.range_min) / histogram.counts.len() as f32;
+            for (bin, count) in histogram.counts.iter().enumerate() {
+                let lower = histogram.range_min + width * bin as f32;
+                csv.push_str(&format!("{prefix},{channel},{bin},{lower},{},{count}\n", lower + width));
+            }
+            csv.push_str(&format!("{prefix},{channel},below,,{},{}\n", histogram.range_min, histogram.below));
+            csv.push_str(&format!("{prefix},{channel},above,{},,{}\n", histogram.range_max, histogram.above));
+            csv.push_str(&format!("{prefix},{channel},non_finite,,,{}\n", histogram.non_finite));
+        }
+    }
+    csv
+}
+
+/// Writes histograms to a file, as CSV if the path ends in `.csv` and as JSON otherwise
+///
+/// # Arguments
+/// * `path` - Path of the file to write
+/// * `histograms` - Histograms of the pass outputs in execution order
+pub fn write_histograms(path: &str, histograms: &[TextureHistogram]) -> Result<(), Box<dyn std::error::Error>> {
+    let is_csv = Path::new(path).extension().is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+    let content = if is_csv { to_csv(histograms) } else { serde_json::to_string_pretty(histograms)? };
+    fs::write(path, content)?;
+    Ok(())
+}
+
+/// Reads histograms from a JSON file written by `write_histograms`
+///
+/// # Arguments
+/// * `path` - Path of the JSON file
+pub fn read_histograms(path: &str) -> Result<Vec<TextureHistogram>, Box<dyn std::error::Error>> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a 4-bin histogram over 0-1 with the given counts
+    fn histogram(counts: [u32; 4], below: u32) -> ChannelHistogram {
+        ChannelHistogram {
+            range_min: 0.0,
+            range_max: 1.0,
+            counts: counts.to_vec(),
+            below,
+            above: 0,
+            non_finite: 0,
+            min: None,
+            max: None,
+        }
+    }
+
+    /// Tests that decoding inverts the order-preserving encoding of the shader
+    #[test]
+    fn test_decode_order_key() {
+        let encode = |value: f32| {
+            let bits = value.to_bits();
+            if bits & 0x8000_0000 != 0 { !bits } else { bits | 0x8000_0000 }
+        };
+        let values = [-1e30, -2.5, -0.0, 0.0, 1e-20, 3.0, 1e30];
+        for value in values {
+            assert_eq!(decode_order_key(encode(value)).to_bits(), value.to_bits());
+        }
+        for pair in values.windows(2) {
+            assert!(encode(pair[0]) <= encode(pair[1]), "{} should not order after {}", pair[0], pair[1]);
+        }
+    }
+
+    /// Tests the distance between histograms, including normalization and layout checks
+    #[test]
+    fn test_distance() {
+        let a = histogram([1, 1, 0, 0], 0);
+        assert_eq!(a.distance(&a), Some(0.0));
+        assert_eq!(a.distance(&histogram([4, 4, 0, 0], 0)), Some(0.0));
+        assert_eq!(a.distance(&histogram([0, 0, 1, 1], 0)), Some(1.0));
+        assert_eq!(a.distance(&histogram([1, 0, 0, 0], 1)), Some(0.5));
+
+        let mut other_range = a.clone();
+        other_range.range_max = 2.0;
+        assert_eq!(a.distance(&other_range), None);
+    }
+
+    /// Tests that the first divergent channel is reported
+    #[test]
+    fn test_first_divergence() {
+        let texture = |label: &str, channels: Vec<ChannelHistogram>| TextureHistogram {
+            label: label.to_string(),
+            width: 2,
+            height: 1,
+            channels,
+        };
+        let reference = vec![
+            texture("hook 1", vec![histogram([1, 1, 0, 0], 0)]),
+            texture("hook 2", vec![histogram([1, 1, 0, 0], 0), histogram([2, 0, 0, 0], 0)]),
+        ];
+        let matching = vec![
+            texture("pass 1", vec![histogram([1, 1, 0, 0], 0)]),
+            texture("pass 2", vec![histogram([1, 1, 0, 0], 0), histogram([2, 0, 0, 0], 0)]),
+        ];
+        assert_eq!(first_divergence(&reference, &matching, 0.01), None);
+
+        let diverging = vec![
+            texture("pass 1", vec![histogram([1, 1, 0, 0], 0)]),
+            texture("pass 2", vec![histogram([1, 1, 0, 0], 0), histogram([0, 0, 0, 0], 2)]),
+        ];
+        let divergence = first_divergence(&reference, &diverging, 0.01).unwrap();
+        assert_eq!((divergence.index, divergence.channel, divergence.distance), (1, 1, 1.0));
+        assert_eq!((divergence.reference_label.as_str(), divergence.label.as_str()), ("hook 2", "pass 2"));
+    }
+
+    /// Tests the CSV rows of a histogram
+    #[test]
+    fn test_to_csv() {
+        let csv = to_csv(&[TextureHistogram {
+            label: "conv2d".to_string(),
+            width: 2,
+            height: 2,
+            channels: vec![histogram([1, 2, 0, 0], 1)],
+        }]);
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 8);
+        assert_eq!(lines[1], "\"conv2d\",2,2,0,0,0,0.25,1");
+        assert_eq!(lines[5], "\"conv2d\",2,2,0,below,,0,1");
+        assert_eq!(lines[7], "\"conv2d\",2,2,0,non_finite,,,0");
+    }
+}
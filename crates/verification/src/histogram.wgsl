@@ -0,0 +1,56 @@
+// Per-channel histogram of a floating-point texture
+//
+// Counts every channel value into one of `bins` equal bins over [range_min, range_max],
+// with separate counters for values below and above the range and for NaN/infinity.
+// The finite minimum and maximum are tracked with atomics on an order-preserving
+// integer encoding of the float bits, decoded on the CPU.
+
+struct Params {
+    range_min: f32,
+    range_max: f32,
+    bins: u32,
+    components: u32,
+}
+
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> params: Params;
+@group(0) @binding(2) var<storage, read_write> counters: array<atomic<u32>>;
+
+// Maps float bits to an unsigned integer with the same ordering as the floats
+fn order_key(bits: u32) -> u32 {
+    return select(bits | 0x80000000u, ~bits, (bits & 0x80000000u) != 0u);
+}
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) global_id: vec3u) {
+    let dims = textureDimensions(source_texture);
+    if global_id.x >= dims.x || global_id.y >= dims.y {
+        return;
+    }
+
+    let texel = textureLoad(source_texture, vec2i(global_id.xy), 0);
+    // Layout per channel: bins, below, above, non-finite, minimum key, maximum key
+    let stride = params.bins + 5u;
+    for (var channel = 0u; channel < params.components; channel++) {
+        let value = texel[channel];
+        let bits = bitcast<u32>(value);
+        let base = channel * stride;
+
+        if (bits & 0x7f800000u) == 0x7f800000u {
+            atomicAdd(&counters[base + params.bins + 2u], 1u);
+            continue;
+        }
+
+        atomicMin(&counters[base + params.bins + 3u], order_key(bits));
+        atomicMax(&counters[base + params.bins + 4u], order_key(bits));
+
+        if value < params.range_min {
+            atomicAdd(&counters[base + params.bins], 1u);
+        } else if value > params.range_max {
+            atomicAdd(&counters[base + params.bins + 1u], 1u);
+        } else {
+            let position = (value - params.range_min) / (params.range_max - params.range_min) * f32(params.bins);
+            atomicAdd(&counters[base + min(u32(position), params.bins - 1u)], 1u);
+        }
+    }
+}
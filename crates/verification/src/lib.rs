@@ -4,6 +4,10 @@
 //! Anime4K implementation against reference implementations.
 
 pub mod compare;
+pub mod device_loss;
 pub mod glsl_reference_engine;
+pub mod histogram;
+pub mod pass_trace;
+pub mod thumbnail;
 mod wgpu_helpers;
 pub mod wgsl_reference_engine;
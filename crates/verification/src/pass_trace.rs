@@ -0,0 +1,152 @@
+//! Multi-page TIFF traces of pipeline pass outputs
+//!
+//! This module collects the output of every pass into a single multi-page TIFF,
+//! giving a complete trace of a pipeline run in one file.
+
+use std::fs::File;
+use std::io::BufWriter;
+use tiff::encoder::{TiffEncoder, colortype::RGBA16};
+use tiff::tags::Tag;
+
+/// Normalizes an image for display by stretching each channel to the full 0-1 range
+///
+/// Intermediate CNN feature maps are frequently negative or far outside 0-1, so each
+/// channel is remapped from its own minimum/maximum. CNN passes store a fourth feature
+/// in alpha, so alpha is normalized like the color channels. Constant channels and
+/// non-finite values map to 0.
+///
+/// # Arguments
+/// * `image` - Raw pass output
+///
+/// # Returns
+/// A copy of the image with every channel normalized to 0-1
+pub fn normalize_for_display(image: &image::Rgba32FImage) -> image::Rgba32FImage {
+    let mut min = [f32::INFINITY; 4];
+    let mut max = [f32::NEG_INFINITY; 4];
+    for pixel in image.pixels() {
+        for i in 0..4 {
+            if pixel[i].is_finite() {
+                min[i] = min[i].min(pixel[i]);
+                max[i] = max[i].max(pixel[i]);
+            }
+        }
+    }
+
+    let mut normalized = image.clone();
+    for pixel in normalized.pixels_mut() {
+        for i in 0..4 {
+            let range = max[i] - min[i];
+            pixel[i] = if range > 0.0 && pixel[i].is_finite() { (pixel[i] - min[i]) / range } else { 0.0 };
+        }
+    }
+    normalized
+}
+
+/// Writer for a multi-page TIFF containing one page per pass output
+///
+/// Each page is normalized with `normalize_for_display` and stored as 16-bit RGBA.
+/// The fourth channel is tagged as unspecified extra data rather than alpha, so image
+/// viewers show every page opaque while the channel still holds its feature map.
+/// The page's ImageDescription tag records the pass id, physical texture id, and dimensions.
+pub struct PassTraceWriter {
+    /// Underlying TIFF encoder
+    encoder: TiffEncoder<BufWriter<File>>,
+    /// Number of pages written so far
+    page_count: usize,
+}
+
+impl PassTraceWriter {
+    /// Creates a new trace file, truncating any existing file at the path
+    ///
+    /// # Arguments
+    /// * `path` - Path of the TIFF file to write
+    pub fn create(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let encoder = TiffEncoder::new(BufWriter::new(File::create(path)?))?;
+        Ok(Self { encoder, page_count: 0 })
+    }
+
+    /// Appends a pass output as a new page
+    ///
+    /// # Arguments
+    /// * `pass_id` - Identifier of the pass that produced the image
+    /// * `physical_id` - Physical texture ID the output was read from
+    /// * `image` - Raw pass output
+    pub fn write_page(&mut self, pass_id: &str, physical_id: u32, image: &image::Rgba32FImage) -> Result<(), Box<dyn std::error::Error>> {
+        let (width, height) = image.dimensions();
+        let data: Vec<u16> = normalize_for_display(image).as_raw().iter().map(|&v| (v * u16::MAX as f32).round() as u16).collect();
+
+        let description = format!("pass={pass_id} physical_texture={physical_id} size={width}x{height}");
+        let mut page = self.encoder.new_image::<RGBA16>(width, height)?;
+        page.encoder().write_tag(Tag::ImageDescription, description.as_str())?;
+        // ExtraSamples value 0: unspecified data, not transparency
+        page.encoder().write_tag(Tag::ExtraSamples, 0u16)?;
+        page.write_data(&data)?;
+
+        self.page_count += 1;
+        Ok(())
+    }
+
+    /// Returns the number of pages written so far
+    pub fn page_count(&self) -> usize {
+        self.page_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_for_display() {
+        let image = image::Rgba32FImage::from_fn(2, 1, |x, _| if x == 0 { image::Rgba([-2.0, 0.5, 3.0, 1.0]) } else { image::Rgba([2.0, 0.5, 5.0, -4.0]) });
+
+        let normalized = normalize_for_display(&image);
+        assert_eq!(normalized.get_pixel(0, 0).0, [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(normalized.get_pixel(1, 0).0, [1.0, 0.0, 1.0, 0.0]);
+    }
+
+    /// Tests that non-finite values neither stretch the range nor survive normalization
+    #[test]
+    fn test_normalize_for_display_non_finite() {
+        let pixels = [[f32::NAN, 1.0, 0.0, f32::INFINITY], [0.0, f32::NEG_INFINITY, 2.0, 4.0], [4.0, 3.0, f32::NAN, 2.0]];
+        let image = image::Rgba32FImage::from_fn(3, 1, |x, _| image::Rgba(pixels[x as usize]));
+
+        let normalized = normalize_for_display(&image);
+        assert_eq!(normalized.get_pixel(0, 0).0, [0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(normalized.get_pixel(1, 0).0, [0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(normalized.get_pixel(2, 0).0, [1.0, 1.0, 0.0, 0.0]);
+    }
+
+    /// Tests that constant channels, including a constant alpha, map to 0 without dividing by zero
+    #[test]
+    fn test_normalize_for_display_constant() {
+        let image = image::Rgba32FImage::from_fn(2, 2, |x, y| image::Rgba([0.25, (x + y) as f32, -7.0, 1.0]));
+
+        let normalized = normalize_for_display(&image);
+        for pixel in normalized.pixels() {
+            assert_eq!([pixel[0], pixel[2], pixel[3]], [0.0; 3]);
+        }
+        assert_eq!(normalized.get_pixel(0, 0)[1], 0.0);
+        assert_eq!(normalized.get_pixel(1, 0)[1], 0.5);
+        assert_eq!(normalized.get_pixel(1, 1)[1], 1.0);
+    }
+
+    /// Tests that pages keep the fourth channel and mark it as data rather than alpha
+    #[test]
+    fn test_write_page_extra_samples() {
+        let path = std::env::temp_dir().join(format!("anime4k_wgpu_pass_trace_{}.tiff", std::process::id()));
+        let image = image::Rgba32FImage::from_fn(2, 1, |x, _| image::Rgba([0.0, 0.0, 0.0, x as f32]));
+        let mut writer = PassTraceWriter::create(path.to_str().unwrap()).unwrap();
+        writer.write_page("Pass 1", 0, &image).unwrap();
+        drop(writer);
+
+        let mut decoder = tiff::decoder::Decoder::new(File::open(&path).unwrap()).unwrap();
+        let extra_samples = decoder.get_tag_u32(Tag::ExtraSamples).unwrap();
+        let tiff::decoder::DecodingResult::U16(data) = decoder.read_image().unwrap() else {
+            panic!("expected 16-bit samples");
+        };
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(extra_samples, 0);
+        assert_eq!([data[3], data[7]], [0, u16::MAX]);
+    }
+}
@@ -0,0 +1,84 @@
+//! Thumbnail generation for visual comparison of outputs
+//!
+//! This module provides downsampling helpers for producing preview images
+//! that preserve perceived brightness of the full-resolution output.
+
+/// Converts an sRGB-encoded component to linear light
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 { value / 12.92 } else { ((value + 0.055) / 1.055).powf(2.4) }
+}
+
+/// Converts a linear-light component to sRGB encoding
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 { value * 12.92 } else { 1.055 * value.powf(1.0 / 2.4) - 0.055 }
+}
+
+/// Downsamples an sRGB-encoded image by an integer factor using a box filter in linear light
+///
+/// Averaging directly in gamma space darkens high-contrast detail such as line art edges,
+/// so color components are converted to linear light before averaging and converted back afterwards.
+/// Alpha is averaged as-is. Edge blocks that extend past the image bounds only average the pixels that exist.
+///
+/// # Arguments
+/// * `image` - Source image with sRGB-encoded color components
+/// * `factor` - Downsampling factor applied to both dimensions (0 is treated as 1)
+///
+/// # Returns
+/// The downsampled image with dimensions rounded up to cover the whole source
+pub fn downsample_srgb_correct(image: &image::Rgba32FImage, factor: u32) -> image::Rgba32FImage {
+    let factor = factor.max(1);
+    let (width, height) = image.dimensions();
+    let out_width = width.div_ceil(factor);
+    let out_height = height.div_ceil(factor);
+
+    image::Rgba32FImage::from_fn(out_width, out_height, |out_x, out_y| {
+        let x_range = (out_x * factor)..((out_x + 1) * factor).min(width);
+        let y_range = (out_y * factor)..((out_y + 1) * factor).min(height);
+
+        let mut sum = [0.0f64; 4];
+        let mut count = 0u32;
+        for y in y_range {
+            for x in x_range.clone() {
+                let pixel = image.get_pixel(x, y);
+                for (i, total) in sum.iter_mut().enumerate() {
+                    *total += if i < 3 { srgb_to_linear(pixel[i]) } else { pixel[i] } as f64;
+                }
+                count += 1;
+            }
+        }
+
+        let mut result = [0.0f32; 4];
+        for (i, value) in result.iter_mut().enumerate() {
+            let average = (sum[i] / count as f64) as f32;
+            *value = if i < 3 { linear_to_srgb(average) } else { average };
+        }
+        image::Rgba(result)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that a black/white checkerboard averages to linear mid-grey rather than sRGB 0.5
+    #[test]
+    fn test_checkerboard_downsample_is_linear() {
+        let checkerboard = image::Rgba32FImage::from_fn(4, 4, |x, y| {
+            let value = if (x + y) % 2 == 0 { 1.0 } else { 0.0 };
+            image::Rgba([value, value, value, 1.0])
+        });
+
+        let thumbnail = downsample_srgb_correct(&checkerboard, 2);
+        assert_eq!(thumbnail.dimensions(), (2, 2));
+
+        // 50% linear coverage encodes to roughly 0.735 in sRGB; gamma-space averaging would give 0.5
+        let expected = linear_to_srgb(0.5);
+        for pixel in thumbnail.pixels() {
+            for i in 0..3 {
+                assert!((pixel[i] - expected).abs() < 1e-4, "expected {expected}, got {}", pixel[i]);
+            }
+            assert_eq!(pixel[3], 1.0);
+        }
+        assert!(expected > 0.7);
+    }
+}
@@ -93,6 +93,9 @@ pub fn load_image_as_texture(device: &wgpu::Device, queue: &wgpu::Queue, image:
     // Convert image to RGBA32F format for consistent processing
     let rgba_image = image.to_rgba32f();
     let (width, height) = rgba_image.dimensions();
+    if width == 0 || height == 0 {
+        return Err(Box::new(std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("Invalid input image size {width}x{height}"))));
+    }
 
     // Create texture with input usage flags
     let texture = create_texture(device, width, height, wgpu::TextureFormat::Rgba32Float, TEXTURE_USAGE_INPUT);
@@ -136,6 +139,37 @@ pub fn load_image_file_as_texture(device: &wgpu::Device, queue: &wgpu::Queue, im
     load_image_as_texture(device, queue, &image::open(image_path)?)
 }
 
+/// Checks that an adapter supports storage binding for the 32-bit float texture formats
+///
+/// The reference engines write every intermediate texture as R32Float, Rg32Float, or
+/// Rgba32Float storage, so an adapter lacking support for one would otherwise only fail
+/// during pipeline creation with an error that does not name the format.
+///
+/// # Arguments
+/// * `adapter` - The adapter to check
+///
+/// # Returns
+/// `Ok(())` if every format is supported, or an error naming the unsupported formats
+pub fn check_storage_format_support(adapter: &wgpu::Adapter) -> Result<(), Box<dyn std::error::Error>> {
+    let unsupported = [wgpu::TextureFormat::R32Float, wgpu::TextureFormat::Rg32Float, wgpu::TextureFormat::Rgba32Float]
+        .into_iter()
+        .filter(|&format| !adapter.get_texture_format_features(format).allowed_usages.contains(wgpu::TextureUsages::STORAGE_BINDING))
+        .map(|format| format!("{format:?}"))
+        .collect::<Vec<_>>();
+    if unsupported.is_empty() {
+        return Ok(());
+    }
+
+    let info = adapter.get_info();
+    Err(format!(
+        "Adapter '{}' ({:?}) does not support storage binding for {}; try another GPU or graphics backend",
+        info.name,
+        info.backend,
+        unsupported.join(", ")
+    )
+    .into())
+}
+
 /// Reads a wgpu texture back to an RGBA32F image
 ///
 /// # Arguments
@@ -162,9 +196,10 @@ pub fn save_texture_as_image(device: &wgpu::Device, queue: &wgpu::Queue, texture
         _ => return Err(format!("Unsupported texture format for saving: {format:?}").into()),
     };
 
-    // Calculate buffer requirements
-    let buffer_size = (width * height * components * bytes_per_component) as u64;
-    let bytes_per_row = width * components * bytes_per_component;
+    // Calculate buffer requirements; rows in the buffer must be aligned for the copy
+    let unpadded_bytes_per_row = width * components * bytes_per_component;
+    let bytes_per_row = unpadded_bytes_per_row.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let buffer_size = (bytes_per_row * height) as u64;
 
     // Create a buffer to copy texture data to CPU
     let buffer = device.create_buffer(&wgpu::BufferDescriptor {
@@ -208,16 +243,19 @@ pub fn save_texture_as_image(device: &wgpu::Device, queue: &wgpu::Queue, texture
     device.poll(wgpu::PollType::Wait)?;
     pollster::block_on(receiver.receive()).ok_or("Failed to map buffer for reading")??;
 
-    // Get the mapped data as f32 values
+    // Get the mapped data as f32 values, dropping the row padding
     let data = buffer_slice.get_mapped_range();
-    let float_data: &[f32] = bytemuck::cast_slice(&data);
+    let float_data: Vec<f32> = data
+        .chunks(bytes_per_row as usize)
+        .flat_map(|row| bytemuck::cast_slice::<u8, f32>(&row[..unpadded_bytes_per_row as usize]).iter().copied())
+        .collect();
 
     // Convert the texture data to RGBA32F format based on source format
     let image = match components {
         1 => {
             // R32Float - expand single component to grayscale RGBA
             let mut rgba_data = Vec::with_capacity((width * height * 4) as usize);
-            for &r in float_data {
+            for &r in &float_data {
                 // Use absolute value to handle potential negative values
                 let val = r.abs();
                 rgba_data.push(val); // R
@@ -240,7 +278,7 @@ pub fn save_texture_as_image(device: &wgpu::Device, queue: &wgpu::Queue, texture
         }
         4 => {
             // RGBA32Float - direct conversion, already in the right format
-            image::Rgba32FImage::from_raw(width, height, float_data.to_vec()).ok_or("Failed to create RGBA32F image from data")?
+            image::Rgba32FImage::from_raw(width, height, float_data).ok_or("Failed to create RGBA32F image from data")?
         }
         _ => return Err(format!("Unsupported component count: {components}").into()),
     };
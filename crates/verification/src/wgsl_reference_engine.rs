@@ -3,8 +3,11 @@
 //! This module provides a reference implementation engine that processes
 //! WGSL shader pipelines to generate reference output for verification.
 
+use crate::device_loss::DeviceLossMonitor;
+use crate::histogram::{HistogramCollector, HistogramConfig, write_histograms};
+use crate::pass_trace::PassTraceWriter;
 use crate::wgpu_helpers::*;
-use anime4k_wgpu_build::pipelines::{ExecutablePass, ExecutablePipeline, PhysicalTexture, SamplerFilterMode};
+use anime4k_wgpu_build::pipelines::{AssetMap, ExecutablePass, ExecutablePipeline, PhysicalTexture, SamplerFilterMode};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
@@ -39,10 +42,14 @@ struct PreparedPass {
 /// Provides a high-performance GPU-based engine for executing WGSL shader pipelines.
 /// Used primarily for verification and testing of converted shader implementations.
 pub struct WgslReferenceEngine {
+    /// The adapter the device was created from, reused when the device is recreated
+    adapter: wgpu::Adapter,
     /// The wgpu device
     device: wgpu::Device,
     /// The wgpu command queue
     queue: wgpu::Queue,
+    /// Observes the device lost callback
+    device_loss: DeviceLossMonitor,
 }
 
 /// Pipeline processor that manages execution of an analyzed pipeline
@@ -64,7 +71,9 @@ pub struct PipelineProcessor {
     input_width: u32,
     /// Input image height
     input_height: u32,
-    /// Whether to enable debug logging
+    /// Input image, kept to re-upload it after the device is recreated
+    input_image: image::DynamicImage,
+    /// Whether to emit `tracing` events describing each step
     log: bool,
 }
 
@@ -87,6 +96,22 @@ impl WgslReferenceEngine {
             })
             .await?;
 
+        Self::from_adapter(adapter).await
+    }
+
+    /// Creates an engine on a caller-selected adapter
+    ///
+    /// Requests the device with the features and limits the engine needs, so callers that
+    /// pick an adapter themselves get the same device as [`Self::new`] would create.
+    ///
+    /// # Arguments
+    /// * `adapter` - The adapter to create the device from
+    ///
+    /// # Returns
+    /// A new engine instance or an error if the adapter is unsuitable
+    pub async fn from_adapter(adapter: wgpu::Adapter) -> Result<Self, Box<dyn std::error::Error>> {
+        check_storage_format_support(&adapter)?;
+
         let (device, queue) = adapter
             .request_device(&wgpu::DeviceDescriptor {
                 label: Some("WGSL Reference Engine"),
@@ -97,7 +122,12 @@ impl WgslReferenceEngine {
             })
             .await?;
 
-        Ok(Self { device, queue })
+        Ok(Self {
+            adapter,
+            device_loss: DeviceLossMonitor::install(&device),
+            device,
+            queue,
+        })
     }
 }
 
@@ -108,13 +138,14 @@ impl PipelineProcessor {
     /// * `engine` - The WGSL reference engine to use
     /// * `pipeline_path` - Path to the pipeline manifest YAML file
     /// * `input_path` - Path to the input image file
-    /// * `log` - Whether to enable debug logging
+    /// * `log` - Whether to emit `tracing` events describing each step
     ///
     /// # Returns
     /// A configured pipeline processor ready for execution
     pub fn new_from_file(engine: WgslReferenceEngine, pipeline_path: &str, input_path: &str, log: bool) -> Result<Self, Box<dyn std::error::Error>> {
         // Load input image
-        let input_texture = load_image_file_as_texture(&engine.device, &engine.queue, input_path)?;
+        let input_image = image::open(input_path)?;
+        let input_texture = load_image_as_texture(&engine.device, &engine.queue, &input_image)?;
 
         let wgpu::Extent3d {
             width: input_width,
@@ -125,11 +156,7 @@ impl PipelineProcessor {
         // Load and compile pipeline
         let executable_pipeline = Self::load_and_compile_pipeline(pipeline_path)?;
 
-        let mut sampler_map: HashMap<SamplerFilterMode, wgpu::Sampler> = HashMap::new();
-        for filter_mode in executable_pipeline.required_samplers.iter().copied() {
-            let sampler = create_sampler(&engine.device, filter_mode);
-            sampler_map.insert(filter_mode, sampler);
-        }
+        let sampler_map = Self::create_sampler_map(&engine.device, &executable_pipeline);
 
         let mut processor = Self {
             engine,
@@ -139,6 +166,7 @@ impl PipelineProcessor {
             sampler_map,
             input_width,
             input_height,
+            input_image,
             log,
         };
 
@@ -164,18 +192,10 @@ impl PipelineProcessor {
         } = input_texture.size();
 
         // Load and compile pipeline
-        let executable_pipeline = ExecutablePipeline::from_yaml(pipeline_content, |file| {
-            shader_map
-                .get(file)
-                .map(|&content| content.to_string())
-                .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, format!("Failed to load shader file '{file}'")))
-        })?;
-
-        let mut sampler_map: HashMap<SamplerFilterMode, wgpu::Sampler> = HashMap::new();
-        for filter_mode in executable_pipeline.required_samplers.iter().copied() {
-            let sampler = create_sampler(&engine.device, filter_mode);
-            sampler_map.insert(filter_mode, sampler);
-        }
+        let assets: AssetMap = shader_map.iter().map(|(&name, &content)| (name, content)).collect();
+        let executable_pipeline = ExecutablePipeline::from_manifest_str(pipeline_content, &assets)?;
+
+        let sampler_map = Self::create_sampler_map(&engine.device, &executable_pipeline);
 
         let mut processor = Self {
             engine,
@@ -185,6 +205,7 @@ impl PipelineProcessor {
             sampler_map,
             input_width,
             input_height,
+            input_image: input_image.clone(),
             log,
         };
 
@@ -193,6 +214,33 @@ impl PipelineProcessor {
         Ok(processor)
     }
 
+    /// Creates one sampler per filter mode required by the pipeline
+    fn create_sampler_map(device: &wgpu::Device, executable_pipeline: &ExecutablePipeline) -> HashMap<SamplerFilterMode, wgpu::Sampler> {
+        executable_pipeline
+            .required_samplers
+            .iter()
+            .map(|&filter_mode| (filter_mode, create_sampler(device, filter_mode)))
+            .collect()
+    }
+
+    /// Rebuilds the engine's device and all pipeline resources after the device has been lost
+    ///
+    /// Creates a fresh engine, re-uploads the input image, and recreates samplers,
+    /// physical textures, and prepared passes. Call this after an execution failed with
+    /// `DeviceLostError`, then retry the execution.
+    ///
+    /// # Returns
+    /// Result indicating whether the device and resources could be recreated
+    pub async fn recreate(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.engine = WgslReferenceEngine::from_adapter(self.engine.adapter.clone()).await?;
+        self.sampler_map = Self::create_sampler_map(&self.engine.device, &self.executable_pipeline);
+        self.physical_textures.clear();
+        self.prepared_passes.clear();
+
+        let input_texture = load_image_as_texture(&self.engine.device, &self.engine.queue, &self.input_image)?;
+        self.initialize_all_resources(input_texture)
+    }
+
     /// Loads and compiles a pipeline from a YAML manifest file
     ///
     /// Reads the pipeline specification from a YAML file and compiles it into
@@ -242,12 +290,12 @@ impl PipelineProcessor {
     /// # Returns
     /// Result indicating success or failure
     fn initialize_all_resources(&mut self, input_texture: wgpu::Texture) -> Result<(), Box<dyn std::error::Error>> {
-        // Log pipeline information if debug logging is enabled
+        // Log pipeline information if logging is enabled
         if self.log {
-            println!("Initializing pipeline: {} ({})", self.executable_pipeline.name, self.executable_pipeline.id);
-            println!("Description: {}", self.executable_pipeline.description.as_deref().unwrap_or("No description"));
-            println!("Found {} shader passes", self.executable_pipeline.passes.len());
-            println!("Found {} physical textures", self.executable_pipeline.physical_textures.len());
+            tracing::info!("Initializing pipeline: {} ({})", self.executable_pipeline.name, self.executable_pipeline.id);
+            tracing::debug!("Description: {}", self.executable_pipeline.description.as_deref().unwrap_or("No description"));
+            tracing::debug!("Found {} shader passes", self.executable_pipeline.passes.len());
+            tracing::debug!("Found {} physical textures", self.executable_pipeline.physical_textures.len());
         }
 
         // Step 1: Allocate all physical textures based on the executable pipeline
@@ -256,9 +304,9 @@ impl PipelineProcessor {
         // Step 2: Prepare all shader passes (compile shaders, create pipelines and bind groups)
         self.prepare_all_shader_passes()?;
 
-        // Log successful completion if debug logging is enabled
+        // Log successful completion if logging is enabled
         if self.log {
-            println!("All resources initialized successfully");
+            tracing::debug!("All resources initialized successfully");
         }
 
         Ok(())
@@ -278,9 +326,12 @@ impl PipelineProcessor {
                 // Source texture: use the provided input texture directly
                 self.physical_textures.insert(physical_texture.id, input_texture.clone());
                 if self.log {
-                    println!(
+                    tracing::debug!(
                         "Assigned SOURCE texture (ID {}): {}x{} components={}",
-                        physical_texture.id, self.input_width, self.input_height, physical_texture.components
+                        physical_texture.id,
+                        self.input_width,
+                        self.input_height,
+                        physical_texture.components
                     );
                 }
             } else {
@@ -292,9 +343,13 @@ impl PipelineProcessor {
                 let texture = create_texture(&self.engine.device, width, height, format, TEXTURE_USAGE_STORAGE);
                 self.physical_textures.insert(physical_texture.id, texture);
                 if self.log {
-                    println!(
+                    tracing::debug!(
                         "Allocated physical texture (ID {}): {}x{} {:?} components={}",
-                        physical_texture.id, width, height, format, physical_texture.components
+                        physical_texture.id,
+                        width,
+                        height,
+                        format,
+                        physical_texture.components
                     );
                 }
             }
@@ -311,15 +366,9 @@ impl PipelineProcessor {
     /// # Returns
     /// Tuple of (width, height) for the texture
     fn calculate_physical_texture_dimensions(&self, physical_texture: &PhysicalTexture) -> (u32, u32) {
-        // Extract scale factors from the physical texture definition
-        let width_scale = physical_texture.scale_factor.0.to_f64();
-        let height_scale = physical_texture.scale_factor.1.to_f64();
-
         // Apply scale factors to input dimensions and floor to get integer dimensions
-        let width = (self.input_width as f64 * width_scale).floor() as u32;
-        let height = (self.input_height as f64 * height_scale).floor() as u32;
-
-        (width, height)
+        let (width_scale, height_scale) = physical_texture.scale_factor;
+        (width_scale.apply(self.input_width), height_scale.apply(self.input_height))
     }
 
     /// Prepares all shader passes by compiling shaders and creating pipelines
@@ -330,8 +379,8 @@ impl PipelineProcessor {
         // Process each shader pass in the pipeline
         for (pass_index, shader_pass) in self.executable_pipeline.passes.iter().enumerate() {
             if self.log {
-                println!("Preparing shader pass {}: {}", pass_index, shader_pass.id);
-                println!("  Creating shader module for pass '{}' with {} chars of WGSL", shader_pass.id, shader_pass.shader.len());
+                tracing::debug!("Preparing shader pass {}: {}", pass_index, shader_pass.id);
+                tracing::trace!("  Creating shader module for pass '{}' with {} chars of WGSL", shader_pass.id, shader_pass.shader.len());
             }
 
             // Compile the WGSL shader into a shader module
@@ -340,7 +389,7 @@ impl PipelineProcessor {
                 source: wgpu::ShaderSource::Wgsl(shader_pass.shader.clone().into()),
             });
             if self.log {
-                println!("  Shader module created successfully");
+                tracing::trace!("  Shader module created successfully");
             }
 
             // Create bind group layout describing all resources this pass needs
@@ -423,10 +472,7 @@ impl PipelineProcessor {
                 bind_group,
                 output_physical_ids,
                 // Calculate actual compute dimensions based on scale factors
-                compute_dimensions: (
-                    (self.input_width as f64 * shader_pass.compute_scale_factors.0).floor() as u32,
-                    (self.input_height as f64 * shader_pass.compute_scale_factors.1).floor() as u32,
-                ),
+                compute_dimensions: shader_pass.compute_dimensions(self.input_width, self.input_height),
             };
 
             // Add the prepared pass to the execution queue
@@ -452,7 +498,7 @@ impl PipelineProcessor {
         let mut bind_group_entries = Vec::new();
 
         if self.log {
-            println!(
+            tracing::trace!(
                 "Creating bind group for pass '{}' with {} inputs, {} outputs, {} samplers",
                 shader_pass.id,
                 shader_pass.input_textures.len(),
@@ -475,7 +521,7 @@ impl PipelineProcessor {
         // Add input texture bindings to the bind group entries
         for (input, texture_view) in shader_pass.input_textures.iter().zip(input_texture_views.iter()) {
             if self.log {
-                println!("  Adding input binding {}: {} (physical ID {})", input.binding, input.logical_id, input.physical_id);
+                tracing::trace!("  Adding input binding {}: {} (physical ID {})", input.binding, input.logical_id, input.physical_id);
             }
             bind_group_entries.push(wgpu::BindGroupEntry {
                 binding: input.binding,
@@ -497,7 +543,7 @@ impl PipelineProcessor {
         // Add output texture bindings to the bind group entries
         for (output, texture_view) in shader_pass.output_textures.iter().zip(output_texture_views.iter()) {
             if self.log {
-                println!("  Adding output binding {}: {} (physical ID {})", output.binding, output.logical_id, output.physical_id);
+                tracing::trace!("  Adding output binding {}: {} (physical ID {})", output.binding, output.logical_id, output.physical_id);
             }
             bind_group_entries.push(wgpu::BindGroupEntry {
                 binding: output.binding,
@@ -521,9 +567,9 @@ impl PipelineProcessor {
         bind_group_entries.sort_by_key(|entry| entry.binding);
 
         if self.log {
-            println!("  Total bindings created: {}", bind_group_entries.len());
+            tracing::trace!("  Total bindings created: {}", bind_group_entries.len());
             for entry in &bind_group_entries {
-                println!("    Binding {}", entry.binding);
+                tracing::trace!("    Binding {}", entry.binding);
             }
         }
 
@@ -548,43 +594,25 @@ impl PipelineProcessor {
     /// * `output_path_base` - Optional base path for saving intermediate outputs from each pass
     ///
     /// # Returns
-    /// Result indicating success or failure of the pipeline execution
+    /// Result indicating success or failure of the pipeline execution, or `DeviceLostError` if the device was lost
     pub fn execute_pipeline(&mut self, output_path: &str, output_path_base: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+        let result = self.run_pipeline(output_path, output_path_base);
+        self.engine.device_loss.check_result(result)
+    }
+
+    /// Implementation of `execute_pipeline` without device loss detection
+    fn run_pipeline(&mut self, output_path: &str, output_path_base: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
         if self.log {
-            println!("Executing pipeline with {} prepared passes", self.prepared_passes.len());
+            tracing::info!("Executing pipeline with {} prepared passes", self.prepared_passes.len());
         }
 
         // Execute each prepared shader pass in sequence
         for (pass_index, prepared_pass) in self.prepared_passes.iter().enumerate() {
             if self.log {
-                println!("Executing pass {}: {}", pass_index, prepared_pass.id);
+                tracing::debug!("Executing pass {}: {}", pass_index, prepared_pass.id);
             }
 
-            // Create command encoder for recording GPU commands
-            let mut encoder = self.engine.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some(&format!("wgsl_encoder_{}", prepared_pass.id)),
-            });
-
-            {
-                // Begin compute pass within a scope for proper resource cleanup
-                let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
-                    label: Some(&format!("wgsl_compute_pass_{}", prepared_pass.id)),
-                    timestamp_writes: None, // No GPU timing needed for this operation
-                });
-
-                // Set pipeline and bind all resources
-                compute_pass.set_pipeline(&prepared_pass.pipeline);
-                compute_pass.set_bind_group(0, &prepared_pass.bind_group, &[]);
-
-                // Calculate workgroup dispatch dimensions based on compute dimensions
-                let (compute_width, compute_height) = prepared_pass.compute_dimensions;
-                let workgroup_x = calculate_workgroup_count(compute_width, COMPUTE_WORKGROUP_SIZE_X);
-                let workgroup_y = calculate_workgroup_count(compute_height, COMPUTE_WORKGROUP_SIZE_Y);
-                compute_pass.dispatch_workgroups(workgroup_x, workgroup_y, 1);
-            }
-
-            // Submit commands to GPU queue for execution
-            self.engine.queue.submit(std::iter::once(encoder.finish()));
+            self.dispatch_pass(prepared_pass);
 
             // Save intermediate outputs for debugging purposes if requested
             if let Some(output_path_base) = output_path_base {
@@ -593,7 +621,7 @@ impl PipelineProcessor {
                         let intermediate_path = format!("{output_path_base}_pass{}_phy{physical_id}.png", pass_index + 1);
                         save_texture_as_image_file(&self.engine.device, &self.engine.queue, texture, &intermediate_path)?;
                         if self.log {
-                            println!("- Pass {pass_index} output saved to: {intermediate_path}");
+                            tracing::info!("- Pass {pass_index} output saved to: {intermediate_path}");
                         }
                     }
                 }
@@ -601,15 +629,132 @@ impl PipelineProcessor {
 
             if self.log {
                 let (output_width, output_height) = prepared_pass.compute_dimensions;
-                println!("- Pass {pass_index} completed: dimensions: {output_width}x{output_height}");
+                tracing::debug!("- Pass {pass_index} completed: dimensions: {output_width}x{output_height}");
+            }
+        }
+
+        self.save_result(output_path)
+    }
+
+    /// Executes the complete pipeline, tracing every pass output into a multi-page TIFF
+    ///
+    /// Behaves like `execute_pipeline`, but instead of one PNG per intermediate output,
+    /// every pass output is appended as a normalized page of a single TIFF file.
+    ///
+    /// # Arguments
+    /// * `output_path` - Path where to save the final processed image
+    /// * `trace_path` - Path of the multi-page TIFF to write pass outputs to
+    ///
+    /// # Returns
+    /// Result indicating success or failure of the pipeline execution, or `DeviceLostError` if the device was lost
+    pub fn execute_pipeline_with_tiff_trace(&mut self, output_path: &str, trace_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let result = self.run_pipeline_with_tiff_trace(output_path, trace_path);
+        self.engine.device_loss.check_result(result)
+    }
+
+    /// Implementation of `execute_pipeline_with_tiff_trace` without device loss detection
+    fn run_pipeline_with_tiff_trace(&mut self, output_path: &str, trace_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let mut trace = PassTraceWriter::create(trace_path)?;
+
+        for (pass_index, prepared_pass) in self.prepared_passes.iter().enumerate() {
+            if self.log {
+                tracing::debug!("Executing pass {}: {}", pass_index, prepared_pass.id);
+            }
+
+            self.dispatch_pass(prepared_pass);
+
+            for physical_id in &prepared_pass.output_physical_ids {
+                if let Some(texture) = self.physical_textures.get(physical_id) {
+                    let image = save_texture_as_image(&self.engine.device, &self.engine.queue, texture)?;
+                    trace.write_page(&prepared_pass.id, *physical_id, &image)?;
+                }
+            }
+        }
+
+        tracing::info!("Pass trace with {} pages saved to: {trace_path}", trace.page_count());
+
+        self.save_result(output_path)
+    }
+
+    /// Executes the complete pipeline, writing a histogram of every pass output
+    ///
+    /// Behaves like `execute_pipeline`, but instead of saving intermediate images, the
+    /// raw values of every channel of every pass output are counted into histograms,
+    /// written as CSV or JSON depending on the extension of `histogram_path`.
+    ///
+    /// # Arguments
+    /// * `output_path` - Path where to save the final processed image
+    /// * `histogram_path` - Path of the CSV or JSON file to write the histograms to
+    /// * `config` - Bin layout of the histograms
+    ///
+    /// # Returns
+    /// Result indicating success or failure of the pipeline execution, or `DeviceLostError` if the device was lost
+    pub fn execute_pipeline_with_histograms(&mut self, output_path: &str, histogram_path: &str, config: HistogramConfig) -> Result<(), Box<dyn std::error::Error>> {
+        let result = self.run_pipeline_with_histograms(output_path, histogram_path, config);
+        self.engine.device_loss.check_result(result)
+    }
+
+    /// Implementation of `execute_pipeline_with_histograms` without device loss detection
+    fn run_pipeline_with_histograms(&mut self, output_path: &str, histogram_path: &str, config: HistogramConfig) -> Result<(), Box<dyn std::error::Error>> {
+        let collector = HistogramCollector::new(&self.engine.device, config);
+        let mut histograms = Vec::new();
+
+        for (pass_index, prepared_pass) in self.prepared_passes.iter().enumerate() {
+            if self.log {
+                tracing::debug!("Executing pass {}: {}", pass_index, prepared_pass.id);
+            }
+
+            self.dispatch_pass(prepared_pass);
+
+            for physical_id in &prepared_pass.output_physical_ids {
+                if let Some(texture) = self.physical_textures.get(physical_id) {
+                    let label = format!("{} phy{physical_id}", prepared_pass.id);
+                    histograms.push(collector.collect(&self.engine.device, &self.engine.queue, texture, &label)?);
+                }
             }
         }
 
-        // Save the final result using the pipeline's designated result texture
+        write_histograms(histogram_path, &histograms)?;
+        tracing::info!("Histograms of {} pass outputs saved to: {histogram_path}", histograms.len());
+
+        self.save_result(output_path)
+    }
+
+    /// Records and submits a single prepared pass
+    fn dispatch_pass(&self, prepared_pass: &PreparedPass) {
+        // Create command encoder for recording GPU commands
+        let mut encoder = self.engine.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some(&format!("wgsl_encoder_{}", prepared_pass.id)),
+        });
+
+        {
+            // Begin compute pass within a scope for proper resource cleanup
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some(&format!("wgsl_compute_pass_{}", prepared_pass.id)),
+                timestamp_writes: None, // No GPU timing needed for this operation
+            });
+
+            // Set pipeline and bind all resources
+            compute_pass.set_pipeline(&prepared_pass.pipeline);
+            compute_pass.set_bind_group(0, &prepared_pass.bind_group, &[]);
+
+            // Calculate workgroup dispatch dimensions based on compute dimensions
+            let (compute_width, compute_height) = prepared_pass.compute_dimensions;
+            let workgroup_x = calculate_workgroup_count(compute_width, COMPUTE_WORKGROUP_SIZE_X);
+            let workgroup_y = calculate_workgroup_count(compute_height, COMPUTE_WORKGROUP_SIZE_Y);
+            compute_pass.dispatch_workgroups(workgroup_x, workgroup_y, 1);
+        }
+
+        // Submit commands to GPU queue for execution
+        self.engine.queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    /// Saves the pipeline's designated result texture to a file
+    fn save_result(&self, output_path: &str) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(result_texture_id) = self.executable_pipeline.get_result_texture_id() {
             if let Some(result_texture) = self.physical_textures.get(&result_texture_id) {
                 save_texture_as_image_file(&self.engine.device, &self.engine.queue, result_texture, output_path)?;
-                println!("Final result saved to: {output_path} (physical texture ID: {result_texture_id})");
+                tracing::info!("Final result saved to: {output_path} (physical texture ID: {result_texture_id})");
             } else {
                 return Err(format!("Result texture with ID {result_texture_id} not found").into());
             }
@@ -628,8 +773,14 @@ impl PipelineProcessor {
     /// should be minimized.
     ///
     /// # Returns
-    /// Tuple of (processed image, execution duration) or error
+    /// Tuple of (processed image, execution duration), or an error (`DeviceLostError` if the device was lost)
     pub fn execute_pipeline_no_io(&mut self) -> Result<(image::Rgba32FImage, std::time::Duration), Box<dyn std::error::Error>> {
+        let result = self.run_pipeline_no_io();
+        self.engine.device_loss.check_result(result)
+    }
+
+    /// Implementation of `execute_pipeline_no_io` without device loss detection
+    fn run_pipeline_no_io(&mut self) -> Result<(image::Rgba32FImage, std::time::Duration), Box<dyn std::error::Error>> {
         // Start timing the execution (excluding result extraction)
         let timepoint = std::time::Instant::now();
 
@@ -0,0 +1,243 @@
+//! Correctness tests for the channel-to-pixel mapping of depth-to-space stages
+//!
+//! Every overlay channel of every overlay texel holds a distinct constant, and the
+//! source is black, so each output pixel must equal exactly the overlay values the
+//! mapping assigns to it. Both the hand-written helpers in `wgsl/helpers` and the
+//! generated stages are checked.
+
+use anime4k_wgpu_build::cnn::generate_depth_to_space_wgsl;
+
+/// Overlay texture width in texels, different from the height to catch transposed axes
+const OVERLAY_WIDTH: u32 = 3;
+
+/// Overlay texture height in texels
+const OVERLAY_HEIGHT: u32 = 2;
+
+/// Creates a device suitable for running depth-to-space stages, if the system has one
+///
+/// Without an adapter supporting `FLOAT32_FILTERABLE` this returns `None` and the tests skip themselves.
+fn create_device() -> Option<(wgpu::Device, wgpu::Queue)> {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::all(),
+        ..Default::default()
+    });
+    let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default())).ok()?;
+    if !adapter.features().contains(wgpu::Features::FLOAT32_FILTERABLE) {
+        return None;
+    }
+    pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+        label: Some("Depth-to-space test"),
+        required_features: wgpu::Features::FLOAT32_FILTERABLE,
+        required_limits: wgpu::Limits::default(),
+        memory_hints: wgpu::MemoryHints::default(),
+        trace: Default::default(),
+    }))
+    .ok()
+}
+
+/// Returns the constant stored in one overlay channel
+///
+/// # Arguments
+/// * `x` - Overlay texel column
+/// * `y` - Overlay texel row
+/// * `flat_index` - Channel index across all overlay textures, four per texture
+fn overlay_value(x: u32, y: u32, flat_index: u32) -> f32 {
+    ((y * OVERLAY_WIDTH + x + 1) * 1000 + flat_index + 1) as f32
+}
+
+/// Creates an `Rgba32Float` texture filled with texel data
+fn create_texture(device: &wgpu::Device, queue: &wgpu::Queue, width: u32, height: u32, texels: &[f32]) -> wgpu::Texture {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba32Float,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    queue.write_texture(
+        texture.as_image_copy(),
+        &texels.iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<_>>(),
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(width * 16),
+            rows_per_image: Some(height),
+        },
+        texture.size(),
+    );
+    texture
+}
+
+/// Runs a depth-to-space stage on the crafted overlays
+///
+/// # Arguments
+/// * `code` - WGSL source of the stage
+/// * `overlays` - Number of overlay textures the stage reads
+/// * `scale` - Upscaling factor of the stage
+///
+/// # Returns
+/// The output texels in row-major order, or `None` if no suitable GPU adapter is available
+fn run(code: &str, overlays: u32, scale: u32) -> Option<Vec<[f32; 4]>> {
+    let (device, queue) = create_device()?;
+
+    let source_texture = create_texture(&device, &queue, OVERLAY_WIDTH, OVERLAY_HEIGHT, &[0.0; (OVERLAY_WIDTH * OVERLAY_HEIGHT * 4) as usize]);
+    let overlay_textures: Vec<_> = (0..overlays)
+        .map(|texture| {
+            let texels: Vec<f32> = (0..OVERLAY_HEIGHT)
+                .flat_map(|y| (0..OVERLAY_WIDTH).flat_map(move |x| (0..4).map(move |component| overlay_value(x, y, texture * 4 + component))))
+                .collect();
+            create_texture(&device, &queue, OVERLAY_WIDTH, OVERLAY_HEIGHT, &texels)
+        })
+        .collect();
+
+    let (width, height) = (OVERLAY_WIDTH * scale, OVERLAY_HEIGHT * scale);
+    let output_texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba32Float,
+        usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+
+    let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Depth-to-space test"),
+        source: wgpu::ShaderSource::Wgsl(code.into()),
+    });
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Depth-to-space test"),
+        layout: None,
+        module: &shader_module,
+        entry_point: Some("main"),
+        compilation_options: Default::default(),
+        cache: None,
+    });
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        ..Default::default()
+    });
+
+    let source_view = source_texture.create_view(&Default::default());
+    let overlay_views: Vec<_> = overlay_textures.iter().map(|texture| texture.create_view(&Default::default())).collect();
+    let output_view = output_texture.create_view(&Default::default());
+    let mut entries = vec![wgpu::BindGroupEntry {
+        binding: 0,
+        resource: wgpu::BindingResource::TextureView(&source_view),
+    }];
+    for (index, view) in overlay_views.iter().enumerate() {
+        entries.push(wgpu::BindGroupEntry {
+            binding: index as u32 + 1,
+            resource: wgpu::BindingResource::TextureView(view),
+        });
+    }
+    entries.push(wgpu::BindGroupEntry {
+        binding: overlays + 1,
+        resource: wgpu::BindingResource::TextureView(&output_view),
+    });
+    entries.push(wgpu::BindGroupEntry {
+        binding: overlays + 2,
+        resource: wgpu::BindingResource::Sampler(&sampler),
+    });
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: None,
+        layout: &pipeline.get_bind_group_layout(0),
+        entries: &entries,
+    });
+
+    // Rows of a texture-to-buffer copy must be aligned
+    let bytes_per_row = (width * 16).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: (bytes_per_row * height) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+        pass.set_pipeline(&pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.dispatch_workgroups(width.div_ceil(8), height.div_ceil(8), 1);
+    }
+    encoder.copy_texture_to_buffer(
+        output_texture.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(bytes_per_row),
+                rows_per_image: Some(height),
+            },
+        },
+        output_texture.size(),
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    buffer.slice(..).map_async(wgpu::MapMode::Read, |result| result.unwrap());
+    device.poll(wgpu::PollType::Wait).unwrap();
+    let data = buffer.slice(..).get_mapped_range();
+    let texels = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (y * bytes_per_row + x * 16) as usize))
+        .map(|offset| std::array::from_fn(|component| f32::from_le_bytes(data[offset + component * 4..offset + component * 4 + 4].try_into().unwrap())))
+        .collect();
+    Some(texels)
+}
+
+/// Checks that every output pixel holds the overlay values its sub-pixel maps to
+///
+/// Overlay values are packed per color channel: color `c` at sub-pixel
+/// `k = dy * scale + dx` is channel `c * scale² + k`. Grayscale overlays are
+/// broadcast to all three colors, and the source contributes nothing.
+fn assert_arrangement(name: &str, output: &[[f32; 4]], colors: u32, scale: u32) {
+    let width = OVERLAY_WIDTH * scale;
+    for (index, texel) in output.iter().enumerate() {
+        let (x, y) = (index as u32 % width, index as u32 / width);
+        let sub_pixel = (y % scale) * scale + x % scale;
+        let value = |color: u32| overlay_value(x / scale, y / scale, color * scale * scale + sub_pixel);
+        let expected = if colors == 1 { [value(0), value(0), value(0), 0.0] } else { [value(0), value(1), value(2), 0.0] };
+        assert_eq!(*texel, expected, "{name}: wrong value at output pixel ({x}, {y})");
+    }
+}
+
+/// Tests the hand-written helpers for 2x upscaling
+#[test]
+fn test_helper_arrangement() {
+    let helpers_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../wgsl/helpers");
+    for (overlays, colors) in [(1, 1), (3, 3)] {
+        let name = format!("depth_to_space_in{overlays}x2.wgsl");
+        let code = std::fs::read_to_string(helpers_dir.join(&name)).unwrap();
+        let Some(output) = run(&code, overlays, 2) else {
+            eprintln!("Skipping depth-to-space test: no GPU adapter with FLOAT32_FILTERABLE");
+            return;
+        };
+        assert_arrangement(&name, &output, colors, 2);
+    }
+}
+
+/// Tests the generated stages, including the scales without a hand-written helper
+#[test]
+fn test_generated_arrangement() {
+    for (overlays, scale, colors) in [(1, 2, 1), (3, 2, 3), (3, 3, 1), (7, 3, 3), (4, 4, 1), (12, 4, 3)] {
+        let name = format!("generated in{overlays}x{scale}");
+        let code = generate_depth_to_space_wgsl(overlays, scale).unwrap();
+        let Some(output) = run(&code, overlays, scale) else {
+            eprintln!("Skipping depth-to-space test: no GPU adapter with FLOAT32_FILTERABLE");
+            return;
+        };
+        assert_arrangement(&name, &output, colors, scale);
+    }
+}
@@ -0,0 +1,134 @@
+//! Correctness tests for the edge modes of converted CNN shaders
+//!
+//! Runs small generated convolution stages on an image whose 1-pixel border is white
+//! and whose interior is black. Clamping repeats the border under the kernel, while
+//! mirroring reflects the taps back into the interior, so the two modes must produce
+//! different values along the edges and identical values inside.
+
+use std::collections::HashMap;
+
+use anime4k_wgpu_build::{cnn::EdgeMode, cnn_glsl_to_pipeline_spec};
+use anime4k_wgpu_verification::wgsl_reference_engine::{PipelineProcessor, WgslReferenceEngine};
+
+/// Test image width in pixels, different from the height to catch transposed axes
+const WIDTH: u32 = 9;
+
+/// Test image height in pixels
+const HEIGHT: u32 = 7;
+
+/// Identity weights, so each tap adds the texel it reads
+const IDENTITY: &str = "1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0";
+
+/// Largest difference allowed for bilinear samples, which GPUs interpolate with limited precision
+const SAMPLE_TOLERANCE: f32 = 1e-2;
+
+/// Creates the test image with a white border around a black interior
+fn create_test_image() -> image::DynamicImage {
+    let image = image::Rgba32FImage::from_fn(WIDTH, HEIGHT, |x, y| {
+        let value = if x == 0 || y == 0 || x == WIDTH - 1 || y == HEIGHT - 1 { 1.0 } else { 0.0 };
+        image::Rgba([value, value, value, 1.0])
+    });
+    image::DynamicImage::ImageRgba32F(image)
+}
+
+/// Converts a single mpv convolution hook with the given edge mode and runs it on the test image
+///
+/// # Arguments
+/// * `name` - Name of the test, used for the temporary GLSL file
+/// * `glsl` - Source of the hook
+/// * `edge_mode` - How the generated stage reads texels outside the texture
+///
+/// # Returns
+/// The output image, or `None` if no suitable GPU adapter is available
+fn run(name: &str, glsl: &str, edge_mode: EdgeMode) -> Option<image::Rgba32FImage> {
+    let glsl_path = std::env::temp_dir().join(format!("anime4k_wgpu_edge_mode_{name}_{}.glsl", std::process::id()));
+    std::fs::write(&glsl_path, glsl).unwrap();
+    let helpers_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../wgsl/helpers");
+    let converted = cnn_glsl_to_pipeline_spec(glsl_path.to_str().unwrap(), helpers_dir.to_str().unwrap(), false, false, edge_mode);
+    std::fs::remove_file(&glsl_path).unwrap();
+    let (spec, files) = converted.unwrap();
+
+    let engine = match pollster::block_on(WgslReferenceEngine::new()) {
+        Ok(engine) => engine,
+        Err(e) => {
+            eprintln!("Skipping edge mode test: no GPU adapter ({e})");
+            return None;
+        }
+    };
+    let manifest = spec.to_yaml().unwrap();
+    let shader_map: HashMap<&str, &str> = files.iter().collect();
+    let mut processor = PipelineProcessor::new_from_data(engine, &manifest, &shader_map, &create_test_image(), false).unwrap();
+    Some(processor.execute_pipeline_no_io().unwrap().0)
+}
+
+/// Tests that a 3x3 box filter reading texels at the same scale repeats the border
+/// under clamping but reads the interior under mirroring
+#[test]
+fn test_edge_mode_strong_border() {
+    let mut glsl = String::from(
+        "//!DESC Test-Conv-Box\n//!HOOK MAIN\n//!BIND MAIN\n//!SAVE MAIN\n//!WIDTH MAIN.w\n//!HEIGHT MAIN.h\n//!COMPONENTS 4\n\
+         #define go_0(x_off, y_off) (MAIN_texOff(vec2(x_off, y_off)))\nvec4 hook() {\n",
+    );
+    for (i, (x_offset, y_offset)) in [-1, 0, 1].into_iter().flat_map(|y| [-1, 0, 1].map(|x| (x, y))).enumerate() {
+        let decl = if i == 0 { "vec4 result =" } else { "result +=" };
+        glsl.push_str(&format!("{decl} mat4({IDENTITY}) * go_0({x_offset}.0, {y_offset}.0);\n"));
+    }
+    glsl.push_str("return result;\n}\n");
+
+    let Some(clamp) = run("box", &glsl, EdgeMode::Clamp) else {
+        return;
+    };
+    let Some(mirror) = run("box", &glsl, EdgeMode::Mirror) else {
+        return;
+    };
+    assert_eq!(clamp.dimensions(), (WIDTH, HEIGHT));
+
+    // Corner: clamping reads the border 8 times, mirroring only the 5 border texels of the kernel
+    assert_eq!(clamp.get_pixel(0, 0)[0], 8.0);
+    assert_eq!(mirror.get_pixel(0, 0)[0], 5.0);
+    assert_eq!(clamp.get_pixel(WIDTH - 1, HEIGHT - 1)[0], 8.0);
+    assert_eq!(mirror.get_pixel(WIDTH - 1, HEIGHT - 1)[0], 5.0);
+    // Edge: clamping reads the border row twice, mirroring once
+    assert_eq!(clamp.get_pixel(4, HEIGHT - 1)[0], 6.0);
+    assert_eq!(mirror.get_pixel(4, HEIGHT - 1)[0], 3.0);
+    assert_eq!(clamp.get_pixel(0, 3)[0], 6.0);
+    assert_eq!(mirror.get_pixel(0, 3)[0], 3.0);
+    // Interior pixels are not affected by the edge mode
+    for y in 2..HEIGHT - 2 {
+        for x in 2..WIDTH - 2 {
+            assert_eq!(clamp.get_pixel(x, y), mirror.get_pixel(x, y), "pixel ({x}, {y})");
+        }
+    }
+}
+
+/// Tests that a tap sampling a lower-resolution texture is clamped by the sampler
+/// but reflected into the interior under mirroring
+#[test]
+fn test_edge_mode_strong_border_fraction() {
+    let glsl = format!(
+        "//!DESC Test-Conv-Upscale\n//!HOOK MAIN\n//!BIND MAIN\n//!SAVE MAIN\n//!WIDTH MAIN.w 2 *\n//!HEIGHT MAIN.h 2 *\n//!COMPONENTS 4\n\
+         #define go_0(x_off, y_off) (MAIN_texOff(vec2(x_off, y_off) * 0.5))\nvec4 hook() {{\n\
+         vec4 result = mat4({IDENTITY}) * go_0(-1.0, -1.0);\nreturn result;\n}}\n"
+    );
+
+    let Some(clamp) = run("upscale", &glsl, EdgeMode::Clamp) else {
+        return;
+    };
+    let Some(mirror) = run("upscale", &glsl, EdgeMode::Mirror) else {
+        return;
+    };
+    assert_eq!(clamp.dimensions(), (WIDTH * 2, HEIGHT * 2));
+
+    // The top-left output pixel samples a quarter texel outside the source. Clamping reads
+    // only the border texel, while mirroring lands at 1.25 texels, 3/4 of the way to the
+    // black interior on both axes.
+    assert!((clamp.get_pixel(0, 0)[0] - 1.0).abs() <= SAMPLE_TOLERANCE, "{:?}", clamp.get_pixel(0, 0));
+    assert!((mirror.get_pixel(0, 0)[0] - (1.0 - 0.75 * 0.75)).abs() <= SAMPLE_TOLERANCE, "{:?}", mirror.get_pixel(0, 0));
+    // Interior pixels are not affected by the edge mode
+    for y in 4..HEIGHT * 2 - 4 {
+        for x in 4..WIDTH * 2 - 4 {
+            let (clamp, mirror) = (clamp.get_pixel(x, y)[0], mirror.get_pixel(x, y)[0]);
+            assert!((clamp - mirror).abs() <= SAMPLE_TOLERANCE, "pixel ({x}, {y}): {clamp} != {mirror}");
+        }
+    }
+}
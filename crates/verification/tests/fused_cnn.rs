@@ -0,0 +1,79 @@
+//! Correctness tests for fused CNN shaders
+//!
+//! Runs the fused single-pass pipeline and the regular multi-pass pipeline on the same
+//! image and checks that they agree.
+
+use std::collections::HashMap;
+
+use anime4k_wgpu_build::{
+    cnn::EdgeMode,
+    cnn_glsl_to_fused_pipeline_spec, cnn_glsl_to_pipeline_spec,
+    pipelines::{AssetMap, PipelineSpec},
+};
+use anime4k_wgpu_verification::{
+    compare::{ColorSpace, diff},
+    wgsl_reference_engine::{PipelineProcessor, WgslReferenceEngine},
+};
+
+/// Largest per-component difference allowed between fused and unfused results
+///
+/// The fused shader accumulates the final layer in a different order, so results may
+/// differ by floating-point rounding.
+const TOLERANCE: f32 = 1e-4;
+
+/// Creates a deterministic test image whose size is not a multiple of the tile size
+fn create_test_image() -> image::DynamicImage {
+    let image = image::Rgba32FImage::from_fn(37, 29, |x, y| {
+        let fx = x as f32 / 36.0;
+        let fy = y as f32 / 28.0;
+        let edge = if (x / 5 + y / 7) % 2 == 0 { 0.9 } else { 0.1 };
+        image::Rgba([fx, fy, edge, 1.0])
+    });
+    image::DynamicImage::ImageRgba32F(image)
+}
+
+/// Runs a pipeline on the test image
+///
+/// # Returns
+/// The output image, or `None` if no suitable GPU adapter is available
+fn run(spec: &PipelineSpec, files: &AssetMap, image: &image::DynamicImage) -> Option<image::Rgba32FImage> {
+    let engine = match pollster::block_on(WgslReferenceEngine::new()) {
+        Ok(engine) => engine,
+        Err(e) => {
+            eprintln!("Skipping fused CNN test: no GPU adapter ({e})");
+            return None;
+        }
+    };
+    let manifest = spec.to_yaml().unwrap();
+    let shader_map: HashMap<&str, &str> = files.iter().collect();
+    let mut processor = PipelineProcessor::new_from_data(engine, &manifest, &shader_map, image, false).unwrap();
+    Some(processor.execute_pipeline_no_io().unwrap().0)
+}
+
+/// Tests that fused restore CNNs match their multi-pass counterparts in both edge modes
+#[test]
+fn test_fused_matches_unfused() {
+    let project_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    let helpers_dir = project_dir.join("wgsl/helpers");
+    let image = create_test_image();
+
+    for name in ["Anime4K_Restore_CNN_S", "Anime4K_Restore_CNN_M"] {
+        let glsl_path = project_dir.join(format!("anime4k-glsl/Restore/{name}.glsl"));
+        let glsl_path = glsl_path.to_str().unwrap();
+        for edge_mode in [EdgeMode::Clamp, EdgeMode::Mirror] {
+            let (fused_spec, fused_files) = cnn_glsl_to_fused_pipeline_spec(glsl_path, false, edge_mode).unwrap().expect("restore CNN should be fusable");
+            assert_eq!(fused_spec.passes.len(), 1);
+            let (spec, files) = cnn_glsl_to_pipeline_spec(glsl_path, helpers_dir.to_str().unwrap(), false, false, edge_mode).unwrap();
+
+            let Some(fused_output) = run(&fused_spec, &fused_files, &image) else {
+                return;
+            };
+            let Some(output) = run(&spec, &files, &image) else {
+                return;
+            };
+
+            let d = diff(&output, &fused_output, ColorSpace::Gamma).expect("dimensions should match");
+            assert!(d.is_within(TOLERANCE), "{name} ({edge_mode:?}) fused output differs: {d:?}");
+        }
+    }
+}
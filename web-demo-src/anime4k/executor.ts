@@ -103,6 +103,13 @@ export interface PipelineExecutor {
   cleanup: () => void;
 }
 
+/**
+ * Scales a dimension, flooring but never below one pixel for a non-empty input
+ */
+function scaleDimension(size: number, factor: number): number {
+  return Math.max(Math.floor(size * factor), Math.min(size, 1));
+}
+
 /**
  * Creates a texture format based on the number of components
  */
@@ -124,6 +131,45 @@ function getGPUFilterMode(filterMode: SamplerFilterMode): GPUFilterMode {
   return filterMode === "nearest" ? "nearest" : "linear";
 }
 
+/**
+ * Memoizes samplers by filter and address modes
+ *
+ * Port of the Rust `SamplerCache`. A cache belongs to the device it was created with.
+ */
+export class SamplerCache {
+  private readonly samplers = new Map<string, GPUSampler>();
+
+  constructor(private readonly device: GPUDevice) {}
+
+  /**
+   * Returns the sampler for a filter and address modes, creating it on first use
+   */
+  get(
+    filter: GPUFilterMode,
+    addressModeU: GPUAddressMode = "clamp-to-edge",
+    addressModeV: GPUAddressMode = "clamp-to-edge",
+    addressModeW: GPUAddressMode = "clamp-to-edge"
+  ): GPUSampler {
+    const key = `${filter} ${addressModeU}/${addressModeV}/${addressModeW}`;
+    let sampler = this.samplers.get(key);
+    if (!sampler) {
+      sampler = this.device.createSampler({
+        label: `Sampler ${key}`,
+        addressModeU,
+        addressModeV,
+        addressModeW,
+        magFilter: filter,
+        minFilter: filter,
+        mipmapFilter: "nearest",
+        lodMinClamp: 0,
+        lodMaxClamp: 0,
+      });
+      this.samplers.set(key, sampler);
+    }
+    return sampler;
+  }
+}
+
 /**
  * Creates a new bound pipeline from an executable pipeline
  *
@@ -133,7 +179,8 @@ function getGPUFilterMode(filterMode: SamplerFilterMode): GPUFilterMode {
 async function createBoundPipeline(
   pipeline: ExecutablePipeline,
   device: GPUDevice,
-  inputTexture: GPUTexture
+  inputTexture: GPUTexture,
+  samplerCache: SamplerCache
 ): Promise<[BoundPipeline, GPUTexture]> {
   const inputSize = [inputTexture.width, inputTexture.height] as const;
 
@@ -147,13 +194,13 @@ async function createBoundPipeline(
       // Use the input texture directly for source textures
       texture = inputTexture;
     } else {
-      const width = Math.floor(
-        (inputSize[0] * pt.scale_factor[0].numerator) /
-          pt.scale_factor[0].denominator
+      const width = scaleDimension(
+        inputSize[0],
+        pt.scale_factor[0].numerator / pt.scale_factor[0].denominator
       );
-      const height = Math.floor(
-        (inputSize[1] * pt.scale_factor[1].numerator) /
-          pt.scale_factor[1].denominator
+      const height = scaleDimension(
+        inputSize[1],
+        pt.scale_factor[1].numerator / pt.scale_factor[1].denominator
       );
 
       const format = getTextureFormat(pt.components);
@@ -182,24 +229,15 @@ async function createBoundPipeline(
   const samplerMap = new Map<SamplerFilterMode, GPUSampler>();
 
   for (const filterMode of pipeline.required_samplers) {
-    const sampler = device.createSampler({
-      label: `Sampler ${filterMode}`,
-      addressModeU: "clamp-to-edge",
-      addressModeV: "clamp-to-edge",
-      addressModeW: "clamp-to-edge",
-      magFilter: getGPUFilterMode(filterMode),
-      minFilter: getGPUFilterMode(filterMode),
-      mipmapFilter: "nearest",
-    });
-    samplerMap.set(filterMode, sampler);
+    samplerMap.set(filterMode, samplerCache.get(getGPUFilterMode(filterMode)));
   }
 
   const passes: BoundExecutablePass[] = [];
 
   for (const shaderPass of pipeline.passes) {
     const computeDimensions: [number, number] = [
-      Math.floor(inputSize[0] * shaderPass.compute_scale_factors[0]),
-      Math.floor(inputSize[1] * shaderPass.compute_scale_factors[1]),
+      scaleDimension(inputSize[0], shaderPass.compute_scale_factors[0]),
+      scaleDimension(inputSize[1], shaderPass.compute_scale_factors[1]),
     ];
 
     const skipBoundCheck =
@@ -376,13 +414,21 @@ function executeBoundPipeline(
  * Creates a new shader pipeline from executable pipelines
  *
  * Binds all pipelines to GPU resources and chains them together so that
- * the output of one pipeline becomes the input of the next.
+ * the output of one pipeline becomes the input of the next. Samplers come from
+ * `samplerCache`, which can be kept across executors of the same device.
  */
 export async function createPipelineExecutor(
-  executablePipelines: ExecutablePipeline[],
+  executablePipelines: readonly ExecutablePipeline[],
   device: GPUDevice,
-  sourceTexture: GPUTexture
+  sourceTexture: GPUTexture,
+  samplerCache: SamplerCache = new SamplerCache(device)
 ): Promise<[PipelineExecutor, GPUTexture]> {
+  if (!(sourceTexture.width > 0 && sourceTexture.height > 0)) {
+    throw new Error(
+      `Cannot execute a pipeline on a ${sourceTexture.width}x${sourceTexture.height} source texture`
+    );
+  }
+
   const boundPipelines: BoundPipeline[] = [];
   let currentInputTexture = sourceTexture;
 
@@ -390,7 +436,8 @@ export async function createPipelineExecutor(
     const [boundPipeline, outputTexture] = await createBoundPipeline(
       pipeline,
       device,
-      currentInputTexture
+      currentInputTexture,
+      samplerCache
     );
     currentInputTexture = outputTexture;
     boundPipelines.push(boundPipeline);
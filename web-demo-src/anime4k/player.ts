@@ -1,6 +1,7 @@
 import {
   createPipelineExecutor,
   executePipeline,
+  SamplerCache,
   type ExecutablePipeline,
   type PipelineExecutor,
 } from "./executor";
@@ -31,6 +32,53 @@ export async function preloadPredefinedPipelines(): Promise<void> {
   await fetchPredefinedPipelines();
 }
 
+/**
+ * A pipeline bundle loaded at runtime
+ *
+ * Either a single executable pipeline or a list of pipelines to chain, in the same
+ * JSON format as the entries of `predefinedPipelines.json` (shaders embedded as WGSL).
+ */
+export type PipelineBundle = ExecutablePipeline | ExecutablePipeline[];
+
+function isExecutablePipeline(value: unknown): value is ExecutablePipeline {
+  const pipeline = value as Partial<ExecutablePipeline> | null;
+  return (
+    typeof pipeline === "object" &&
+    pipeline !== null &&
+    Array.isArray(pipeline.physical_textures) &&
+    Array.isArray(pipeline.required_samplers) &&
+    Array.isArray(pipeline.passes) &&
+    pipeline.passes.length > 0
+  );
+}
+
+/**
+ * Fetches a pipeline bundle over HTTP
+ *
+ * @param url - URL of the JSON bundle
+ * @param signal - Optional signal to abort the request
+ * @returns The pipelines of the bundle, in execution order
+ */
+export async function fetchPipelineBundle(
+  url: string,
+  signal?: AbortSignal
+): Promise<ExecutablePipeline[]> {
+  const response = await fetch(url, { signal });
+  if (!response.ok) {
+    throw new Error(
+      `Failed to fetch pipeline bundle ${url}: ${response.status} ${response.statusText}`
+    );
+  }
+
+  const bundle = (await response.json()) as unknown;
+  const pipelines = Array.isArray(bundle) ? bundle : [bundle];
+  if (pipelines.length === 0 || !pipelines.every(isExecutablePipeline)) {
+    throw new Error(`Invalid pipeline bundle: ${url}`);
+  }
+
+  return pipelines;
+}
+
 export interface Anime4KConfig {
   readonly preset: Anime4KPreset;
   readonly performance: Anime4KPerformancePreset;
@@ -40,6 +88,7 @@ export interface Anime4KConfig {
 interface RenderingContextInit {
   readonly renderPipeline: GPURenderPipeline;
   readonly renderSampler: GPUSampler;
+  readonly samplerCache: SamplerCache;
 }
 
 function createContextInit(device: GPUDevice): RenderingContextInit {
@@ -68,22 +117,21 @@ function createContextInit(device: GPUDevice): RenderingContextInit {
     },
   });
 
-  const renderSampler = device.createSampler({
-    magFilter: "linear",
-    minFilter: "linear",
-  });
+  const samplerCache = new SamplerCache(device);
+  const renderSampler = samplerCache.get("linear");
 
   return {
     renderPipeline,
     renderSampler,
+    samplerCache,
   };
 }
 
 interface RenderingContext {
   readonly device: GPUDevice;
-  readonly video: HTMLVideoElement;
   readonly canvasContext: GPUCanvasContext;
   readonly config: Anime4KConfig | null;
+  readonly customPipelines: readonly ExecutablePipeline[] | null;
   readonly renderPipeline: GPURenderPipeline;
   readonly latestFrame: GPUTexture;
   readonly outputTexture: GPUTexture;
@@ -93,16 +141,18 @@ interface RenderingContext {
 
 async function createContext(
   device: GPUDevice,
-  { renderPipeline, renderSampler }: RenderingContextInit,
-  video: HTMLVideoElement,
+  { renderPipeline, renderSampler, samplerCache }: RenderingContextInit,
+  frameWidth: number,
+  frameHeight: number,
   canvas: HTMLCanvasElement,
   canvasContext: GPUCanvasContext,
-  config: Anime4KConfig | null
+  config: Anime4KConfig | null,
+  customPipelines: readonly ExecutablePipeline[] | null
 ): Promise<RenderingContext> {
   // Update canvas dimensions
   const effectiveScale = Math.max(config?.scale ?? 1, 1);
-  canvas.width = Math.floor(video.videoWidth * effectiveScale);
-  canvas.height = Math.floor(video.videoHeight * effectiveScale);
+  canvas.width = Math.floor(frameWidth * effectiveScale);
+  canvas.height = Math.floor(frameHeight * effectiveScale);
 
   canvasContext.configure({
     device,
@@ -113,7 +163,7 @@ async function createContext(
 
   // Create a new texture for the latest frame (input to Anime4K)
   const latestFrame = device.createTexture({
-    size: [video.videoWidth, video.videoHeight],
+    size: [frameWidth, frameHeight],
     format: "rgba8unorm",
     usage:
       GPUTextureUsage.COPY_DST |
@@ -122,19 +172,32 @@ async function createContext(
       GPUTextureUsage.RENDER_ATTACHMENT,
   });
 
-  // Create a new pipeline executor
-  const pipelineIds = config
-    ? createPipelines(config.preset, config.performance, config.scale)
-    : [];
+  // Create a new pipeline executor, preferring runtime-loaded pipelines over the preset
+  let executablePipelines: readonly ExecutablePipeline[];
+  if (!config) {
+    executablePipelines = [];
+  } else if (customPipelines) {
+    executablePipelines = customPipelines;
+  } else {
+    const pipelineIds = createPipelines(
+      config.preset,
+      config.performance,
+      config.scale
+    );
 
-  const predefinedPipelines = await fetchPredefinedPipelines();
-  const executablePipelines = pipelineIds.map((id) => predefinedPipelines[id]);
+    const predefinedPipelines = await fetchPredefinedPipelines();
+    executablePipelines = pipelineIds.map((id) => predefinedPipelines[id]);
 
-  // Check if all pipelines are valid
-  const invalidPipelines = pipelineIds.filter((id) => !predefinedPipelines[id]);
-  if (invalidPipelines.length > 0) {
-    console.error("❌ Found invalid pipelines:", invalidPipelines);
-    throw new Error(`Invalid pipelines found: ${invalidPipelines.join(", ")}`);
+    // Check if all pipelines are valid
+    const invalidPipelines = pipelineIds.filter(
+      (id) => !predefinedPipelines[id]
+    );
+    if (invalidPipelines.length > 0) {
+      console.error("❌ Found invalid pipelines:", invalidPipelines);
+      throw new Error(
+        `Invalid pipelines found: ${invalidPipelines.join(", ")}`
+      );
+    }
   }
 
   let executor: PipelineExecutor;
@@ -144,7 +207,8 @@ async function createContext(
     [executor, outputTexture] = await createPipelineExecutor(
       executablePipelines,
       device,
-      latestFrame
+      latestFrame,
+      samplerCache
     );
   } catch (error) {
     console.error("❌ Failed to create pipeline executor:", error);
@@ -170,9 +234,9 @@ async function createContext(
 
   return {
     device,
-    video,
     canvasContext,
     config,
+    customPipelines,
     renderPipeline,
     latestFrame,
     outputTexture,
@@ -183,12 +247,15 @@ async function createContext(
 
 function shouldRecreateContext(
   context: RenderingContext,
-  video: HTMLVideoElement,
-  config: Anime4KConfig | null
+  frameWidth: number,
+  frameHeight: number,
+  config: Anime4KConfig | null,
+  customPipelines: readonly ExecutablePipeline[] | null
 ): boolean {
   return (
-    context.latestFrame.width !== video.videoWidth ||
-    context.latestFrame.height !== video.videoHeight ||
+    context.customPipelines !== customPipelines ||
+    context.latestFrame.width !== frameWidth ||
+    context.latestFrame.height !== frameHeight ||
     context.config?.preset !== config?.preset ||
     context.config?.performance !== config?.performance ||
     context.config?.scale !== config?.scale
@@ -205,25 +272,27 @@ function cleanupContext(context?: RenderingContext | null): void {
   context.executor.cleanup();
 }
 
-function render({
-  device,
-  video,
-  latestFrame,
-  executor,
-  canvasContext,
-  renderPipeline,
-  renderBindGroup,
-}: RenderingContext): void {
-  // Copy the external texture (video frame) to the latestFrame texture
+function render(
+  {
+    device,
+    latestFrame,
+    executor,
+    canvasContext,
+    renderPipeline,
+    renderBindGroup,
+  }: RenderingContext,
+  source: GPUCopyExternalImageSource
+): void {
+  // Copy the external image (video frame) to the latestFrame texture
   device.queue.copyExternalImageToTexture(
     {
-      source: video,
+      source,
     },
     {
       texture: latestFrame,
       premultipliedAlpha: false,
     },
-    [video.videoWidth, video.videoHeight]
+    [latestFrame.width, latestFrame.height]
   );
 
   const encoder = device.createCommandEncoder();
@@ -256,6 +325,35 @@ export interface Anime4KController {
   ready: Promise<void>;
   cleanup: () => void;
   updateConfig: (config: Anime4KConfig | null) => void;
+  /**
+   * Fetches a pipeline bundle and uses it instead of the preset pipelines
+   *
+   * The configured scale still sizes the canvas, and a `null` config still disables
+   * processing. Passing `null` as the URL reverts to the preset pipelines.
+   */
+  loadPipelineFromUrl: (url: string | null) => Promise<void>;
+}
+
+async function createDevice(signal: AbortSignal): Promise<GPUDevice> {
+  // Create a WebGPU adapter and device
+  const adapter = await navigator.gpu?.requestAdapter();
+  if (!adapter) {
+    throw new Error("WebGPU adapter not available");
+  }
+
+  const device = await adapter.requestDevice({
+    requiredFeatures: ["float32-filterable"],
+  });
+
+  device.addEventListener(
+    "uncapturederror",
+    (event) => {
+      console.error("🚨 WebGPU uncaptured error:", event.error);
+    },
+    { signal }
+  );
+
+  return device;
 }
 
 export function setupAnime4K(
@@ -267,28 +365,13 @@ export function setupAnime4K(
   const { signal } = abortController;
 
   let currentConfig: Anime4KConfig | null = config && { ...config };
+  let currentCustomPipelines: readonly ExecutablePipeline[] | null = null;
   let contextPromise: Promise<RenderingContext> | null = null;
   let timerId: number | null = null;
   let onConfigUpdate: (() => void) | null = null;
 
   const init = async () => {
-    // Create a WebGPU adapter and device
-    const adapter = await navigator.gpu?.requestAdapter();
-    if (!adapter) {
-      throw new Error("WebGPU adapter not available");
-    }
-
-    const device = await adapter.requestDevice({
-      requiredFeatures: ["float32-filterable"],
-    });
-
-    device.addEventListener(
-      "uncapturederror",
-      (event) => {
-        console.error("🚨 WebGPU uncaptured error:", event.error);
-      },
-      { signal }
-    );
+    const device = await createDevice(signal);
 
     // Configure the canvas for WebGPU
     const canvasContext = canvas.getContext("webgpu");
@@ -306,10 +389,12 @@ export function setupAnime4K(
         return await createContext(
           device,
           contextInit,
-          video,
+          video.videoWidth,
+          video.videoHeight,
           canvas,
           canvasContext,
-          currentConfig
+          currentConfig,
+          currentCustomPipelines
         );
       } catch (error) {
         console.error("❌ Failed to create rendering context:", error);
@@ -331,8 +416,8 @@ export function setupAnime4K(
 
       if (
         video.readyState < 2 ||
-        !isFinite(video.videoWidth) ||
-        !isFinite(video.videoHeight)
+        !(isFinite(video.videoWidth) && video.videoWidth > 0) ||
+        !(isFinite(video.videoHeight) && video.videoHeight > 0)
       ) {
         console.warn(
           "⚠️ Video not ready or dimensions are invalid, waiting for next frame"
@@ -347,7 +432,13 @@ export function setupAnime4K(
         .then((context) => {
           if (
             signal.aborted ||
-            !shouldRecreateContext(context, video, currentConfig)
+            !shouldRecreateContext(
+              context,
+              video.videoWidth,
+              video.videoHeight,
+              currentConfig,
+              currentCustomPipelines
+            )
           ) {
             return context;
           }
@@ -363,7 +454,7 @@ export function setupAnime4K(
           }
 
           // Render the frame
-          render(context);
+          render(context, video);
         })
         .catch((error) => {
           if (signal.aborted) {
@@ -424,5 +515,138 @@ export function setupAnime4K(
       currentConfig = config && { ...config };
       onConfigUpdate?.();
     },
+    loadPipelineFromUrl: async (url): Promise<void> => {
+      currentCustomPipelines = url
+        ? await fetchPipelineBundle(url, signal)
+        : null;
+      onConfigUpdate?.();
+    },
+  };
+}
+
+export interface Anime4KFrameSinkController extends Anime4KController {
+  pushFrame: (
+    source: GPUCopyExternalImageSource,
+    width: number,
+    height: number
+  ) => Promise<void>;
+}
+
+/**
+ * Sets up Anime4K rendering driven by frames supplied from JavaScript
+ *
+ * Unlike `setupAnime4K`, no video element is involved: each call to `pushFrame`
+ * uploads the given frame (e.g. an `ImageBitmap`, `VideoFrame` or `ImageData`
+ * from MSE or a custom decoder), runs the Anime4K pipeline, and presents the
+ * result to the canvas. The rendering context is recreated whenever the frame
+ * dimensions or the configuration change.
+ */
+export function setupAnime4KFrameSink(
+  canvas: HTMLCanvasElement,
+  config: Anime4KConfig | null = null
+): Anime4KFrameSinkController {
+  const abortController = new AbortController();
+  const { signal } = abortController;
+
+  let currentConfig: Anime4KConfig | null = config && { ...config };
+  let currentCustomPipelines: readonly ExecutablePipeline[] | null = null;
+  let contextPromise: Promise<RenderingContext> | null = null;
+
+  const ready = (async () => {
+    const device = await createDevice(signal);
+
+    const canvasContext = canvas.getContext("webgpu");
+    if (!canvasContext) {
+      throw new Error("WebGPU context not available on canvas");
+    }
+
+    return { device, canvasContext, contextInit: createContextInit(device) };
+  })();
+
+  const pushFrame = async (
+    source: GPUCopyExternalImageSource,
+    width: number,
+    height: number
+  ): Promise<void> => {
+    if (signal.aborted) {
+      return;
+    }
+
+    if (!(width > 0 && height > 0)) {
+      throw new Error(`Invalid frame dimensions: ${width}x${height}`);
+    }
+
+    const { device, canvasContext, contextInit } = await ready;
+
+    // Frames are pushed by the caller, so serialize context (re)creation through the promise
+    const previousContextPromise = contextPromise;
+    contextPromise = (async () => {
+      const context = await previousContextPromise?.catch(() => null);
+      if (
+        context &&
+        !shouldRecreateContext(
+          context,
+          width,
+          height,
+          currentConfig,
+          currentCustomPipelines
+        )
+      ) {
+        return context;
+      }
+
+      cleanupContext(context);
+      return createContext(
+        device,
+        contextInit,
+        width,
+        height,
+        canvas,
+        canvasContext,
+        currentConfig,
+        currentCustomPipelines
+      );
+    })();
+
+    let context: RenderingContext;
+    try {
+      context = await contextPromise;
+    } catch (error) {
+      console.error("❌ Failed to create rendering context:", error);
+      contextPromise = null;
+      throw error;
+    }
+
+    if (signal.aborted) {
+      cleanupContext(context);
+      return;
+    }
+
+    render(context, source);
+  };
+
+  const cleanup = (): void => {
+    abortController.abort();
+
+    contextPromise
+      ?.then((context): void => {
+        cleanupContext(context);
+      })
+      .catch(() => {});
+    contextPromise = null;
+  };
+
+  return {
+    ready: ready.then(() => {}),
+    cleanup,
+    updateConfig: (config): void => {
+      currentConfig = config && { ...config };
+    },
+    loadPipelineFromUrl: async (url): Promise<void> => {
+      currentCustomPipelines = url
+        ? await fetchPipelineBundle(url, signal)
+        : null;
+    },
+    pushFrame,
   };
 }
@@ -8,22 +8,75 @@ import {
 import { VideoControls, type Anime4KState } from "./VideoControls";
 import type { CompareConfig } from "./constants";
 
+export type VideoLoadState =
+  | {
+      type: "loading";
+      /** Buffered fraction of the video in `[0, 1]`, or `null` while the duration is unknown */
+      progress: number | null;
+    }
+  | { type: "loaded" }
+  | {
+      type: "error";
+      error: string;
+    };
+
+function getBufferedProgress(video: HTMLVideoElement): number | null {
+  const { buffered, duration } = video;
+  if (!isFinite(duration) || duration <= 0 || buffered.length === 0) {
+    return null;
+  }
+
+  return Math.min(buffered.end(buffered.length - 1) / duration, 1);
+}
+
+async function describeLoadError(
+  video: HTMLVideoElement,
+  src: string
+): Promise<string> {
+  const code = video.error?.code;
+  if (code === MediaError.MEDIA_ERR_DECODE) {
+    return "The video could not be decoded.";
+  }
+
+  // The media element does not tell a missing file apart from a CORS rejection, so ask the server directly
+  if (/^https?:/i.test(src)) {
+    try {
+      const response = await fetch(src, { method: "HEAD", mode: "cors" });
+      if (!response.ok) {
+        return `The video could not be fetched (HTTP ${response.status}).`;
+      }
+    } catch {
+      return "The video could not be fetched. The server may be unreachable or may not allow cross-origin requests (CORS).";
+    }
+  }
+
+  if (code === MediaError.MEDIA_ERR_NETWORK) {
+    return "A network error interrupted loading the video.";
+  }
+
+  return "The video format is not supported by this browser.";
+}
+
 export function VideoPlayer({
   src,
   config,
+  pipelineUrl = null,
   compare,
   onUpdateConfig,
   onUpdateCompare,
   onSelectFile,
   onLoadedMetadata,
+  onLoadStateChange,
 }: {
   readonly src: string;
   readonly config: Anime4KConfig | null;
+  readonly pipelineUrl?: string | null;
   readonly compare: CompareConfig;
   readonly onUpdateConfig: (config: Anime4KConfig | null) => void;
   readonly onUpdateCompare: (compare: CompareConfig) => void;
   readonly onSelectFile: () => void;
   readonly onLoadedMetadata?: (event: Event) => void;
+  readonly onLoadStateChange?: (state: VideoLoadState) => void;
 }) {
   const containerRef = useRef<HTMLDivElement>(null);
 
@@ -92,6 +145,58 @@ export function VideoPlayer({
     };
   }, [canvas, video]);
 
+  const [loadState, setLoadState] = useState<VideoLoadState>({
+    type: "loading",
+    progress: null,
+  });
+
+  useEffect(() => {
+    if (!video) {
+      return;
+    }
+
+    const controller = new AbortController();
+    const { signal } = controller;
+
+    setLoadState({ type: "loading", progress: null });
+
+    const handleProgress = (): void => {
+      setLoadState((prevState) =>
+        prevState.type === "loading"
+          ? { type: "loading", progress: getBufferedProgress(video) }
+          : prevState
+      );
+    };
+    video.addEventListener("progress", handleProgress, { signal });
+    video.addEventListener("loadedmetadata", handleProgress, { signal });
+    video.addEventListener(
+      "loadeddata",
+      () => setLoadState({ type: "loaded" }),
+      { signal }
+    );
+    video.addEventListener(
+      "error",
+      () => {
+        describeLoadError(video, src).then((error) => {
+          if (signal.aborted) {
+            return;
+          }
+          console.error("❌ Video load failed:", error);
+          setLoadState({ type: "error", error });
+        });
+      },
+      { signal }
+    );
+
+    return (): void => {
+      controller.abort();
+    };
+  }, [video, src]);
+
+  useEffect(() => {
+    onLoadStateChange?.(loadState);
+  }, [loadState, onLoadStateChange]);
+
   useEffect(() => {
     if (!controllerState) {
       return;
@@ -100,6 +205,26 @@ export function VideoPlayer({
     controllerState.controller.updateConfig(config);
   }, [controllerState, config]);
 
+  useEffect(() => {
+    if (!controllerState) {
+      return;
+    }
+
+    controllerState.controller
+      .loadPipelineFromUrl(pipelineUrl)
+      .then(
+        () => {
+          if (pipelineUrl) {
+            console.info("✅ Loaded pipeline bundle:", pipelineUrl);
+          }
+        },
+        (error) => {
+          console.error("❌ Failed to load pipeline bundle:", error);
+          setAnime4KState({ type: "error", error: String(error) });
+        }
+      );
+  }, [controllerState, pipelineUrl]);
+
   const handleFullscreen = useCallback((): void => {
     const container = containerRef.current;
     if (!container) {
@@ -116,6 +241,7 @@ export function VideoPlayer({
       ref={containerRef}
       class="relative w-full h-full overflow-clip contain-strict"
       data-theme="sunset"
+      data-load-state={loadState.type}
     >
       <video
         ref={videoRefCallback}
@@ -124,7 +250,7 @@ export function VideoPlayer({
           compare.mode === "none" && "hidden"
         )}
         src={src}
-        onError={(e) => console.error("❌ Video error:", e)}
+        crossOrigin="anonymous"
         onLoadedMetadata={onLoadedMetadata}
       >
         <track kind="captions" />
@@ -147,6 +273,32 @@ export function VideoPlayer({
           "--compare-opacity": compare.ratio,
         }}
       />
+      {loadState.type !== "loaded" && (
+        <div class="absolute inset-0 flex items-center justify-center pointer-events-none">
+          {loadState.type === "loading" ? (
+            <div class="flex flex-col items-center gap-2 text-sm">
+              {loadState.progress === null ? (
+                <progress class="progress w-56" />
+              ) : (
+                <progress
+                  class="progress w-56"
+                  value={loadState.progress}
+                  max={1}
+                />
+              )}
+              <span>
+                Loading video
+                {loadState.progress !== null &&
+                  ` (${Math.round(loadState.progress * 100)}%)`}
+              </span>
+            </div>
+          ) : (
+            <div role="alert" class="alert alert-error alert-soft max-w-lg">
+              <span>{loadState.error}</span>
+            </div>
+          )}
+        </div>
+      )}
       {video && (
         <VideoControls
           video={video}
@@ -22,6 +22,43 @@ import {
 import { getBrowserSupportedVideoMediaTypes } from "./videoMediaTypes";
 import { VideoPlayer } from "./VideoPlayer";
 
+/**
+ * Returns the pipeline bundle URL from the `?pipeline=<url>` query parameter, if it may be run
+ *
+ * A bundle contains arbitrary WGSL, so a link must not be enough to run one. Outside
+ * development builds, only same-origin bundles are accepted, and only after the user
+ * confirms.
+ */
+function getPipelineUrlFromQuery(): string | null {
+  const param = new URLSearchParams(location.search).get("pipeline");
+  if (!param) {
+    return null;
+  }
+
+  let url: URL;
+  try {
+    url = new URL(param, location.href);
+  } catch {
+    console.error("❌ Invalid pipeline bundle URL:", param);
+    return null;
+  }
+
+  if (import.meta.env.DEV) {
+    return url.href;
+  }
+
+  if (url.origin !== location.origin) {
+    console.error("❌ Ignoring pipeline bundle from another origin:", url.href);
+    return null;
+  }
+
+  return confirm(
+    `Run the custom pipeline from ${url.href}?\n\nIt replaces the presets with shader code from that file.`
+  )
+    ? url.href
+    : null;
+}
+
 export function VideoPlayerPage() {
   const [accept, setAccept] = useState<string>("video/*");
   const [selectedFile, setSelectedFile] = useState<string | null>(null);
@@ -29,6 +66,7 @@ export function VideoPlayerPage() {
   const [config, setConfig] = useState<Anime4KConfig>(DEFAULT_CONFIG);
   const [compare, setCompare] = useState<CompareConfig>(DEFAULT_COMPARE);
   const [isDragOver, setIsDragOver] = useState(false);
+  const [pipelineUrl, setPipelineUrl] = useState<string | null>(null);
 
   useLayoutEffect(() => {
     setAccept(getBrowserSupportedVideoMediaTypes().join(", "));
@@ -38,6 +76,19 @@ export function VideoPlayerPage() {
     preloadPredefinedPipelines();
   }, []);
 
+  useLayoutEffect(() => {
+    // A `?pipeline=<url>` query parameter replaces the presets with a pipeline bundle
+    setPipelineUrl(getPipelineUrlFromQuery());
+  }, []);
+
+  useLayoutEffect(() => {
+    // A `?video=<url>` query parameter loads a remote video instead of waiting for a file
+    const videoUrl = new URLSearchParams(location.search).get("video");
+    if (videoUrl) {
+      setSelectedFile(videoUrl);
+    }
+  }, []);
+
   const fileInputRef = useRef<HTMLInputElement>(null);
 
   const currentURL = useRef<string | null>(null);
@@ -497,6 +548,7 @@ export function VideoPlayerPage() {
           <VideoPlayer
             src={selectedFile}
             config={enabled ? config : null}
+            pipelineUrl={pipelineUrl}
             compare={compare}
             onUpdateConfig={handleUpdateConfig}
             onUpdateCompare={setCompare}
@@ -38,7 +38,7 @@ fn process(pos: vec2i) {
     let gaussian = lum_gaussian7(pos, vec2i(1, 0));
     let minmax = minmax3(pos, vec2i(1, 0));
 
-    textureStore(output_texture, pos, vec4f(gaussian, minmax.x, minmax.y, 1.0));
+    textureStore(output_texture, pos, vec4f(gaussian, minmax.x, minmax.y, 0.0));
 }
 
 @compute @workgroup_size(8, 8)
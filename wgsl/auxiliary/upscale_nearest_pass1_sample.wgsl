@@ -0,0 +1,27 @@
+// Integer nearest-neighbor upscaling for pixel-art content
+// SCALE is substituted from the manifest constants
+
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var output_texture: texture_storage_2d<rgba32float, write>;
+
+const SCALE: i32 = {{ SCALE }};
+
+fn process(pos: vec2i) {
+    let bound = vec2i(textureDimensions(source_texture)) - 1;
+    textureStore(output_texture, pos, textureLoad(source_texture, min(pos / SCALE, bound), 0));
+}
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) global_id: vec3u) {
+    let dims = textureDimensions(output_texture);
+    if global_id.x >= dims.x || global_id.y >= dims.y {
+        return;
+    }
+
+    process(vec2i(global_id.xy));
+}
+
+@compute @workgroup_size(8, 8)
+fn main_unchecked(@builtin(global_invocation_id) global_id: vec3u) {
+    process(vec2i(global_id.xy));
+}
@@ -51,7 +51,7 @@ fn process(pos: vec2i) {
     let gaussian_result = lumGaussian7(pos, d);
     let minmax_result = minmax3(pos, d);
 
-    textureStore(output_texture, pos, vec4f(gaussian_result, minmax_result.x, minmax_result.y, 1.0));
+    textureStore(output_texture, pos, vec4f(gaussian_result, minmax_result.x, minmax_result.y, 0.0));
 }
 
 @compute @workgroup_size(8, 8)